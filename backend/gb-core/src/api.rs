@@ -10,19 +10,26 @@ use crate::interrupts::InterruptRegisters;
 use crate::memory::Memory;
 use crate::ppu::Ppu;
 use crate::serial::SerialPort;
-use crate::sm83::Sm83;
+use crate::sm83::{debug, disassemble, Sm83};
 use crate::speed::SpeedRegister;
 use crate::timer::GbTimer;
 use crate::{ppu, HardwareMode};
 use bincode::{Decode, Encode};
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, PixelAspectRatio, Renderer, SaveWriter, TickEffect,
     TickResult, TimingMode,
 };
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, PartialClone};
 use std::fmt::{Debug, Display};
+use std::mem;
 use thiserror::Error;
 
+const SM83_DEBUG_CPU: usize = 0;
+
+const DEBUG_CPUS: &[DebugCpu] = &[DebugCpu { name: "SM83", arch: CpuArch::Sm83 }];
+
 #[derive(Debug, Error)]
 pub enum GameBoyLoadError {
     #[error("ROM header contains invalid SRAM size byte: ${0:02X}")]
@@ -111,6 +118,27 @@ pub struct GameBoyEmulator {
     rgba_buffer: RgbaFrameBuffer,
     config: GameBoyEmulatorConfig,
     frame_count: u64,
+    cheats: CheatSet,
+    debug_hooks: DebugHooks,
+}
+
+macro_rules! new_bus {
+    ($self:expr) => {
+        Bus {
+            hardware_mode: $self.hardware_mode,
+            ppu: &mut $self.ppu,
+            apu: &mut $self.apu,
+            memory: &mut $self.memory,
+            serial_port: &mut $self.serial_port,
+            cartridge: &mut $self.cartridge,
+            interrupt_registers: &mut $self.interrupt_registers,
+            speed_register: &mut $self.speed_register,
+            timer: &mut $self.timer,
+            dma_unit: &mut $self.dma_unit,
+            input_state: &mut $self.input_state,
+            cheats: &$self.cheats,
+        }
+    };
 }
 
 impl GameBoyEmulator {
@@ -151,6 +179,8 @@ impl GameBoyEmulator {
             rgba_buffer: RgbaFrameBuffer::default(),
             config,
             frame_count: 0,
+            cheats: CheatSet::new(),
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
         })
     }
 
@@ -177,6 +207,25 @@ impl GameBoyEmulator {
     pub fn is_cgb_mode(&self) -> bool {
         self.hardware_mode == HardwareMode::Cgb
     }
+
+    /// Replace the active cheats. Use [`cheats::decode_cheat`](crate::cheats::decode_cheat) to
+    /// decode cheat codes.
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.cheats = cheats;
+    }
+
+    // Write RAM cheat values to cartridge RAM, work RAM, or HRAM; this is called once per frame
+    fn apply_ram_cheats(&mut self) {
+        for patch in self.cheats.ram_patches() {
+            let address = patch.address as u16;
+            match address {
+                0xA000..=0xBFFF => self.cartridge.write_ram(address, patch.value),
+                0xC000..=0xFDFF => self.memory.write_main_ram(address, patch.value),
+                0xFF80..=0xFFFE => self.memory.write_hram(address, patch.value),
+                _ => {}
+            }
+        }
+    }
 }
 
 impl EmulatorTrait for GameBoyEmulator {
@@ -205,19 +254,31 @@ impl EmulatorTrait for GameBoyEmulator {
     {
         self.input_state.set_inputs(*inputs);
 
-        self.cpu.execute_instruction(&mut Bus {
-            hardware_mode: self.hardware_mode,
-            ppu: &mut self.ppu,
-            apu: &mut self.apu,
-            memory: &mut self.memory,
-            serial_port: &mut self.serial_port,
-            cartridge: &mut self.cartridge,
-            interrupt_registers: &mut self.interrupt_registers,
-            speed_register: &mut self.speed_register,
-            timer: &mut self.timer,
-            dma_unit: &mut self.dma_unit,
-            input_state: &mut self.input_state,
-        });
+        if self.debug_hooks.checks_execution(SM83_DEBUG_CPU) && self.cpu.will_execute_instruction()
+        {
+            if self.debug_hooks.check_execute(SM83_DEBUG_CPU, self.cpu.pc().into()) {
+                return Ok(TickEffect::None);
+            }
+
+            self.cpu.trace_instruction(
+                |address| new_bus!(self).read_memory(address),
+                &mut self.debug_hooks,
+                SM83_DEBUG_CPU,
+            );
+        }
+
+        let mut bus = new_bus!(self);
+        if self.debug_hooks.checks_accesses(SM83_DEBUG_CPU)
+            || self.debug_hooks.is_tracing(SM83_DEBUG_CPU)
+        {
+            self.cpu.execute_instruction(&mut debug::DebugBus::new(
+                &mut bus,
+                &mut self.debug_hooks,
+                SM83_DEBUG_CPU,
+            ));
+        } else {
+            self.cpu.execute_instruction(&mut bus);
+        }
 
         self.input_state.check_for_joypad_interrupt(&mut self.interrupt_registers);
 
@@ -251,6 +312,8 @@ impl EmulatorTrait for GameBoyEmulator {
                 self.cartridge.save_rtc_state(save_writer).map_err(GameBoyError::SaveWrite)?;
             }
 
+            self.apply_ram_cheats();
+
             self.frame_count += 1;
 
             Ok(TickEffect::FrameRendered)
@@ -289,6 +352,8 @@ impl EmulatorTrait for GameBoyEmulator {
 
     fn take_rom_from(&mut self, other: &mut Self) {
         self.cartridge.take_rom_from(&mut other.cartridge);
+        self.cheats = mem::take(&mut other.cheats);
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
     }
 
     fn soft_reset(&mut self) {
@@ -297,12 +362,56 @@ impl EmulatorTrait for GameBoyEmulator {
 
     fn hard_reset<S: SaveWriter>(&mut self, save_writer: &mut S) {
         let rom = self.cartridge.take_rom();
+        let cheats = mem::take(&mut self.cheats);
+        let debug_hooks = mem::take(&mut self.debug_hooks);
 
         *self = Self::create(rom, self.config, save_writer)
             .expect("Hard reset should never fail to load cartridge");
+        self.cheats = cheats;
+        self.debug_hooks = debug_hooks;
     }
 
     fn timing_mode(&self) -> TimingMode {
         TimingMode::Ntsc
     }
 }
+
+impl Debuggable for GameBoyEmulator {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, _cpu: usize) -> Vec<u32> {
+        self.cpu.debug_registers().map(u32::from).to_vec()
+    }
+
+    fn write_registers(&mut self, _cpu: usize, registers: &[u32]) {
+        if let Ok(registers) = <[u32; 6]>::try_from(registers) {
+            self.cpu.set_debug_registers(registers.map(|value| value as u16));
+        }
+    }
+
+    fn read_memory(&mut self, _cpu: usize, address: u32) -> u8 {
+        new_bus!(self).read_memory(address as u16)
+    }
+
+    fn write_memory(&mut self, _cpu: usize, address: u32, value: u8) {
+        new_bus!(self).write_memory(address as u16, value);
+    }
+
+    fn disassemble(&mut self, cpu: usize, address: u32) -> (String, u32) {
+        let (text, len) = disassemble::disassemble(
+            |address| self.read_memory(cpu, address.into()),
+            address as u16,
+        );
+        (text, len.into())
+    }
+}
@@ -13,6 +13,7 @@ use crate::sm83::InterruptType;
 use crate::speed::{CpuSpeed, SpeedRegister};
 use crate::timer::GbTimer;
 use crate::HardwareMode;
+use jgenesis_common::cheats::CheatSet;
 
 trait HardwareModeExt {
     fn read_opri(self) -> u8;
@@ -41,6 +42,7 @@ pub struct Bus<'a> {
     pub timer: &'a mut GbTimer,
     pub dma_unit: &'a mut DmaUnit,
     pub input_state: &'a mut InputState,
+    pub cheats: &'a CheatSet,
 }
 
 macro_rules! cgb_only_read {
@@ -112,37 +114,13 @@ impl<'a> Bus<'a> {
         }
     }
 
-    fn tick_components(&mut self) {
-        self.timer.tick_m_cycle(self.interrupt_registers);
-        self.dma_unit.oam_dma_tick_m_cycle(self.cartridge, self.memory, self.ppu);
-        self.serial_port.tick(self.interrupt_registers);
-
-        if self.speed_register.speed == CpuSpeed::Double {
-            self.speed_register.double_speed_odd_cycle =
-                !self.speed_register.double_speed_odd_cycle;
-            if self.speed_register.double_speed_odd_cycle {
-                return;
-            }
-        }
-
-        for _ in 0..2 {
-            self.dma_unit.vram_dma_copy_byte(self.cartridge, self.memory, self.ppu);
-        }
-
-        for _ in 0..4 {
-            self.ppu.tick_dot(self.speed_register.speed, self.dma_unit, self.interrupt_registers);
-        }
-
-        self.apu.tick_m_cycle(self.timer, self.speed_register.speed);
-    }
-}
-
-impl<'a> BusInterface for Bus<'a> {
-    fn read(&mut self, address: u16) -> u8 {
-        self.tick_components();
-
+    /// Read a memory address without advancing any components.
+    pub fn read_memory(&mut self, address: u16) -> u8 {
         match address {
-            0x0000..=0x7FFF => self.cartridge.read_rom(address),
+            0x0000..=0x7FFF => {
+                let value = self.cartridge.read_rom(address);
+                self.cheats.apply_rom_read(address.into(), value)
+            }
             0x8000..=0x9FFF => self.ppu.read_vram(address),
             0xA000..=0xBFFF => self.cartridge.read_ram(address),
             0xC000..=0xFDFF => self.memory.read_main_ram(address),
@@ -161,9 +139,8 @@ impl<'a> BusInterface for Bus<'a> {
         }
     }
 
-    fn write(&mut self, address: u16, value: u8) {
-        self.tick_components();
-
+    /// Write a memory address without advancing any components.
+    pub fn write_memory(&mut self, address: u16, value: u8) {
         match address {
             0x0000..=0x7FFF => self.cartridge.write_rom(address, value),
             0x8000..=0x9FFF => self.ppu.write_vram(address, value),
@@ -182,6 +159,42 @@ impl<'a> BusInterface for Bus<'a> {
         }
     }
 
+    fn tick_components(&mut self) {
+        self.timer.tick_m_cycle(self.interrupt_registers);
+        self.dma_unit.oam_dma_tick_m_cycle(self.cartridge, self.memory, self.ppu);
+        self.serial_port.tick(self.interrupt_registers);
+
+        if self.speed_register.speed == CpuSpeed::Double {
+            self.speed_register.double_speed_odd_cycle =
+                !self.speed_register.double_speed_odd_cycle;
+            if self.speed_register.double_speed_odd_cycle {
+                return;
+            }
+        }
+
+        for _ in 0..2 {
+            self.dma_unit.vram_dma_copy_byte(self.cartridge, self.memory, self.ppu);
+        }
+
+        for _ in 0..4 {
+            self.ppu.tick_dot(self.speed_register.speed, self.dma_unit, self.interrupt_registers);
+        }
+
+        self.apu.tick_m_cycle(self.timer, self.speed_register.speed);
+    }
+}
+
+impl<'a> BusInterface for Bus<'a> {
+    fn read(&mut self, address: u16) -> u8 {
+        self.tick_components();
+        self.read_memory(address)
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.tick_components();
+        self.write_memory(address, value);
+    }
+
     fn idle(&mut self) {
         self.tick_components();
     }
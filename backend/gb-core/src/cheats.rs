@@ -0,0 +1,95 @@
+//! Game Boy cheat code decoding
+//!
+//! Supported formats:
+//! * Game Genie: `ABC-DEF` or `ABC-DEF-GHI`, which patch ROM reads; the 9-digit form includes a
+//!   compare value
+//! * GameShark: `TTVVLLHH`, which writes a value to RAM every frame; the type/bank byte is ignored
+//! * Raw: `AAAA:VV`; addresses $0000-$7FFF patch ROM reads and higher addresses are written to RAM
+//!   every frame
+
+use jgenesis_common::cheats::{parse_hex, split_raw_code, CheatError, CheatPatch, CheatResult};
+
+/// Decode a Game Genie, GameShark, or raw cheat code.
+///
+/// # Errors
+///
+/// Returns an error if the code is not in a recognized format.
+pub fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+    let code = code.trim().to_ascii_uppercase();
+
+    if let Some((address, value)) = split_raw_code(&code) {
+        let address = parse_hex(&code, address)?;
+        let value = parse_hex(&code, value)?;
+        if address > 0xFFFF || value > 0xFF {
+            return Err(CheatError::UnrecognizedFormat(code));
+        }
+
+        let patch = if address < 0x8000 {
+            CheatPatch::rom(address, value as u8, None)
+        } else {
+            CheatPatch::ram(address, value as u8)
+        };
+        return Ok(vec![patch]);
+    }
+
+    let patch = match code.len() {
+        7 | 11 => decode_game_genie(&code)?,
+        8 => decode_gameshark(&code)?,
+        _ => return Err(CheatError::UnrecognizedFormat(code)),
+    };
+    Ok(vec![patch])
+}
+
+// Decoding from https://gbdev.io/pandocs/Shark_Cheats.html
+fn decode_game_genie(code: &str) -> CheatResult<CheatPatch> {
+    let groups: Vec<_> = code.split('-').collect();
+    if !groups.iter().all(|group| group.len() == 3) {
+        return Err(CheatError::UnrecognizedFormat(code.into()));
+    }
+
+    let digits = groups.concat();
+    let n: Vec<u32> = digits
+        .chars()
+        .map(|ch| {
+            ch.to_digit(16).ok_or_else(|| CheatError::InvalidCharacter { code: code.into(), ch })
+        })
+        .collect::<CheatResult<_>>()?;
+
+    let value = (n[0] << 4) | n[1];
+    let address = ((n[5] << 12) | (n[2] << 8) | (n[3] << 4) | n[4]) ^ 0xF000;
+
+    // The third group encodes the compare value rotated and XORed; the middle digit is unused
+    let compare = (n.len() == 9).then(|| (((n[6] << 4) | n[8]) as u8).rotate_right(2) ^ 0xBA);
+
+    Ok(CheatPatch::rom(address, value as u8, compare))
+}
+
+fn decode_gameshark(code: &str) -> CheatResult<CheatPatch> {
+    let n = parse_hex(code, code)?;
+
+    let value = (n >> 16) as u8;
+    let address = u16::from_le_bytes([(n >> 8) as u8, n as u8]);
+
+    Ok(CheatPatch::ram(address.into(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_genie() {
+        assert_eq!(decode_cheat("3E8-F2B").unwrap(), vec![CheatPatch::rom(0x48F2, 0x3E, None)]);
+        assert_eq!(
+            decode_cheat("3e8-f2b-e6e").unwrap(),
+            vec![CheatPatch::rom(0x48F2, 0x3E, Some(0x01))]
+        );
+        assert!(decode_cheat("3E8-F2").is_err());
+    }
+
+    #[test]
+    fn gameshark() {
+        assert_eq!(decode_cheat("010FE1C0").unwrap(), vec![CheatPatch::ram(0xC0E1, 0x0F)]);
+        assert_eq!(decode_cheat("D123:05").unwrap(), vec![CheatPatch::ram(0xD123, 0x05)]);
+    }
+}
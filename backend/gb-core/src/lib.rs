@@ -3,6 +3,7 @@ mod apu;
 mod audio;
 mod bus;
 mod cartridge;
+pub mod cheats;
 mod dma;
 mod graphics;
 pub mod inputs;
@@ -6,7 +6,8 @@
 mod arithmetic;
 mod bits;
 pub mod bus;
-mod disassemble;
+pub mod debug;
+pub mod disassemble;
 mod flags;
 mod flow;
 mod load;
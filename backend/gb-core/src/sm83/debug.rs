@@ -0,0 +1,131 @@
+//! Debugger support: register access, instruction tracing, and a bus wrapper that reports memory
+//! accesses for watchpoints.
+
+use crate::sm83::bus::BusInterface;
+use crate::sm83::{disassemble, InterruptType, Sm83};
+use jgenesis_common::debug::{CpuArch, DebugHooks};
+
+impl Sm83 {
+    /// Registers in the order AF, BC, DE, HL, SP, PC.
+    #[must_use]
+    pub fn debug_registers(&self) -> [u16; 6] {
+        let registers = &self.registers;
+        [registers.af(), registers.bc(), registers.de(), registers.hl(), registers.sp, registers.pc]
+    }
+
+    /// Overwrite registers using the same order as [`Self::debug_registers`].
+    pub fn set_debug_registers(&mut self, values: [u16; 6]) {
+        let [af, bc, de, hl, sp, pc] = values;
+
+        let registers = &mut self.registers;
+        let [a, f] = af.to_be_bytes();
+        registers.a = a;
+        registers.f = f.into();
+        [registers.b, registers.c] = bc.to_be_bytes();
+        [registers.d, registers.e] = de.to_be_bytes();
+        registers.set_hl(hl);
+        registers.sp = sp;
+        registers.pc = pc;
+    }
+
+    #[must_use]
+    pub fn pc(&self) -> u16 {
+        self.registers.pc
+    }
+
+    /// Whether the next call to [`Self::execute_instruction`] will execute the instruction at PC,
+    /// as opposed to idling while halted or servicing an interrupt.
+    #[must_use]
+    pub fn will_execute_instruction(&self) -> bool {
+        !(self.state.halted || self.state.handling_interrupt || self.state.executed_invalid_opcode)
+    }
+
+    /// Record the instruction at PC in the given CPU's trace if it is being traced and PC is within
+    /// the trace's address ranges. `read_memory` should read without advancing any components.
+    pub fn trace_instruction(
+        &self,
+        read_memory: impl FnMut(u16) -> u8,
+        hooks: &mut DebugHooks,
+        cpu: usize,
+    ) {
+        if !hooks.traces(cpu, self.registers.pc.into()) {
+            return;
+        }
+
+        let (disassembly, _) = disassemble::disassemble(read_memory, self.registers.pc);
+        let registers = self.debug_registers().map(u32::from);
+        hooks.record_trace(cpu, CpuArch::Sm83, &disassembly, &registers);
+    }
+}
+
+/// Wraps a bus and reports every memory access to [`DebugHooks::check_access`]. Also counts
+/// M-cycles for instruction traces, since [`Sm83::execute_instruction`] does not return a cycle
+/// count.
+pub struct DebugBus<'a, B> {
+    bus: &'a mut B,
+    hooks: &'a mut DebugHooks,
+    cpu: usize,
+}
+
+impl<'a, B: BusInterface> DebugBus<'a, B> {
+    pub fn new(bus: &'a mut B, hooks: &'a mut DebugHooks, cpu: usize) -> Self {
+        Self { bus, hooks, cpu }
+    }
+}
+
+impl<B: BusInterface> BusInterface for DebugBus<'_, B> {
+    #[inline]
+    fn read(&mut self, address: u16) -> u8 {
+        self.hooks.check_access(self.cpu, address.into(), 1, false);
+        self.hooks.add_trace_cycles(self.cpu, 1);
+        self.bus.read(address)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u16, value: u8) {
+        self.hooks.check_access(self.cpu, address.into(), 1, true);
+        self.hooks.add_trace_cycles(self.cpu, 1);
+        self.bus.write(address, value);
+    }
+
+    #[inline]
+    fn idle(&mut self) {
+        self.hooks.add_trace_cycles(self.cpu, 1);
+        self.bus.idle();
+    }
+
+    #[inline]
+    fn read_ie_register(&self) -> u8 {
+        self.bus.read_ie_register()
+    }
+
+    #[inline]
+    fn read_if_register(&self) -> u8 {
+        self.bus.read_if_register()
+    }
+
+    #[inline]
+    fn interrupt_pending(&self) -> bool {
+        self.bus.interrupt_pending()
+    }
+
+    #[inline]
+    fn acknowledge_interrupt(&mut self, interrupt_type: InterruptType) {
+        self.bus.acknowledge_interrupt(interrupt_type);
+    }
+
+    #[inline]
+    fn halt(&self) -> bool {
+        self.bus.halt()
+    }
+
+    #[inline]
+    fn speed_switch_armed(&self) -> bool {
+        self.bus.speed_switch_armed()
+    }
+
+    #[inline]
+    fn perform_speed_switch(&mut self) {
+        self.bus.perform_speed_switch();
+    }
+}
@@ -1,3 +1,18 @@
+/// Length in bytes of the instruction with the given opcode, including the opcode itself and the
+/// second byte of CB-prefixed instructions.
+pub fn instruction_len(opcode: u8) -> u16 {
+    match opcode {
+        // u16 operands
+        0x01 | 0x08 | 0x11 | 0x21 | 0x31 | 0xC2 | 0xC3 | 0xC4 | 0xCA | 0xCC | 0xCD | 0xD2
+        | 0xD4 | 0xDA | 0xDC | 0xEA | 0xFA => 3,
+        // u8/i8 operands, STOP (which is followed by an unused byte), and CB-prefixed instructions
+        0x06 | 0x0E | 0x10 | 0x16 | 0x18 | 0x1E | 0x20 | 0x26 | 0x28 | 0x2E | 0x30 | 0x36
+        | 0x38 | 0x3E | 0xC6 | 0xCB | 0xCE | 0xD6 | 0xDE | 0xE0 | 0xE6 | 0xE8 | 0xEE | 0xF0
+        | 0xF6 | 0xF8 | 0xFE => 2,
+        _ => 1,
+    }
+}
+
 pub fn instruction_str(opcode: u8) -> String {
     match opcode {
         0x00 => "NOP".into(),
@@ -167,3 +182,64 @@ pub fn cb_instruction_str(opcode: u8) -> String {
         0xC0..=0xFF => format!("SET {}, {}", (opcode >> 3) & 7, register_bits_to_str(opcode)),
     }
 }
+
+/// Disassemble the instruction at `address`, substituting operand values into the instruction
+/// text. Returns the instruction text and its length in bytes.
+///
+/// Memory is read through a closure rather than [`BusInterface`](crate::sm83::bus::BusInterface)
+/// because bus reads advance the rest of the system.
+pub fn disassemble(mut read_memory: impl FnMut(u16) -> u8, address: u16) -> (String, u16) {
+    let opcode = read_memory(address);
+    if opcode == 0xCB {
+        return (cb_instruction_str(read_memory(address.wrapping_add(1))), 2);
+    }
+
+    let len = instruction_len(opcode);
+    let text = instruction_str(opcode);
+    let operand_u8 = read_memory(address.wrapping_add(1));
+
+    let text = if text.contains("u16") {
+        let operand = u16::from_le_bytes([operand_u8, read_memory(address.wrapping_add(2))]);
+        text.replace("u16", &format!("${operand:04X}"))
+    } else if text.starts_with("LDH") {
+        text.replace("(u8)", &format!("($FF{operand_u8:02X})"))
+    } else if text.contains("u8") {
+        text.replace("u8", &format!("${operand_u8:02X}"))
+    } else if text.starts_with("JR") {
+        let target = address.wrapping_add(len).wrapping_add(operand_u8 as i8 as u16);
+        text.replace("i8", &format!("${target:04X}"))
+    } else if text.contains("SP+i8") {
+        let offset = operand_u8 as i8;
+        let sign = if offset < 0 { '-' } else { '+' };
+        text.replace("+i8", &format!("{sign}${:02X}", offset.unsigned_abs()))
+    } else if text.contains("i8") {
+        let offset = operand_u8 as i8;
+        let sign = if offset < 0 { "-" } else { "" };
+        text.replace("i8", &format!("{sign}${:02X}", offset.unsigned_abs()))
+    } else {
+        text
+    };
+
+    (text, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disassemble_bytes(bytes: &[u8]) -> (String, u16) {
+        disassemble(|address| bytes.get(address as usize).copied().unwrap_or(0), 0)
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(disassemble_bytes(&[0x00]), ("NOP".into(), 1));
+        assert_eq!(disassemble_bytes(&[0x3E, 0x12]), ("LD A, $12".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x21, 0x00, 0xC0]), ("LD HL, $C000".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xE0, 0x40]), ("LDH ($FF40), A".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x20, 0xFE]), ("JR NZ, $0000".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xF8, 0xFC]), ("LD HL, SP-$04".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xE8, 0x08]), ("ADD SP, $08".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xCB, 0x7C]), ("BIT 7, H".into(), 2));
+    }
+}
@@ -1,12 +1,14 @@
 //! Genesis public interface and main loop
 
 use crate::audio::GenesisAudioResampler;
-use crate::input::{GenesisInputs, InputState};
+use crate::input::{GenesisInputs, GenesisPadType, InputState};
 use crate::memory::{Cartridge, MainBus, MainBusSignals, MainBusWrites, Memory};
 use crate::vdp::{Vdp, VdpConfig, VdpTickEffect};
 use crate::ym2612::{Ym2612, YmTickEffect};
 use crate::GenesisControllerType;
 use bincode::{Decode, Encode};
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
     SaveWriter, TickEffect, TimingMode,
@@ -15,6 +17,7 @@ use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 use m68000_emu::M68000;
 use smsgg_core::psg::{Psg, PsgTickEffect, PsgVersion};
+use smsgg_core::vgm::{VgmChips, VgmLogger};
 use std::fmt::{Debug, Display};
 use std::mem;
 use thiserror::Error;
@@ -24,6 +27,16 @@ const M68K_MCLK_DIVIDER: u64 = 7;
 const Z80_MCLK_DIVIDER: u64 = 15;
 const PSG_MCLK_DIVIDER: u64 = 15;
 
+/// Debugger CPU index of the 68000.
+pub const M68K_DEBUG_CPU: usize = 0;
+/// Debugger CPU index of the Z80.
+pub const Z80_DEBUG_CPU: usize = 1;
+
+const DEBUG_CPUS: &[DebugCpu] = &[
+    DebugCpu { name: "68000", arch: CpuArch::M68000 },
+    DebugCpu { name: "Z80", arch: CpuArch::Z80 },
+];
+
 #[derive(Debug, Error)]
 pub enum GenesisError<RErr, AErr, SErr> {
     #[error("Rendering error: {0}")]
@@ -128,6 +141,8 @@ impl GenesisRegion {
 pub struct GenesisEmulatorConfig {
     pub p1_controller_type: GenesisControllerType,
     pub p2_controller_type: GenesisControllerType,
+    /// Pad types for each player, used for any players connected through a multitap
+    pub multitap_pad_types: [GenesisPadType; 8],
     pub forced_timing_mode: Option<TimingMode>,
     pub forced_region: Option<GenesisRegion>,
     pub aspect_ratio: GenesisAspectRatio,
@@ -186,6 +201,9 @@ pub struct GenesisEmulator {
     z80_mclk_cycles: u64,
     psg_mclk_cycles: u64,
     wait_states: WaitStates,
+    #[partial_clone(default)]
+    vgm_logger: VgmLogger,
+    debug_hooks: DebugHooks,
 }
 
 // This is a macro instead of a function so that it only mutably borrows the needed fields
@@ -197,6 +215,7 @@ macro_rules! new_main_bus {
             &mut $self.psg,
             &mut $self.ym2612,
             &mut $self.input,
+            &mut $self.vgm_logger,
             $self.timing_mode,
             MainBusSignals { z80_busack: $self.z80.stalled(), m68k_reset: $m68k_reset },
             std::mem::take(&mut $self.main_bus_writes),
@@ -232,7 +251,11 @@ impl GenesisEmulator {
         let vdp = Vdp::new(timing_mode, config.to_vdp_config());
         let psg = Psg::new(PsgVersion::Standard);
         let ym2612 = Ym2612::new(config.quantize_ym2612_output);
-        let input = InputState::new(config.p1_controller_type, config.p2_controller_type);
+        let input = InputState::new(
+            config.p1_controller_type,
+            config.p2_controller_type,
+            config.multitap_pad_types,
+        );
 
         // The Genesis does not allow TAS to lock the bus, so don't allow TAS writes
         let m68k = M68000::builder().allow_tas_writes(false).build();
@@ -253,6 +276,8 @@ impl GenesisEmulator {
             z80_mclk_cycles: 0,
             psg_mclk_cycles: 0,
             wait_states: WaitStates::default(),
+            vgm_logger: VgmLogger::default(),
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
         };
 
         // Reset CPU so that execution will start from the right place
@@ -288,6 +313,33 @@ impl GenesisEmulator {
     pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         self.vdp.copy_vram(out, palette, row_len);
     }
+
+    /// Start logging PSG and YM2612 writes in VGM format, replacing any log in progress.
+    pub fn start_vgm_logging(&mut self) {
+        let chips = VgmChips::new(self.timing_mode, true, false, true);
+        self.vgm_logger.start(chips, 1);
+        self.log_vgm_registers();
+    }
+
+    // Log the current sound chip registers so that an active VGM log matches the emulator state
+    fn log_vgm_registers(&mut self) {
+        if !self.vgm_logger.is_active() {
+            return;
+        }
+
+        self.psg.log_registers(&mut self.vgm_logger);
+        self.ym2612.log_registers(&mut self.vgm_logger);
+    }
+
+    pub fn vgm_logger_mut(&mut self) -> &mut VgmLogger {
+        &mut self.vgm_logger
+    }
+
+    /// Replace the active cheats. Use [`cheats::decode_cheat`](crate::cheats::decode_cheat) to
+    /// decode cheat codes.
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.memory.set_cheats(cheats);
+    }
 }
 
 /// Render the current VDP frame buffer.
@@ -344,12 +396,36 @@ impl EmulatorTrait for GenesisEmulator {
         S: SaveWriter,
         S::Err: Debug + Display + Send + Sync + 'static,
     {
+        if self.wait_states.m68k_cpu_cycles == 0
+            && self.debug_hooks.checks_execution(M68K_DEBUG_CPU)
+            && self.debug_hooks.check_execute(M68K_DEBUG_CPU, self.m68k.pc())
+        {
+            return Ok(TickEffect::None);
+        }
+
         let mut bus = new_main_bus!(self, m68k_reset: false);
+        let m68k_tracing = self.debug_hooks.is_tracing(M68K_DEBUG_CPU);
         let m68k_cycles = if self.wait_states.m68k_cpu_cycles != 0 {
             mem::take(&mut self.wait_states.m68k_cpu_cycles)
         } else {
-            self.m68k.execute_instruction(&mut bus)
+            if m68k_tracing {
+                self.m68k.trace_instruction(&mut bus, &mut self.debug_hooks, M68K_DEBUG_CPU);
+            }
+
+            if self.debug_hooks.checks_accesses(M68K_DEBUG_CPU) {
+                let mut bus = m68000_emu::debug::DebugBus::new(
+                    &mut bus,
+                    &mut self.debug_hooks,
+                    M68K_DEBUG_CPU,
+                );
+                self.m68k.execute_instruction(&mut bus)
+            } else {
+                self.m68k.execute_instruction(&mut bus)
+            }
         };
+        if m68k_tracing {
+            self.debug_hooks.add_trace_cycles(M68K_DEBUG_CPU, m68k_cycles.into());
+        }
 
         let elapsed_mclk_cycles = u64::from(m68k_cycles) * M68K_MCLK_DIVIDER;
 
@@ -362,8 +438,30 @@ impl EmulatorTrait for GenesisEmulator {
             self.z80_mclk_cycles = 0;
         }
 
+        let z80_tracing = self.debug_hooks.is_tracing(Z80_DEBUG_CPU);
         while self.z80_mclk_cycles >= Z80_MCLK_DIVIDER {
-            self.z80.tick(&mut bus);
+            if self.z80.at_instruction_boundary()
+                && self.debug_hooks.checks_execution(Z80_DEBUG_CPU)
+            {
+                if self.debug_hooks.check_execute(Z80_DEBUG_CPU, self.z80.pc().into()) {
+                    break;
+                }
+
+                self.z80.trace_instruction(&mut bus, &mut self.debug_hooks, Z80_DEBUG_CPU);
+            }
+
+            if self.debug_hooks.checks_accesses(Z80_DEBUG_CPU) {
+                self.z80.tick(&mut z80_emu::debug::DebugBus::new(
+                    &mut bus,
+                    &mut self.debug_hooks,
+                    Z80_DEBUG_CPU,
+                ));
+            } else {
+                self.z80.tick(&mut bus);
+            }
+            if z80_tracing {
+                self.debug_hooks.add_trace_cycles(Z80_DEBUG_CPU, 1);
+            }
             self.z80_mclk_cycles -= Z80_MCLK_DIVIDER;
         }
 
@@ -373,6 +471,8 @@ impl EmulatorTrait for GenesisEmulator {
 
         self.main_bus_writes = bus.apply_writes();
 
+        self.vgm_logger.advance_cycles(elapsed_mclk_cycles);
+
         self.memory.medium_mut().tick(m68k_cycles);
 
         self.input.tick(m68k_cycles);
@@ -395,6 +495,7 @@ impl EmulatorTrait for GenesisEmulator {
             }
         }
 
+        self.vdp.set_light_gun_position(self.input.light_gun_position());
         if self.vdp.tick(elapsed_mclk_cycles, &mut self.memory) == VdpTickEffect::FrameComplete {
             self.render_frame(renderer).map_err(GenesisError::Render)?;
 
@@ -411,6 +512,8 @@ impl EmulatorTrait for GenesisEmulator {
                 }
             }
 
+            self.memory.apply_ram_cheats();
+
             return Ok(TickEffect::FrameRendered);
         }
 
@@ -434,6 +537,10 @@ impl EmulatorTrait for GenesisEmulator {
 
     fn take_rom_from(&mut self, other: &mut Self) {
         self.memory.take_rom_from(&mut other.memory);
+        self.memory.set_cheats(other.memory.take_cheats());
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
+        self.vgm_logger = mem::take(&mut other.vgm_logger);
+        self.log_vgm_registers();
     }
 
     fn soft_reset(&mut self) {
@@ -450,6 +557,7 @@ impl EmulatorTrait for GenesisEmulator {
         let rom = self.memory.take_rom();
         let vdp_config = self.vdp.config();
         let (p1_controller_type, p2_controller_type) = self.input.controller_types();
+        let multitap_pad_types = self.input.multitap_pad_types();
 
         let config = GenesisEmulatorConfig {
             forced_timing_mode: Some(self.timing_mode),
@@ -463,12 +571,92 @@ impl EmulatorTrait for GenesisEmulator {
             quantize_ym2612_output: self.ym2612.get_quantize_output(),
             p1_controller_type,
             p2_controller_type,
+            multitap_pad_types,
         };
 
+        let vgm_logger = mem::take(&mut self.vgm_logger);
+        let cheats = self.memory.take_cheats();
+        let debug_hooks = mem::take(&mut self.debug_hooks);
         *self = GenesisEmulator::create(rom, config, save_writer);
+        self.vgm_logger = vgm_logger;
+        self.memory.set_cheats(cheats);
+        self.debug_hooks = debug_hooks;
+        self.log_vgm_registers();
     }
 
     fn timing_mode(&self) -> TimingMode {
         self.timing_mode
     }
 }
+
+impl Debuggable for GenesisEmulator {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, cpu: usize) -> Vec<u32> {
+        match cpu {
+            M68K_DEBUG_CPU => self.m68k.debug_registers().to_vec(),
+            Z80_DEBUG_CPU => self.z80.debug_registers().map(u32::from).to_vec(),
+            _ => vec![],
+        }
+    }
+
+    fn write_registers(&mut self, cpu: usize, registers: &[u32]) {
+        match cpu {
+            M68K_DEBUG_CPU => {
+                if let Ok(registers) = registers.try_into() {
+                    self.m68k.set_debug_registers(registers);
+                }
+            }
+            Z80_DEBUG_CPU => {
+                if let Ok(registers) = <[u32; 13]>::try_from(registers) {
+                    self.z80.set_debug_registers(registers.map(|value| value as u16));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_memory(&mut self, cpu: usize, address: u32) -> u8 {
+        let mut bus = new_main_bus!(self, m68k_reset: false);
+        let value = match cpu {
+            Z80_DEBUG_CPU => bus.peek_z80_byte(address as u16),
+            _ => bus.peek_m68k_byte(address),
+        };
+        self.main_bus_writes = bus.take_writes();
+
+        value
+    }
+
+    fn write_memory(&mut self, cpu: usize, address: u32, value: u8) {
+        let mut bus = new_main_bus!(self, m68k_reset: false);
+        match cpu {
+            Z80_DEBUG_CPU => z80_emu::BusInterface::write_memory(&mut bus, address as u16, value),
+            _ => m68000_emu::BusInterface::write_byte(&mut bus, address, value),
+        }
+        self.main_bus_writes = bus.apply_writes();
+    }
+
+    fn disassemble(&mut self, cpu: usize, address: u32) -> (String, u32) {
+        let mut bus = new_main_bus!(self, m68k_reset: false);
+        let disassembled = match cpu {
+            Z80_DEBUG_CPU => {
+                let (text, len) = z80_emu::disassemble::disassemble(&mut bus, address as u16);
+                (text, len.into())
+            }
+            _ => m68000_emu::disassemble::disassemble(&mut bus, address),
+        };
+        self.main_bus_writes = bus.apply_writes();
+
+        disassembled
+    }
+}
@@ -0,0 +1,106 @@
+//! Genesis cheat code decoding
+//!
+//! Supported formats:
+//! * Game Genie: `XXXX-XXXX`, which patches a 16-bit word of ROM
+//! * Pro Action Replay / raw: `AAAAAA:VVVV` or `AAAAAA:VV`; addresses in $E00000-$FFFFFF are
+//!   written to work RAM every frame and lower addresses patch ROM reads
+
+use jgenesis_common::cheats::{parse_hex, split_raw_code, CheatError, CheatPatch, CheatResult};
+
+const GAME_GENIE_CHARS: &[u8; 32] = b"ABCDEFGHJKLMNPRSTVWXYZ0123456789";
+
+const RAM_START: u32 = 0xE00000;
+const ADDRESS_MASK: u32 = 0xFFFFFF;
+
+/// Decode a Game Genie or Pro Action Replay cheat code.
+///
+/// # Errors
+///
+/// Returns an error if the code is not in a recognized format.
+pub fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+    let code = code.trim().to_ascii_uppercase();
+
+    if let Some((address, value)) = split_raw_code(&code) {
+        let address = parse_hex(&code, address)?;
+        if address > ADDRESS_MASK {
+            return Err(CheatError::UnrecognizedFormat(code));
+        }
+
+        let parsed_value = parse_hex(&code, value)?;
+        let bytes = match value.len() {
+            1..=2 => vec![(address, parsed_value as u8)],
+            3..=4 => {
+                let [msb, lsb] = (parsed_value as u16).to_be_bytes();
+                vec![(address, msb), ((address + 1) & ADDRESS_MASK, lsb)]
+            }
+            _ => return Err(CheatError::UnrecognizedFormat(code)),
+        };
+
+        let patches = bytes
+            .into_iter()
+            .map(|(address, value)| {
+                if address >= RAM_START {
+                    CheatPatch::ram(address, value)
+                } else {
+                    CheatPatch::rom(address, value, None)
+                }
+            })
+            .collect();
+        return Ok(patches);
+    }
+
+    decode_game_genie(&code)
+}
+
+fn decode_game_genie(code: &str) -> CheatResult<Vec<CheatPatch>> {
+    let code: String = code.chars().filter(|&ch| ch != '-').collect();
+    if code.len() != 8 {
+        return Err(CheatError::UnrecognizedFormat(code));
+    }
+
+    // Each character encodes 5 bits
+    let mut n = 0_u64;
+    for ch in code.chars() {
+        let Some(position) = GAME_GENIE_CHARS.iter().position(|&gg_char| char::from(gg_char) == ch)
+        else {
+            return Err(CheatError::InvalidCharacter { code, ch });
+        };
+        n = (n << 5) | position as u64;
+    }
+
+    // The 40 bits are ordered ijklmnop IJKLMNOP ABCDEFGH defghabc QRSTUVWX, which map to address
+    // ABCDEFGH IJKLMNOP QRSTUVWX and value abcdefgh ijklmnop
+    let address = (((n >> 16) & 0xFF) << 16) | (((n >> 24) & 0xFF) << 8) | (n & 0xFF);
+    let value = ((n >> 32) & 0xFF) | (((n >> 11) & 0x1F) << 8) | (((n >> 8) & 0x07) << 13);
+
+    let address = address as u32;
+    let [msb, lsb] = (value as u16).to_be_bytes();
+    Ok(vec![CheatPatch::rom(address, msb, None), CheatPatch::rom(address + 1, lsb, None)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_genie() {
+        assert_eq!(
+            decode_cheat("SCRA-BJX0").unwrap(),
+            vec![CheatPatch::rom(0x009C76, 0x54, None), CheatPatch::rom(0x009C77, 0x78, None)]
+        );
+        assert!(decode_cheat("SCRA-BJXI").is_err());
+    }
+
+    #[test]
+    fn pro_action_replay() {
+        assert_eq!(
+            decode_cheat("FFFE10:0063").unwrap(),
+            vec![CheatPatch::ram(0xFFFE10, 0x00), CheatPatch::ram(0xFFFE11, 0x63)]
+        );
+        assert_eq!(decode_cheat("ff0004:01").unwrap(), vec![CheatPatch::ram(0xFF0004, 0x01)]);
+        assert_eq!(
+            decode_cheat("01F2A0:4E71").unwrap(),
+            vec![CheatPatch::rom(0x01F2A0, 0x4E, None), CheatPatch::rom(0x01F2A1, 0x71, None)]
+        );
+    }
+}
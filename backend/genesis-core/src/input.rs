@@ -2,11 +2,12 @@
 
 use crate::GenesisEmulatorConfig;
 use bincode::{Decode, Encode};
+use jgenesis_common::input::Player;
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::{define_controller_inputs, EnumDisplay, EnumFromStr};
 
 define_controller_inputs! {
-    enum GenesisButton {
+    enum GenesisControllerButton {
         Up,
         Left,
         Right,
@@ -24,16 +25,180 @@ define_controller_inputs! {
     struct GenesisJoypadState {
         buttons!
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenesisMouseButton {
+    Left,
+    Right,
+    Middle,
+    Start,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightGunButton {
+    Trigger,
+    A,
+    B,
+    Start,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenesisButton {
+    Controller(GenesisControllerButton),
+    Mouse(GenesisMouseButton),
+    LightGun(LightGunButton),
+}
 
-    struct GenesisInputs {
-        p1: Player::One,
-        p2: Player::Two,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct GenesisMouseState {
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+    pub start: bool,
+    // Pointer position in frame buffer pixels, or None if the pointer is outside of the frame.
+    // The mouse reports how far this position has moved each time the game reads it
+    pub position: Option<(u16, u16)>,
+}
+
+impl GenesisMouseState {
+    #[inline]
+    pub fn set_button(&mut self, button: GenesisMouseButton, pressed: bool) {
+        match button {
+            GenesisMouseButton::Left => self.left = pressed,
+            GenesisMouseButton::Right => self.right = pressed,
+            GenesisMouseButton::Middle => self.middle = pressed,
+            GenesisMouseButton::Start => self.start = pressed,
+        }
     }
 }
 
+/// State of a Menacer or a Justifier. The Justifier only has the trigger and Start buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct LightGunState {
+    pub trigger: bool,
+    pub a: bool,
+    pub b: bool,
+    pub start: bool,
+    // Position in frame buffer pixels (including any rendered borders), or None if offscreen.
+    // Positions outside of the active display area are treated as offscreen
+    pub position: Option<(u16, u16)>,
+}
+
+impl LightGunState {
+    #[inline]
+    pub fn set_button(&mut self, button: LightGunButton, pressed: bool) {
+        match button {
+            LightGunButton::Trigger => self.trigger = pressed,
+            LightGunButton::A => self.a = pressed,
+            LightGunButton::B => self.b = pressed,
+            LightGunButton::Start => self.start = pressed,
+        }
+    }
+}
+
+/// Inputs for every supported device. Which of these the console sees depends on the controller
+/// type configured for each port.
+///
+/// Players 3-8 are only connected when using a multitap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct GenesisInputs {
+    pub p1: GenesisJoypadState,
+    pub p2: GenesisJoypadState,
+    pub p3: GenesisJoypadState,
+    pub p4: GenesisJoypadState,
+    pub p5: GenesisJoypadState,
+    pub p6: GenesisJoypadState,
+    pub p7: GenesisJoypadState,
+    pub p8: GenesisJoypadState,
+    pub mouse: GenesisMouseState,
+    pub light_gun: LightGunState,
+}
+
+impl GenesisInputs {
+    #[inline]
+    #[must_use]
+    pub fn joypad(&self, player: Player) -> GenesisJoypadState {
+        match player {
+            Player::One => self.p1,
+            Player::Two => self.p2,
+            Player::Three => self.p3,
+            Player::Four => self.p4,
+            Player::Five => self.p5,
+            Player::Six => self.p6,
+            Player::Seven => self.p7,
+            Player::Eight => self.p8,
+        }
+    }
+
+    #[inline]
+    pub fn joypad_mut(&mut self, player: Player) -> &mut GenesisJoypadState {
+        match player {
+            Player::One => &mut self.p1,
+            Player::Two => &mut self.p2,
+            Player::Three => &mut self.p3,
+            Player::Four => &mut self.p4,
+            Player::Five => &mut self.p5,
+            Player::Six => &mut self.p6,
+            Player::Seven => &mut self.p7,
+            Player::Eight => &mut self.p8,
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: GenesisButton, player: Player, pressed: bool) {
+        match button {
+            GenesisButton::Controller(button) => {
+                self.joypad_mut(player).set_button(button, pressed);
+            }
+            GenesisButton::Mouse(button) => self.mouse.set_button(button, pressed),
+            GenesisButton::LightGun(button) => self.light_gun.set_button(button, pressed),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_button(mut self, button: GenesisButton, player: Player, pressed: bool) -> Self {
+        self.set_button(button, player, pressed);
+        self
+    }
+}
+
+/// Device connected to a controller port: nothing, a 3-button or 6-button pad, or a peripheral.
+/// The Menacer and the Justifier are only supported on port 2.
+///
+/// The EA 4-Way Play plugs into both ports, so selecting it for either port replaces the device in
+/// the other port as well.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumFromStr, EnumDisplay)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GenesisControllerType {
+    /// Nothing is plugged in; all pins are pulled high
+    None,
+    ThreeButton,
+    #[default]
+    SixButton,
+    Mouse,
+    Menacer,
+    Justifier,
+    TeamPlayer,
+    EaFourWayPlay,
+}
+
+impl GenesisControllerType {
+    // Number of players whose controllers are read through this port
+    fn player_count(self) -> usize {
+        match self {
+            Self::TeamPlayer => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// Controller connected to one of the ports on a multitap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumFromStr, EnumDisplay)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GenesisPadType {
+    None,
     ThreeButton,
     #[default]
     SixButton,
@@ -63,14 +228,10 @@ impl InputPinDirection {
     }
 }
 
-// Slightly less than 1.5ms
-const FLIP_COUNTER_CYCLES: u32 = 10000;
-
 #[derive(Debug, Clone, Copy, Default, Encode, Decode)]
 struct PinDirections {
     last_data_write: u8,
-    th_flip_count: u8,
-    flip_reset_counter: u32,
+    th_interrupt_enabled: bool,
     th: InputPinDirection,
     tr: InputPinDirection,
     tl: InputPinDirection,
@@ -82,6 +243,7 @@ struct PinDirections {
 
 impl PinDirections {
     fn write_ctrl(&mut self, ctrl_byte: u8) {
+        self.th_interrupt_enabled = ctrl_byte.bit(7);
         self.th = InputPinDirection::from_ctrl_bit(ctrl_byte.bit(6));
         self.tr = InputPinDirection::from_ctrl_bit(ctrl_byte.bit(5));
         self.tl = InputPinDirection::from_ctrl_bit(ctrl_byte.bit(4));
@@ -91,66 +253,35 @@ impl PinDirections {
         self.up = InputPinDirection::from_ctrl_bit(ctrl_byte.bit(0));
     }
 
-    fn write_data(&mut self, data_byte: u8, controller_type: GenesisControllerType) {
-        let prev_th = self.th.to_data_bit(true, self.last_data_write.bit(6));
+    fn write_data(&mut self, data_byte: u8) {
         self.last_data_write = data_byte;
-        let th = self.th.to_data_bit(true, self.last_data_write.bit(6));
-
-        // 6-button controller cycles through 4 different modes whenever TH flips from 0 to 1,
-        // resetting after ~1.5ms have passed without such a flip
-        if controller_type == GenesisControllerType::SixButton && !prev_th && th {
-            self.th_flip_count = (self.th_flip_count + 1) & 0x03;
-            self.flip_reset_counter = FLIP_COUNTER_CYCLES;
-        }
     }
 
-    fn to_data_byte(self, joypad_state: GenesisJoypadState) -> u8 {
-        let th = self.th.to_data_bit(true, self.last_data_write.bit(6));
+    // TH and TR are pulled high when they are not set as outputs
+    fn th_level(self) -> bool {
+        self.th.to_data_bit(true, self.last_data_write.bit(6))
+    }
 
-        let tr_joypad = if th { !joypad_state.c } else { !joypad_state.start };
-        let tl_joypad = if th { !joypad_state.b } else { !joypad_state.a };
-        let d3_joypad = match (self.th_flip_count, th) {
-            (0..=2, true) => !joypad_state.right,
-            (3, true) => !joypad_state.mode,
-            (0..=2, false) => false,
-            (3, false) => true,
-            _ => panic!("th_flip_count should always be <= 3"),
-        };
-        let d2_joypad = match (self.th_flip_count, th) {
-            (0..=2, true) => !joypad_state.left,
-            (3, true) => !joypad_state.x,
-            (0..=2, false) => false,
-            (3, false) => true,
-            _ => panic!("th_flip_count should always be <= 3"),
-        };
-        let d1_joypad = match (self.th_flip_count, th) {
-            (0 | 1, _) | (2, true) => !joypad_state.down,
-            (3, true) => !joypad_state.y,
-            (2, false) => false,
-            (3, false) => true,
-            _ => panic!("th_flip_count should always be <= 3"),
-        };
-        let d0_joypad = match (self.th_flip_count, th) {
-            (0 | 1, _) | (2, true) => !joypad_state.up,
-            (3, true) => !joypad_state.z,
-            (2, false) => false,
-            (3, false) => true,
-            _ => panic!("th_flip_count should always be <= 3"),
-        };
+    fn tr_level(self) -> bool {
+        self.tr.to_data_bit(true, self.last_data_write.bit(5))
+    }
 
+    // Combine the pin levels driven by the connected device with the levels of any output pins
+    fn to_data_byte(self, device_pins: u8) -> u8 {
         let last_data_write = self.last_data_write;
         (last_data_write & 0x80)
-            | (u8::from(th) << 6)
-            | (u8::from(self.tr.to_data_bit(tr_joypad, last_data_write.bit(5))) << 5)
-            | (u8::from(self.tl.to_data_bit(tl_joypad, last_data_write.bit(4))) << 4)
-            | (u8::from(self.right.to_data_bit(d3_joypad, last_data_write.bit(3))) << 3)
-            | (u8::from(self.left.to_data_bit(d2_joypad, last_data_write.bit(2))) << 2)
-            | (u8::from(self.down.to_data_bit(d1_joypad, last_data_write.bit(1))) << 1)
-            | u8::from(self.up.to_data_bit(d0_joypad, last_data_write.bit(0)))
+            | (u8::from(self.th.to_data_bit(device_pins.bit(6), last_data_write.bit(6))) << 6)
+            | (u8::from(self.tr.to_data_bit(device_pins.bit(5), last_data_write.bit(5))) << 5)
+            | (u8::from(self.tl.to_data_bit(device_pins.bit(4), last_data_write.bit(4))) << 4)
+            | (u8::from(self.right.to_data_bit(device_pins.bit(3), last_data_write.bit(3))) << 3)
+            | (u8::from(self.left.to_data_bit(device_pins.bit(2), last_data_write.bit(2))) << 2)
+            | (u8::from(self.down.to_data_bit(device_pins.bit(1), last_data_write.bit(1))) << 1)
+            | u8::from(self.up.to_data_bit(device_pins.bit(0), last_data_write.bit(0)))
     }
 
     fn to_ctrl_byte(self) -> u8 {
-        (u8::from(self.th.to_ctrl_bit()) << 6)
+        (u8::from(self.th_interrupt_enabled) << 7)
+            | (u8::from(self.th.to_ctrl_bit()) << 6)
             | (u8::from(self.tr.to_ctrl_bit()) << 5)
             | (u8::from(self.tl.to_ctrl_bit()) << 4)
             | (u8::from(self.right.to_ctrl_bit()) << 3)
@@ -158,6 +289,26 @@ impl PinDirections {
             | (u8::from(self.down.to_ctrl_bit()) << 1)
             | u8::from(self.up.to_ctrl_bit())
     }
+}
+
+// Slightly less than 1.5ms
+const FLIP_COUNTER_CYCLES: u32 = 10000;
+
+// 6-button controller cycles through 4 different modes whenever TH flips from 0 to 1, resetting
+// after ~1.5ms have passed without such a flip
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct SixButtonCounter {
+    th_flip_count: u8,
+    flip_reset_counter: u32,
+}
+
+impl SixButtonCounter {
+    fn handle_th_write(&mut self, prev_th: bool, th: bool) {
+        if !prev_th && th {
+            self.th_flip_count = (self.th_flip_count + 1) & 0x03;
+            self.flip_reset_counter = FLIP_COUNTER_CYCLES;
+        }
+    }
 
     fn tick(&mut self, m68k_cycles: u32) {
         self.flip_reset_counter = self.flip_reset_counter.saturating_sub(m68k_cycles);
@@ -167,13 +318,363 @@ impl PinDirections {
     }
 }
 
+// 3-button controllers never advance the flip counter, so they always read as if it is 0
+fn joypad_pins(th: bool, th_flip_count: u8, joypad_state: GenesisJoypadState) -> u8 {
+    let tr_joypad = if th { !joypad_state.c } else { !joypad_state.start };
+    let tl_joypad = if th { !joypad_state.b } else { !joypad_state.a };
+    let d3_joypad = match (th_flip_count, th) {
+        (0..=2, true) => !joypad_state.right,
+        (3, true) => !joypad_state.mode,
+        (0..=2, false) => false,
+        (3, false) => true,
+        _ => panic!("th_flip_count should always be <= 3"),
+    };
+    let d2_joypad = match (th_flip_count, th) {
+        (0..=2, true) => !joypad_state.left,
+        (3, true) => !joypad_state.x,
+        (0..=2, false) => false,
+        (3, false) => true,
+        _ => panic!("th_flip_count should always be <= 3"),
+    };
+    let d1_joypad = match (th_flip_count, th) {
+        (0 | 1, _) | (2, true) => !joypad_state.down,
+        (3, true) => !joypad_state.y,
+        (2, false) => false,
+        (3, false) => true,
+        _ => panic!("th_flip_count should always be <= 3"),
+    };
+    let d0_joypad = match (th_flip_count, th) {
+        (0 | 1, _) | (2, true) => !joypad_state.up,
+        (3, true) => !joypad_state.z,
+        (2, false) => false,
+        (3, false) => true,
+        _ => panic!("th_flip_count should always be <= 3"),
+    };
+
+    0x40 | (u8::from(tr_joypad) << 5)
+        | (u8::from(tl_joypad) << 4)
+        | (u8::from(d3_joypad) << 3)
+        | (u8::from(d2_joypad) << 2)
+        | (u8::from(d1_joypad) << 1)
+        | u8::from(d0_joypad)
+}
+
+// How long the mouse holds TL at the busy level after a TR handshake; some games depend on seeing
+// at least one busy read
+const MOUSE_BUSY_CYCLES: u32 = 50;
+
+// Largest motion that the mouse can report on each axis; larger motion sets the overflow flag
+const MOUSE_MAX_DELTA: i32 = 255;
+
+/// Sega Mega Mouse read protocol.
+///
+/// The game starts a read by setting TH low, then toggles TR to step through 9 nibbles on D3-D0:
+/// a fixed ID ($B, $F, $F), the sign and overflow flags, the buttons, and then the X and Y motion
+/// as 8-bit values. The mouse acknowledges each step by setting TL to the level of TR. Setting TH
+/// high ends the read.
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct MouseHandshake {
+    phase: u8,
+    busy_cycles: u32,
+    delta_x: i32,
+    delta_y: i32,
+    last_position: Option<(u16, u16)>,
+}
+
+impl MouseHandshake {
+    fn handle_write(
+        &mut self,
+        prev: PinDirections,
+        current: PinDirections,
+        mouse: GenesisMouseState,
+    ) {
+        if current.th_level() {
+            self.phase = 0;
+            return;
+        }
+
+        if prev.th_level() {
+            // TH 1->0 starts a read; report all motion since the previous read
+            self.phase = 1;
+            self.latch_motion(mouse.position);
+        } else if prev.tr_level() != current.tr_level() {
+            self.phase = (self.phase + 1).min(9);
+            self.busy_cycles = MOUSE_BUSY_CYCLES;
+        }
+    }
+
+    fn latch_motion(&mut self, position: Option<(u16, u16)>) {
+        (self.delta_x, self.delta_y) = match (self.last_position, position) {
+            // Y axis is positive going up
+            (Some((last_x, last_y)), Some((x, y))) => {
+                (i32::from(x) - i32::from(last_x), i32::from(last_y) - i32::from(y))
+            }
+            _ => (0, 0),
+        };
+        self.last_position = position;
+    }
+
+    fn pins(self, tr: bool, mouse: GenesisMouseState) -> u8 {
+        let delta_x = self.delta_x.clamp(-MOUSE_MAX_DELTA, MOUSE_MAX_DELTA) as u8;
+        let delta_y = self.delta_y.clamp(-MOUSE_MAX_DELTA, MOUSE_MAX_DELTA) as u8;
+
+        let nibble = match self.phase {
+            0 => 0x0,
+            1 => 0xB,
+            2 | 3 => 0xF,
+            4 => {
+                (u8::from(self.delta_y.abs() > MOUSE_MAX_DELTA) << 3)
+                    | (u8::from(self.delta_x.abs() > MOUSE_MAX_DELTA) << 2)
+                    | (u8::from(self.delta_y < 0) << 1)
+                    | u8::from(self.delta_x < 0)
+            }
+            5 => {
+                (u8::from(mouse.start) << 3)
+                    | (u8::from(mouse.middle) << 2)
+                    | (u8::from(mouse.right) << 1)
+                    | u8::from(mouse.left)
+            }
+            6 => delta_x >> 4,
+            7 => delta_x & 0x0F,
+            8 => delta_y >> 4,
+            9 => delta_y & 0x0F,
+            _ => panic!("mouse phase should always be <= 9"),
+        };
+
+        let tl = if self.busy_cycles != 0 { !tr } else { tr };
+
+        0x60 | (u8::from(tl) << 4) | nibble
+    }
+
+    fn tick(&mut self, m68k_cycles: u32) {
+        self.busy_cycles = self.busy_cycles.saturating_sub(m68k_cycles);
+    }
+}
+
+fn menacer_pins(light_gun: LightGunState) -> u8 {
+    // Buttons are active high on D3-D0; TL and TR are not connected
+    0x40 | (u8::from(light_gun.start) << 3)
+        | (u8::from(light_gun.trigger) << 2)
+        | (u8::from(light_gun.b) << 1)
+        | u8::from(light_gun.a)
+}
+
+fn justifier_pins(pin_directions: PinDirections, light_gun: LightGunState) -> u8 {
+    if pin_directions.th_level() {
+        // No gun selected
+        return 0x70;
+    }
+
+    // TR selects between the blue gun (low) and the pink gun (high). Only the blue gun is
+    // connected, and its buttons are active low on D1-D0
+    if pin_directions.tr_level() {
+        0x73
+    } else {
+        0x70 | (u8::from(!light_gun.start) << 1) | u8::from(!light_gun.trigger)
+    }
+}
+
+// Bits are in order from D3 to D0
+fn active_low_nibble(bits: [bool; 4]) -> u8 {
+    bits.into_iter().fold(0, |nibble, bit| (nibble << 1) | u8::from(!bit))
+}
+
+/// Sega Team Player protocol.
+///
+/// The game starts a read by setting TH low, then toggles TR to step through nibbles on D3-D0: a
+/// fixed ID ($F, $0, $0), the type of each of the 4 pads (0 = 3-button, 1 = 6-button), and then
+/// the buttons of each pad in order. 3-button pads send 2 nibbles (RLDU, SACB) and 6-button pads
+/// send a third (MXYZ). The Team Player acknowledges each step by setting TL to the level of TR.
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct TeamPlayerHandshake {
+    phase: u8,
+}
+
+impl TeamPlayerHandshake {
+    fn handle_write(&mut self, prev: PinDirections, current: PinDirections) {
+        if current.th_level() {
+            self.phase = 0;
+        } else if prev.th_level() {
+            self.phase = 1;
+        } else if prev.tr_level() != current.tr_level() {
+            self.phase = self.phase.saturating_add(1);
+        }
+    }
+
+    fn pins(self, tr: bool, pads: [(GenesisPadType, GenesisJoypadState); 4]) -> u8 {
+        let nibble = match self.phase {
+            0 => return 0x73,
+            1 => 0xF,
+            2 | 3 => 0x0,
+            4..=7 => match pads[usize::from(self.phase - 4)].0 {
+                GenesisPadType::None => 0xF,
+                GenesisPadType::ThreeButton => 0x0,
+                GenesisPadType::SixButton => 0x1,
+            },
+            _ => pads
+                .into_iter()
+                .flat_map(|(pad_type, joypad)| {
+                    let len = match pad_type {
+                        GenesisPadType::None => 0,
+                        GenesisPadType::ThreeButton => 2,
+                        GenesisPadType::SixButton => 3,
+                    };
+                    [
+                        active_low_nibble([joypad.right, joypad.left, joypad.down, joypad.up]),
+                        active_low_nibble([joypad.start, joypad.a, joypad.c, joypad.b]),
+                        active_low_nibble([joypad.mode, joypad.x, joypad.y, joypad.z]),
+                    ]
+                    .into_iter()
+                    .take(len)
+                })
+                .nth(usize::from(self.phase - 8))
+                .unwrap_or(0xF),
+        };
+
+        0x60 | (u8::from(tr) << 4) | nibble
+    }
+}
+
+/// EA 4-Way Play protocol.
+///
+/// The adapter plugs into both ports. Writing to port 2 with TH/TR/TL set as outputs selects which
+/// pad is connected to port 1 ($0x-$3x), and port 1 then reads the selected pad like a normal
+/// controller. Selecting a value with D6 set disconnects all pads, which makes port 1 read D1-D0
+/// as 0; games check this to detect the adapter.
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct EaFourWayPlay {
+    selected: u8,
+    six_button: [SixButtonCounter; 4],
+}
+
+impl EaFourWayPlay {
+    fn handle_port_2_write(&mut self, pin_directions: PinDirections) {
+        if pin_directions.th.to_ctrl_bit()
+            && pin_directions.tr.to_ctrl_bit()
+            && pin_directions.tl.to_ctrl_bit()
+        {
+            self.selected = (pin_directions.last_data_write >> 4) & 0x07;
+        }
+    }
+
+    fn handle_port_1_write(
+        &mut self,
+        prev: PinDirections,
+        current: PinDirections,
+        pad_types: [GenesisPadType; 8],
+    ) {
+        let pad = usize::from(self.selected & 0x03);
+        if pad_types[pad] == GenesisPadType::SixButton {
+            self.six_button[pad].handle_th_write(prev.th_level(), current.th_level());
+        }
+    }
+
+    fn port_1_pins(self, th: bool, inputs: &GenesisInputs, pad_types: [GenesisPadType; 8]) -> u8 {
+        if self.selected.bit(2) {
+            return 0x7C;
+        }
+
+        let pad = usize::from(self.selected & 0x03);
+        if pad_types[pad] == GenesisPadType::None {
+            return 0x7F;
+        }
+
+        joypad_pins(th, self.six_button[pad].th_flip_count, inputs.joypad(Player::ALL[pad]))
+    }
+
+    fn tick(&mut self, m68k_cycles: u32) {
+        for six_button in &mut self.six_button {
+            six_button.tick(m68k_cycles);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+struct ControllerPort {
+    controller_type: GenesisControllerType,
+    pin_directions: PinDirections,
+    six_button: SixButtonCounter,
+    mouse: MouseHandshake,
+    team_player: TeamPlayerHandshake,
+}
+
+impl ControllerPort {
+    fn new(controller_type: GenesisControllerType) -> Self {
+        Self {
+            controller_type,
+            pin_directions: PinDirections::default(),
+            six_button: SixButtonCounter::default(),
+            mouse: MouseHandshake::default(),
+            team_player: TeamPlayerHandshake::default(),
+        }
+    }
+
+    // first_player is the index of the first player whose controller is read through this port
+    fn read_data(
+        &self,
+        inputs: &GenesisInputs,
+        first_player: usize,
+        pad_types: [GenesisPadType; 8],
+    ) -> u8 {
+        let pin_directions = self.pin_directions;
+        let th = pin_directions.th_level();
+        let joypad = inputs.joypad(Player::ALL[first_player]);
+
+        let device_pins = match self.controller_type {
+            GenesisControllerType::ThreeButton => joypad_pins(th, 0, joypad),
+            GenesisControllerType::SixButton => {
+                joypad_pins(th, self.six_button.th_flip_count, joypad)
+            }
+            GenesisControllerType::Mouse => {
+                self.mouse.pins(pin_directions.tr_level(), inputs.mouse)
+            }
+            GenesisControllerType::Menacer => menacer_pins(inputs.light_gun),
+            GenesisControllerType::Justifier => justifier_pins(pin_directions, inputs.light_gun),
+            GenesisControllerType::TeamPlayer => {
+                let pads = [0, 1, 2, 3].map(|i| {
+                    let player = first_player + i;
+                    (pad_types[player], inputs.joypad(Player::ALL[player]))
+                });
+                self.team_player.pins(pin_directions.tr_level(), pads)
+            }
+            // Nothing drives the pins when unplugged; the EA 4-Way Play is handled by InputState
+            GenesisControllerType::None | GenesisControllerType::EaFourWayPlay => 0x7F,
+        };
+
+        pin_directions.to_data_byte(device_pins)
+    }
+
+    fn write_data(&mut self, value: u8, inputs: &GenesisInputs) {
+        let prev_pin_directions = self.pin_directions;
+        self.pin_directions.write_data(value);
+
+        match self.controller_type {
+            GenesisControllerType::SixButton => self
+                .six_button
+                .handle_th_write(prev_pin_directions.th_level(), self.pin_directions.th_level()),
+            GenesisControllerType::Mouse => {
+                self.mouse.handle_write(prev_pin_directions, self.pin_directions, inputs.mouse);
+            }
+            GenesisControllerType::TeamPlayer => {
+                self.team_player.handle_write(prev_pin_directions, self.pin_directions);
+            }
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self, m68k_cycles: u32) {
+        self.six_button.tick(m68k_cycles);
+        self.mouse.tick(m68k_cycles);
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct InputState {
     inputs: GenesisInputs,
-    p1_controller_type: GenesisControllerType,
-    p2_controller_type: GenesisControllerType,
-    p1_pin_directions: PinDirections,
-    p2_pin_directions: PinDirections,
+    p1: ControllerPort,
+    p2: ControllerPort,
+    multitap_pad_types: [GenesisPadType; 8],
+    ea_four_way_play: EaFourWayPlay,
 }
 
 impl InputState {
@@ -181,13 +682,14 @@ impl InputState {
     pub fn new(
         p1_controller_type: GenesisControllerType,
         p2_controller_type: GenesisControllerType,
+        multitap_pad_types: [GenesisPadType; 8],
     ) -> Self {
         Self {
             inputs: GenesisInputs::default(),
-            p1_controller_type,
-            p2_controller_type,
-            p1_pin_directions: PinDirections::default(),
-            p2_pin_directions: PinDirections::default(),
+            p1: ControllerPort::new(p1_controller_type),
+            p2: ControllerPort::new(p2_controller_type),
+            multitap_pad_types,
+            ea_four_way_play: EaFourWayPlay::default(),
         }
     }
 
@@ -196,53 +698,308 @@ impl InputState {
     }
 
     pub fn reload_config(&mut self, config: GenesisEmulatorConfig) {
-        self.p1_controller_type = config.p1_controller_type;
-        self.p2_controller_type = config.p2_controller_type;
+        self.p1.controller_type = config.p1_controller_type;
+        self.p2.controller_type = config.p2_controller_type;
+        self.multitap_pad_types = config.multitap_pad_types;
     }
 
     #[must_use]
     pub fn controller_types(&self) -> (GenesisControllerType, GenesisControllerType) {
-        (self.p1_controller_type, self.p2_controller_type)
+        (self.p1.controller_type, self.p2.controller_type)
+    }
+
+    #[must_use]
+    pub fn multitap_pad_types(&self) -> [GenesisPadType; 8] {
+        self.multitap_pad_types
+    }
+
+    fn ea_four_way_play_connected(&self) -> bool {
+        self.p1.controller_type == GenesisControllerType::EaFourWayPlay
+            || self.p2.controller_type == GenesisControllerType::EaFourWayPlay
     }
 
     #[must_use]
     pub fn read_p1_data(&self) -> u8 {
-        self.p1_pin_directions.to_data_byte(self.inputs.p1)
+        if self.ea_four_way_play_connected() {
+            let pin_directions = self.p1.pin_directions;
+            let device_pins = self.ea_four_way_play.port_1_pins(
+                pin_directions.th_level(),
+                &self.inputs,
+                self.multitap_pad_types,
+            );
+            return pin_directions.to_data_byte(device_pins);
+        }
+
+        self.p1.read_data(&self.inputs, 0, self.multitap_pad_types)
     }
 
     #[must_use]
     pub fn read_p2_data(&self) -> u8 {
-        self.p2_pin_directions.to_data_byte(self.inputs.p2)
+        if self.ea_four_way_play_connected() {
+            return self.p2.pin_directions.to_data_byte(0x7F);
+        }
+
+        // Player numbers continue from the last player connected to port 1
+        let first_player = self.p1.controller_type.player_count();
+        self.p2.read_data(&self.inputs, first_player, self.multitap_pad_types)
     }
 
     pub fn write_p1_data(&mut self, value: u8) {
-        self.p1_pin_directions.write_data(value, self.p1_controller_type);
+        if self.ea_four_way_play_connected() {
+            let prev_pin_directions = self.p1.pin_directions;
+            self.p1.pin_directions.write_data(value);
+            self.ea_four_way_play.handle_port_1_write(
+                prev_pin_directions,
+                self.p1.pin_directions,
+                self.multitap_pad_types,
+            );
+            return;
+        }
+
+        self.p1.write_data(value, &self.inputs);
     }
 
     pub fn write_p2_data(&mut self, value: u8) {
-        self.p2_pin_directions.write_data(value, self.p2_controller_type);
+        if self.ea_four_way_play_connected() {
+            self.p2.pin_directions.write_data(value);
+            self.ea_four_way_play.handle_port_2_write(self.p2.pin_directions);
+            return;
+        }
+
+        self.p2.write_data(value, &self.inputs);
     }
 
     #[must_use]
     pub fn read_p1_ctrl(&self) -> u8 {
-        self.p1_pin_directions.to_ctrl_byte()
+        self.p1.pin_directions.to_ctrl_byte()
     }
 
     #[must_use]
     pub fn read_p2_ctrl(&self) -> u8 {
-        self.p2_pin_directions.to_ctrl_byte()
+        self.p2.pin_directions.to_ctrl_byte()
     }
 
     pub fn write_p1_ctrl(&mut self, value: u8) {
-        self.p1_pin_directions.write_ctrl(value);
+        self.p1.pin_directions.write_ctrl(value);
     }
 
     pub fn write_p2_ctrl(&mut self, value: u8) {
-        self.p2_pin_directions.write_ctrl(value);
+        self.p2.pin_directions.write_ctrl(value);
+    }
+
+    /// Frame buffer position where the light gun in port 2 will sense the beam and pulse the VDP's
+    /// HL line, or `None` if there is no light gun that is currently able to do so.
+    ///
+    /// The HL pulse only reaches the VDP if the game has enabled TH interrupts for port 2.
+    #[must_use]
+    pub fn light_gun_position(&self) -> Option<(u16, u16)> {
+        let pin_directions = self.p2.pin_directions;
+        if !pin_directions.th_interrupt_enabled || self.ea_four_way_play_connected() {
+            return None;
+        }
+
+        match self.p2.controller_type {
+            GenesisControllerType::Menacer => self.inputs.light_gun.position,
+            GenesisControllerType::Justifier => {
+                // Only the blue gun is connected, and it only senses light while it is selected
+                let blue_gun_selected = !pin_directions.th_level() && !pin_directions.tr_level();
+                self.inputs.light_gun.position.filter(|_| blue_gun_selected)
+            }
+            GenesisControllerType::None
+            | GenesisControllerType::ThreeButton
+            | GenesisControllerType::SixButton
+            | GenesisControllerType::Mouse
+            | GenesisControllerType::TeamPlayer
+            | GenesisControllerType::EaFourWayPlay => None,
+        }
     }
 
     pub fn tick(&mut self, m68k_cycles: u32) {
-        self.p1_pin_directions.tick(m68k_cycles);
-        self.p2_pin_directions.tick(m68k_cycles);
+        self.p1.tick(m68k_cycles);
+        self.p2.tick(m68k_cycles);
+        self.ea_four_way_play.tick(m68k_cycles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_mouse_nibbles(input: &mut InputState) -> Vec<u8> {
+        // TH low starts the read, then each TR toggle advances to the next nibble
+        input.write_p1_data(0x20);
+
+        let mut nibbles = vec![input.read_p1_data() & 0x0F];
+        let mut tr = true;
+        for _ in 1..9 {
+            tr = !tr;
+            input.write_p1_data(u8::from(tr) << 5);
+            assert_eq!(input.read_p1_data().bit(4), !tr, "TL should be busy after a handshake");
+
+            input.tick(MOUSE_BUSY_CYCLES);
+            let data = input.read_p1_data();
+            assert_eq!(data.bit(4), tr, "TL should acknowledge the handshake");
+            nibbles.push(data & 0x0F);
+        }
+        input.write_p1_data(0x60);
+
+        nibbles
+    }
+
+    #[test]
+    fn mouse_handshake() {
+        let mut input = InputState::new(
+            GenesisControllerType::Mouse,
+            GenesisControllerType::SixButton,
+            [GenesisPadType::default(); 8],
+        );
+        input.write_p1_ctrl(0x60);
+        input.write_p1_data(0x60);
+
+        input.set_inputs(GenesisInputs {
+            mouse: GenesisMouseState { position: Some((100, 100)), ..GenesisMouseState::default() },
+            ..GenesisInputs::default()
+        });
+        read_mouse_nibbles(&mut input);
+
+        // Right 20 pixels and down 3 pixels, with the left button held
+        input.set_inputs(GenesisInputs {
+            mouse: GenesisMouseState {
+                left: true,
+                position: Some((120, 103)),
+                ..GenesisMouseState::default()
+            },
+            ..GenesisInputs::default()
+        });
+        assert_eq!(
+            read_mouse_nibbles(&mut input),
+            vec![0xB, 0xF, 0xF, 0b0010, 0b0001, 0x1, 0x4, 0xF, 0xD]
+        );
+    }
+
+    #[test]
+    fn team_player_handshake() {
+        let mut pad_types = [GenesisPadType::ThreeButton; 8];
+        pad_types[1] = GenesisPadType::SixButton;
+
+        let mut input = InputState::new(
+            GenesisControllerType::TeamPlayer,
+            GenesisControllerType::SixButton,
+            pad_types,
+        );
+        input.write_p1_ctrl(0x60);
+        input.write_p1_data(0x60);
+        assert_eq!(input.read_p1_data(), 0x73);
+
+        let mut inputs = GenesisInputs::default();
+        inputs.p1.start = true;
+        inputs.p2.x = true;
+        input.set_inputs(inputs);
+
+        let mut nibbles = Vec::new();
+        let mut tr = true;
+        input.write_p1_data(0x20);
+        for _ in 0..20 {
+            let data = input.read_p1_data();
+            assert_eq!(data.bit(4), tr, "TL should follow TR");
+            nibbles.push(data & 0x0F);
+
+            tr = !tr;
+            input.write_p1_data(u8::from(tr) << 5);
+        }
+
+        assert_eq!(nibbles[..3], [0xF, 0x0, 0x0], "ID");
+        assert_eq!(nibbles[3..7], [0x0, 0x1, 0x0, 0x0], "pad types");
+        assert_eq!(nibbles[7..9], [0xF, 0x7], "player 1 (3-button) with Start pressed");
+        assert_eq!(nibbles[9..12], [0xF, 0xF, 0xB], "player 2 (6-button) with X pressed");
+        assert!(nibbles[12..].iter().all(|&nibble| nibble == 0xF), "players 3-4: {nibbles:X?}");
+    }
+
+    #[test]
+    fn ea_four_way_play() {
+        let mut pad_types = [GenesisPadType::None; 8];
+        pad_types[..4].copy_from_slice(&[
+            GenesisPadType::SixButton,
+            GenesisPadType::ThreeButton,
+            GenesisPadType::SixButton,
+            GenesisPadType::ThreeButton,
+        ]);
+
+        let mut input = InputState::new(
+            GenesisControllerType::EaFourWayPlay,
+            GenesisControllerType::EaFourWayPlay,
+            pad_types,
+        );
+        input.write_p1_ctrl(0x40);
+        input.write_p1_data(0x40);
+        input.write_p2_ctrl(0x70);
+
+        // Selecting a value with D6 set disconnects all pads
+        input.write_p2_data(0x40);
+        assert_eq!(input.read_p1_data(), 0x7C);
+
+        let mut inputs = GenesisInputs::default();
+        inputs.p1.x = true;
+        inputs.p2.b = true;
+        inputs.p3.x = true;
+        input.set_inputs(inputs);
+
+        // Port 2 selects which pad port 1 reads
+        input.write_p2_data(0x10);
+        assert_eq!(input.read_p1_data(), 0x6F, "player 2 (3-button) with B pressed");
+        assert_eq!(input.read_p2_data() & 0x0F, 0x0F, "port 2 should not read any pad");
+
+        // Advance player 1's 6-button counter to the extra buttons
+        input.tick(FLIP_COUNTER_CYCLES);
+        input.write_p2_data(0x00);
+        for _ in 0..3 {
+            input.write_p1_data(0x00);
+            input.write_p1_data(0x40);
+        }
+        assert_eq!(input.read_p1_data() & 0x0F, 0xB, "player 1 extra buttons with X pressed");
+
+        // Each pad has its own counter
+        input.write_p2_data(0x20);
+        assert_eq!(input.read_p1_data() & 0x0F, 0xF, "player 3 directions");
+        input.write_p2_data(0x00);
+        assert_eq!(input.read_p1_data() & 0x0F, 0xB, "player 1 extra buttons with X pressed");
+    }
+
+    #[test]
+    fn unplugged_devices() {
+        let mut pad_types = [GenesisPadType::None; 8];
+        pad_types[2] = GenesisPadType::ThreeButton;
+
+        let mut input = InputState::new(
+            GenesisControllerType::TeamPlayer,
+            GenesisControllerType::None,
+            pad_types,
+        );
+        input.write_p1_ctrl(0x60);
+        input.write_p1_data(0x60);
+        input.write_p2_ctrl(0x40);
+        input.write_p2_data(0x00);
+
+        let mut inputs = GenesisInputs::default();
+        inputs.p2.a = true;
+        inputs.p3.a = true;
+        input.set_inputs(inputs);
+
+        // Nothing drives the input pins on port 2, so they read high regardless of player 2's inputs
+        assert_eq!(input.read_p2_data() & 0x3F, 0x3F);
+
+        let mut nibbles = Vec::new();
+        let mut tr = true;
+        input.write_p1_data(0x20);
+        for _ in 0..12 {
+            nibbles.push(input.read_p1_data() & 0x0F);
+
+            tr = !tr;
+            input.write_p1_data(u8::from(tr) << 5);
+        }
+
+        assert_eq!(nibbles[3..7], [0xF, 0xF, 0x0, 0xF], "pad types");
+        assert_eq!(nibbles[7..9], [0xF, 0xB], "player 3 (3-button) with A pressed");
+        assert!(nibbles[9..].iter().all(|&nibble| nibble == 0xF), "{nibbles:X?}");
     }
 }
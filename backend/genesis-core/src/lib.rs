@@ -1,5 +1,6 @@
 mod api;
 pub mod audio;
+pub mod cheats;
 pub mod input;
 pub mod memory;
 mod svp;
@@ -8,6 +9,6 @@ pub mod ym2612;
 
 pub use api::{
     render_frame, GenesisAspectRatio, GenesisEmulator, GenesisEmulatorConfig, GenesisError,
-    GenesisRegion, GenesisResult,
+    GenesisRegion, GenesisResult, M68K_DEBUG_CPU, Z80_DEBUG_CPU,
 };
-pub use input::{GenesisControllerType, GenesisInputs, GenesisJoypadState};
+pub use input::{GenesisControllerType, GenesisInputs, GenesisJoypadState, GenesisPadType};
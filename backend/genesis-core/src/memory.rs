@@ -11,11 +11,13 @@ use crate::vdp::Vdp;
 use crate::ym2612::Ym2612;
 use bincode::{Decode, Encode};
 use crc::Crc;
+use jgenesis_common::cheats::CheatSet;
 use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::{GetBit, U16Ext};
 use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
 use regex::Regex;
 use smsgg_core::psg::Psg;
+use smsgg_core::vgm::VgmLogger;
 use std::ops::Index;
 use std::sync::OnceLock;
 use std::{array, mem};
@@ -357,6 +359,7 @@ pub struct Memory<Medium> {
     audio_ram: Box<[u8; AUDIO_RAM_LEN]>,
     z80_bank_register: Z80BankRegister,
     signals: Signals,
+    cheats: CheatSet,
 }
 
 impl<Medium: PhysicalMedium> Memory<Medium> {
@@ -369,6 +372,7 @@ impl<Medium: PhysicalMedium> Memory<Medium> {
             audio_ram: vec![0; AUDIO_RAM_LEN].into_boxed_slice().try_into().unwrap(),
             z80_bank_register: Z80BankRegister::default(),
             signals: Signals::default(),
+            cheats: CheatSet::new(),
         }
     }
 
@@ -409,6 +413,37 @@ impl<Medium: PhysicalMedium> Memory<Medium> {
     pub fn reset_z80_signals(&mut self) {
         self.signals = Signals::default();
     }
+
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.cheats = cheats;
+    }
+
+    #[must_use]
+    pub fn take_cheats(&mut self) -> CheatSet {
+        mem::take(&mut self.cheats)
+    }
+
+    /// Write RAM cheat values to work RAM; this should be called once per frame.
+    pub fn apply_ram_cheats(&mut self) {
+        for patch in self.cheats.ram_patches() {
+            if patch.address >= 0xE00000 {
+                self.main_ram[(patch.address & 0xFFFF) as usize] = patch.value;
+            }
+        }
+    }
+
+    #[inline]
+    fn apply_rom_cheats_word(&self, address: u32, word: u16) -> u16 {
+        if self.cheats.is_empty() {
+            return word;
+        }
+
+        let [msb, lsb] = word.to_be_bytes();
+        u16::from_be_bytes([
+            self.cheats.apply_rom_read(address, msb),
+            self.cheats.apply_rom_read(address + 1, lsb),
+        ])
+    }
 }
 
 impl Memory<Cartridge> {
@@ -476,6 +511,7 @@ pub struct MainBus<'a, Medium> {
     psg: &'a mut Psg,
     ym2612: &'a mut Ym2612,
     input: &'a mut InputState,
+    vgm_logger: &'a mut VgmLogger,
     timing_mode: TimingMode,
     signals: MainBusSignals,
     pending_writes: MainBusWrites,
@@ -491,6 +527,7 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
         psg: &'a mut Psg,
         ym2612: &'a mut Ym2612,
         input: &'a mut InputState,
+        vgm_logger: &'a mut VgmLogger,
         timing_mode: TimingMode,
         signals: MainBusSignals,
         pending_writes: MainBusWrites,
@@ -501,6 +538,7 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
             psg,
             ym2612,
             input,
+            vgm_logger,
             timing_mode,
             signals,
             pending_writes,
@@ -571,6 +609,7 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
                 self.vdp.write_control(vdp_word);
             }
             0x11 | 0x13 | 0x15 | 0x17 => {
+                self.vgm_logger.log_psg_write(value);
                 self.psg.write(value);
             }
             0x10 | 0x12 | 0x14 | 0x16 | 0x18..=0x1F => {}
@@ -680,6 +719,35 @@ impl<'a, Medium: PhysicalMedium> MainBus<'a, Medium> {
         }
     }
 
+    /// Read a byte from the 68000 address space without side effects, for use by debuggers. VDP
+    /// ports and cartridge/expansion registers read as $FF.
+    pub fn peek_m68k_byte(&mut self, address: u32) -> u8 {
+        let address = address & ADDRESS_MASK;
+        match address {
+            0xA00000..=0xA0FFFF => self.peek_z80_byte((address & 0x7FFF) as u16),
+            0xA12000..=0xA1500F | 0xC00000..=0xC0001F => 0xFF,
+            _ => <Self as m68000_emu::BusInterface>::read_byte(self, address),
+        }
+    }
+
+    /// Read a byte from the Z80 address space without side effects, for use by debuggers. VDP
+    /// ports read as $FF.
+    pub fn peek_z80_byte(&mut self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x3FFF => self.memory.audio_ram[(address & 0x1FFF) as usize],
+            0x4000..=0x5FFF => self.ym2612.read_register(),
+            0x6000..=0x7FFF => 0xFF,
+            0x8000..=0xFFFF => {
+                let m68k_addr = self.memory.z80_bank_register.map_to_68k_address(address);
+                if (0xA00000..=0xA0FFFF).contains(&m68k_addr) {
+                    0xFF
+                } else {
+                    self.peek_m68k_byte(m68k_addr)
+                }
+            }
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn z80_accessed_68k_bus(&self) -> bool {
@@ -697,7 +765,8 @@ impl<'a, Medium: PhysicalMedium> m68000_emu::BusInterface for MainBus<'a, Medium
         log::trace!("Main bus byte read, address={address:06X}");
         match address {
             0x000000..=0x7FFFFF | 0xA12000..=0xA1500F => {
-                self.memory.physical_medium.read_byte(address)
+                let value = self.memory.physical_medium.read_byte(address);
+                self.memory.cheats.apply_rom_read(address, value)
             }
             0xA00000..=0xA0FFFF => {
                 // Z80 memory map
@@ -718,7 +787,8 @@ impl<'a, Medium: PhysicalMedium> m68000_emu::BusInterface for MainBus<'a, Medium
         log::trace!("Main bus word read, address={address:06X}");
         match address {
             0x000000..=0x7FFFFF | 0xA12000..=0xA1500F => {
-                self.memory.physical_medium.read_word(address)
+                let word = self.memory.physical_medium.read_word(address);
+                self.memory.apply_rom_cheats_word(address, word)
             }
             0xA00000..=0xA0FFFF => {
                 // All Z80 access is byte-size; word reads mirror the byte in both MSB and LSB
@@ -840,9 +910,18 @@ impl<'a, Medium: PhysicalMedium> z80_emu::BusInterface for MainBus<'a, Medium> {
             0x4000..=0x5FFF => {
                 // YM2612 registers/ports (mirrored every 4 addresses)
                 match address & 0x03 {
-                    0x00 => self.ym2612.write_address_1(value),
-                    0x02 => self.ym2612.write_address_2(value),
-                    0x01 | 0x03 => self.ym2612.write_data(value),
+                    0x00 => {
+                        self.vgm_logger.log_ym2612_address_write(0, value);
+                        self.ym2612.write_address_1(value);
+                    }
+                    0x02 => {
+                        self.vgm_logger.log_ym2612_address_write(1, value);
+                        self.ym2612.write_address_2(value);
+                    }
+                    0x01 | 0x03 => {
+                        self.vgm_logger.log_ym2612_data_write(value);
+                        self.ym2612.write_data(value);
+                    }
                     _ => unreachable!("value & 0x03 is always <= 0x03"),
                 }
             }
@@ -79,7 +79,9 @@ struct InternalState {
     v_interrupt_pending: bool,
     h_interrupt_pending: bool,
     h_interrupt_counter: u16,
+    external_interrupt_pending: bool,
     latched_hv_counter: Option<u16>,
+    light_gun_position: Option<(u16, u16)>,
     v_border_forgotten: bool,
     top_border: u16,
     last_scroll_b_palettes: [u8; 2],
@@ -104,7 +106,9 @@ impl InternalState {
             v_interrupt_pending: false,
             h_interrupt_pending: false,
             h_interrupt_counter: 0,
+            external_interrupt_pending: false,
             latched_hv_counter: None,
+            light_gun_position: None,
             v_border_forgotten: false,
             top_border: VerticalDisplaySize::default().top_border(timing_mode),
             last_scroll_b_palettes: [0; 2],
@@ -363,7 +367,7 @@ impl Vdp {
 
                     if self.registers.hv_counter_stopped && self.state.latched_hv_counter.is_none()
                     {
-                        self.state.latched_hv_counter = Some(self.hv_counter());
+                        self.state.latched_hv_counter = Some(self.current_hv_counter());
                     } else if !self.registers.hv_counter_stopped
                         && self.state.latched_hv_counter.is_some()
                     {
@@ -673,8 +677,16 @@ impl Vdp {
             return latched_hv_counter;
         }
 
-        let h_counter = self.h_counter(self.state.scanline_mclk_cycles);
-        let v_counter = self.v_counter(self.state.scanline_mclk_cycles);
+        self.current_hv_counter()
+    }
+
+    fn current_hv_counter(&self) -> u16 {
+        self.hv_counter_at(self.state.scanline_mclk_cycles)
+    }
+
+    fn hv_counter_at(&self, scanline_mclk: u64) -> u16 {
+        let h_counter = self.h_counter(scanline_mclk);
+        let v_counter = self.v_counter(scanline_mclk);
 
         log::trace!(
             "HV counter read on scanline {}; H={h_counter:02X}, V={v_counter:02X}",
@@ -775,6 +787,12 @@ impl Vdp {
         let prev_scanline_mclk = self.state.scanline_mclk_cycles;
         self.state.scanline_mclk_cycles += master_clock_cycles;
 
+        self.check_light_gun(
+            self.state.scanline,
+            prev_scanline_mclk,
+            self.state.scanline_mclk_cycles.min(MCLK_CYCLES_PER_SCANLINE),
+        );
+
         if prev_scanline_mclk < ACTIVE_MCLK_CYCLES_PER_SCANLINE
             && self.state.scanline_mclk_cycles >= ACTIVE_MCLK_CYCLES_PER_SCANLINE
         {
@@ -880,6 +898,8 @@ impl Vdp {
                 self.state.v_interrupt_pending = true;
             }
 
+            self.check_light_gun(self.state.scanline, 0, self.state.scanline_mclk_cycles);
+
             let last_scanline_of_frame =
                 self.timing_mode.rendered_lines_per_frame() - self.state.top_border;
             if self.state.scanline == last_scanline_of_frame {
@@ -908,6 +928,66 @@ impl Vdp {
         tick_effect
     }
 
+    /// Set the frame buffer position where a light gun will sense the beam, or `None` if no light
+    /// gun is currently able to sense it.
+    ///
+    /// When the beam reaches this position, the light gun pulses the HL line, which latches the HV
+    /// counter if it is stopped and generates a level 2 external interrupt if that is enabled.
+    pub fn set_light_gun_position(&mut self, position: Option<(u16, u16)>) {
+        self.state.light_gun_position = position;
+    }
+
+    // Convert a frame buffer position to a position in the active display, as (scanline, mclk)
+    fn light_gun_beam_position(&self, (x, y): (u16, u16)) -> Option<(u16, u64)> {
+        let h_display_size = self.registers.horizontal_display_size;
+        let x = if self.config.render_horizontal_border {
+            x.checked_sub(h_display_size.left_border())?
+        } else {
+            x
+        };
+        if x >= h_display_size.active_display_pixels() {
+            return None;
+        }
+
+        let y = match self.registers.interlacing_mode {
+            InterlacingMode::Progressive | InterlacingMode::Interlaced => y,
+            InterlacingMode::InterlacedDouble => y / 2,
+        };
+        let y = if self.config.render_vertical_border {
+            y.checked_sub(self.state.top_border)?
+        } else {
+            y
+        };
+        if y >= self.registers.vertical_display_size.active_scanlines() {
+            return None;
+        }
+
+        // Active display lasts for the same number of mclk cycles in both H32 and H40 mode
+        let mclk = u64::from(x) * ACTIVE_MCLK_CYCLES_PER_SCANLINE
+            / u64::from(h_display_size.active_display_pixels());
+        Some((y, mclk))
+    }
+
+    // Pulse HL if the beam passed the light gun position within the given range of the scanline
+    fn check_light_gun(&mut self, scanline: u16, start_mclk: u64, end_mclk: u64) {
+        let Some(position) = self.state.light_gun_position else { return };
+        let Some((gun_scanline, gun_mclk)) = self.light_gun_beam_position(position) else {
+            return;
+        };
+
+        if scanline != gun_scanline || !(start_mclk..end_mclk).contains(&gun_mclk) {
+            return;
+        }
+
+        // Latch the counter at the time of the pulse rather than at the end of the CPU instruction
+        if self.registers.hv_counter_stopped {
+            self.state.latched_hv_counter = Some(self.hv_counter_at(gun_mclk));
+        }
+        self.state.external_interrupt_pending = true;
+
+        log::trace!("Light gun triggered HL at scanline {scanline}, mclk {gun_mclk}");
+    }
+
     fn apply_pending_writes(&mut self) {
         let mut pending_writes = [PendingWrite::default(); 10];
         let pending_writes_len = self.state.pending_writes.len();
@@ -1005,11 +1085,13 @@ impl Vdp {
 
     #[must_use]
     pub fn m68k_interrupt_level(&self) -> u8 {
-        // TODO external interrupts at level 2
         if self.state.v_interrupt_pending && self.registers.v_interrupt_enabled {
             6
         } else if self.state.h_interrupt_pending && self.registers.h_interrupt_enabled {
             4
+        } else if self.state.external_interrupt_pending && self.registers.external_interrupt_enabled
+        {
+            2
         } else {
             0
         }
@@ -1022,6 +1104,8 @@ impl Vdp {
             self.state.v_interrupt_pending = false;
         } else if interrupt_level == 4 {
             self.state.h_interrupt_pending = false;
+        } else if interrupt_level == 2 {
+            self.state.external_interrupt_pending = false;
         }
     }
 
@@ -1212,4 +1296,68 @@ mod tests {
         assert_eq!(vdp.h_counter(MCLK_CYCLES_PER_SCANLINE - 16), 0xFF);
         assert_eq!(vdp.h_counter(MCLK_CYCLES_PER_SCANLINE - 1), 0xFF);
     }
+
+    fn light_gun_hv_latch(h_display_size_register: u16, x: u16, expected_hv: u16) {
+        let mut vdp = new_vdp();
+
+        // Stop the HV counter and set the horizontal display size
+        vdp.write_control(0x8002);
+        vdp.write_control(h_display_size_register);
+
+        vdp.set_light_gun_position(Some((x, 100)));
+        vdp.state.scanline = 100;
+        vdp.state.scanline_mclk_cycles = MCLK_CYCLES_PER_SCANLINE;
+        vdp.check_light_gun(100, 0, MCLK_CYCLES_PER_SCANLINE);
+
+        assert_eq!(vdp.hv_counter(), expected_hv);
+    }
+
+    #[test]
+    fn light_gun_hv_latch_h32() {
+        // The H counter advances once every 2 pixels during active display
+        light_gun_hv_latch(0x8C00, 200, 0x6464);
+    }
+
+    #[test]
+    fn light_gun_hv_latch_h40() {
+        light_gun_hv_latch(0x8C81, 300, 0x6496);
+    }
+
+    #[test]
+    fn light_gun_hv_latch_requires_stopped_counter() {
+        let mut vdp = new_vdp();
+
+        vdp.set_light_gun_position(Some((128, 100)));
+        vdp.state.scanline = 100;
+        vdp.check_light_gun(100, 0, MCLK_CYCLES_PER_SCANLINE);
+
+        assert_eq!(vdp.state.latched_hv_counter, None);
+    }
+
+    #[test]
+    fn light_gun_external_interrupt() {
+        let mut vdp = new_vdp();
+
+        vdp.set_light_gun_position(Some((128, 100)));
+        vdp.state.scanline = 100;
+
+        // Beam has not reached the light gun position yet
+        vdp.check_light_gun(100, 0, 1000);
+        assert_eq!(vdp.m68k_interrupt_level(), 0);
+
+        // Pending but masked while register 11 bit 3 is clear
+        vdp.check_light_gun(100, 1000, MCLK_CYCLES_PER_SCANLINE);
+        assert_eq!(vdp.m68k_interrupt_level(), 0);
+
+        vdp.write_control(0x8B08);
+        assert_eq!(vdp.m68k_interrupt_level(), 2);
+
+        vdp.acknowledge_m68k_interrupt();
+        assert_eq!(vdp.m68k_interrupt_level(), 0);
+
+        // Re-enabling does not raise the acknowledged interrupt again
+        vdp.write_control(0x8B00);
+        vdp.write_control(0x8B08);
+        assert_eq!(vdp.m68k_interrupt_level(), 0);
+    }
 }
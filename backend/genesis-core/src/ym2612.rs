@@ -14,6 +14,7 @@ use crate::ym2612::phase::PhaseGenerator;
 use crate::ym2612::timer::{TimerA, TimerB, TimerTickEffect};
 use bincode::{Decode, Encode};
 use jgenesis_common::num::{GetBit, U16Ext};
+use smsgg_core::vgm::VgmLogger;
 use std::array;
 use std::sync::OnceLock;
 
@@ -360,6 +361,9 @@ pub struct Ym2612 {
     lfo: LowFrequencyOscillator,
     selected_register: u8,
     selected_register_group: RegisterGroup,
+    // Raw register values as last written to each group, for logging the chip state when VGM
+    // logging starts
+    register_values: [[u8; 0x100]; 2],
     clock_divider: u8,
     sample_divider: u8,
     busy_cycles_remaining: u8,
@@ -379,6 +383,7 @@ impl Ym2612 {
             lfo: LowFrequencyOscillator::new(),
             selected_register: 0,
             selected_register_group: RegisterGroup::default(),
+            register_values: [[0; 0x100]; 2],
             clock_divider: FM_CLOCK_DIVIDER,
             sample_divider: FM_SAMPLE_DIVIDER,
             busy_cycles_remaining: 0,
@@ -423,6 +428,7 @@ impl Ym2612 {
         self.busy_cycles_remaining = WRITE_BUSY_CYCLES;
 
         let register = self.selected_register;
+        self.register_values[0][register as usize] = value;
         match register {
             0x22 => {
                 // LFO configuration register
@@ -522,6 +528,7 @@ impl Ym2612 {
         self.busy_cycles_remaining = WRITE_BUSY_CYCLES;
 
         let register = self.selected_register;
+        self.register_values[1][register as usize] = value;
         match register {
             0x30..=0x9F => {
                 self.write_operator_level_register(register, value, GROUP_2_BASE_CHANNEL);
@@ -533,6 +540,51 @@ impl Ym2612 {
         }
     }
 
+    /// Log writes that set every register to its current value, followed by the currently selected
+    /// register address.
+    ///
+    /// Frequency high bytes are written before the low bytes that latch them, and the key on/off
+    /// state of every channel is written last.
+    pub fn log_registers(&self, logger: &mut VgmLogger) {
+        const SYSTEM_REGISTERS: [u8; 7] = [0x22, 0x24, 0x25, 0x26, 0x27, 0x2B, 0x2A];
+
+        // Registers with the low 2 bits set to 3 do not map to a channel
+        let channel_registers = (0x30..=0x9F)
+            .chain(0xA4..=0xA6)
+            .chain(0xA0..=0xA2)
+            .chain(0xAC..=0xAE)
+            .chain(0xA8..=0xAA)
+            .chain(0xB0..=0xB6)
+            .filter(|register| register & 0x03 != 0x03);
+
+        for (port, values) in self.register_values.iter().enumerate() {
+            let system_registers: &[u8] = if port == 0 { &SYSTEM_REGISTERS } else { &[] };
+            for register in system_registers.iter().copied().chain(channel_registers.clone()) {
+                logger.log_ym2612_address_write(port as u8, register);
+                logger.log_ym2612_data_write(values[register as usize]);
+            }
+        }
+
+        logger.log_ym2612_address_write(0, 0x28);
+        for (i, channel) in self.channels.iter().enumerate() {
+            // Channels 4-6 are numbered 4-6 rather than 3-5 in key on/off writes
+            let channel_bits = if i < 3 { i as u8 } else { i as u8 + 1 };
+            let key_bits: u8 = channel
+                .operators
+                .iter()
+                .enumerate()
+                .map(|(j, operator)| u8::from(operator.envelope.is_key_on()) << (4 + j))
+                .sum();
+            logger.log_ym2612_data_write(key_bits | channel_bits);
+        }
+
+        let port = match self.selected_register_group {
+            RegisterGroup::One => 0,
+            RegisterGroup::Two => 1,
+        };
+        logger.log_ym2612_address_write(port, self.selected_register);
+    }
+
     #[allow(clippy::unused_self)]
     #[must_use]
     pub fn read_register(&self) -> u8 {
@@ -1,13 +1,16 @@
 use crate::apu::ApuState;
 use crate::audio::AudioResampler;
-use crate::bus::cartridge::CartridgeFileError;
+use crate::bus::cartridge::{CartridgeFileError, Mapper};
 use crate::bus::{cartridge, Bus};
 use crate::cpu::CpuState;
 use crate::graphics::TimingModeGraphicsExt;
-use crate::input::NesInputs;
+use crate::input::{NesInputs, NesJoypadState};
+use crate::nsf::NsfMetadata;
 use crate::ppu::PpuState;
 use crate::{apu, cpu, graphics, ppu};
 use bincode::{Decode, Encode};
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
     TickEffect, TickResult, TimingMode,
@@ -26,6 +29,8 @@ const PAL_MASTER_CLOCK_TICKS: u32 = 80;
 const PAL_CPU_DIVIDER: u32 = 16;
 const PAL_PPU_DIVIDER: u32 = 5;
 
+const DEBUG_CPUS: &[DebugCpu] = &[DebugCpu { name: "6502", arch: CpuArch::Mos6502 }];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NesAspectRatio {
@@ -73,7 +78,7 @@ impl Display for Overscan {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 pub struct NesEmulatorConfig {
     /// Force timing mode to NTSC/PAL if set
-    /// If None, timing mode will default based on iNES ROM header
+    /// If None, timing mode will default based on iNES ROM header or NSF region
     pub forced_timing_mode: Option<TimingMode>,
     /// Aspect ratio
     pub aspect_ratio: NesAspectRatio,
@@ -123,6 +128,11 @@ pub struct NesEmulator {
     // Kept around to enable hard reset
     #[partial_clone(default)]
     raw_rom_bytes: Vec<u8>,
+    #[partial_clone(default)]
+    raw_fds_bios: Option<Vec<u8>>,
+    // Used to detect NSF track select presses
+    prev_p1_inputs: NesJoypadState,
+    debug_hooks: DebugHooks,
 }
 
 impl NesEmulator {
@@ -132,13 +142,56 @@ impl NesEmulator {
     ///
     /// This function will return an error if it cannot successfully parse NES ROM data out of the
     /// given ROM bytes.
+    ///
+    /// NSF and NSFe files are also accepted, in which case the emulator plays the file's music.
+    /// While playing an NSF file, pressing left or right on the P1 controller selects the previous
+    /// or next track.
     pub fn create<S: SaveWriter>(
         rom_bytes: Vec<u8>,
         config: NesEmulatorConfig,
         save_writer: &mut S,
+    ) -> Result<Self, NesInitializationError> {
+        let mapper = if cartridge::is_nsf_file(&rom_bytes) {
+            cartridge::from_nsf_file(&rom_bytes, config.forced_timing_mode)?
+        } else {
+            let sav_bytes = save_writer.load_bytes("sav").ok();
+            cartridge::from_ines_file(&rom_bytes, sav_bytes, config.forced_timing_mode)?
+        };
+
+        Ok(Self::from_mapper(mapper, config, rom_bytes, None))
+    }
+
+    /// Create a new emulator instance that runs a Famicom Disk System game. `bios_rom` should
+    /// contain the 8KB FDS BIOS ROM and `disk_bytes` should contain an .fds disk image. Disk
+    /// writes are persisted using the given save writer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the BIOS ROM is the wrong size or if it cannot parse
+    /// an FDS disk image out of the given disk bytes.
+    pub fn create_fds<S: SaveWriter>(
+        bios_rom: Vec<u8>,
+        disk_bytes: Vec<u8>,
+        config: NesEmulatorConfig,
+        save_writer: &mut S,
     ) -> Result<Self, NesInitializationError> {
         let sav_bytes = save_writer.load_bytes("sav").ok();
-        let mapper = cartridge::from_ines_file(&rom_bytes, sav_bytes, config.forced_timing_mode)?;
+        let mapper = cartridge::from_fds_file(
+            bios_rom.clone(),
+            &disk_bytes,
+            sav_bytes,
+            config.forced_timing_mode,
+        )?;
+
+        Ok(Self::from_mapper(mapper, config, disk_bytes, Some(bios_rom)))
+    }
+
+    fn from_mapper(
+        mapper: Mapper,
+        config: NesEmulatorConfig,
+        raw_rom_bytes: Vec<u8>,
+        raw_fds_bios: Option<Vec<u8>>,
+    ) -> Self {
         let timing_mode = mapper.timing_mode();
 
         let mut bus = Bus::from_cartridge(mapper, config.overscan);
@@ -149,7 +202,7 @@ impl NesEmulator {
 
         init_apu(&mut apu_state, &mut bus, config);
 
-        Ok(Self {
+        Self {
             bus,
             cpu_state,
             ppu_state,
@@ -157,12 +210,20 @@ impl NesEmulator {
             config,
             rgba_frame_buffer: new_rgba_frame_buffer(),
             audio_resampler: AudioResampler::new(timing_mode, config.audio_refresh_rate_adjustment),
-            raw_rom_bytes: rom_bytes,
-        })
+            raw_rom_bytes,
+            raw_fds_bios,
+            prev_p1_inputs: NesJoypadState::default(),
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
+        }
     }
 
     fn ntsc_tick(&mut self) {
-        cpu::tick(&mut self.cpu_state, &mut self.bus.cpu(), self.apu_state.is_active_cycle());
+        cpu::tick(
+            &mut self.cpu_state,
+            &mut self.bus.cpu(),
+            self.apu_state.is_active_cycle(),
+            &mut self.debug_hooks,
+        );
         apu::tick(&mut self.apu_state, &mut self.bus.cpu(), self.config);
         ppu::tick(&mut self.ppu_state, &mut self.bus.ppu(), self.config);
         self.bus.tick_cpu();
@@ -181,7 +242,12 @@ impl NesEmulator {
 
     fn pal_tick(&mut self) {
         // Both CPU and PPU tick on the first master clock cycle
-        cpu::tick(&mut self.cpu_state, &mut self.bus.cpu(), self.apu_state.is_active_cycle());
+        cpu::tick(
+            &mut self.cpu_state,
+            &mut self.bus.cpu(),
+            self.apu_state.is_active_cycle(),
+            &mut self.debug_hooks,
+        );
         apu::tick(&mut self.apu_state, &mut self.bus.cpu(), self.config);
         ppu::tick(&mut self.ppu_state, &mut self.bus.ppu(), self.config);
         self.bus.tick_cpu();
@@ -197,6 +263,7 @@ impl NesEmulator {
                     &mut self.cpu_state,
                     &mut self.bus.cpu(),
                     self.apu_state.is_active_cycle(),
+                    &mut self.debug_hooks,
                 );
                 apu::tick(&mut self.apu_state, &mut self.bus.cpu(), self.config);
                 self.bus.tick_cpu();
@@ -269,6 +336,61 @@ impl NesEmulator {
     pub fn using_double_height_sprites(&mut self) -> bool {
         self.bus.ppu().get_ppu_registers().double_height_sprites()
     }
+
+    /// Retrieve the metadata of the NSF file that is playing, if an NSF file was loaded.
+    #[must_use]
+    pub fn nsf_metadata(&self) -> Option<&NsfMetadata> {
+        self.bus.mapper().nsf_metadata()
+    }
+
+    /// Retrieve the 0-based index of the NSF track that is playing, if an NSF file was loaded.
+    #[must_use]
+    pub fn nsf_track(&self) -> Option<u8> {
+        self.bus.mapper().nsf_track()
+    }
+
+    /// Start playing the given 0-based NSF track from the beginning. Does nothing if an NSF file
+    /// was not loaded or if the track index is out of range.
+    pub fn select_nsf_track(&mut self, track: u8) {
+        let Some(metadata) = self.nsf_metadata() else { return };
+        if track >= metadata.total_tracks {
+            return;
+        }
+
+        log::info!("Playing NSF track {} of {}", track + 1, metadata.total_tracks);
+
+        self.bus.mapper_mut().set_nsf_track(track);
+        self.soft_reset();
+    }
+
+    /// Eject the current Famicom Disk System disk side and insert the next one, cycling through
+    /// all sides of all disks in order. The new side is inserted after a short delay so that the
+    /// BIOS notices the disk change.
+    ///
+    /// Returns false and does nothing if this is not an FDS game.
+    pub fn switch_fds_disk_side(&mut self) -> bool {
+        self.bus.mapper_mut().switch_fds_disk_side()
+    }
+
+    /// Replace the active cheats. Use [`cheats::decode_cheat`](crate::cheats::decode_cheat) to
+    /// decode cheat codes.
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.bus.set_cheats(cheats);
+    }
+
+    fn handle_nsf_inputs(&mut self, p1_inputs: NesJoypadState) {
+        let prev_inputs = mem::replace(&mut self.prev_p1_inputs, p1_inputs);
+        let (Some(track), Some(metadata)) = (self.nsf_track(), self.nsf_metadata()) else {
+            return;
+        };
+        let total_tracks = metadata.total_tracks;
+
+        if p1_inputs.right && !prev_inputs.right {
+            self.select_nsf_track((track + 1) % total_tracks);
+        } else if p1_inputs.left && !prev_inputs.left {
+            self.select_nsf_track(track.checked_sub(1).unwrap_or(total_tracks - 1));
+        }
+    }
 }
 
 fn new_rgba_frame_buffer() -> Vec<Color> {
@@ -305,6 +427,12 @@ impl EmulatorTrait for NesEmulator {
         S: SaveWriter,
         S::Err: Debug + Display + Send + Sync + 'static,
     {
+        if self.debug_hooks.stop_event().is_some() {
+            // Stopped in the debugger. The stop can land partway through a master clock tick, in
+            // which case the other components may run a few cycles past the CPU before halting
+            return Ok(TickEffect::None);
+        }
+
         let prev_in_vblank = self.ppu_state.in_vblank();
 
         self.bus.update_p1_joypad_state(inputs.p1, self.config.allow_opposing_joypad_inputs);
@@ -331,6 +459,10 @@ impl EmulatorTrait for NesEmulator {
                 save_writer.persist_bytes("sav", sram).map_err(NesError::SaveWrite)?;
             }
 
+            self.handle_nsf_inputs(inputs.p1);
+
+            self.bus.apply_ram_cheats();
+
             return Ok(TickEffect::FrameRendered);
         }
 
@@ -355,6 +487,9 @@ impl EmulatorTrait for NesEmulator {
     fn take_rom_from(&mut self, other: &mut Self) {
         self.bus.move_rom_from(&mut other.bus);
         self.raw_rom_bytes = mem::take(&mut other.raw_rom_bytes);
+        self.raw_fds_bios = other.raw_fds_bios.take();
+        self.bus.set_cheats(other.bus.take_cheats());
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
     }
 
     fn soft_reset(&mut self) {
@@ -370,9 +505,16 @@ impl EmulatorTrait for NesEmulator {
 
     fn hard_reset<S: SaveWriter>(&mut self, save_writer: &mut S) {
         let rom_bytes = mem::take(&mut self.raw_rom_bytes);
+        let cheats = self.bus.take_cheats();
+        let debug_hooks = mem::take(&mut self.debug_hooks);
 
-        *self = Self::create(rom_bytes, self.config, save_writer)
-            .expect("Creation during hard reset should never fail");
+        *self = match self.raw_fds_bios.take() {
+            Some(fds_bios) => Self::create_fds(fds_bios, rom_bytes, self.config, save_writer),
+            None => Self::create(rom_bytes, self.config, save_writer),
+        }
+        .expect("Creation during hard reset should never fail");
+        self.bus.set_cheats(cheats);
+        self.debug_hooks = debug_hooks;
     }
 
     fn timing_mode(&self) -> TimingMode {
@@ -380,6 +522,44 @@ impl EmulatorTrait for NesEmulator {
     }
 }
 
+impl Debuggable for NesEmulator {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, _cpu: usize) -> Vec<u32> {
+        self.cpu_state.mos6502().debug_registers().map(u32::from).to_vec()
+    }
+
+    fn write_registers(&mut self, _cpu: usize, registers: &[u32]) {
+        if let Ok(registers) = <[u32; 6]>::try_from(registers) {
+            self.cpu_state.mos6502_mut().set_debug_registers(registers.map(|value| value as u16));
+        }
+    }
+
+    fn read_memory(&mut self, _cpu: usize, address: u32) -> u8 {
+        self.bus.peek_cpu_address(address as u16)
+    }
+
+    fn write_memory(&mut self, _cpu: usize, address: u32, value: u8) {
+        self.bus.poke_cpu_address(address as u16, value);
+    }
+
+    fn disassemble(&mut self, _cpu: usize, address: u32) -> (String, u32) {
+        let (text, len) =
+            mos6502_emu::disassemble::disassemble(&mut self.bus.cpu(), address as u16);
+        (text, len.into())
+    }
+}
+
 fn init_apu(apu_state: &mut ApuState, bus: &mut Bus, config: NesEmulatorConfig) {
     // Write 0x00 to JOY2 to reset the frame counter
     bus.cpu().write(0x4017, 0x00);
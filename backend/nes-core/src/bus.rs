@@ -40,11 +40,12 @@ use crate::bus::cartridge::Mapper;
 use crate::graphics::TimingModeGraphicsExt;
 use crate::input::{LatchedJoypadState, NesInputDevice, NesJoypadState, ZapperState};
 use bincode::{Decode, Encode};
+use jgenesis_common::cheats::CheatSet;
 use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::PartialClone;
 use mos6502_emu::bus::BusInterface;
-use std::array;
+use std::{array, mem};
 
 pub const CPU_RAM_START: u16 = 0x0000;
 pub const CPU_RAM_END: u16 = 0x1FFF;
@@ -730,6 +731,7 @@ pub struct Bus {
     ppu_bus_address: u16,
     interrupt_lines: InterruptLines,
     pending_write: Option<PendingCpuWrite>,
+    cheats: CheatSet,
 }
 
 impl Bus {
@@ -746,6 +748,7 @@ impl Bus {
             ppu_bus_address: 0,
             interrupt_lines: InterruptLines::new(),
             pending_write: None,
+            cheats: CheatSet::new(),
         }
     }
 
@@ -837,6 +840,50 @@ impl Bus {
     pub(crate) fn reload_config(&mut self, config: NesEmulatorConfig) {
         self.io_registers.overscan = config.overscan;
     }
+
+    pub(crate) fn set_cheats(&mut self, cheats: CheatSet) {
+        self.cheats = cheats;
+    }
+
+    pub(crate) fn take_cheats(&mut self) -> CheatSet {
+        mem::take(&mut self.cheats)
+    }
+
+    /// Read a value from the CPU address space without side effects, for use by debuggers. PPU and
+    /// I/O registers read as open bus.
+    pub(crate) fn peek_cpu_address(&mut self, address: u16) -> u8 {
+        match address {
+            CPU_RAM_START..=CPU_RAM_END => self.cpu_internal_ram[(address & CPU_RAM_MASK) as usize],
+            CPU_PPU_REGISTERS_START..=CPU_IO_TEST_MODE_END => cpu_open_bus(address),
+            CPU_CARTRIDGE_START..=CPU_CARTRIDGE_END => {
+                let value = self.mapper.peek_cpu_address(address);
+                self.cheats.apply_rom_read(address.into(), value)
+            }
+        }
+    }
+
+    /// Write a value to the CPU address space immediately rather than on the next CPU cycle, for
+    /// use by debuggers.
+    pub(crate) fn poke_cpu_address(&mut self, address: u16, value: u8) {
+        self.cpu().apply_write(address, value);
+    }
+
+    // Write RAM cheat values; this should be called once per frame.
+    //
+    // Only internal RAM and PRG RAM are patched, and the values are written directly rather than
+    // through the CPU bus so that a bad code can't trigger register side effects every frame
+    pub(crate) fn apply_ram_cheats(&mut self) {
+        for patch in self.cheats.ram_patches() {
+            match patch.address {
+                0x0000..=0x1FFF => {
+                    let ram_address = (patch.address as u16) & CPU_RAM_MASK;
+                    self.cpu_internal_ram[ram_address as usize] = patch.value;
+                }
+                0x6000..=0x7FFF => self.mapper.poke_prg_ram(patch.address as u16, patch.value),
+                _ => {}
+            }
+        }
+    }
 }
 
 /// A view of the bus containing methods that are appropriate for use by the CPU and APU.
@@ -860,7 +907,8 @@ impl<'a> BusInterface for CpuBus<'a> {
             }
             _address @ CPU_IO_TEST_MODE_START..=CPU_IO_TEST_MODE_END => cpu_open_bus(address),
             address @ CPU_CARTRIDGE_START..=CPU_CARTRIDGE_END => {
-                self.0.mapper.read_cpu_address(address)
+                let value = self.0.mapper.read_cpu_address(address);
+                self.0.cheats.apply_rom_read(address.into(), value)
             }
         }
     }
@@ -1145,6 +1193,22 @@ mod tests {
 
         assert_ne!(bus1.cpu_internal_ram, bus2.cpu_internal_ram);
     }
+
+    #[test]
+    fn debugger_peek_and_poke() {
+        let mapper = cartridge::new_mmc1(vec![0; 32768]);
+        let mut bus = Bus::from_cartridge(mapper, Overscan::default());
+
+        // Debugger writes apply immediately, so several can happen within the same CPU cycle
+        bus.poke_cpu_address(0x0010, 0x12);
+        bus.poke_cpu_address(0x0811, 0x34);
+        assert_eq!(bus.peek_cpu_address(0x0810), 0x12);
+        assert_eq!(bus.peek_cpu_address(0x0011), 0x34);
+
+        // Peeking PPU registers should not trigger read side effects
+        assert_eq!(bus.peek_cpu_address(0x2002), 0x20);
+        assert!(!bus.ppu_registers.ppu_status_read);
+    }
 }
 
 pub(crate) fn cpu_open_bus(address: u16) -> u8 {
@@ -1,9 +1,12 @@
 mod mappers;
 
+use crate::bus;
 use crate::bus::cartridge::mappers::{
-    Action52, Axrom, BandaiFcg, Bnrom, ChrType, Cnrom, Gxrom, Mmc1, Mmc2, Mmc3, Mmc5, Namco163,
-    Namco175, NametableMirroring, Nrom, PpuMapResult, Sunsoft, Uxrom, Vrc4, Vrc6, Vrc7,
+    Action52, Axrom, BandaiFcg, Bnrom, ChrType, Cnrom, Fds, Gxrom, Mmc1, Mmc2, Mmc3, Mmc5,
+    Namco163, Namco175, NametableMirroring, Nrom, Nsf, PpuMapResult, Sunsoft, Uxrom, Vrc4, Vrc6,
+    Vrc7,
 };
+use crate::nsf::{NsfError, NsfFile, NsfMetadata};
 use bincode::de::{BorrowDecoder, Decoder};
 use bincode::enc::Encoder;
 use bincode::error::{DecodeError, EncodeError};
@@ -166,6 +169,7 @@ pub(crate) enum Mapper {
     BandaiFcg(#[partial_clone(partial)] MapperImpl<BandaiFcg>),
     Bnrom(#[partial_clone(partial)] MapperImpl<Bnrom>),
     Cnrom(#[partial_clone(partial)] MapperImpl<Cnrom>),
+    Fds(#[partial_clone(partial)] MapperImpl<Fds>),
     Gxrom(#[partial_clone(partial)] MapperImpl<Gxrom>),
     Mmc1(#[partial_clone(partial)] MapperImpl<Mmc1>),
     Mmc2(#[partial_clone(partial)] MapperImpl<Mmc2>),
@@ -174,6 +178,7 @@ pub(crate) enum Mapper {
     Namco163(#[partial_clone(partial)] MapperImpl<Namco163>),
     Namco175(#[partial_clone(partial)] MapperImpl<Namco175>),
     Nrom(#[partial_clone(partial)] MapperImpl<Nrom>),
+    Nsf(#[partial_clone(partial)] MapperImpl<Nsf>),
     Sunsoft(#[partial_clone(partial)] MapperImpl<Sunsoft>),
     Uxrom(#[partial_clone(partial)] MapperImpl<Uxrom>),
     Vrc4(#[partial_clone(partial)] MapperImpl<Vrc4>),
@@ -190,6 +195,7 @@ impl Mapper {
             Self::BandaiFcg(bandai_fcg) => bandai_fcg.name(),
             Self::Bnrom(..) => "BNROM / NINA-001",
             Self::Cnrom(..) => "CNROM",
+            Self::Fds(..) => "Famicom Disk System",
             Self::Gxrom(gxrom) => gxrom.name(),
             Self::Mmc1(..) => "MMC1",
             Self::Mmc2(mmc2) => mmc2.name(),
@@ -198,6 +204,7 @@ impl Mapper {
             Self::Namco163(..) => "Namco 163",
             Self::Namco175(..) => "Namco 175",
             Self::Nrom(..) => "NROM",
+            Self::Nsf(..) => "NSF",
             Self::Sunsoft(..) => "Sunsoft",
             Self::Uxrom(uxrom) => uxrom.name(),
             Self::Vrc4(vrc4) => vrc4.name(),
@@ -211,6 +218,18 @@ impl Mapper {
         match_each_variant!(self, mapper => mapper.read_cpu_address(address))
     }
 
+    /// Read a value from the given address in the CPU address space without side effects, for use
+    /// by debuggers. Mapper registers that change state when read report open bus.
+    pub(crate) fn peek_cpu_address(&mut self, address: u16) -> u8 {
+        match self {
+            // These boards have registers in $4020-$5FFF that change state when read
+            Self::Fds(..) | Self::Namco163(..) if address < 0x6000 => bus::cpu_open_bus(address),
+            Self::Mmc5(mmc5) => mmc5.peek_cpu_address(address),
+            Self::Nsf(nsf) => nsf.peek_cpu_address(address),
+            _ => self.read_cpu_address(address),
+        }
+    }
+
     /// Write a value to the given address in the CPU address space.
     pub(crate) fn write_cpu_address(&mut self, address: u16, value: u8) {
         match_each_variant!(self, mapper => mapper.write_cpu_address(address, value));
@@ -240,6 +259,9 @@ impl Mapper {
             Self::BandaiFcg(bandai_fcg) => {
                 bandai_fcg.tick_cpu();
             }
+            Self::Fds(fds) => {
+                fds.tick_cpu();
+            }
             Self::Mmc1(mmc1) => {
                 mmc1.tick_cpu();
             }
@@ -249,6 +271,9 @@ impl Mapper {
             Self::Namco163(namco163) => {
                 namco163.tick_cpu();
             }
+            Self::Nsf(nsf) => {
+                nsf.tick_cpu();
+            }
             Self::Sunsoft(sunsoft) => {
                 sunsoft.tick_cpu();
             }
@@ -269,6 +294,7 @@ impl Mapper {
     pub(crate) fn interrupt_flag(&self) -> bool {
         match self {
             Self::BandaiFcg(bandai_fcg) => bandai_fcg.interrupt_flag(),
+            Self::Fds(fds) => fds.interrupt_flag(),
             Self::Mmc3(mmc3) => mmc3.interrupt_flag(),
             Self::Mmc5(mmc5) => mmc5.interrupt_flag(),
             Self::Namco163(namco163) => namco163.interrupt_flag(),
@@ -314,6 +340,9 @@ impl Mapper {
                     return true;
                 }
             }
+            Mapper::Fds(mapper) => {
+                return mapper.get_and_clear_disk_dirty_bit();
+            }
             Mapper::Namco163(mapper) => {
                 if mapper.has_battery_backed_internal_ram()
                     && mapper.get_and_clear_internal_ram_dirty_bit()
@@ -332,7 +361,7 @@ impl Mapper {
     }
 
     /// Return the board's writable memory as a slice. This will be an empty slice if the board
-    /// has no PRG RAM or EEPROM.
+    /// has no PRG RAM or EEPROM. For the FDS this is the contents of the disk rather than PRG RAM.
     pub(crate) fn get_prg_ram(&self) -> &[u8] {
         match self {
             Mapper::BandaiFcg(mapper) => {
@@ -340,6 +369,9 @@ impl Mapper {
                     return eeprom;
                 }
             }
+            Mapper::Fds(mapper) => {
+                return mapper.disk();
+            }
             Mapper::Namco163(mapper) => {
                 if mapper.has_battery_backed_internal_ram() {
                     return mapper.get_internal_ram();
@@ -351,6 +383,19 @@ impl Mapper {
         match_each_variant!(self, mapper => &mapper.cartridge.prg_ram)
     }
 
+    /// Write a value directly to the PRG RAM mapped at the given CPU address in \$6000-\$7FFF,
+    /// bypassing the board's RAM banking and write protection. Used for RAM cheats; does nothing if
+    /// the board has no PRG RAM.
+    pub(crate) fn poke_prg_ram(&mut self, address: u16, value: u8) {
+        match_each_variant!(self, mapper => {
+            let prg_ram = &mut mapper.cartridge.prg_ram;
+            if !prg_ram.is_empty() {
+                let prg_ram_len = prg_ram.len();
+                prg_ram[usize::from(address - 0x6000) & (prg_ram_len - 1)] = value;
+            }
+        });
+    }
+
     /// Retrieve the timing mode of the cartridge (NTSC/PAL).
     pub(crate) fn timing_mode(&self) -> TimingMode {
         match_each_variant!(self, mapper => mapper.cartridge.timing_mode)
@@ -363,8 +408,10 @@ impl Mapper {
     /// return the mixed APU sample as-is.
     pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
         match self {
+            Self::Fds(fds) => fds.sample_audio(mixed_apu_sample),
             Self::Mmc5(mmc5) => mmc5.sample_audio(mixed_apu_sample),
             Self::Namco163(namco163) => namco163.sample_audio(mixed_apu_sample),
+            Self::Nsf(nsf) => nsf.sample_audio(mixed_apu_sample),
             Self::Sunsoft(sunsoft) => sunsoft.sample_audio(mixed_apu_sample),
             Self::Vrc6(vrc6) => vrc6.sample_audio(mixed_apu_sample),
             Self::Vrc7(vrc7) => vrc7.sample_audio(mixed_apu_sample),
@@ -379,8 +426,50 @@ impl Mapper {
     }
 
     pub(crate) fn reset(&mut self) {
-        if let Self::Action52(action52) = self {
-            action52.reset();
+        match self {
+            Self::Action52(action52) => {
+                action52.reset();
+            }
+            Self::Nsf(nsf) => {
+                nsf.reset();
+            }
+            _ => {}
+        }
+    }
+
+    /// Retrieve NSF file metadata. Returns None if this is not an NSF board.
+    pub(crate) fn nsf_metadata(&self) -> Option<&NsfMetadata> {
+        match self {
+            Self::Nsf(nsf) => Some(nsf.metadata()),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the 0-based index of the NSF track that is playing. Returns None if this is not an
+    /// NSF board.
+    pub(crate) fn nsf_track(&self) -> Option<u8> {
+        match self {
+            Self::Nsf(nsf) => Some(nsf.track()),
+            _ => None,
+        }
+    }
+
+    /// Eject the current FDS disk side and insert the next one. Returns false if this is not an FDS
+    /// board.
+    pub(crate) fn switch_fds_disk_side(&mut self) -> bool {
+        match self {
+            Self::Fds(fds) => {
+                fds.switch_disk_side();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Select the NSF track to play. The new track starts playing after the next reset.
+    pub(crate) fn set_nsf_track(&mut self, track: u8) {
+        if let Self::Nsf(nsf) = self {
+            nsf.set_track(track);
         }
     }
 }
@@ -404,6 +493,16 @@ pub enum CartridgeFileError {
     InvalidRomSize { file_size: u32, prg_rom_size: u32, chr_rom_size: u32 },
     #[error("unsupported timing mode byte: {byte}")]
     UnsupportedTimingMode { byte: u8 },
+    #[error("invalid NSF file: {0}")]
+    Nsf(#[from] NsfError),
+    #[error(
+        "FDS disk image size must be a multiple of 65500 bytes, optionally with a 16-byte header; was {file_size} bytes"
+    )]
+    FdsImageSize { file_size: usize },
+    #[error("FDS disk side {side} does not begin with a disk info block")]
+    FdsMissingDiskInfo { side: usize },
+    #[error("FDS BIOS ROM must be 8192 bytes, was {bios_size} bytes")]
+    FdsBiosSize { bios_size: usize },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -742,3 +841,104 @@ pub(crate) fn from_ines_file(
 
     Ok(mapper)
 }
+
+/// Build a Famicom Disk System board from a BIOS ROM and an .fds disk image. Disk contents are
+/// restored from the save file if one exists.
+///
+/// # Errors
+///
+/// This function will return an error if the BIOS is the wrong size or if the disk image is not a
+/// valid .fds file.
+pub(crate) fn from_fds_file(
+    bios_rom: Vec<u8>,
+    file_bytes: &[u8],
+    sav_bytes: Option<Vec<u8>>,
+    forced_timing_mode: Option<TimingMode>,
+) -> Result<Mapper, CartridgeFileError> {
+    if bios_rom.len() != mappers::FDS_BIOS_LEN {
+        return Err(CartridgeFileError::FdsBiosSize { bios_size: bios_rom.len() });
+    }
+
+    let (mut disk, side_len, side_count) = mappers::parse_fds_disk_image(file_bytes)?;
+    if let Some(sav_bytes) = sav_bytes {
+        if sav_bytes.len() == disk.len() {
+            log::info!("Loaded FDS disk contents from save file");
+            disk = sav_bytes;
+        } else {
+            // Saves contain the raw disk bytestream, so a save from a different disk image or with
+            // a different raw side layout cannot be loaded
+            log::warn!(
+                "Ignoring FDS save file of {} bytes; expected {} bytes for {side_count} disk sides",
+                sav_bytes.len(),
+                disk.len()
+            );
+        }
+    }
+
+    // The FDS was only released in Japan
+    let timing_mode = forced_timing_mode.unwrap_or(TimingMode::Ntsc);
+
+    let cartridge = Cartridge {
+        timing_mode,
+        prg_rom: bios_rom,
+        prg_ram: vec![0; mappers::FDS_PRG_RAM_LEN],
+        has_ram_battery: false,
+        prg_ram_dirty_bit: false,
+        chr_rom: vec![],
+        chr_ram: vec![0; 8 * 1024],
+    };
+
+    log::info!("FDS disk sides: {side_count}");
+    log::info!("Timing mode: {timing_mode}");
+
+    let data = Fds::new(disk, side_len, side_count);
+    Ok(Mapper::Fds(MapperImpl { cartridge, data }))
+}
+
+/// Build a synthetic cartridge around the contents of an NSF or NSFe file.
+///
+/// # Errors
+///
+/// This function will return an error if the given bytes are not a valid NSF or NSFe file.
+pub(crate) fn from_nsf_file(
+    file_bytes: &[u8],
+    forced_timing_mode: Option<TimingMode>,
+) -> Result<Mapper, CartridgeFileError> {
+    let nsf = NsfFile::parse(file_bytes)?;
+    let (prg_rom, initial_banks) = mappers::nsf_prg_rom(&nsf);
+
+    let timing_mode = forced_timing_mode.unwrap_or(nsf.timing_mode());
+
+    let cartridge = Cartridge {
+        timing_mode,
+        prg_rom,
+        prg_ram: vec![0; 8 * 1024],
+        has_ram_battery: false,
+        prg_ram_dirty_bit: false,
+        chr_rom: vec![],
+        chr_ram: vec![0; 8 * 1024],
+    };
+
+    let metadata = &nsf.metadata;
+    log::info!("NSF title: {}", metadata.title);
+    log::info!("NSF artist: {}", metadata.artist);
+    log::info!("NSF copyright: {}", metadata.copyright);
+    log::info!("NSF track count: {}", metadata.total_tracks);
+    log::info!(
+        "NSF load/init/play addresses: ${:04X} / ${:04X} / ${:04X}",
+        nsf.load_address,
+        nsf.init_address,
+        nsf.play_address
+    );
+    log::info!("NSF uses bank switching: {}", nsf.initial_banks.is_some());
+    log::info!("NSF expansion audio: {:?}", metadata.expansion_audio.chip_names());
+    log::info!("Timing mode: {timing_mode}");
+
+    let data = Nsf::new(&nsf, initial_banks, timing_mode);
+    Ok(Mapper::Nsf(MapperImpl { cartridge, data }))
+}
+
+/// Returns whether the given file appears to be an NSF or NSFe file rather than a ROM image.
+pub(crate) fn is_nsf_file(file_bytes: &[u8]) -> bool {
+    NsfFile::is_nsf(file_bytes)
+}
@@ -1,5 +1,6 @@
 mod action52;
 mod bandai;
+mod fds;
 mod konami;
 mod mmc1;
 mod mmc2;
@@ -8,6 +9,7 @@ mod mmc5;
 mod namco163;
 mod namco175;
 mod nrom;
+mod nsf;
 mod sunsoft;
 
 use crate::bus::cartridge::Cartridge;
@@ -16,15 +18,17 @@ use bincode::{Decode, Encode};
 use crate::bus;
 pub(crate) use action52::Action52;
 pub(crate) use bandai::BandaiFcg;
-pub(crate) use konami::{Vrc4, Vrc6, Vrc7};
+pub(crate) use fds::{FDS_BIOS_LEN, FDS_PRG_RAM_LEN, Fds, FdsAudioUnit, parse_fds_disk_image};
+pub(crate) use konami::{Vrc4, Vrc6, Vrc6AudioUnit, Vrc7, Vrc7AudioUnit};
 pub(crate) use mmc1::Mmc1;
 pub(crate) use mmc2::Mmc2;
 pub(crate) use mmc3::Mmc3;
-pub(crate) use mmc5::Mmc5;
-pub(crate) use namco163::Namco163;
+pub(crate) use mmc5::{Mmc5, Mmc5AudioUnit, MultiplierUnit};
+pub(crate) use namco163::{Namco163, Namco163AudioUnit};
 pub(crate) use namco175::Namco175;
 pub(crate) use nrom::{Axrom, Bnrom, Cnrom, Gxrom, Nrom, Uxrom};
-pub(crate) use sunsoft::Sunsoft;
+pub(crate) use nsf::{Nsf, nsf_prg_rom};
+pub(crate) use sunsoft::{Sunsoft, Sunsoft5bAudioUnit};
 
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
@@ -0,0 +1,576 @@
+//! Code for the Famicom Disk System.
+//!
+//! The BIOS is mapped as PRG ROM at $E000-$FFFF and the RAM adapter's 32KB of PRG RAM is mapped at
+//! $6000-$DFFF. Disk images are converted from the .fds format to a raw bytestream that includes
+//! the gaps, start marks, and CRCs that the BIOS expects to see while the drive is scanning.
+
+mod audio;
+
+use crate::bus;
+use crate::bus::cartridge::mappers::{NametableMirroring, PpuMapResult};
+use crate::bus::cartridge::{CartridgeFileError, HasBasicPpuMapping, MapperImpl};
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+pub(crate) use audio::FdsAudioUnit;
+
+pub(crate) const FDS_BIOS_LEN: usize = 8 * 1024;
+pub(crate) const FDS_PRG_RAM_LEN: usize = 32 * 1024;
+
+const FDS_DISK_SIDE_LEN: usize = 65500;
+const FWNES_HEADER_LEN: usize = 16;
+const FWNES_HEADER_MAGIC: [u8; 4] = *b"FDS\x1A";
+const DISK_INFO_BLOCK_MAGIC: &[u8] = b"\x01*NINTENDO-HVC*";
+
+// Gap lengths in bits, from https://www.nesdev.org/wiki/FDS_disk_format
+const LEADING_GAP_BITS: usize = 28300;
+const BLOCK_GAP_BITS: usize = 976;
+const GAP_END_MARK: u8 = 0x80;
+
+// Large enough to hold a full side of data plus gaps; raw sides are padded to at least this length
+// so that games can append files to the end of the disk
+const MIN_RAW_SIDE_LEN: usize = 80000;
+
+// Roughly how long it takes the drive to start reading after the motor turns on, and how long each
+// byte takes to pass under the head at ~96.4 kbit/s
+const HEAD_RESET_CYCLES: u32 = 50000;
+const BYTE_TRANSFER_CYCLES: u32 = 150;
+
+// How long a disk remains ejected when switching sides, long enough for the BIOS to notice
+const DISK_SWAP_CYCLES: u32 = 1_800_000;
+
+fn update_crc(crc: u16, value: u8) -> u16 {
+    let mut crc = crc;
+    for i in 0..8 {
+        let carry = crc.bit(0);
+        crc >>= 1;
+        if carry {
+            crc ^= 0x8408;
+        }
+        if value.bit(i) {
+            crc ^= 0x8000;
+        }
+    }
+    crc
+}
+
+fn block_crc(block: &[u8]) -> u16 {
+    let crc = [GAP_END_MARK].iter().chain(block).fold(0, |crc, &byte| update_crc(crc, byte));
+    update_crc(update_crc(crc, 0), 0)
+}
+
+fn build_raw_disk_side(side: &[u8]) -> Vec<u8> {
+    let mut raw_side = vec![0; LEADING_GAP_BITS / 8];
+
+    let mut i = 0;
+    while i < side.len() {
+        let block_len = match side[i] {
+            // Disk info block
+            1 => 56,
+            // File amount block
+            2 => 2,
+            // File header block
+            3 => 16,
+            // File data block; length comes from the preceding file header block
+            4 if i >= 3 => 1 + usize::from(u16::from_le_bytes([side[i - 3], side[i - 2]])),
+            // End of data
+            _ => break,
+        };
+
+        let block = &side[i..(i + block_len).min(side.len())];
+        let crc = block_crc(block);
+
+        raw_side.push(GAP_END_MARK);
+        raw_side.extend(block);
+        raw_side.extend(crc.to_le_bytes());
+        raw_side.extend([0; BLOCK_GAP_BITS / 8]);
+
+        i += block_len;
+    }
+
+    raw_side
+}
+
+/// Parse the disk sides out of an .fds file, with or without the 16-byte fwNES header, and convert
+/// them to raw bytestreams. Returns the raw disk contents, the length of each raw side, and the
+/// number of sides.
+pub(crate) fn parse_fds_disk_image(
+    file_bytes: &[u8],
+) -> Result<(Vec<u8>, usize, usize), CartridgeFileError> {
+    let data = if file_bytes.starts_with(&FWNES_HEADER_MAGIC) {
+        &file_bytes[FWNES_HEADER_LEN..]
+    } else {
+        file_bytes
+    };
+
+    if data.is_empty() || data.len() % FDS_DISK_SIDE_LEN != 0 {
+        return Err(CartridgeFileError::FdsImageSize { file_size: file_bytes.len() });
+    }
+
+    let raw_sides: Vec<_> = data
+        .chunks_exact(FDS_DISK_SIDE_LEN)
+        .enumerate()
+        .map(|(side, side_bytes)| {
+            if !side_bytes.starts_with(DISK_INFO_BLOCK_MAGIC) {
+                return Err(CartridgeFileError::FdsMissingDiskInfo { side });
+            }
+
+            Ok(build_raw_disk_side(side_bytes))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let side_count = raw_sides.len();
+    let side_len = raw_sides.iter().map(Vec::len).max().unwrap_or(0).max(MIN_RAW_SIDE_LEN);
+
+    let mut disk = Vec::with_capacity(side_count * side_len);
+    for mut raw_side in raw_sides {
+        raw_side.resize(side_len, 0);
+        disk.extend(raw_side);
+    }
+
+    Ok((disk, side_len, side_count))
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct DiskDrive {
+    disk: Vec<u8>,
+    side_len: usize,
+    side_count: usize,
+    current_side: Option<usize>,
+    next_side: usize,
+    swap_delay: u32,
+    disk_dirty: bool,
+    motor_on: bool,
+    transfer_reset: bool,
+    read_mode: bool,
+    crc_control: bool,
+    prev_crc_control: bool,
+    transfer_enabled: bool,
+    transfer_irq_enabled: bool,
+    scanning: bool,
+    end_of_head: bool,
+    gap_ended: bool,
+    position: usize,
+    delay: u32,
+    read_data: u8,
+    write_data: u8,
+    transfer_complete: bool,
+    crc: u16,
+    irq_pending: bool,
+}
+
+impl DiskDrive {
+    fn new(disk: Vec<u8>, side_len: usize, side_count: usize) -> Self {
+        Self {
+            disk,
+            side_len,
+            side_count,
+            current_side: Some(0),
+            next_side: 0,
+            swap_delay: 0,
+            disk_dirty: false,
+            motor_on: false,
+            transfer_reset: false,
+            read_mode: true,
+            crc_control: false,
+            prev_crc_control: false,
+            transfer_enabled: false,
+            transfer_irq_enabled: false,
+            scanning: false,
+            end_of_head: true,
+            gap_ended: false,
+            position: 0,
+            delay: 0,
+            read_data: 0,
+            write_data: 0,
+            transfer_complete: false,
+            crc: 0,
+            irq_pending: false,
+        }
+    }
+
+    fn switch_side(&mut self) {
+        let next_side = match self.current_side {
+            Some(side) => (side + 1) % self.side_count,
+            None => (self.next_side + 1) % self.side_count,
+        };
+
+        self.current_side = None;
+        self.next_side = next_side;
+        self.swap_delay = DISK_SWAP_CYCLES;
+
+        log::info!(
+            "Ejected FDS disk; inserting disk {} side {} next",
+            next_side / 2 + 1,
+            if next_side % 2 == 0 { 'A' } else { 'B' }
+        );
+    }
+
+    fn handle_control_write(&mut self, value: u8) {
+        self.motor_on = value.bit(0);
+        self.transfer_reset = value.bit(1);
+        self.read_mode = value.bit(2);
+        self.crc_control = value.bit(4);
+        self.transfer_enabled = value.bit(6);
+        self.transfer_irq_enabled = value.bit(7);
+
+        self.irq_pending = false;
+    }
+
+    fn tick_cpu(&mut self) {
+        if self.swap_delay != 0 {
+            self.swap_delay -= 1;
+            if self.swap_delay == 0 {
+                self.current_side = Some(self.next_side);
+            }
+        }
+
+        let Some(side) = self.current_side else {
+            self.end_of_head = true;
+            self.scanning = false;
+            return;
+        };
+
+        if !self.motor_on {
+            self.end_of_head = true;
+            self.scanning = false;
+            return;
+        }
+
+        if self.transfer_reset && !self.scanning {
+            return;
+        }
+
+        if self.end_of_head {
+            self.end_of_head = false;
+            self.delay = HEAD_RESET_CYCLES;
+            self.position = 0;
+            self.gap_ended = false;
+            return;
+        }
+
+        if self.delay != 0 {
+            self.delay -= 1;
+            return;
+        }
+
+        self.scanning = true;
+
+        let disk_addr = side * self.side_len + self.position;
+        if self.read_mode {
+            self.read_byte(self.disk[disk_addr]);
+        } else {
+            let byte = self.next_write_byte();
+            if self.disk[disk_addr] != byte {
+                self.disk[disk_addr] = byte;
+                self.disk_dirty = true;
+            }
+        }
+
+        self.prev_crc_control = self.crc_control;
+
+        self.position += 1;
+        if self.position >= self.side_len {
+            self.end_of_head = true;
+        } else {
+            self.delay = BYTE_TRANSFER_CYCLES;
+        }
+    }
+
+    fn read_byte(&mut self, byte: u8) {
+        if !self.prev_crc_control {
+            self.crc = update_crc(self.crc, byte);
+        }
+
+        let mut trigger_irq = self.transfer_irq_enabled;
+        if !self.transfer_enabled {
+            self.gap_ended = false;
+            self.crc = 0;
+        } else if byte != 0 && !self.gap_ended {
+            // The gap end mark itself is not transferred to the CPU
+            self.gap_ended = true;
+            trigger_irq = false;
+        }
+
+        if self.gap_ended {
+            self.transfer_complete = true;
+            self.read_data = byte;
+            self.irq_pending |= trigger_irq;
+        }
+    }
+
+    fn next_write_byte(&mut self) -> u8 {
+        let mut byte = 0;
+
+        if !self.crc_control {
+            self.transfer_complete = true;
+            byte = self.write_data;
+            self.irq_pending |= self.transfer_irq_enabled;
+        }
+
+        if !self.transfer_enabled {
+            byte = 0;
+        }
+
+        if self.crc_control {
+            if !self.prev_crc_control {
+                // Finish the CRC calculation before the drive starts writing it out
+                self.crc = update_crc(update_crc(self.crc, 0), 0);
+            }
+            byte = self.crc as u8;
+            self.crc >>= 8;
+        } else {
+            self.crc = update_crc(self.crc, byte);
+        }
+
+        self.gap_ended = false;
+
+        byte
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct Fds {
+    disk_registers_enabled: bool,
+    sound_registers_enabled: bool,
+    timer_reload: u16,
+    timer_counter: u16,
+    timer_repeat: bool,
+    timer_enabled: bool,
+    timer_irq_pending: bool,
+    nametable_mirroring: NametableMirroring,
+    drive: DiskDrive,
+    audio: FdsAudioUnit,
+}
+
+impl Fds {
+    pub(crate) fn new(disk: Vec<u8>, side_len: usize, side_count: usize) -> Self {
+        Self {
+            disk_registers_enabled: false,
+            sound_registers_enabled: false,
+            timer_reload: 0,
+            timer_counter: 0,
+            timer_repeat: false,
+            timer_enabled: false,
+            timer_irq_pending: false,
+            nametable_mirroring: NametableMirroring::Horizontal,
+            drive: DiskDrive::new(disk, side_len, side_count),
+            audio: FdsAudioUnit::new(),
+        }
+    }
+}
+
+impl MapperImpl<Fds> {
+    pub(crate) fn read_cpu_address(&mut self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x401F => panic!("invalid CPU map address: {address:04X}"),
+            0x4030 => {
+                let value = u8::from(self.data.timer_irq_pending)
+                    | (u8::from(self.data.drive.transfer_complete) << 1)
+                    | (u8::from(self.data.drive.end_of_head) << 6);
+
+                self.data.drive.transfer_complete = false;
+                self.data.drive.irq_pending = false;
+                self.data.timer_irq_pending = false;
+
+                value
+            }
+            0x4031 => {
+                self.data.drive.transfer_complete = false;
+                self.data.drive.irq_pending = false;
+
+                self.data.drive.read_data
+            }
+            0x4032 => {
+                let disk_inserted = self.data.drive.current_side.is_some();
+                let disk_ready = disk_inserted && self.data.drive.scanning;
+
+                u8::from(!disk_inserted)
+                    | (u8::from(!disk_ready) << 1)
+                    | (u8::from(!disk_inserted) << 2)
+                    | (bus::cpu_open_bus(address) & 0xF8)
+            }
+            // Bit 7 set indicates a good battery
+            0x4033 => 0x80,
+            0x4040..=0x4097 => match self.data.audio.read_register(address) {
+                Some(value) => value | (bus::cpu_open_bus(address) & 0xC0),
+                None => bus::cpu_open_bus(address),
+            },
+            0x4020..=0x5FFF => bus::cpu_open_bus(address),
+            0x6000..=0xDFFF => self.cartridge.get_prg_ram(u32::from(address - 0x6000)),
+            0xE000..=0xFFFF => self.cartridge.get_prg_rom(u32::from(address - 0xE000)),
+        }
+    }
+
+    pub(crate) fn write_cpu_address(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x401F => panic!("invalid CPU map address: {address:04X}"),
+            0x4023 => {
+                self.data.disk_registers_enabled = value.bit(0);
+                self.data.sound_registers_enabled = value.bit(1);
+
+                if !self.data.disk_registers_enabled {
+                    self.data.timer_enabled = false;
+                    self.data.timer_irq_pending = false;
+                    self.data.drive.irq_pending = false;
+                }
+            }
+            0x4020..=0x4026 if self.data.disk_registers_enabled => {
+                self.write_disk_register(address, value);
+            }
+            0x4040..=0x4097 if self.data.sound_registers_enabled => {
+                self.data.audio.write_register(address, value);
+            }
+            0x4020..=0x5FFF | 0xE000..=0xFFFF => {}
+            0x6000..=0xDFFF => {
+                self.cartridge.set_prg_ram(u32::from(address - 0x6000), value);
+            }
+        }
+    }
+
+    fn write_disk_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x4020 => {
+                self.data.timer_reload = (self.data.timer_reload & 0xFF00) | u16::from(value);
+            }
+            0x4021 => {
+                self.data.timer_reload =
+                    (self.data.timer_reload & 0x00FF) | (u16::from(value) << 8);
+            }
+            0x4022 => {
+                self.data.timer_repeat = value.bit(0);
+                self.data.timer_enabled = value.bit(1);
+
+                if self.data.timer_enabled {
+                    self.data.timer_counter = self.data.timer_reload;
+                } else {
+                    self.data.timer_irq_pending = false;
+                }
+            }
+            0x4024 => {
+                self.data.drive.write_data = value;
+                self.data.drive.transfer_complete = false;
+                self.data.drive.irq_pending = false;
+            }
+            0x4025 => {
+                self.data.drive.handle_control_write(value);
+                self.data.nametable_mirroring = if value.bit(3) {
+                    NametableMirroring::Horizontal
+                } else {
+                    NametableMirroring::Vertical
+                };
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn tick_cpu(&mut self) {
+        if self.data.timer_enabled {
+            if self.data.timer_counter == 0 {
+                self.data.timer_irq_pending = true;
+                self.data.timer_counter = self.data.timer_reload;
+                self.data.timer_enabled = self.data.timer_repeat;
+            } else {
+                self.data.timer_counter -= 1;
+            }
+        }
+
+        self.data.drive.tick_cpu();
+        self.data.audio.tick_cpu();
+    }
+
+    pub(crate) fn interrupt_flag(&self) -> bool {
+        self.data.timer_irq_pending || self.data.drive.irq_pending
+    }
+
+    pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        self.data.audio.sample_audio(mixed_apu_sample)
+    }
+
+    /// Eject the current disk side and insert the next one after a short delay. Cycles through
+    /// all sides of all disks in order.
+    pub(crate) fn switch_disk_side(&mut self) {
+        self.data.drive.switch_side();
+    }
+
+    pub(crate) fn get_and_clear_disk_dirty_bit(&mut self) -> bool {
+        let dirty = self.data.drive.disk_dirty;
+        self.data.drive.disk_dirty = false;
+        dirty
+    }
+
+    /// The raw contents of all disk sides, which are persisted as the save file.
+    pub(crate) fn disk(&self) -> &[u8] {
+        &self.data.drive.disk
+    }
+}
+
+impl HasBasicPpuMapping for MapperImpl<Fds> {
+    fn map_ppu_address(&self, address: u16) -> PpuMapResult {
+        match address {
+            0x0000..=0x1FFF => PpuMapResult::ChrRAM(address.into()),
+            0x2000..=0x3EFF => {
+                PpuMapResult::Vram(self.data.nametable_mirroring.map_to_vram(address))
+            }
+            0x3F00..=0xFFFF => panic!("invalid PPU map address: {address:04X}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_disk_side() -> Vec<u8> {
+        let mut side = vec![0; FDS_DISK_SIDE_LEN];
+
+        // Disk info block
+        side[..15].copy_from_slice(DISK_INFO_BLOCK_MAGIC);
+        // File amount block
+        side[56..58].copy_from_slice(&[2, 1]);
+        // File header block specifying a 4-byte file
+        side[58] = 3;
+        side[71..73].copy_from_slice(&4_u16.to_le_bytes());
+        // File data block
+        side[74..79].copy_from_slice(&[4, 0xAA, 0xBB, 0xCC, 0xDD]);
+
+        side
+    }
+
+    #[test]
+    fn raw_disk_side_layout() {
+        let side = test_disk_side();
+        let raw_side = build_raw_disk_side(&side);
+
+        let block_overhead = 1 + 2 + BLOCK_GAP_BITS / 8;
+        assert_eq!(raw_side.len(), LEADING_GAP_BITS / 8 + 56 + 2 + 16 + 5 + 4 * block_overhead);
+
+        let disk_info_start = LEADING_GAP_BITS / 8;
+        assert_eq!(raw_side[disk_info_start], GAP_END_MARK);
+        assert_eq!(&raw_side[disk_info_start + 1..disk_info_start + 16], DISK_INFO_BLOCK_MAGIC);
+
+        let data_block_start = raw_side.len() - (5 + block_overhead);
+        assert_eq!(
+            &raw_side[data_block_start..data_block_start + 6],
+            &[GAP_END_MARK, 4, 0xAA, 0xBB, 0xCC, 0xDD]
+        );
+    }
+
+    #[test]
+    fn disk_image_with_header() {
+        let mut file = Vec::from(FWNES_HEADER_MAGIC);
+        file.resize(FWNES_HEADER_LEN, 0);
+        file.extend(test_disk_side());
+        file.extend(test_disk_side());
+
+        let (disk, side_len, side_count) = parse_fds_disk_image(&file).unwrap();
+        assert_eq!(side_count, 2);
+        assert_eq!(side_len, MIN_RAW_SIDE_LEN);
+        assert_eq!(disk.len(), 2 * MIN_RAW_SIDE_LEN);
+
+        assert!(matches!(
+            parse_fds_disk_image(&file[..file.len() - 1]),
+            Err(CartridgeFileError::FdsImageSize { .. })
+        ));
+    }
+}
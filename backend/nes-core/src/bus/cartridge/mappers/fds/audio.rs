@@ -0,0 +1,443 @@
+//! FDS expansion audio: a single wavetable channel with a 64-step waveform, a frequency modulation
+//! unit, and volume/modulation envelopes.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::num::GetBit;
+
+// Master volume register divides output by 2/2, 2/3, 2/4, or 2/5
+const MASTER_VOLUME_MULTIPLIERS: [f64; 4] = [1.0, 2.0 / 3.0, 0.5, 0.4];
+
+// Mod table entries map to these changes to the mod counter; 4 resets the counter to 0
+const MOD_TABLE_ADJUSTMENTS: [i8; 8] = [0, 1, 2, 4, 0, -4, -2, -1];
+const MOD_TABLE_RESET: u8 = 4;
+
+// FDS audio at max volume is roughly 2.4x as loud as an APU pulse channel at max volume
+// https://www.nesdev.org/wiki/FDS_audio
+const FDS_MAX_AMPLITUDE: f64 = 0.3586;
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct Envelope {
+    speed: u8,
+    increase: bool,
+    disabled: bool,
+    gain: u8,
+    timer: u32,
+}
+
+impl Envelope {
+    fn new() -> Self {
+        Self { speed: 0, increase: false, disabled: true, gain: 0, timer: 0 }
+    }
+
+    fn handle_write(&mut self, value: u8, master_speed: u8) {
+        self.speed = value & 0x3F;
+        self.increase = value.bit(6);
+        self.disabled = value.bit(7);
+
+        if self.disabled {
+            self.gain = self.speed;
+        }
+
+        self.reset_timer(master_speed);
+    }
+
+    fn reset_timer(&mut self, master_speed: u8) {
+        self.timer = 8 * (u32::from(master_speed) + 1) * (u32::from(self.speed) + 1);
+    }
+
+    fn tick_cpu(&mut self, master_speed: u8) {
+        if self.disabled || master_speed == 0 {
+            return;
+        }
+
+        self.timer -= 1;
+        if self.timer != 0 {
+            return;
+        }
+
+        self.reset_timer(master_speed);
+
+        if self.increase && self.gain < 32 {
+            self.gain += 1;
+        } else if !self.increase && self.gain > 0 {
+            self.gain -= 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct ModulationUnit {
+    table: [u8; 64],
+    table_position: u8,
+    counter: i8,
+    frequency: u16,
+    accumulator: u16,
+    halted: bool,
+    envelope: Envelope,
+}
+
+impl ModulationUnit {
+    fn new() -> Self {
+        Self {
+            table: [0; 64],
+            table_position: 0,
+            counter: 0,
+            frequency: 0,
+            accumulator: 0,
+            halted: true,
+            envelope: Envelope::new(),
+        }
+    }
+
+    fn write_table(&mut self, value: u8) {
+        if !self.halted {
+            return;
+        }
+
+        // Each write fills two consecutive entries
+        let value = value & 0x07;
+        self.table[self.table_position as usize] = value;
+        self.table[((self.table_position + 1) & 0x3F) as usize] = value;
+        self.table_position = (self.table_position + 2) & 0x3F;
+    }
+
+    fn set_counter(&mut self, value: u8) {
+        // Sign extend from 7 bits
+        self.counter = ((value << 1) as i8) >> 1;
+    }
+
+    fn tick_cpu(&mut self) {
+        if self.halted || self.frequency == 0 {
+            return;
+        }
+
+        let (accumulator, overflowed) = self.accumulator.overflowing_add(self.frequency);
+        self.accumulator = accumulator;
+        if !overflowed {
+            return;
+        }
+
+        let entry = self.table[self.table_position as usize];
+        let counter = if entry == MOD_TABLE_RESET {
+            0
+        } else {
+            self.counter.wrapping_add(MOD_TABLE_ADJUSTMENTS[entry as usize])
+        };
+        // Counter is a 7-bit signed value
+        self.set_counter(counter as u8);
+        self.table_position = (self.table_position + 1) & 0x3F;
+    }
+
+    // Pitch calculation from https://www.nesdev.org/wiki/FDS_audio
+    fn pitch_offset(&self, wave_frequency: u16) -> i32 {
+        let counter: i32 = self.counter.into();
+
+        // Multiply counter by gain, dropping the lowest 4 bits with unusual rounding
+        let product = counter * i32::from(self.envelope.gain);
+        let mut offset = product >> 4;
+        if product & 0x0F != 0 && offset & 0x80 == 0 {
+            offset += if counter < 0 { -1 } else { 2 };
+        }
+
+        // Wrap if outside of the 8-bit range
+        if offset >= 192 {
+            offset -= 256;
+        } else if offset < -64 {
+            offset += 256;
+        }
+
+        // Multiply by wave pitch, then drop the lowest 6 bits with rounding
+        let product = i32::from(wave_frequency) * offset;
+        let mut offset = product >> 6;
+        if product & 0x20 != 0 {
+            offset += 1;
+        }
+
+        offset
+    }
+}
+
+/// The FDS wavetable expansion audio unit, mapped at $4040-$4097.
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct FdsAudioUnit {
+    wave_table: [u8; 64],
+    wave_write_enabled: bool,
+    wave_frequency: u16,
+    wave_accumulator: u32,
+    wave_halted: bool,
+    envelopes_halted: bool,
+    volume_envelope: Envelope,
+    // Volume gain is only applied at the start of each waveform cycle
+    latched_volume_gain: u8,
+    modulation: ModulationUnit,
+    master_volume: u8,
+    master_envelope_speed: u8,
+}
+
+impl FdsAudioUnit {
+    pub(crate) fn new() -> Self {
+        Self {
+            wave_table: [0; 64],
+            wave_write_enabled: false,
+            wave_frequency: 0,
+            wave_accumulator: 0,
+            wave_halted: true,
+            envelopes_halted: false,
+            volume_envelope: Envelope::new(),
+            latched_volume_gain: 0,
+            modulation: ModulationUnit::new(),
+            master_volume: 0,
+            master_envelope_speed: 0xE8,
+        }
+    }
+
+    fn wave_position(&self) -> u8 {
+        ((self.wave_accumulator >> 16) & 0x3F) as u8
+    }
+
+    /// Handle a read from $4040-$4097. Returns None for addresses that are not readable.
+    pub(crate) fn read_register(&self, address: u16) -> Option<u8> {
+        match address {
+            0x4040..=0x407F => Some(self.wave_table[(address & 0x3F) as usize]),
+            0x4090 => Some(self.volume_envelope.gain),
+            0x4092 => Some(self.modulation.envelope.gain),
+            _ => None,
+        }
+    }
+
+    /// Handle a write to $4040-$4097.
+    pub(crate) fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x4040..=0x407F if self.wave_write_enabled => {
+                self.wave_table[(address & 0x3F) as usize] = value & 0x3F;
+            }
+            0x4080 => {
+                self.volume_envelope.handle_write(value, self.master_envelope_speed);
+            }
+            0x4082 => {
+                self.wave_frequency = (self.wave_frequency & 0x0F00) | u16::from(value);
+            }
+            0x4083 => {
+                self.wave_frequency =
+                    (self.wave_frequency & 0x00FF) | (u16::from(value & 0x0F) << 8);
+                self.wave_halted = value.bit(7);
+                self.envelopes_halted = value.bit(6);
+
+                if self.wave_halted {
+                    self.wave_accumulator = 0;
+                    self.latched_volume_gain = self.volume_envelope.gain;
+                }
+
+                if self.envelopes_halted {
+                    self.volume_envelope.reset_timer(self.master_envelope_speed);
+                    self.modulation.envelope.reset_timer(self.master_envelope_speed);
+                }
+            }
+            0x4084 => {
+                self.modulation.envelope.handle_write(value, self.master_envelope_speed);
+            }
+            0x4085 => {
+                self.modulation.set_counter(value & 0x7F);
+            }
+            0x4086 => {
+                self.modulation.frequency = (self.modulation.frequency & 0x0F00) | u16::from(value);
+            }
+            0x4087 => {
+                self.modulation.frequency =
+                    (self.modulation.frequency & 0x00FF) | (u16::from(value & 0x0F) << 8);
+                self.modulation.halted = value.bit(7);
+
+                if self.modulation.halted {
+                    self.modulation.accumulator = 0;
+                }
+            }
+            0x4088 => {
+                self.modulation.write_table(value);
+            }
+            0x4089 => {
+                self.wave_write_enabled = value.bit(7);
+                self.master_volume = value & 0x03;
+            }
+            0x408A => {
+                self.master_envelope_speed = value;
+                self.volume_envelope.reset_timer(value);
+                self.modulation.envelope.reset_timer(value);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn tick_cpu(&mut self) {
+        if !self.wave_halted && !self.envelopes_halted {
+            self.volume_envelope.tick_cpu(self.master_envelope_speed);
+            self.modulation.envelope.tick_cpu(self.master_envelope_speed);
+        }
+
+        self.modulation.tick_cpu();
+
+        if self.wave_halted || self.wave_write_enabled {
+            return;
+        }
+
+        let pitch = i32::from(self.wave_frequency)
+            + if self.modulation.halted {
+                0
+            } else {
+                self.modulation.pitch_offset(self.wave_frequency)
+            };
+        if pitch <= 0 {
+            return;
+        }
+
+        let prev_position = self.wave_position();
+        self.wave_accumulator = (self.wave_accumulator + pitch as u32) & 0x3F_FFFF;
+        if self.wave_position() < prev_position {
+            self.latched_volume_gain = self.volume_envelope.gain;
+        }
+    }
+
+    fn sample(&self) -> f64 {
+        let wave_sample = self.wave_table[self.wave_position() as usize];
+        let gain = self.latched_volume_gain.min(32);
+
+        // Wave samples are 0-63 and gain is 0-32
+        let sample = f64::from(wave_sample) / 63.0 * f64::from(gain) / 32.0;
+        sample * MASTER_VOLUME_MULTIPLIERS[self.master_volume as usize]
+    }
+
+    pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        mixed_apu_sample - FDS_MAX_AMPLITUDE * self.sample()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {expected}, got {actual}");
+    }
+
+    #[test]
+    fn wave_table_writes() {
+        let mut audio = FdsAudioUnit::new();
+
+        // Wave table is read-only unless wave writes are enabled
+        audio.write_register(0x4045, 0x12);
+        assert_eq!(audio.read_register(0x4045), Some(0x00));
+
+        audio.write_register(0x4089, 0x80);
+        audio.write_register(0x4045, 0xFF);
+        assert_eq!(audio.read_register(0x4045), Some(0x3F));
+
+        assert_eq!(audio.read_register(0x4082), None);
+    }
+
+    #[test]
+    fn volume_envelope() {
+        let mut audio = FdsAudioUnit::new();
+
+        // Disabling the envelope sets gain directly
+        audio.write_register(0x4080, 0x80 | 0x14);
+        assert_eq!(audio.read_register(0x4090), Some(0x14));
+
+        // Increase every 8 * (master speed + 1) * (speed + 1) CPU cycles
+        audio.write_register(0x408A, 1);
+        audio.write_register(0x4080, 0x40 | 0x01);
+        audio.write_register(0x4083, 0x00);
+        for _ in 0..31 {
+            audio.tick_cpu();
+        }
+        assert_eq!(audio.read_register(0x4090), Some(0x14));
+        audio.tick_cpu();
+        assert_eq!(audio.read_register(0x4090), Some(0x15));
+
+        // Envelopes do not run while halted
+        audio.write_register(0x4083, 0x40);
+        for _ in 0..100 {
+            audio.tick_cpu();
+        }
+        assert_eq!(audio.read_register(0x4090), Some(0x15));
+    }
+
+    #[test]
+    fn wave_output() {
+        let mut audio = FdsAudioUnit::new();
+
+        audio.write_register(0x4089, 0x80);
+        for i in 0..64 {
+            audio.write_register(0x4040 + i, i as u8);
+        }
+        audio.write_register(0x4089, 0x00);
+
+        // Max gain, latched when the wave is halted
+        audio.write_register(0x4080, 0x80 | 0x20);
+        audio.write_register(0x4083, 0x80);
+        assert_close(audio.sample_audio(0.0), 0.0);
+
+        // Frequency $400 advances one step every 64 CPU cycles
+        audio.write_register(0x4082, 0x00);
+        audio.write_register(0x4083, 0x04);
+        for _ in 0..5 * 64 {
+            audio.tick_cpu();
+        }
+        assert_eq!(audio.wave_position(), 5);
+        assert_close(audio.sample_audio(0.0), -FDS_MAX_AMPLITUDE * 5.0 / 63.0);
+
+        // Master volume 2 halves the output
+        audio.write_register(0x4089, 0x02);
+        assert_close(audio.sample_audio(0.0), -FDS_MAX_AMPLITUDE * 5.0 / 63.0 * 0.5);
+    }
+
+    #[test]
+    fn modulation() {
+        let mut audio = FdsAudioUnit::new();
+
+        // Counter is a sign-extended 7-bit value
+        audio.write_register(0x4085, 0x7F);
+        assert_eq!(audio.modulation.counter, -1);
+
+        // Mod table entries are written in pairs, and only while modulation is halted. Writing all
+        // 32 pairs returns the table position to the start
+        let values = [1, 1, 1, 1, MOD_TABLE_RESET, 1, 1, 1].into_iter().chain([0; 24]);
+        for value in values {
+            audio.write_register(0x4088, value);
+        }
+        assert_eq!(audio.modulation.table[..4], [1, 1, 1, 1]);
+        assert_eq!(audio.modulation.table[8..10], [MOD_TABLE_RESET, MOD_TABLE_RESET]);
+
+        audio.write_register(0x4087, 0x00);
+        audio.write_register(0x4088, 7);
+        assert_eq!(audio.modulation.table[16], 0);
+
+        // Frequency $800 steps through the table every 32 CPU cycles
+        audio.write_register(0x4085, 0x00);
+        audio.write_register(0x4086, 0x00);
+        audio.write_register(0x4087, 0x08);
+        for _ in 0..8 * 32 {
+            audio.tick_cpu();
+        }
+        assert_eq!(audio.modulation.counter, 8);
+        for _ in 0..2 * 32 {
+            audio.tick_cpu();
+        }
+        assert_eq!(audio.modulation.counter, 0);
+    }
+
+    #[test]
+    fn pitch_offset() {
+        let mut modulation = ModulationUnit::new();
+
+        modulation.envelope.gain = 32;
+        modulation.counter = 2;
+        assert_eq!(modulation.pitch_offset(0x100), 16);
+
+        modulation.counter = -2;
+        assert_eq!(modulation.pitch_offset(0x100), -16);
+
+        // A positive counter * gain with a remainder is rounded up by 2
+        modulation.envelope.gain = 1;
+        modulation.counter = 1;
+        assert_eq!(modulation.pitch_offset(0x100), 8);
+    }
+}
@@ -5,8 +5,8 @@ mod vrc7;
 
 use crate::bus::cartridge::mappers::{BankSizeKb, ChrType, NametableMirroring, PpuMapResult};
 pub(crate) use vrc4::Vrc4;
-pub(crate) use vrc6::Vrc6;
-pub(crate) use vrc7::Vrc7;
+pub(crate) use vrc6::{Vrc6, Vrc6AudioUnit};
+pub(crate) use vrc7::{Vrc7, Vrc7AudioUnit};
 
 fn map_ppu_address<N: Into<u32> + Copy>(
     address: u16,
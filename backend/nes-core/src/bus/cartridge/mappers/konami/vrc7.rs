@@ -707,6 +707,15 @@ impl Vrc7AudioUnit {
         }
     }
 
+    /// Enable or disable the audio chip. Disabling the chip clears all audio state.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.enabled = true;
+        } else {
+            *self = Self::new();
+        }
+    }
+
     pub fn select_register(&mut self, register: u8) {
         self.selected_register = register;
     }
@@ -768,6 +777,20 @@ impl Vrc7AudioUnit {
 
         (sample / 6.0).clamp(-1.0, 1.0)
     }
+
+    pub fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        if !self.enabled {
+            return mixed_apu_sample;
+        }
+
+        let vrc7_sample = self.sample();
+
+        // Amplify the VRC7 samples by ~4dB because otherwise this chip is very quiet
+        let amplified_sample = vrc7_sample * 1.5848931924611136;
+        let clamped_sample = amplified_sample.clamp(-1.0, 1.0);
+
+        mixed_apu_sample - clamped_sample
+    }
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
@@ -900,11 +923,7 @@ impl MapperImpl<Vrc7> {
                     };
                     self.data.ram_enabled = value.bit(7);
 
-                    self.data.audio.enabled = !value.bit(6);
-                    if !self.data.audio.enabled {
-                        // Clear all audio state when audio is disabled
-                        self.data.audio = Vrc7AudioUnit::new();
-                    }
+                    self.data.audio.set_enabled(!value.bit(6));
                 }
                 (Variant::Vrc7a | Variant::Unknown, 0xE010)
                 | (Variant::Vrc7b | Variant::Unknown, 0xE008) => {
@@ -932,17 +951,7 @@ impl MapperImpl<Vrc7> {
     }
 
     pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
-        if !self.data.audio.enabled {
-            return mixed_apu_sample;
-        }
-
-        let vrc7_sample = self.data.audio.sample();
-
-        // Amplify the VRC7 samples by ~4dB because otherwise this chip is very quiet
-        let amplified_sample = vrc7_sample * 1.5848931924611136;
-        let clamped_sample = amplified_sample.clamp(-1.0, 1.0);
-
-        mixed_apu_sample - clamped_sample
+        self.data.audio.sample_audio(mixed_apu_sample)
     }
 }
 
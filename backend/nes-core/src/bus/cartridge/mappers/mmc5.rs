@@ -447,20 +447,43 @@ impl ExtendedAttributesState {
     }
 }
 
+/// The unsigned 8x8 multiplier at $5205-$5206.
 #[derive(Debug, Clone, Copy, Encode, Decode)]
-struct MultiplierUnit {
+pub(crate) struct MultiplierUnit {
     operand_l: u16,
     operand_r: u16,
 }
 
 impl MultiplierUnit {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self { operand_l: 0xFF, operand_r: 0xFF }
     }
 
     fn output(self) -> u16 {
         self.operand_l * self.operand_r
     }
+
+    /// Read the low ($5205) or high ($5206) byte of the product.
+    pub(crate) fn read_register(self, address: u16) -> u8 {
+        match address {
+            0x5205 => (self.output() & 0x00FF) as u8,
+            0x5206 => (self.output() >> 8) as u8,
+            _ => panic!("invalid MMC5 multiplier address: {address:04X}"),
+        }
+    }
+
+    /// Write the left ($5205) or right ($5206) operand.
+    pub(crate) fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x5205 => {
+                self.operand_l = value.into();
+            }
+            0x5206 => {
+                self.operand_r = value.into();
+            }
+            _ => panic!("invalid MMC5 multiplier address: {address:04X}"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
@@ -527,6 +550,114 @@ impl PcmChannel {
     }
 }
 
+/// The MMC5 expansion audio channels: two pulse channels that are nearly identical to the APU pulse
+/// channels, and a raw PCM channel.
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct Mmc5AudioUnit {
+    pulse_channel_1: PulseChannel,
+    pulse_channel_2: PulseChannel,
+    pcm_channel: PcmChannel,
+    frame_counter: FrameCounter,
+}
+
+impl Mmc5AudioUnit {
+    pub(crate) fn new() -> Self {
+        Self {
+            pulse_channel_1: PulseChannel::new_channel_1(SweepStatus::Disabled),
+            pulse_channel_2: PulseChannel::new_channel_2(SweepStatus::Disabled),
+            pcm_channel: PcmChannel::new(),
+            frame_counter: FrameCounter::new(TimingMode::Ntsc),
+        }
+    }
+
+    /// Read an audio register in the $5000-$5015 range. Only $5010 and $5015 are readable.
+    pub(crate) fn read_register(&mut self, address: u16) -> u8 {
+        match address {
+            0x5010 => self.pcm_channel.read_control(),
+            0x5015 => {
+                (u8::from(self.pulse_channel_2.length_counter() != 0) << 1)
+                    | u8::from(self.pulse_channel_1.length_counter() != 0)
+            }
+            _ => bus::cpu_open_bus(address),
+        }
+    }
+
+    /// Write an audio register in the $5000-$5015 range. Writes to other addresses are ignored.
+    pub(crate) fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x5000 => {
+                self.pulse_channel_1.process_vol_update(value);
+            }
+            0x5002 => {
+                self.pulse_channel_1.process_lo_update(value);
+            }
+            0x5003 => {
+                self.pulse_channel_1.process_hi_update(value);
+            }
+            0x5004 => {
+                self.pulse_channel_2.process_vol_update(value);
+            }
+            0x5006 => {
+                self.pulse_channel_2.process_lo_update(value);
+            }
+            0x5007 => {
+                self.pulse_channel_2.process_hi_update(value);
+            }
+            0x5010 => {
+                self.pcm_channel.process_control_update(value);
+            }
+            0x5011 => {
+                self.pcm_channel.process_raw_pcm_update(value);
+            }
+            0x5015 => {
+                self.pulse_channel_1.process_snd_chn_update(value);
+                self.pulse_channel_2.process_snd_chn_update(value);
+            }
+            _ => {}
+        }
+    }
+
+    /// Notify the PCM channel of a CPU read, which it uses as its input in read mode.
+    pub(crate) fn process_cpu_read(&mut self, address: u16, value: u8) {
+        self.pcm_channel.process_cpu_read(address, value);
+    }
+
+    pub(crate) fn irq_pending(&self) -> bool {
+        self.pcm_channel.irq_pending
+    }
+
+    pub(crate) fn tick_cpu(&mut self) {
+        self.pulse_channel_1.tick_cpu();
+        self.pulse_channel_2.tick_cpu();
+        self.frame_counter.tick();
+
+        if self.frame_counter.generate_quarter_frame_clock() {
+            // MMC5 channels clock both length counter and envelope at 240Hz
+            self.pulse_channel_1.clock_quarter_frame();
+            self.pulse_channel_1.clock_half_frame();
+
+            self.pulse_channel_2.clock_quarter_frame();
+            self.pulse_channel_2.clock_half_frame();
+        }
+    }
+
+    pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        let pulse1_sample = self.pulse_channel_1.sample();
+        let pulse2_sample = self.pulse_channel_2.sample();
+        let mmc5_pulse_mix = apu::mix_pulse_samples(pulse1_sample, pulse2_sample);
+
+        // Partial formula from from https://www.nesdev.org/wiki/APU_Mixer
+        let pcm_sample = self.pcm_channel.output_level;
+        let scaled_pcm_sample = if pcm_sample != 0 {
+            159.79 / (1.0 / (f64::from(pcm_sample) / 22638.0) + 100.0)
+        } else {
+            0.0
+        };
+
+        mixed_apu_sample - mmc5_pulse_mix - scaled_pcm_sample
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub(crate) struct Mmc5 {
     extended_ram: [u8; 1024],
@@ -541,10 +672,7 @@ pub(crate) struct Mmc5 {
     scanline_counter: ScanlineCounter,
     extended_attributes_state: ExtendedAttributesState,
     multiplier: MultiplierUnit,
-    pulse_channel_1: PulseChannel,
-    pulse_channel_2: PulseChannel,
-    pcm_channel: PcmChannel,
-    frame_counter: FrameCounter,
+    audio: Mmc5AudioUnit,
     ram_writes_enabled_1: bool,
     ram_writes_enabled_2: bool,
     rendering_enabled: bool,
@@ -565,10 +693,7 @@ impl Mmc5 {
             scanline_counter: ScanlineCounter::new(),
             extended_attributes_state: ExtendedAttributesState::new(),
             multiplier: MultiplierUnit::new(),
-            pulse_channel_1: PulseChannel::new_channel_1(SweepStatus::Disabled),
-            pulse_channel_2: PulseChannel::new_channel_2(SweepStatus::Disabled),
-            pcm_channel: PcmChannel::new(),
-            frame_counter: FrameCounter::new(TimingMode::Ntsc),
+            audio: Mmc5AudioUnit::new(),
             ram_writes_enabled_1: false,
             ram_writes_enabled_2: false,
             rendering_enabled: false,
@@ -591,11 +716,7 @@ impl MapperImpl<Mmc5> {
 
     fn read_internal_register(&mut self, address: u16) -> u8 {
         match address {
-            0x5010 => self.data.pcm_channel.read_control(),
-            0x5015 => {
-                (u8::from(self.data.pulse_channel_2.length_counter() != 0) << 1)
-                    | u8::from(self.data.pulse_channel_1.length_counter() != 0)
-            }
+            0x5010 | 0x5015 => self.data.audio.read_register(address),
             0x5204 => {
                 log::trace!("Scanline IRQ status register read, clearing IRQ pending flag");
 
@@ -604,41 +725,15 @@ impl MapperImpl<Mmc5> {
                 self.data.scanline_counter.irq_pending = false;
                 result
             }
-            0x5205 => (self.data.multiplier.output() & 0x00FF) as u8,
-            0x5206 => (self.data.multiplier.output() >> 8) as u8,
+            0x5205 | 0x5206 => self.data.multiplier.read_register(address),
             _ => bus::cpu_open_bus(address),
         }
     }
 
     fn write_internal_register(&mut self, address: u16, value: u8) {
         match address {
-            0x5000 => {
-                self.data.pulse_channel_1.process_vol_update(value);
-            }
-            0x5002 => {
-                self.data.pulse_channel_1.process_lo_update(value);
-            }
-            0x5003 => {
-                self.data.pulse_channel_1.process_hi_update(value);
-            }
-            0x5004 => {
-                self.data.pulse_channel_2.process_vol_update(value);
-            }
-            0x5006 => {
-                self.data.pulse_channel_2.process_lo_update(value);
-            }
-            0x5007 => {
-                self.data.pulse_channel_2.process_hi_update(value);
-            }
-            0x5010 => {
-                self.data.pcm_channel.process_control_update(value);
-            }
-            0x5011 => {
-                self.data.pcm_channel.process_raw_pcm_update(value);
-            }
-            0x5015 => {
-                self.data.pulse_channel_1.process_snd_chn_update(value);
-                self.data.pulse_channel_2.process_snd_chn_update(value);
+            0x5000..=0x5015 => {
+                self.data.audio.write_register(address, value);
             }
             0x5100 => {
                 self.data.prg_banking_mode = match value & 0x03 {
@@ -732,11 +827,8 @@ impl MapperImpl<Mmc5> {
                     self.data.scanline_counter.irq_enabled
                 );
             }
-            0x5205 => {
-                self.data.multiplier.operand_l = value.into();
-            }
-            0x5206 => {
-                self.data.multiplier.operand_r = value.into();
+            0x5205 | 0x5206 => {
+                self.data.multiplier.write_register(address, value);
             }
             _ => {}
         }
@@ -748,9 +840,22 @@ impl MapperImpl<Mmc5> {
         }
 
         match address {
-            0x0000..=0x401F => panic!("invalid CPU map address: {address:04X}"),
-            0x4020..=0x4FFF => bus::cpu_open_bus(address),
             0x5000..=0x5BFF => self.read_internal_register(address),
+            0x6000..=0xFFFF => {
+                let value = self.peek_cpu_address(address);
+                self.data.audio.process_cpu_read(address, value);
+                value
+            }
+            _ => self.peek_cpu_address(address),
+        }
+    }
+
+    /// Read a value from the given CPU address without side effects. Internal registers read as
+    /// open bus.
+    pub(crate) fn peek_cpu_address(&self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x401F => panic!("invalid CPU map address: {address:04X}"),
+            0x4020..=0x5BFF => bus::cpu_open_bus(address),
             0x5C00..=0x5FFF => match self.data.extended_ram_mode {
                 ExtendedRamMode::ReadWrite | ExtendedRamMode::ReadOnly => {
                     self.data.extended_ram[(address - 0x5C00) as usize]
@@ -759,17 +864,11 @@ impl MapperImpl<Mmc5> {
                     bus::cpu_open_bus(address)
                 }
             },
-            0x6000..=0xFFFF => {
-                let value = self
-                    .data
-                    .prg_banking_mode
-                    .map_prg_address(self.data.prg_bank_registers, address)
-                    .read(&self.cartridge);
-
-                self.data.pcm_channel.process_cpu_read(address, value);
-
-                value
-            }
+            0x6000..=0xFFFF => self
+                .data
+                .prg_banking_mode
+                .map_prg_address(self.data.prg_bank_registers, address)
+                .read(&self.cartridge),
         }
     }
 
@@ -936,39 +1035,15 @@ impl MapperImpl<Mmc5> {
     }
 
     pub(crate) fn interrupt_flag(&self) -> bool {
-        self.data.scanline_counter.interrupt_flag() || self.data.pcm_channel.irq_pending
+        self.data.scanline_counter.interrupt_flag() || self.data.audio.irq_pending()
     }
 
     pub(crate) fn tick_cpu(&mut self) {
         self.data.scanline_counter.tick_cpu();
-
-        self.data.pulse_channel_1.tick_cpu();
-        self.data.pulse_channel_2.tick_cpu();
-        self.data.frame_counter.tick();
-
-        if self.data.frame_counter.generate_quarter_frame_clock() {
-            // MMC5 channels clock both length counter and envelope at 240Hz
-            self.data.pulse_channel_1.clock_quarter_frame();
-            self.data.pulse_channel_1.clock_half_frame();
-
-            self.data.pulse_channel_2.clock_quarter_frame();
-            self.data.pulse_channel_2.clock_half_frame();
-        }
+        self.data.audio.tick_cpu();
     }
 
     pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
-        let pulse1_sample = self.data.pulse_channel_1.sample();
-        let pulse2_sample = self.data.pulse_channel_2.sample();
-        let mmc5_pulse_mix = apu::mix_pulse_samples(pulse1_sample, pulse2_sample);
-
-        // Partial formula from from https://www.nesdev.org/wiki/APU_Mixer
-        let pcm_sample = self.data.pcm_channel.output_level;
-        let scaled_pcm_sample = if pcm_sample != 0 {
-            159.79 / (1.0 / (f64::from(pcm_sample) / 22638.0) + 100.0)
-        } else {
-            0.0
-        };
-
-        mixed_apu_sample - mmc5_pulse_mix - scaled_pcm_sample
+        self.data.audio.sample_audio(mixed_apu_sample)
     }
 }
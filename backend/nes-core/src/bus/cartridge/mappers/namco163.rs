@@ -123,27 +123,36 @@ impl Namco163AudioChannel {
 
 const AUDIO_DIVIDER: u8 = 15;
 
+/// The Namco 163 expansion audio channels, which play waveforms stored in the chip's internal RAM.
+/// Channel registers are also stored in internal RAM, so all internal RAM writes must be passed
+/// through to the audio unit.
 #[derive(Debug, Clone, Encode, Decode)]
-struct Namco163AudioUnit {
+pub(crate) struct Namco163AudioUnit {
     enabled: bool,
     channels: [Namco163AudioChannel; 8],
     divider: u8,
     current_channel: u8,
     enabled_channel_count: u8,
+    volume_variant: VolumeVariantDb,
 }
 
 impl Namco163AudioUnit {
-    fn new() -> Self {
+    pub(crate) fn new(volume_variant: VolumeVariantDb) -> Self {
         Self {
             enabled: false,
             channels: array::from_fn(|_| Namco163AudioChannel::new()),
             divider: AUDIO_DIVIDER,
             current_channel: 0,
             enabled_channel_count: 0,
+            volume_variant,
         }
     }
 
-    fn process_internal_ram_update(&mut self, address: u8, value: u8) {
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub(crate) fn process_internal_ram_update(&mut self, address: u8, value: u8) {
         if address >= 0x40 {
             let channel_index = (address & 0x3F) / 0x08;
             self.channels[channel_index as usize].process_register_update(address & 0x07, value);
@@ -167,7 +176,7 @@ impl Namco163AudioUnit {
         self.channels[self.current_channel as usize].clock(internal_ram);
     }
 
-    fn tick_cpu(&mut self, internal_ram: &[u8; 128]) {
+    pub(crate) fn tick_cpu(&mut self, internal_ram: &[u8; 128]) {
         self.divider -= 1;
         if self.divider == 0 {
             self.clock(internal_ram);
@@ -191,10 +200,23 @@ impl Namco163AudioUnit {
             channel_sum / f64::from(self.enabled_channel_count)
         }
     }
+
+    pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        if !self.enabled {
+            return mixed_apu_sample;
+        }
+
+        let n163_sample = self.sample() * self.volume_variant.n163_coefficient();
+        let clamped_n163_sample = if n163_sample > 1.0 { 1.0 } else { n163_sample };
+
+        mixed_apu_sample - clamped_n163_sample
+    }
 }
 
+/// Namco 163 boards use different resistors to mix expansion audio, so the expansion audio volume
+/// relative to the APU varies from game to game.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
-enum VolumeVariantDb {
+pub(crate) enum VolumeVariantDb {
     Twelve,
     Sixteen,
     Eighteen,
@@ -234,7 +256,6 @@ pub(crate) struct Namco163 {
     ram_window_writes_enabled: [bool; 4],
     irq: IrqCounter,
     audio: Namco163AudioUnit,
-    volume_variant: VolumeVariantDb,
 }
 
 impl Namco163 {
@@ -276,8 +297,7 @@ impl Namco163 {
             ram_writes_enabled: false,
             ram_window_writes_enabled: [false; 4],
             irq: IrqCounter::new(),
-            audio: Namco163AudioUnit::new(),
-            volume_variant,
+            audio: Namco163AudioUnit::new(volume_variant),
         }
     }
 }
@@ -359,7 +379,7 @@ impl MapperImpl<Namco163> {
                 self.data.nametable_chr_banks[bank_index as usize] = value;
             }
             0xE000..=0xE7FF => {
-                self.data.audio.enabled = !value.bit(6);
+                self.data.audio.set_enabled(!value.bit(6));
                 self.data.prg_banks[0] = value & 0x3F;
             }
             0xE800..=0xEFFF => {
@@ -407,14 +427,7 @@ impl MapperImpl<Namco163> {
     }
 
     pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
-        if !self.data.audio.enabled {
-            return mixed_apu_sample;
-        }
-
-        let n163_sample = self.data.audio.sample() * self.data.volume_variant.n163_coefficient();
-        let clamped_n163_sample = if n163_sample > 1.0 { 1.0 } else { n163_sample };
-
-        mixed_apu_sample - clamped_n163_sample
+        self.data.audio.sample_audio(mixed_apu_sample)
     }
 }
 
@@ -0,0 +1,548 @@
+//! Synthetic board used for NSF playback.
+//!
+//! NSF files do not contain a reset routine, so the board maps a small driver program at $4100
+//! that clears RAM, initializes the APU, calls the file's init routine with the selected track, and
+//! then calls the play routine every time the board's play timer expires. The CPU vectors are
+//! overridden to point into the driver.
+//!
+//! Expansion audio uses the same audio units as the original boards, at the NSF register addresses:
+//! * VRC6: $9000-$9002, $A000-$A002, $B000-$B002
+//! * VRC7: $9010 (register select), $9030 (data)
+//! * MMC5: $5000-$5015 (audio), $5205-$5206 (multiplier), $5C00-$5FF5 (extended RAM)
+//! * Namco 163: $4800 (data), $F800 (address)
+//! * Sunsoft 5B: $C000 (register select), $E000 (data)
+//! * FDS: $4040-$407F (wave table), $4080-$408A (registers), $4090 and $4092 (envelope gains)
+
+use crate::bus;
+use crate::bus::cartridge::mappers::namco163::VolumeVariantDb;
+use crate::bus::cartridge::mappers::{
+    BankSizeKb, FdsAudioUnit, Mmc5AudioUnit, MultiplierUnit, Namco163AudioUnit, NametableMirroring,
+    PpuMapResult, Sunsoft5bAudioUnit, Vrc6AudioUnit, Vrc7AudioUnit,
+};
+use crate::bus::cartridge::{HasBasicPpuMapping, MapperImpl};
+use crate::nsf::{NsfFile, NsfMetadata};
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::TimingMode;
+use jgenesis_common::num::GetBit;
+use std::mem;
+
+const DRIVER_ADDRESS: u16 = 0x4100;
+
+// Registers read by the driver
+const TRACK_REGISTER: u16 = 0x4180;
+const REGION_REGISTER: u16 = 0x4181;
+// Reads as non-zero if the play routine should be called; reading clears the flag
+const PLAY_REGISTER: u16 = 0x4182;
+
+const NTSC_CPU_FREQUENCY: u64 = 1_789_773;
+const PAL_CPU_FREQUENCY: u64 = 1_662_607;
+
+// Used if the file specifies a play speed of 0
+const DEFAULT_NTSC_PLAY_SPEED: u16 = 16639;
+const DEFAULT_PAL_PLAY_SPEED: u16 = 19997;
+
+fn driver_program(init_address: u16, play_address: u16) -> Vec<u8> {
+    let [init_lsb, init_msb] = init_address.to_le_bytes();
+    let [play_lsb, play_msb] = play_address.to_le_bytes();
+
+    vec![
+        // $4100: Initialize CPU state
+        0x78, // SEI
+        0xD8, // CLD
+        0xA2, 0xFF, // LDX #$FF
+        0x9A, // TXS
+        0xE8, // INX
+        0x8A, // TXA
+        // $4107: Clear internal RAM
+        0x95, 0x00, // STA $00,X
+        0x9D, 0x00, 0x01, // STA $0100,X
+        0x9D, 0x00, 0x02, // STA $0200,X
+        0x9D, 0x00, 0x03, // STA $0300,X
+        0x9D, 0x00, 0x04, // STA $0400,X
+        0x9D, 0x00, 0x05, // STA $0500,X
+        0x9D, 0x00, 0x06, // STA $0600,X
+        0x9D, 0x00, 0x07, // STA $0700,X
+        0xE8, // INX
+        0xD0, 0xE6, // BNE $4107
+        // $4121: Clear APU registers $4000-$4013
+        0xA2, 0x13, // LDX #$13
+        0x9D, 0x00, 0x40, // STA $4000,X
+        0xCA, // DEX
+        0x10, 0xFA, // BPL $4123
+        // $4129: Enable all APU channels and set 4-step frame counter mode
+        0x8D, 0x15, 0x40, // STA $4015
+        0xA9, 0x0F, // LDA #$0F
+        0x8D, 0x15, 0x40, // STA $4015
+        0xA9, 0x40, // LDA #$40
+        0x8D, 0x17, 0x40, // STA $4017
+        // $4136: Call init with A = track and X = region
+        0xAD, 0x80, 0x41, // LDA $4180
+        0xAE, 0x81, 0x41, // LDX $4181
+        0x20, init_lsb, init_msb, // JSR init
+        // $413F: Call play whenever the play timer expires
+        0xAD, 0x82, 0x41, // LDA $4182
+        0xF0, 0xFB, // BEQ $413F
+        0x20, play_lsb, play_msb, // JSR play
+        0x4C, 0x3F, 0x41, // JMP $413F
+        // $414A: NMI and IRQ handler
+        0x40, // RTI
+    ]
+}
+
+/// Lay out NSF program data as PRG ROM. Returns the PRG ROM and the initial values of the eight
+/// 4KB bank registers for $8000-$FFFF.
+pub(crate) fn nsf_prg_rom(nsf: &NsfFile) -> (Vec<u8>, [u8; 8]) {
+    match nsf.initial_banks {
+        Some(initial_banks) => {
+            // Bank switched data is padded so that the load address is at the same offset within
+            // its 4KB bank
+            let padding = (nsf.load_address & 0x0FFF) as usize;
+            let len = (padding + nsf.data.len()).next_power_of_two().max(0x1000);
+            let mut prg_rom = vec![0; len];
+            prg_rom[padding..padding + nsf.data.len()].copy_from_slice(&nsf.data);
+            (prg_rom, initial_banks)
+        }
+        None => {
+            // Data is loaded directly at the load address, truncated at the end of the address space
+            let mut prg_rom = vec![0; 0x8000];
+            let start = (nsf.load_address - 0x8000) as usize;
+            let len = nsf.data.len().min(prg_rom.len() - start);
+            prg_rom[start..start + len].copy_from_slice(&nsf.data[..len]);
+            (prg_rom, [0, 1, 2, 3, 4, 5, 6, 7])
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct NsfMmc5 {
+    audio: Mmc5AudioUnit,
+    multiplier: MultiplierUnit,
+    extended_ram: [u8; 1024],
+}
+
+impl NsfMmc5 {
+    fn new() -> Self {
+        Self {
+            audio: Mmc5AudioUnit::new(),
+            multiplier: MultiplierUnit::new(),
+            extended_ram: [0; 1024],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct NsfNamco163 {
+    audio: Namco163AudioUnit,
+    internal_ram: [u8; 128],
+    internal_ram_addr: u8,
+    internal_ram_auto_increment: bool,
+}
+
+impl NsfNamco163 {
+    fn new() -> Self {
+        let mut audio = Namco163AudioUnit::new(VolumeVariantDb::Twelve);
+        audio.set_enabled(true);
+
+        Self {
+            audio,
+            internal_ram: [0; 128],
+            internal_ram_addr: 0,
+            internal_ram_auto_increment: false,
+        }
+    }
+
+    fn read_data(&mut self) -> u8 {
+        let byte = self.internal_ram[self.internal_ram_addr as usize];
+        if self.internal_ram_auto_increment {
+            self.internal_ram_addr = (self.internal_ram_addr + 1) & 0x7F;
+        }
+        byte
+    }
+
+    fn write_data(&mut self, value: u8) {
+        let ram_addr = self.internal_ram_addr;
+        self.internal_ram[ram_addr as usize] = value;
+        self.audio.process_internal_ram_update(ram_addr, value);
+
+        if self.internal_ram_auto_increment {
+            self.internal_ram_addr = (ram_addr + 1) & 0x7F;
+        }
+    }
+
+    fn write_address(&mut self, value: u8) {
+        self.internal_ram_auto_increment = value.bit(7);
+        self.internal_ram_addr = value & 0x7F;
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+pub(crate) struct Nsf {
+    metadata: NsfMetadata,
+    driver: Vec<u8>,
+    bank_switching: bool,
+    initial_banks: [u8; 8],
+    prg_banks: [u8; 8],
+    track: u8,
+    pal: bool,
+    // The play timer counts in units of 1/1000000 CPU cycles so that it can count microseconds
+    // exactly
+    play_period: u64,
+    play_timer: u64,
+    play_pending: bool,
+    vrc6: Option<Vrc6AudioUnit>,
+    vrc7: Option<Vrc7AudioUnit>,
+    mmc5: Option<NsfMmc5>,
+    namco163: Option<NsfNamco163>,
+    sunsoft_5b: Option<Sunsoft5bAudioUnit>,
+    fds: Option<FdsAudioUnit>,
+}
+
+impl Nsf {
+    pub(crate) fn new(nsf: &NsfFile, initial_banks: [u8; 8], timing_mode: TimingMode) -> Self {
+        let (play_speed, default_play_speed, cpu_frequency) = match timing_mode {
+            TimingMode::Ntsc => (nsf.ntsc_play_speed, DEFAULT_NTSC_PLAY_SPEED, NTSC_CPU_FREQUENCY),
+            TimingMode::Pal => (nsf.pal_play_speed, DEFAULT_PAL_PLAY_SPEED, PAL_CPU_FREQUENCY),
+        };
+        let play_speed = if play_speed != 0 { play_speed } else { default_play_speed };
+
+        let mut data = Self {
+            metadata: nsf.metadata.clone(),
+            driver: driver_program(nsf.init_address, nsf.play_address),
+            bank_switching: nsf.initial_banks.is_some(),
+            initial_banks,
+            prg_banks: initial_banks,
+            track: nsf.metadata.starting_track.min(nsf.metadata.total_tracks - 1),
+            pal: timing_mode == TimingMode::Pal,
+            play_period: u64::from(play_speed) * cpu_frequency,
+            play_timer: 0,
+            play_pending: false,
+            vrc6: None,
+            vrc7: None,
+            mmc5: None,
+            namco163: None,
+            sunsoft_5b: None,
+            fds: None,
+        };
+        data.reset();
+        data
+    }
+
+    fn reset(&mut self) {
+        self.prg_banks = self.initial_banks;
+        self.play_timer = 0;
+        self.play_pending = false;
+
+        let expansion_audio = self.metadata.expansion_audio;
+        self.vrc6 = expansion_audio.vrc6.then(Vrc6AudioUnit::new);
+        self.vrc7 = expansion_audio.vrc7.then(|| {
+            let mut vrc7 = Vrc7AudioUnit::new();
+            vrc7.set_enabled(true);
+            vrc7
+        });
+        self.mmc5 = expansion_audio.mmc5.then(NsfMmc5::new);
+        self.namco163 = expansion_audio.namco163.then(NsfNamco163::new);
+        self.sunsoft_5b = expansion_audio.sunsoft_5b.then(Sunsoft5bAudioUnit::new);
+        self.fds = expansion_audio.fds.then(FdsAudioUnit::new);
+    }
+}
+
+impl MapperImpl<Nsf> {
+    fn read_prg_rom(&self, address: u16) -> u8 {
+        let bank_index = (address - 0x8000) / 0x1000;
+        let prg_rom_addr =
+            BankSizeKb::Four.to_absolute_address(self.data.prg_banks[bank_index as usize], address);
+        self.cartridge.get_prg_rom(prg_rom_addr)
+    }
+
+    pub(crate) fn read_cpu_address(&mut self, address: u16) -> u8 {
+        match address {
+            PLAY_REGISTER => mem::take(&mut self.data.play_pending).into(),
+            0x4800..=0x4FFF => match &mut self.data.namco163 {
+                Some(namco163) => namco163.read_data(),
+                None => bus::cpu_open_bus(address),
+            },
+            0x5000..=0x5015 => match &mut self.data.mmc5 {
+                Some(mmc5) => mmc5.audio.read_register(address),
+                None => bus::cpu_open_bus(address),
+            },
+            0x8000..=0xFFF9 => {
+                let value = self.read_prg_rom(address);
+                if let Some(mmc5) = &mut self.data.mmc5 {
+                    mmc5.audio.process_cpu_read(address, value);
+                }
+                value
+            }
+            _ => self.peek_cpu_address(address),
+        }
+    }
+
+    /// Read a value from the given CPU address without side effects. Registers that change state
+    /// when read report open bus.
+    pub(crate) fn peek_cpu_address(&self, address: u16) -> u8 {
+        let driver_end = DRIVER_ADDRESS + self.data.driver.len() as u16;
+        match address {
+            0x0000..=0x401F => panic!("invalid CPU map address: {address:04X}"),
+            _ if (DRIVER_ADDRESS..driver_end).contains(&address) => {
+                self.data.driver[(address - DRIVER_ADDRESS) as usize]
+            }
+            TRACK_REGISTER => self.data.track,
+            REGION_REGISTER => self.data.pal.into(),
+            0x4040..=0x4097 => self
+                .data
+                .fds
+                .as_ref()
+                .and_then(|fds| fds.read_register(address))
+                .unwrap_or_else(|| bus::cpu_open_bus(address)),
+            0x5205..=0x5206 => match &self.data.mmc5 {
+                Some(mmc5) => mmc5.multiplier.read_register(address),
+                None => bus::cpu_open_bus(address),
+            },
+            0x5C00..=0x5FF5 => match &self.data.mmc5 {
+                Some(mmc5) => mmc5.extended_ram[(address - 0x5C00) as usize],
+                None => bus::cpu_open_bus(address),
+            },
+            0x4020..=0x5FFF => bus::cpu_open_bus(address),
+            0x6000..=0x7FFF => self.cartridge.get_prg_ram((address & 0x1FFF).into()),
+            0xFFFA..=0xFFFF => {
+                // Point the reset vector to the driver and the NMI/IRQ vectors to the driver's RTI
+                let vector =
+                    if address & 0xFFFE == 0xFFFC { DRIVER_ADDRESS } else { driver_end - 1 };
+                vector.to_le_bytes()[(address & 1) as usize]
+            }
+            0x8000..=0xFFF9 => self.read_prg_rom(address),
+        }
+    }
+
+    pub(crate) fn write_cpu_address(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x401F => panic!("invalid CPU map address: {address:04X}"),
+            0x4040..=0x408A => {
+                if let Some(fds) = &mut self.data.fds {
+                    fds.write_register(address, value);
+                }
+            }
+            0x4800..=0x4FFF => {
+                if let Some(namco163) = &mut self.data.namco163 {
+                    namco163.write_data(value);
+                }
+            }
+            0x5000..=0x5015 => {
+                if let Some(mmc5) = &mut self.data.mmc5 {
+                    mmc5.audio.write_register(address, value);
+                }
+            }
+            0x5205..=0x5206 => {
+                if let Some(mmc5) = &mut self.data.mmc5 {
+                    mmc5.multiplier.write_register(address, value);
+                }
+            }
+            0x5C00..=0x5FF5 => {
+                if let Some(mmc5) = &mut self.data.mmc5 {
+                    mmc5.extended_ram[(address - 0x5C00) as usize] = value;
+                }
+            }
+            0x5FF8..=0x5FFF => {
+                if self.data.bank_switching {
+                    self.data.prg_banks[(address - 0x5FF8) as usize] = value;
+                }
+            }
+            0x4020..=0x5FFF => {}
+            0x6000..=0x7FFF => {
+                self.cartridge.set_prg_ram((address & 0x1FFF).into(), value);
+            }
+            0x8000..=0xFFFF => self.write_expansion_audio(address, value),
+        }
+    }
+
+    fn write_expansion_audio(&mut self, address: u16, value: u8) {
+        match address {
+            0x9000..=0x9002 | 0xA000..=0xA002 | 0xB000..=0xB002 => {
+                if let Some(vrc6) = &mut self.data.vrc6 {
+                    vrc6.write_register(address, value);
+                }
+            }
+            0x9010 => {
+                if let Some(vrc7) = &mut self.data.vrc7 {
+                    vrc7.select_register(value);
+                }
+            }
+            0x9030 => {
+                if let Some(vrc7) = &mut self.data.vrc7 {
+                    vrc7.write_data(value);
+                }
+            }
+            0xC000 => {
+                if let Some(sunsoft_5b) = &mut self.data.sunsoft_5b {
+                    sunsoft_5b.handle_select_update(value);
+                }
+            }
+            0xE000 => {
+                if let Some(sunsoft_5b) = &mut self.data.sunsoft_5b {
+                    sunsoft_5b.handle_write(value);
+                }
+            }
+            0xF800..=0xFFFF => {
+                if let Some(namco163) = &mut self.data.namco163 {
+                    namco163.write_address(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn tick_cpu(&mut self) {
+        self.data.play_timer += 1_000_000;
+        if self.data.play_timer >= self.data.play_period {
+            self.data.play_timer -= self.data.play_period;
+            self.data.play_pending = true;
+        }
+
+        if let Some(vrc6) = &mut self.data.vrc6 {
+            vrc6.tick_cpu();
+        }
+        if let Some(vrc7) = &mut self.data.vrc7 {
+            vrc7.tick();
+        }
+        if let Some(mmc5) = &mut self.data.mmc5 {
+            mmc5.audio.tick_cpu();
+        }
+        if let Some(namco163) = &mut self.data.namco163 {
+            namco163.audio.tick_cpu(&namco163.internal_ram);
+        }
+        if let Some(sunsoft_5b) = &mut self.data.sunsoft_5b {
+            sunsoft_5b.tick_cpu();
+        }
+        if let Some(fds) = &mut self.data.fds {
+            fds.tick_cpu();
+        }
+    }
+
+    pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        let mut sample = mixed_apu_sample;
+
+        if let Some(vrc6) = &self.data.vrc6 {
+            sample = vrc6.sample_audio(sample);
+        }
+        if let Some(vrc7) = &self.data.vrc7 {
+            sample = vrc7.sample_audio(sample);
+        }
+        if let Some(mmc5) = &self.data.mmc5 {
+            sample = mmc5.audio.sample_audio(sample);
+        }
+        if let Some(namco163) = &self.data.namco163 {
+            sample = namco163.audio.sample_audio(sample);
+        }
+        if let Some(sunsoft_5b) = &self.data.sunsoft_5b {
+            sample = sunsoft_5b.sample_audio(sample);
+        }
+        if let Some(fds) = &self.data.fds {
+            sample = fds.sample_audio(sample);
+        }
+
+        sample
+    }
+
+    pub(crate) fn metadata(&self) -> &NsfMetadata {
+        &self.data.metadata
+    }
+
+    pub(crate) fn track(&self) -> u8 {
+        self.data.track
+    }
+
+    /// Select the track to play after the next reset. Out of range tracks are ignored.
+    pub(crate) fn set_track(&mut self, track: u8) {
+        if track < self.data.metadata.total_tracks {
+            self.data.track = track;
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.cartridge.prg_ram.fill(0);
+        self.data.reset();
+    }
+}
+
+impl HasBasicPpuMapping for MapperImpl<Nsf> {
+    fn map_ppu_address(&self, address: u16) -> PpuMapResult {
+        match address {
+            0x0000..=0x1FFF => PpuMapResult::ChrRAM(address.into()),
+            0x2000..=0x3EFF => {
+                PpuMapResult::Vram(NametableMirroring::Vertical.map_to_vram(address))
+            }
+            0x3F00..=0xFFFF => panic!("invalid PPU map address: {address:04X}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::Overscan;
+    use crate::bus::{Bus, cartridge};
+    use crate::cpu::{self, CpuState};
+    use jgenesis_common::debug::DebugHooks;
+    use mos6502_emu::bus::BusInterface;
+
+    fn test_nsf_file(expansion_audio: u8) -> Vec<u8> {
+        let mut file = vec![0; 0x80];
+        file[..5].copy_from_slice(b"NESM\x1A");
+        file[0x05] = 1;
+        file[0x06] = 3;
+        file[0x07] = 2;
+        // Load at $8000, init at $8000, play at $8003
+        file[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x03, 0x80]);
+        file[0x6E..0x70].copy_from_slice(&16639_u16.to_le_bytes());
+        file[0x7B] = expansion_audio;
+        file.extend([
+            0x85, 0x10, // STA $10
+            0x60, // RTS
+            0xE6, 0x11, // INC $11
+            0x60, // RTS
+        ]);
+        file
+    }
+
+    #[test]
+    fn driver_calls_init_and_play() {
+        let file = test_nsf_file(0);
+        let mapper = cartridge::from_nsf_file(&file, None).unwrap();
+        let mut bus = Bus::from_cartridge(mapper, Overscan::default());
+        let mut cpu_state = CpuState::new(&mut bus.cpu());
+        let mut debug_hooks = DebugHooks::new(1);
+
+        // Just over 3 play periods
+        for _ in 0..95_000 {
+            cpu::tick(&mut cpu_state, &mut bus.cpu(), false, &mut debug_hooks);
+            // Normally handled by the APU
+            bus.cpu().get_io_registers_mut().take_dirty_register();
+            bus.tick_cpu();
+        }
+
+        // Init should receive the 0-based starting track, and play should have been called 3 times
+        assert_eq!(bus.cpu().read(0x0010), 1);
+        assert_eq!(bus.cpu().read(0x0011), 3);
+    }
+
+    #[test]
+    fn fds_expansion_audio() {
+        let mut mapper = cartridge::from_nsf_file(&test_nsf_file(0), None).unwrap();
+        mapper.write_cpu_address(0x4089, 0x80);
+        mapper.write_cpu_address(0x4045, 0x3F);
+        assert_eq!(mapper.read_cpu_address(0x4045), 0x40, "no FDS audio should read open bus");
+
+        let mut mapper = cartridge::from_nsf_file(&test_nsf_file(1 << 2), None).unwrap();
+
+        // Fill the wave table with the max sample and set max gain
+        mapper.write_cpu_address(0x4089, 0x80);
+        for address in 0x4040..=0x407F {
+            mapper.write_cpu_address(address, 0x3F);
+        }
+        mapper.write_cpu_address(0x4089, 0x00);
+        mapper.write_cpu_address(0x4080, 0x80 | 0x20);
+        mapper.write_cpu_address(0x4083, 0x80);
+
+        assert_eq!(mapper.read_cpu_address(0x4045), 0x3F);
+        assert_eq!(mapper.read_cpu_address(0x4090), 0x20);
+        assert!(mapper.sample_audio(0.0) < -0.3, "FDS audio should be mixed into the output");
+    }
+}
@@ -113,8 +113,9 @@ impl Sunsoft5bChannel {
     }
 }
 
+/// The Sunsoft 5B expansion audio chip, a variant of the AY-3-8910 with three square wave channels.
 #[derive(Debug, Clone, Encode, Decode)]
-struct Sunsoft5bAudioUnit {
+pub(crate) struct Sunsoft5bAudioUnit {
     register_select: u8,
     register_writes_enabled: bool,
     channel_1: Sunsoft5bChannel,
@@ -123,7 +124,7 @@ struct Sunsoft5bAudioUnit {
 }
 
 impl Sunsoft5bAudioUnit {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             register_select: 0,
             register_writes_enabled: false,
@@ -133,12 +134,12 @@ impl Sunsoft5bAudioUnit {
         }
     }
 
-    fn handle_select_update(&mut self, value: u8) {
+    pub(crate) fn handle_select_update(&mut self, value: u8) {
         self.register_select = value & 0x0F;
         self.register_writes_enabled = value & 0xF0 == 0;
     }
 
-    fn handle_write(&mut self, value: u8) {
+    pub(crate) fn handle_write(&mut self, value: u8) {
         if !self.register_writes_enabled {
             return;
         }
@@ -180,7 +181,7 @@ impl Sunsoft5bAudioUnit {
         }
     }
 
-    fn tick_cpu(&mut self) {
+    pub(crate) fn tick_cpu(&mut self) {
         self.channel_1.tick_cpu();
         self.channel_2.tick_cpu();
         self.channel_3.tick_cpu();
@@ -201,6 +202,17 @@ impl Sunsoft5bAudioUnit {
             || self.channel_2.volume != 0
             || self.channel_3.volume != 0
     }
+
+    pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
+        if !self.enabled() {
+            return mixed_apu_sample;
+        }
+
+        let sunsoft_5b_sample = self.sample();
+
+        // This audio chip appears to slightly decrease APU channel volume
+        0.7 * mixed_apu_sample - sunsoft_5b_sample
+    }
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
@@ -355,14 +367,7 @@ impl MapperImpl<Sunsoft> {
     }
 
     pub(crate) fn sample_audio(&self, mixed_apu_sample: f64) -> f64 {
-        if !self.data.audio.enabled() {
-            return mixed_apu_sample;
-        }
-
-        let sunsoft_5b_sample = self.data.audio.sample();
-
-        // This audio chip appears to slightly decrease APU channel volume
-        0.7 * mixed_apu_sample - sunsoft_5b_sample
+        self.data.audio.sample_audio(mixed_apu_sample)
     }
 }
 
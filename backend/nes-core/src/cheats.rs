@@ -0,0 +1,96 @@
+//! NES cheat code decoding
+//!
+//! Supported formats:
+//! * Game Genie: 6 or 8 letters, e.g. `SXIOPO`; 8-letter codes include a compare value
+//! * Raw: `AAAA:VV`; addresses $8000-$FFFF patch ROM reads and lower addresses are written to
+//!   memory every frame
+
+use jgenesis_common::cheats::{parse_hex, split_raw_code, CheatError, CheatPatch, CheatResult};
+
+const GAME_GENIE_LETTERS: &[u8; 16] = b"APZLGITYEOXUKSVN";
+
+/// Decode a Game Genie or raw cheat code.
+///
+/// # Errors
+///
+/// Returns an error if the code is not in a recognized format.
+pub fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+    let code = code.trim().to_ascii_uppercase();
+
+    if let Some((address, value)) = split_raw_code(&code) {
+        let address = parse_hex(&code, address)?;
+        let value = parse_hex(&code, value)?;
+        if address > 0xFFFF || value > 0xFF {
+            return Err(CheatError::UnrecognizedFormat(code));
+        }
+
+        let patch = if address >= 0x8000 {
+            CheatPatch::rom(address, value as u8, None)
+        } else {
+            CheatPatch::ram(address, value as u8)
+        };
+        return Ok(vec![patch]);
+    }
+
+    decode_game_genie(&code).map(|patch| vec![patch])
+}
+
+// Decoding from https://www.nesdev.org/wiki/Game_Genie
+fn decode_game_genie(code: &str) -> CheatResult<CheatPatch> {
+    if code.len() != 6 && code.len() != 8 {
+        return Err(CheatError::UnrecognizedFormat(code.into()));
+    }
+
+    let n = code
+        .chars()
+        .map(|ch| {
+            GAME_GENIE_LETTERS
+                .iter()
+                .position(|&letter| char::from(letter) == ch)
+                .map(|position| position as u32)
+                .ok_or_else(|| CheatError::InvalidCharacter { code: code.into(), ch })
+        })
+        .collect::<CheatResult<Vec<_>>>()?;
+
+    let address = 0x8000
+        | ((n[3] & 7) << 12)
+        | ((n[5] & 7) << 8)
+        | ((n[4] & 8) << 8)
+        | ((n[2] & 7) << 4)
+        | ((n[1] & 8) << 4)
+        | (n[4] & 7)
+        | (n[3] & 8);
+
+    let value_high = ((n[1] & 7) << 4) | ((n[0] & 8) << 4) | (n[0] & 7);
+
+    if n.len() == 6 {
+        let value = value_high | (n[5] & 8);
+        return Ok(CheatPatch::rom(address, value as u8, None));
+    }
+
+    let value = value_high | (n[7] & 8);
+    let compare = ((n[7] & 7) << 4) | ((n[6] & 8) << 4) | (n[6] & 7) | (n[5] & 8);
+    Ok(CheatPatch::rom(address, value as u8, Some(compare as u8)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_genie() {
+        assert_eq!(decode_cheat("SXIOPO").unwrap(), vec![CheatPatch::rom(0x91D9, 0xAD, None)]);
+        assert_eq!(
+            decode_cheat("sxiopoxp").unwrap(),
+            vec![CheatPatch::rom(0x91D9, 0xA5, Some(0x9A))]
+        );
+        assert!(decode_cheat("SXIOPB").is_err());
+    }
+
+    #[test]
+    fn raw() {
+        assert_eq!(decode_cheat("075A:09").unwrap(), vec![CheatPatch::ram(0x075A, 0x09)]);
+        assert_eq!(decode_cheat("C123=EA").unwrap(), vec![CheatPatch::rom(0xC123, 0xEA, None)]);
+        assert!(decode_cheat("10000:00").is_err());
+    }
+}
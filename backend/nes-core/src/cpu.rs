@@ -2,9 +2,14 @@
 
 use crate::bus::{CpuBus, PpuRegister};
 use bincode::{Decode, Encode};
+use jgenesis_common::debug::DebugHooks;
 use mos6502_emu::bus::BusInterface;
+use mos6502_emu::debug::DebugBus;
 use mos6502_emu::Mos6502;
 
+/// Debugger CPU index of the 6502.
+pub const DEBUG_CPU: usize = 0;
+
 #[derive(Debug, Clone, Encode, Decode)]
 struct OamDmaState {
     cycles_remaining: u16,
@@ -37,10 +42,26 @@ impl CpuState {
 
         Self { mos6502, state: State::default() }
     }
+
+    pub fn mos6502(&self) -> &Mos6502 {
+        &self.mos6502
+    }
+
+    pub fn mos6502_mut(&mut self) -> &mut Mos6502 {
+        &mut self.mos6502
+    }
 }
 
 /// Run the CPU for 1 CPU cycle.
-pub fn tick(state: &mut CpuState, bus: &mut CpuBus<'_>, is_apu_active_cycle: bool) {
+///
+/// If the debugger stops execution at an instruction boundary, the CPU stalls without doing
+/// anything until execution is resumed.
+pub fn tick(
+    state: &mut CpuState,
+    bus: &mut CpuBus<'_>,
+    is_apu_active_cycle: bool,
+    debug_hooks: &mut DebugHooks,
+) {
     if state.mos6502.frozen() {
         return;
     }
@@ -63,8 +84,26 @@ pub fn tick(state: &mut CpuState, bus: &mut CpuBus<'_>, is_apu_active_cycle: boo
                 } else {
                     State::OamDma(oam_dma_state)
                 }
+            } else if debug_hooks.checks_execution(DEBUG_CPU)
+                && !state.mos6502.is_mid_instruction()
+                && debug_hooks.check_execute(DEBUG_CPU, state.mos6502.pc().into())
+            {
+                // Stopped in the debugger
+                State::CpuExecuting
             } else {
-                state.mos6502.tick(bus);
+                let tracing = debug_hooks.is_tracing(DEBUG_CPU);
+                if tracing && !state.mos6502.is_mid_instruction() {
+                    state.mos6502.trace_instruction(bus, debug_hooks, DEBUG_CPU);
+                }
+
+                if debug_hooks.checks_accesses(DEBUG_CPU) {
+                    state.mos6502.tick(&mut DebugBus::new(bus, debug_hooks, DEBUG_CPU));
+                } else {
+                    state.mos6502.tick(bus);
+                }
+                if tracing {
+                    debug_hooks.add_trace_cycles(DEBUG_CPU, 1);
+                }
                 State::CpuExecuting
             }
         }
@@ -111,6 +111,7 @@ impl NesInputs {
                     joypad_state.set_button(button, pressed);
                 }
             }
+            _ => {}
         }
     }
 }
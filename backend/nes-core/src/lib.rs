@@ -2,7 +2,9 @@ pub mod api;
 mod apu;
 mod audio;
 mod bus;
+pub mod cheats;
 mod cpu;
 mod graphics;
 pub mod input;
+pub mod nsf;
 mod ppu;
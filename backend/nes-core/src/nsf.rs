@@ -0,0 +1,395 @@
+//! NSF and NSFe music file parsing
+//!
+//! An NSF file contains the sound driver and music data ripped from a game along with the addresses
+//! of the driver's init and play routines. NSF playback builds a synthetic cartridge around the
+//! file's data; see the `Nsf` board for details.
+//!
+//! NSFe files contain the same information in a chunk-based format that also supports per-track
+//! labels.
+
+use bincode::{Decode, Encode};
+use jgenesis_common::frontend::TimingMode;
+use jgenesis_common::num::GetBit;
+use thiserror::Error;
+
+const NSF_SIGNATURE: &[u8; 5] = b"NESM\x1A";
+const NSFE_SIGNATURE: &[u8; 4] = b"NSFE";
+
+const NSF_HEADER_LEN: usize = 0x80;
+
+// Play rates used when an NSFe file does not have a RATE chunk, in microseconds
+const NSFE_DEFAULT_NTSC_PLAY_SPEED: u16 = 16639;
+const NSFE_DEFAULT_PAL_PLAY_SPEED: u16 = 19997;
+
+#[derive(Debug, Error)]
+pub enum NsfError {
+    #[error("NSF file is too short ({len} bytes)")]
+    TooShort { len: usize },
+    #[error("NSF file has no songs")]
+    NoSongs,
+    #[error("NSF load address ${load_address:04X} is invalid for a file without bank switching")]
+    InvalidLoadAddress { load_address: u16 },
+    #[error("NSFe file is missing required chunk '{chunk_id}'")]
+    MissingChunk { chunk_id: &'static str },
+    #[error("NSFe chunk '{chunk_id}' extends past the end of the file")]
+    TruncatedChunk { chunk_id: String },
+    #[error("NSFe chunk '{chunk_id}' is too short ({len} bytes)")]
+    ChunkTooShort { chunk_id: &'static str, len: usize },
+    #[error("NSFe file contains unsupported required chunk '{chunk_id}'")]
+    UnsupportedChunk { chunk_id: String },
+}
+
+/// Which expansion audio chips an NSF file uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct NsfExpansionAudio {
+    pub vrc6: bool,
+    pub vrc7: bool,
+    pub fds: bool,
+    pub mmc5: bool,
+    pub namco163: bool,
+    pub sunsoft_5b: bool,
+}
+
+impl NsfExpansionAudio {
+    fn from_byte(byte: u8) -> Self {
+        Self {
+            vrc6: byte.bit(0),
+            vrc7: byte.bit(1),
+            fds: byte.bit(2),
+            mmc5: byte.bit(3),
+            namco163: byte.bit(4),
+            sunsoft_5b: byte.bit(5),
+        }
+    }
+
+    /// Names of the chips in use, for display purposes.
+    #[must_use]
+    pub fn chip_names(self) -> Vec<&'static str> {
+        [
+            (self.vrc6, "VRC6"),
+            (self.vrc7, "VRC7"),
+            (self.fds, "FDS"),
+            (self.mmc5, "MMC5"),
+            (self.namco163, "Namco 163"),
+            (self.sunsoft_5b, "Sunsoft 5B"),
+        ]
+        .into_iter()
+        .filter_map(|(present, name)| present.then_some(name))
+        .collect()
+    }
+}
+
+/// Descriptive information from an NSF or NSFe file. Empty strings indicate missing fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct NsfMetadata {
+    pub title: String,
+    pub artist: String,
+    pub copyright: String,
+    /// Only present in NSFe files
+    pub ripper: String,
+    pub total_tracks: u8,
+    /// 0-based index of the track to play first
+    pub starting_track: u8,
+    /// Per-track names; only present in NSFe files, and may have fewer entries than there are
+    /// tracks
+    pub track_labels: Vec<String>,
+    pub expansion_audio: NsfExpansionAudio,
+}
+
+impl NsfMetadata {
+    /// The label for the given 0-based track index, if the file has one.
+    #[must_use]
+    pub fn track_label(&self, track: u8) -> Option<&str> {
+        self.track_labels.get(track as usize).map(String::as_str).filter(|label| !label.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NsfRegion {
+    Ntsc,
+    Pal,
+    Dual,
+}
+
+impl NsfRegion {
+    fn from_byte(byte: u8) -> Self {
+        if byte.bit(1) {
+            Self::Dual
+        } else if byte.bit(0) {
+            Self::Pal
+        } else {
+            Self::Ntsc
+        }
+    }
+}
+
+/// The contents of an NSF or NSFe file.
+#[derive(Debug, Clone)]
+pub(crate) struct NsfFile {
+    pub(crate) metadata: NsfMetadata,
+    pub(crate) load_address: u16,
+    pub(crate) init_address: u16,
+    pub(crate) play_address: u16,
+    /// Play routine call period in microseconds
+    pub(crate) ntsc_play_speed: u16,
+    pub(crate) pal_play_speed: u16,
+    /// None if the file does not use bank switching
+    pub(crate) initial_banks: Option<[u8; 8]>,
+    region: NsfRegion,
+    pub(crate) data: Vec<u8>,
+}
+
+impl NsfFile {
+    /// Returns whether the given file appears to be an NSF or NSFe file rather than a ROM image.
+    pub(crate) fn is_nsf(file: &[u8]) -> bool {
+        file.starts_with(NSF_SIGNATURE) || file.starts_with(NSFE_SIGNATURE)
+    }
+
+    /// Parse an NSF or NSFe file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is truncated or otherwise invalid.
+    pub(crate) fn parse(file: &[u8]) -> Result<Self, NsfError> {
+        let nsf =
+            if file.starts_with(NSFE_SIGNATURE) { parse_nsfe(file)? } else { parse_nsf(file)? };
+
+        if nsf.metadata.total_tracks == 0 {
+            return Err(NsfError::NoSongs);
+        }
+
+        if nsf.initial_banks.is_none() && nsf.load_address < 0x8000 {
+            return Err(NsfError::InvalidLoadAddress { load_address: nsf.load_address });
+        }
+
+        Ok(nsf)
+    }
+
+    /// The timing mode that the file was written for. Files that support both NTSC and PAL play as
+    /// NTSC.
+    pub(crate) fn timing_mode(&self) -> TimingMode {
+        match self.region {
+            NsfRegion::Ntsc | NsfRegion::Dual => TimingMode::Ntsc,
+            NsfRegion::Pal => TimingMode::Pal,
+        }
+    }
+}
+
+fn parse_nsf(file: &[u8]) -> Result<NsfFile, NsfError> {
+    if file.len() < NSF_HEADER_LEN {
+        return Err(NsfError::TooShort { len: file.len() });
+    }
+
+    let header = &file[..NSF_HEADER_LEN];
+    let read_u16 = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+
+    let version = header[0x05];
+    let initial_banks: [u8; 8] = header[0x70..0x78].try_into().unwrap();
+
+    // NSF2 files can have metadata after the program data, in which case the header specifies the
+    // program data length
+    let data_len = u32::from_le_bytes([header[0x7D], header[0x7E], header[0x7F], 0]) as usize;
+    let data = if version >= 2 && data_len != 0 {
+        &file[NSF_HEADER_LEN..(NSF_HEADER_LEN + data_len).min(file.len())]
+    } else {
+        &file[NSF_HEADER_LEN..]
+    };
+
+    let metadata = NsfMetadata {
+        title: read_string(&header[0x0E..0x2E]),
+        artist: read_string(&header[0x2E..0x4E]),
+        copyright: read_string(&header[0x4E..0x6E]),
+        ripper: String::new(),
+        total_tracks: header[0x06],
+        starting_track: header[0x07].saturating_sub(1),
+        track_labels: vec![],
+        expansion_audio: NsfExpansionAudio::from_byte(header[0x7B]),
+    };
+
+    Ok(NsfFile {
+        metadata,
+        load_address: read_u16(0x08),
+        init_address: read_u16(0x0A),
+        play_address: read_u16(0x0C),
+        ntsc_play_speed: read_u16(0x6E),
+        pal_play_speed: read_u16(0x78),
+        initial_banks: initial_banks.iter().any(|&bank| bank != 0).then_some(initial_banks),
+        region: NsfRegion::from_byte(header[0x7A]),
+        data: data.to_vec(),
+    })
+}
+
+fn parse_nsfe(file: &[u8]) -> Result<NsfFile, NsfError> {
+    let mut info: Option<&[u8]> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut initial_banks = None;
+    let mut play_speeds = (NSFE_DEFAULT_NTSC_PLAY_SPEED, NSFE_DEFAULT_PAL_PLAY_SPEED);
+    let mut auth: Vec<String> = vec![];
+    let mut track_labels = vec![];
+
+    let mut position = NSFE_SIGNATURE.len();
+    while position + 8 <= file.len() {
+        let chunk_len = u32::from_le_bytes(file[position..position + 4].try_into().unwrap());
+        let chunk_id = &file[position + 4..position + 8];
+        let chunk_start = position + 8;
+        let chunk_end = chunk_start.saturating_add(chunk_len as usize);
+        if chunk_end > file.len() {
+            return Err(NsfError::TruncatedChunk {
+                chunk_id: String::from_utf8_lossy(chunk_id).into(),
+            });
+        }
+        let chunk = &file[chunk_start..chunk_end];
+        position = chunk_end;
+
+        match chunk_id {
+            b"INFO" => info = Some(chunk),
+            b"DATA" => data = Some(chunk),
+            b"BANK" => {
+                let mut banks = [0; 8];
+                let len = chunk.len().min(banks.len());
+                banks[..len].copy_from_slice(&chunk[..len]);
+                initial_banks = Some(banks);
+            }
+            b"RATE" => {
+                let read_u16 = |offset: usize| {
+                    chunk
+                        .get(offset..offset + 2)
+                        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+                };
+                play_speeds = (
+                    read_u16(0).unwrap_or(NSFE_DEFAULT_NTSC_PLAY_SPEED),
+                    read_u16(2).unwrap_or(NSFE_DEFAULT_PAL_PLAY_SPEED),
+                );
+            }
+            b"auth" => auth = read_string_list(chunk),
+            b"tlbl" => track_labels = read_string_list(chunk),
+            b"NEND" => break,
+            _ => {
+                // Chunks with IDs that start with an uppercase letter must be understood in order
+                // to play the file correctly
+                if chunk_id[0].is_ascii_uppercase() {
+                    return Err(NsfError::UnsupportedChunk {
+                        chunk_id: String::from_utf8_lossy(chunk_id).into(),
+                    });
+                }
+            }
+        }
+    }
+
+    let info = info.ok_or(NsfError::MissingChunk { chunk_id: "INFO" })?;
+    let data = data.ok_or(NsfError::MissingChunk { chunk_id: "DATA" })?;
+
+    if info.len() < 8 {
+        return Err(NsfError::ChunkTooShort { chunk_id: "INFO", len: info.len() });
+    }
+    let read_u16 = |offset: usize| u16::from_le_bytes([info[offset], info[offset + 1]]);
+
+    let mut auth = auth.into_iter();
+    let metadata = NsfMetadata {
+        title: auth.next().unwrap_or_default(),
+        artist: auth.next().unwrap_or_default(),
+        copyright: auth.next().unwrap_or_default(),
+        ripper: auth.next().unwrap_or_default(),
+        total_tracks: info.get(8).copied().unwrap_or(1),
+        starting_track: info.get(9).copied().unwrap_or(0),
+        track_labels,
+        expansion_audio: NsfExpansionAudio::from_byte(info[7]),
+    };
+
+    Ok(NsfFile {
+        metadata,
+        load_address: read_u16(0),
+        init_address: read_u16(2),
+        play_address: read_u16(4),
+        ntsc_play_speed: play_speeds.0,
+        pal_play_speed: play_speeds.1,
+        initial_banks,
+        region: NsfRegion::from_byte(info[6]),
+        data: data.to_vec(),
+    })
+}
+
+fn read_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().into()
+}
+
+fn read_string_list(bytes: &[u8]) -> Vec<String> {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    bytes.split(|&b| b == 0).map(read_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nsf_header() -> Vec<u8> {
+        let mut header = vec![0; NSF_HEADER_LEN];
+        header[..5].copy_from_slice(NSF_SIGNATURE);
+        header[0x05] = 1;
+        header[0x06] = 5;
+        header[0x07] = 2;
+        header[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x03, 0x80, 0x06, 0x80]);
+        header[0x0E..0x13].copy_from_slice(b"Title");
+        header[0x6E..0x70].copy_from_slice(&16639_u16.to_le_bytes());
+        header[0x7B] = 0x21;
+        header
+    }
+
+    #[test]
+    fn parse_nsf_header() {
+        let mut file = nsf_header();
+        file.extend([0x60; 16]);
+
+        let nsf = NsfFile::parse(&file).unwrap();
+        assert_eq!(nsf.metadata.title, "Title");
+        assert_eq!(nsf.metadata.total_tracks, 5);
+        assert_eq!(nsf.metadata.starting_track, 1);
+        assert_eq!(
+            nsf.metadata.expansion_audio,
+            NsfExpansionAudio { vrc6: true, sunsoft_5b: true, ..NsfExpansionAudio::default() }
+        );
+        assert_eq!(
+            (nsf.load_address, nsf.init_address, nsf.play_address),
+            (0x8000, 0x8003, 0x8006)
+        );
+        assert_eq!(nsf.initial_banks, None);
+        assert_eq!(nsf.data.len(), 16);
+    }
+
+    #[test]
+    fn parse_nsfe_chunks() {
+        let mut file = NSFE_SIGNATURE.to_vec();
+        let mut push_chunk = |id: &[u8; 4], data: &[u8]| {
+            file.extend((data.len() as u32).to_le_bytes());
+            file.extend(id);
+            file.extend(data);
+        };
+        push_chunk(b"INFO", &[0x00, 0x80, 0x03, 0x80, 0x06, 0x80, 0x00, 0x00, 3, 0]);
+        push_chunk(b"DATA", &[0x60; 8]);
+        push_chunk(b"auth", b"Game\0Artist\0\0Ripper\0");
+        push_chunk(b"tlbl", b"One\0Two\0");
+        push_chunk(b"text", b"ignored");
+        push_chunk(b"NEND", &[]);
+
+        let nsf = NsfFile::parse(&file).unwrap();
+        assert_eq!(nsf.metadata.title, "Game");
+        assert_eq!(nsf.metadata.artist, "Artist");
+        assert_eq!(nsf.metadata.copyright, "");
+        assert_eq!(nsf.metadata.ripper, "Ripper");
+        assert_eq!(nsf.metadata.total_tracks, 3);
+        assert_eq!(nsf.metadata.track_label(1), Some("Two"));
+        assert_eq!(nsf.metadata.track_label(2), None);
+        assert_eq!(nsf.ntsc_play_speed, NSFE_DEFAULT_NTSC_PLAY_SPEED);
+        assert_eq!(nsf.data, vec![0x60; 8]);
+    }
+
+    #[test]
+    fn nsfe_unknown_required_chunk() {
+        let mut file = NSFE_SIGNATURE.to_vec();
+        file.extend(0_u32.to_le_bytes());
+        file.extend(b"ABCD");
+
+        assert!(matches!(NsfFile::parse(&file), Err(NsfError::UnsupportedChunk { .. })));
+    }
+}
@@ -13,13 +13,20 @@ use genesis_core::input::InputState;
 use genesis_core::memory::{MainBus, MainBusSignals, MainBusWrites, Memory};
 use genesis_core::vdp::{Vdp, VdpTickEffect};
 use genesis_core::ym2612::{Ym2612, YmTickEffect};
-use genesis_core::{GenesisAspectRatio, GenesisEmulatorConfig, GenesisInputs, GenesisRegion};
+use genesis_core::{
+    GenesisAspectRatio, GenesisEmulatorConfig, GenesisInputs, GenesisRegion, M68K_DEBUG_CPU,
+    Z80_DEBUG_CPU,
+};
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, PartialClone, Renderer, SaveWriter, TickEffect, TimingMode,
 };
 use m68000_emu::M68000;
 use smsgg_core::psg::{Psg, PsgTickEffect, PsgVersion};
+use smsgg_core::vgm::{VgmChips, VgmLogger};
 use std::fmt::{Debug, Display};
+use std::mem;
 use std::path::Path;
 use thiserror::Error;
 use z80_emu::Z80;
@@ -34,6 +41,16 @@ pub(crate) const SEGA_CD_MASTER_CLOCK_RATE: u64 = 50_000_000;
 
 const BIOS_LEN: usize = memory::BIOS_LEN;
 
+/// Debugger CPU index of the sub 68000. The main 68000 and the Z80 use the same indices as in
+/// the Genesis core.
+pub const SUB_CPU_DEBUG_CPU: usize = 2;
+
+const DEBUG_CPUS: &[DebugCpu] = &[
+    DebugCpu { name: "Main 68000", arch: CpuArch::M68000 },
+    DebugCpu { name: "Z80", arch: CpuArch::Z80 },
+    DebugCpu { name: "Sub 68000", arch: CpuArch::M68000 },
+];
+
 #[derive(Debug, Error)]
 pub enum SegaCdLoadError {
     #[error("BIOS is required for Sega CD emulation")]
@@ -91,6 +108,9 @@ pub struct SegaCdEmulator {
     sega_cd_mclk_cycle_product: u64,
     sub_cpu_wait_cycles: u64,
     load_disc_into_ram: bool,
+    #[partial_clone(default)]
+    vgm_logger: VgmLogger,
+    debug_hooks: DebugHooks,
 }
 
 // This is a macro instead of a function so that it only mutably borrows the needed fields
@@ -102,6 +122,7 @@ macro_rules! new_main_bus {
             &mut $self.psg,
             &mut $self.ym2612,
             &mut $self.input,
+            &mut $self.vgm_logger,
             $self.timing_mode,
             MainBusSignals { z80_busack: $self.z80.stalled(), m68k_reset: $m68k_reset },
             std::mem::take(&mut $self.main_bus_writes),
@@ -202,6 +223,7 @@ impl SegaCdEmulator {
         let input = InputState::new(
             emulator_config.genesis.p1_controller_type,
             emulator_config.genesis.p2_controller_type,
+            emulator_config.genesis.multitap_pad_types,
         );
 
         let audio_resampler = AudioResampler::new(timing_mode);
@@ -229,6 +251,8 @@ impl SegaCdEmulator {
             sega_cd_mclk_cycle_product: 0,
             sub_cpu_wait_cycles: 0,
             load_disc_into_ram: emulator_config.load_disc_into_ram,
+            vgm_logger: VgmLogger::default(),
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
         };
 
         // Reset main CPU so that execution starts from the right place
@@ -240,10 +264,35 @@ impl SegaCdEmulator {
     #[inline]
     fn tick_sub_cpu(&mut self, mut sub_cpu_cycles: u64) {
         while sub_cpu_cycles >= self.sub_cpu_wait_cycles {
+            if self.debug_hooks.checks_execution(SUB_CPU_DEBUG_CPU)
+                && self.debug_hooks.check_execute(SUB_CPU_DEBUG_CPU, self.sub_cpu.pc())
+            {
+                // Drop the remaining cycles so that the sub CPU resumes from this instruction
+                self.sub_cpu_wait_cycles = 0;
+                return;
+            }
+
             let wait_cycles = self.sub_cpu_wait_cycles;
             let mut bus =
                 SubBus::new(&mut self.memory, &mut self.graphics_coprocessor, &mut self.pcm);
-            self.sub_cpu_wait_cycles = self.sub_cpu.execute_instruction(&mut bus).into();
+            let tracing = self.debug_hooks.is_tracing(SUB_CPU_DEBUG_CPU);
+            if tracing {
+                self.sub_cpu.trace_instruction(&mut bus, &mut self.debug_hooks, SUB_CPU_DEBUG_CPU);
+            }
+
+            let cycles = if self.debug_hooks.checks_accesses(SUB_CPU_DEBUG_CPU) {
+                self.sub_cpu.execute_instruction(&mut m68000_emu::debug::DebugBus::new(
+                    &mut bus,
+                    &mut self.debug_hooks,
+                    SUB_CPU_DEBUG_CPU,
+                ))
+            } else {
+                self.sub_cpu.execute_instruction(&mut bus)
+            };
+            if tracing {
+                self.debug_hooks.add_trace_cycles(SUB_CPU_DEBUG_CPU, cycles.into());
+            }
+            self.sub_cpu_wait_cycles = cycles.into();
             sub_cpu_cycles -= wait_cycles;
         }
 
@@ -264,6 +313,16 @@ impl SegaCdEmulator {
         &self.disc_title
     }
 
+    /// Returns data that identifies the current disc: the first sector of track 1 followed by the
+    /// disc's TOC. Returns `None` if there is no disc.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the disc drive is unable to read from the disc.
+    pub fn disc_identifier(&mut self) -> SegaCdLoadResult<Option<Vec<u8>>> {
+        self.memory.medium_mut().disc_identifier()
+    }
+
     pub fn remove_disc(&mut self) {
         self.memory.medium_mut().remove_disc();
         self.disc_title = "(no disc)".into();
@@ -291,6 +350,34 @@ impl SegaCdEmulator {
     pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         self.vdp.copy_vram(out, palette, row_len);
     }
+
+    /// Start logging PSG and YM2612 writes in VGM format, replacing any log in progress. RF5C164
+    /// PCM and CD-DA audio are not logged.
+    pub fn start_vgm_logging(&mut self) {
+        let chips = VgmChips::new(self.timing_mode, true, false, true);
+        self.vgm_logger.start(chips, 1);
+        self.log_vgm_registers();
+    }
+
+    // Log the current sound chip registers so that an active VGM log matches the emulator state
+    fn log_vgm_registers(&mut self) {
+        if !self.vgm_logger.is_active() {
+            return;
+        }
+
+        self.psg.log_registers(&mut self.vgm_logger);
+        self.ym2612.log_registers(&mut self.vgm_logger);
+    }
+
+    pub fn vgm_logger_mut(&mut self) -> &mut VgmLogger {
+        &mut self.vgm_logger
+    }
+
+    /// Replace the active cheats. Sega CD games use the Genesis cheat code formats; use
+    /// [`genesis_core::cheats::decode_cheat`] to decode cheat codes.
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.memory.set_cheats(cheats);
+    }
 }
 
 impl EmulatorTrait for SegaCdEmulator {
@@ -318,10 +405,32 @@ impl EmulatorTrait for SegaCdEmulator {
         S: SaveWriter,
         S::Err: Debug + Display + Send + Sync + 'static,
     {
+        if self.debug_hooks.checks_execution(M68K_DEBUG_CPU)
+            && self.debug_hooks.check_execute(M68K_DEBUG_CPU, self.main_cpu.pc())
+        {
+            return Ok(TickEffect::None);
+        }
+
         let mut main_bus = new_main_bus!(self, m68k_reset: false);
 
         // Main 68000
-        let main_cpu_cycles = self.main_cpu.execute_instruction(&mut main_bus);
+        let m68k_tracing = self.debug_hooks.is_tracing(M68K_DEBUG_CPU);
+        if m68k_tracing {
+            self.main_cpu.trace_instruction(&mut main_bus, &mut self.debug_hooks, M68K_DEBUG_CPU);
+        }
+
+        let main_cpu_cycles = if self.debug_hooks.checks_accesses(M68K_DEBUG_CPU) {
+            self.main_cpu.execute_instruction(&mut m68000_emu::debug::DebugBus::new(
+                &mut main_bus,
+                &mut self.debug_hooks,
+                M68K_DEBUG_CPU,
+            ))
+        } else {
+            self.main_cpu.execute_instruction(&mut main_bus)
+        };
+        if m68k_tracing {
+            self.debug_hooks.add_trace_cycles(M68K_DEBUG_CPU, main_cpu_cycles.into());
+        }
 
         let genesis_mclk_elapsed = u64::from(main_cpu_cycles) * MAIN_CPU_DIVIDER;
         let z80_cycles = (self.genesis_mclk_cycles + genesis_mclk_elapsed) / Z80_DIVIDER
@@ -329,8 +438,30 @@ impl EmulatorTrait for SegaCdEmulator {
         self.genesis_mclk_cycles += genesis_mclk_elapsed;
 
         // Z80
+        let z80_tracing = self.debug_hooks.is_tracing(Z80_DEBUG_CPU);
         for _ in 0..z80_cycles {
-            self.z80.tick(&mut main_bus);
+            if self.z80.at_instruction_boundary()
+                && self.debug_hooks.checks_execution(Z80_DEBUG_CPU)
+            {
+                if self.debug_hooks.check_execute(Z80_DEBUG_CPU, self.z80.pc().into()) {
+                    break;
+                }
+
+                self.z80.trace_instruction(&mut main_bus, &mut self.debug_hooks, Z80_DEBUG_CPU);
+            }
+
+            if self.debug_hooks.checks_accesses(Z80_DEBUG_CPU) {
+                self.z80.tick(&mut z80_emu::debug::DebugBus::new(
+                    &mut main_bus,
+                    &mut self.debug_hooks,
+                    Z80_DEBUG_CPU,
+                ));
+            } else {
+                self.z80.tick(&mut main_bus);
+            }
+            if z80_tracing {
+                self.debug_hooks.add_trace_cycles(Z80_DEBUG_CPU, 1);
+            }
         }
 
         self.main_bus_writes = main_bus.take_writes();
@@ -378,6 +509,8 @@ impl EmulatorTrait for SegaCdEmulator {
         // Apply main CPU writes after ticking the sub CPU; this fixes random freezing in Silpheed
         self.main_bus_writes = new_main_bus!(self, m68k_reset: false).apply_writes();
 
+        self.vgm_logger.advance_cycles(genesis_mclk_elapsed);
+
         // Input state (for 6-button controller reset)
         self.input.tick(main_cpu_cycles);
 
@@ -407,6 +540,7 @@ impl EmulatorTrait for SegaCdEmulator {
         self.audio_resampler.output_samples(audio_output).map_err(SegaCdError::Audio)?;
 
         // VDP
+        self.vdp.set_light_gun_position(self.input.light_gun_position());
         if self.vdp.tick(genesis_mclk_elapsed, &mut self.memory) == VdpTickEffect::FrameComplete {
             self.render_frame(renderer).map_err(SegaCdError::Render)?;
 
@@ -424,6 +558,8 @@ impl EmulatorTrait for SegaCdEmulator {
                     .map_err(SegaCdError::SaveWrite)?;
             }
 
+            self.memory.apply_ram_cheats();
+
             return Ok(TickEffect::FrameRendered);
         }
 
@@ -452,6 +588,10 @@ impl EmulatorTrait for SegaCdEmulator {
 
     fn take_rom_from(&mut self, other: &mut Self) {
         self.memory.medium_mut().take_rom_from(other.memory.medium_mut());
+        self.memory.set_cheats(other.memory.take_cheats());
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
+        self.vgm_logger = mem::take(&mut other.vgm_logger);
+        self.log_vgm_registers();
     }
 
     fn soft_reset(&mut self) {
@@ -473,6 +613,10 @@ impl EmulatorTrait for SegaCdEmulator {
         let enable_ram_cartridge = sega_cd.get_enable_ram_cartridge();
         let vdp_config = self.vdp.config();
         let (p1_controller_type, p2_controller_type) = self.input.controller_types();
+        let multitap_pad_types = self.input.multitap_pad_types();
+        let vgm_logger = mem::take(&mut self.vgm_logger);
+        let cheats = self.memory.take_cheats();
+        let debug_hooks = mem::take(&mut self.debug_hooks);
 
         *self = Self::create_from_disc(
             bios,
@@ -490,6 +634,7 @@ impl EmulatorTrait for SegaCdEmulator {
                     quantize_ym2612_output: self.ym2612.get_quantize_output(),
                     p1_controller_type,
                     p2_controller_type,
+                    multitap_pad_types,
                 },
                 enable_ram_cartridge,
                 load_disc_into_ram: self.load_disc_into_ram,
@@ -497,9 +642,110 @@ impl EmulatorTrait for SegaCdEmulator {
             save_writer,
         )
         .expect("Hard reset should not cause an I/O error");
+        self.vgm_logger = vgm_logger;
+        self.memory.set_cheats(cheats);
+        self.debug_hooks = debug_hooks;
+        self.log_vgm_registers();
     }
 
     fn timing_mode(&self) -> TimingMode {
         self.timing_mode
     }
 }
+
+impl Debuggable for SegaCdEmulator {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, cpu: usize) -> Vec<u32> {
+        match cpu {
+            M68K_DEBUG_CPU => self.main_cpu.debug_registers().to_vec(),
+            Z80_DEBUG_CPU => self.z80.debug_registers().map(u32::from).to_vec(),
+            SUB_CPU_DEBUG_CPU => self.sub_cpu.debug_registers().to_vec(),
+            _ => vec![],
+        }
+    }
+
+    fn write_registers(&mut self, cpu: usize, registers: &[u32]) {
+        match cpu {
+            M68K_DEBUG_CPU => {
+                if let Ok(registers) = registers.try_into() {
+                    self.main_cpu.set_debug_registers(registers);
+                }
+            }
+            Z80_DEBUG_CPU => {
+                if let Ok(registers) = <[u32; 13]>::try_from(registers) {
+                    self.z80.set_debug_registers(registers.map(|value| value as u16));
+                }
+            }
+            SUB_CPU_DEBUG_CPU => {
+                if let Ok(registers) = registers.try_into() {
+                    self.sub_cpu.set_debug_registers(registers);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_memory(&mut self, cpu: usize, address: u32) -> u8 {
+        if cpu == SUB_CPU_DEBUG_CPU {
+            let mut bus =
+                SubBus::new(&mut self.memory, &mut self.graphics_coprocessor, &mut self.pcm);
+            return m68000_emu::BusInterface::read_byte(&mut bus, address);
+        }
+
+        let mut bus = new_main_bus!(self, m68k_reset: false);
+        let value = match cpu {
+            Z80_DEBUG_CPU => bus.peek_z80_byte(address as u16),
+            _ => bus.peek_m68k_byte(address),
+        };
+        self.main_bus_writes = bus.take_writes();
+
+        value
+    }
+
+    fn write_memory(&mut self, cpu: usize, address: u32, value: u8) {
+        if cpu == SUB_CPU_DEBUG_CPU {
+            let mut bus =
+                SubBus::new(&mut self.memory, &mut self.graphics_coprocessor, &mut self.pcm);
+            m68000_emu::BusInterface::write_byte(&mut bus, address, value);
+            return;
+        }
+
+        let mut bus = new_main_bus!(self, m68k_reset: false);
+        match cpu {
+            Z80_DEBUG_CPU => z80_emu::BusInterface::write_memory(&mut bus, address as u16, value),
+            _ => m68000_emu::BusInterface::write_byte(&mut bus, address, value),
+        }
+        self.main_bus_writes = bus.apply_writes();
+    }
+
+    fn disassemble(&mut self, cpu: usize, address: u32) -> (String, u32) {
+        if cpu == SUB_CPU_DEBUG_CPU {
+            let mut bus =
+                SubBus::new(&mut self.memory, &mut self.graphics_coprocessor, &mut self.pcm);
+            return m68000_emu::disassemble::disassemble(&mut bus, address);
+        }
+
+        let mut bus = new_main_bus!(self, m68k_reset: false);
+        let disassembled = match cpu {
+            Z80_DEBUG_CPU => {
+                let (text, len) = z80_emu::disassemble::disassemble(&mut bus, address as u16);
+                (text, len.into())
+            }
+            _ => m68000_emu::disassemble::disassemble(&mut bus, address),
+        };
+        self.main_bus_writes = bus.apply_writes();
+
+        disassembled
+    }
+}
@@ -135,6 +135,10 @@ impl CdController {
         self.drive.disc_title(region)
     }
 
+    pub fn disc_identifier(&mut self) -> SegaCdLoadResult<Option<Vec<u8>>> {
+        self.drive.disc_identifier()
+    }
+
     pub fn take_disc(&mut self) -> Option<CdRom> {
         self.drive.take_disc()
     }
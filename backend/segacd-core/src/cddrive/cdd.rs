@@ -4,7 +4,7 @@ use crate::api::SegaCdLoadResult;
 use crate::cddrive::cdc::Rchip;
 use bincode::{Decode, Encode};
 use cdrom::cdtime::CdTime;
-use cdrom::cue::{Track, TrackType};
+use cdrom::cue::{Track, TrackMode, TrackType};
 use cdrom::reader::{CdRom, CdRomFileFormat};
 use genesis_core::GenesisRegion;
 use jgenesis_proc_macros::PartialClone;
@@ -800,6 +800,31 @@ impl CdDrive {
         Ok(Some(whitespace_re.replace_all(title.trim(), " ").to_string()))
     }
 
+    /// Returns the first sector of track 1 followed by the disc's TOC, or `None` if there is no
+    /// disc in the drive.
+    pub fn disc_identifier(&mut self) -> SegaCdLoadResult<Option<Vec<u8>>> {
+        let Some(disc) = &mut self.disc else { return Ok(None) };
+
+        disc.read_sector(1, CdTime::SECTOR_0_START, &mut self.sector_buffer)?;
+        let mut identifier = self.sector_buffer.to_vec();
+
+        let cue = disc.cue();
+        for track_number in 1..=cue.last_track().number {
+            let track = cue.track(track_number);
+            let mode = match track.mode {
+                TrackMode::Mode1 => 1,
+                TrackMode::Mode2 => 2,
+                TrackMode::Audio => 0,
+            };
+
+            identifier.extend([track.number, mode]);
+            identifier.extend(track.start_time.to_sector_number().to_le_bytes());
+            identifier.extend(track.end_time.to_sector_number().to_le_bytes());
+        }
+
+        Ok(Some(identifier))
+    }
+
     pub fn take_disc(&mut self) -> Option<CdRom> {
         self.disc.take()
     }
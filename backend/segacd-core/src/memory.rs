@@ -510,6 +510,10 @@ impl SegaCd {
         self.disc_drive.disc_title(self.region())
     }
 
+    pub fn disc_identifier(&mut self) -> SegaCdLoadResult<Option<Vec<u8>>> {
+        self.disc_drive.disc_identifier()
+    }
+
     pub fn word_ram_mut(&mut self) -> &mut WordRam {
         &mut self.word_ram
     }
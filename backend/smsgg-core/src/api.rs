@@ -2,23 +2,31 @@
 
 use crate::audio::AudioResampler;
 use crate::bus::Bus;
-use crate::input::InputState;
+use crate::input::{InputState, SmsControllerType};
 use crate::memory::Memory;
 use crate::psg::{Psg, PsgTickEffect, PsgVersion};
 use crate::vdp::{Vdp, VdpBuffer, VdpTickEffect};
+use crate::vgm::{VgmChips, VgmLogger};
 use crate::ym2413::Ym2413;
-use crate::{vdp, SmsGgInputs, VdpVersion};
+use crate::{vdp, vgm, SmsGgInputs, VdpVersion};
 use bincode::{Decode, Encode};
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
     SaveWriter, TickEffect, TimingMode,
 };
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr, FakeDecode, FakeEncode};
 use std::fmt::{Debug, Display};
+use std::mem;
 use std::ops::{Deref, DerefMut};
 use thiserror::Error;
 use z80_emu::{InterruptMode, Z80};
 
+const Z80_DEBUG_CPU: usize = 0;
+
+const DEBUG_CPUS: &[DebugCpu] = &[DebugCpu { name: "Z80", arch: CpuArch::Z80 }];
+
 #[derive(Debug, Error)]
 pub enum SmsGgError<RErr, AErr, SErr> {
     #[error("Rendering error: {0}")]
@@ -79,6 +87,8 @@ pub struct SmsGgEmulatorConfig {
     pub sms_crop_left_border: bool,
     pub fm_sound_unit_enabled: bool,
     pub overclock_z80: bool,
+    pub p1_controller_type: SmsControllerType,
+    pub p2_controller_type: SmsControllerType,
 }
 
 #[derive(Debug, Clone, Encode, Decode, PartialClone)]
@@ -101,6 +111,9 @@ pub struct SmsGgEmulator {
     vdp_cycles_remainder: u32,
     frame_count: u64,
     reset_frames_remaining: u32,
+    #[partial_clone(default)]
+    vgm_logger: VgmLogger,
+    debug_hooks: DebugHooks,
 }
 
 impl SmsGgEmulator {
@@ -115,7 +128,11 @@ impl SmsGgEmulator {
         let memory = Memory::new(rom, cartridge_ram);
         let vdp = Vdp::new(config.vdp_version, config.remove_sprite_limit);
         let psg = Psg::new(config.psg_version);
-        let input = InputState::new(config.sms_region);
+        let input = InputState::new(
+            config.sms_region,
+            config.p1_controller_type,
+            config.p2_controller_type,
+        );
 
         let mut z80 = Z80::new();
         init_z80(&mut z80);
@@ -141,6 +158,8 @@ impl SmsGgEmulator {
             vdp_cycles_remainder: 0,
             frame_count: 0,
             reset_frames_remaining: 0,
+            vgm_logger: VgmLogger::default(),
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
         }
     }
 
@@ -155,6 +174,25 @@ impl SmsGgEmulator {
         self.memory.cartridge_has_battery()
     }
 
+    // Convert the Light Phaser position from the rendered frame to the uncropped frame
+    fn light_phaser_position(&self) -> Option<(u16, u16)> {
+        let (x, y) = self.input.light_phaser_position()?;
+
+        let viewport = self.vdp_version.viewport_size();
+        let x = if self.vdp_version.is_master_system() && self.sms_crop_left_border {
+            x + viewport.left_border_width
+        } else {
+            x
+        };
+        let y = if self.vdp_version.is_master_system() && self.sms_crop_vertical_border {
+            y + viewport.top_border_height
+        } else {
+            y
+        };
+
+        Some((x, y))
+    }
+
     fn render_frame<R: Renderer>(&mut self, renderer: &mut R) -> Result<(), R::Err> {
         let crop_vertical_border =
             self.vdp_version.is_master_system() && self.sms_crop_vertical_border;
@@ -187,6 +225,35 @@ impl SmsGgEmulator {
         self.vdp.copy_cram(out);
     }
 
+    /// Start logging PSG and YM2413 writes in VGM format, replacing any log in progress.
+    pub fn start_vgm_logging(&mut self) {
+        let chips = VgmChips::new(self.vdp.timing_mode(), true, self.ym2413.is_some(), false);
+        self.vgm_logger.start(chips, vgm::PSG_MCLK_DIVIDER);
+        self.log_vgm_registers();
+    }
+
+    // Log the current sound chip registers so that an active VGM log matches the emulator state
+    fn log_vgm_registers(&mut self) {
+        if !self.vgm_logger.is_active() {
+            return;
+        }
+
+        self.psg.log_registers(&mut self.vgm_logger);
+        if let Some(ym2413) = &self.ym2413 {
+            ym2413.log_registers(&mut self.vgm_logger);
+        }
+    }
+
+    pub fn vgm_logger_mut(&mut self) -> &mut VgmLogger {
+        &mut self.vgm_logger
+    }
+
+    /// Replace the active cheats. Use [`cheats::decode_cheat`](crate::cheats::decode_cheat) to
+    /// decode cheat codes.
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.memory.set_cheats(cheats);
+    }
+
     pub fn copy_vram(&self, out: &mut [Color], palette: u8, row_len: usize) {
         self.vdp.copy_vram(out, palette, row_len);
     }
@@ -228,14 +295,38 @@ impl EmulatorTrait for SmsGgEmulator {
         A: AudioOutput,
         S: SaveWriter,
     {
-        let t_cycles = self.z80.execute_instruction(&mut Bus::new(
+        if self.debug_hooks.checks_execution(Z80_DEBUG_CPU)
+            && self.debug_hooks.check_execute(Z80_DEBUG_CPU, self.z80.pc().into())
+        {
+            return Ok(TickEffect::None);
+        }
+
+        let mut bus = Bus::new(
             self.vdp_version,
             &mut self.memory,
             &mut self.vdp,
             &mut self.psg,
             self.ym2413.as_mut(),
             &mut self.input,
-        ));
+            &mut self.vgm_logger,
+        );
+        let tracing = self.debug_hooks.is_tracing(Z80_DEBUG_CPU);
+        if tracing {
+            self.z80.trace_instruction(&mut bus, &mut self.debug_hooks, Z80_DEBUG_CPU);
+        }
+
+        let t_cycles = if self.debug_hooks.checks_accesses(Z80_DEBUG_CPU) {
+            self.z80.execute_instruction(&mut z80_emu::debug::DebugBus::new(
+                &mut bus,
+                &mut self.debug_hooks,
+                Z80_DEBUG_CPU,
+            ))
+        } else {
+            self.z80.execute_instruction(&mut bus)
+        };
+        if tracing {
+            self.debug_hooks.add_trace_cycles(Z80_DEBUG_CPU, t_cycles.into());
+        }
         let (t_cycles, remainder) = if self.overclock_z80 {
             // Emulate a Z80 running at 2x speed by only ticking the rest of the components for
             // half as many cycles
@@ -246,6 +337,8 @@ impl EmulatorTrait for SmsGgEmulator {
         };
         self.z80_cycles_remainder = remainder;
 
+        self.vgm_logger.advance_cycles(t_cycles.into());
+
         for _ in 0..t_cycles {
             if let Some(ym2413) = &mut self.ym2413 {
                 ym2413.tick();
@@ -268,6 +361,8 @@ impl EmulatorTrait for SmsGgEmulator {
         let t_cycles_plus_leftover = t_cycles + self.vdp_cycles_remainder;
         self.vdp_cycles_remainder = t_cycles_plus_leftover % 2;
 
+        self.vdp.set_light_phaser_position(self.light_phaser_position());
+
         let mut frame_rendered = false;
         let vdp_cycles = t_cycles_plus_leftover / 2 * 3;
         for _ in 0..vdp_cycles {
@@ -281,6 +376,8 @@ impl EmulatorTrait for SmsGgEmulator {
                 self.input.set_reset(self.reset_frames_remaining != 0);
                 self.reset_frames_remaining = self.reset_frames_remaining.saturating_sub(1);
 
+                self.memory.apply_ram_cheats();
+
                 self.frame_count += 1;
                 if self.frame_count % 60 == 0
                     && self.memory.cartridge_has_battery()
@@ -311,6 +408,7 @@ impl EmulatorTrait for SmsGgEmulator {
         self.pixel_aspect_ratio = config.pixel_aspect_ratio;
         self.vdp.set_remove_sprite_limit(config.remove_sprite_limit);
         self.input.set_region(config.sms_region);
+        self.input.set_controller_types(config.p1_controller_type, config.p2_controller_type);
         self.sms_crop_vertical_border = config.sms_crop_vertical_border;
         self.sms_crop_left_border = config.sms_crop_left_border;
         self.overclock_z80 = config.overclock_z80;
@@ -319,6 +417,10 @@ impl EmulatorTrait for SmsGgEmulator {
 
     fn take_rom_from(&mut self, other: &mut Self) {
         self.memory.take_rom_from(&mut other.memory);
+        self.memory.set_cheats(other.memory.take_cheats());
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
+        self.vgm_logger = mem::take(&mut other.vgm_logger);
+        self.log_vgm_registers();
     }
 
     fn soft_reset(&mut self) {
@@ -333,17 +435,22 @@ impl EmulatorTrait for SmsGgEmulator {
         log::info!("Hard resetting console");
 
         let (rom, ram) = self.memory.take_cartridge_rom_and_ram();
+        let cheats = self.memory.take_cheats();
         self.memory = Memory::new(rom, Some(ram));
+        self.memory.set_cheats(cheats);
 
         self.z80 = Z80::new();
         init_z80(&mut self.z80);
 
         self.vdp = Vdp::new(self.vdp_version, self.vdp.get_remove_sprite_limit());
         self.psg = Psg::new(self.psg.version());
-        self.input = InputState::new(self.input.region());
+        let (p1_controller_type, p2_controller_type) = self.input.controller_types();
+        self.input = InputState::new(self.input.region(), p1_controller_type, p2_controller_type);
 
         self.vdp_cycles_remainder = 0;
         self.frame_count = 0;
+
+        self.log_vgm_registers();
     }
 
     fn timing_mode(&self) -> TimingMode {
@@ -351,6 +458,52 @@ impl EmulatorTrait for SmsGgEmulator {
     }
 }
 
+impl Debuggable for SmsGgEmulator {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, _cpu: usize) -> Vec<u32> {
+        self.z80.debug_registers().map(u32::from).to_vec()
+    }
+
+    fn write_registers(&mut self, _cpu: usize, registers: &[u32]) {
+        if let Ok(registers) = <[u32; 13]>::try_from(registers) {
+            self.z80.set_debug_registers(registers.map(|value| value as u16));
+        }
+    }
+
+    fn read_memory(&mut self, _cpu: usize, address: u32) -> u8 {
+        self.memory.read(address as u16)
+    }
+
+    fn write_memory(&mut self, _cpu: usize, address: u32, value: u8) {
+        self.memory.write(address as u16, value);
+    }
+
+    fn disassemble(&mut self, _cpu: usize, address: u32) -> (String, u32) {
+        let mut bus = Bus::new(
+            self.vdp_version,
+            &mut self.memory,
+            &mut self.vdp,
+            &mut self.psg,
+            self.ym2413.as_mut(),
+            &mut self.input,
+            &mut self.vgm_logger,
+        );
+        let (text, len) = z80_emu::disassemble::disassemble(&mut bus, address as u16);
+        (text, len.into())
+    }
+}
+
 fn populate_frame_buffer(
     vdp_buffer: &VdpBuffer,
     vdp_version: VdpVersion,
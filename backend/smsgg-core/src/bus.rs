@@ -4,6 +4,7 @@ use crate::input::InputState;
 use crate::memory::Memory;
 use crate::psg::Psg;
 use crate::vdp::Vdp;
+use crate::vgm::VgmLogger;
 use crate::ym2413::Ym2413;
 use crate::VdpVersion;
 use jgenesis_common::num::GetBit;
@@ -16,6 +17,7 @@ pub struct Bus<'a> {
     psg: &'a mut Psg,
     ym2413: Option<&'a mut Ym2413>,
     input: &'a mut InputState,
+    vgm_logger: &'a mut VgmLogger,
 }
 
 impl<'a> Bus<'a> {
@@ -26,8 +28,9 @@ impl<'a> Bus<'a> {
         psg: &'a mut Psg,
         ym2413: Option<&'a mut Ym2413>,
         input: &'a mut InputState,
+        vgm_logger: &'a mut VgmLogger,
     ) -> Self {
-        Self { version, memory, vdp, psg, ym2413, input }
+        Self { version, memory, vdp, psg, ym2413, input, vgm_logger }
     }
 }
 
@@ -67,9 +70,8 @@ impl<'a> BusInterface for Bus<'a> {
                 self.vdp.v_counter()
             }
             (false, true, true) => {
-                // TODO H counter
                 log::trace!("H counter read");
-                0x00
+                self.vdp.h_counter()
             }
             (true, false, false) => {
                 log::trace!("VDP data read");
@@ -81,11 +83,11 @@ impl<'a> BusInterface for Bus<'a> {
             }
             (true, true, false) => {
                 log::trace!("I/O A/B read");
-                self.input.port_dc()
+                self.input.port_dc(self.vdp.light_phaser_sensed())
             }
             (true, true, true) => {
                 log::trace!("I/O B/misc. read");
-                self.input.port_dd()
+                self.input.port_dd(self.vdp.light_phaser_sensed())
             }
         }
     }
@@ -94,6 +96,7 @@ impl<'a> BusInterface for Bus<'a> {
         let address = address & 0xFF;
         if self.version == VdpVersion::GameGear && address <= 0x06 {
             if address == 0x06 {
+                self.vgm_logger.log_gg_stereo_write(value);
                 self.psg.write_stereo_control(value);
             }
             return;
@@ -102,10 +105,12 @@ impl<'a> BusInterface for Bus<'a> {
         if let Some(ym2413) = &mut self.ym2413 {
             match address {
                 0xF0 => {
+                    self.vgm_logger.log_ym2413_register_select(value);
                     ym2413.select_register(value);
                     return;
                 }
                 0xF1 => {
+                    self.vgm_logger.log_ym2413_data_write(value);
                     ym2413.write_data(value);
                     return;
                 }
@@ -124,10 +129,13 @@ impl<'a> BusInterface for Bus<'a> {
             }
             (false, false, true) => {
                 log::trace!("I/O control write: {value:02X}");
-                self.input.write_control(value);
+                if self.input.write_control(value) {
+                    self.vdp.latch_h_counter();
+                }
             }
             (false, true, _) => {
                 log::trace!("PSG write: {value:02X}");
+                self.vgm_logger.log_psg_write(value);
                 self.psg.write(value);
             }
             (true, false, false) => {
@@ -0,0 +1,32 @@
+//! Master System / Game Gear cheat code decoding
+//!
+//! Only raw `AAAA:VV` codes are supported. Addresses $0000-$BFFF patch ROM reads and addresses
+//! $C000-$FFFF are written to system RAM every frame.
+
+use jgenesis_common::cheats::{parse_hex, split_raw_code, CheatError, CheatPatch, CheatResult};
+
+/// Decode a raw cheat code.
+///
+/// # Errors
+///
+/// Returns an error if the code is not in a recognized format.
+pub fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+    let code = code.trim().to_ascii_uppercase();
+
+    let Some((address, value)) = split_raw_code(&code) else {
+        return Err(CheatError::UnrecognizedFormat(code));
+    };
+
+    let address = parse_hex(&code, address)?;
+    let value = parse_hex(&code, value)?;
+    if address > 0xFFFF || value > 0xFF {
+        return Err(CheatError::UnrecognizedFormat(code));
+    }
+
+    let patch = if address < 0xC000 {
+        CheatPatch::rom(address, value as u8, None)
+    } else {
+        CheatPatch::ram(address, value as u8)
+    };
+    Ok(vec![patch])
+}
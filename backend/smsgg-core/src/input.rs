@@ -2,11 +2,12 @@
 
 use crate::api::SmsRegion;
 use bincode::{Decode, Encode};
+use jgenesis_common::input::Player;
 use jgenesis_common::num::GetBit;
-use jgenesis_proc_macros::define_controller_inputs;
+use jgenesis_proc_macros::{define_controller_inputs, EnumDisplay, EnumFromStr};
 
 define_controller_inputs! {
-    enum SmsGgButton {
+    enum SmsGgControllerButton {
         Up,
         Left,
         Right,
@@ -20,14 +21,109 @@ define_controller_inputs! {
     struct SmsGgJoypadState {
         buttons!
     }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightPhaserButton {
+    Trigger,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaddleButton {
+    Button,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SportsPadButton {
+    Button1,
+    Button2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsGgButton {
+    Controller(SmsGgControllerButton),
+    LightPhaser(LightPhaserButton),
+    Paddle(PaddleButton),
+    SportsPad(SportsPadButton),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct LightPhaserState {
+    pub trigger: bool,
+    // Position in frame pixels, or None if offscreen. Positions outside of the active display area
+    // are treated as offscreen
+    pub position: Option<(u16, u16)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct PaddleState {
+    pub button: bool,
+    // Knob position, from 0 (turned fully left) to 255 (turned fully right)
+    pub position: u8,
+}
 
-    struct SmsGgInputs {
-        p1: Player::One,
-        p2: Player::Two,
-        pause: Button::Pause,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct SportsPadState {
+    pub button1: bool,
+    pub button2: bool,
+    // Pointer position in frame pixels, or None if the pointer is outside of the frame.
+    // The Sports Pad reports how far this position has moved each time the game reads it
+    pub position: Option<(u16, u16)>,
+}
+
+/// Inputs for every supported device. Which of these the console sees depends on the controller
+/// type configured for each port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct SmsGgInputs {
+    pub p1: SmsGgJoypadState,
+    pub p2: SmsGgJoypadState,
+    pub pause: bool,
+    pub light_phaser: LightPhaserState,
+    pub paddle: PaddleState,
+    pub sports_pad: SportsPadState,
+}
+
+impl SmsGgInputs {
+    #[inline]
+    pub fn set_button(&mut self, button: SmsGgButton, player: Player, pressed: bool) {
+        match (button, player) {
+            (SmsGgButton::Controller(SmsGgControllerButton::Pause), _) => self.pause = pressed,
+            (SmsGgButton::Controller(button), Player::One) => self.p1.set_button(button, pressed),
+            (SmsGgButton::Controller(button), Player::Two) => self.p2.set_button(button, pressed),
+            (SmsGgButton::Controller(_), _) => {}
+            (SmsGgButton::LightPhaser(LightPhaserButton::Trigger), _) => {
+                self.light_phaser.trigger = pressed;
+            }
+            (SmsGgButton::Paddle(PaddleButton::Button), _) => self.paddle.button = pressed,
+            (SmsGgButton::SportsPad(SportsPadButton::Button1), _) => {
+                self.sports_pad.button1 = pressed;
+            }
+            (SmsGgButton::SportsPad(SportsPadButton::Button2), _) => {
+                self.sports_pad.button2 = pressed;
+            }
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_button(mut self, button: SmsGgButton, player: Player, pressed: bool) -> Self {
+        self.set_button(button, player, pressed);
+        self
     }
 }
 
+/// Device connected to a Master System controller port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode, EnumDisplay, EnumFromStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SmsControllerType {
+    #[default]
+    Gamepad,
+    LightPhaser,
+    /// HPD-200 Paddle Control
+    Paddle,
+    SportsPad,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 enum PinDirection {
     Input,
@@ -43,25 +139,164 @@ impl PinDirection {
     }
 }
 
+// Bits are in order from D3 to D0
+fn active_low_nibble(bits: [bool; 4]) -> u8 {
+    bits.into_iter().fold(0, |nibble, bit| (nibble << 1) | u8::from(!bit))
+}
+
+// Largest motion that the Sports Pad can report on each axis
+const SPORTS_PAD_MAX_DELTA: i32 = 127;
+
+/// Sports Pad read protocol.
+///
+/// The game toggles TH to step through 4 nibbles on D3-D0: the high and low nibbles of the X
+/// motion, and then the high and low nibbles of the Y motion. Motion is latched when the game
+/// starts reading the X motion, and it is positive when the trackball rolls left or up.
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct SportsPadHandshake {
+    phase: u8,
+    delta_x: u8,
+    delta_y: u8,
+    last_position: Option<(u16, u16)>,
+}
+
+impl SportsPadHandshake {
+    fn handle_th_change(&mut self, position: Option<(u16, u16)>) {
+        self.phase = (self.phase + 1) & 0x03;
+        if self.phase == 1 {
+            self.latch_motion(position);
+        }
+    }
+
+    fn latch_motion(&mut self, position: Option<(u16, u16)>) {
+        let (delta_x, delta_y) = match (self.last_position, position) {
+            (Some((last_x, last_y)), Some((x, y))) => {
+                (i32::from(last_x) - i32::from(x), i32::from(last_y) - i32::from(y))
+            }
+            _ => (0, 0),
+        };
+        self.delta_x = delta_x.clamp(-SPORTS_PAD_MAX_DELTA, SPORTS_PAD_MAX_DELTA) as u8;
+        self.delta_y = delta_y.clamp(-SPORTS_PAD_MAX_DELTA, SPORTS_PAD_MAX_DELTA) as u8;
+        self.last_position = position;
+    }
+
+    fn nibble(self) -> u8 {
+        match self.phase {
+            0 => self.delta_y & 0x0F,
+            1 => self.delta_x >> 4,
+            2 => self.delta_x & 0x0F,
+            3 => self.delta_y >> 4,
+            _ => panic!("Sports Pad phase should always be <= 3"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode)]
+struct ControllerPort {
+    controller_type: SmsControllerType,
+    tr: PinDirection,
+    th: PinDirection,
+    // Whether the paddle is currently outputting the high nibble of its position
+    paddle_high_nibble: bool,
+    sports_pad: SportsPadHandshake,
+}
+
+impl ControllerPort {
+    fn new(controller_type: SmsControllerType) -> Self {
+        Self {
+            controller_type,
+            tr: PinDirection::Input,
+            th: PinDirection::Input,
+            paddle_high_nibble: false,
+            sports_pad: SportsPadHandshake::default(),
+        }
+    }
+
+    // Returns whether the write should latch the H counter, which happens when TH switches from an
+    // output to an input or when TH is driven from high to low
+    fn write_control(&mut self, tr: PinDirection, th: PinDirection, inputs: &SmsGgInputs) -> bool {
+        let prev_th_direction = self.th;
+        let prev_th = prev_th_direction.bit(true);
+        self.tr = tr;
+        self.th = th;
+
+        if self.controller_type == SmsControllerType::SportsPad && prev_th != th.bit(true) {
+            self.sports_pad.handle_th_change(inputs.sports_pad.position);
+        }
+
+        let output_to_input =
+            matches!(prev_th_direction, PinDirection::Output(_)) && th == PinDirection::Input;
+        output_to_input || (prev_th && !th.bit(true))
+    }
+
+    // Japanese paddles switch between nibbles on their own, which is emulated by switching on every
+    // read of the register that contains TR. Export paddles output the nibble selected by TH
+    fn advance_paddle(&mut self) {
+        if self.controller_type != SmsControllerType::Paddle {
+            return;
+        }
+
+        self.paddle_high_nibble = match self.th {
+            PinDirection::Input => !self.paddle_high_nibble,
+            PinDirection::Output(th) => th,
+        };
+    }
+
+    // Returns pin levels in the same layout as the Genesis I/O ports: TH, TR, and TL in bits 6-4,
+    // then right/left/down/up on D3-D0
+    fn pins(&self, joypad: SmsGgJoypadState, inputs: &SmsGgInputs, light_sensed: bool) -> u8 {
+        let device_pins = match self.controller_type {
+            SmsControllerType::Gamepad => {
+                0x40 | (u8::from(!joypad.button2) << 5)
+                    | (u8::from(!joypad.button1) << 4)
+                    | active_low_nibble([joypad.right, joypad.left, joypad.down, joypad.up])
+            }
+            SmsControllerType::LightPhaser => {
+                // The light sensor pulls TH low while it sees the beam
+                (u8::from(!light_sensed) << 6)
+                    | 0x2F
+                    | (u8::from(!inputs.light_phaser.trigger) << 4)
+            }
+            SmsControllerType::Paddle => {
+                // TR is low while the high nibble is output
+                let position = inputs.paddle.position;
+                let nibble = if self.paddle_high_nibble { position >> 4 } else { position & 0x0F };
+                0x40 | (u8::from(!self.paddle_high_nibble) << 5)
+                    | (u8::from(!inputs.paddle.button) << 4)
+                    | nibble
+            }
+            SmsControllerType::SportsPad => {
+                0x40 | (u8::from(!inputs.sports_pad.button2) << 5)
+                    | (u8::from(!inputs.sports_pad.button1) << 4)
+                    | self.sports_pad.nibble()
+            }
+        };
+
+        (u8::from(self.th.bit(device_pins.bit(6))) << 6)
+            | (u8::from(self.tr.bit(device_pins.bit(5))) << 5)
+            | (device_pins & 0x1F)
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct InputState {
     inputs: SmsGgInputs,
-    port_a_tr: PinDirection,
-    port_a_th: PinDirection,
-    port_b_tr: PinDirection,
-    port_b_th: PinDirection,
+    port_a: ControllerPort,
+    port_b: ControllerPort,
     region: SmsRegion,
     reset: bool,
 }
 
 impl InputState {
-    pub fn new(region: SmsRegion) -> Self {
+    pub fn new(
+        region: SmsRegion,
+        p1_controller_type: SmsControllerType,
+        p2_controller_type: SmsControllerType,
+    ) -> Self {
         Self {
             inputs: SmsGgInputs::default(),
-            port_a_tr: PinDirection::Input,
-            port_a_th: PinDirection::Input,
-            port_b_tr: PinDirection::Input,
-            port_b_th: PinDirection::Input,
+            port_a: ControllerPort::new(p1_controller_type),
+            port_b: ControllerPort::new(p2_controller_type),
             region,
             reset: false,
         }
@@ -83,48 +318,205 @@ impl InputState {
         self.region = region;
     }
 
+    pub fn controller_types(&self) -> (SmsControllerType, SmsControllerType) {
+        (self.port_a.controller_type, self.port_b.controller_type)
+    }
+
+    pub fn set_controller_types(
+        &mut self,
+        p1_controller_type: SmsControllerType,
+        p2_controller_type: SmsControllerType,
+    ) {
+        self.port_a.controller_type = p1_controller_type;
+        self.port_b.controller_type = p2_controller_type;
+    }
+
     pub fn set_reset(&mut self, reset: bool) {
         self.reset = reset;
     }
 
-    pub fn write_control(&mut self, value: u8) {
-        self.port_b_th =
+    /// Frame position where a Light Phaser is pointed, or `None` if no Light Phaser is connected
+    /// to a port with TH set as an input.
+    pub fn light_phaser_position(&self) -> Option<(u16, u16)> {
+        let connected = [self.port_a, self.port_b].into_iter().any(|port| {
+            port.controller_type == SmsControllerType::LightPhaser && port.th == PinDirection::Input
+        });
+        if connected { self.inputs.light_phaser.position } else { None }
+    }
+
+    /// Write the I/O control register. Returns whether the write latched the H counter.
+    pub fn write_control(&mut self, value: u8) -> bool {
+        let port_b_th =
             if value.bit(3) { PinDirection::Input } else { PinDirection::Output(value.bit(7)) };
-        self.port_b_tr =
+        let port_b_tr =
             if value.bit(2) { PinDirection::Input } else { PinDirection::Output(value.bit(6)) };
-        self.port_a_th =
+        let port_a_th =
             if value.bit(1) { PinDirection::Input } else { PinDirection::Output(value.bit(5)) };
-        self.port_a_tr =
+        let port_a_tr =
             if value.bit(0) { PinDirection::Input } else { PinDirection::Output(value.bit(4)) };
+
+        let port_a_latch = self.port_a.write_control(port_a_tr, port_a_th, &self.inputs);
+        let port_b_latch = self.port_b.write_control(port_b_tr, port_b_th, &self.inputs);
+        port_a_latch || port_b_latch
+    }
+
+    pub fn port_dc(&mut self, light_sensed: bool) -> u8 {
+        self.port_a.advance_paddle();
+
+        let port_a = self.port_a.pins(self.inputs.p1, &self.inputs, light_sensed);
+        let port_b = self.port_b.pins(self.inputs.p2, &self.inputs, light_sensed);
+
+        ((port_b & 0x03) << 6) | (port_a & 0x3F)
     }
 
-    pub fn port_dc(&self) -> u8 {
-        let port_a_tr_bit = u8::from(self.port_a_tr.bit(!self.inputs.p1.button2)) << 5;
-
-        (u8::from(!self.inputs.p2.down) << 7)
-            | (u8::from(!self.inputs.p2.up) << 6)
-            | port_a_tr_bit
-            | (u8::from(!self.inputs.p1.button1) << 4)
-            | (u8::from(!self.inputs.p1.right) << 3)
-            | (u8::from(!self.inputs.p1.left) << 2)
-            | (u8::from(!self.inputs.p1.down) << 1)
-            | u8::from(!self.inputs.p1.up)
-    }
-
-    pub fn port_dd(&self) -> u8 {
-        let port_b_th_bit =
-            u8::from(self.region == SmsRegion::International && self.port_b_th.bit(true)) << 7;
-        let port_a_th_bit =
-            u8::from(self.region == SmsRegion::International && self.port_a_th.bit(true)) << 6;
-        let port_b_tr_bit = u8::from(self.port_b_tr.bit(!self.inputs.p2.button2)) << 3;
-
-        port_b_th_bit
-            | port_a_th_bit
-            | 0x20
-            | (u8::from(!self.reset) << 4)
-            | port_b_tr_bit
-            | (u8::from(!self.inputs.p2.button1) << 2)
-            | (u8::from(!self.inputs.p2.right) << 1)
-            | u8::from(!self.inputs.p2.left)
+    pub fn port_dd(&mut self, light_sensed: bool) -> u8 {
+        self.port_b.advance_paddle();
+
+        let port_a = self.port_a.pins(self.inputs.p1, &self.inputs, light_sensed);
+        let port_b = self.port_b.pins(self.inputs.p2, &self.inputs, light_sensed);
+
+        let port_b_th_bit = u8::from(self.region == SmsRegion::International && port_b.bit(6)) << 7;
+        let port_a_th_bit = u8::from(self.region == SmsRegion::International && port_a.bit(6)) << 6;
+
+        port_b_th_bit | port_a_th_bit | 0x20 | (u8::from(!self.reset) << 4) | ((port_b >> 2) & 0x0F)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sports_pad_handshake() {
+        let mut input = InputState::new(
+            SmsRegion::International,
+            SmsControllerType::SportsPad,
+            SmsControllerType::Gamepad,
+        );
+
+        let mut inputs = SmsGgInputs::default();
+        inputs.sports_pad.position = Some((100, 100));
+        input.set_inputs(inputs);
+
+        // Port A TH output high, then step through a full read to latch the starting position
+        input.write_control(0xFD);
+        for th in [false, true, false, true] {
+            input.write_control(0xFD & !(u8::from(!th) << 5));
+        }
+
+        // Left 18 pixels and down 3 pixels, with button 1 held
+        inputs.sports_pad =
+            SportsPadState { button1: true, button2: false, position: Some((82, 103)) };
+        input.set_inputs(inputs);
+
+        let mut nibbles = Vec::new();
+        for th in [false, true, false, true] {
+            input.write_control(0xFD & !(u8::from(!th) << 5));
+            let port_dc = input.port_dc(false);
+            assert_eq!(port_dc & 0x30, 0x20, "button 1 should be pressed");
+            nibbles.push(port_dc & 0x0F);
+        }
+
+        // X = +18, Y = -3
+        assert_eq!(nibbles, vec![0x1, 0x2, 0xF, 0xD]);
+    }
+
+    #[test]
+    fn h_counter_latch() {
+        let mut input = InputState::new(
+            SmsRegion::International,
+            SmsControllerType::Gamepad,
+            SmsControllerType::Gamepad,
+        );
+
+        // All pins start as inputs
+        assert!(!input.write_control(0xFF));
+
+        // Port A TH output high, then output low
+        assert!(!input.write_control(0xFD));
+        assert!(input.write_control(0xDD));
+
+        // Output low to output high
+        assert!(!input.write_control(0xFD));
+
+        // Output to input
+        assert!(input.write_control(0xFF));
+
+        // Port B TH output high, then output low
+        assert!(!input.write_control(0xF7));
+        assert!(input.write_control(0x77));
+
+        // TR transitions do not latch
+        assert!(!input.write_control(0x76));
+        assert!(!input.write_control(0x66));
+    }
+
+    #[test]
+    fn light_phaser() {
+        let mut input = InputState::new(
+            SmsRegion::International,
+            SmsControllerType::LightPhaser,
+            SmsControllerType::Gamepad,
+        );
+
+        let mut inputs = SmsGgInputs::default();
+        inputs.light_phaser.position = Some((100, 50));
+        input.set_inputs(inputs);
+
+        assert_eq!(input.light_phaser_position(), Some((100, 50)));
+        assert_eq!(input.port_dc(false) & 0x10, 0x10, "trigger should not be pressed");
+        assert_eq!(input.port_dd(false) & 0x40, 0x40, "TH should be high when light is not sensed");
+        assert_eq!(input.port_dd(true) & 0x40, 0x00, "TH should be low when light is sensed");
+
+        inputs.light_phaser.trigger = true;
+        input.set_inputs(inputs);
+        assert_eq!(input.port_dc(false) & 0x10, 0x00, "trigger should be pressed");
+
+        // The Light Phaser cannot pull TH low while TH is an output
+        input.write_control(0xFD);
+        assert_eq!(input.light_phaser_position(), None);
+        assert_eq!(input.port_dd(true) & 0x40, 0x40);
+    }
+
+    #[test]
+    fn japanese_paddle() {
+        let mut input = InputState::new(
+            SmsRegion::Domestic,
+            SmsControllerType::Paddle,
+            SmsControllerType::Gamepad,
+        );
+
+        let mut inputs = SmsGgInputs::default();
+        inputs.paddle.position = 0xA5;
+        input.set_inputs(inputs);
+
+        // With TH as an input, the paddle alternates nibbles on its own and signals the high nibble
+        // with TR low
+        let reads: Vec<_> = (0..4).map(|_| input.port_dc(false) & 0x2F).collect();
+        assert_eq!(reads, vec![0x0A, 0x25, 0x0A, 0x25]);
+    }
+
+    #[test]
+    fn export_paddle() {
+        let mut input = InputState::new(
+            SmsRegion::International,
+            SmsControllerType::Paddle,
+            SmsControllerType::Gamepad,
+        );
+
+        let mut inputs = SmsGgInputs::default();
+        inputs.paddle.position = 0xA5;
+        inputs.paddle.button = true;
+        input.set_inputs(inputs);
+
+        // Port A TH output low selects the low nibble
+        input.write_control(0xDD);
+        assert_eq!(input.port_dc(false) & 0x3F, 0x25);
+        assert_eq!(input.port_dc(false) & 0x3F, 0x25);
+
+        // Port A TH output high selects the high nibble
+        input.write_control(0xFD);
+        assert_eq!(input.port_dc(false) & 0x3F, 0x0A);
+        assert_eq!(input.port_dc(false) & 0x3F, 0x0A);
     }
 }
@@ -1,14 +1,19 @@
 mod api;
 pub mod audio;
 mod bus;
+pub mod cheats;
 mod input;
 mod memory;
 pub mod psg;
 mod vdp;
+pub mod vgm;
 mod ym2413;
 
 pub use api::{SmsGgEmulator, SmsGgEmulatorConfig, SmsGgError, SmsGgResult, SmsRegion};
-pub use input::{SmsGgButton, SmsGgInputs, SmsGgJoypadState};
+pub use input::{
+    LightPhaserButton, LightPhaserState, PaddleButton, PaddleState, SmsControllerType, SmsGgButton,
+    SmsGgControllerButton, SmsGgInputs, SmsGgJoypadState, SportsPadButton, SportsPadState,
+};
 pub use vdp::{gg_color_to_rgb, sms_color_to_rgb, VdpVersion};
 
 // 8:7
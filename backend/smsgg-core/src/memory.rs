@@ -4,6 +4,7 @@ mod metadata;
 
 use bincode::{Decode, Encode};
 use crc::Crc;
+use jgenesis_common::cheats::CheatSet;
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::{FakeDecode, FakeEncode, PartialClone};
 use std::mem;
@@ -207,6 +208,7 @@ pub struct Memory {
     cartridge: Cartridge,
     ram: [u8; SYSTEM_RAM_SIZE],
     audio_control: AudioControl,
+    cheats: CheatSet,
 }
 
 impl Memory {
@@ -215,12 +217,16 @@ impl Memory {
             cartridge: Cartridge::new(rom, initial_cartridge_ram),
             ram: [0; SYSTEM_RAM_SIZE],
             audio_control: AudioControl::default(),
+            cheats: CheatSet::new(),
         }
     }
 
     pub fn read(&self, address: u16) -> u8 {
         match address {
-            0x0000..=0xBFFF => self.cartridge.read(address),
+            0x0000..=0xBFFF => {
+                let value = self.cartridge.read(address);
+                self.cheats.apply_rom_read(address.into(), value)
+            }
             0xC000..=0xFFFF => {
                 let ram_addr = address & 0x1FFF;
                 self.ram[ram_addr as usize]
@@ -291,6 +297,22 @@ impl Memory {
         self.cartridge.rom = mem::take(&mut other.cartridge.rom);
     }
 
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.cheats = cheats;
+    }
+
+    pub fn take_cheats(&mut self) -> CheatSet {
+        mem::take(&mut self.cheats)
+    }
+
+    // Write RAM cheat values to system RAM; this should be called once per frame
+    pub fn apply_ram_cheats(&mut self) {
+        for patch in self.cheats.ram_patches() {
+            let ram_addr = patch.address & 0x1FFF;
+            self.ram[ram_addr as usize] = patch.value;
+        }
+    }
+
     pub fn take_cartridge_rom_and_ram(&mut self) -> (Vec<u8>, Vec<u8>) {
         let rom = mem::take(&mut self.cartridge.rom);
         let ram = mem::take(&mut self.cartridge.ram);
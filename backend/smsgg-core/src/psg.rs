@@ -1,5 +1,6 @@
 //! SN76498 PSG (programmable sound generator)
 
+use crate::vgm::VgmLogger;
 use bincode::{Decode, Encode};
 use jgenesis_common::num::GetBit;
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
@@ -184,6 +185,16 @@ impl NoiseGenerator {
         self.lfsr = INITIAL_LFSR;
     }
 
+    fn register_value(&self) -> u8 {
+        let rate = match self.counter_reload {
+            NoiseReload::Value(0x10) => 0x00,
+            NoiseReload::Value(0x20) => 0x01,
+            NoiseReload::Value(_) => 0x02,
+            NoiseReload::Tone2 => 0x03,
+        };
+        (u8::from(self.noise_type == NoiseType::White) << 2) | rate
+    }
+
     fn clock(&mut self, tone2: u16) {
         if self.counter == 0 {
             self.counter = self.counter_reload.value(tone2);
@@ -217,6 +228,17 @@ enum Register {
 }
 
 impl Register {
+    const ALL: [Self; 8] = [
+        Self::Tone0,
+        Self::Tone1,
+        Self::Tone2,
+        Self::Noise,
+        Self::Volume0,
+        Self::Volume1,
+        Self::Volume2,
+        Self::Volume3,
+    ];
+
     fn from_latch_byte(value: u8) -> Self {
         match value & 0x70 {
             0x00 => Self::Tone0,
@@ -230,6 +252,19 @@ impl Register {
             _ => unreachable!("value & 0x70 is always one of the above values"),
         }
     }
+
+    fn latch_bits(self) -> u8 {
+        match self {
+            Self::Tone0 => 0x00,
+            Self::Volume0 => 0x10,
+            Self::Tone1 => 0x20,
+            Self::Volume1 => 0x30,
+            Self::Tone2 => 0x40,
+            Self::Volume2 => 0x50,
+            Self::Noise => 0x60,
+            Self::Volume3 => 0x70,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -259,6 +294,23 @@ struct StereoControl {
 }
 
 impl StereoControl {
+    fn value(self) -> u8 {
+        [
+            self.square_0_r,
+            self.square_1_r,
+            self.square_2_r,
+            self.noise_r,
+            self.square_0_l,
+            self.square_1_l,
+            self.square_2_l,
+            self.noise_l,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, enabled)| u8::from(enabled) << i)
+        .sum()
+    }
+
     fn write(&mut self, value: u8) {
         self.square_0_r = value.bit(0);
         self.square_1_r = value.bit(1);
@@ -372,6 +424,45 @@ impl Psg {
         self.stereo_control.write(value);
     }
 
+    /// Log writes that set every register to its current value, ending with the currently latched
+    /// register so that subsequent data bytes go to the same register as they do in the emulator.
+    ///
+    /// The Game Gear stereo register is only logged if it differs from its power-on value, since
+    /// the other consoles never write to it.
+    pub fn log_registers(&self, logger: &mut VgmLogger) {
+        let registers = Register::ALL
+            .into_iter()
+            .filter(|&register| register != self.latched_register)
+            .chain([self.latched_register]);
+        let tone_data = |channel: usize| {
+            let tone = self.square_wave_channels[channel].tone;
+            ((tone & 0x0F) as u8, Some((tone >> 4) as u8))
+        };
+
+        for register in registers {
+            let (low_bits, high_bits) = match register {
+                Register::Tone0 => tone_data(0),
+                Register::Tone1 => tone_data(1),
+                Register::Tone2 => tone_data(2),
+                Register::Noise => (self.noise_channel.register_value(), None),
+                Register::Volume0 => (self.square_wave_channels[0].attenuation, None),
+                Register::Volume1 => (self.square_wave_channels[1].attenuation, None),
+                Register::Volume2 => (self.square_wave_channels[2].attenuation, None),
+                Register::Volume3 => (self.noise_channel.attenuation, None),
+            };
+
+            logger.log_psg_write(0x80 | register.latch_bits() | low_bits);
+            if let Some(high_bits) = high_bits {
+                logger.log_psg_write(high_bits);
+            }
+        }
+
+        let stereo_control = self.stereo_control.value();
+        if stereo_control != 0xFF {
+            logger.log_gg_stereo_write(stereo_control);
+        }
+    }
+
     #[inline]
     pub fn tick(&mut self) -> PsgTickEffect {
         self.divider -= 1;
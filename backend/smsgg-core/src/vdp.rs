@@ -690,9 +690,16 @@ pub struct Vdp {
     sprite_buffer: SpriteBuffer,
     remove_sprite_limit: bool,
     line_counter: u8,
+    light_phaser_position: Option<(u16, u16)>,
+    latched_h_counter: u8,
 }
 
 const DOTS_PER_SCANLINE: u16 = 342;
+
+// The Light Phaser senses the beam on a few scanlines above and below the position it is pointed at,
+// for a short time after the beam reaches that position on each scanline
+const LIGHT_PHASER_SENSED_LINES: u16 = 4;
+const LIGHT_PHASER_SENSED_DOTS: u16 = 48;
 const NTSC_SCANLINES_PER_FRAME: u16 = 262;
 const PAL_SCANLINES_PER_FRAME: u16 = 313;
 
@@ -714,6 +721,8 @@ impl Vdp {
             sprite_buffer: SpriteBuffer::new(),
             remove_sprite_limit,
             line_counter: 0xFF,
+            light_phaser_position: None,
+            latched_h_counter: 0,
         }
     }
 
@@ -960,6 +969,10 @@ impl Vdp {
         let tick_effect =
             if vblank_start { VdpTickEffect::FrameComplete } else { VdpTickEffect::None };
 
+        if self.light_phaser_position.is_some() {
+            self.check_light_phaser();
+        }
+
         self.dot += 1;
         if self.dot == DOTS_PER_SCANLINE {
             self.scanline += 1;
@@ -977,6 +990,61 @@ impl Vdp {
         tick_effect
     }
 
+    /// Set the frame position where a Light Phaser is pointed, or `None` if no Light Phaser is
+    /// currently able to sense the beam.
+    ///
+    /// The Light Phaser pulls TH low while it senses the beam, which latches the H counter.
+    pub fn set_light_phaser_position(&mut self, position: Option<(u16, u16)>) {
+        self.light_phaser_position = position;
+    }
+
+    // Convert a frame position to a position in the active display, as (scanline, dot)
+    fn light_phaser_beam_position(&self) -> Option<(u16, u16)> {
+        let (x, y) = self.light_phaser_position?;
+        let viewport = self.frame_buffer.viewport;
+
+        let dot = viewport.left + x;
+        let scanline = (viewport.top + y + self.registers.mode.vertical_border_offset())
+            .checked_sub(viewport.top_border_height)?;
+        (dot < SCREEN_WIDTH && scanline < self.registers.mode.active_scanlines())
+            .then_some((scanline, dot))
+    }
+
+    /// Whether a Light Phaser is currently sensing the beam.
+    pub fn light_phaser_sensed(&self) -> bool {
+        let Some((gun_scanline, gun_dot)) = self.light_phaser_beam_position() else {
+            return false;
+        };
+
+        self.registers.display_enabled
+            && self.scanline.abs_diff(gun_scanline) <= LIGHT_PHASER_SENSED_LINES
+            && (gun_dot..gun_dot + LIGHT_PHASER_SENSED_DOTS).contains(&self.dot)
+    }
+
+    /// Latch the current H counter value, which happens when TH is pulled low on either controller
+    /// port or when software switches TH from an output to an input.
+    pub fn latch_h_counter(&mut self) {
+        // H counter values start from 0 at the left edge of the active display and advance once
+        // every 2 dots, jumping from $93 to $E9 during horizontal blanking
+        let h_counter = self.dot / 2;
+        self.latched_h_counter =
+            if h_counter <= 0x93 { h_counter as u8 } else { (h_counter + 0xE9 - 0x94) as u8 };
+    }
+
+    fn check_light_phaser(&mut self) {
+        let Some((_, gun_dot)) = self.light_phaser_beam_position() else { return };
+
+        if self.dot == gun_dot && self.light_phaser_sensed() {
+            self.latch_h_counter();
+
+            log::trace!(
+                "Light Phaser latched H counter at scanline {}, dot {}",
+                self.scanline,
+                self.dot
+            );
+        }
+    }
+
     fn fill_vertical_border(&mut self) {
         let backdrop_color = match self.registers.mode {
             Mode::Four | Mode::Four224Line => {
@@ -1027,6 +1095,12 @@ impl Vdp {
         self.registers.write_data(value, &mut self.vram, &mut self.color_ram);
     }
 
+    /// Returns the H counter value that was latched by the last TH transition on either controller
+    /// port.
+    pub fn h_counter(&self) -> u8 {
+        self.latched_h_counter
+    }
+
     pub fn v_counter(&self) -> u8 {
         match (self.registers.version.timing_mode(), self.registers.mode) {
             (TimingMode::Ntsc, Mode::Four | Mode::GraphicsII) => {
@@ -1114,3 +1188,70 @@ pub fn gg_color_to_rgb(color: u16) -> Color {
     let b = convert_gg_color((color >> 8) & 0x0F);
     Color::rgb(r, g, b)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_vdp(display_enabled: bool) -> Vdp {
+        let mut vdp = Vdp::new(VdpVersion::NtscMasterSystem2, false);
+
+        // Register 1
+        vdp.write_control(if display_enabled { 0x40 } else { 0x00 });
+        vdp.write_control(0x81);
+
+        vdp
+    }
+
+    fn run_frame(vdp: &mut Vdp) -> Vec<(u16, u16)> {
+        let mut sensed = Vec::new();
+        for _ in 0..u32::from(NTSC_SCANLINES_PER_FRAME) * u32::from(DOTS_PER_SCANLINE) {
+            if vdp.light_phaser_sensed() {
+                sensed.push((vdp.scanline, vdp.dot));
+            }
+            let _ = vdp.tick();
+        }
+        sensed
+    }
+
+    #[test]
+    fn light_phaser_sensing_and_latch() {
+        let mut vdp = new_vdp(true);
+
+        // Frame row 66 is active scanline 50 because of the 16-line top border
+        vdp.set_light_phaser_position(Some((100, 66)));
+        let sensed = run_frame(&mut vdp);
+
+        assert_eq!(sensed.first(), Some(&(46, 100)));
+        assert_eq!(sensed.last(), Some(&(54, 100 + LIGHT_PHASER_SENSED_DOTS - 1)));
+        assert_eq!(sensed.len(), 9 * LIGHT_PHASER_SENSED_DOTS as usize);
+        assert_eq!(vdp.h_counter(), 50);
+    }
+
+    #[test]
+    fn light_phaser_not_sensed_while_display_disabled() {
+        let mut vdp = new_vdp(false);
+
+        vdp.set_light_phaser_position(Some((100, 66)));
+        assert!(run_frame(&mut vdp).is_empty());
+        assert_eq!(vdp.h_counter(), 0);
+    }
+
+    #[test]
+    fn h_counter_latch() {
+        let mut vdp = new_vdp(true);
+
+        for _ in 0..100 {
+            let _ = vdp.tick();
+        }
+        vdp.latch_h_counter();
+        assert_eq!(vdp.h_counter(), 0x32);
+
+        // H counter skips from $93 to $E9 during horizontal blanking
+        for _ in 100..300 {
+            let _ = vdp.tick();
+        }
+        vdp.latch_h_counter();
+        assert_eq!(vdp.h_counter(), 0xEB);
+    }
+}
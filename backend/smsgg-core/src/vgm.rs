@@ -0,0 +1,368 @@
+//! VGM logging of sound chip register writes.
+//!
+//! The emulation cores record every write to the SN76489 PSG, YM2413, and YM2612 into a
+//! [`VgmLogger`] along with wait commands derived from the emulated master clock. Frontends
+//! periodically drain the recorded commands and write them to a file after a header produced by
+//! [`vgm_header`].
+//!
+//! When logging starts, and whenever the emulator state is replaced while logging (loading a save
+//! state, rewinding, or a hard reset), the emulators log writes that set every sound chip register
+//! to its current value so that the log plays back from the same chip state.
+
+use jgenesis_common::frontend::TimingMode;
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::mem;
+
+/// VGM files always use a 44100 Hz sample clock for wait commands.
+pub const VGM_SAMPLE_RATE: u64 = 44100;
+
+/// Length of the header produced by [`vgm_header`]; the command data starts at this offset.
+pub const VGM_HEADER_LEN: usize = 0x40;
+
+/// Command that marks the end of the command data.
+pub const VGM_END_OF_DATA: u8 = 0x66;
+
+const VGM_VERSION: u32 = 0x0000_0150;
+
+const NTSC_MCLK_FREQUENCY: u64 = 53_693_175;
+const PAL_MCLK_FREQUENCY: u64 = 53_203_424;
+
+/// SN76489 clock divider relative to the master clock; the Z80 uses the same clock
+pub const PSG_MCLK_DIVIDER: u64 = 15;
+/// YM2612 clock divider relative to the master clock; the 68000 uses the same clock
+pub const YM2612_MCLK_DIVIDER: u64 = 7;
+
+// Sega's SN76489 variant uses a 16-bit noise shift register tapped at bits 0 and 3
+const SEGA_PSG_NOISE_FEEDBACK: u16 = 0x0009;
+const SEGA_PSG_SHIFT_REGISTER_WIDTH: u8 = 16;
+
+mod cmd {
+    pub const GG_STEREO: u8 = 0x4F;
+    pub const PSG: u8 = 0x50;
+    pub const YM2413: u8 = 0x51;
+    pub const YM2612_PORT_0: u8 = 0x52;
+    pub const YM2612_PORT_1: u8 = 0x53;
+    pub const WAIT_N: u8 = 0x61;
+    pub const WAIT_NTSC_FRAME: u8 = 0x62;
+    pub const WAIT_PAL_FRAME: u8 = 0x63;
+    pub const WAIT_SHORT: u8 = 0x70;
+}
+
+/// Which sound chips are present and what clock rates they run at, in Hz. A clock rate of 0 means
+/// that the chip is not present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VgmChips {
+    pub psg_clock: u32,
+    pub ym2413_clock: u32,
+    pub ym2612_clock: u32,
+    pub timing_mode: TimingMode,
+}
+
+impl VgmChips {
+    #[must_use]
+    pub fn new(timing_mode: TimingMode, psg: bool, ym2413: bool, ym2612: bool) -> Self {
+        let mclk_frequency = mclk_frequency(timing_mode);
+        let clock = |present: bool, divider: u64| {
+            if present { (mclk_frequency / divider) as u32 } else { 0 }
+        };
+
+        Self {
+            psg_clock: clock(psg, PSG_MCLK_DIVIDER),
+            // The YM2413 in the SMS FM sound unit shares the Z80/PSG clock
+            ym2413_clock: clock(ym2413, PSG_MCLK_DIVIDER),
+            ym2612_clock: clock(ym2612, YM2612_MCLK_DIVIDER),
+            timing_mode,
+        }
+    }
+}
+
+fn mclk_frequency(timing_mode: TimingMode) -> u64 {
+    match timing_mode {
+        TimingMode::Ntsc => NTSC_MCLK_FREQUENCY,
+        TimingMode::Pal => PAL_MCLK_FREQUENCY,
+    }
+}
+
+/// Build a VGM 1.50 header for a file with the given chips, total length in samples, and command
+/// data length in bytes (including the end of data command).
+#[must_use]
+pub fn vgm_header(chips: VgmChips, total_samples: u32, data_len: u32) -> [u8; VGM_HEADER_LEN] {
+    let mut header = [0; VGM_HEADER_LEN];
+
+    let mut put_u32 = |offset: usize, value: u32| {
+        header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    };
+
+    // Offsets in the header are relative to the offset of the field itself
+    let eof_offset = VGM_HEADER_LEN as u32 + data_len - 0x04;
+    let data_offset = VGM_HEADER_LEN as u32 - 0x34;
+    let rate = match chips.timing_mode {
+        TimingMode::Ntsc => 60,
+        TimingMode::Pal => 50,
+    };
+
+    put_u32(0x04, eof_offset);
+    put_u32(0x08, VGM_VERSION);
+    put_u32(0x0C, chips.psg_clock);
+    put_u32(0x10, chips.ym2413_clock);
+    put_u32(0x18, total_samples);
+    put_u32(0x24, rate);
+    put_u32(0x2C, chips.ym2612_clock);
+    put_u32(0x34, data_offset);
+
+    header[0x00..0x04].copy_from_slice(b"Vgm ");
+    if chips.psg_clock != 0 {
+        header[0x28..0x2A].copy_from_slice(&SEGA_PSG_NOISE_FEEDBACK.to_le_bytes());
+        header[0x2A] = SEGA_PSG_SHIFT_REGISTER_WIDTH;
+    }
+
+    header
+}
+
+/// Commands drained from a [`VgmLogger`].
+#[derive(Debug, Clone, Default)]
+pub struct VgmCommands {
+    pub data: Vec<u8>,
+    /// Number of 44100 Hz samples covered by the wait commands in `data`
+    pub samples: u64,
+}
+
+#[derive(Debug, Clone)]
+struct VgmLoggerState {
+    chips: VgmChips,
+    commands: Vec<u8>,
+    cycles_divider: u64,
+    mclk_frequency: u64,
+    cycles: u64,
+    samples_logged: u64,
+    samples_drained: u64,
+    ym2413_register: u8,
+    ym2612_register: u8,
+    ym2612_port: u8,
+}
+
+impl VgmLoggerState {
+    fn push_wait(&mut self) {
+        let target_samples =
+            u128::from(self.cycles) * u128::from(self.cycles_divider) * u128::from(VGM_SAMPLE_RATE)
+                / u128::from(self.mclk_frequency);
+        let mut remaining = target_samples as u64 - self.samples_logged;
+        self.samples_logged = target_samples as u64;
+
+        let frame_samples = match self.chips.timing_mode {
+            TimingMode::Ntsc => (735, cmd::WAIT_NTSC_FRAME),
+            TimingMode::Pal => (882, cmd::WAIT_PAL_FRAME),
+        };
+
+        while remaining != 0 {
+            if remaining == frame_samples.0 {
+                self.commands.push(frame_samples.1);
+                remaining = 0;
+            } else if remaining <= 16 {
+                self.commands.push(cmd::WAIT_SHORT | (remaining - 1) as u8);
+                remaining = 0;
+            } else {
+                let wait = remaining.min(u16::MAX.into());
+                self.commands.push(cmd::WAIT_N);
+                self.commands.extend((wait as u16).to_le_bytes());
+                remaining -= wait;
+            }
+        }
+    }
+
+    fn push_command(&mut self, command: &[u8]) {
+        self.push_wait();
+        self.commands.extend_from_slice(command);
+    }
+}
+
+/// Records sound chip writes as VGM commands while logging is active. Inactive loggers ignore all
+/// writes.
+///
+/// Logger state is never included in save states. Emulators move an active logger into the new
+/// state when a save state is loaded, and log their current register values at that point.
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct VgmLogger(Option<Box<VgmLoggerState>>);
+
+impl VgmLogger {
+    /// Start logging, discarding any commands that have not been drained.
+    ///
+    /// `cycles_divider` is the master clock divider for the cycle counts that will be passed to
+    /// [`advance_cycles`](Self::advance_cycles).
+    pub fn start(&mut self, chips: VgmChips, cycles_divider: u64) {
+        self.0 = Some(Box::new(VgmLoggerState {
+            chips,
+            commands: Vec::with_capacity(4096),
+            cycles_divider,
+            mclk_frequency: mclk_frequency(chips.timing_mode),
+            cycles: 0,
+            samples_logged: 0,
+            samples_drained: 0,
+            ym2413_register: 0,
+            ym2612_register: 0,
+            ym2612_port: 0,
+        }));
+    }
+
+    pub fn stop(&mut self) {
+        self.0 = None;
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+
+    #[must_use]
+    pub fn chips(&self) -> Option<VgmChips> {
+        self.0.as_ref().map(|state| state.chips)
+    }
+
+    #[inline]
+    pub fn advance_cycles(&mut self, cycles: u64) {
+        if let Some(state) = &mut self.0 {
+            state.cycles += cycles;
+        }
+    }
+
+    #[inline]
+    pub fn log_psg_write(&mut self, value: u8) {
+        if let Some(state) = &mut self.0 {
+            state.push_command(&[cmd::PSG, value]);
+        }
+    }
+
+    #[inline]
+    pub fn log_gg_stereo_write(&mut self, value: u8) {
+        if let Some(state) = &mut self.0 {
+            state.push_command(&[cmd::GG_STEREO, value]);
+        }
+    }
+
+    #[inline]
+    pub fn log_ym2413_register_select(&mut self, register: u8) {
+        if let Some(state) = &mut self.0 {
+            state.ym2413_register = register;
+        }
+    }
+
+    #[inline]
+    pub fn log_ym2413_data_write(&mut self, value: u8) {
+        if let Some(state) = &mut self.0 {
+            let register = state.ym2413_register;
+            state.push_command(&[cmd::YM2413, register, value]);
+        }
+    }
+
+    /// Port is 0 for channels 1-3 and system registers, 1 for channels 4-6.
+    #[inline]
+    pub fn log_ym2612_address_write(&mut self, port: u8, register: u8) {
+        if let Some(state) = &mut self.0 {
+            state.ym2612_port = port;
+            state.ym2612_register = register;
+        }
+    }
+
+    /// Log a YM2612 data port write, including DAC sample writes to register $2A.
+    #[inline]
+    pub fn log_ym2612_data_write(&mut self, value: u8) {
+        if let Some(state) = &mut self.0 {
+            let command =
+                if state.ym2612_port == 0 { cmd::YM2612_PORT_0 } else { cmd::YM2612_PORT_1 };
+            let register = state.ym2612_register;
+            state.push_command(&[command, register, value]);
+        }
+    }
+
+    /// Take all commands recorded since the last drain, including a wait up to the current time.
+    /// Returns `None` if logging is not active.
+    #[must_use]
+    pub fn drain(&mut self) -> Option<VgmCommands> {
+        let state = self.0.as_mut()?;
+        state.push_wait();
+
+        let samples = state.samples_logged - state.samples_drained;
+        state.samples_drained = state.samples_logged;
+
+        Some(VgmCommands { data: mem::take(&mut state.commands), samples })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::psg::{Psg, PsgVersion};
+
+    fn ntsc_sms_logger() -> VgmLogger {
+        let mut logger = VgmLogger::default();
+        logger.start(VgmChips::new(TimingMode::Ntsc, true, false, false), PSG_MCLK_DIVIDER);
+        logger
+    }
+
+    #[test]
+    fn inactive_logger_ignores_writes() {
+        let mut logger = VgmLogger::default();
+        logger.advance_cycles(1000);
+        logger.log_psg_write(0x9F);
+        assert!(logger.drain().is_none());
+    }
+
+    #[test]
+    fn waits_are_derived_from_cycles() {
+        let mut logger = ntsc_sms_logger();
+
+        // 1 NTSC frame's worth of Z80 cycles: 3579545 / 44100 * 735 = ~59659.08
+        logger.log_psg_write(0x9F);
+        logger.advance_cycles(59_660);
+        logger.log_psg_write(0xBF);
+        // ~5 samples
+        logger.advance_cycles(406);
+        logger.log_psg_write(0xDF);
+
+        let commands = logger.drain().unwrap();
+        assert_eq!(commands.data, vec![0x50, 0x9F, 0x62, 0x50, 0xBF, 0x74, 0x50, 0xDF]);
+        assert_eq!(commands.samples, 740);
+    }
+
+    #[test]
+    fn long_waits_are_split() {
+        let mut logger = ntsc_sms_logger();
+
+        // 100000 samples
+        logger.advance_cycles(100_000 * 3_579_545 / 44100 + 1);
+        let commands = logger.drain().unwrap();
+        assert_eq!(commands.data, vec![0x61, 0xFF, 0xFF, 0x61, 0xA1, 0x86]);
+        assert_eq!(commands.samples, 100_000);
+
+        assert_eq!(logger.drain().unwrap().data, vec![]);
+    }
+
+    #[test]
+    fn psg_register_dump() {
+        let mut psg = Psg::new(PsgVersion::Standard);
+        // Tone 1 = $1A5, volume 2 = 3, white noise at the tone 2 rate, then latch volume 0
+        for value in [0xA5, 0x1A, 0xD3, 0xE7, 0x94] {
+            psg.write(value);
+        }
+
+        let mut logger = ntsc_sms_logger();
+        psg.log_registers(&mut logger);
+
+        let commands = logger.drain().unwrap();
+        let psg_writes: Vec<_> = commands
+            .data
+            .chunks(2)
+            .map(|command| {
+                assert_eq!(command[0], cmd::PSG);
+                command[1]
+            })
+            .collect();
+
+        // Every register, with the latched volume 0 register last
+        assert_eq!(
+            psg_writes,
+            vec![0x80, 0x00, 0xA5, 0x1A, 0xC0, 0x00, 0xE7, 0xBF, 0xD3, 0xFF, 0x94]
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! This implementation is largely based on reverse engineering work by andete:
 //! <https://github.com/andete/ym2413>
 
+use crate::vgm::VgmLogger;
 use bincode::{Decode, Encode};
 use jgenesis_common::num::{GetBit, U16Ext};
 use std::sync::OnceLock;
@@ -687,6 +688,8 @@ pub struct Ym2413 {
     fm_unit: FmUnit,
     selected_register: u8,
     custom_instrument_patch: [u8; 8],
+    // Raw register values as last written, for logging the chip state when VGM logging starts
+    register_values: [u8; 0x40],
     divider: u8,
 }
 
@@ -704,6 +707,7 @@ impl Ym2413 {
             fm_unit: FmUnit::new(),
             selected_register: 0,
             custom_instrument_patch: [0; 8],
+            register_values: [0; 0x40],
             divider: YM2413_DIVIDER,
         }
     }
@@ -715,6 +719,11 @@ impl Ym2413 {
     pub fn write_data(&mut self, value: u8) {
         log::trace!("Write to register {:02X}: {value:02X}", self.selected_register);
 
+        if let Some(register_value) = self.register_values.get_mut(self.selected_register as usize)
+        {
+            *register_value = value;
+        }
+
         match self.selected_register {
             register @ 0x00..=0x07 => {
                 self.custom_instrument_patch[register as usize] = value;
@@ -770,6 +779,19 @@ impl Ym2413 {
         }
     }
 
+    /// Log writes that set every register to its current value, followed by the currently selected
+    /// register address. Key-on registers are written last so that channels start playing with
+    /// their current instrument and volume.
+    pub fn log_registers(&self, logger: &mut VgmLogger) {
+        let registers = (0x00..=0x07).chain([0x0E]).chain(0x10..=0x18).chain(0x30..=0x38);
+        for register in registers.chain(0x20..=0x28) {
+            logger.log_ym2413_register_select(register);
+            logger.log_ym2413_data_write(self.register_values[register as usize]);
+        }
+
+        logger.log_ym2413_register_select(self.selected_register);
+    }
+
     fn handle_rhythm_register_write(&mut self, value: u8) {
         let rhythm_mode_enabled = value.bit(5);
         if rhythm_mode_enabled != self.rhythm_mode_enabled {
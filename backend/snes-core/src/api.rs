@@ -7,9 +7,12 @@ use crate::input::SnesInputs;
 use crate::memory::dma::{DmaStatus, DmaUnit};
 use crate::memory::{CpuInternalRegisters, Memory};
 use crate::ppu::{Ppu, PpuTickEffect};
+use crate::spc::SpcMetadata;
 use bincode::error::EncodeError;
 use bincode::{Decode, Encode};
 use crc::Crc;
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, PixelAspectRatio, Renderer,
     SaveWriter, TickEffect, TimingMode,
@@ -20,6 +23,7 @@ use std::num::NonZeroU64;
 use std::{io, mem};
 use thiserror::Error;
 use wdc65816_emu::core::Wdc65816;
+use wdc65816_emu::debug::DebugBus;
 use wdc65816_emu::traits::BusInterface;
 
 const MEMORY_REFRESH_MCLK: u64 = 536;
@@ -125,6 +129,16 @@ pub enum SnesLoadError {
 
 pub type SnesLoadResult<T> = Result<T, SnesLoadError>;
 
+/// Debugger CPU index of the main 65816.
+pub const MAIN_CPU_DEBUG_CPU: usize = 0;
+/// Debugger CPU index of the SPC700 in the APU.
+pub const SPC700_DEBUG_CPU: usize = 1;
+
+const DEBUG_CPUS: &[DebugCpu] = &[
+    DebugCpu { name: "65816", arch: CpuArch::Wdc65816 },
+    DebugCpu { name: "SPC700", arch: CpuArch::Spc700 },
+];
+
 macro_rules! new_bus {
     ($self:expr) => {
         Bus {
@@ -166,6 +180,7 @@ pub struct SnesEmulator {
     #[partial_clone(default)]
     coprocessor_roms: CoprocessorRoms,
     emulator_config: SnesEmulatorConfig,
+    debug_hooks: DebugHooks,
 }
 
 impl SnesEmulator {
@@ -217,6 +232,7 @@ impl SnesEmulator {
             last_sram_checksum: sram_checksum,
             coprocessor_roms,
             emulator_config: config,
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
         };
 
         // Reset CPU so that execution starts from the right place
@@ -230,6 +246,19 @@ impl SnesEmulator {
         self.memory.cartridge_title()
     }
 
+    /// Save the current state of the APU as an SPC file.
+    #[must_use]
+    pub fn export_spc(&mut self) -> Vec<u8> {
+        let metadata = SpcMetadata { game_title: self.cartridge_title(), ..SpcMetadata::default() };
+        self.apu.to_spc_file(&metadata)
+    }
+
+    /// Replace the active cheats. Use [`cheats::decode_cheat`](crate::cheats::decode_cheat) to
+    /// decode cheat codes.
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.memory.set_cheats(cheats);
+    }
+
     #[inline]
     #[must_use]
     pub fn has_sram(&self) -> bool {
@@ -282,6 +311,10 @@ impl EmulatorTrait for SnesEmulator {
         S: SaveWriter,
         S::Err: Debug + Display + Send + Sync + 'static,
     {
+        if self.debug_hooks.stop_event().is_some() {
+            return Ok(TickEffect::None);
+        }
+
         let (master_cycles_elapsed, pending_write) = if self.memory_refresh_pending {
             // The CPU (including DMA) halts for 40 cycles partway through every scanline so that
             // the system can refresh DRAM (used for work RAM)
@@ -292,8 +325,37 @@ impl EmulatorTrait for SnesEmulator {
 
             match self.dma_unit.tick(&mut bus, self.total_master_cycles) {
                 DmaStatus::None => {
+                    if self.debug_hooks.checks_execution(MAIN_CPU_DEBUG_CPU)
+                        && !self.main_cpu.is_mid_instruction()
+                        && self
+                            .debug_hooks
+                            .check_execute(MAIN_CPU_DEBUG_CPU, main_cpu_pc(&self.main_cpu))
+                    {
+                        return Ok(TickEffect::None);
+                    }
+
+                    let tracing = self.debug_hooks.is_tracing(MAIN_CPU_DEBUG_CPU);
+                    if tracing && !self.main_cpu.is_mid_instruction() {
+                        self.main_cpu.trace_instruction(
+                            &mut bus,
+                            &mut self.debug_hooks,
+                            MAIN_CPU_DEBUG_CPU,
+                        );
+                    }
+
                     // DMA not in progress, tick CPU
-                    self.main_cpu.tick(&mut bus);
+                    if self.debug_hooks.checks_accesses(MAIN_CPU_DEBUG_CPU) {
+                        self.main_cpu.tick(&mut DebugBus::new(
+                            &mut bus,
+                            &mut self.debug_hooks,
+                            MAIN_CPU_DEBUG_CPU,
+                        ));
+                    } else {
+                        self.main_cpu.tick(&mut bus);
+                    }
+                    if tracing {
+                        self.debug_hooks.add_trace_cycles(MAIN_CPU_DEBUG_CPU, 1);
+                    }
                     self.latched_interrupts = None;
 
                     (bus.access_master_cycles, bus.pending_write)
@@ -351,6 +413,8 @@ impl EmulatorTrait for SnesEmulator {
                 }
             }
 
+            self.memory.apply_ram_cheats();
+
             self.frame_count += 1;
             tick_effect = TickEffect::FrameRendered;
         }
@@ -358,7 +422,7 @@ impl EmulatorTrait for SnesEmulator {
         self.cpu_registers.tick(master_cycles_elapsed, &self.ppu, prev_scanline_mclk, inputs);
 
         if let ApuTickEffect::OutputSample(sample_l, sample_r) =
-            self.apu.tick(master_cycles_elapsed)
+            self.apu.tick(master_cycles_elapsed, &mut self.debug_hooks, SPC700_DEBUG_CPU)
         {
             self.audio_downsampler.collect_sample(sample_l, sample_r);
         }
@@ -401,6 +465,8 @@ impl EmulatorTrait for SnesEmulator {
     fn take_rom_from(&mut self, other: &mut Self) {
         self.memory.take_rom_from(&mut other.memory);
         self.coprocessor_roms = mem::take(&mut other.coprocessor_roms);
+        self.memory.set_cheats(other.memory.take_cheats());
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
     }
 
     fn soft_reset(&mut self) {
@@ -420,13 +486,84 @@ impl EmulatorTrait for SnesEmulator {
         log::info!("Hard resetting");
 
         let rom = self.memory.take_rom();
+        let cheats = self.memory.take_cheats();
+        let debug_hooks = mem::take(&mut self.debug_hooks);
 
         let coprocessor_roms = mem::take(&mut self.coprocessor_roms);
         *self = Self::create(rom, self.emulator_config, coprocessor_roms, save_writer)
             .expect("Hard resetting should never fail to load");
+        self.memory.set_cheats(cheats);
+        self.debug_hooks = debug_hooks;
     }
 
     fn timing_mode(&self) -> TimingMode {
         self.timing_mode
     }
 }
+
+fn main_cpu_pc(main_cpu: &Wdc65816) -> u32 {
+    let registers = main_cpu.registers();
+    (u32::from(registers.pbr) << 16) | u32::from(registers.pc)
+}
+
+impl Debuggable for SnesEmulator {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, cpu: usize) -> Vec<u32> {
+        match cpu {
+            MAIN_CPU_DEBUG_CPU => self.main_cpu.debug_registers().map(u32::from).to_vec(),
+            SPC700_DEBUG_CPU => self.apu.spc700().debug_registers().map(u32::from).to_vec(),
+            _ => vec![],
+        }
+    }
+
+    fn write_registers(&mut self, cpu: usize, registers: &[u32]) {
+        match cpu {
+            MAIN_CPU_DEBUG_CPU => {
+                if let Ok(registers) = <[u32; 10]>::try_from(registers) {
+                    self.main_cpu.set_debug_registers(registers.map(|value| value as u16));
+                }
+            }
+            SPC700_DEBUG_CPU => {
+                if let Ok(registers) = <[u32; 6]>::try_from(registers) {
+                    self.apu.spc700_mut().set_debug_registers(registers.map(|value| value as u16));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read_memory(&mut self, cpu: usize, address: u32) -> u8 {
+        match cpu {
+            SPC700_DEBUG_CPU => self.apu.debug_read(address as u16),
+            _ => new_bus!(self).read(address & 0xFFFFFF),
+        }
+    }
+
+    fn write_memory(&mut self, cpu: usize, address: u32, value: u8) {
+        match cpu {
+            SPC700_DEBUG_CPU => self.apu.debug_write(address as u16, value),
+            _ => new_bus!(self).apply_write(address & 0xFFFFFF, value),
+        }
+    }
+
+    fn disassemble(&mut self, cpu: usize, address: u32) -> (String, u32) {
+        match cpu {
+            SPC700_DEBUG_CPU => {
+                let (text, len) = self.apu.disassemble(address as u16);
+                (text, len.into())
+            }
+            _ => self.main_cpu.disassemble(&mut new_bus!(self), address & 0xFFFFFF),
+        }
+    }
+}
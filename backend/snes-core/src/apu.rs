@@ -4,14 +4,17 @@
 
 mod bootrom;
 mod dsp;
+mod spc;
 mod timer;
 
 use crate::apu::dsp::AudioDsp;
 use crate::apu::timer::{FastTimer, SlowTimer};
 use crate::constants;
 use bincode::{Decode, Encode};
+use jgenesis_common::debug::DebugHooks;
 use jgenesis_common::frontend::TimingMode;
 use jgenesis_common::num::GetBit;
+use spc700_emu::debug::DebugBus;
 use spc700_emu::traits::BusInterface;
 use spc700_emu::Spc700;
 
@@ -66,13 +69,7 @@ impl ApuRegisters {
                 log::warn!("Unimplemented APU test register was read");
                 0x00
             }
-            1 => {
-                // Control register
-                u8::from(self.timer_0.enabled())
-                    | (u8::from(self.timer_1.enabled()) << 1)
-                    | (u8::from(self.timer_2.enabled()) << 2)
-                    | (u8::from(self.boot_rom_mapped) << 7)
-            }
+            1 => self.read_control(),
             2 => dsp.read_address(),
             3 => dsp.read_register(),
             4 => self.main_cpu_communication[0],
@@ -91,6 +88,13 @@ impl ApuRegisters {
         }
     }
 
+    fn read_control(&self) -> u8 {
+        u8::from(self.timer_0.enabled())
+            | (u8::from(self.timer_1.enabled()) << 1)
+            | (u8::from(self.timer_2.enabled()) << 2)
+            | (u8::from(self.boot_rom_mapped) << 7)
+    }
+
     fn write(&mut self, register: u16, value: u8, dsp: &mut AudioDsp) {
         log::trace!("SPC700 register write: {register} {value:02X}");
 
@@ -263,8 +267,15 @@ impl Apu {
         apu
     }
 
+    /// Advance the APU by the given number of main master clock cycles. `debug_cpu` is the
+    /// SPC700's debugger CPU index in `debug_hooks`.
     #[must_use]
-    pub fn tick(&mut self, main_master_cycles: u64) -> ApuTickEffect {
+    pub fn tick(
+        &mut self,
+        main_master_cycles: u64,
+        debug_hooks: &mut DebugHooks,
+        debug_cpu: usize,
+    ) -> ApuTickEffect {
         let apu_master_clock_frequency = if self.enable_audio_60hz_hack {
             ADJUSTED_APU_MASTER_CLOCK_FREQUENCY
         } else {
@@ -274,15 +285,8 @@ impl Apu {
 
         while self.master_cycles_product >= 24 * self.main_master_clock_frequency {
             self.master_cycles_product -= 24 * self.main_master_clock_frequency;
-            self.clock();
-
-            self.sample_divider -= 1;
-            if self.sample_divider == 0 {
-                self.sample_divider = SAMPLE_DIVIDER;
 
-                let (sample_l, sample_r) = self.dsp.clock(&mut self.audio_ram);
-                let sample_l = f64::from(sample_l) / -f64::from(i16::MIN);
-                let sample_r = f64::from(sample_r) / -f64::from(i16::MIN);
+            if let Some((sample_l, sample_r)) = self.clock_and_sample(debug_hooks, debug_cpu) {
                 return ApuTickEffect::OutputSample(sample_l, sample_r);
             }
         }
@@ -290,8 +294,66 @@ impl Apu {
         ApuTickEffect::None
     }
 
-    fn clock(&mut self) {
-        self.spc700.tick(&mut new_spc700_bus!(self));
+    /// Run the APU until it outputs a sample, independent of the main SNES clock. Used for
+    /// standalone SPC playback.
+    #[must_use]
+    pub fn generate_sample(
+        &mut self,
+        debug_hooks: &mut DebugHooks,
+        debug_cpu: usize,
+    ) -> (f64, f64) {
+        loop {
+            if let Some(sample) = self.clock_and_sample(debug_hooks, debug_cpu) {
+                return sample;
+            }
+        }
+    }
+
+    fn clock_and_sample(
+        &mut self,
+        debug_hooks: &mut DebugHooks,
+        debug_cpu: usize,
+    ) -> Option<(f64, f64)> {
+        self.clock(debug_hooks, debug_cpu);
+
+        self.sample_divider -= 1;
+        if self.sample_divider != 0 {
+            return None;
+        }
+        self.sample_divider = SAMPLE_DIVIDER;
+
+        let (sample_l, sample_r) = self.dsp.clock(&mut self.audio_ram);
+        let sample_l = f64::from(sample_l) / -f64::from(i16::MIN);
+        let sample_r = f64::from(sample_r) / -f64::from(i16::MIN);
+        Some((sample_l, sample_r))
+    }
+
+    fn clock(&mut self, debug_hooks: &mut DebugHooks, debug_cpu: usize) {
+        if debug_hooks.checks_execution(debug_cpu)
+            && !self.spc700.is_mid_instruction()
+            && debug_hooks.check_execute(debug_cpu, self.spc700.registers().pc.into())
+        {
+            // Stopped in the debugger; the SPC700 stalls while the timers and DSP keep running
+            // until the emulator stops being ticked
+        } else {
+            let tracing = debug_hooks.is_tracing(debug_cpu);
+            if tracing && !self.spc700.is_mid_instruction() {
+                self.spc700.trace_instruction(&mut new_spc700_bus!(self), debug_hooks, debug_cpu);
+            }
+
+            if debug_hooks.checks_accesses(debug_cpu) {
+                self.spc700.tick(&mut DebugBus::new(
+                    &mut new_spc700_bus!(self),
+                    debug_hooks,
+                    debug_cpu,
+                ));
+            } else {
+                self.spc700.tick(&mut new_spc700_bus!(self));
+            }
+            if tracing {
+                debug_hooks.add_trace_cycles(debug_cpu, 1);
+            }
+        }
 
         self.registers.timer_0.tick();
         self.registers.timer_1.tick();
@@ -315,4 +377,27 @@ impl Apu {
     pub fn set_audio_60hz_hack(&mut self, audio_60hz_hack: bool) {
         self.enable_audio_60hz_hack = audio_60hz_hack;
     }
+
+    pub fn spc700(&self) -> &Spc700 {
+        &self.spc700
+    }
+
+    pub fn spc700_mut(&mut self) -> &mut Spc700 {
+        &mut self.spc700
+    }
+
+    /// Read from the SPC700 address space for the debugger.
+    pub fn debug_read(&mut self, address: u16) -> u8 {
+        new_spc700_bus!(self).read(address)
+    }
+
+    /// Write to the SPC700 address space for the debugger.
+    pub fn debug_write(&mut self, address: u16, value: u8) {
+        new_spc700_bus!(self).write(address, value);
+    }
+
+    /// Disassemble the SPC700 instruction at the given address for the debugger.
+    pub fn disassemble(&mut self, address: u16) -> (String, u16) {
+        spc700_emu::disassemble::disassemble(&mut new_spc700_bus!(self), address)
+    }
 }
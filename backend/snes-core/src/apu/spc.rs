@@ -0,0 +1,259 @@
+//! Conversion between APU state and the `.spc` file format (v0.30), which contains a snapshot of
+//! audio RAM, the DSP registers, and the SPC700 registers along with an optional ID666 text tag
+
+use crate::apu::{AUDIO_RAM_LEN, Apu};
+use crate::spc::{SPC_FILE_LEN, SpcError, SpcMetadata};
+use jgenesis_common::debug::DebugHooks;
+use jgenesis_common::frontend::TimingMode;
+use jgenesis_common::num::GetBit;
+use spc700_emu::{Registers, StatusRegister};
+
+const SIGNATURE: &[u8; 33] = b"SNES-SPC700 Sound File Data v0.30";
+const HAS_ID666_TAG: u8 = 26;
+const MINOR_VERSION: u8 = 30;
+
+const PC_OFFSET: usize = 0x25;
+const A_OFFSET: usize = 0x27;
+const X_OFFSET: usize = 0x28;
+const Y_OFFSET: usize = 0x29;
+const PSW_OFFSET: usize = 0x2A;
+const SP_OFFSET: usize = 0x2B;
+
+// ID666 tag fields, text format
+const SONG_TITLE: (usize, usize) = (0x2E, 32);
+const GAME_TITLE: (usize, usize) = (0x4E, 32);
+const DUMPER: (usize, usize) = (0x6E, 16);
+const COMMENTS: (usize, usize) = (0x7E, 32);
+const PLAY_SECONDS: (usize, usize) = (0xA9, 3);
+const FADE_MILLISECONDS: (usize, usize) = (0xAC, 5);
+const ARTIST: (usize, usize) = (0xB1, 32);
+
+const RAM_OFFSET: usize = 0x100;
+const DSP_REGISTERS_OFFSET: usize = 0x10100;
+// RAM at $FFC0-$FFFF, which is hidden behind the IPL ROM while the ROM is mapped
+const EXTRA_RAM_OFFSET: usize = 0x101C0;
+
+const DSP_KON_ADDRESS: u8 = 0x4C;
+const DSP_KOF_ADDRESS: u8 = 0x5C;
+
+// Power-on value of the TEST register
+const TEST_REGISTER_DEFAULT: u8 = 0x0A;
+
+impl Apu {
+    /// Serialize the current APU state into an `.spc` file.
+    #[must_use]
+    pub fn to_spc_file(&self, metadata: &SpcMetadata) -> Vec<u8> {
+        if self.spc700.is_mid_instruction() {
+            // SPC files can only represent the SPC700 between instructions
+            let mut apu = self.clone();
+            while apu.spc700.is_mid_instruction() {
+                apu.clock(&mut DebugHooks::default(), 0);
+            }
+            return apu.to_spc_file(metadata);
+        }
+
+        let mut file = vec![0; SPC_FILE_LEN];
+
+        file[..SIGNATURE.len()].copy_from_slice(SIGNATURE);
+        file[0x21] = 26;
+        file[0x22] = 26;
+        file[0x23] = HAS_ID666_TAG;
+        file[0x24] = MINOR_VERSION;
+
+        let registers = self.spc700.registers();
+        file[PC_OFFSET..PC_OFFSET + 2].copy_from_slice(&registers.pc.to_le_bytes());
+        file[A_OFFSET] = registers.a;
+        file[X_OFFSET] = registers.x;
+        file[Y_OFFSET] = registers.y;
+        file[PSW_OFFSET] = registers.psw.into();
+        file[SP_OFFSET] = registers.sp;
+
+        write_text(&mut file, SONG_TITLE, &metadata.song_title);
+        write_text(&mut file, GAME_TITLE, &metadata.game_title);
+        write_text(&mut file, DUMPER, &metadata.dumper);
+        write_text(&mut file, COMMENTS, &metadata.comments);
+        write_text(&mut file, ARTIST, &metadata.artist);
+        if let Some(play_seconds) = metadata.play_seconds {
+            write_text(&mut file, PLAY_SECONDS, &play_seconds.min(999).to_string());
+        }
+        if let Some(fade_milliseconds) = metadata.fade_milliseconds {
+            write_text(&mut file, FADE_MILLISECONDS, &fade_milliseconds.min(99999).to_string());
+        }
+
+        let ram = &mut file[RAM_OFFSET..RAM_OFFSET + AUDIO_RAM_LEN];
+        ram.copy_from_slice(self.audio_ram.as_ref());
+        ram[0xF0..0x100].copy_from_slice(&self.io_register_snapshot());
+
+        let mut dsp = self.dsp.clone();
+        for address in 0..0x80 {
+            dsp.write_address(address);
+            file[DSP_REGISTERS_OFFSET + address as usize] = dsp.read_register();
+        }
+
+        file[EXTRA_RAM_OFFSET..EXTRA_RAM_OFFSET + 0x40].copy_from_slice(&self.audio_ram[0xFFC0..]);
+
+        file
+    }
+
+    /// Values that the SPC700 would read from the I/O registers at $F0-$FF.
+    fn io_register_snapshot(&self) -> [u8; 16] {
+        let registers = &self.registers;
+        let communication = registers.main_cpu_communication;
+        [
+            TEST_REGISTER_DEFAULT,
+            registers.read_control(),
+            self.dsp.read_address(),
+            self.dsp.read_register(),
+            communication[0],
+            communication[1],
+            communication[2],
+            communication[3],
+            registers.auxio4,
+            registers.auxio5,
+            registers.timer_0.divider(),
+            registers.timer_1.divider(),
+            registers.timer_2.divider(),
+            registers.timer_0.peek_output(),
+            registers.timer_1.peek_output(),
+            registers.timer_2.peek_output(),
+        ]
+    }
+
+    /// Create an APU from the contents of an `.spc` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is too short or does not have a valid SPC signature.
+    pub fn from_spc_file(file: &[u8]) -> Result<(Self, SpcMetadata), SpcError> {
+        if file.len() < SPC_FILE_LEN {
+            return Err(SpcError::TooShort { len: file.len() });
+        }
+
+        // Only check the signature up to the version number; the v0.30 layout is used by all
+        // versions of the format
+        let signature_len = SIGNATURE.len() - "0.30".len();
+        if file[..signature_len] != SIGNATURE[..signature_len] {
+            return Err(SpcError::InvalidSignature);
+        }
+
+        let metadata = if file[0x23] == HAS_ID666_TAG {
+            SpcMetadata {
+                song_title: read_text(file, SONG_TITLE),
+                game_title: read_text(file, GAME_TITLE),
+                artist: read_text(file, ARTIST),
+                dumper: read_text(file, DUMPER),
+                comments: read_text(file, COMMENTS),
+                play_seconds: read_text(file, PLAY_SECONDS).parse().ok(),
+                fade_milliseconds: read_text(file, FADE_MILLISECONDS).parse().ok(),
+            }
+        } else {
+            SpcMetadata::default()
+        };
+
+        let mut apu = Self::new(TimingMode::Ntsc, false);
+
+        apu.audio_ram.copy_from_slice(&file[RAM_OFFSET..RAM_OFFSET + AUDIO_RAM_LEN]);
+
+        let io_registers: [u8; 16] = apu.audio_ram[0xF0..0x100].try_into().unwrap();
+        apu.load_io_registers(io_registers);
+
+        apu.spc700.set_registers(Registers {
+            a: file[A_OFFSET],
+            x: file[X_OFFSET],
+            y: file[Y_OFFSET],
+            sp: file[SP_OFFSET],
+            pc: u16::from_le_bytes([file[PC_OFFSET], file[PC_OFFSET + 1]]),
+            psw: StatusRegister::from(file[PSW_OFFSET]),
+        });
+
+        let dsp_registers = &file[DSP_REGISTERS_OFFSET..DSP_REGISTERS_OFFSET + 0x80];
+        for address in 0..0x80 {
+            if address != DSP_KON_ADDRESS && address != DSP_KOF_ADDRESS {
+                apu.dsp.write_address(address);
+                apu.dsp.write_register(dsp_registers[address as usize]);
+            }
+        }
+
+        // Write key off after FLG because a soft reset keys off every voice, then key on last so
+        // that voices that were playing when the file was saved restart from the beginning of
+        // their samples (the SPC format does not store voice playback state)
+        let key_off = dsp_registers[DSP_KOF_ADDRESS as usize];
+        let key_on = dsp_registers[DSP_KON_ADDRESS as usize] & !key_off;
+        apu.dsp.write_address(DSP_KOF_ADDRESS);
+        apu.dsp.write_register(key_off);
+        apu.dsp.write_address(DSP_KON_ADDRESS);
+        apu.dsp.write_register(key_on);
+
+        apu.dsp.write_address(io_registers[2]);
+
+        Ok((apu, metadata))
+    }
+
+    fn load_io_registers(&mut self, io_registers: [u8; 16]) {
+        let registers = &mut self.registers;
+
+        let control = io_registers[1];
+        registers.boot_rom_mapped = control.bit(7);
+
+        registers.main_cpu_communication.copy_from_slice(&io_registers[4..8]);
+        registers.auxio4 = io_registers[8];
+        registers.auxio5 = io_registers[9];
+
+        registers.timer_0.set_divider(io_registers[10]);
+        registers.timer_1.set_divider(io_registers[11]);
+        registers.timer_2.set_divider(io_registers[12]);
+
+        registers.timer_0.set_enabled(control.bit(0));
+        registers.timer_1.set_enabled(control.bit(1));
+        registers.timer_2.set_enabled(control.bit(2));
+
+        registers.timer_0.set_output(io_registers[13]);
+        registers.timer_1.set_output(io_registers[14]);
+        registers.timer_2.set_output(io_registers[15]);
+    }
+}
+
+fn write_text(file: &mut [u8], (offset, len): (usize, usize), text: &str) {
+    let bytes = text.as_bytes();
+    let len = bytes.len().min(len);
+    file[offset..offset + len].copy_from_slice(&bytes[..len]);
+}
+
+fn read_text(file: &[u8], (offset, len): (usize, usize)) -> String {
+    let field = &file[offset..offset + len];
+    let end = field.iter().position(|&b| b == 0).unwrap_or(len);
+    String::from_utf8_lossy(&field[..end]).trim().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spc_file_round_trip() {
+        let mut apu = Apu::new(TimingMode::Ntsc, false);
+        let mut debug_hooks = DebugHooks::default();
+        for _ in 0..10000 {
+            let _ = apu.generate_sample(&mut debug_hooks, 0);
+        }
+
+        let metadata = SpcMetadata {
+            song_title: "Title".into(),
+            game_title: "Game".into(),
+            play_seconds: Some(120),
+            ..SpcMetadata::default()
+        };
+        let file = apu.to_spc_file(&metadata);
+        assert_eq!(file.len(), SPC_FILE_LEN);
+
+        let (loaded, loaded_metadata) = Apu::from_spc_file(&file).unwrap();
+        assert_eq!(loaded_metadata, metadata);
+        assert_eq!(loaded.to_spc_file(&metadata), file);
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let file = vec![0; SPC_FILE_LEN];
+        assert!(matches!(Apu::from_spc_file(&file), Err(SpcError::InvalidSignature)));
+    }
+}
@@ -65,6 +65,15 @@ impl<const MCLK_DIVIDER: u8> Timer<MCLK_DIVIDER> {
         self.output = 0;
         output
     }
+
+    /// Read the 4-bit output counter without clearing it.
+    pub fn peek_output(&self) -> u8 {
+        self.output & 0x0F
+    }
+
+    pub fn set_output(&mut self, output: u8) {
+        self.output = output & 0x0F;
+    }
 }
 
 pub type SlowTimer = Timer<128>;
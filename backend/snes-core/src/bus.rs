@@ -192,13 +192,17 @@ impl<'a> BusInterface for Bus<'a> {
                 self.access_master_cycles = SLOW_MASTER_CYCLES;
 
                 // Cartridge (Memory-1)
-                self.memory.read_cartridge(address).unwrap_or(self.memory.cpu_open_bus())
+                let value =
+                    self.memory.read_cartridge(address).unwrap_or(self.memory.cpu_open_bus());
+                self.memory.apply_rom_cheats(address, value)
             }
             (0x80..=0xBF, 0x8000..=0xFFFF) | (0xC0..=0xFF, _) => {
                 self.access_master_cycles = self.cpu_registers.memory_2_speed().master_cycles();
 
                 // Cartridge (Memory-2)
-                self.memory.read_cartridge(address).unwrap_or(self.memory.cpu_open_bus())
+                let value =
+                    self.memory.read_cartridge(address).unwrap_or(self.memory.cpu_open_bus());
+                self.memory.apply_rom_cheats(address, value)
             }
             (0x7E..=0x7F, _) => {
                 self.access_master_cycles = SLOW_MASTER_CYCLES;
@@ -0,0 +1,105 @@
+//! SNES cheat code decoding
+//!
+//! Supported formats:
+//! * Game Genie: `XXXX-XXXX`, which patches a byte of ROM
+//! * Pro Action Replay: `AAAAAAVV`
+//! * Raw: `AAAAAA:VV`
+//!
+//! Pro Action Replay and raw codes that target WRAM or cartridge SRAM are written every frame;
+//! codes that target any other address patch ROM reads.
+
+use jgenesis_common::cheats::{parse_hex, split_raw_code, CheatError, CheatPatch, CheatResult};
+
+// Game Genie codes substitute these characters for the hex digits 0-F
+const GAME_GENIE_CHARS: &[u8; 16] = b"DF4709156BC8A23E";
+
+/// Decode a Game Genie, Pro Action Replay, or raw cheat code.
+///
+/// # Errors
+///
+/// Returns an error if the code is not in a recognized format.
+pub fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+    let code = code.trim().to_ascii_uppercase();
+
+    if let Some((address, value)) = split_raw_code(&code) {
+        let address = parse_hex(&code, address)?;
+        let value = parse_hex(&code, value)?;
+        if address > 0xFFFFFF || value > 0xFF {
+            return Err(CheatError::UnrecognizedFormat(code));
+        }
+
+        return Ok(vec![address_patch(address, value as u8)]);
+    }
+
+    match code.len() {
+        8 => {
+            let raw = parse_hex(&code, &code)?;
+            Ok(vec![address_patch(raw >> 8, raw as u8)])
+        }
+        9 if code.as_bytes()[4] == b'-' => decode_game_genie(&code).map(|patch| vec![patch]),
+        _ => Err(CheatError::UnrecognizedFormat(code)),
+    }
+}
+
+fn address_patch(address: u32, value: u8) -> CheatPatch {
+    if is_ram_address(address) {
+        CheatPatch::ram(address, value)
+    } else {
+        CheatPatch::rom(address, value, None)
+    }
+}
+
+// This is a heuristic; whether $6000-$7FFF and banks $70-$7D are SRAM or ROM depends on the
+// cartridge, but codes in those ranges are much more likely to target SRAM
+fn is_ram_address(address: u32) -> bool {
+    let bank = (address >> 16) as u8;
+    let offset = address as u16;
+    matches!(
+        (bank, offset),
+        (0x7E..=0x7F, _) | (0x00..=0x3F | 0x80..=0xBF | 0x70..=0x7D | 0xF0..=0xFF, 0x0000..=0x7FFF)
+    )
+}
+
+// After substituting the Game Genie characters, the first 2 hex digits are the value and the
+// remaining 6 are the scrambled address
+fn decode_game_genie(code: &str) -> CheatResult<CheatPatch> {
+    let mut n = 0_u32;
+    for ch in code.chars().filter(|&ch| ch != '-') {
+        let Some(position) = GAME_GENIE_CHARS.iter().position(|&gg_char| char::from(gg_char) == ch)
+        else {
+            return Err(CheatError::InvalidCharacter { code: code.into(), ch });
+        };
+        n = (n << 4) | position as u32;
+    }
+
+    let value = (n >> 24) as u8;
+
+    // Address bits are scrambled: ijklqrst opabcduv wxefghmn -> abcdefgh ijklmnop qrstuvwx
+    let address = ((n & 0x003C00) << 10)
+        | ((n & 0x00003C) << 14)
+        | ((n & 0xF00000) >> 8)
+        | ((n & 0x000003) << 10)
+        | ((n & 0x00C000) >> 6)
+        | ((n & 0x0F0000) >> 12)
+        | ((n & 0x0003C0) >> 6);
+
+    Ok(CheatPatch::rom(address, value, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_genie() {
+        assert_eq!(decode_cheat("C264-64D7").unwrap(), vec![CheatPatch::rom(0x008E28, 0xAD, None)]);
+        assert!(decode_cheat("C264-64DG").is_err());
+    }
+
+    #[test]
+    fn pro_action_replay() {
+        assert_eq!(decode_cheat("7E0DBE09").unwrap(), vec![CheatPatch::ram(0x7E0DBE, 0x09)]);
+        assert_eq!(decode_cheat("00A0F0EA").unwrap(), vec![CheatPatch::rom(0x00A0F0, 0xEA, None)]);
+        assert_eq!(decode_cheat("7E0019:02").unwrap(), vec![CheatPatch::ram(0x7E0019, 0x02)]);
+    }
+}
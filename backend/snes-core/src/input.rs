@@ -31,10 +31,17 @@ pub enum SuperScopeButton {
     TurboToggle,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnesMouseButton {
+    Left,
+    Right,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SnesButton {
     Controller(SnesControllerButton),
     SuperScope(SuperScopeButton),
+    Mouse(SnesMouseButton),
 }
 
 impl SnesJoypadState {
@@ -95,10 +102,36 @@ impl SuperScopeState {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
+pub struct SnesMouseState {
+    pub left: bool,
+    pub right: bool,
+    // Pointer position in SNES pixels, or None if the pointer is outside of the frame.
+    // The mouse reports how far this position has moved each time the game strobes it
+    pub position: Option<(u16, u16)>,
+}
+
+impl SnesMouseState {
+    #[inline]
+    pub fn set_button(&mut self, button: SnesMouseButton, pressed: bool) {
+        match button {
+            SnesMouseButton::Left => self.left = pressed,
+            SnesMouseButton::Right => self.right = pressed,
+        }
+    }
+}
+
+/// Device connected to a controller port.
+///
+/// The Super Scope and the Super Multitap are only supported in port 2; a port 1 device of either
+/// type is treated as if nothing is plugged in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 pub enum SnesInputDevice {
     Controller(SnesJoypadState),
     SuperScope(SuperScopeState),
+    Mouse(SnesMouseState),
+    /// Super Multitap with controllers for players 2-5
+    Multitap([SnesJoypadState; 4]),
 }
 
 impl Default for SnesInputDevice {
@@ -109,7 +142,7 @@ impl Default for SnesInputDevice {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Encode, Decode)]
 pub struct SnesInputs {
-    pub p1: SnesJoypadState,
+    pub p1: SnesInputDevice,
     pub p2: SnesInputDevice,
 }
 
@@ -117,19 +150,41 @@ impl SnesInputs {
     #[inline]
     pub fn set_button(&mut self, button: SnesButton, player: Player, pressed: bool) {
         match (button, player) {
-            (SnesButton::SuperScope(button), _) => match &mut self.p2 {
-                SnesInputDevice::SuperScope(super_scope_state) => {
+            (SnesButton::SuperScope(button), _) => {
+                if let SnesInputDevice::SuperScope(super_scope_state) = &mut self.p2 {
                     super_scope_state.set_button(button, pressed);
                 }
-                SnesInputDevice::Controller(_) => {}
-            },
-            (SnesButton::Controller(button), Player::One) => self.p1.set_button(button, pressed),
+            }
+            (SnesButton::Mouse(button), _) => {
+                // Mouse buttons apply to every connected mouse
+                for device in [&mut self.p1, &mut self.p2] {
+                    if let SnesInputDevice::Mouse(mouse_state) = device {
+                        mouse_state.set_button(button, pressed);
+                    }
+                }
+            }
+            (SnesButton::Controller(button), Player::One) => {
+                if let SnesInputDevice::Controller(joypad_state) = &mut self.p1 {
+                    joypad_state.set_button(button, pressed);
+                }
+            }
             (SnesButton::Controller(button), Player::Two) => match &mut self.p2 {
                 SnesInputDevice::Controller(joypad_state) => {
                     joypad_state.set_button(button, pressed);
                 }
-                SnesInputDevice::SuperScope(_) => {}
+                SnesInputDevice::Multitap(joypad_states) => {
+                    joypad_states[0].set_button(button, pressed);
+                }
+                SnesInputDevice::SuperScope(_) | SnesInputDevice::Mouse(_) => {}
             },
+            (SnesButton::Controller(button), _) => {
+                // Players 3-5 are only connected through the multitap
+                if let SnesInputDevice::Multitap(joypad_states) = &mut self.p2 {
+                    if let Some(joypad_state) = joypad_states.get_mut(player.index() - 1) {
+                        joypad_state.set_button(button, pressed);
+                    }
+                }
+            }
         }
     }
 
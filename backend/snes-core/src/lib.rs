@@ -2,7 +2,9 @@ pub mod api;
 mod apu;
 mod audio;
 mod bus;
+pub mod cheats;
 pub(crate) mod constants;
 pub mod input;
 mod memory;
 mod ppu;
+pub mod spc;
@@ -10,10 +10,11 @@ use crate::memory::cartridge::Cartridge;
 use crate::memory::inputs::InputState;
 use crate::ppu::Ppu;
 use bincode::{Decode, Encode};
+use jgenesis_common::cheats::CheatSet;
 use jgenesis_common::frontend::{SaveWriter, TimingMode};
 use jgenesis_common::num::{GetBit, U16Ext, U24Ext};
 use jgenesis_proc_macros::PartialClone;
-use std::array;
+use std::{array, mem};
 use std::num::NonZeroU64;
 
 const MAIN_RAM_LEN: usize = 128 * 1024;
@@ -46,6 +47,7 @@ pub struct Memory {
     main_ram: Box<MainRam>,
     wram_port_address: u32,
     cpu_open_bus: u8,
+    cheats: CheatSet,
 }
 
 impl Memory {
@@ -73,6 +75,7 @@ impl Memory {
             main_ram: vec![0; MAIN_RAM_LEN].into_boxed_slice().try_into().unwrap(),
             wram_port_address: 0,
             cpu_open_bus: 0,
+            cheats: CheatSet::new(),
         })
     }
 
@@ -165,6 +168,37 @@ impl Memory {
         self.cartridge.take_rom_from(&mut other.cartridge);
     }
 
+    pub fn set_cheats(&mut self, cheats: CheatSet) {
+        self.cheats = cheats;
+    }
+
+    pub fn take_cheats(&mut self) -> CheatSet {
+        mem::take(&mut self.cheats)
+    }
+
+    #[inline]
+    pub fn apply_rom_cheats(&self, address: u32, value: u8) -> u8 {
+        self.cheats.apply_rom_read(address, value)
+    }
+
+    // Write RAM cheat values to WRAM or cartridge SRAM; this should be called once per frame
+    pub fn apply_ram_cheats(&mut self) {
+        let cheats = mem::take(&mut self.cheats);
+        for patch in cheats.ram_patches() {
+            let bank = (patch.address >> 16) as u8;
+            let offset = patch.address as u16;
+            match (bank, offset) {
+                (0x7E..=0x7F, _) | (0x00..=0x3F | 0x80..=0xBF, 0x0000..=0x1FFF) => {
+                    self.write_wram(patch.address, patch.value);
+                }
+                _ => {
+                    self.write_cartridge(patch.address, patch.value);
+                }
+            }
+        }
+        self.cheats = cheats;
+    }
+
     pub fn sram(&self) -> Option<&[u8]> {
         self.cartridge.sram()
     }
@@ -385,13 +419,14 @@ impl CpuInternalRegisters {
             0x4016 => {
                 // JOYA: Manual joypad register A
                 // Bits 7-2 are open bus
-                u8::from(self.input_state.next_manual_p1_bit()) | (cpu_open_bus & 0xFC)
+                self.input_state.read_port_1() | (cpu_open_bus & 0xFC)
             }
             0x4017 => {
                 // JOYB: Manual joypad register B
                 // Bits 2-4 always set
                 // Bits 7-5 are open bus
-                0x1C | u8::from(self.input_state.next_manual_p2_bit()) | (cpu_open_bus & 0xE0)
+                let iobit = self.programmable_joypad_port.bit(7);
+                0x1C | self.input_state.read_port_2(iobit) | (cpu_open_bus & 0xE0)
             }
             0x4210 => {
                 // RDNMI: VBlank NMI flag and CPU version number
@@ -444,23 +479,35 @@ impl CpuInternalRegisters {
             }
             0x4218 => {
                 // JOY1L: Joypad 1, low byte (auto read)
-                self.input_state.auto_joypad_p1_inputs().lsb()
+                self.input_state.auto_joypad_inputs(0).lsb()
             }
             0x4219 => {
                 // JOY1H: Joypad 1, high byte (auto read)
-                self.input_state.auto_joypad_p1_inputs().msb()
+                self.input_state.auto_joypad_inputs(0).msb()
             }
             0x421A => {
                 // JOY2L: Joypad 2, low byte (auto read)
-                self.input_state.auto_joypad_p2_inputs().lsb()
+                self.input_state.auto_joypad_inputs(1).lsb()
             }
             0x421B => {
                 // JOY2H: Joypad 2, high byte (auto read)
-                self.input_state.auto_joypad_p2_inputs().msb()
+                self.input_state.auto_joypad_inputs(1).msb()
+            }
+            0x421C => {
+                // JOY3L: Joypad 3, low byte (auto read from port 1 D1)
+                self.input_state.auto_joypad_inputs(2).lsb()
+            }
+            0x421D => {
+                // JOY3H: Joypad 3, high byte (auto read from port 1 D1)
+                self.input_state.auto_joypad_inputs(2).msb()
+            }
+            0x421E => {
+                // JOY4L: Joypad 4, low byte (auto read from port 2 D1)
+                self.input_state.auto_joypad_inputs(3).lsb()
             }
-            0x421C..=0x421F => {
-                // JOY3L/JOY3H/JOY4L/JOY4H: Joypad 3/4 (not implemented)
-                0x00
+            0x421F => {
+                // JOY4H: Joypad 4, high byte (auto read from port 2 D1)
+                self.input_state.auto_joypad_inputs(3).msb()
             }
             0x4300..=0x437F => {
                 // DMA registers
@@ -806,7 +853,7 @@ impl CpuInternalRegisters {
         inputs: &SnesInputs,
     ) {
         // Progress auto joypad read if it's running
-        self.input_state.tick(master_cycles_elapsed, *inputs);
+        self.input_state.tick(master_cycles_elapsed, *inputs, self.programmable_joypad_port.bit(7));
 
         // Update VBlank, HBlank, and NMI flags
         self.update_hv_blank_flags(ppu);
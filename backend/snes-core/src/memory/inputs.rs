@@ -1,7 +1,6 @@
-use crate::input::{SnesInputDevice, SnesInputs, SnesJoypadState, SuperScopeState};
+use crate::input::{SnesInputDevice, SnesInputs, SnesJoypadState, SnesMouseState, SuperScopeState};
 use bincode::{Decode, Encode};
 use jgenesis_common::num::GetBit;
-use std::mem;
 
 const AUTO_JOYPAD_DURATION_MCLK: u64 = 4224;
 
@@ -67,42 +66,115 @@ impl SuperScopeRegister {
     }
 }
 
+// Largest motion that the mouse can report on each axis
+const MOUSE_MAX_DELTA: i32 = 127;
+
+#[derive(Debug, Clone, Copy, Default, Encode, Decode)]
+struct MouseRegister {
+    // 0 = low, 1 = medium, 2 = high
+    sensitivity: u8,
+    last_position: Option<(u16, u16)>,
+}
+
+impl MouseRegister {
+    fn cycle_sensitivity(&mut self) {
+        self.sensitivity = (self.sensitivity + 1) % 3;
+    }
+
+    // Higher sensitivity settings scale up motion by 1.5x and 2x
+    fn scale_motion(self, delta: i32) -> u8 {
+        let scaled = match self.sensitivity {
+            0 => delta,
+            1 => delta * 3 / 2,
+            _ => delta * 2,
+        };
+
+        // Motion is reported as sign and magnitude; the sign bit is set for left and up
+        let magnitude = scaled.unsigned_abs().min(MOUSE_MAX_DELTA as u32) as u8;
+        (u8::from(scaled < 0) << 7) | magnitude
+    }
+
+    /// Report the buttons and the motion since the previous strobe, as 32 bits read MSB first.
+    fn latch(&mut self, mouse: SnesMouseState) -> u32 {
+        let (delta_x, delta_y) = match (self.last_position, mouse.position) {
+            (Some((last_x, last_y)), Some((x, y))) => {
+                (i32::from(x) - i32::from(last_x), i32::from(y) - i32::from(last_y))
+            }
+            _ => (0, 0),
+        };
+        self.last_position = mouse.position;
+
+        // Bits 19-16 are the mouse's device signature (0001)
+        (u32::from(mouse.right) << 23)
+            | (u32::from(mouse.left) << 22)
+            | (u32::from(self.sensitivity) << 20)
+            | (1 << 16)
+            | (u32::from(self.scale_motion(delta_y)) << 8)
+            | u32::from(self.scale_motion(delta_x))
+    }
+}
+
+// Joypads report 16 bits and then read out 1s
+fn joypad_shift_register(joypad_state: SnesJoypadState) -> u32 {
+    (u32::from(joypad_state.to_register_word()) << 16) | 0xFFFF
+}
+
+fn shift_out(register: &mut u32) -> bool {
+    let bit = register.bit(31);
+    *register = (*register << 1) | 1;
+    bit
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct InputState {
     auto_read_cycles_remaining: u64,
-    auto_joypad_p1_inputs: u16,
-    auto_joypad_p2_inputs: u16,
+    // JOY1-JOY4, i.e. port 1 D0, port 2 D0, port 1 D1, port 2 D1
+    auto_joypad_inputs: [u16; 4],
     strobe: bool,
-    manual_joypad_p1_inputs: u16,
-    manual_joypad_p2_inputs: u16,
+    // Devices shift out their data starting from bit 31. Port 2 has one shift register per
+    // multitap controller; other devices only use the first one
+    manual_p1_inputs: u32,
+    manual_p2_inputs: [u32; 4],
     current_inputs: SnesInputs,
     last_strobe_inputs: SnesInputs,
     super_scope_register: SuperScopeRegister,
+    p1_mouse_register: MouseRegister,
+    p2_mouse_register: MouseRegister,
 }
 
 impl InputState {
     pub fn new() -> Self {
+        let joypad_register = joypad_shift_register(SnesJoypadState::default());
         Self {
             auto_read_cycles_remaining: 0,
-            auto_joypad_p1_inputs: SnesJoypadState::default().to_register_word(),
-            auto_joypad_p2_inputs: SnesJoypadState::default().to_register_word(),
+            auto_joypad_inputs: [0; 4],
             strobe: false,
-            manual_joypad_p1_inputs: SnesJoypadState::default().to_register_word(),
-            manual_joypad_p2_inputs: SnesJoypadState::default().to_register_word(),
+            manual_p1_inputs: joypad_register,
+            manual_p2_inputs: [joypad_register; 4],
             current_inputs: SnesInputs::default(),
             last_strobe_inputs: SnesInputs::default(),
             super_scope_register: SuperScopeRegister::default(),
+            p1_mouse_register: MouseRegister::default(),
+            p2_mouse_register: MouseRegister::default(),
         }
     }
 
     pub fn set_strobe(&mut self, strobe: bool) {
         if !self.strobe && strobe {
-            self.manual_joypad_p1_inputs = self.current_inputs.p1.to_register_word();
-            self.manual_joypad_p2_inputs = match self.current_inputs.p2 {
-                SnesInputDevice::Controller(joypad_state) => {
-                    self.super_scope_register = SuperScopeRegister::default();
+            self.manual_p1_inputs = match self.current_inputs.p1 {
+                SnesInputDevice::Controller(joypad_state) => joypad_shift_register(joypad_state),
+                SnesInputDevice::Mouse(mouse_state) => self.p1_mouse_register.latch(mouse_state),
+                // Not supported in port 1
+                SnesInputDevice::SuperScope(_) | SnesInputDevice::Multitap(_) => 0,
+            };
 
-                    joypad_state.to_register_word()
+            if !matches!(self.current_inputs.p2, SnesInputDevice::SuperScope(_)) {
+                self.super_scope_register = SuperScopeRegister::default();
+            }
+
+            self.manual_p2_inputs = match self.current_inputs.p2 {
+                SnesInputDevice::Controller(joypad_state) => {
+                    [joypad_shift_register(joypad_state); 4]
                 }
                 SnesInputDevice::SuperScope(super_scope_state) => {
                     // Read out the bits before updating them; otherwise the SNES will read Fire=1 on the frame before
@@ -111,11 +183,17 @@ impl InputState {
 
                     let last_strobe_state = match self.last_strobe_inputs.p2 {
                         SnesInputDevice::SuperScope(last_state) => last_state,
-                        SnesInputDevice::Controller(_) => SuperScopeState::default(),
+                        _ => SuperScopeState::default(),
                     };
                     self.super_scope_register.update(super_scope_state, last_strobe_state);
 
-                    word
+                    [(u32::from(word) << 16) | 0xFFFF; 4]
+                }
+                SnesInputDevice::Mouse(mouse_state) => {
+                    [self.p2_mouse_register.latch(mouse_state); 4]
+                }
+                SnesInputDevice::Multitap(joypad_states) => {
+                    joypad_states.map(joypad_shift_register)
                 }
             };
 
@@ -129,39 +207,62 @@ impl InputState {
         self.auto_read_cycles_remaining != 0
     }
 
-    pub fn auto_joypad_p1_inputs(&self) -> u16 {
-        self.auto_joypad_p1_inputs
+    /// Auto joypad read result for JOY1-JOY4 (`joypad` in 0..4).
+    pub fn auto_joypad_inputs(&self, joypad: usize) -> u16 {
+        self.auto_joypad_inputs[joypad]
     }
 
-    pub fn auto_joypad_p2_inputs(&self) -> u16 {
-        self.auto_joypad_p2_inputs
-    }
+    /// Clock port 1 and return its data lines in bits 1-0.
+    pub fn read_port_1(&mut self) -> u8 {
+        if self.strobe {
+            if let SnesInputDevice::Mouse(_) = self.current_inputs.p1 {
+                // Clocking the mouse while strobe is high cycles its sensitivity
+                self.p1_mouse_register.cycle_sensitivity();
+                return 0;
+            }
+        }
 
-    pub fn next_manual_p1_bit(&mut self) -> bool {
-        let bit = self.manual_joypad_p1_inputs.bit(15);
-        self.manual_joypad_p1_inputs = (self.manual_joypad_p1_inputs << 1) | 0x0001;
-        bit
+        u8::from(shift_out(&mut self.manual_p1_inputs))
     }
 
-    pub fn next_manual_p2_bit(&mut self) -> bool {
-        let bit = self.manual_joypad_p2_inputs.bit(15);
-        self.manual_joypad_p2_inputs = (self.manual_joypad_p2_inputs << 1) | 0x0001;
-        bit
+    /// Clock port 2 and return its data lines in bits 1-0.
+    ///
+    /// `iobit` is the port 2 I/O line (WRIO bit 7), which the multitap uses to select between
+    /// players 2-3 (high) and players 4-5 (low).
+    pub fn read_port_2(&mut self, iobit: bool) -> u8 {
+        match self.current_inputs.p2 {
+            SnesInputDevice::Multitap(_) => {
+                if self.strobe {
+                    // The multitap holds D1 high while strobe is high so that games can detect it
+                    return 0x02;
+                }
+
+                let first_joypad = if iobit { 0 } else { 2 };
+                let d0 = shift_out(&mut self.manual_p2_inputs[first_joypad]);
+                let d1 = shift_out(&mut self.manual_p2_inputs[first_joypad + 1]);
+                (u8::from(d1) << 1) | u8::from(d0)
+            }
+            SnesInputDevice::Mouse(_) if self.strobe => {
+                self.p2_mouse_register.cycle_sensitivity();
+                0
+            }
+            _ => u8::from(shift_out(&mut self.manual_p2_inputs[0])),
+        }
     }
 
     pub fn start_auto_joypad_read(&mut self) {
         self.auto_read_cycles_remaining = AUTO_JOYPAD_DURATION_MCLK;
     }
 
-    pub fn tick(&mut self, master_cycles_elapsed: u64, inputs: SnesInputs) {
+    pub fn tick(&mut self, master_cycles_elapsed: u64, inputs: SnesInputs, iobit: bool) {
         self.current_inputs = inputs;
 
         if self.auto_read_cycles_remaining != 0 {
-            self.progress_auto_joypad_read(master_cycles_elapsed);
+            self.progress_auto_joypad_read(master_cycles_elapsed, iobit);
         }
     }
 
-    fn progress_auto_joypad_read(&mut self, master_cycles_elapsed: u64) {
+    fn progress_auto_joypad_read(&mut self, master_cycles_elapsed: u64, iobit: bool) {
         self.auto_read_cycles_remaining =
             self.auto_read_cycles_remaining.saturating_sub(master_cycles_elapsed);
 
@@ -171,11 +272,21 @@ impl InputState {
             self.set_strobe(true);
             self.set_strobe(false);
 
-            // Drain the manual joypad read registers into the auto joypad read registers
-            // Donkey Kong Country depends on the manual joypad read registers reading out 1s after
-            // auto joypad read finishes
-            self.auto_joypad_p1_inputs = mem::replace(&mut self.manual_joypad_p1_inputs, !0);
-            self.auto_joypad_p2_inputs = mem::replace(&mut self.manual_joypad_p2_inputs, !0);
+            // Clock 16 bits out of both ports into the auto joypad read registers. Anything past the
+            // first 16 bits stays in the manual joypad read registers; for joypads this is all 1s,
+            // which Donkey Kong Country depends on
+            self.auto_joypad_inputs = [0; 4];
+            for _ in 0..16 {
+                let port_1 = self.read_port_1();
+                let port_2 = self.read_port_2(iobit);
+                for (joypad, bit) in [port_1.bit(0), port_2.bit(0), port_1.bit(1), port_2.bit(1)]
+                    .into_iter()
+                    .enumerate()
+                {
+                    self.auto_joypad_inputs[joypad] =
+                        (self.auto_joypad_inputs[joypad] << 1) | u16::from(bit);
+                }
+            }
         }
     }
 
@@ -189,3 +300,64 @@ impl InputState {
             .flatten()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{SnesButton, SnesControllerButton};
+    use jgenesis_common::input::Player;
+
+    fn strobe(input: &mut InputState, inputs: SnesInputs) {
+        input.tick(0, inputs, true);
+        input.set_strobe(true);
+        input.set_strobe(false);
+    }
+
+    #[test]
+    fn multitap_select() {
+        let mut input = InputState::new();
+        let inputs = SnesInputs {
+            p2: SnesInputDevice::Multitap([SnesJoypadState::default(); 4]),
+            ..SnesInputs::default()
+        }
+        .with_button(SnesButton::Controller(SnesControllerButton::B), Player::Three, true)
+        .with_button(SnesButton::Controller(SnesControllerButton::Y), Player::Five, true);
+
+        input.tick(0, inputs, true);
+        input.set_strobe(true);
+        assert_eq!(input.read_port_2(true), 0x02);
+        input.set_strobe(false);
+
+        // Players 2 and 3 on D0 and D1, then players 4 and 5
+        assert_eq!(input.read_port_2(true), 0x02);
+        assert_eq!(input.read_port_2(false), 0x00);
+        assert_eq!(input.read_port_2(false), 0x02);
+    }
+
+    #[test]
+    fn mouse_report() {
+        let mut input = InputState::new();
+        let mut mouse = SnesMouseState { left: true, right: false, position: Some((100, 100)) };
+        strobe(
+            &mut input,
+            SnesInputs { p1: SnesInputDevice::Mouse(mouse), ..SnesInputs::default() },
+        );
+
+        mouse.position = Some((110, 95));
+        let inputs = SnesInputs { p1: SnesInputDevice::Mouse(mouse), ..SnesInputs::default() };
+        input.tick(0, inputs, true);
+        input.set_strobe(true);
+        input.read_port_1();
+        input.set_strobe(false);
+
+        let report =
+            (0..32).fold(0_u32, |report, _| (report << 1) | u32::from(input.read_port_1()));
+        assert_eq!(report, 0x0041_850A);
+
+        // Sensitivity changed to medium while strobe was high
+        strobe(&mut input, inputs);
+        let report =
+            (0..32).fold(0_u32, |report, _| (report << 1) | u32::from(input.read_port_1()));
+        assert_eq!(report, 0x0051_0000);
+    }
+}
@@ -0,0 +1,227 @@
+//! SPC file export and standalone SPC playback
+//!
+//! An `.spc` file is a snapshot of the SNES APU (audio RAM, DSP registers, and SPC700 registers).
+//! Because the SNES audio subsystem runs independently of the rest of the console once a game has
+//! uploaded its sound driver, running only the APU from a snapshot will continue playing the music.
+
+use crate::api::{SnesEmulatorConfig, SnesError};
+use crate::apu::Apu;
+use crate::audio::AudioResampler;
+use crate::input::SnesInputs;
+use bincode::{Decode, Encode};
+use jgenesis_common::debug::{CpuArch, DebugCpu, DebugHooks, Debuggable};
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PartialClone, Renderer, SaveWriter, TickEffect,
+    TimingMode,
+};
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::fmt::{Debug, Display};
+use std::mem;
+use thiserror::Error;
+
+/// Length of an SPC file, not including the optional extended ID666 chunk at the end.
+pub const SPC_FILE_LEN: usize = 0x10200;
+
+/// Text fields from an SPC file's ID666 tag. Empty strings indicate missing fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct SpcMetadata {
+    pub song_title: String,
+    pub game_title: String,
+    pub artist: String,
+    pub dumper: String,
+    pub comments: String,
+    /// How long the song should play before fading out
+    pub play_seconds: Option<u32>,
+    pub fade_milliseconds: Option<u32>,
+}
+
+#[derive(Debug, Error)]
+pub enum SpcError {
+    #[error("SPC file is too short ({len} bytes); expected at least {} bytes", SPC_FILE_LEN)]
+    TooShort { len: usize },
+    #[error("File does not have an SPC file signature")]
+    InvalidSignature,
+}
+
+const FRAME_SIZE: FrameSize = FrameSize { width: 256, height: 224 };
+
+// Output a frame every 534 samples, very close to 60 frames per second at 32040 Hz; frames only
+// serve to pace the frontend since the player has no video output
+const SAMPLES_PER_FRAME: u32 = 534;
+
+const SPC700_DEBUG_CPU: usize = 0;
+
+const DEBUG_CPUS: &[DebugCpu] = &[DebugCpu { name: "SPC700", arch: CpuArch::Spc700 }];
+
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+struct SpcFile(Box<[u8]>);
+
+/// Plays back an `.spc` file by running only the APU.
+///
+/// This implements [`EmulatorTrait`] using the SNES input and config types so that frontends can
+/// run it the same way as the full SNES emulator. Inputs are ignored and every frame is black.
+#[derive(Debug, Encode, Decode, PartialClone)]
+pub struct SpcPlayer {
+    apu: Apu,
+    audio_resampler: AudioResampler,
+    metadata: SpcMetadata,
+    frame_sample_counter: u32,
+    #[partial_clone(default)]
+    spc_file: SpcFile,
+    #[partial_clone(default)]
+    frame_buffer: Vec<Color>,
+    debug_hooks: DebugHooks,
+}
+
+impl SpcPlayer {
+    /// # Errors
+    ///
+    /// Returns an error if the file is not a valid SPC file.
+    pub fn create(spc_file: Vec<u8>) -> Result<Self, SpcError> {
+        let (apu, metadata) = Apu::from_spc_file(&spc_file)?;
+
+        Ok(Self {
+            apu,
+            audio_resampler: AudioResampler::new(),
+            metadata,
+            frame_sample_counter: 0,
+            spc_file: SpcFile(spc_file.into_boxed_slice()),
+            frame_buffer: vec![Color::BLACK; (FRAME_SIZE.width * FRAME_SIZE.height) as usize],
+            debug_hooks: DebugHooks::new(DEBUG_CPUS.len()),
+        })
+    }
+
+    #[must_use]
+    pub fn metadata(&self) -> &SpcMetadata {
+        &self.metadata
+    }
+
+    /// Save the current APU state as an SPC file with the original file's metadata.
+    #[must_use]
+    pub fn export_spc(&self) -> Vec<u8> {
+        self.apu.to_spc_file(&self.metadata)
+    }
+}
+
+impl EmulatorTrait for SpcPlayer {
+    type Inputs = SnesInputs;
+    type Config = SnesEmulatorConfig;
+
+    type Err<
+        RErr: Debug + Display + Send + Sync + 'static,
+        AErr: Debug + Display + Send + Sync + 'static,
+        SErr: Debug + Display + Send + Sync + 'static,
+    > = SnesError<RErr, AErr, SErr>;
+
+    fn tick<R, A, S>(
+        &mut self,
+        renderer: &mut R,
+        audio_output: &mut A,
+        _inputs: &Self::Inputs,
+        _save_writer: &mut S,
+    ) -> Result<TickEffect, Self::Err<R::Err, A::Err, S::Err>>
+    where
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+        S: SaveWriter,
+        S::Err: Debug + Display + Send + Sync + 'static,
+    {
+        if self.debug_hooks.stop_event().is_some() {
+            return Ok(TickEffect::None);
+        }
+
+        let (sample_l, sample_r) =
+            self.apu.generate_sample(&mut self.debug_hooks, SPC700_DEBUG_CPU);
+        self.audio_resampler.collect_sample(sample_l, sample_r);
+
+        self.frame_sample_counter += 1;
+        if self.frame_sample_counter < SAMPLES_PER_FRAME {
+            return Ok(TickEffect::None);
+        }
+        self.frame_sample_counter = 0;
+
+        self.force_render(renderer).map_err(SnesError::Render)?;
+        self.audio_resampler.output_samples(audio_output).map_err(SnesError::AudioOutput)?;
+
+        Ok(TickEffect::FrameRendered)
+    }
+
+    fn force_render<R>(&mut self, renderer: &mut R) -> Result<(), R::Err>
+    where
+        R: Renderer,
+    {
+        if self.frame_buffer.is_empty() {
+            // Frame buffer is not preserved by save states
+            self.frame_buffer = vec![Color::BLACK; (FRAME_SIZE.width * FRAME_SIZE.height) as usize];
+        }
+
+        renderer.render_frame(&self.frame_buffer, FRAME_SIZE, None)
+    }
+
+    fn reload_config(&mut self, _config: &Self::Config) {}
+
+    fn take_rom_from(&mut self, other: &mut Self) {
+        self.spc_file = mem::take(&mut other.spc_file);
+        self.debug_hooks = mem::take(&mut other.debug_hooks);
+    }
+
+    fn soft_reset(&mut self) {
+        log::info!("Restarting SPC playback");
+
+        match Apu::from_spc_file(&self.spc_file.0) {
+            Ok((apu, _)) => {
+                self.apu = apu;
+                self.frame_sample_counter = 0;
+            }
+            Err(err) => {
+                log::error!("Unable to reload SPC file: {err}");
+            }
+        }
+    }
+
+    fn hard_reset<S: SaveWriter>(&mut self, _save_writer: &mut S) {
+        self.soft_reset();
+    }
+
+    fn timing_mode(&self) -> TimingMode {
+        TimingMode::Ntsc
+    }
+}
+
+impl Debuggable for SpcPlayer {
+    fn debug_cpus(&self) -> &'static [DebugCpu] {
+        DEBUG_CPUS
+    }
+
+    fn debug_hooks(&self) -> &DebugHooks {
+        &self.debug_hooks
+    }
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+        &mut self.debug_hooks
+    }
+
+    fn read_registers(&self, _cpu: usize) -> Vec<u32> {
+        self.apu.spc700().debug_registers().map(u32::from).to_vec()
+    }
+
+    fn write_registers(&mut self, _cpu: usize, registers: &[u32]) {
+        if let Ok(registers) = <[u32; 6]>::try_from(registers) {
+            self.apu.spc700_mut().set_debug_registers(registers.map(|value| value as u16));
+        }
+    }
+
+    fn read_memory(&mut self, _cpu: usize, address: u32) -> u8 {
+        self.apu.debug_read(address as u16)
+    }
+
+    fn write_memory(&mut self, _cpu: usize, address: u32, value: u8) {
+        self.apu.debug_write(address as u16, value);
+    }
+    fn disassemble(&mut self, _cpu: usize, address: u32) -> (String, u32) {
+        let (text, len) = self.apu.disassemble(address as u16);
+        (text, len.into())
+    }
+}
@@ -4,6 +4,8 @@ mod controlflow;
 mod load;
 mod table;
 
+pub(crate) use table::decode;
+
 use crate::core::{
     AddressRegister, AddressingMode, ConditionCodes, DataRegister, Exception, ExecuteResult,
     InstructionExecutor, OpSize, Registers,
@@ -0,0 +1,127 @@
+//! Debugger support: register access in GDB order and a bus wrapper that reports memory accesses
+//! for watchpoints.
+
+use crate::{disassemble, BusInterface, M68000};
+use jgenesis_common::debug::{CpuArch, DebugHooks};
+
+const SUPERVISOR_MODE_BIT: u16 = 1 << 13;
+
+impl M68000 {
+    /// Registers in the order D0-D7, A0-A6, active stack pointer, SR, PC.
+    #[must_use]
+    pub fn debug_registers(&self) -> [u32; 18] {
+        let mut registers = [0; 18];
+        registers[..8].copy_from_slice(&self.data_registers());
+        registers[8..15].copy_from_slice(&self.address_registers());
+        registers[15] = if self.status_register() & SUPERVISOR_MODE_BIT != 0 {
+            self.supervisor_stack_pointer()
+        } else {
+            self.user_stack_pointer()
+        };
+        registers[16] = self.status_register().into();
+        registers[17] = self.pc();
+        registers
+    }
+
+    /// Overwrite registers using the same order as [`Self::debug_registers`]. The stack pointer
+    /// value is written to whichever of USP/SSP is active after updating SR.
+    pub fn set_debug_registers(&mut self, registers: [u32; 18]) {
+        let mut data = [0; 8];
+        data.copy_from_slice(&registers[..8]);
+        self.set_data_registers(data);
+
+        self.set_status_register(registers[16] as u16);
+
+        let mut address = [0; 7];
+        address.copy_from_slice(&registers[8..15]);
+        let (usp, ssp) = if self.status_register() & SUPERVISOR_MODE_BIT != 0 {
+            (self.user_stack_pointer(), registers[15])
+        } else {
+            (registers[15], self.supervisor_stack_pointer())
+        };
+        self.set_address_registers(address, usp, ssp);
+
+        self.set_pc(registers[17]);
+    }
+
+    /// Record the instruction at PC in the given CPU's trace if it is being traced and PC is within
+    /// the trace's address ranges. Cores should call this before executing each instruction.
+    pub fn trace_instruction<B: BusInterface>(
+        &self,
+        bus: &mut B,
+        hooks: &mut DebugHooks,
+        cpu: usize,
+    ) {
+        if self.is_stopped() || !hooks.traces(cpu, self.pc()) {
+            return;
+        }
+
+        let (disassembly, _) = disassemble::disassemble(bus, self.pc());
+        hooks.record_trace(cpu, CpuArch::M68000, &disassembly, &self.debug_registers());
+    }
+}
+
+/// Wraps a bus and reports every memory access to [`DebugHooks::check_access`].
+pub struct DebugBus<'a, B> {
+    bus: &'a mut B,
+    hooks: &'a mut DebugHooks,
+    cpu: usize,
+}
+
+impl<'a, B: BusInterface> DebugBus<'a, B> {
+    pub fn new(bus: &'a mut B, hooks: &'a mut DebugHooks, cpu: usize) -> Self {
+        Self { bus, hooks, cpu }
+    }
+
+    fn check(&mut self, address: u32, len: u32, write: bool) {
+        self.hooks.check_access(self.cpu, address & B::ADDRESS_MASK, len, write);
+    }
+}
+
+impl<B: BusInterface> BusInterface for DebugBus<'_, B> {
+    const ADDRESS_MASK: u32 = B::ADDRESS_MASK;
+
+    #[inline]
+    fn read_byte(&mut self, address: u32) -> u8 {
+        self.check(address, 1, false);
+        self.bus.read_byte(address)
+    }
+
+    #[inline]
+    fn read_word(&mut self, address: u32) -> u16 {
+        self.check(address, 2, false);
+        self.bus.read_word(address)
+    }
+
+    #[inline]
+    fn write_byte(&mut self, address: u32, value: u8) {
+        self.check(address, 1, true);
+        self.bus.write_byte(address, value);
+    }
+
+    #[inline]
+    fn write_word(&mut self, address: u32, value: u16) {
+        self.check(address, 2, true);
+        self.bus.write_word(address, value);
+    }
+
+    #[inline]
+    fn interrupt_level(&self) -> u8 {
+        self.bus.interrupt_level()
+    }
+
+    #[inline]
+    fn acknowledge_interrupt(&mut self) {
+        self.bus.acknowledge_interrupt();
+    }
+
+    #[inline]
+    fn halt(&self) -> bool {
+        self.bus.halt()
+    }
+
+    #[inline]
+    fn reset(&self) -> bool {
+        self.bus.reset()
+    }
+}
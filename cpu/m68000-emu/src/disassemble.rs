@@ -0,0 +1,522 @@
+//! 68000 disassembler, using Motorola syntax
+//!
+//! Opcodes are decoded using the same lookup table that the CPU uses to execute instructions;
+//! extension words are read from the bus as needed to format operands.
+
+use crate::core::instructions::{
+    self, BranchCondition, Direction, Instruction, ShiftCount, ShiftDirection, UspDirection,
+};
+use crate::core::{AddressRegister, AddressingMode, DataRegister, OpSize};
+use crate::traits::BusInterface;
+
+struct Disassembler<'a, B> {
+    bus: &'a mut B,
+    address: u32,
+    len: u32,
+}
+
+impl<B: BusInterface> Disassembler<'_, B> {
+    fn word(&mut self) -> u16 {
+        let word = self.bus.read_word(self.address.wrapping_add(self.len));
+        self.len += 2;
+        word
+    }
+
+    fn long_word(&mut self) -> u32 {
+        let high = self.word();
+        let low = self.word();
+        (u32::from(high) << 16) | u32::from(low)
+    }
+
+    fn immediate(&mut self, size: OpSize) -> String {
+        match size {
+            OpSize::Byte => format!("#${:02X}", self.word() as u8),
+            OpSize::Word => format!("#${:04X}", self.word()),
+            OpSize::LongWord => format!("#${:08X}", self.long_word()),
+        }
+    }
+
+    fn index(&mut self) -> (i8, String) {
+        let extension = self.word();
+        let register_type = if extension & 0x8000 != 0 { 'A' } else { 'D' };
+        let register = (extension >> 12) & 7;
+        let size = if extension & 0x0800 != 0 { 'L' } else { 'W' };
+        (extension as i8, format!("{register_type}{register}.{size}"))
+    }
+
+    fn effective_address(&mut self, mode: AddressingMode, size: OpSize) -> String {
+        match mode {
+            AddressingMode::DataDirect(register) => data(register),
+            AddressingMode::AddressDirect(register) => address(register),
+            AddressingMode::AddressIndirect(register) => format!("({})", address(register)),
+            AddressingMode::AddressIndirectPostincrement(register) => {
+                format!("({})+", address(register))
+            }
+            AddressingMode::AddressIndirectPredecrement(register) => {
+                format!("-({})", address(register))
+            }
+            AddressingMode::AddressIndirectDisplacement(register) => {
+                let displacement = self.word() as i16;
+                format!("({}, {})", signed_hex(displacement.into()), address(register))
+            }
+            AddressingMode::AddressIndirectIndexed(register) => {
+                let (displacement, index) = self.index();
+                format!("({}, {}, {index})", signed_hex(displacement.into()), address(register))
+            }
+            AddressingMode::PcRelativeDisplacement => {
+                let pc = self.address.wrapping_add(self.len);
+                let target = pc.wrapping_add(self.word() as i16 as u32);
+                format!("(${target:06X}, PC)")
+            }
+            AddressingMode::PcRelativeIndexed => {
+                let pc = self.address.wrapping_add(self.len);
+                let (displacement, index) = self.index();
+                let target = pc.wrapping_add(displacement as u32);
+                format!("(${target:06X}, PC, {index})")
+            }
+            AddressingMode::AbsoluteShort => format!("(${:04X}).W", self.word()),
+            AddressingMode::AbsoluteLong => format!("(${:06X}).L", self.long_word()),
+            AddressingMode::Immediate => self.immediate(size),
+            AddressingMode::Quick(n) => format!("#{n}"),
+        }
+    }
+
+    fn operands(
+        &mut self,
+        size: OpSize,
+        source: AddressingMode,
+        dest: AddressingMode,
+    ) -> (String, String) {
+        let source = self.effective_address(source, size);
+        let dest = self.effective_address(dest, size);
+        (source, dest)
+    }
+
+    fn branch_target(&mut self, displacement: i8) -> String {
+        let pc = self.address.wrapping_add(2);
+        let displacement =
+            if displacement == 0 { self.word() as i16 as u32 } else { displacement as u32 };
+        format!("${:06X}", pc.wrapping_add(displacement))
+    }
+
+    fn instruction(&mut self) -> String {
+        let opcode = self.word();
+        // ORI, ANDI, SUBI, ADDI, EORI, and CMPI all decode to the same instructions as their
+        // non-immediate equivalents
+        let immediate_suffix = if opcode >> 12 == 0 { "I" } else { "" };
+
+        match instructions::decode(opcode) {
+            Instruction::Add { size, source, dest, with_extend } => {
+                let (source_str, dest_str) = self.operands(size, source, dest);
+                let mnemonic = arithmetic_mnemonic("ADD", source, dest, with_extend);
+                format!("{mnemonic}{immediate_suffix}.{} {source_str}, {dest_str}", size_str(size))
+            }
+            Instruction::Subtract { size, source, dest, with_extend } => {
+                let (source_str, dest_str) = self.operands(size, source, dest);
+                let mnemonic = arithmetic_mnemonic("SUB", source, dest, with_extend);
+                format!("{mnemonic}{immediate_suffix}.{} {source_str}, {dest_str}", size_str(size))
+            }
+            Instruction::AddDecimal { source, dest } => {
+                let (source, dest) = self.operands(OpSize::Byte, source, dest);
+                format!("ABCD {source}, {dest}")
+            }
+            Instruction::SubtractDecimal { source, dest } => {
+                let (source, dest) = self.operands(OpSize::Byte, source, dest);
+                format!("SBCD {source}, {dest}")
+            }
+            Instruction::And { size, source, dest } => {
+                let (source, dest) = self.operands(size, source, dest);
+                format!("AND{immediate_suffix}.{} {source}, {dest}", size_str(size))
+            }
+            Instruction::Or { size, source, dest } => {
+                let (source, dest) = self.operands(size, source, dest);
+                format!("OR{immediate_suffix}.{} {source}, {dest}", size_str(size))
+            }
+            Instruction::ExclusiveOr { size, source, dest } => {
+                let (source, dest) = self.operands(size, source, dest);
+                format!("EOR{immediate_suffix}.{} {source}, {dest}", size_str(size))
+            }
+            Instruction::Compare { size, source, dest } => {
+                let (source_str, dest_str) = self.operands(size, source, dest);
+                let mnemonic = match (source, dest) {
+                    (_, AddressingMode::AddressDirect(..)) => "CMPA",
+                    (
+                        AddressingMode::AddressIndirectPostincrement(..),
+                        AddressingMode::AddressIndirectPostincrement(..),
+                    ) => "CMPM",
+                    _ => "CMP",
+                };
+                format!("{mnemonic}{immediate_suffix}.{} {source_str}, {dest_str}", size_str(size))
+            }
+            Instruction::AndToCcr => format!("ANDI {}, CCR", self.immediate(OpSize::Byte)),
+            Instruction::AndToSr => format!("ANDI {}, SR", self.immediate(OpSize::Word)),
+            Instruction::OrToCcr => format!("ORI {}, CCR", self.immediate(OpSize::Byte)),
+            Instruction::OrToSr => format!("ORI {}, SR", self.immediate(OpSize::Word)),
+            Instruction::ExclusiveOrToCcr => format!("EORI {}, CCR", self.immediate(OpSize::Byte)),
+            Instruction::ExclusiveOrToSr => format!("EORI {}, SR", self.immediate(OpSize::Word)),
+            Instruction::ArithmeticShiftMemory(direction, dest) => {
+                self.shift_memory("AS", direction, dest)
+            }
+            Instruction::LogicalShiftMemory(direction, dest) => {
+                self.shift_memory("LS", direction, dest)
+            }
+            Instruction::RotateMemory(direction, dest) => self.shift_memory("RO", direction, dest),
+            Instruction::RotateThruExtendMemory(direction, dest) => {
+                self.shift_memory("ROX", direction, dest)
+            }
+            Instruction::ArithmeticShiftRegister(size, direction, register, count) => {
+                shift_register("AS", size, direction, register, count)
+            }
+            Instruction::LogicalShiftRegister(size, direction, register, count) => {
+                shift_register("LS", size, direction, register, count)
+            }
+            Instruction::RotateRegister(size, direction, register, count) => {
+                shift_register("RO", size, direction, register, count)
+            }
+            Instruction::RotateThruExtendRegister(size, direction, register, count) => {
+                shift_register("ROX", size, direction, register, count)
+            }
+            Instruction::BitTest { source, dest } => self.bit_instruction("BTST", source, dest),
+            Instruction::BitTestAndChange { source, dest } => {
+                self.bit_instruction("BCHG", source, dest)
+            }
+            Instruction::BitTestAndClear { source, dest } => {
+                self.bit_instruction("BCLR", source, dest)
+            }
+            Instruction::BitTestAndSet { source, dest } => {
+                self.bit_instruction("BSET", source, dest)
+            }
+            Instruction::Branch(condition, displacement) => {
+                let mnemonic = match condition {
+                    BranchCondition::True => "RA",
+                    BranchCondition::False => "SR",
+                    _ => condition_str(condition),
+                };
+                format!("B{mnemonic} {}", self.branch_target(displacement))
+            }
+            Instruction::BranchToSubroutine(displacement) => {
+                format!("BSR {}", self.branch_target(displacement))
+            }
+            Instruction::BranchDecrement(condition, register) => {
+                let mnemonic = match condition {
+                    BranchCondition::False => "RA",
+                    _ => condition_str(condition),
+                };
+                format!("DB{mnemonic} {}, {}", data(register), self.branch_target(0))
+            }
+            Instruction::Set(condition, dest) => {
+                format!(
+                    "S{} {}",
+                    condition_str(condition),
+                    self.effective_address(dest, OpSize::Byte)
+                )
+            }
+            Instruction::CheckRegister(register, source) => {
+                format!("CHK {}, {}", self.effective_address(source, OpSize::Word), data(register))
+            }
+            Instruction::DivideSigned(register, source) => {
+                format!("DIVS {}, {}", self.effective_address(source, OpSize::Word), data(register))
+            }
+            Instruction::DivideUnsigned(register, source) => {
+                format!("DIVU {}, {}", self.effective_address(source, OpSize::Word), data(register))
+            }
+            Instruction::MultiplySigned(register, source) => {
+                format!("MULS {}, {}", self.effective_address(source, OpSize::Word), data(register))
+            }
+            Instruction::MultiplyUnsigned(register, source) => {
+                format!("MULU {}, {}", self.effective_address(source, OpSize::Word), data(register))
+            }
+            Instruction::Clear(size, dest) => self.single_operand("CLR", size, dest),
+            Instruction::Negate { size, dest, with_extend } => {
+                self.single_operand(if with_extend { "NEGX" } else { "NEG" }, size, dest)
+            }
+            Instruction::Not(size, dest) => self.single_operand("NOT", size, dest),
+            Instruction::Test(size, source) => self.single_operand("TST", size, source),
+            Instruction::NegateDecimal(dest) => {
+                format!("NBCD {}", self.effective_address(dest, OpSize::Byte))
+            }
+            Instruction::TestAndSet(dest) => {
+                format!("TAS {}", self.effective_address(dest, OpSize::Byte))
+            }
+            Instruction::ExchangeAddress(a, b) => format!("EXG {}, {}", address(a), address(b)),
+            Instruction::ExchangeData(a, b) => format!("EXG {}, {}", data(a), data(b)),
+            Instruction::ExchangeDataAddress(a, b) => format!("EXG {}, {}", data(a), address(b)),
+            Instruction::Extend(size, register) => {
+                format!("EXT.{} {}", size_str(size), data(register))
+            }
+            Instruction::Swap(register) => format!("SWAP {}", data(register)),
+            Instruction::Illegal { opcode: 0x4AFC } => "ILLEGAL".into(),
+            Instruction::Illegal { opcode } => format!("DC.W ${opcode:04X}"),
+            Instruction::Jump(dest) => {
+                format!("JMP {}", self.effective_address(dest, OpSize::LongWord))
+            }
+            Instruction::JumpToSubroutine(dest) => {
+                format!("JSR {}", self.effective_address(dest, OpSize::LongWord))
+            }
+            Instruction::LoadEffectiveAddress(source, register) => {
+                let source = self.effective_address(source, OpSize::LongWord);
+                format!("LEA {source}, {}", address(register))
+            }
+            Instruction::PushEffectiveAddress(source) => {
+                format!("PEA {}", self.effective_address(source, OpSize::LongWord))
+            }
+            Instruction::Link(register) => {
+                let displacement = self.word() as i16;
+                format!("LINK {}, #{}", address(register), signed_hex(displacement.into()))
+            }
+            Instruction::Unlink(register) => format!("UNLK {}", address(register)),
+            Instruction::Move { size, source, dest } => {
+                let (source_str, dest_str) = self.operands(size, source, dest);
+                let mnemonic = match dest {
+                    AddressingMode::AddressDirect(..) => "MOVEA",
+                    _ => "MOVE",
+                };
+                format!("{mnemonic}.{} {source_str}, {dest_str}", size_str(size))
+            }
+            Instruction::MoveQuick(value, register) => {
+                format!("MOVEQ #{}, {}", signed_hex(value.into()), data(register))
+            }
+            Instruction::MoveFromSr(dest) => {
+                format!("MOVE SR, {}", self.effective_address(dest, OpSize::Word))
+            }
+            Instruction::MoveToSr(source) => {
+                format!("MOVE {}, SR", self.effective_address(source, OpSize::Word))
+            }
+            Instruction::MoveToCcr(source) => {
+                format!("MOVE {}, CCR", self.effective_address(source, OpSize::Word))
+            }
+            Instruction::MoveUsp(UspDirection::RegisterToUsp, register) => {
+                format!("MOVE {}, USP", address(register))
+            }
+            Instruction::MoveUsp(UspDirection::UspToRegister, register) => {
+                format!("MOVE USP, {}", address(register))
+            }
+            Instruction::MoveMultiple(size, mode, direction) => {
+                // The register mask extension word precedes the effective address extension words
+                let mask = self.word();
+                let predecrement = matches!(mode, AddressingMode::AddressIndirectPredecrement(..));
+                let registers = register_list(mask, predecrement);
+                let ea = self.effective_address(mode, size);
+                match direction {
+                    Direction::RegisterToMemory => {
+                        format!("MOVEM.{} {registers}, {ea}", size_str(size))
+                    }
+                    Direction::MemoryToRegister => {
+                        format!("MOVEM.{} {ea}, {registers}", size_str(size))
+                    }
+                }
+            }
+            Instruction::MovePeripheral(size, data_register, address_register, direction) => {
+                let displacement = signed_hex((self.word() as i16).into());
+                let memory = format!("({displacement}, {})", address(address_register));
+                match direction {
+                    Direction::RegisterToMemory => {
+                        format!("MOVEP.{} {}, {memory}", size_str(size), data(data_register))
+                    }
+                    Direction::MemoryToRegister => {
+                        format!("MOVEP.{} {memory}, {}", size_str(size), data(data_register))
+                    }
+                }
+            }
+            Instruction::NoOp => "NOP".into(),
+            Instruction::Reset => "RESET".into(),
+            Instruction::Return { restore_ccr: false } => "RTS".into(),
+            Instruction::Return { restore_ccr: true } => "RTR".into(),
+            Instruction::ReturnFromException => "RTE".into(),
+            Instruction::Stop => format!("STOP {}", self.immediate(OpSize::Word)),
+            Instruction::Trap(vector) => format!("TRAP #{vector}"),
+            Instruction::TrapOnOverflow => "TRAPV".into(),
+        }
+    }
+
+    fn single_operand(&mut self, mnemonic: &str, size: OpSize, operand: AddressingMode) -> String {
+        format!("{mnemonic}.{} {}", size_str(size), self.effective_address(operand, size))
+    }
+
+    fn shift_memory(
+        &mut self,
+        mnemonic: &str,
+        direction: ShiftDirection,
+        dest: AddressingMode,
+    ) -> String {
+        let dest = self.effective_address(dest, OpSize::Word);
+        format!("{mnemonic}{}.W {dest}", direction_str(direction))
+    }
+
+    fn bit_instruction(
+        &mut self,
+        mnemonic: &str,
+        source: AddressingMode,
+        dest: AddressingMode,
+    ) -> String {
+        let (source, dest) = self.operands(OpSize::Byte, source, dest);
+        format!("{mnemonic} {source}, {dest}")
+    }
+}
+
+fn data(register: DataRegister) -> String {
+    format!("D{}", register.0)
+}
+
+fn address(register: AddressRegister) -> String {
+    if register.0 == 7 { "SP".into() } else { format!("A{}", register.0) }
+}
+
+fn signed_hex(value: i32) -> String {
+    if value < 0 { format!("-${:X}", value.unsigned_abs()) } else { format!("${value:X}") }
+}
+
+fn size_str(size: OpSize) -> &'static str {
+    match size {
+        OpSize::Byte => "B",
+        OpSize::Word => "W",
+        OpSize::LongWord => "L",
+    }
+}
+
+fn direction_str(direction: ShiftDirection) -> char {
+    match direction {
+        ShiftDirection::Left => 'L',
+        ShiftDirection::Right => 'R',
+    }
+}
+
+fn condition_str(condition: BranchCondition) -> &'static str {
+    match condition {
+        BranchCondition::True => "T",
+        BranchCondition::False => "F",
+        BranchCondition::Higher => "HI",
+        BranchCondition::LowerOrSame => "LS",
+        BranchCondition::CarryClear => "CC",
+        BranchCondition::CarrySet => "CS",
+        BranchCondition::NotEqual => "NE",
+        BranchCondition::Equal => "EQ",
+        BranchCondition::OverflowClear => "VC",
+        BranchCondition::OverflowSet => "VS",
+        BranchCondition::Plus => "PL",
+        BranchCondition::Minus => "MI",
+        BranchCondition::GreaterOrEqual => "GE",
+        BranchCondition::LessThan => "LT",
+        BranchCondition::GreaterThan => "GT",
+        BranchCondition::LessOrEqual => "LE",
+    }
+}
+
+fn arithmetic_mnemonic(
+    base: &str,
+    source: AddressingMode,
+    dest: AddressingMode,
+    with_extend: bool,
+) -> String {
+    let suffix = match (source, dest) {
+        _ if with_extend => "X",
+        (AddressingMode::Quick(..), _) => "Q",
+        (_, AddressingMode::AddressDirect(..)) => "A",
+        _ => "",
+    };
+    format!("{base}{suffix}")
+}
+
+fn shift_register(
+    mnemonic: &str,
+    size: OpSize,
+    direction: ShiftDirection,
+    register: DataRegister,
+    count: ShiftCount,
+) -> String {
+    let count = match count {
+        ShiftCount::Constant(n) => format!("#{n}"),
+        ShiftCount::Register(register) => data(register),
+    };
+    format!("{mnemonic}{}.{} {count}, {}", direction_str(direction), size_str(size), data(register))
+}
+
+// Formats a MOVEM register mask as a list of register ranges, e.g. D0-D3/A5/SP. The mask is
+// reversed for predecrement mode, with bit 0 representing A7 and bit 15 representing D0
+fn register_list(mask: u16, predecrement: bool) -> String {
+    let mask = if predecrement { mask.reverse_bits() } else { mask };
+
+    let mut groups = Vec::new();
+    for (prefix, bits) in [('D', mask & 0xFF), ('A', mask >> 8)] {
+        let mut i = 0;
+        while i < 8 {
+            if bits & (1 << i) == 0 {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            while i < 8 && bits & (1 << i) != 0 {
+                i += 1;
+            }
+            let end = i - 1;
+
+            let name = |n: u16| {
+                if prefix == 'A' && n == 7 { "SP".into() } else { format!("{prefix}{n}") }
+            };
+            if start == end {
+                groups.push(name(start));
+            } else {
+                groups.push(format!("{}-{}", name(start), name(end)));
+            }
+        }
+    }
+
+    groups.join("/")
+}
+
+/// Disassemble the instruction at `address`, returning the instruction text and its length in
+/// bytes.
+pub fn disassemble<B: BusInterface>(bus: &mut B, address: u32) -> (String, u32) {
+    let mut disassembler = Disassembler { bus, address, len: 0 };
+    let text = disassembler.instruction();
+    (text, disassembler.len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::InMemoryBus;
+
+    fn disassemble_words(words: &[u16]) -> (String, u32) {
+        let mut bus = InMemoryBus::new();
+        for (i, &word) in words.iter().enumerate() {
+            bus.write_word(0x1000 + 2 * i as u32, word);
+        }
+        disassemble(&mut bus, 0x1000)
+    }
+
+    #[test]
+    fn basic_instructions() {
+        assert_eq!(disassemble_words(&[0x4E71]), ("NOP".into(), 2));
+        assert_eq!(disassemble_words(&[0x70FF]), ("MOVEQ #-$1, D0".into(), 2));
+        assert_eq!(
+            disassemble_words(&[0x33FC, 0x1234, 0x00FF, 0x0000]),
+            ("MOVE.W #$1234, ($FF0000).L".into(), 8)
+        );
+        assert_eq!(disassemble_words(&[0x2068, 0xFFFC]), ("MOVEA.L (-$4, A0), A0".into(), 4));
+        assert_eq!(disassemble_words(&[0x0640, 0x0010]), ("ADDI.W #$0010, D0".into(), 4));
+        assert_eq!(disassemble_words(&[0x5281]), ("ADDQ.L #1, D1".into(), 2));
+        assert_eq!(disassemble_words(&[0xE548]), ("LSL.W #2, D0".into(), 2));
+        assert_eq!(disassemble_words(&[0x46FC, 0x2700]), ("MOVE #$2700, SR".into(), 4));
+        assert_eq!(disassemble_words(&[0x4AFC]), ("ILLEGAL".into(), 2));
+        assert_eq!(disassemble_words(&[0xA000]), ("DC.W $A000".into(), 2));
+    }
+
+    #[test]
+    fn control_flow() {
+        assert_eq!(disassemble_words(&[0x6000, 0x0010]), ("BRA $001012".into(), 4));
+        assert_eq!(disassemble_words(&[0x66FE]), ("BNE $001000".into(), 2));
+        assert_eq!(disassemble_words(&[0x51C8, 0xFFFE]), ("DBRA D0, $001000".into(), 4));
+        assert_eq!(disassemble_words(&[0x4EBB, 0x0004]), ("JSR ($001006, PC, D0.W)".into(), 4));
+    }
+
+    #[test]
+    fn movem() {
+        assert_eq!(disassemble_words(&[0x48E7, 0xC0C0]), ("MOVEM.L D0-D1/A0-A1, -(SP)".into(), 4));
+        assert_eq!(
+            disassemble_words(&[0x4CDF, 0x8303]),
+            ("MOVEM.L (SP)+, D0-D1/A0-A1/SP".into(), 4)
+        );
+    }
+}
@@ -4,6 +4,7 @@ use clap::Parser;
 use env_logger::Env;
 use flate2::read::GzDecoder;
 use m68000_emu::bus::InMemoryBus;
+use m68000_emu::disassemble;
 use m68000_emu::traits::BusInterface;
 use m68000_emu::M68000;
 use serde::{Deserialize, Serialize};
@@ -14,6 +15,13 @@ use std::path::Path;
 use std::sync::mpsc;
 use std::thread;
 
+// Instructions that do not always end at the address immediately following the instruction
+const CONTROL_FLOW_MNEMONICS: &[&str] = &[
+    "BRA", "BSR", "BHI", "BLS", "BCC", "BCS", "BNE", "BEQ", "BVC", "BVS", "BPL", "BMI", "BGE",
+    "BLT", "BGT", "BLE", "JMP", "JSR", "RTS", "RTR", "RTE", "TRAP", "TRAPV", "CHK", "ILLEGAL",
+    "DC",
+];
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct State {
     d0: u32,
@@ -232,8 +240,10 @@ fn run_single_test<P: AsRef<Path>>(
     let mut failure_count = 0_u32;
     let mut timing_failure_count = 0_u32;
     let mut address_error_count = 0_u32;
+    let mut disassembly_failure_count = 0_u32;
     for test_description in test_descriptions {
         let mut m68000 = init_test_state(&test_description.initial, bus);
+        let (disassembled, len) = disassemble::disassemble(bus, test_description.initial.pc);
         let cycles = m68000.execute_instruction(bus);
 
         let state = State::from(&m68000, bus, &test_description.final_state);
@@ -260,6 +270,15 @@ fn run_single_test<P: AsRef<Path>>(
 
         if m68000.address_error() {
             address_error_count += 1;
+        } else if check_disassembly(&disassembled, len, test_description) {
+            if individual_logs {
+                log::info!(
+                    "Disassembly mismatch for test '{}'; '{disassembled}' has length {len}",
+                    test_description.name
+                );
+            }
+
+            disassembly_failure_count += 1;
         }
     }
 
@@ -271,6 +290,26 @@ fn run_single_test<P: AsRef<Path>>(
     log::info!(
         "{timing_failure_count} timing mismatches out of {num_tests_without_address_errors} tests in {display_path}"
     );
+    log::info!(
+        "{disassembly_failure_count} disassembly length mismatches out of {num_tests_without_address_errors} tests in {display_path}"
+    );
+}
+
+fn check_disassembly(disassembled: &str, len: u32, test_description: &TestDescription) -> bool {
+    let mnemonic = disassembled.split(['.', ' ']).next().unwrap_or_default();
+    if CONTROL_FLOW_MNEMONICS.contains(&mnemonic) || mnemonic.starts_with("DB") {
+        return false;
+    }
+
+    // Skip tests that trigger exceptions (e.g. privilege violations or division by zero), which
+    // push a 6-byte exception frame onto the supervisor stack
+    let initial = &test_description.initial;
+    let final_state = &test_description.final_state;
+    if final_state.ssp == initial.ssp.wrapping_sub(6) && final_state.sr & 0x2000 != 0 {
+        return false;
+    }
+
+    initial.pc.wrapping_add(len) != final_state.pc
 }
 
 fn init_test_state(state: &State, bus: &mut InMemoryBus) -> M68000 {
@@ -0,0 +1,99 @@
+//! Debugger support: register access and a bus wrapper that reports memory accesses for
+//! watchpoints.
+
+use crate::bus::BusInterface;
+use crate::{disassemble, Mos6502, StatusFlags, StatusReadContext};
+use jgenesis_common::debug::{CpuArch, DebugHooks};
+
+impl Mos6502 {
+    /// Registers in the order A, X, Y, S, P, PC.
+    #[must_use]
+    pub fn debug_registers(&self) -> [u16; 6] {
+        let registers = &self.registers;
+        [
+            registers.accumulator.into(),
+            registers.x.into(),
+            registers.y.into(),
+            registers.sp.into(),
+            registers.status.to_byte(StatusReadContext::HardwareInterruptHandler).into(),
+            registers.pc,
+        ]
+    }
+
+    /// Overwrite registers using the same order as [`Self::debug_registers`].
+    pub fn set_debug_registers(&mut self, values: [u16; 6]) {
+        let [a, x, y, sp, p, pc] = values;
+
+        let registers = &mut self.registers;
+        registers.accumulator = a as u8;
+        registers.x = x as u8;
+        registers.y = y as u8;
+        registers.sp = sp as u8;
+        registers.status = StatusFlags::from_byte(p as u8);
+        registers.pc = pc;
+    }
+
+    /// Record the instruction at PC in the given CPU's trace if it is being traced and PC is within
+    /// the trace's address ranges. Cores should call this before executing each instruction.
+    pub fn trace_instruction<B: BusInterface>(
+        &self,
+        bus: &mut B,
+        hooks: &mut DebugHooks,
+        cpu: usize,
+    ) {
+        let pc = self.registers.pc;
+        if !hooks.traces(cpu, pc.into()) {
+            return;
+        }
+
+        let (disassembly, _) = disassemble::disassemble(bus, pc);
+        hooks.record_trace(
+            cpu,
+            CpuArch::Mos6502,
+            &disassembly,
+            &self.debug_registers().map(u32::from),
+        );
+    }
+}
+
+/// Wraps a bus and reports every memory access to [`DebugHooks::check_access`].
+pub struct DebugBus<'a, B> {
+    bus: &'a mut B,
+    hooks: &'a mut DebugHooks,
+    cpu: usize,
+}
+
+impl<'a, B: BusInterface> DebugBus<'a, B> {
+    pub fn new(bus: &'a mut B, hooks: &'a mut DebugHooks, cpu: usize) -> Self {
+        Self { bus, hooks, cpu }
+    }
+}
+
+impl<B: BusInterface> BusInterface for DebugBus<'_, B> {
+    #[inline]
+    fn read(&mut self, address: u16) -> u8 {
+        self.hooks.check_access(self.cpu, address.into(), 1, false);
+        self.bus.read(address)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u16, value: u8) {
+        self.hooks.check_access(self.cpu, address.into(), 1, true);
+        self.bus.write(address, value);
+    }
+
+    #[inline]
+    fn nmi(&self) -> bool {
+        self.bus.nmi()
+    }
+
+    #[inline]
+    fn acknowledge_nmi(&mut self) {
+        self.bus.acknowledge_nmi();
+    }
+
+    #[inline]
+    fn irq(&self) -> bool {
+        self.bus.irq()
+    }
+}
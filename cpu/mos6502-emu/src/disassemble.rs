@@ -0,0 +1,155 @@
+//! 6502 disassembler
+//!
+//! Unofficial opcodes use their most common mnemonics, e.g. SLO, LAX, and DCP.
+
+use crate::bus::BusInterface;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddressingMode {
+    Imp,
+    Acc,
+    Imm,
+    Zp,
+    Zpx,
+    Zpy,
+    Abs,
+    Abx,
+    Aby,
+    Ind,
+    Izx,
+    Izy,
+    Rel,
+}
+
+use AddressingMode::{Abs, Abx, Aby, Acc, Imm, Imp, Ind, Izx, Izy, Rel, Zp, Zpx, Zpy};
+
+#[rustfmt::skip]
+const MNEMONICS: [&str; 256] = [
+    "BRK", "ORA", "KIL", "SLO", "NOP", "ORA", "ASL", "SLO",
+    "PHP", "ORA", "ASL", "ANC", "NOP", "ORA", "ASL", "SLO",
+    "BPL", "ORA", "KIL", "SLO", "NOP", "ORA", "ASL", "SLO",
+    "CLC", "ORA", "NOP", "SLO", "NOP", "ORA", "ASL", "SLO",
+    "JSR", "AND", "KIL", "RLA", "BIT", "AND", "ROL", "RLA",
+    "PLP", "AND", "ROL", "ANC", "BIT", "AND", "ROL", "RLA",
+    "BMI", "AND", "KIL", "RLA", "NOP", "AND", "ROL", "RLA",
+    "SEC", "AND", "NOP", "RLA", "NOP", "AND", "ROL", "RLA",
+    "RTI", "EOR", "KIL", "SRE", "NOP", "EOR", "LSR", "SRE",
+    "PHA", "EOR", "LSR", "ALR", "JMP", "EOR", "LSR", "SRE",
+    "BVC", "EOR", "KIL", "SRE", "NOP", "EOR", "LSR", "SRE",
+    "CLI", "EOR", "NOP", "SRE", "NOP", "EOR", "LSR", "SRE",
+    "RTS", "ADC", "KIL", "RRA", "NOP", "ADC", "ROR", "RRA",
+    "PLA", "ADC", "ROR", "ARR", "JMP", "ADC", "ROR", "RRA",
+    "BVS", "ADC", "KIL", "RRA", "NOP", "ADC", "ROR", "RRA",
+    "SEI", "ADC", "NOP", "RRA", "NOP", "ADC", "ROR", "RRA",
+    "NOP", "STA", "NOP", "SAX", "STY", "STA", "STX", "SAX",
+    "DEY", "NOP", "TXA", "XAA", "STY", "STA", "STX", "SAX",
+    "BCC", "STA", "KIL", "AHX", "STY", "STA", "STX", "SAX",
+    "TYA", "STA", "TXS", "TAS", "SHY", "STA", "SHX", "AHX",
+    "LDY", "LDA", "LDX", "LAX", "LDY", "LDA", "LDX", "LAX",
+    "TAY", "LDA", "TAX", "LAX", "LDY", "LDA", "LDX", "LAX",
+    "BCS", "LDA", "KIL", "LAX", "LDY", "LDA", "LDX", "LAX",
+    "CLV", "LDA", "TSX", "LAS", "LDY", "LDA", "LDX", "LAX",
+    "CPY", "CMP", "NOP", "DCP", "CPY", "CMP", "DEC", "DCP",
+    "INY", "CMP", "DEX", "AXS", "CPY", "CMP", "DEC", "DCP",
+    "BNE", "CMP", "KIL", "DCP", "NOP", "CMP", "DEC", "DCP",
+    "CLD", "CMP", "NOP", "DCP", "NOP", "CMP", "DEC", "DCP",
+    "CPX", "SBC", "NOP", "ISC", "CPX", "SBC", "INC", "ISC",
+    "INX", "SBC", "NOP", "SBC", "CPX", "SBC", "INC", "ISC",
+    "BEQ", "SBC", "KIL", "ISC", "NOP", "SBC", "INC", "ISC",
+    "SED", "SBC", "NOP", "ISC", "NOP", "SBC", "INC", "ISC",
+];
+
+#[rustfmt::skip]
+const ADDRESSING_MODES: [AddressingMode; 256] = [
+    Imp, Izx, Imp, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Acc, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx,
+    Abs, Izx, Imp, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Acc, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx,
+    Imp, Izx, Imp, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Acc, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx,
+    Imp, Izx, Imp, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Acc, Imm, Ind, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx,
+    Imm, Izx, Imm, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpy, Zpy, Imp, Aby, Imp, Aby, Abx, Abx, Aby, Aby,
+    Imm, Izx, Imm, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpy, Zpy, Imp, Aby, Imp, Aby, Abx, Abx, Aby, Aby,
+    Imm, Izx, Imm, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx,
+    Imm, Izx, Imm, Izx, Zp, Zp, Zp, Zp, Imp, Imm, Imp, Imm, Abs, Abs, Abs, Abs,
+    Rel, Izy, Imp, Izy, Zpx, Zpx, Zpx, Zpx, Imp, Aby, Imp, Aby, Abx, Abx, Abx, Abx,
+];
+
+/// Disassemble the instruction at `address`, returning the instruction text and its length in
+/// bytes.
+pub fn disassemble<B: BusInterface>(bus: &mut B, address: u16) -> (String, u16) {
+    let opcode = bus.read(address);
+    let mnemonic = MNEMONICS[opcode as usize];
+
+    let byte = |bus: &mut B| bus.read(address.wrapping_add(1));
+    let word = |bus: &mut B| {
+        u16::from_le_bytes([bus.read(address.wrapping_add(1)), bus.read(address.wrapping_add(2))])
+    };
+
+    match ADDRESSING_MODES[opcode as usize] {
+        Imp => (mnemonic.into(), 1),
+        Acc => (format!("{mnemonic} A"), 1),
+        Imm => (format!("{mnemonic} #${:02X}", byte(bus)), 2),
+        Zp => (format!("{mnemonic} ${:02X}", byte(bus)), 2),
+        Zpx => (format!("{mnemonic} ${:02X},X", byte(bus)), 2),
+        Zpy => (format!("{mnemonic} ${:02X},Y", byte(bus)), 2),
+        Izx => (format!("{mnemonic} (${:02X},X)", byte(bus)), 2),
+        Izy => (format!("{mnemonic} (${:02X}),Y", byte(bus)), 2),
+        Rel => {
+            let offset = byte(bus) as i8;
+            let target = address.wrapping_add(2).wrapping_add(offset as u16);
+            (format!("{mnemonic} ${target:04X}"), 2)
+        }
+        Abs => (format!("{mnemonic} ${:04X}", word(bus)), 3),
+        Abx => (format!("{mnemonic} ${:04X},X", word(bus)), 3),
+        Aby => (format!("{mnemonic} ${:04X},Y", word(bus)), 3),
+        Ind => (format!("{mnemonic} (${:04X})", word(bus)), 3),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Memory(Vec<u8>);
+
+    impl BusInterface for Memory {
+        fn read(&mut self, address: u16) -> u8 {
+            self.0.get(address as usize).copied().unwrap_or(0)
+        }
+
+        fn write(&mut self, _address: u16, _value: u8) {}
+
+        fn nmi(&self) -> bool {
+            false
+        }
+
+        fn acknowledge_nmi(&mut self) {}
+
+        fn irq(&self) -> bool {
+            false
+        }
+    }
+
+    fn disassemble_bytes(bytes: &[u8]) -> (String, u16) {
+        disassemble(&mut Memory(bytes.to_vec()), 0)
+    }
+
+    #[test]
+    fn addressing_modes() {
+        assert_eq!(disassemble_bytes(&[0xEA]), ("NOP".into(), 1));
+        assert_eq!(disassemble_bytes(&[0x0A]), ("ASL A".into(), 1));
+        assert_eq!(disassemble_bytes(&[0xA9, 0x1F]), ("LDA #$1F".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xB6, 0x80]), ("LDX $80,Y".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x81, 0x20]), ("STA ($20,X)".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xB1, 0x20]), ("LDA ($20),Y".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xD0, 0xFE]), ("BNE $0000".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x20, 0x34, 0x12]), ("JSR $1234".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x6C, 0xFC, 0xFF]), ("JMP ($FFFC)".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x9E, 0x00, 0x02]), ("SHX $0200,Y".into(), 3));
+    }
+}
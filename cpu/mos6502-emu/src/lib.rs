@@ -1,4 +1,6 @@
 pub mod bus;
+pub mod debug;
+pub mod disassemble;
 mod instructions;
 
 use crate::bus::BusInterface;
@@ -1,6 +1,7 @@
 use clap::Parser;
 use env_logger::Env;
 use mos6502_emu::bus::BusInterface;
+use mos6502_emu::disassemble;
 use mos6502_emu::{CpuRegisters, Mos6502, StatusFlags, StatusReadContext};
 use serde::Deserialize;
 use std::fs::File;
@@ -8,6 +9,10 @@ use std::io::BufReader;
 use std::mem;
 use std::path::Path;
 
+// Instructions that do not always end at the address immediately following the instruction
+const CONTROL_FLOW_MNEMONICS: &[&str] =
+    &["BPL", "BMI", "BVC", "BVS", "BCC", "BCS", "BNE", "BEQ", "JMP", "JSR", "RTS", "RTI", "BRK"];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BusCycle {
     Read(u16, u8),
@@ -118,6 +123,7 @@ fn main() -> anyhow::Result<()> {
             serde_json::from_reader(BufReader::new(File::open(&file_path)?))?;
 
         let mut failures = 0;
+        let mut disassembly_failures = 0;
         let test_count = tests.len();
         for test in tests {
             bus.clear();
@@ -138,6 +144,8 @@ fn main() -> anyhow::Result<()> {
                 ..cpu.registers().clone()
             });
 
+            let (disassembled, len) = disassemble::disassemble(&mut bus, test.initial.pc);
+
             bus.cycles.clear();
             cpu.tick(&mut bus);
             while cpu.is_mid_instruction() && !cpu.frozen() {
@@ -153,11 +161,22 @@ fn main() -> anyhow::Result<()> {
                 failures += 1;
                 log::debug!("Above failures in '{}'", test.name);
             }
+
+            if check_disassembly(&disassembled, len, test.initial.pc, test.final_.pc) {
+                disassembly_failures += 1;
+                log::debug!("Above disassembly failure in '{}'", test.name);
+            }
         }
 
         if failures != 0 {
             log::error!("Failed {failures} out of {test_count} tests for opcode {opcode:02X}");
         }
+
+        if disassembly_failures != 0 {
+            log::error!(
+                "Disassembly length did not match in {disassembly_failures} out of {test_count} tests for opcode {opcode:02X}"
+            );
+        }
     }
 
     Ok(())
@@ -214,6 +233,21 @@ fn check_state(cpu: &Mos6502, bus: &Bus, final_state: &SystemState, cycles: &[Cy
     errors
 }
 
+fn check_disassembly(disassembled: &str, len: u16, initial_pc: u16, final_pc: u16) -> bool {
+    let mnemonic = disassembled.split(' ').next().unwrap_or_default();
+    if CONTROL_FLOW_MNEMONICS.contains(&mnemonic) {
+        return false;
+    }
+
+    let expected_pc = initial_pc.wrapping_add(len);
+    if expected_pc != final_pc {
+        log::debug!("'{disassembled}' length {len} does not match final PC {final_pc:04X}");
+        true
+    } else {
+        false
+    }
+}
+
 fn check_register(name: &str, expected: u8, actual: u8) -> bool {
     if expected != actual {
         log::debug!("{name}: expected={expected:02X}, actual={actual:02X}");
@@ -0,0 +1,86 @@
+//! Debugger support: register access and a bus wrapper that reports memory accesses for
+//! watchpoints.
+
+use crate::traits::BusInterface;
+use crate::{disassemble, Spc700};
+use jgenesis_common::debug::{CpuArch, DebugHooks};
+
+impl Spc700 {
+    /// Registers in the order A, X, Y, SP, PSW, PC.
+    #[must_use]
+    pub fn debug_registers(&self) -> [u16; 6] {
+        let registers = &self.registers;
+        [
+            registers.a.into(),
+            registers.x.into(),
+            registers.y.into(),
+            registers.sp.into(),
+            u8::from(registers.psw).into(),
+            registers.pc,
+        ]
+    }
+
+    /// Overwrite registers using the same order as [`Self::debug_registers`].
+    pub fn set_debug_registers(&mut self, values: [u16; 6]) {
+        let [a, x, y, sp, psw, pc] = values;
+
+        let registers = &mut self.registers;
+        registers.a = a as u8;
+        registers.x = x as u8;
+        registers.y = y as u8;
+        registers.sp = sp as u8;
+        registers.psw = (psw as u8).into();
+        registers.pc = pc;
+    }
+
+    /// Record the instruction at PC in the given CPU's trace if it is being traced and PC is within
+    /// the trace's address ranges. Cores should call this before executing each instruction.
+    pub fn trace_instruction<B: BusInterface>(
+        &self,
+        bus: &mut B,
+        hooks: &mut DebugHooks,
+        cpu: usize,
+    ) {
+        let pc = self.registers.pc;
+        // SLEEP and STOP halt the CPU until reset
+        if self.state.stopped || !hooks.traces(cpu, pc.into()) {
+            return;
+        }
+
+        let (disassembly, _) = disassemble::disassemble(bus, pc);
+        let registers = self.debug_registers().map(u32::from);
+        hooks.record_trace(cpu, CpuArch::Spc700, &disassembly, &registers);
+    }
+}
+
+/// Wraps a bus and reports every memory access to [`DebugHooks::check_access`].
+pub struct DebugBus<'a, B> {
+    bus: &'a mut B,
+    hooks: &'a mut DebugHooks,
+    cpu: usize,
+}
+
+impl<'a, B: BusInterface> DebugBus<'a, B> {
+    pub fn new(bus: &'a mut B, hooks: &'a mut DebugHooks, cpu: usize) -> Self {
+        Self { bus, hooks, cpu }
+    }
+}
+
+impl<B: BusInterface> BusInterface for DebugBus<'_, B> {
+    #[inline]
+    fn read(&mut self, address: u16) -> u8 {
+        self.hooks.check_access(self.cpu, address.into(), 1, false);
+        self.bus.read(address)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u16, value: u8) {
+        self.hooks.check_access(self.cpu, address.into(), 1, true);
+        self.bus.write(address, value);
+    }
+
+    #[inline]
+    fn idle(&mut self) {
+        self.bus.idle();
+    }
+}
@@ -1,3 +1,12 @@
+//! SPC700 disassembler
+//!
+//! Instruction templates use lowercase placeholders for operands: `d` is a direct page address,
+//! `!a` an absolute address, `#i` an immediate, `r` a relative branch target, `m.b` an absolute
+//! address with bit index, and `u` a PCALL offset into the uppermost page.
+
+use crate::traits::BusInterface;
+
+#[must_use]
 pub fn instruction_str(opcode: u8) -> &'static str {
     match opcode {
         0x00 => "NOP",
@@ -123,7 +132,7 @@ pub fn instruction_str(opcode: u8) -> &'static str {
         0x78 => "CMP d, #i",
         0x79 => "CMP (X), (Y)",
         0x7A => "ADDW YA, d",
-        0x7B => "ROR d+x",
+        0x7B => "ROR d+X",
         0x7C => "ROR A",
         0x7D => "MOV A, X",
         0x7E => "CMP Y, d",
@@ -180,7 +189,7 @@ pub fn instruction_str(opcode: u8) -> &'static str {
         0xB1 => "TCALL 11",
         0xB2 => "CLR1 d.5",
         0xB3 => "BBC d.5, r",
-        0xB4 => "SBC a, d+X",
+        0xB4 => "SBC A, d+X",
         0xB5 => "SBC A, !a+X",
         0xB6 => "SBC A, !a+Y",
         0xB7 => "SBC A, [d]+Y",
@@ -258,3 +267,110 @@ pub fn instruction_str(opcode: u8) -> &'static str {
         0xFF => "STOP",
     }
 }
+
+/// Disassemble the instruction at `address`, returning the instruction text and its length in
+/// bytes.
+pub fn disassemble<B: BusInterface>(bus: &mut B, address: u16) -> (String, u16) {
+    let template = instruction_str(bus.read(address));
+
+    let (mnemonic, operands) = template.split_once(' ').unwrap_or((template, ""));
+    let operands: Vec<&str> =
+        if operands.is_empty() { vec![] } else { operands.split(", ").collect() };
+    let operand_lens: Vec<u16> = operands.iter().map(|operand| operand_len(operand)).collect();
+    let len = 1 + operand_lens.iter().sum::<u16>();
+
+    // Operand bytes are encoded in source-destination order for direct page to direct page and
+    // immediate to direct page instructions, which is the reverse of how they are written
+    let mut offsets = Vec::with_capacity(operands.len());
+    let mut offset = 1;
+    for &operand_len in &operand_lens {
+        offsets.push(offset);
+        offset += operand_len;
+    }
+    if operands.len() == 2 && (operands[1] == "ds" || operands[1] == "#i") {
+        offsets.swap(0, 1);
+    }
+
+    let next_address = address.wrapping_add(len);
+    let operands: Vec<String> = operands
+        .iter()
+        .zip(offsets)
+        .map(|(&operand, offset)| {
+            let byte = bus.read(address.wrapping_add(offset));
+            let word = u16::from_le_bytes([byte, bus.read(address.wrapping_add(offset + 1))]);
+            format_operand(operand, byte, word, next_address)
+        })
+        .collect();
+
+    if operands.is_empty() {
+        (mnemonic.into(), len)
+    } else {
+        (format!("{mnemonic} {}", operands.join(", ")), len)
+    }
+}
+
+fn operand_len(operand: &str) -> u16 {
+    if operand.contains("!a") || operand.contains("m.b") {
+        2
+    } else {
+        // All other placeholders are single bytes
+        operand.contains(|c: char| c.is_ascii_lowercase()).into()
+    }
+}
+
+fn format_operand(operand: &str, byte: u8, word: u16, next_address: u16) -> String {
+    if operand.contains("!a") {
+        operand.replace("!a", &format!("!${word:04X}"))
+    } else if operand.contains("m.b") {
+        operand.replace("m.b", &format!("${:04X}.{}", word & 0x1FFF, word >> 13))
+    } else if operand == "#i" {
+        format!("#${byte:02X}")
+    } else if operand == "r" {
+        let target = next_address.wrapping_add(byte as i8 as u16);
+        format!("${target:04X}")
+    } else if operand == "u" {
+        format!("$FF{byte:02X}")
+    } else if operand == "dd" || operand == "ds" {
+        format!("${byte:02X}")
+    } else if operand.contains('d') {
+        operand.replacen('d', &format!("${byte:02X}"), 1)
+    } else {
+        operand.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Memory(Vec<u8>);
+
+    impl BusInterface for Memory {
+        fn read(&mut self, address: u16) -> u8 {
+            self.0.get(address as usize).copied().unwrap_or(0)
+        }
+
+        fn write(&mut self, _address: u16, _value: u8) {}
+
+        fn idle(&mut self) {}
+    }
+
+    fn disassemble_bytes(bytes: &[u8]) -> (String, u16) {
+        disassemble(&mut Memory(bytes.to_vec()), 0)
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(disassemble_bytes(&[0x00]), ("NOP".into(), 1));
+        assert_eq!(disassemble_bytes(&[0xE8, 0x12]), ("MOV A, #$12".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xC5, 0x34, 0x12]), ("MOV !$1234, A".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xF7, 0x40]), ("MOV A, [$40]+Y".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xFA, 0x10, 0x20]), ("MOV $20, $10".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x8F, 0xAA, 0xF1]), ("MOV $F1, #$AA".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x2F, 0xFE]), ("BRA $0000".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x13, 0x05, 0x10]), ("BBC $05.0, $0013".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xAA, 0x34, 0xF2]), ("MOV1 C, $1234.7".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x4F, 0xC0]), ("PCALL $FFC0".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x1F, 0x00, 0x80]), ("JMP [!$8000+X]".into(), 3));
+    }
+}
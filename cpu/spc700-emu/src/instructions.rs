@@ -1,10 +1,10 @@
 mod alu;
 mod bits;
-mod disassemble;
 mod flags;
 mod flow;
 mod load;
 
+use crate::disassemble;
 use crate::traits::BusInterface;
 use crate::Spc700;
 use jgenesis_common::num::{GetBit, SignBit};
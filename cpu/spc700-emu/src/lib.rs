@@ -1,3 +1,5 @@
+pub mod debug;
+pub mod disassemble;
 mod instructions;
 pub mod traits;
 
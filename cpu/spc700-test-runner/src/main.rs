@@ -1,6 +1,7 @@
 use clap::Parser;
 use env_logger::Env;
 use serde::Deserialize;
+use spc700_emu::disassemble;
 use spc700_emu::traits::BusInterface;
 use spc700_emu::{Registers, Spc700};
 use std::error::Error;
@@ -17,6 +18,12 @@ const SLEEP_OPCODE: u8 = 0xEF;
 const STOP_OPCODE: u8 = 0xFF;
 const STOP_TEST_CYCLES: usize = 7;
 
+// Instructions that do not always end at the address immediately following the instruction
+const CONTROL_FLOW_MNEMONICS: &[&str] = &[
+    "BPL", "BMI", "BVC", "BVS", "BCC", "BCS", "BNE", "BEQ", "BRA", "BBS", "BBC", "CBNE", "DBNZ",
+    "JMP", "CALL", "PCALL", "TCALL", "RET", "RETI", "BRK", "SLEEP", "STOP",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BusOp {
     Read(u16, u8),
@@ -165,6 +172,9 @@ fn run_test<P: AsRef<Path>>(file_path: P) -> Result<(), Box<dyn Error>> {
 
         init_test(&mut cpu, &mut bus, &test_description.initial);
 
+        let (disassembled, len) = disassemble::disassemble(&mut bus, test_description.initial.pc);
+        bus.clear();
+
         let opcode = bus.ram[test_description.initial.pc as usize];
         if opcode != SLEEP_OPCODE && opcode != STOP_OPCODE {
             // Run CPU for a full instruction
@@ -180,7 +190,13 @@ fn run_test<P: AsRef<Path>>(file_path: P) -> Result<(), Box<dyn Error>> {
             }
         }
 
-        let errors = check_test(&cpu, &bus, &test_description.final_, &test_description.cycles);
+        let mut errors = check_test(&cpu, &bus, &test_description.final_, &test_description.cycles);
+        errors.extend(check_disassembly(
+            &disassembled,
+            len,
+            test_description.initial.pc,
+            test_description.final_.pc,
+        ));
         if !errors.is_empty() {
             failures += 1;
 
@@ -270,3 +286,20 @@ fn check_test(cpu: &Spc700, bus: &RecordingBus, state: &State, cycles: &[Cycle])
 
     errors
 }
+
+fn check_disassembly(
+    disassembled: &str,
+    len: u16,
+    initial_pc: u16,
+    final_pc: u16,
+) -> Option<String> {
+    let mnemonic = disassembled.split(' ').next().unwrap_or_default();
+    if CONTROL_FLOW_MNEMONICS.contains(&mnemonic) {
+        return None;
+    }
+
+    let expected_pc = initial_pc.wrapping_add(len);
+    (expected_pc != final_pc).then(|| {
+        format!("Disassembly: '{disassembled}' length {len} does not match final PC {final_pc:04X}")
+    })
+}
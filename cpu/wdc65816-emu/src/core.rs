@@ -234,6 +234,11 @@ impl Wdc65816 {
         self.state.cycle != 0
     }
 
+    /// Whether the CPU is halted by a WAI or STP instruction.
+    pub(crate) fn is_halted(&self) -> bool {
+        self.state.waiting || self.state.stopped
+    }
+
     #[inline]
     #[must_use]
     pub fn registers(&self) -> &Registers {
@@ -1,11 +1,11 @@
 mod alu;
 mod bits;
-mod disassemble;
 mod flags;
 mod flow;
 mod load;
 
 use crate::core::{InterruptType, Registers, SizeBits, Wdc65816};
+use crate::disassemble;
 use crate::traits::BusInterface;
 use jgenesis_common::num::{GetBit, SignBit, U16Ext};
 
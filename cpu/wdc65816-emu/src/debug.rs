@@ -0,0 +1,124 @@
+//! Debugger support: register access and a bus wrapper that reports memory accesses for
+//! watchpoints.
+
+use crate::core::Wdc65816;
+use crate::traits::BusInterface;
+use jgenesis_common::debug::{CpuArch, DebugHooks};
+
+impl Wdc65816 {
+    /// Registers in the order A, X, Y, S, D, PC, PBR, DBR, P, E.
+    #[must_use]
+    pub fn debug_registers(&self) -> [u16; 10] {
+        let registers = self.registers();
+        [
+            registers.a,
+            registers.x,
+            registers.y,
+            registers.s,
+            registers.d,
+            registers.pc,
+            registers.pbr.into(),
+            registers.dbr.into(),
+            u8::from(registers.p).into(),
+            registers.emulation_mode.into(),
+        ]
+    }
+
+    /// Overwrite registers using the same order as [`Self::debug_registers`].
+    pub fn set_debug_registers(&mut self, values: [u16; 10]) {
+        let mut registers = self.registers().clone();
+        registers.a = values[0];
+        registers.x = values[1];
+        registers.y = values[2];
+        registers.s = values[3];
+        registers.d = values[4];
+        registers.pc = values[5];
+        registers.pbr = values[6] as u8;
+        registers.dbr = values[7] as u8;
+        registers.p = (values[8] as u8).into();
+        registers.emulation_mode = values[9] != 0;
+        self.set_registers(registers);
+    }
+
+    /// Record the instruction at PC in the given CPU's trace if it is being traced and PC is within
+    /// the trace's address ranges. Cores should call this before executing each instruction.
+    pub fn trace_instruction<B: BusInterface>(
+        &self,
+        bus: &mut B,
+        hooks: &mut DebugHooks,
+        cpu: usize,
+    ) {
+        let registers = self.registers();
+        let pc = (u32::from(registers.pbr) << 16) | u32::from(registers.pc);
+        if self.is_halted() || !hooks.traces(cpu, pc) {
+            return;
+        }
+
+        let (disassembly, _) = self.disassemble(bus, pc);
+        hooks.record_trace(
+            cpu,
+            CpuArch::Wdc65816,
+            &disassembly,
+            &self.debug_registers().map(u32::from),
+        );
+    }
+}
+
+/// Wraps a bus and reports every memory access to [`DebugHooks::check_access`].
+pub struct DebugBus<'a, B> {
+    bus: &'a mut B,
+    hooks: &'a mut DebugHooks,
+    cpu: usize,
+}
+
+impl<'a, B: BusInterface> DebugBus<'a, B> {
+    pub fn new(bus: &'a mut B, hooks: &'a mut DebugHooks, cpu: usize) -> Self {
+        Self { bus, hooks, cpu }
+    }
+}
+
+impl<B: BusInterface> BusInterface for DebugBus<'_, B> {
+    const ADDRESS_MASK: u32 = B::ADDRESS_MASK;
+
+    #[inline]
+    fn read(&mut self, address: u32) -> u8 {
+        self.hooks.check_access(self.cpu, address, 1, false);
+        self.bus.read(address)
+    }
+
+    #[inline]
+    fn write(&mut self, address: u32, value: u8) {
+        self.hooks.check_access(self.cpu, address, 1, true);
+        self.bus.write(address, value);
+    }
+
+    #[inline]
+    fn idle(&mut self) {
+        self.bus.idle();
+    }
+
+    #[inline]
+    fn nmi(&self) -> bool {
+        self.bus.nmi()
+    }
+
+    #[inline]
+    fn acknowledge_nmi(&mut self) {
+        self.bus.acknowledge_nmi();
+    }
+
+    #[inline]
+    fn irq(&self) -> bool {
+        self.bus.irq()
+    }
+
+    #[inline]
+    fn halt(&self) -> bool {
+        self.bus.halt()
+    }
+
+    #[inline]
+    fn reset(&self) -> bool {
+        self.bus.reset()
+    }
+}
@@ -1,3 +1,13 @@
+//! 65816 disassembler
+//!
+//! Instruction templates use `$xx` / `$xxxx` / `$xxxxxx` for direct page, absolute, and long
+//! address operands respectively, and `#<d>` for immediates and branch displacements. The size of
+//! most immediate operands depends on the M and X flags.
+
+use crate::core::Wdc65816;
+use crate::traits::BusInterface;
+
+#[must_use]
 pub fn instruction_str(opcode: u8) -> &'static str {
     match opcode {
         0x00 => "BRK",
@@ -258,3 +268,160 @@ pub fn instruction_str(opcode: u8) -> &'static str {
         0xFF => "SBC $xxxxxx,X",
     }
 }
+
+/// Disassemble the instruction at `address`, returning the instruction text and its length in
+/// bytes.
+///
+/// `accumulator_8bit` and `index_8bit` should match the M and X flags at the time the instruction
+/// executes; both are always set in emulation mode.
+pub fn disassemble<B: BusInterface>(
+    bus: &mut B,
+    address: u32,
+    accumulator_8bit: bool,
+    index_8bit: bool,
+) -> (String, u32) {
+    let opcode = bus.read(address);
+    let template = instruction_str(opcode);
+
+    match opcode {
+        // BRK / COP / WDM
+        0x00 | 0x02 | 0x42 => {
+            let signature = read_operand(bus, address, 1);
+            return (format!("{template} #${signature:02X}"), 2);
+        }
+        // MVP / MVN: destination bank followed by source bank
+        0x44 | 0x54 => {
+            let banks = read_operand(bus, address, 2);
+            return (format!("{template} ${:02X},${:02X}", banks >> 8, banks & 0xFF), 3);
+        }
+        _ => {}
+    }
+
+    if template.contains("#<d>") {
+        let (operand, len) = match opcode {
+            // Branches
+            0x10 | 0x30 | 0x50 | 0x70 | 0x80 | 0x90 | 0xB0 | 0xD0 | 0xF0 => {
+                let displacement = read_operand(bus, address, 1) as i8;
+                (format!("${:06X}", branch_target(address, 2, displacement as u16)), 2)
+            }
+            // BRL / PER
+            0x62 | 0x82 => {
+                let displacement = read_operand(bus, address, 2) as u16;
+                (format!("${:06X}", branch_target(address, 3, displacement)), 3)
+            }
+            // REP / SEP
+            0xC2 | 0xE2 => immediate(bus, address, true),
+            // LDY / LDX / CPY / CPX
+            0xA0 | 0xA2 | 0xC0 | 0xE0 => immediate(bus, address, index_8bit),
+            _ => immediate(bus, address, accumulator_8bit),
+        };
+        return (template.replace("#<d>", &operand), len);
+    }
+
+    for (placeholder, len) in [("$xxxxxx", 3), ("$xxxx", 2), ("$xx", 1)] {
+        if template.contains(placeholder) {
+            let value = read_operand(bus, address, len);
+            let width = 2 * len as usize;
+            return (template.replace(placeholder, &format!("${value:0width$X}")), 1 + len);
+        }
+    }
+
+    (template.into(), 1)
+}
+
+// Read a little-endian operand of `len` bytes following the opcode. Operands wrap within the
+// program bank, same as the program counter
+fn read_operand<B: BusInterface>(bus: &mut B, address: u32, len: u32) -> u32 {
+    (0..len).fold(0, |value, i| {
+        let operand_address = (address & 0xFF0000) | (address.wrapping_add(1 + i) & 0xFFFF);
+        value | (u32::from(bus.read(operand_address)) << (8 * i))
+    })
+}
+
+fn branch_target(address: u32, len: u16, displacement: u16) -> u32 {
+    let pc = (address as u16).wrapping_add(len).wrapping_add(displacement);
+    (address & 0xFF0000) | u32::from(pc)
+}
+
+fn immediate<B: BusInterface>(bus: &mut B, address: u32, is_8bit: bool) -> (String, u32) {
+    if is_8bit {
+        (format!("#${:02X}", read_operand(bus, address, 1)), 2)
+    } else {
+        (format!("#${:04X}", read_operand(bus, address, 2)), 3)
+    }
+}
+
+impl Wdc65816 {
+    /// Disassemble the instruction at `address` using the current M and X flags to determine
+    /// immediate operand sizes.
+    pub fn disassemble<B: BusInterface>(&self, bus: &mut B, address: u32) -> (String, u32) {
+        let registers = self.registers();
+        let p = u8::from(registers.p);
+        let accumulator_8bit = registers.emulation_mode || p & 0x20 != 0;
+        let index_8bit = registers.emulation_mode || p & 0x10 != 0;
+        disassemble(bus, address, accumulator_8bit, index_8bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Memory(Vec<u8>);
+
+    impl BusInterface for Memory {
+        fn read(&mut self, address: u32) -> u8 {
+            self.0.get((address & 0xFFFF) as usize).copied().unwrap_or(0)
+        }
+
+        fn write(&mut self, _address: u32, _value: u8) {}
+
+        fn idle(&mut self) {}
+
+        fn nmi(&self) -> bool {
+            false
+        }
+
+        fn acknowledge_nmi(&mut self) {}
+
+        fn irq(&self) -> bool {
+            false
+        }
+
+        fn halt(&self) -> bool {
+            false
+        }
+
+        fn reset(&self) -> bool {
+            false
+        }
+    }
+
+    fn disassemble_bytes(bytes: &[u8], m: bool, x: bool) -> (String, u32) {
+        disassemble(&mut Memory(bytes.to_vec()), 0x7E0000, m, x)
+    }
+
+    #[test]
+    fn immediate_sizes() {
+        assert_eq!(disassemble_bytes(&[0xA9, 0x34, 0x12], true, true), ("LDA #$34".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xA9, 0x34, 0x12], false, true), ("LDA #$1234".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xA2, 0x34, 0x12], false, true), ("LDX #$34".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xA2, 0x34, 0x12], true, false), ("LDX #$1234".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xC2, 0x30], false, false), ("REP #$30".into(), 2));
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(disassemble_bytes(&[0xEA], true, true), ("NOP".into(), 1));
+        assert_eq!(disassemble_bytes(&[0xA5, 0x10], true, true), ("LDA $10".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xB7, 0x10], true, true), ("LDA [$10],Y".into(), 2));
+        assert_eq!(
+            disassemble_bytes(&[0x22, 0x56, 0x34, 0x12], true, true),
+            ("JSL $123456".into(), 4)
+        );
+        assert_eq!(disassemble_bytes(&[0x80, 0xFE], true, true), ("BRA $7E0000".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x82, 0xFD, 0xFF], true, true), ("BRL $7E0000".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x54, 0x7E, 0x7F], true, true), ("MVN $7F,$7E".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x00, 0x01], true, true), ("BRK #$01".into(), 2));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod core;
+pub mod debug;
+pub mod disassemble;
 pub mod traits;
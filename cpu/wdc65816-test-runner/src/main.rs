@@ -16,6 +16,12 @@ use wdc65816_emu::core::{Registers, Wdc65816};
 const MVN_OPCODE: u8 = 0x44;
 const MVP_OPCODE: u8 = 0x54;
 
+// Instructions that do not always end at the address immediately following the instruction
+const CONTROL_FLOW_MNEMONICS: &[&str] = &[
+    "BPL", "BMI", "BVC", "BVS", "BCC", "BCS", "BNE", "BEQ", "BRA", "BRL", "JMP", "JSR", "JSL",
+    "RTS", "RTL", "RTI", "BRK", "COP", "MVN", "MVP",
+];
+
 #[derive(Debug, Clone, Deserialize)]
 struct State {
     pc: u16,
@@ -156,6 +162,11 @@ fn test_file<R: Read>(
         let opcode_addr = (u32::from(test_description.initial.pbr) << 16)
             | u32::from(test_description.initial.pc);
         let opcode = bus.ram[opcode_addr as usize];
+
+        // Disassemble using the initial M and X flags
+        let (disassembled, len) = wdc65816.disassemble(bus, opcode_addr);
+        bus.clear();
+
         if opcode != MVN_OPCODE && opcode != MVP_OPCODE {
             wdc65816.tick(bus);
             while wdc65816.is_mid_instruction() {
@@ -172,7 +183,14 @@ fn test_file<R: Read>(
             }
         }
 
-        let errors = check_test(&wdc65816, bus, &test_description.final_, &test_description.cycles);
+        let mut errors =
+            check_test(&wdc65816, bus, &test_description.final_, &test_description.cycles);
+        errors.extend(check_disassembly(
+            &disassembled,
+            len,
+            test_description.initial.pc,
+            test_description.final_.pc,
+        ));
 
         if !errors.is_empty() {
             failures += 1;
@@ -285,3 +303,20 @@ fn check_test(
 
     errors
 }
+
+fn check_disassembly(
+    disassembled: &str,
+    len: u32,
+    initial_pc: u16,
+    final_pc: u16,
+) -> Option<String> {
+    let mnemonic = disassembled.split(' ').next().unwrap_or_default();
+    if CONTROL_FLOW_MNEMONICS.contains(&mnemonic) {
+        return None;
+    }
+
+    let expected_pc = initial_pc.wrapping_add(len as u16);
+    (expected_pc != final_pc).then(|| {
+        format!("Disassembly: '{disassembled}' length {len} does not match final PC {final_pc:04X}")
+    })
+}
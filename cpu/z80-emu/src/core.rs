@@ -319,6 +319,10 @@ impl Z80 {
         self.registers.pc
     }
 
+    pub(crate) fn is_halted(&self) -> bool {
+        self.registers.halted
+    }
+
     pub fn set_pc(&mut self, pc: u16) {
         self.registers.pc = pc;
     }
@@ -331,6 +335,57 @@ impl Z80 {
         self.registers.interrupt_mode = mode;
     }
 
+    /// Registers in the order AF, BC, DE, HL, SP, PC, IX, IY, AF', BC', DE', HL', IR.
+    #[must_use]
+    pub fn debug_registers(&self) -> [u16; 13] {
+        let registers = &self.registers;
+        [
+            Register16::AF.read_from(registers),
+            Register16::BC.read_from(registers),
+            Register16::DE.read_from(registers),
+            Register16::HL.read_from(registers),
+            registers.sp,
+            registers.pc,
+            registers.ix,
+            registers.iy,
+            u16::from_be_bytes([registers.ap, registers.fp.into()]),
+            u16::from_be_bytes([registers.bp, registers.cp]),
+            u16::from_be_bytes([registers.dp, registers.ep]),
+            u16::from_be_bytes([registers.hp, registers.lp]),
+            u16::from_be_bytes([registers.i, registers.r]),
+        ]
+    }
+
+    /// Overwrite registers using the same order as [`Self::debug_registers`].
+    pub fn set_debug_registers(&mut self, values: [u16; 13]) {
+        let [af, bc, de, hl, sp, pc, ix, iy, afp, bcp, dep, hlp, ir] = values;
+
+        let registers = &mut self.registers;
+        Register16::AF.write_to(af, registers);
+        Register16::BC.write_to(bc, registers);
+        Register16::DE.write_to(de, registers);
+        Register16::HL.write_to(hl, registers);
+        registers.sp = sp;
+        registers.pc = pc;
+        registers.ix = ix;
+        registers.iy = iy;
+
+        let [ap, fp] = afp.to_be_bytes();
+        registers.ap = ap;
+        registers.fp = fp.into();
+        [registers.bp, registers.cp] = bcp.to_be_bytes();
+        [registers.dp, registers.ep] = dep.to_be_bytes();
+        [registers.hp, registers.lp] = hlp.to_be_bytes();
+        [registers.i, registers.r] = ir.to_be_bytes();
+    }
+
+    /// Whether the next call to [`Self::tick`] will start executing a new instruction.
+    #[must_use]
+    #[inline]
+    pub fn at_instruction_boundary(&self) -> bool {
+        self.t_cycles_wait == 0
+    }
+
     #[must_use]
     #[inline]
     pub fn stalled(&self) -> bool {
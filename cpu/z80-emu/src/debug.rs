@@ -0,0 +1,83 @@
+//! Debugger support: instruction tracing and a bus wrapper that reports memory accesses for
+//! watchpoints.
+
+use crate::traits::InterruptLine;
+use crate::{disassemble, BusInterface, Z80};
+use jgenesis_common::debug::{CpuArch, DebugHooks};
+
+impl Z80 {
+    /// Record the instruction at PC in the given CPU's trace if it is being traced and PC is within
+    /// the trace's address ranges. Cores should call this before executing each instruction.
+    pub fn trace_instruction<B: BusInterface>(
+        &self,
+        bus: &mut B,
+        hooks: &mut DebugHooks,
+        cpu: usize,
+    ) {
+        // The Z80 repeatedly executes NOPs internally while halted; don't log each one
+        if self.is_halted() || !hooks.traces(cpu, self.pc().into()) {
+            return;
+        }
+
+        let (disassembly, _) = disassemble::disassemble(bus, self.pc());
+        hooks.record_trace(cpu, CpuArch::Z80, &disassembly, &self.debug_registers().map(u32::from));
+    }
+}
+
+/// Wraps a bus and reports every memory access to [`DebugHooks::check_access`]. I/O port
+/// accesses are not reported.
+pub struct DebugBus<'a, B> {
+    bus: &'a mut B,
+    hooks: &'a mut DebugHooks,
+    cpu: usize,
+}
+
+impl<'a, B: BusInterface> DebugBus<'a, B> {
+    pub fn new(bus: &'a mut B, hooks: &'a mut DebugHooks, cpu: usize) -> Self {
+        Self { bus, hooks, cpu }
+    }
+}
+
+impl<B: BusInterface> BusInterface for DebugBus<'_, B> {
+    #[inline]
+    fn read_memory(&mut self, address: u16) -> u8 {
+        self.hooks.check_access(self.cpu, address.into(), 1, false);
+        self.bus.read_memory(address)
+    }
+
+    #[inline]
+    fn write_memory(&mut self, address: u16, value: u8) {
+        self.hooks.check_access(self.cpu, address.into(), 1, true);
+        self.bus.write_memory(address, value);
+    }
+
+    #[inline]
+    fn read_io(&mut self, address: u16) -> u8 {
+        self.bus.read_io(address)
+    }
+
+    #[inline]
+    fn write_io(&mut self, address: u16, value: u8) {
+        self.bus.write_io(address, value);
+    }
+
+    #[inline]
+    fn nmi(&self) -> InterruptLine {
+        self.bus.nmi()
+    }
+
+    #[inline]
+    fn int(&self) -> InterruptLine {
+        self.bus.int()
+    }
+
+    #[inline]
+    fn busreq(&self) -> bool {
+        self.bus.busreq()
+    }
+
+    #[inline]
+    fn reset(&self) -> bool {
+        self.bus.reset()
+    }
+}
@@ -0,0 +1,314 @@
+//! Z80 disassembler
+//!
+//! Opcodes are decoded using the x/y/z/p/q bit fields of each opcode rather than lookup tables so
+//! that the DD/FD index prefixes can be applied to any instruction that references HL, H, or L.
+//! Undocumented instructions use the mnemonics most commonly used by other Z80 assemblers.
+
+use crate::traits::BusInterface;
+
+const REGISTERS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+const REGISTER_PAIRS: [&str; 4] = ["BC", "DE", "HL", "SP"];
+const REGISTER_PAIRS_AF: [&str; 4] = ["BC", "DE", "HL", "AF"];
+const CONDITIONS: [&str; 8] = ["NZ", "Z", "NC", "C", "PO", "PE", "P", "M"];
+const ALU_OPERATIONS: [&str; 8] =
+    ["ADD A, ", "ADC A, ", "SUB ", "SBC A, ", "AND ", "XOR ", "OR ", "CP "];
+const ROTATE_OPERATIONS: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SLL", "SRL"];
+const INTERRUPT_MODES: [&str; 8] = ["0", "0/1", "1", "2", "0", "0/1", "1", "2"];
+const BLOCK_INSTRUCTIONS: [[&str; 4]; 4] = [
+    ["LDI", "CPI", "INI", "OUTI"],
+    ["LDD", "CPD", "IND", "OUTD"],
+    ["LDIR", "CPIR", "INIR", "OTIR"],
+    ["LDDR", "CPDR", "INDR", "OTDR"],
+];
+
+struct Disassembler<'a, B> {
+    bus: &'a mut B,
+    address: u16,
+    len: u16,
+    index: Option<&'static str>,
+}
+
+impl<B: BusInterface> Disassembler<'_, B> {
+    fn byte(&mut self) -> u8 {
+        let byte = self.bus.read_memory(self.address.wrapping_add(self.len));
+        self.len += 1;
+        byte
+    }
+
+    fn word(&mut self) -> u16 {
+        let lsb = self.byte();
+        let msb = self.byte();
+        u16::from_le_bytes([lsb, msb])
+    }
+
+    fn immediate(&mut self) -> String {
+        format!("${:02X}", self.byte())
+    }
+
+    fn absolute(&mut self) -> String {
+        format!("${:04X}", self.word())
+    }
+
+    fn relative(&mut self) -> String {
+        let offset = self.byte() as i8;
+        let target = self.address.wrapping_add(self.len).wrapping_add(offset as u16);
+        format!("${target:04X}")
+    }
+
+    fn indexed(&mut self, index: &str) -> String {
+        let displacement = self.byte() as i8;
+        let sign = if displacement < 0 { '-' } else { '+' };
+        format!("({index}{sign}${:02X})", displacement.unsigned_abs())
+    }
+
+    fn hl(&self) -> &'static str {
+        self.index.unwrap_or("HL")
+    }
+
+    // Register operand r[i], with H, L, and (HL) replaced by their index register equivalents when
+    // executing an index-prefixed opcode. Reads the displacement byte for (IX+d) / (IY+d)
+    fn register(&mut self, i: u8) -> String {
+        match (self.index, i) {
+            (Some(index), 4) => format!("{index}H"),
+            (Some(index), 5) => format!("{index}L"),
+            (Some(index), 6) => self.indexed(index),
+            _ => REGISTERS[i as usize].into(),
+        }
+    }
+
+    fn register_pair(&self, p: u8) -> &'static str {
+        if p == 2 { self.hl() } else { REGISTER_PAIRS[p as usize] }
+    }
+
+    fn register_pair_af(&self, p: u8) -> &'static str {
+        if p == 2 { self.hl() } else { REGISTER_PAIRS_AF[p as usize] }
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn instruction(&mut self) -> String {
+        let opcode = self.byte();
+        match opcode {
+            0xCB => {
+                return match self.index {
+                    Some(index) => self.indexed_cb_instruction(index),
+                    None => self.cb_instruction(),
+                };
+            }
+            0xED => return self.ed_instruction(),
+            0xDD | 0xFD => {
+                // When multiple index prefixes are chained, only the last one takes effect
+                self.index = Some(if opcode == 0xDD { "IX" } else { "IY" });
+                return self.instruction();
+            }
+            _ => {}
+        }
+
+        let x = opcode >> 6;
+        let y = (opcode >> 3) & 7;
+        let z = opcode & 7;
+        let p = y >> 1;
+        let q = y & 1;
+
+        match (x, z) {
+            (0, 0) => match y {
+                0 => "NOP".into(),
+                1 => "EX AF, AF'".into(),
+                2 => format!("DJNZ {}", self.relative()),
+                3 => format!("JR {}", self.relative()),
+                _ => format!("JR {}, {}", CONDITIONS[(y - 4) as usize], self.relative()),
+            },
+            (0, 1) => {
+                if q == 0 {
+                    format!("LD {}, {}", self.register_pair(p), self.absolute())
+                } else {
+                    format!("ADD {}, {}", self.hl(), self.register_pair(p))
+                }
+            }
+            (0, 2) => match (q, p) {
+                (0, 0) => "LD (BC), A".into(),
+                (0, 1) => "LD (DE), A".into(),
+                (0, 2) => format!("LD ({}), {}", self.absolute(), self.hl()),
+                (0, _) => format!("LD ({}), A", self.absolute()),
+                (_, 0) => "LD A, (BC)".into(),
+                (_, 1) => "LD A, (DE)".into(),
+                (_, 2) => format!("LD {}, ({})", self.hl(), self.absolute()),
+                (_, _) => format!("LD A, ({})", self.absolute()),
+            },
+            (0, 3) => {
+                let mnemonic = if q == 0 { "INC" } else { "DEC" };
+                format!("{mnemonic} {}", self.register_pair(p))
+            }
+            (0, 4) => format!("INC {}", self.register(y)),
+            (0, 5) => format!("DEC {}", self.register(y)),
+            (0, 6) => {
+                let register = self.register(y);
+                format!("LD {register}, {}", self.immediate())
+            }
+            (0, _) => ["RLCA", "RRCA", "RLA", "RRA", "DAA", "CPL", "SCF", "CCF"][y as usize].into(),
+            (1, 6) if y == 6 => "HALT".into(),
+            (1, _) => {
+                // Instructions that use (IX+d) / (IY+d) do not also replace H and L
+                let destination = self.register(y);
+                let source = self.register(z);
+                if y == 6 || z == 6 {
+                    format!("LD {}, {}", unindexed(destination, y), unindexed(source, z))
+                } else {
+                    format!("LD {destination}, {source}")
+                }
+            }
+            (2, _) => format!("{}{}", ALU_OPERATIONS[y as usize], self.register(z)),
+            (_, 0) => format!("RET {}", CONDITIONS[y as usize]),
+            (_, 1) => match (q, p) {
+                (0, _) => format!("POP {}", self.register_pair_af(p)),
+                (_, 0) => "RET".into(),
+                (_, 1) => "EXX".into(),
+                (_, 2) => format!("JP ({})", self.hl()),
+                (_, _) => format!("LD SP, {}", self.hl()),
+            },
+            (_, 2) => format!("JP {}, {}", CONDITIONS[y as usize], self.absolute()),
+            (_, 3) => match y {
+                0 => format!("JP {}", self.absolute()),
+                2 => format!("OUT ({}), A", self.immediate()),
+                3 => format!("IN A, ({})", self.immediate()),
+                4 => format!("EX (SP), {}", self.hl()),
+                5 => "EX DE, HL".into(),
+                6 => "DI".into(),
+                _ => "EI".into(),
+            },
+            (_, 4) => format!("CALL {}, {}", CONDITIONS[y as usize], self.absolute()),
+            (_, 5) => {
+                if q == 0 {
+                    format!("PUSH {}", self.register_pair_af(p))
+                } else {
+                    format!("CALL {}", self.absolute())
+                }
+            }
+            (_, 6) => format!("{}{}", ALU_OPERATIONS[y as usize], self.immediate()),
+            (_, _) => format!("RST ${:02X}", y * 8),
+        }
+    }
+
+    fn cb_instruction(&mut self) -> String {
+        let opcode = self.byte();
+        let y = (opcode >> 3) & 7;
+        let register = REGISTERS[(opcode & 7) as usize];
+
+        match opcode >> 6 {
+            0 => format!("{} {register}", ROTATE_OPERATIONS[y as usize]),
+            1 => format!("BIT {y}, {register}"),
+            2 => format!("RES {y}, {register}"),
+            _ => format!("SET {y}, {register}"),
+        }
+    }
+
+    // DDCB / FDCB opcodes: the displacement byte comes before the opcode. Undocumented opcodes
+    // that use a register other than (HL) also copy the result into that register
+    fn indexed_cb_instruction(&mut self, index: &str) -> String {
+        let operand = self.indexed(index);
+        let opcode = self.byte();
+        let y = (opcode >> 3) & 7;
+        let z = opcode & 7;
+
+        let copy = if z == 6 { String::new() } else { format!(", {}", REGISTERS[z as usize]) };
+        match opcode >> 6 {
+            0 => format!("{} {operand}{copy}", ROTATE_OPERATIONS[y as usize]),
+            1 => format!("BIT {y}, {operand}"),
+            2 => format!("RES {y}, {operand}{copy}"),
+            _ => format!("SET {y}, {operand}{copy}"),
+        }
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn ed_instruction(&mut self) -> String {
+        // Index prefixes have no effect on ED-prefixed opcodes
+        self.index = None;
+
+        let opcode = self.byte();
+        let x = opcode >> 6;
+        let y = (opcode >> 3) & 7;
+        let z = opcode & 7;
+        let p = y >> 1;
+        let q = y & 1;
+
+        match (x, z) {
+            (1, 0) if y == 6 => "IN (C)".into(),
+            (1, 0) => format!("IN {}, (C)", REGISTERS[y as usize]),
+            (1, 1) if y == 6 => "OUT (C), 0".into(),
+            (1, 1) => format!("OUT (C), {}", REGISTERS[y as usize]),
+            (1, 2) => {
+                let mnemonic = if q == 0 { "SBC" } else { "ADC" };
+                format!("{mnemonic} HL, {}", REGISTER_PAIRS[p as usize])
+            }
+            (1, 3) => {
+                if q == 0 {
+                    format!("LD ({}), {}", self.absolute(), REGISTER_PAIRS[p as usize])
+                } else {
+                    format!("LD {}, ({})", REGISTER_PAIRS[p as usize], self.absolute())
+                }
+            }
+            (1, 4) => "NEG".into(),
+            (1, 5) => (if y == 1 { "RETI" } else { "RETN" }).into(),
+            (1, 6) => format!("IM {}", INTERRUPT_MODES[y as usize]),
+            (1, _) => ["LD I, A", "LD R, A", "LD A, I", "LD A, R", "RRD", "RLD", "NOP", "NOP"]
+                [y as usize]
+                .into(),
+            (2, 0..=3) if y >= 4 => BLOCK_INSTRUCTIONS[(y - 4) as usize][z as usize].into(),
+            _ => "NOP".into(),
+        }
+    }
+}
+
+// In LD r, r' instructions that use (IX+d) or (IY+d), the other operand is always an unprefixed
+// register
+fn unindexed(operand: String, i: u8) -> String {
+    if i == 6 { operand } else { REGISTERS[i as usize].into() }
+}
+
+/// Disassemble the instruction at `address`, returning the instruction text and its length in
+/// bytes.
+pub fn disassemble<B: BusInterface>(bus: &mut B, address: u16) -> (String, u16) {
+    let mut disassembler = Disassembler { bus, address, len: 0, index: None };
+    let text = disassembler.instruction();
+    (text, disassembler.len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::InMemoryBus;
+
+    fn disassemble_bytes(bytes: &[u8]) -> (String, u16) {
+        let mut bus = InMemoryBus::new();
+        bus.memory[..bytes.len()].copy_from_slice(bytes);
+        disassemble(&mut bus, 0)
+    }
+
+    #[test]
+    fn unprefixed() {
+        assert_eq!(disassemble_bytes(&[0x00]), ("NOP".into(), 1));
+        assert_eq!(disassemble_bytes(&[0x21, 0x34, 0x12]), ("LD HL, $1234".into(), 3));
+        assert_eq!(disassemble_bytes(&[0x36, 0x55]), ("LD (HL), $55".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x18, 0xFE]), ("JR $0000".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x20, 0x10]), ("JR NZ, $0012".into(), 2));
+        assert_eq!(disassemble_bytes(&[0x76]), ("HALT".into(), 1));
+        assert_eq!(disassemble_bytes(&[0x7E]), ("LD A, (HL)".into(), 1));
+        assert_eq!(disassemble_bytes(&[0xD3, 0xBF]), ("OUT ($BF), A".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xCD, 0x00, 0x80]), ("CALL $8000".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xFF]), ("RST $38".into(), 1));
+    }
+
+    #[test]
+    fn prefixed() {
+        assert_eq!(disassemble_bytes(&[0xCB, 0x46]), ("BIT 0, (HL)".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xED, 0xB0]), ("LDIR".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xED, 0x43, 0x00, 0xC0]), ("LD ($C000), BC".into(), 4));
+        assert_eq!(disassemble_bytes(&[0xDD, 0x21, 0x34, 0x12]), ("LD IX, $1234".into(), 4));
+        assert_eq!(disassemble_bytes(&[0xDD, 0x36, 0xFE, 0x12]), ("LD (IX-$02), $12".into(), 4));
+        assert_eq!(disassemble_bytes(&[0xFD, 0x66, 0x05]), ("LD H, (IY+$05)".into(), 3));
+        assert_eq!(disassemble_bytes(&[0xFD, 0x7C]), ("LD A, IYH".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xDD, 0xE9]), ("JP (IX)".into(), 2));
+        assert_eq!(disassemble_bytes(&[0xDD, 0xCB, 0x03, 0xC6]), ("SET 0, (IX+$03)".into(), 4));
+        assert_eq!(disassemble_bytes(&[0xFD, 0xCB, 0x03, 0x00]), ("RLC (IY+$03), B".into(), 4));
+        assert_eq!(disassemble_bytes(&[0xDD, 0xFD, 0x23]), ("INC IY".into(), 3));
+    }
+}
@@ -1,4 +1,6 @@
 mod core;
+pub mod debug;
+pub mod disassemble;
 pub mod traits;
 
 pub use crate::core::{InterruptMode, Z80};
@@ -4,19 +4,22 @@ use clap::Parser;
 use env_logger::Env;
 use gb_core::api::{GbAspectRatio, GbPalette, GbcColorCorrection};
 use genesis_core::{GenesisAspectRatio, GenesisControllerType, GenesisRegion};
+use jgenesis_common::debug::TraceRange;
 use jgenesis_common::frontend::TimingMode;
-use jgenesis_native_config::smsgg::SmsModel;
 use jgenesis_native_config::AppConfig;
+use jgenesis_native_config::smsgg::SmsModel;
+use jgenesis_native_driver::archive;
+use jgenesis_native_driver::archive::ArchiveEntry;
 use jgenesis_native_driver::config::input::{NesControllerType, SnesControllerType};
 use jgenesis_native_driver::config::{GgAspectRatio, SmsAspectRatio};
-use jgenesis_native_driver::NativeTickEffect;
+use jgenesis_native_driver::{MovieStart, NativeTickEffect, SAVE_STATE_SLOTS, SaveStateSlot};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
 use jgenesis_renderer::config::{
     FilterMode, PreprocessShader, PrescaleFactor, Scanlines, VSyncMode, WgpuBackend,
 };
 use nes_core::api::NesAspectRatio;
+use smsgg_core::{SmsControllerType, SmsRegion};
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::SmsRegion;
 use snes_core::api::SnesAspectRatio;
 use std::ffi::OsStr;
 use std::fs;
@@ -49,10 +52,27 @@ struct Args {
     #[arg(long)]
     hardware: Option<Hardware>,
 
-    /// ROM file path
+    /// ROM file path; SPC files are played back by running only the SNES audio processor, and NSF
+    /// files are played back using a synthetic NES cartridge
     #[arg(short = 'f', long)]
     file_path: String,
 
+    /// IPS / BPS / UPS patch to apply to a cartridge ROM at load time; if not set, a patch file next to the ROM with the same name is applied if one exists
+    #[arg(long)]
+    patch_file: Option<String>,
+
+    /// Cartridge ROM to load from inside a zip or gzip archive; if not set, the first file with a supported extension is loaded
+    #[arg(long)]
+    archive_entry: Option<String>,
+
+    /// Start a GDB remote debugging server on this localhost port (Genesis / Sega CD / SMS / Game Gear only)
+    #[arg(long)]
+    gdb_port: Option<u16>,
+
+    /// CPU to debug over GDB: "68000" or "Z80" for Genesis, "Main 68000", "Z80", or "Sub 68000" for Sega CD; defaults to the main 68000
+    #[arg(long)]
+    gdb_target: Option<String>,
+
     /// Override default config file path (jgenesis-config.toml)
     #[arg(long = "config")]
     config_path_override: Option<String>,
@@ -69,6 +89,58 @@ struct Args {
     #[arg(long)]
     hide_cursor_over_window: Option<bool>,
 
+    /// Directory to save screenshots to; defaults to the ROM file's directory
+    #[arg(long)]
+    screenshot_directory: Option<String>,
+
+    /// Stretch screenshots to correct for the console's pixel aspect ratio
+    #[arg(long)]
+    screenshot_aspect_ratio_correction: Option<bool>,
+
+    /// Load the save state in the given slot (0-9) immediately after launching
+    #[arg(long)]
+    load_save_state: Option<usize>,
+
+    /// Print the contents of every save state slot for the given ROM file and exit
+    #[arg(long, default_value_t)]
+    list_save_states: bool,
+
+    /// Record an input movie to the given file, starting from power-on (or from the loaded save state if --load-save-state is set)
+    #[arg(long, conflicts_with = "play_movie")]
+    record_movie: Option<PathBuf>,
+
+    /// Play back the input movie in the given file
+    #[arg(long)]
+    play_movie: Option<PathBuf>,
+
+    /// Allow movie playback to be taken over, and continue recording once playback reaches the end of the movie
+    #[arg(long, default_value_t, requires = "play_movie")]
+    movie_read_write: bool,
+
+    /// Record the emulator's raw audio output (48 KHz stereo, before audio gain) to the given WAV file
+    #[arg(long)]
+    record_wav: Option<PathBuf>,
+
+    /// Record every frame (as PNGs at native resolution) and the raw audio output (as WAV) to the given directory
+    #[arg(long)]
+    record_video: Option<PathBuf>,
+
+    /// Log PSG, YM2612, and YM2413 register writes to a VGM file (SMS / Game Gear / Genesis / Sega CD only)
+    #[arg(long)]
+    log_vgm: Option<PathBuf>,
+
+    /// Write an instruction trace of the CPU selected by --trace-cpu to the given file
+    #[arg(long)]
+    trace_log: Option<PathBuf>,
+
+    /// CPU to trace, named as in the debugger: e.g. "68000" or "Z80" for Genesis, "65816" or "SPC700" for SNES; defaults to the main CPU
+    #[arg(long)]
+    trace_cpu: Option<String>,
+
+    /// Only trace instructions that start within this range of hex addresses, e.g. 8000-FFFF; can be specified multiple times
+    #[arg(long = "trace-range")]
+    trace_ranges: Vec<TraceRange>,
+
     /// MasterSystem model (Sms2 / Sms1)
     #[arg(long, help_heading = SMSGG_OPTIONS_HEADING)]
     sms_model: Option<SmsModel>,
@@ -105,6 +177,14 @@ struct Args {
     #[arg(long, help_heading = SMSGG_OPTIONS_HEADING)]
     smsgg_overclock_z80: Option<bool>,
 
+    /// P1 Master System controller type (Gamepad / LightPhaser / Paddle / SportsPad)
+    #[arg(long, help_heading = SMSGG_OPTIONS_HEADING)]
+    sms_p1_controller_type: Option<SmsControllerType>,
+
+    /// P2 Master System controller type (Gamepad / LightPhaser / Paddle / SportsPad)
+    #[arg(long, help_heading = SMSGG_OPTIONS_HEADING)]
+    sms_p2_controller_type: Option<SmsControllerType>,
+
     /// Emulate the VDP's non-linear DAC, which tends to brighten darker colors and darken brighter colors
     #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
     emulate_non_linear_vdp_dac: Option<bool>,
@@ -133,10 +213,14 @@ struct Args {
     #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
     genesis_region: Option<GenesisRegion>,
 
-    /// P1 Genesis controller type (ThreeButton / SixButton)
+    /// P1 Genesis controller type (None / ThreeButton / SixButton / Mouse / TeamPlayer / EaFourWayPlay)
     #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
     genesis_p1_controller_type: Option<GenesisControllerType>,
 
+    /// P2 Genesis controller type (None / ThreeButton / SixButton / Mouse / Menacer / Justifier / TeamPlayer / EaFourWayPlay)
+    #[arg(long, help_heading = GENESIS_OPTIONS_HEADING)]
+    genesis_p2_controller_type: Option<GenesisControllerType>,
+
     /// Sega CD BIOS path
     #[arg(short = 'b', long, help_heading = SCD_OPTIONS_HEADING)]
     bios_path: Option<String>,
@@ -193,6 +277,10 @@ struct Args {
     #[arg(long, help_heading = NES_OPTIONS_HEADING)]
     nes_audio_60hz_hack: Option<bool>,
 
+    /// Famicom Disk System BIOS path (required to run .fds disk images)
+    #[arg(long, help_heading = NES_OPTIONS_HEADING)]
+    fds_bios_path: Option<String>,
+
     /// SNES aspect ratio (Ntsc / Pal / SquarePixels / Stretched)
     #[arg(long, help_heading = SNES_OPTIONS_HEADING)]
     snes_aspect_ratio: Option<SnesAspectRatio>,
@@ -205,7 +293,11 @@ struct Args {
     #[arg(long, help_heading = SNES_OPTIONS_HEADING)]
     gsu_overclock_factor: Option<NonZeroU64>,
 
-    /// Player 2 input device (Gamepad / SuperScope)
+    /// Player 1 input device (Gamepad / Mouse)
+    #[arg(long, help_heading = SNES_OPTIONS_HEADING)]
+    snes_p1_controller_type: Option<SnesControllerType>,
+
+    /// Player 2 input device (Gamepad / Mouse / SuperScope / SuperMultitap)
     #[arg(long, help_heading = SNES_OPTIONS_HEADING)]
     snes_p2_controller_type: Option<SnesControllerType>,
 
@@ -384,7 +476,11 @@ impl Args {
             config.nes.remove_sprite_limit = remove_sprite_limit;
         }
 
-        apply_overrides!(self, config.common, [hide_cursor_over_window]);
+        apply_overrides!(self, config.common, [
+            hide_cursor_over_window,
+            screenshot_aspect_ratio_correction,
+        ]);
+        apply_path_overrides!(self, config.common, [screenshot_directory]);
     }
 
     fn apply_smsgg_overrides(&self, config: &mut AppConfig) {
@@ -402,6 +498,11 @@ impl Args {
         if let Some(psg_version) = self.psg_version {
             config.smsgg.psg_version = Some(psg_version);
         }
+
+        apply_overrides!(self, config.inputs, [
+            sms_p1_controller_type -> smsgg_p1_type,
+            sms_p2_controller_type -> smsgg_p2_type,
+        ]);
     }
 
     fn apply_genesis_overrides(&self, config: &mut AppConfig) {
@@ -418,7 +519,10 @@ impl Args {
             config.genesis.forced_region = Some(region);
         }
 
-        apply_overrides!(self, config.inputs, [genesis_p1_controller_type -> genesis_p1_type]);
+        apply_overrides!(self, config.inputs, [
+            genesis_p1_controller_type -> genesis_p1_type,
+            genesis_p2_controller_type -> genesis_p2_type,
+        ]);
     }
 
     fn apply_sega_cd_overrides(&self, config: &mut AppConfig) {
@@ -447,6 +551,8 @@ impl Args {
         ]);
 
         apply_overrides!(self, config.inputs, [nes_p2_controller_type -> nes_p2_type]);
+
+        apply_path_overrides!(self, config.nes, [fds_bios_path]);
     }
 
     fn apply_snes_overrides(&self, config: &mut AppConfig) {
@@ -456,9 +562,10 @@ impl Args {
             gsu_overclock_factor,
         ]);
 
-        if let Some(p2_controller_type) = self.snes_p2_controller_type {
-            config.inputs.snes_p2_type = p2_controller_type;
-        }
+        apply_overrides!(self, config.inputs, [
+            snes_p1_controller_type -> snes_p1_type,
+            snes_p2_controller_type -> snes_p2_type,
+        ]);
 
         apply_path_overrides!(
             self,
@@ -544,21 +651,18 @@ fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
 
-    let hardware = args.hardware.unwrap_or_else(|| {
-        let file_ext = Path::new(&args.file_path).extension().and_then(OsStr::to_str).unwrap_or("");
-        match file_ext {
-            "sms" | "gg" => Hardware::MasterSystem,
-            "md" | "bin" => Hardware::Genesis,
-            "cue" | "chd" => Hardware::SegaCd,
-            "nes" => Hardware::Nes,
-            "sfc" | "smc" => Hardware::Snes,
-            "gb" | "gbc" => Hardware::GameBoy,
-            _ => {
-                log::warn!("Unrecognized file extension: '{file_ext}' defaulting to Genesis");
-                Hardware::Genesis
-            }
+    if args.list_save_states {
+        print_save_states(&args);
+        return Ok(());
+    }
+
+    if let Some(slot) = args.load_save_state {
+        if slot >= SAVE_STATE_SLOTS {
+            anyhow::bail!("Invalid save state slot {slot}; must be less than {SAVE_STATE_SLOTS}");
         }
-    });
+    }
+
+    let hardware = args.hardware.unwrap_or_else(|| detect_hardware(&args));
 
     log::info!("Running with hardware {hardware}");
 
@@ -590,48 +694,215 @@ fn main() -> anyhow::Result<()> {
     }
 }
 
-fn run_sms(args: Args, config: AppConfig) -> anyhow::Result<()> {
-    let mut emulator = jgenesis_native_driver::create_smsgg(config.smsgg_config(args.file_path))?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+fn detect_hardware(args: &Args) -> Hardware {
+    let file_path = Path::new(&args.file_path);
+    let file_ext = if archive::is_archive(file_path) {
+        archive_rom_entry(file_path, args.archive_entry.as_deref())
+            .and_then(|entry| entry.extension().map(str::to_ascii_lowercase))
+            .unwrap_or_default()
+    } else {
+        file_path.extension().and_then(OsStr::to_str).unwrap_or("").into()
+    };
 
-    Ok(())
+    match file_ext.as_str() {
+        "cue" | "chd" => Hardware::SegaCd,
+        "spc" => Hardware::Snes,
+        _ => cartridge_hardware_for_extension(&file_ext).unwrap_or_else(|| {
+            log::warn!("Unrecognized file extension: '{file_ext}' defaulting to Genesis");
+            Hardware::Genesis
+        }),
+    }
 }
 
-fn run_genesis(args: Args, config: AppConfig) -> anyhow::Result<()> {
-    let mut emulator =
-        jgenesis_native_driver::create_genesis(config.genesis_config(args.file_path))?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+fn cartridge_hardware_for_extension(file_ext: &str) -> Option<Hardware> {
+    match file_ext {
+        "sms" | "gg" => Some(Hardware::MasterSystem),
+        "md" | "bin" => Some(Hardware::Genesis),
+        "nes" | "nsf" | "nsfe" | "fds" => Some(Hardware::Nes),
+        "sfc" | "smc" => Some(Hardware::Snes),
+        "gb" | "gbc" => Some(Hardware::GameBoy),
+        _ => None,
+    }
+}
+
+// Only cartridge ROMs can be loaded from archives, so ignore any CD images or SPC files
+fn archive_rom_entry(path: &Path, entry_name: Option<&str>) -> Option<ArchiveEntry> {
+    let entries = archive::list_entries(path)
+        .inspect_err(|err| log::error!("Error reading archive '{}': {err}", path.display()))
+        .ok()?;
+
+    entries.into_iter().find(|entry| match entry_name {
+        Some(entry_name) => entry.name == entry_name,
+        None => entry.extension().is_some_and(|file_ext| {
+            cartridge_hardware_for_extension(&file_ext.to_ascii_lowercase()).is_some()
+        }),
+    })
+}
+
+fn print_save_states(args: &Args) {
+    // Save states are named after the archive entry for ROMs inside archives, same as when running
+    let file_path = Path::new(&args.file_path);
+    let base_path = if archive::is_archive(file_path) {
+        let Some(entry) = archive_rom_entry(file_path, args.archive_entry.as_deref()) else {
+            log::error!("No ROM found in archive '{}'", file_path.display());
+            return;
+        };
+        jgenesis_native_driver::rom_base_path(file_path, Some(&entry.name))
+    } else {
+        file_path.into()
+    };
+
+    for SaveStateSlot { slot, metadata, .. } in jgenesis_native_driver::list_save_states(base_path)
+    {
+        match metadata {
+            Some(metadata) => {
+                println!(
+                    "Slot {slot}: {} ({}), saved {} by version {}",
+                    metadata.rom_name,
+                    metadata.console,
+                    metadata.formatted_timestamp(),
+                    metadata.emulator_version
+                );
+            }
+            None => {
+                println!("Slot {slot}: <empty>");
+            }
+        }
+    }
+}
+
+// Patches and archives are only supported for cartridge ROMs
+fn check_cartridge_only_args(args: &Args, file_kind: &str) -> anyhow::Result<()> {
+    if args.patch_file.is_some() {
+        anyhow::bail!("--patch-file is not supported for {file_kind}");
+    }
+
+    if args.archive_entry.is_some() {
+        anyhow::bail!("--archive-entry is not supported for {file_kind}");
+    }
 
     Ok(())
 }
 
+macro_rules! run_emulator {
+    ($args:expr, $emulator:expr) => {{
+        let mut emulator = $emulator;
+        if let Some(slot) = $args.load_save_state {
+            emulator.select_save_state_slot(slot);
+            emulator.load_state(slot);
+        }
+
+        if let Some(path) = &$args.record_movie {
+            let start = if $args.load_save_state.is_some() {
+                MovieStart::CurrentState
+            } else {
+                MovieStart::PowerOn
+            };
+            emulator.start_movie_recording(path.clone(), start)?;
+        }
+
+        if let Some(path) = &$args.play_movie {
+            emulator.start_movie_playback(path.clone(), !$args.movie_read_write)?;
+        }
+
+        if let Some(path) = &$args.record_wav {
+            emulator.start_wav_recording(path.clone())?;
+        }
+
+        if let Some(path) = &$args.record_video {
+            emulator.start_video_recording(path.clone())?;
+        }
+
+        if let Some(path) = &$args.log_vgm {
+            emulator.start_vgm_logging(path.clone())?;
+        }
+
+        if let Some(path) = &$args.trace_log {
+            emulator.start_trace_logging(path.clone())?;
+        }
+
+        while emulator.render_frame()? != NativeTickEffect::Exit {}
+
+        Ok(())
+    }};
+}
+
+fn run_sms(args: Args, config: AppConfig) -> anyhow::Result<()> {
+    let mut smsgg_config = config.smsgg_config(args.file_path.clone());
+    smsgg_config.common.patch_path.clone_from(&args.patch_file);
+    smsgg_config.common.archive_entry.clone_from(&args.archive_entry);
+    smsgg_config.common.trace_cpu.clone_from(&args.trace_cpu);
+    smsgg_config.common.trace_ranges.clone_from(&args.trace_ranges);
+    smsgg_config.common.gdb_port = args.gdb_port;
+
+    let emulator = jgenesis_native_driver::create_smsgg(smsgg_config)?;
+    run_emulator!(args, emulator)
+}
+
+fn run_genesis(args: Args, config: AppConfig) -> anyhow::Result<()> {
+    let mut genesis_config = config.genesis_config(args.file_path.clone());
+    genesis_config.common.patch_path.clone_from(&args.patch_file);
+    genesis_config.common.archive_entry.clone_from(&args.archive_entry);
+    genesis_config.common.trace_cpu.clone_from(&args.trace_cpu);
+    genesis_config.common.trace_ranges.clone_from(&args.trace_ranges);
+    genesis_config.common.gdb_port = args.gdb_port;
+    genesis_config.common.gdb_target.clone_from(&args.gdb_target);
+
+    let emulator = jgenesis_native_driver::create_genesis(genesis_config)?;
+    run_emulator!(args, emulator)
+}
+
 fn run_sega_cd(args: Args, config: AppConfig) -> anyhow::Result<()> {
-    let mut scd_config = config.sega_cd_config(args.file_path);
-    scd_config.run_without_disc = args.scd_no_disc;
+    check_cartridge_only_args(&args, "Sega CD discs")?;
 
-    let mut emulator = jgenesis_native_driver::create_sega_cd(scd_config)?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+    let mut scd_config = config.sega_cd_config(args.file_path.clone());
+    scd_config.run_without_disc = args.scd_no_disc;
+    scd_config.genesis.common.trace_cpu.clone_from(&args.trace_cpu);
+    scd_config.genesis.common.trace_ranges.clone_from(&args.trace_ranges);
+    scd_config.genesis.common.gdb_port = args.gdb_port;
+    scd_config.genesis.common.gdb_target.clone_from(&args.gdb_target);
 
-    Ok(())
+    let emulator = jgenesis_native_driver::create_sega_cd(scd_config)?;
+    run_emulator!(args, emulator)
 }
 
 fn run_nes(args: Args, config: AppConfig) -> anyhow::Result<()> {
-    let mut emulator = jgenesis_native_driver::create_nes(config.nes_config(args.file_path))?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
-
-    Ok(())
+    let mut nes_config = config.nes_config(args.file_path.clone());
+    nes_config.common.patch_path.clone_from(&args.patch_file);
+    nes_config.common.archive_entry.clone_from(&args.archive_entry);
+    nes_config.common.trace_cpu.clone_from(&args.trace_cpu);
+    nes_config.common.trace_ranges.clone_from(&args.trace_ranges);
+
+    let emulator = jgenesis_native_driver::create_nes(nes_config)?;
+    run_emulator!(args, emulator)
 }
 
 fn run_snes(args: Args, config: AppConfig) -> anyhow::Result<()> {
-    let mut emulator = jgenesis_native_driver::create_snes(config.snes_config(args.file_path))?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
+    let mut snes_config = config.snes_config(args.file_path.clone());
+    snes_config.common.trace_cpu.clone_from(&args.trace_cpu);
+    snes_config.common.trace_ranges.clone_from(&args.trace_ranges);
 
-    Ok(())
+    if Path::new(&args.file_path).extension() == Some(OsStr::new("spc")) {
+        check_cartridge_only_args(&args, "SPC files")?;
+
+        let emulator = jgenesis_native_driver::create_spc_player(snes_config)?;
+        return run_emulator!(args, emulator);
+    }
+
+    snes_config.common.patch_path.clone_from(&args.patch_file);
+    snes_config.common.archive_entry.clone_from(&args.archive_entry);
+
+    let emulator = jgenesis_native_driver::create_snes(snes_config)?;
+    run_emulator!(args, emulator)
 }
 
 fn run_gb(args: Args, config: AppConfig) -> anyhow::Result<()> {
-    let mut emulator = jgenesis_native_driver::create_gb(config.gb_config(args.file_path))?;
-    while emulator.render_frame()? != NativeTickEffect::Exit {}
-
-    Ok(())
+    let mut gb_config = config.gb_config(args.file_path.clone());
+    gb_config.common.patch_path.clone_from(&args.patch_file);
+    gb_config.common.archive_entry.clone_from(&args.archive_entry);
+    gb_config.common.trace_cpu.clone_from(&args.trace_cpu);
+    gb_config.common.trace_ranges.clone_from(&args.trace_ranges);
+
+    let emulator = jgenesis_native_driver::create_gb(gb_config)?;
+    run_emulator!(args, emulator)
 }
@@ -1,15 +1,21 @@
+mod archives;
+mod cheats;
 mod common;
 mod gb;
 mod genesis;
 mod input;
 mod nes;
 mod romlist;
+mod savestates;
 mod smsgg;
 mod snes;
 
+use crate::app::archives::ArchivePrompt;
+use crate::app::cheats::CheatList;
 use crate::app::input::{GenericButton, InputAppConfigExt};
 use crate::app::nes::OverscanState;
 use crate::app::romlist::{Console, RomMetadata};
+use crate::app::savestates::SaveStateList;
 use crate::emuthread;
 use crate::emuthread::{EmuThreadCommand, EmuThreadHandle, EmuThreadStatus};
 use eframe::Frame;
@@ -20,6 +26,7 @@ use egui::{
 };
 use egui_extras::{Column, TableBuilder};
 use jgenesis_native_config::{AppConfig, ListFilters};
+use jgenesis_native_driver::archive;
 use jgenesis_renderer::config::Scanlines;
 use rfd::FileDialog;
 use std::cell::RefCell;
@@ -96,8 +103,10 @@ enum OpenWindow {
     SnesAudio,
     SmsGgKeyboard,
     SmsGgGamepad,
+    SmsGgPeripherals,
     GenesisKeyboard,
     GenesisGamepad,
+    GenesisPeripherals,
     NesKeyboard,
     NesGamepad,
     NesPeripherals,
@@ -107,11 +116,16 @@ enum OpenWindow {
     GameBoyKeyboard,
     GameBoyGamepad,
     Hotkeys,
+    SaveStates,
+    Cheats,
+    ArchiveEntry,
     About,
 }
 
 struct AppState {
     current_file_path: String,
+    // Save states and other per-game files are named after this path; see rom_base_path()
+    current_rom_base_path: PathBuf,
     open_windows: HashSet<OpenWindow>,
     error_window_open: bool,
     prescale_factor_text: String,
@@ -139,6 +153,9 @@ struct AppState {
     title_match_lowercase: Rc<str>,
     rendered_first_frame: bool,
     close_on_emulator_exit: bool,
+    save_states: SaveStateList,
+    cheats: CheatList,
+    archive_prompt: Option<ArchivePrompt>,
 }
 
 impl AppState {
@@ -147,6 +164,7 @@ impl AppState {
         let recent_open_list = romlist::from_recent_opens(&config.recent_opens);
         Self {
             current_file_path: String::new(),
+            current_rom_base_path: PathBuf::new(),
             open_windows: HashSet::new(),
             error_window_open: false,
             prescale_factor_text: config.common.prescale_factor.get().to_string(),
@@ -174,6 +192,9 @@ impl AppState {
             recent_open_list,
             rendered_first_frame: false,
             close_on_emulator_exit: false,
+            save_states: SaveStateList::default(),
+            cheats: CheatList::default(),
+            archive_prompt: None,
         }
     }
 }
@@ -259,7 +280,10 @@ impl App {
 
         let mut file_dialog = FileDialog::new().add_filter(
             "Supported ROM files",
-            &["sms", "gg", "md", "bin", "cue", "chd", "nes", "sfc", "smc", "gb", "gbc"],
+            &[
+                "sms", "gg", "md", "bin", "cue", "chd", "nes", "nsf", "nsfe", "fds", "sfc", "smc",
+                "gb", "gbc", "zip", "gz",
+            ],
         );
         if let Some(dir) = self.config.rom_search_dirs.first() {
             file_dialog = file_dialog.set_directory(Path::new(dir));
@@ -267,11 +291,22 @@ impl App {
         let Some(path) = file_dialog.pick_file() else { return };
 
         let Some(path_str) = path.to_str().map(String::from) else { return };
-        self.launch_emulator(path_str);
+        self.launch_emulator(path_str, None);
     }
 
-    fn launch_emulator(&mut self, path: String) {
+    fn launch_emulator(&mut self, path: String, archive_entry: Option<String>) {
+        let archive_entry = match archive_entry {
+            Some(archive_entry) => Some(archive_entry),
+            None if archive::is_archive(&path) => {
+                let Some(archive_entry) = self.resolve_archive_entry(&path) else { return };
+                Some(archive_entry)
+            }
+            None => None,
+        };
+
         self.state.current_file_path.clone_from(&path);
+        self.state.current_rom_base_path =
+            jgenesis_native_driver::rom_base_path(&path, archive_entry.as_deref());
 
         // Update Open Recent contents
         self.config.recent_opens.retain(|recent_open_path| recent_open_path != &path);
@@ -279,17 +314,25 @@ impl App {
         self.config.recent_opens.truncate(10);
         self.state.recent_open_list = romlist::from_recent_opens(&self.config.recent_opens);
 
-        match Path::new(&path).extension().and_then(OsStr::to_str) {
+        // For archives, the console is determined by the extension of the file inside the archive
+        let extension_path = archive_entry.clone().unwrap_or_else(|| path.clone());
+        let extension = Path::new(&extension_path)
+            .extension()
+            .and_then(OsStr::to_str)
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
             Some("sms" | "gg") => {
                 self.emu_thread.stop_emulator_if_running();
 
-                let config = self.config.smsgg_config(path);
+                let mut config = self.config.smsgg_config(path);
+                config.common.archive_entry = archive_entry;
                 self.emu_thread.send(EmuThreadCommand::RunSms(config));
             }
             Some("md" | "bin") => {
                 self.emu_thread.stop_emulator_if_running();
 
-                let config = self.config.genesis_config(path);
+                let mut config = self.config.genesis_config(path);
+                config.common.archive_entry = archive_entry;
                 self.emu_thread.send(EmuThreadCommand::RunGenesis(config));
             }
             Some("cue" | "chd") => {
@@ -298,22 +341,25 @@ impl App {
                 let config = self.config.sega_cd_config(path);
                 self.emu_thread.send(EmuThreadCommand::RunSegaCd(config));
             }
-            Some("nes") => {
+            Some("nes" | "nsf" | "nsfe" | "fds") => {
                 self.emu_thread.stop_emulator_if_running();
 
-                let config = self.config.nes_config(path);
+                let mut config = self.config.nes_config(path);
+                config.common.archive_entry = archive_entry;
                 self.emu_thread.send(EmuThreadCommand::RunNes(config));
             }
             Some("sfc" | "smc") => {
                 self.emu_thread.stop_emulator_if_running();
 
-                let config = self.config.snes_config(path);
+                let mut config = self.config.snes_config(path);
+                config.common.archive_entry = archive_entry;
                 self.emu_thread.send(EmuThreadCommand::RunSnes(config));
             }
             Some("gb" | "gbc") => {
                 self.emu_thread.stop_emulator_if_running();
 
-                let config = self.config.gb_config(path);
+                let mut config = self.config.gb_config(path);
+                config.common.archive_entry = archive_entry;
                 self.emu_thread.send(EmuThreadCommand::RunGameBoy(config));
             }
             Some(extension) => {
@@ -321,12 +367,39 @@ impl App {
                 self.emu_thread.clear_waiting_for_first_command();
             }
             None => {
-                log::error!("Unable to determine file extension of path: {path}");
+                log::error!("Unable to determine file extension of path: {extension_path}");
                 self.emu_thread.clear_waiting_for_first_command();
             }
         }
     }
 
+    /// Determine which file to load from an archive. If the archive contains more than one ROM,
+    /// this opens a window prompting the user to pick one and returns `None`.
+    fn resolve_archive_entry(&mut self, path: &str) -> Option<String> {
+        let roms = match romlist::list_archive_roms(Path::new(path)) {
+            Ok(roms) => roms,
+            Err(err) => {
+                log::error!("Error reading archive '{path}': {err}");
+                self.emu_thread.clear_waiting_for_first_command();
+                return None;
+            }
+        };
+
+        match roms.len() {
+            0 => {
+                log::error!("Archive does not contain any supported ROM files: {path}");
+                self.emu_thread.clear_waiting_for_first_command();
+                None
+            }
+            1 => roms.into_iter().next().map(|(entry, _)| entry.name),
+            _ => {
+                self.state.archive_prompt = Some(ArchivePrompt::new(path.into(), roms));
+                self.state.open_windows.insert(OpenWindow::ArchiveEntry);
+                None
+            }
+        }
+    }
+
     fn add_rom_search_directory(&mut self) {
         let Some(dir) = FileDialog::new().pick_folder() else { return };
         let Some(dir) = dir.to_str() else { return };
@@ -368,6 +441,41 @@ impl App {
                     self.add_rom_search_directory();
                 }
             });
+
+            ui.add_space(5.0);
+
+            ui.group(|ui| {
+                ui.label("Screenshots");
+
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    let directory_label = self
+                        .config
+                        .common
+                        .screenshot_directory
+                        .as_deref()
+                        .unwrap_or("<Same directory as ROM file>");
+                    ui.label(directory_label);
+
+                    if ui.button("Change").clicked() {
+                        if let Some(dir) = FileDialog::new().pick_folder() {
+                            if let Some(dir) = dir.to_str() {
+                                self.config.common.screenshot_directory = Some(dir.into());
+                            }
+                        }
+                    }
+
+                    if ui.button("Reset").clicked() {
+                        self.config.common.screenshot_directory = None;
+                    }
+                });
+
+                ui.checkbox(
+                    &mut self.config.common.screenshot_aspect_ratio_correction,
+                    "Apply pixel aspect ratio correction to screenshots",
+                );
+            });
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::Interface);
@@ -416,7 +524,10 @@ impl App {
                         ui.menu_button("Open Recent", |ui| {
                             for recent_open in self.state.recent_open_list.clone() {
                                 if ui.button(&recent_open.file_name_no_ext).clicked() {
-                                    self.launch_emulator(recent_open.full_path);
+                                    self.launch_emulator(
+                                        recent_open.full_path,
+                                        recent_open.archive_entry,
+                                    );
                                     ui.close_menu();
                                 }
 
@@ -442,7 +553,7 @@ impl App {
                 ui.menu_button("Emulation", |ui| {
                     ui.set_enabled(self.emu_thread.status().is_running());
 
-                    if ui.button("Open Memory Viewer").clicked() {
+                    if ui.button("Open Debugger").clicked() {
                         self.emu_thread.send(EmuThreadCommand::OpenMemoryViewer);
                         ui.close_menu();
                     }
@@ -466,6 +577,52 @@ impl App {
 
                     ui.add_space(15.0);
 
+                    self.render_save_state_menus(ctx, ui);
+
+                    if ui.button("Cheats...").clicked() {
+                        self.state.open_windows.insert(OpenWindow::Cheats);
+                        ui.close_menu();
+                    }
+
+                    ui.add_space(15.0);
+
+                    ui.menu_button("Movies", |ui| {
+                        if ui.button("Record Movie...").clicked() {
+                            if let Some(path) =
+                                FileDialog::new().add_filter("jgm", &["jgm"]).save_file()
+                            {
+                                self.emu_thread.send(EmuThreadCommand::StartMovieRecording(path));
+                            }
+
+                            ui.close_menu();
+                        }
+
+                        for (label, read_only) in [
+                            ("Play Movie (Read-Only)...", true),
+                            ("Play Movie (Read-Write)...", false),
+                        ] {
+                            if ui.button(label).clicked() {
+                                if let Some(path) =
+                                    FileDialog::new().add_filter("jgm", &["jgm"]).pick_file()
+                                {
+                                    self.emu_thread.send(EmuThreadCommand::StartMoviePlayback {
+                                        path,
+                                        read_only,
+                                    });
+                                }
+
+                                ui.close_menu();
+                            }
+                        }
+
+                        if ui.button("Stop Movie").clicked() {
+                            self.emu_thread.send(EmuThreadCommand::StopMovie);
+                            ui.close_menu();
+                        }
+                    });
+
+                    ui.add_space(15.0);
+
                     ui.add_enabled_ui(
                         self.emu_thread.status() == EmuThreadStatus::RunningSegaCd,
                         |ui| {
@@ -590,6 +747,11 @@ impl App {
                             self.state.open_windows.insert(OpenWindow::SmsGgGamepad);
                             ui.close_menu();
                         }
+
+                        if ui.button("Peripherals").clicked() {
+                            self.state.open_windows.insert(OpenWindow::SmsGgPeripherals);
+                            ui.close_menu();
+                        }
                     });
 
                     ui.add_space(5.0);
@@ -604,6 +766,11 @@ impl App {
                             self.state.open_windows.insert(OpenWindow::GenesisGamepad);
                             ui.close_menu();
                         }
+
+                        if ui.button("Peripherals").clicked() {
+                            self.state.open_windows.insert(OpenWindow::GenesisPeripherals);
+                            ui.close_menu();
+                        }
                     });
 
                     ui.add_space(5.0);
@@ -737,7 +904,10 @@ impl App {
                                         .clicked()
                                     {
                                         self.emu_thread.stop_emulator_if_running();
-                                        self.launch_emulator(metadata.full_path.clone());
+                                        self.launch_emulator(
+                                            metadata.full_path.clone(),
+                                            metadata.archive_entry.clone(),
+                                        );
                                     }
                                 });
 
@@ -854,7 +1024,7 @@ impl eframe::App for App {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
         if self.state.rendered_first_frame {
             if let Some(startup_file_path) = self.startup_file_path.take() {
-                self.launch_emulator(startup_file_path);
+                self.launch_emulator(startup_file_path, None);
                 self.state.close_on_emulator_exit = true;
             }
         }
@@ -889,8 +1059,10 @@ impl eframe::App for App {
                 OpenWindow::SnesAudio => self.render_snes_audio_settings(ctx),
                 OpenWindow::SmsGgKeyboard => self.render_smsgg_keyboard_settings(ctx),
                 OpenWindow::SmsGgGamepad => self.render_smsgg_gamepad_settings(ctx),
+                OpenWindow::SmsGgPeripherals => self.render_smsgg_peripheral_settings(ctx),
                 OpenWindow::GenesisKeyboard => self.render_genesis_keyboard_settings(ctx),
                 OpenWindow::GenesisGamepad => self.render_genesis_gamepad_settings(ctx),
+                OpenWindow::GenesisPeripherals => self.render_genesis_peripheral_settings(ctx),
                 OpenWindow::NesKeyboard => self.render_nes_keyboard_settings(ctx),
                 OpenWindow::NesGamepad => self.render_nes_joystick_settings(ctx),
                 OpenWindow::NesPeripherals => self.render_nes_peripheral_settings(ctx),
@@ -900,6 +1072,9 @@ impl eframe::App for App {
                 OpenWindow::GameBoyKeyboard => self.render_gb_keyboard_settings(ctx),
                 OpenWindow::GameBoyGamepad => self.render_gb_joystick_settings(ctx),
                 OpenWindow::Hotkeys => self.render_hotkey_settings(ctx),
+                OpenWindow::SaveStates => self.render_save_states_window(ctx),
+                OpenWindow::Cheats => self.render_cheats_window(ctx),
+                OpenWindow::ArchiveEntry => self.render_archive_prompt(ctx),
                 OpenWindow::About => self.render_about(ctx),
             }
         }
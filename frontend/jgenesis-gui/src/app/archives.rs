@@ -0,0 +1,55 @@
+use crate::app::romlist::Console;
+use crate::app::{App, OpenWindow};
+use egui::{Context, Grid, Window};
+use jgenesis_native_driver::archive::ArchiveEntry;
+use std::path::Path;
+
+pub(super) struct ArchivePrompt {
+    path: String,
+    roms: Vec<(ArchiveEntry, Console)>,
+}
+
+impl ArchivePrompt {
+    pub(super) fn new(path: String, roms: Vec<(ArchiveEntry, Console)>) -> Self {
+        Self { path, roms }
+    }
+}
+
+impl App {
+    pub(super) fn render_archive_prompt(&mut self, ctx: &Context) {
+        let Some(prompt) = &self.state.archive_prompt else {
+            self.state.open_windows.remove(&OpenWindow::ArchiveEntry);
+            return;
+        };
+
+        let mut selected = None;
+        let mut open = true;
+        Window::new("Select ROM").open(&mut open).resizable(false).show(ctx, |ui| {
+            let file_name =
+                Path::new(&prompt.path).file_name().unwrap_or_default().to_string_lossy();
+            ui.label(format!("'{file_name}' contains multiple ROM files"));
+
+            ui.add_space(10.0);
+
+            Grid::new("archive_prompt_grid").num_columns(2).spacing([10.0, 5.0]).show(ui, |ui| {
+                for (entry, console) in &prompt.roms {
+                    if ui.button(&entry.name).clicked() {
+                        selected = Some(entry.name.clone());
+                    }
+                    ui.label(console.to_str());
+                    ui.end_row();
+                }
+            });
+        });
+
+        if !open || selected.is_some() {
+            self.state.open_windows.remove(&OpenWindow::ArchiveEntry);
+            let prompt = self.state.archive_prompt.take();
+
+            match (prompt, selected) {
+                (Some(prompt), Some(entry)) => self.launch_emulator(prompt.path, Some(entry)),
+                _ => self.emu_thread.clear_waiting_for_first_command(),
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::app::{App, OpenWindow};
+use crate::emuthread::{EmuThreadCommand, EmuThreadStatus};
+use egui::{Color32, Context, Grid, TextEdit, Window};
+use jgenesis_common::cheats::{CheatPatch, CheatResult};
+use jgenesis_native_driver::CheatEntry;
+use std::path::{Path, PathBuf};
+
+type DecodeFn = fn(&str) -> CheatResult<Vec<CheatPatch>>;
+
+fn decode_fn(status: EmuThreadStatus) -> Option<DecodeFn> {
+    match status {
+        EmuThreadStatus::RunningSmsGg => Some(smsgg_core::cheats::decode_cheat),
+        EmuThreadStatus::RunningGenesis | EmuThreadStatus::RunningSegaCd => {
+            Some(genesis_core::cheats::decode_cheat)
+        }
+        EmuThreadStatus::RunningNes => Some(nes_core::cheats::decode_cheat),
+        EmuThreadStatus::RunningSnes => Some(snes_core::cheats::decode_cheat),
+        EmuThreadStatus::RunningGameBoy => Some(gb_core::cheats::decode_cheat),
+        EmuThreadStatus::Idle | EmuThreadStatus::WaitingForFirstCommand => None,
+    }
+}
+
+#[derive(Default)]
+pub(super) struct CheatList {
+    rom_path: PathBuf,
+    file_path: PathBuf,
+    entries: Vec<CheatEntry>,
+    new_code: String,
+    new_description: String,
+    error: Option<String>,
+}
+
+impl CheatList {
+    fn reload_if_changed(&mut self, rom_path: &Path) {
+        if self.rom_path == rom_path {
+            return;
+        }
+
+        self.rom_path = rom_path.into();
+        self.file_path = jgenesis_native_driver::cheat_file_path(
+            jgenesis_native_config::cheat_directory(),
+            rom_path,
+        );
+        self.entries = jgenesis_native_driver::load_cheats(&self.file_path).unwrap_or_else(|err| {
+            log::error!("{err}");
+            vec![]
+        });
+        self.error = None;
+    }
+
+    fn save(&self) {
+        if let Err(err) = jgenesis_native_driver::save_cheats(&self.file_path, &self.entries) {
+            log::error!("{err}");
+        }
+    }
+}
+
+impl App {
+    pub(super) fn render_cheats_window(&mut self, ctx: &Context) {
+        self.state.cheats.reload_if_changed(&self.state.current_rom_base_path);
+
+        let status = self.emu_thread.status();
+        let decode_fn = decode_fn(status);
+
+        let mut changed = false;
+        let mut open = true;
+        Window::new("Cheats").open(&mut open).resizable(false).show(ctx, |ui| {
+            let Some(decode_fn) = decode_fn else {
+                ui.label("Cheats can only be edited while a game is running");
+                return;
+            };
+
+            let cheats = &mut self.state.cheats;
+
+            let mut remove_idx = None;
+            Grid::new("cheats_grid").num_columns(4).spacing([10.0, 5.0]).show(ui, |ui| {
+                for (i, entry) in cheats.entries.iter_mut().enumerate() {
+                    changed |= ui.checkbox(&mut entry.enabled, "").changed();
+                    ui.monospace(&entry.code);
+                    ui.label(&entry.description);
+                    if ui.button("Remove").clicked() {
+                        remove_idx = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if let Some(i) = remove_idx {
+                cheats.entries.remove(i);
+                changed = true;
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut cheats.new_code)
+                        .hint_text("Code")
+                        .desired_width(120.0),
+                );
+                ui.add(
+                    TextEdit::singleline(&mut cheats.new_description)
+                        .hint_text("Description")
+                        .desired_width(200.0),
+                );
+
+                if ui.button("Add").clicked() {
+                    let code = cheats.new_code.trim().to_string();
+                    match decode_fn(&code) {
+                        Ok(_) => {
+                            cheats.entries.push(CheatEntry {
+                                code,
+                                description: cheats.new_description.trim().into(),
+                                enabled: true,
+                            });
+                            cheats.new_code.clear();
+                            cheats.new_description.clear();
+                            cheats.error = None;
+                            changed = true;
+                        }
+                        Err(err) => {
+                            cheats.error = Some(err.to_string());
+                        }
+                    }
+                }
+            });
+
+            if let Some(error) = &cheats.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::Cheats);
+        }
+
+        if changed {
+            self.state.cheats.save();
+            self.emu_thread.send(EmuThreadCommand::UpdateCheats(self.state.cheats.entries.clone()));
+        }
+    }
+}
@@ -2,8 +2,10 @@ use crate::app::{App, NumericTextEdit, OpenWindow};
 use crate::emuthread::{EmuThreadCommand, GenericInput, InputType};
 use egui::{Color32, Context, Grid, Ui, Window};
 use gb_core::inputs::GameBoyButton;
-use genesis_core::input::GenesisButton;
-use genesis_core::GenesisControllerType;
+use genesis_core::input::{
+    GenesisButton, GenesisControllerButton, GenesisMouseButton, LightGunButton,
+};
+use genesis_core::{GenesisControllerType, GenesisPadType};
 use jgenesis_common::input::Player;
 use jgenesis_native_config::input::InputAppConfig;
 use jgenesis_native_driver::config::input::{
@@ -12,8 +14,11 @@ use jgenesis_native_driver::config::input::{
 };
 use jgenesis_native_driver::input::Hotkey;
 use nes_core::input::NesButton;
-use smsgg_core::SmsGgButton;
-use snes_core::input::{SnesButton, SnesControllerButton, SuperScopeButton};
+use smsgg_core::{
+    LightPhaserButton, PaddleButton, SmsControllerType, SmsGgButton, SmsGgControllerButton,
+    SportsPadButton,
+};
+use snes_core::input::{SnesButton, SnesControllerButton, SnesMouseButton, SuperScopeButton};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GenericButton {
@@ -57,24 +62,49 @@ pub trait InputAppConfigExt {
 impl InputAppConfigExt for InputAppConfig {
     fn set_input(&mut self, input: GenericInput, button: GenericButton) {
         match button {
-            GenericButton::SmsGg(button, player) => {
-                set_input(
+            GenericButton::SmsGg(button, player) => match button {
+                SmsGgButton::Controller(button) => set_input(
                     input,
                     button,
                     player,
                     &mut self.smsgg_keyboard,
                     &mut self.smsgg_joystick,
-                );
-            }
-            GenericButton::Genesis(button, player) => {
-                set_input(
+                ),
+                SmsGgButton::LightPhaser(button) => {
+                    if let GenericInput::KeyboardOrMouse(input) = input {
+                        self.smsgg_light_phaser.set_button(button, Some(input));
+                    }
+                }
+                SmsGgButton::Paddle(button) => {
+                    if let GenericInput::KeyboardOrMouse(input) = input {
+                        self.smsgg_paddle.set_button(button, Some(input));
+                    }
+                }
+                SmsGgButton::SportsPad(button) => {
+                    if let GenericInput::KeyboardOrMouse(input) = input {
+                        self.smsgg_sports_pad.set_button(button, Some(input));
+                    }
+                }
+            },
+            GenericButton::Genesis(button, player) => match button {
+                GenesisButton::Controller(button) => set_input(
                     input,
                     button,
                     player,
                     &mut self.genesis_keyboard,
                     &mut self.genesis_joystick,
-                );
-            }
+                ),
+                GenesisButton::Mouse(button) => {
+                    if let GenericInput::KeyboardOrMouse(input) = input {
+                        self.genesis_mouse.set_button(button, Some(input));
+                    }
+                }
+                GenesisButton::LightGun(button) => {
+                    if let GenericInput::KeyboardOrMouse(input) = input {
+                        self.genesis_light_gun.set_button(button, Some(input));
+                    }
+                }
+            },
             GenericButton::Nes(button, player) => match &input {
                 GenericInput::KeyboardOrMouse(key_or_mouse_input) => {
                     self.nes_zapper.set_input(button, Some(key_or_mouse_input.clone()));
@@ -103,6 +133,11 @@ impl InputAppConfigExt for InputAppConfig {
                             self.snes_super_scope.set_button(button, input);
                         }
                     }
+                    SnesButton::Mouse(button) => {
+                        if let GenericInput::KeyboardOrMouse(input) = input {
+                            self.snes_mouse.set_button(button, Some(input));
+                        }
+                    }
                 };
             }
             GenericButton::GameBoy(button) => {
@@ -130,6 +165,12 @@ impl InputAppConfigExt for InputAppConfig {
             Hotkey::LoadState => {
                 self.hotkeys.load_state = Some(input);
             }
+            Hotkey::NextSaveStateSlot => {
+                self.hotkeys.next_save_state_slot = Some(input);
+            }
+            Hotkey::PrevSaveStateSlot => {
+                self.hotkeys.prev_save_state_slot = Some(input);
+            }
             Hotkey::SoftReset => {
                 self.hotkeys.soft_reset = Some(input);
             }
@@ -151,6 +192,39 @@ impl InputAppConfigExt for InputAppConfig {
             Hotkey::OpenDebugger => {
                 self.hotkeys.open_debugger = Some(input);
             }
+            Hotkey::ToggleMovieRecording => {
+                self.hotkeys.toggle_movie_recording = Some(input);
+            }
+            Hotkey::ToggleMoviePlayback => {
+                self.hotkeys.toggle_movie_playback = Some(input);
+            }
+            Hotkey::ToggleMovieReadOnly => {
+                self.hotkeys.toggle_movie_read_only = Some(input);
+            }
+            Hotkey::Screenshot => {
+                self.hotkeys.screenshot = Some(input);
+            }
+            Hotkey::ToggleAudioRecording => {
+                self.hotkeys.toggle_audio_recording = Some(input);
+            }
+            Hotkey::ToggleVideoRecording => {
+                self.hotkeys.toggle_video_recording = Some(input);
+            }
+            Hotkey::ToggleVgmLogging => {
+                self.hotkeys.toggle_vgm_logging = Some(input);
+            }
+            Hotkey::ExportSpc => {
+                self.hotkeys.export_spc = Some(input);
+            }
+            Hotkey::SwitchFdsDiskSide => {
+                self.hotkeys.switch_fds_disk_side = Some(input);
+            }
+            Hotkey::ToggleCheats => {
+                self.hotkeys.toggle_cheats = Some(input);
+            }
+            Hotkey::ToggleTraceLogging => {
+                self.hotkeys.toggle_trace_logging = Some(input);
+            }
         }
     }
 }
@@ -170,8 +244,8 @@ impl App {
                         ui.heading(heading);
                         ui.end_row();
 
-                        for button in SmsGgButton::ALL {
-                            if button == SmsGgButton::Pause {
+                        for button in SmsGgControllerButton::ALL {
+                            if button == SmsGgControllerButton::Pause {
                                 continue;
                             }
 
@@ -184,7 +258,7 @@ impl App {
                             self.keyboard_input_button(
                                 current_value,
                                 &button.to_string(),
-                                GenericButton::SmsGg(button, player),
+                                GenericButton::SmsGg(SmsGgButton::Controller(button), player),
                                 ui,
                             );
                         }
@@ -200,7 +274,10 @@ impl App {
                 self.keyboard_input_button(
                     self.config.inputs.smsgg_keyboard.pause.clone(),
                     "Start/Pause",
-                    GenericButton::SmsGg(SmsGgButton::Pause, Player::One),
+                    GenericButton::SmsGg(
+                        SmsGgButton::Controller(SmsGgControllerButton::Pause),
+                        Player::One,
+                    ),
                     ui,
                 );
             });
@@ -224,8 +301,8 @@ impl App {
                         ui.heading(heading);
                         ui.end_row();
 
-                        for button in SmsGgButton::ALL {
-                            if button == SmsGgButton::Pause {
+                        for button in SmsGgControllerButton::ALL {
+                            if button == SmsGgControllerButton::Pause {
                                 continue;
                             }
 
@@ -238,7 +315,7 @@ impl App {
                             self.gamepad_input_button(
                                 current_value,
                                 &button.to_string(),
-                                GenericButton::SmsGg(button, player),
+                                GenericButton::SmsGg(SmsGgButton::Controller(button), player),
                                 ui,
                             );
                         }
@@ -254,7 +331,10 @@ impl App {
                 self.gamepad_input_button(
                     self.config.inputs.smsgg_joystick.pause.clone(),
                     "Start/Pause",
-                    GenericButton::SmsGg(SmsGgButton::Pause, Player::One),
+                    GenericButton::SmsGg(
+                        SmsGgButton::Controller(SmsGgControllerButton::Pause),
+                        Player::One,
+                    ),
                     ui,
                 );
             });
@@ -267,38 +347,90 @@ impl App {
         }
     }
 
+    pub(super) fn render_smsgg_peripheral_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        Window::new("SMS Peripheral Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.set_enabled(self.state.waiting_for_input.is_none());
+
+            self.smsgg_controller_type_input("Player 1 controller", Player::One, ui);
+            self.smsgg_controller_type_input("Player 2 controller", Player::Two, ui);
+
+            ui.add_space(10.0);
+
+            ui.heading("Light Phaser");
+
+            Grid::new("smsgg_light_phaser_grid").show(ui, |ui| {
+                let current_value = self
+                    .config
+                    .inputs
+                    .smsgg_light_phaser
+                    .get_button(LightPhaserButton::Trigger)
+                    .cloned();
+                self.smsgg_peripheral_button(
+                    current_value,
+                    "Trigger",
+                    SmsGgButton::LightPhaser(LightPhaserButton::Trigger),
+                    ui,
+                );
+            });
+
+            ui.add_space(10.0);
+
+            ui.heading("Paddle Control");
+
+            Grid::new("smsgg_paddle_grid").show(ui, |ui| {
+                let current_value =
+                    self.config.inputs.smsgg_paddle.get_button(PaddleButton::Button).cloned();
+                self.smsgg_peripheral_button(
+                    current_value,
+                    "Button",
+                    SmsGgButton::Paddle(PaddleButton::Button),
+                    ui,
+                );
+            });
+
+            ui.add_space(10.0);
+
+            ui.heading("Sports Pad");
+
+            Grid::new("smsgg_sports_pad_grid").show(ui, |ui| {
+                for (label, button) in
+                    [("Button 1", SportsPadButton::Button1), ("Button 2", SportsPadButton::Button2)]
+                {
+                    let current_value =
+                        self.config.inputs.smsgg_sports_pad.get_button(button).cloned();
+                    self.smsgg_peripheral_button(
+                        current_value,
+                        label,
+                        SmsGgButton::SportsPad(button),
+                        ui,
+                    );
+                }
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::SmsGgPeripherals);
+        }
+    }
+
     pub(super) fn render_genesis_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
         Window::new("Genesis Keyboard Settings").open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("genesis_keyboard_grid").show(ui, |ui| {
-                for (grid_id, heading, player) in [
-                    ("genesis_p1_keyboard_grid", "Player 1", Player::One),
-                    ("genesis_p2_keyboard_grid", "Player 2", Player::Two),
-                ] {
-                    Grid::new(grid_id).show(ui, |ui| {
-                        ui.heading(heading);
-                        ui.end_row();
+                self.genesis_player_grids(&[Player::One, Player::Two], InputType::Keyboard, ui);
+            });
 
-                        for button in GenesisButton::ALL {
-                            let current_value = self
-                                .config
-                                .inputs
-                                .genesis_keyboard
-                                .get_input(button, player)
-                                .cloned();
-                            self.keyboard_input_button(
-                                current_value,
-                                &button.to_string(),
-                                GenericButton::Genesis(button, player),
-                                ui,
-                            );
-                        }
-                    });
+            ui.add_space(10.0);
 
-                    ui.add_space(50.0);
-                }
+            ui.collapsing("Multitap players", |ui| {
+                Grid::new("genesis_multitap_keyboard_grid").show(ui, |ui| {
+                    self.genesis_player_grids(&Player::ALL[2..5], InputType::Keyboard, ui);
+                    ui.end_row();
+
+                    self.genesis_player_grids(&Player::ALL[5..], InputType::Keyboard, ui);
+                });
             });
 
             ui.add_space(30.0);
@@ -317,32 +449,18 @@ impl App {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("genesis_gamepad_grid").show(ui, |ui| {
-                for (grid_id, heading, player) in [
-                    ("genesis_p1_gamepad_grid", "Player 1", Player::One),
-                    ("genesis_p2_gamepad_grid", "Player 2", Player::Two),
-                ] {
-                    Grid::new(grid_id).show(ui, |ui| {
-                        ui.heading(heading);
-                        ui.end_row();
+                self.genesis_player_grids(&[Player::One, Player::Two], InputType::Joystick, ui);
+            });
 
-                        for button in GenesisButton::ALL {
-                            let current_value = self
-                                .config
-                                .inputs
-                                .genesis_joystick
-                                .get_input(button, player)
-                                .cloned();
-                            self.gamepad_input_button(
-                                current_value,
-                                &button.to_string(),
-                                GenericButton::Genesis(button, player),
-                                ui,
-                            );
-                        }
-                    });
+            ui.add_space(10.0);
 
-                    ui.add_space(50.0);
-                }
+            ui.collapsing("Multitap players", |ui| {
+                Grid::new("genesis_multitap_gamepad_grid").show(ui, |ui| {
+                    self.genesis_player_grids(&Player::ALL[2..5], InputType::Joystick, ui);
+                    ui.end_row();
+
+                    self.genesis_player_grids(&Player::ALL[5..], InputType::Joystick, ui);
+                });
             });
 
             ui.add_space(30.0);
@@ -359,6 +477,84 @@ impl App {
         }
     }
 
+    pub(super) fn render_genesis_peripheral_settings(&mut self, ctx: &Context) {
+        let mut open = true;
+        Window::new("Genesis Peripheral Settings").open(&mut open).resizable(false).show(
+            ctx,
+            |ui| {
+                ui.set_enabled(self.state.waiting_for_input.is_none());
+
+                ui.heading("Mouse");
+
+                Grid::new("genesis_mouse_grid").show(ui, |ui| {
+                    for (label, button) in [
+                        ("Left", GenesisMouseButton::Left),
+                        ("Right", GenesisMouseButton::Right),
+                        ("Middle", GenesisMouseButton::Middle),
+                        ("Start", GenesisMouseButton::Start),
+                    ] {
+                        let current_value =
+                            self.config.inputs.genesis_mouse.get_button(button).cloned();
+                        self.genesis_peripheral_button(
+                            current_value,
+                            label,
+                            GenesisButton::Mouse(button),
+                            ui,
+                        );
+                    }
+                });
+
+                ui.add_space(10.0);
+
+                ui.heading("Menacer / Justifier");
+
+                Grid::new("genesis_light_gun_grid").show(ui, |ui| {
+                    for (label, button) in [
+                        ("Trigger", LightGunButton::Trigger),
+                        ("A", LightGunButton::A),
+                        ("B", LightGunButton::B),
+                        ("Start", LightGunButton::Start),
+                    ] {
+                        let current_value =
+                            self.config.inputs.genesis_light_gun.get_button(button).cloned();
+                        self.genesis_peripheral_button(
+                            current_value,
+                            label,
+                            GenesisButton::LightGun(button),
+                            ui,
+                        );
+                    }
+                });
+
+                ui.add_space(10.0);
+
+                self.controller_type_input("Player 1 controller", Player::One, ui);
+                self.controller_type_input("Player 2 controller", Player::Two, ui);
+
+                ui.add_space(10.0);
+
+                ui.group(|ui| {
+                    ui.label("Multitap controllers");
+
+                    Grid::new("genesis_multitap_pad_types_grid").show(ui, |ui| {
+                        for (i, pad_type) in
+                            self.config.inputs.genesis_multitap_pad_types.iter_mut().enumerate()
+                        {
+                            ui.label(format!("Player {}", i + 1));
+                            ui.radio_value(pad_type, GenesisPadType::None, "None");
+                            ui.radio_value(pad_type, GenesisPadType::ThreeButton, "3-button");
+                            ui.radio_value(pad_type, GenesisPadType::SixButton, "6-button");
+                            ui.end_row();
+                        }
+                    });
+                });
+            },
+        );
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::GenesisPeripherals);
+        }
+    }
+
     pub(super) fn render_nes_keyboard_settings(&mut self, ctx: &Context) {
         let mut open = true;
         Window::new("NES Keyboard Settings").open(&mut open).resizable(false).show(ctx, |ui| {
@@ -486,28 +682,15 @@ impl App {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("snes_keyboard_grid").show(ui, |ui| {
-                for (grid_id, heading, player) in [
-                    ("snes_p1_keyboard_grid", "Player 1", Player::One),
-                    ("snes_p2_keyboard_grid", "Player 2", Player::Two),
-                ] {
-                    Grid::new(grid_id).show(ui, |ui| {
-                        ui.heading(heading);
-                        ui.end_row();
+                self.snes_player_grids(&[Player::One, Player::Two], InputType::Keyboard, ui);
+            });
 
-                        for button in SnesControllerButton::ALL {
-                            let current_value =
-                                self.config.inputs.snes_keyboard.get_input(button, player).cloned();
-                            self.keyboard_input_button(
-                                current_value,
-                                &button.to_string(),
-                                GenericButton::Snes(SnesButton::Controller(button), player),
-                                ui,
-                            );
-                        }
-                    });
+            ui.add_space(10.0);
 
-                    ui.add_space(50.0);
-                }
+            ui.collapsing("Multitap players", |ui| {
+                Grid::new("snes_multitap_keyboard_grid").show(ui, |ui| {
+                    self.snes_player_grids(&Player::ALL[2..5], InputType::Keyboard, ui);
+                });
             });
         });
         if !open {
@@ -521,28 +704,15 @@ impl App {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
             Grid::new("snes_gamepad_grid").show(ui, |ui| {
-                for (grid_id, heading, player) in [
-                    ("snes_p1_gamepad_grid", "Player 1", Player::One),
-                    ("snes_p2_gamepad_grid", "Player 2", Player::Two),
-                ] {
-                    Grid::new(grid_id).show(ui, |ui| {
-                        ui.heading(heading);
-                        ui.end_row();
+                self.snes_player_grids(&[Player::One, Player::Two], InputType::Joystick, ui);
+            });
 
-                        for button in SnesControllerButton::ALL {
-                            let current_value =
-                                self.config.inputs.snes_joystick.get_input(button, player).cloned();
-                            self.gamepad_input_button(
-                                current_value,
-                                &button.to_string(),
-                                GenericButton::Snes(SnesButton::Controller(button), player),
-                                ui,
-                            );
-                        }
-                    });
+            ui.add_space(10.0);
 
-                    ui.add_space(50.0);
-                }
+            ui.collapsing("Multitap players", |ui| {
+                Grid::new("snes_multitap_gamepad_grid").show(ui, |ui| {
+                    self.snes_player_grids(&Player::ALL[2..5], InputType::Joystick, ui);
+                });
             });
 
             ui.add_space(30.0);
@@ -559,6 +729,23 @@ impl App {
         Window::new("SNES Peripheral Settings").open(&mut open).resizable(false).show(ctx, |ui| {
             ui.set_enabled(self.state.waiting_for_input.is_none());
 
+            ui.group(|ui| {
+                ui.label("P1 input device");
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.config.inputs.snes_p1_type,
+                        SnesControllerType::Gamepad,
+                        "Gamepad",
+                    );
+                    ui.radio_value(
+                        &mut self.config.inputs.snes_p1_type,
+                        SnesControllerType::Mouse,
+                        "Mouse",
+                    );
+                });
+            });
+
             ui.group(|ui| {
                 ui.label("P2 input device");
 
@@ -568,11 +755,21 @@ impl App {
                         SnesControllerType::Gamepad,
                         "Gamepad",
                     );
+                    ui.radio_value(
+                        &mut self.config.inputs.snes_p2_type,
+                        SnesControllerType::Mouse,
+                        "Mouse",
+                    );
                     ui.radio_value(
                         &mut self.config.inputs.snes_p2_type,
                         SnesControllerType::SuperScope,
                         "Super Scope",
                     );
+                    ui.radio_value(
+                        &mut self.config.inputs.snes_p2_type,
+                        SnesControllerType::SuperMultitap,
+                        "Super Multitap",
+                    );
                 });
             });
 
@@ -581,30 +778,39 @@ impl App {
             ui.heading("Super Scope");
 
             Grid::new("super_scope_grid").show(ui, |ui| {
-                self.super_scope_button(
-                    self.config.inputs.snes_super_scope.fire.clone(),
-                    "Fire",
-                    SuperScopeButton::Fire,
-                    ui,
-                );
-                self.super_scope_button(
-                    self.config.inputs.snes_super_scope.cursor.clone(),
-                    "Cursor",
-                    SuperScopeButton::Cursor,
-                    ui,
-                );
-                self.super_scope_button(
-                    self.config.inputs.snes_super_scope.pause.clone(),
-                    "Pause",
-                    SuperScopeButton::Pause,
-                    ui,
-                );
-                self.super_scope_button(
-                    self.config.inputs.snes_super_scope.turbo_toggle.clone(),
-                    "Turbo (Toggle)",
-                    SuperScopeButton::TurboToggle,
-                    ui,
-                );
+                for (label, button) in [
+                    ("Fire", SuperScopeButton::Fire),
+                    ("Cursor", SuperScopeButton::Cursor),
+                    ("Pause", SuperScopeButton::Pause),
+                    ("Turbo (Toggle)", SuperScopeButton::TurboToggle),
+                ] {
+                    let current_value =
+                        self.config.inputs.snes_super_scope.get_button(button).cloned();
+                    self.snes_peripheral_button(
+                        current_value,
+                        label,
+                        SnesButton::SuperScope(button),
+                        ui,
+                    );
+                }
+            });
+
+            ui.add_space(10.0);
+
+            ui.heading("Mouse");
+
+            Grid::new("snes_mouse_grid").show(ui, |ui| {
+                for (label, button) in
+                    [("Left", SnesMouseButton::Left), ("Right", SnesMouseButton::Right)]
+                {
+                    let current_value = self.config.inputs.snes_mouse.get_button(button).cloned();
+                    self.snes_peripheral_button(
+                        current_value,
+                        label,
+                        SnesButton::Mouse(button),
+                        ui,
+                    );
+                }
             });
         });
         if !open {
@@ -698,6 +904,18 @@ impl App {
                     Hotkey::LoadState,
                     ui,
                 );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.next_save_state_slot.clone(),
+                    "Next save state slot",
+                    Hotkey::NextSaveStateSlot,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.prev_save_state_slot.clone(),
+                    "Previous save state slot",
+                    Hotkey::PrevSaveStateSlot,
+                    ui,
+                );
                 self.hotkey_button(
                     self.config.inputs.hotkeys.soft_reset.clone(),
                     "Soft reset",
@@ -740,6 +958,72 @@ impl App {
                     Hotkey::OpenDebugger,
                     ui,
                 );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_movie_recording.clone(),
+                    "Start/stop movie recording",
+                    Hotkey::ToggleMovieRecording,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_movie_playback.clone(),
+                    "Start/stop movie playback",
+                    Hotkey::ToggleMoviePlayback,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_movie_read_only.clone(),
+                    "Toggle movie read-only mode",
+                    Hotkey::ToggleMovieReadOnly,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.screenshot.clone(),
+                    "Save screenshot",
+                    Hotkey::Screenshot,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_audio_recording.clone(),
+                    "Start/stop WAV audio recording",
+                    Hotkey::ToggleAudioRecording,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_video_recording.clone(),
+                    "Start/stop video recording",
+                    Hotkey::ToggleVideoRecording,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_vgm_logging.clone(),
+                    "Start/stop VGM logging",
+                    Hotkey::ToggleVgmLogging,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.export_spc.clone(),
+                    "Export SNES audio as SPC",
+                    Hotkey::ExportSpc,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.switch_fds_disk_side.clone(),
+                    "Switch FDS disk side",
+                    Hotkey::SwitchFdsDiskSide,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_cheats.clone(),
+                    "Enable/disable cheats",
+                    Hotkey::ToggleCheats,
+                    ui,
+                );
+                self.hotkey_button(
+                    self.config.inputs.hotkeys.toggle_trace_logging.clone(),
+                    "Start/stop instruction trace",
+                    Hotkey::ToggleTraceLogging,
+                    ui,
+                );
             });
 
             ui.add_space(20.0);
@@ -807,6 +1091,106 @@ impl App {
         }
     }
 
+    fn snes_player_grids(&mut self, players: &[Player], input_type: InputType, ui: &mut Ui) {
+        for &player in players {
+            let player_number = player.index() + 1;
+            let grid_id = match input_type {
+                InputType::Keyboard => format!("snes_p{player_number}_keyboard_grid"),
+                InputType::Joystick | InputType::KeyboardOrMouse => {
+                    format!("snes_p{player_number}_gamepad_grid")
+                }
+            };
+
+            Grid::new(grid_id).show(ui, |ui| {
+                ui.heading(format!("Player {player_number}"));
+                ui.end_row();
+
+                for button in SnesControllerButton::ALL {
+                    let generic_button =
+                        GenericButton::Snes(SnesButton::Controller(button), player);
+                    match input_type {
+                        InputType::Keyboard => {
+                            let current_value =
+                                self.config.inputs.snes_keyboard.get_input(button, player).cloned();
+                            self.keyboard_input_button(
+                                current_value,
+                                &button.to_string(),
+                                generic_button,
+                                ui,
+                            );
+                        }
+                        InputType::Joystick | InputType::KeyboardOrMouse => {
+                            let current_value =
+                                self.config.inputs.snes_joystick.get_input(button, player).cloned();
+                            self.gamepad_input_button(
+                                current_value,
+                                &button.to_string(),
+                                generic_button,
+                                ui,
+                            );
+                        }
+                    }
+                }
+            });
+
+            ui.add_space(50.0);
+        }
+    }
+
+    fn genesis_player_grids(&mut self, players: &[Player], input_type: InputType, ui: &mut Ui) {
+        for &player in players {
+            let player_number = player.index() + 1;
+            let grid_id = match input_type {
+                InputType::Keyboard => format!("genesis_p{player_number}_keyboard_grid"),
+                InputType::Joystick | InputType::KeyboardOrMouse => {
+                    format!("genesis_p{player_number}_gamepad_grid")
+                }
+            };
+
+            Grid::new(grid_id).show(ui, |ui| {
+                ui.heading(format!("Player {player_number}"));
+                ui.end_row();
+
+                for button in GenesisControllerButton::ALL {
+                    let generic_button =
+                        GenericButton::Genesis(GenesisButton::Controller(button), player);
+                    match input_type {
+                        InputType::Keyboard => {
+                            let current_value = self
+                                .config
+                                .inputs
+                                .genesis_keyboard
+                                .get_input(button, player)
+                                .cloned();
+                            self.keyboard_input_button(
+                                current_value,
+                                &button.to_string(),
+                                generic_button,
+                                ui,
+                            );
+                        }
+                        InputType::Joystick | InputType::KeyboardOrMouse => {
+                            let current_value = self
+                                .config
+                                .inputs
+                                .genesis_joystick
+                                .get_input(button, player)
+                                .cloned();
+                            self.gamepad_input_button(
+                                current_value,
+                                &button.to_string(),
+                                generic_button,
+                                ui,
+                            );
+                        }
+                    }
+                }
+            });
+
+            ui.add_space(50.0);
+        }
+    }
+
     fn keyboard_input_button(
         &mut self,
         current_value: Option<KeyboardInput>,
@@ -861,23 +1245,38 @@ impl App {
 
     fn clear_button_in_config(&mut self, button: GenericButton, input_type: InputType) {
         match button {
-            GenericButton::SmsGg(button, player) => match input_type {
-                InputType::Keyboard => {
+            GenericButton::SmsGg(button, player) => match (input_type, button) {
+                (InputType::Keyboard, SmsGgButton::Controller(button)) => {
                     self.config.inputs.smsgg_keyboard.clear_input(button, player);
                 }
-                InputType::Joystick => {
+                (InputType::Joystick, SmsGgButton::Controller(button)) => {
                     self.config.inputs.smsgg_joystick.clear_input(button, player);
                 }
-                InputType::KeyboardOrMouse => {}
+                (InputType::KeyboardOrMouse, SmsGgButton::LightPhaser(button)) => {
+                    self.config.inputs.smsgg_light_phaser.set_button(button, None);
+                }
+                (InputType::KeyboardOrMouse, SmsGgButton::Paddle(button)) => {
+                    self.config.inputs.smsgg_paddle.set_button(button, None);
+                }
+                (InputType::KeyboardOrMouse, SmsGgButton::SportsPad(button)) => {
+                    self.config.inputs.smsgg_sports_pad.set_button(button, None);
+                }
+                _ => {}
             },
-            GenericButton::Genesis(button, player) => match input_type {
-                InputType::Keyboard => {
+            GenericButton::Genesis(button, player) => match (input_type, button) {
+                (InputType::Keyboard, GenesisButton::Controller(button)) => {
                     self.config.inputs.genesis_keyboard.clear_input(button, player);
                 }
-                InputType::Joystick => {
+                (InputType::Joystick, GenesisButton::Controller(button)) => {
                     self.config.inputs.genesis_joystick.clear_input(button, player);
                 }
-                InputType::KeyboardOrMouse => {}
+                (InputType::KeyboardOrMouse, GenesisButton::Mouse(button)) => {
+                    self.config.inputs.genesis_mouse.set_button(button, None);
+                }
+                (InputType::KeyboardOrMouse, GenesisButton::LightGun(button)) => {
+                    self.config.inputs.genesis_light_gun.set_button(button, None);
+                }
+                _ => {}
             },
             GenericButton::Nes(button, player) => match input_type {
                 InputType::Keyboard => self.config.inputs.nes_keyboard.clear_input(button, player),
@@ -894,6 +1293,9 @@ impl App {
                 (InputType::KeyboardOrMouse, SnesButton::SuperScope(button)) => {
                     self.config.inputs.snes_super_scope.clear_button(button);
                 }
+                (InputType::KeyboardOrMouse, SnesButton::Mouse(button)) => {
+                    self.config.inputs.snes_mouse.set_button(button, None);
+                }
                 _ => {}
             },
             GenericButton::GameBoy(button) => match input_type {
@@ -914,6 +1316,12 @@ impl App {
                 Hotkey::LoadState => {
                     self.config.inputs.hotkeys.load_state = None;
                 }
+                Hotkey::NextSaveStateSlot => {
+                    self.config.inputs.hotkeys.next_save_state_slot = None;
+                }
+                Hotkey::PrevSaveStateSlot => {
+                    self.config.inputs.hotkeys.prev_save_state_slot = None;
+                }
                 Hotkey::SoftReset => {
                     self.config.inputs.hotkeys.soft_reset = None;
                 }
@@ -935,26 +1343,110 @@ impl App {
                 Hotkey::OpenDebugger => {
                     self.config.inputs.hotkeys.open_debugger = None;
                 }
+                Hotkey::ToggleMovieRecording => {
+                    self.config.inputs.hotkeys.toggle_movie_recording = None;
+                }
+                Hotkey::ToggleMoviePlayback => {
+                    self.config.inputs.hotkeys.toggle_movie_playback = None;
+                }
+                Hotkey::ToggleMovieReadOnly => {
+                    self.config.inputs.hotkeys.toggle_movie_read_only = None;
+                }
+                Hotkey::Screenshot => {
+                    self.config.inputs.hotkeys.screenshot = None;
+                }
+                Hotkey::ToggleAudioRecording => {
+                    self.config.inputs.hotkeys.toggle_audio_recording = None;
+                }
+                Hotkey::ToggleVideoRecording => {
+                    self.config.inputs.hotkeys.toggle_video_recording = None;
+                }
+                Hotkey::ToggleVgmLogging => {
+                    self.config.inputs.hotkeys.toggle_vgm_logging = None;
+                }
+                Hotkey::ExportSpc => {
+                    self.config.inputs.hotkeys.export_spc = None;
+                }
+                Hotkey::SwitchFdsDiskSide => {
+                    self.config.inputs.hotkeys.switch_fds_disk_side = None;
+                }
+                Hotkey::ToggleCheats => {
+                    self.config.inputs.hotkeys.toggle_cheats = None;
+                }
+                Hotkey::ToggleTraceLogging => {
+                    self.config.inputs.hotkeys.toggle_trace_logging = None;
+                }
             },
         }
     }
 
+    fn smsgg_controller_type_input(&mut self, label: &str, player: Player, ui: &mut Ui) {
+        ui.group(|ui| {
+            ui.label(label);
+
+            let controller_type_field = if player == Player::Two {
+                &mut self.config.inputs.smsgg_p2_type
+            } else {
+                &mut self.config.inputs.smsgg_p1_type
+            };
+
+            ui.horizontal(|ui| {
+                ui.radio_value(controller_type_field, SmsControllerType::Gamepad, "Gamepad");
+                ui.radio_value(
+                    controller_type_field,
+                    SmsControllerType::LightPhaser,
+                    "Light Phaser",
+                );
+                ui.radio_value(controller_type_field, SmsControllerType::Paddle, "Paddle Control");
+                ui.radio_value(controller_type_field, SmsControllerType::SportsPad, "Sports Pad");
+            });
+        });
+    }
+
     fn controller_type_input(&mut self, label: &str, player: Player, ui: &mut Ui) {
         ui.group(|ui| {
             ui.label(label);
 
-            let controller_type_field = match player {
-                Player::One => &mut self.config.inputs.genesis_p1_type,
-                Player::Two => &mut self.config.inputs.genesis_p2_type,
+            // Players beyond 2 are connected through a multitap, so only ports 1 and 2 have a type
+            let controller_type_field = if player == Player::Two {
+                &mut self.config.inputs.genesis_p2_type
+            } else {
+                &mut self.config.inputs.genesis_p1_type
             };
 
             ui.horizontal(|ui| {
+                ui.radio_value(controller_type_field, GenesisControllerType::None, "None");
                 ui.radio_value(
                     controller_type_field,
                     GenesisControllerType::ThreeButton,
                     "3-button",
                 );
                 ui.radio_value(controller_type_field, GenesisControllerType::SixButton, "6-button");
+                ui.radio_value(controller_type_field, GenesisControllerType::Mouse, "Mouse");
+                ui.radio_value(
+                    controller_type_field,
+                    GenesisControllerType::TeamPlayer,
+                    "Team Player",
+                );
+                ui.radio_value(
+                    controller_type_field,
+                    GenesisControllerType::EaFourWayPlay,
+                    "EA 4-Way Play",
+                );
+
+                // Light guns only work in port 2
+                if player == Player::Two {
+                    ui.radio_value(
+                        controller_type_field,
+                        GenesisControllerType::Menacer,
+                        "Menacer",
+                    );
+                    ui.radio_value(
+                        controller_type_field,
+                        GenesisControllerType::Justifier,
+                        "Justifier",
+                    );
+                }
             });
         });
     }
@@ -988,6 +1480,70 @@ impl App {
         ui.end_row();
     }
 
+    fn smsgg_peripheral_button(
+        &mut self,
+        current_value: Option<KeyboardOrMouseInput>,
+        label: &str,
+        button: SmsGgButton,
+        ui: &mut Ui,
+    ) {
+        ui.label(format!("{label}:"));
+
+        let text = match current_value {
+            Some(value) => value.to_string(),
+            None => "<None>".into(),
+        };
+        if ui.button(text).clicked() {
+            log::debug!("Sending collect input request for SMS peripheral button {button:?}");
+            self.emu_thread.send(EmuThreadCommand::CollectInput {
+                input_type: InputType::KeyboardOrMouse,
+                axis_deadzone: self.config.inputs.axis_deadzone,
+            });
+            self.state.waiting_for_input = Some(GenericButton::SmsGg(button, Player::One));
+        }
+
+        if ui.button("Clear").clicked() {
+            self.clear_button_in_config(
+                GenericButton::SmsGg(button, Player::One),
+                InputType::KeyboardOrMouse,
+            );
+        }
+
+        ui.end_row();
+    }
+
+    fn genesis_peripheral_button(
+        &mut self,
+        current_value: Option<KeyboardOrMouseInput>,
+        label: &str,
+        button: GenesisButton,
+        ui: &mut Ui,
+    ) {
+        ui.label(format!("{label}:"));
+
+        let text = match current_value {
+            Some(value) => value.to_string(),
+            None => "<None>".into(),
+        };
+        if ui.button(text).clicked() {
+            log::debug!("Sending collect input request for Genesis peripheral button {button:?}");
+            self.emu_thread.send(EmuThreadCommand::CollectInput {
+                input_type: InputType::KeyboardOrMouse,
+                axis_deadzone: self.config.inputs.axis_deadzone,
+            });
+            self.state.waiting_for_input = Some(GenericButton::Genesis(button, Player::One));
+        }
+
+        if ui.button("Clear").clicked() {
+            self.clear_button_in_config(
+                GenericButton::Genesis(button, Player::One),
+                InputType::KeyboardOrMouse,
+            );
+        }
+
+        ui.end_row();
+    }
+
     fn zapper_button(
         &mut self,
         current_value: Option<KeyboardOrMouseInput>,
@@ -1020,11 +1576,11 @@ impl App {
         ui.end_row();
     }
 
-    fn super_scope_button(
+    fn snes_peripheral_button(
         &mut self,
         current_value: Option<KeyboardOrMouseInput>,
         label: &str,
-        button: SuperScopeButton,
+        button: SnesButton,
         ui: &mut Ui,
     ) {
         ui.label(format!("{label}:"));
@@ -1034,18 +1590,17 @@ impl App {
             None => "<None>".into(),
         };
         if ui.button(text).clicked() {
-            log::debug!("Sending collect input request for Super Scope button {button:?}");
+            log::debug!("Sending collect input request for SNES peripheral button {button:?}");
             self.emu_thread.send(EmuThreadCommand::CollectInput {
                 input_type: InputType::KeyboardOrMouse,
                 axis_deadzone: self.config.inputs.axis_deadzone,
             });
-            self.state.waiting_for_input =
-                Some(GenericButton::Snes(SnesButton::SuperScope(button), Player::One));
+            self.state.waiting_for_input = Some(GenericButton::Snes(button, Player::One));
         }
 
         if ui.button("Clear").clicked() {
             self.clear_button_in_config(
-                GenericButton::Snes(SnesButton::SuperScope(button), Player::One),
+                GenericButton::Snes(button, Player::One),
                 InputType::KeyboardOrMouse,
             );
         }
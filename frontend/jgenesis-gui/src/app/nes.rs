@@ -5,6 +5,7 @@ use eframe::epaint::Color32;
 use egui::{Context, Layout, Window};
 use jgenesis_common::frontend::TimingMode;
 use nes_core::api::{NesAspectRatio, Overscan};
+use rfd::FileDialog;
 
 pub struct OverscanState {
     top_text: String,
@@ -58,6 +59,24 @@ impl App {
                 ui.checkbox(&mut self.config.nes.allow_opposing_joypad_inputs, "Allow simultaneous opposing directional inputs")
                     .on_hover_text("Some games exhibit major glitches when opposing directions are pressed simultaneously");
             });
+
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                ui.set_enabled(self.emu_thread.status() != EmuThreadStatus::RunningNes);
+
+                let bios_path_str =
+                    self.config.nes.fds_bios_path.as_ref().map_or("<None>", String::as_str);
+                if ui.button(bios_path_str).clicked() {
+                    if let Some(bios_path) =
+                        FileDialog::new().add_filter("rom", &["rom", "bin"]).pick_file()
+                    {
+                        self.config.nes.fds_bios_path =
+                            Some(bios_path.to_string_lossy().to_string());
+                    }
+                }
+
+                ui.label("FDS BIOS path");
+            });
         });
         if !open {
             self.state.open_windows.remove(&OpenWindow::NesGeneral);
@@ -1,3 +1,5 @@
+use jgenesis_native_driver::archive;
+use jgenesis_native_driver::archive::{ArchiveEntry, ArchiveResult};
 use jgenesis_proc_macros::EnumAll;
 use regex::Regex;
 use std::collections::HashSet;
@@ -25,7 +27,7 @@ impl Console {
             "gg" => Some(Self::GameGear),
             "md" | "bin" => Some(Self::Genesis),
             "cue" | "chd" => Some(Self::SegaCd),
-            "nes" => Some(Self::Nes),
+            "nes" | "nsf" | "nsfe" | "fds" => Some(Self::Nes),
             "sfc" | "smc" => Some(Self::Snes),
             "gb" => Some(Self::GameBoy),
             "gbc" => Some(Self::GameBoyColor),
@@ -33,6 +35,12 @@ impl Console {
         }
     }
 
+    // CD images cannot be loaded out of archives
+    fn from_archive_extension(extension: &str) -> Option<Self> {
+        Self::from_extension(&extension.to_ascii_lowercase())
+            .filter(|&console| console != Self::SegaCd)
+    }
+
     pub fn to_str(self) -> &'static str {
         match self {
             Self::MasterSystem => "Master System",
@@ -53,6 +61,7 @@ pub struct RomMetadata {
     pub file_name_no_ext: String,
     pub console: Console,
     pub file_size: u64,
+    pub archive_entry: Option<String>,
 }
 
 pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
@@ -69,9 +78,15 @@ pub fn build(rom_search_dirs: &[String]) -> Vec<RomMetadata> {
                                 return None;
                             }
 
+                            let path = dir_entry.path();
+                            if archive::is_archive(&path) {
+                                return Some(process_archive(&path));
+                            }
+
                             let file_name = dir_entry.file_name().to_string_lossy().to_string();
-                            process_file(&file_name, &dir_entry.path(), metadata)
+                            process_file(&file_name, &path, metadata).map(|metadata| vec![metadata])
                         })
+                        .flatten()
                         .collect::<Vec<_>>()
                 })
                 .unwrap_or_default()
@@ -116,7 +131,48 @@ fn process_file(file_name: &str, path: &Path, metadata: fs::Metadata) -> Option<
         _ => metadata.len(),
     };
 
-    Some(RomMetadata { full_path, file_name_no_ext, console, file_size })
+    Some(RomMetadata { full_path, file_name_no_ext, console, file_size, archive_entry: None })
+}
+
+/// List the files inside an archive that can be loaded as ROMs.
+pub fn list_archive_roms(path: &Path) -> ArchiveResult<Vec<(ArchiveEntry, Console)>> {
+    let entries = archive::list_entries(path)?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let console = Console::from_archive_extension(entry.extension()?)?;
+            Some((entry, console))
+        })
+        .collect())
+}
+
+fn process_archive(path: &Path) -> Vec<RomMetadata> {
+    let Some(full_path) = path.to_str().map(String::from) else { return vec![] };
+
+    let roms = match list_archive_roms(path) {
+        Ok(roms) => roms,
+        Err(err) => {
+            log::warn!("Unable to read archive '{full_path}': {err}");
+            return vec![];
+        }
+    };
+
+    roms.into_iter()
+        .map(|(entry, console)| {
+            let file_name_no_ext = Path::new(&entry.name)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            RomMetadata {
+                full_path: full_path.clone(),
+                file_name_no_ext,
+                console,
+                file_size: entry.size,
+                archive_entry: Some(entry.name),
+            }
+        })
+        .collect()
 }
 
 fn sega_cd_file_size(cue_path: &str) -> io::Result<u64> {
@@ -151,8 +207,22 @@ pub fn from_recent_opens(recent_opens: &[String]) -> Vec<RomMetadata> {
         .filter_map(|path| {
             let path = Path::new(path);
             let file_name = path.file_name()?.to_string_lossy();
-            let metadata = fs::metadata(path).ok()?;
 
+            // Archives are listed under the archive's name; the ROM to load is chosen on launch
+            if archive::is_archive(path) {
+                let console = process_archive(path).first()?.console;
+                let file_name_no_ext =
+                    path.with_extension("").file_name()?.to_string_lossy().to_string();
+                return Some(RomMetadata {
+                    full_path: path.to_str()?.into(),
+                    file_name_no_ext,
+                    console,
+                    file_size: fs::metadata(path).ok()?.len(),
+                    archive_entry: None,
+                });
+            }
+
+            let metadata = fs::metadata(path).ok()?;
             process_file(&file_name, path, metadata)
         })
         .collect()
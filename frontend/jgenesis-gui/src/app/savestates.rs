@@ -0,0 +1,189 @@
+use crate::app::{App, OpenWindow};
+use crate::emuthread::EmuThreadCommand;
+use egui::{ColorImage, Context, Grid, TextureHandle, TextureOptions, Ui, Vec2, Window};
+use jgenesis_native_driver::{SaveStateSlot, SaveStateThumbnail, SAVE_STATE_SLOTS};
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// Save state files are written by the emulation thread, so the GUI has no way of knowing exactly
+// when they change; check their modification times periodically while they are being displayed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+const THUMBNAIL_DISPLAY_HEIGHT: f32 = 90.0;
+
+struct CachedSlot {
+    slot: SaveStateSlot,
+    /// None if the file does not exist
+    modified: Option<SystemTime>,
+    thumbnail: Option<TextureHandle>,
+}
+
+#[derive(Default)]
+pub(super) struct SaveStateList {
+    rom_path: PathBuf,
+    slots: Vec<CachedSlot>,
+    last_refresh: Option<Instant>,
+}
+
+impl SaveStateList {
+    fn refresh_if_stale(&mut self, ctx: &Context, rom_path: &Path) {
+        let stale = self.rom_path != rom_path
+            || self
+                .last_refresh
+                .map_or(true, |last_refresh| last_refresh.elapsed() >= REFRESH_INTERVAL);
+        if !stale {
+            return;
+        }
+
+        let mut prev_slots = if self.rom_path == rom_path {
+            mem::take(&mut self.slots).into_iter()
+        } else {
+            vec![].into_iter()
+        };
+
+        self.rom_path = rom_path.into();
+        self.last_refresh = Some(Instant::now());
+        self.slots = (0..SAVE_STATE_SLOTS)
+            .map(|slot| {
+                let path = jgenesis_native_driver::save_state_path(rom_path, slot);
+                let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+
+                // Only re-read state files that have changed since the last refresh
+                if let Some(prev) = prev_slots.next().filter(|prev| prev.modified == modified) {
+                    return prev;
+                }
+
+                let slot = jgenesis_native_driver::read_save_state_slot(rom_path, slot);
+                let thumbnail = slot
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.thumbnail.as_ref())
+                    .map(|thumbnail| load_thumbnail(ctx, slot.slot, thumbnail));
+
+                CachedSlot { slot, modified, thumbnail }
+            })
+            .collect();
+    }
+}
+
+fn load_thumbnail(ctx: &Context, slot: usize, thumbnail: &SaveStateThumbnail) -> TextureHandle {
+    let image = ColorImage::from_rgba_unmultiplied(
+        [thumbnail.width as usize, thumbnail.height as usize],
+        &thumbnail.rgba,
+    );
+    ctx.load_texture(format!("save_state_thumbnail_{slot}"), image, TextureOptions::LINEAR)
+}
+
+fn slot_label(slot: &SaveStateSlot) -> String {
+    match &slot.metadata {
+        Some(metadata) => format!(
+            "Slot {} - {} ({})",
+            slot.slot,
+            metadata.rom_name,
+            metadata.formatted_timestamp()
+        ),
+        None => format!("Slot {} - Empty", slot.slot),
+    }
+}
+
+fn show_thumbnail(ui: &mut Ui, thumbnail: &TextureHandle) {
+    let [width, height] = thumbnail.size();
+    let scale = THUMBNAIL_DISPLAY_HEIGHT / height as f32;
+    ui.image((thumbnail.id(), Vec2::new(width as f32 * scale, THUMBNAIL_DISPLAY_HEIGHT)));
+}
+
+impl App {
+    pub(super) fn render_save_state_menus(&mut self, ctx: &Context, ui: &mut Ui) {
+        self.state.save_states.refresh_if_stale(ctx, &self.state.current_rom_base_path);
+
+        ui.menu_button("Save State", |ui| {
+            for cached in &self.state.save_states.slots {
+                let response = ui.button(slot_label(&cached.slot));
+                let response = match &cached.thumbnail {
+                    Some(thumbnail) => response.on_hover_ui(|ui| show_thumbnail(ui, thumbnail)),
+                    None => response,
+                };
+
+                if response.clicked() {
+                    self.emu_thread.send(EmuThreadCommand::SaveState(cached.slot.slot));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        ui.menu_button("Load State", |ui| {
+            for cached in &self.state.save_states.slots {
+                ui.add_enabled_ui(cached.slot.metadata.is_some(), |ui| {
+                    let response = ui.button(slot_label(&cached.slot));
+                    let response = match &cached.thumbnail {
+                        Some(thumbnail) => response.on_hover_ui(|ui| show_thumbnail(ui, thumbnail)),
+                        None => response,
+                    };
+
+                    if response.clicked() {
+                        self.emu_thread.send(EmuThreadCommand::LoadState(cached.slot.slot));
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        if ui.button("Save State Slots...").clicked() {
+            self.state.open_windows.insert(OpenWindow::SaveStates);
+            ui.close_menu();
+        }
+    }
+
+    pub(super) fn render_save_states_window(&mut self, ctx: &Context) {
+        self.state.save_states.refresh_if_stale(ctx, &self.state.current_rom_base_path);
+
+        let is_running = self.emu_thread.status().is_running();
+
+        let mut open = true;
+        Window::new("Save States").open(&mut open).resizable(false).show(ctx, |ui| {
+            Grid::new("save_states_grid").num_columns(4).spacing([10.0, 10.0]).show(ui, |ui| {
+                for cached in &self.state.save_states.slots {
+                    match &cached.thumbnail {
+                        Some(thumbnail) => show_thumbnail(ui, thumbnail),
+                        None => {
+                            ui.label("");
+                        }
+                    }
+
+                    ui.vertical(|ui| {
+                        ui.label(format!("Slot {}", cached.slot.slot));
+                        match &cached.slot.metadata {
+                            Some(metadata) => {
+                                ui.label(&metadata.rom_name);
+                                ui.label(metadata.formatted_timestamp());
+                                ui.label(format!("Version {}", metadata.emulator_version));
+                            }
+                            None => {
+                                ui.label("Empty");
+                            }
+                        }
+                    });
+
+                    ui.add_enabled_ui(is_running, |ui| {
+                        if ui.button("Save").clicked() {
+                            self.emu_thread.send(EmuThreadCommand::SaveState(cached.slot.slot));
+                        }
+                    });
+
+                    ui.add_enabled_ui(is_running && cached.slot.metadata.is_some(), |ui| {
+                        if ui.button("Load").clicked() {
+                            self.emu_thread.send(EmuThreadCommand::LoadState(cached.slot.slot));
+                        }
+                    });
+
+                    ui.end_row();
+                }
+            });
+        });
+        if !open {
+            self.state.open_windows.remove(&OpenWindow::SaveStates);
+        }
+    }
+}
@@ -7,9 +7,9 @@ use jgenesis_native_driver::config::{
 };
 use jgenesis_native_driver::input::Joysticks;
 use jgenesis_native_driver::{
-    AudioError, NativeEmulatorResult, NativeGameBoyEmulator, NativeGenesisEmulator,
-    NativeNesEmulator, NativeSegaCdEmulator, NativeSmsGgEmulator, NativeSnesEmulator,
-    NativeTickEffect,
+    AudioError, CheatEntry, MovieStart, NativeEmulatorResult, NativeGameBoyEmulator,
+    NativeGenesisEmulator, NativeNesEmulator, NativeSegaCdEmulator, NativeSmsGgEmulator,
+    NativeSnesEmulator, NativeTickEffect,
 };
 use sdl2::event::Event;
 use sdl2::joystick::HatState;
@@ -86,6 +86,12 @@ pub enum EmuThreadCommand {
     OpenMemoryViewer,
     SegaCdRemoveDisc,
     SegaCdChangeDisc(PathBuf),
+    SaveState(usize),
+    LoadState(usize),
+    StartMovieRecording(PathBuf),
+    StartMoviePlayback { path: PathBuf, read_only: bool },
+    StopMovie,
+    UpdateCheats(Vec<CheatEntry>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -337,7 +343,13 @@ pub fn spawn(ctx: egui::Context) -> EmuThreadHandle {
                     | EmuThreadCommand::HardReset
                     | EmuThreadCommand::OpenMemoryViewer
                     | EmuThreadCommand::SegaCdRemoveDisc
-                    | EmuThreadCommand::SegaCdChangeDisc(_),
+                    | EmuThreadCommand::SegaCdChangeDisc(_)
+                    | EmuThreadCommand::SaveState(_)
+                    | EmuThreadCommand::LoadState(_)
+                    | EmuThreadCommand::StartMovieRecording(_)
+                    | EmuThreadCommand::StartMoviePlayback { .. }
+                    | EmuThreadCommand::StopMovie
+                    | EmuThreadCommand::UpdateCheats(_),
                 ) => {}
                 Err(err) => {
                     log::info!(
@@ -458,6 +470,40 @@ impl GenericEmulator {
         match_each_emulator_variant!(self, emulator => emulator.open_memory_viewer());
     }
 
+    fn save_state(&mut self, slot: usize) -> NativeEmulatorResult<()> {
+        match_each_emulator_variant!(self, emulator => {
+            emulator.select_save_state_slot(slot);
+            emulator.save_state(slot)
+        })
+    }
+
+    fn load_state(&mut self, slot: usize) {
+        match_each_emulator_variant!(self, emulator => {
+            emulator.select_save_state_slot(slot);
+            emulator.load_state(slot);
+        });
+    }
+
+    fn start_movie_recording(&mut self, path: PathBuf) -> NativeEmulatorResult<()> {
+        match_each_emulator_variant!(self, emulator => {
+            emulator.start_movie_recording(path, MovieStart::CurrentState)
+        })
+    }
+
+    fn start_movie_playback(&mut self, path: PathBuf, read_only: bool) -> NativeEmulatorResult<()> {
+        match_each_emulator_variant!(self, emulator => {
+            emulator.start_movie_playback(path, read_only)
+        })
+    }
+
+    fn stop_movie(&mut self) {
+        match_each_emulator_variant!(self, emulator => emulator.stop_movie());
+    }
+
+    fn update_cheats(&mut self, cheats: Vec<CheatEntry>) {
+        match_each_emulator_variant!(self, emulator => emulator.update_cheats(cheats));
+    }
+
     fn focus(&mut self) {
         match_each_emulator_variant!(self, emulator => emulator.focus());
     }
@@ -565,6 +611,30 @@ fn run_emulator(
                                 return;
                             }
                         }
+                        EmuThreadCommand::SaveState(slot) => {
+                            if let Err(err) = emulator.save_state(slot) {
+                                log::error!("Error saving state to slot {slot}: {err}");
+                            }
+                        }
+                        EmuThreadCommand::LoadState(slot) => {
+                            emulator.load_state(slot);
+                        }
+                        EmuThreadCommand::StartMovieRecording(path) => {
+                            if let Err(err) = emulator.start_movie_recording(path) {
+                                log::error!("Error starting movie recording: {err}");
+                            }
+                        }
+                        EmuThreadCommand::StartMoviePlayback { path, read_only } => {
+                            if let Err(err) = emulator.start_movie_playback(path, read_only) {
+                                log::error!("Error starting movie playback: {err}");
+                            }
+                        }
+                        EmuThreadCommand::StopMovie => {
+                            emulator.stop_movie();
+                        }
+                        EmuThreadCommand::UpdateCheats(cheats) => {
+                            emulator.update_cheats(cheats);
+                        }
                         EmuThreadCommand::RunSms(_)
                         | EmuThreadCommand::RunGenesis(_)
                         | EmuThreadCommand::RunSegaCd(_)
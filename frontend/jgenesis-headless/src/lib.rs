@@ -0,0 +1,179 @@
+//! Frontend that runs emulator cores entirely in memory, without a window, an audio device, or
+//! save files on disk. Runs are fully deterministic, which makes this suitable for screenshot
+//! regression tests on machines without a GPU.
+
+mod output;
+pub mod script;
+
+pub use output::{HeadlessAudioOutput, HeadlessRenderer, MemorySaveError, MemorySaveWriter};
+pub use script::{InputScript, ScriptError, ScriptableInputs};
+
+use jgenesis_common::frontend::{EmulatorTrait, FrameSize, TickEffect};
+use std::convert::Infallible;
+
+pub type HeadlessError<Emulator> =
+    <Emulator as EmulatorTrait>::Err<Infallible, Infallible, MemorySaveError>;
+
+/// Results of a headless run, suitable for comparing against known-good values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSummary {
+    pub frames: u64,
+    pub frame_size: FrameSize,
+    pub frame_crc32: u32,
+    pub audio_samples: u64,
+    pub audio_crc32: u32,
+}
+
+pub struct HeadlessRunner<Emulator> {
+    emulator: Emulator,
+    renderer: HeadlessRenderer,
+    audio_output: HeadlessAudioOutput,
+    save_writer: MemorySaveWriter,
+}
+
+impl<Emulator> HeadlessRunner<Emulator>
+where
+    Emulator: EmulatorTrait,
+    Emulator::Inputs: ScriptableInputs,
+{
+    /// Create a runner for an emulator. `save_writer` should be the save writer that was used to
+    /// create the emulator.
+    #[must_use]
+    pub fn new(emulator: Emulator, save_writer: MemorySaveWriter) -> Self {
+        Self {
+            emulator,
+            renderer: HeadlessRenderer::new(),
+            audio_output: HeadlessAudioOutput::new(),
+            save_writer,
+        }
+    }
+
+    /// Run the emulator until it has rendered `frames` more frames, using inputs from the given
+    /// script. Script frame numbers count from the first frame rendered by this runner.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error returned by the emulator core.
+    pub fn run_frames(
+        &mut self,
+        frames: u64,
+        script: &InputScript<Emulator::Inputs>,
+    ) -> Result<(), HeadlessError<Emulator>> {
+        let end_frame = self.renderer.frames_rendered() + frames;
+        while self.renderer.frames_rendered() < end_frame {
+            let inputs = script.inputs_for_frame(self.renderer.frames_rendered());
+            while self.emulator.tick(
+                &mut self.renderer,
+                &mut self.audio_output,
+                &inputs,
+                &mut self.save_writer,
+            )? != TickEffect::FrameRendered
+            {}
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    #[must_use]
+    pub fn renderer(&self) -> &HeadlessRenderer {
+        &self.renderer
+    }
+
+    #[must_use]
+    pub fn save_writer(&self) -> &MemorySaveWriter {
+        &self.save_writer
+    }
+
+    #[must_use]
+    pub fn summary(&self) -> RunSummary {
+        RunSummary {
+            frames: self.renderer.frames_rendered(),
+            frame_size: self.renderer.frame_size(),
+            frame_crc32: self.renderer.frame_crc32(),
+            audio_samples: self.audio_output.samples(),
+            audio_crc32: self.audio_output.audio_crc32(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jgenesis_common::frontend::PixelAspectRatio;
+    use smsgg_core::psg::PsgVersion;
+    use smsgg_core::{
+        SmsControllerType, SmsGgEmulator, SmsGgEmulatorConfig, SmsGgInputs, SmsRegion, VdpVersion,
+    };
+
+    // Enables the display with a blue background, starts a PSG square wave, then loops forever
+    const TEST_PROGRAM: &[u8] = &[
+        0xF3, // DI
+        0x3E, 0x04, 0xD3, 0xBF, 0x3E, 0x80, 0xD3, 0xBF, // VDP register 0 = $04 (mode 4)
+        0x3E, 0x40, 0xD3, 0xBF, 0x3E, 0x81, 0xD3, 0xBF, // VDP register 1 = $40 (display on)
+        0x3E, 0x00, 0xD3, 0xBF, 0x3E, 0xC0, 0xD3, 0xBF, // CRAM address 0
+        0x3E, 0x30, 0xD3, 0xBE, // CRAM[0] = blue
+        0x3E, 0x8F, 0xD3, 0x7F, 0x3E, 0x08, 0xD3, 0x7F, // Tone 0 period = $08F
+        0x3E, 0x90, 0xD3, 0x7F, // Tone 0 volume = max
+        0x18, 0xFE, // JR $
+    ];
+
+    fn test_rom() -> Vec<u8> {
+        let mut rom = vec![0; 0x8000];
+        rom[..TEST_PROGRAM.len()].copy_from_slice(TEST_PROGRAM);
+        rom
+    }
+
+    fn test_config() -> SmsGgEmulatorConfig {
+        SmsGgEmulatorConfig {
+            vdp_version: VdpVersion::NtscMasterSystem2,
+            psg_version: PsgVersion::MasterSystem2,
+            pixel_aspect_ratio: PixelAspectRatio::try_from(smsgg_core::SMS_NTSC_ASPECT_RATIO).ok(),
+            remove_sprite_limit: false,
+            sms_region: SmsRegion::default(),
+            sms_crop_vertical_border: false,
+            sms_crop_left_border: false,
+            fm_sound_unit_enabled: true,
+            overclock_z80: false,
+            p1_controller_type: SmsControllerType::default(),
+            p2_controller_type: SmsControllerType::default(),
+        }
+    }
+
+    fn run_test_rom(frames: u64) -> HeadlessRunner<SmsGgEmulator> {
+        let mut save_writer = MemorySaveWriter::new();
+        let emulator = SmsGgEmulator::create(test_rom(), test_config(), &mut save_writer);
+
+        let mut runner = HeadlessRunner::new(emulator, save_writer);
+        runner.run_frames(frames, &InputScript::<SmsGgInputs>::parse("").unwrap()).unwrap();
+        runner
+    }
+
+    #[test]
+    fn run_summary() {
+        let runner = run_test_rom(10);
+
+        let center = runner.renderer().frame_buffer()[112 * 256 + 128];
+        assert_eq!((center.r, center.g, center.b), (0, 0, 255));
+
+        assert_eq!(
+            runner.summary(),
+            RunSummary {
+                frames: 10,
+                frame_size: FrameSize { width: 256, height: 224 },
+                frame_crc32: 0x5CFA8A95,
+                audio_samples: 7799,
+                audio_crc32: 0x06F66EDE,
+            }
+        );
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(run_test_rom(30).summary(), run_test_rom(30).summary());
+    }
+}
@@ -0,0 +1,324 @@
+#![allow(clippy::doc_markdown)]
+
+use anyhow::Context;
+use clap::Parser;
+use env_logger::Env;
+use gb_core::api::{
+    GameBoyEmulator, GameBoyEmulatorConfig, GbAspectRatio, GbPalette, GbcColorCorrection,
+};
+use genesis_core::{
+    GenesisAspectRatio, GenesisControllerType, GenesisEmulator, GenesisEmulatorConfig,
+    GenesisPadType,
+};
+use jgenesis_common::frontend::{EmulatorTrait, PixelAspectRatio, TimingMode};
+use jgenesis_headless::{
+    HeadlessRunner, InputScript, MemorySaveWriter, RunSummary, ScriptableInputs,
+};
+use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
+use nes_core::api::{NesAspectRatio, NesEmulator, NesEmulatorConfig, Overscan};
+use smsgg_core::psg::PsgVersion;
+use smsgg_core::{SmsControllerType, SmsGgEmulator, SmsGgEmulatorConfig, SmsRegion, VdpVersion};
+use snes_core::api::{CoprocessorRoms, SnesAspectRatio, SnesEmulator, SnesEmulatorConfig};
+use snes_core::spc::SpcPlayer;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::BufWriter;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::{fs, process};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumDisplay, EnumFromStr)]
+enum Hardware {
+    MasterSystem,
+    GameGear,
+    Genesis,
+    Nes,
+    Snes,
+    Spc,
+    GameBoy,
+}
+
+/// Run a ROM without video or audio output and report checksums of the final frame and of all
+/// audio output
+#[derive(Debug, Parser)]
+struct Args {
+    /// Hardware (MasterSystem / GameGear / Genesis / Nes / Snes / Spc / GameBoy); defaults based on file extension if not set
+    #[arg(long)]
+    hardware: Option<Hardware>,
+
+    /// ROM file path
+    #[arg(short = 'f', long)]
+    file_path: PathBuf,
+
+    /// Number of frames to run
+    #[arg(short = 'n', long)]
+    frames: u64,
+
+    /// Input script file; see the jgenesis-headless script module docs for the format
+    #[arg(long)]
+    input_script: Option<PathBuf>,
+
+    /// Write the final frame to the given path as a PNG image
+    #[arg(long)]
+    screenshot: Option<PathBuf>,
+
+    /// Exit with an error if the final frame's CRC-32 (hex) does not match
+    #[arg(long, value_parser = parse_crc32)]
+    expected_frame_crc: Option<u32>,
+
+    /// Exit with an error if the audio CRC-32 (hex) does not match
+    #[arg(long, value_parser = parse_crc32)]
+    expected_audio_crc: Option<u32>,
+
+    /// Force timing mode (Ntsc / Pal)
+    #[arg(long)]
+    forced_timing_mode: Option<TimingMode>,
+
+    /// Famicom Disk System BIOS path; required when running .fds disk images
+    #[arg(long)]
+    fds_bios_path: Option<PathBuf>,
+}
+
+fn parse_crc32(s: &str) -> Result<u32, String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    u32::from_str_radix(s, 16).map_err(|err| format!("invalid CRC-32 '{s}': {err}"))
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
+
+    let args = Args::parse();
+
+    let hardware = match args.hardware {
+        Some(hardware) => hardware,
+        None => {
+            let file_ext = args.file_path.extension().and_then(OsStr::to_str).unwrap_or("");
+            match file_ext {
+                "sms" => Hardware::MasterSystem,
+                "gg" => Hardware::GameGear,
+                "md" | "bin" => Hardware::Genesis,
+                "nes" | "nsf" | "nsfe" | "fds" => Hardware::Nes,
+                "sfc" | "smc" => Hardware::Snes,
+                "spc" => Hardware::Spc,
+                "gb" | "gbc" => Hardware::GameBoy,
+                _ => anyhow::bail!(
+                    "Unable to determine hardware from file extension '{file_ext}'; use --hardware"
+                ),
+            }
+        }
+    };
+
+    let rom = fs::read(&args.file_path)
+        .with_context(|| format!("Error reading ROM file '{}'", args.file_path.display()))?;
+    let script = match &args.input_script {
+        Some(path) => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("Error reading input script '{}'", path.display()))?,
+        ),
+        None => None,
+    };
+    let script = script.as_deref().unwrap_or("");
+
+    let mut save_writer = MemorySaveWriter::new();
+    let summary = match hardware {
+        Hardware::MasterSystem | Hardware::GameGear => {
+            let config = smsgg_config(hardware, args.forced_timing_mode);
+            let emulator = SmsGgEmulator::create(rom, config, &mut save_writer);
+            run(&args, emulator, save_writer, script)?
+        }
+        Hardware::Genesis => {
+            let config = genesis_config(args.forced_timing_mode);
+            let emulator = GenesisEmulator::create(rom, config, &mut save_writer);
+            run(&args, emulator, save_writer, script)?
+        }
+        Hardware::Nes => {
+            let config = nes_config(args.forced_timing_mode);
+            let emulator = if args.file_path.extension().and_then(OsStr::to_str) == Some("fds") {
+                let Some(bios_path) = &args.fds_bios_path else {
+                    anyhow::bail!("FDS disk images require --fds-bios-path");
+                };
+                let bios_rom = fs::read(bios_path).with_context(|| {
+                    format!("Error reading FDS BIOS file '{}'", bios_path.display())
+                })?;
+                NesEmulator::create_fds(bios_rom, rom, config, &mut save_writer)?
+            } else {
+                NesEmulator::create(rom, config, &mut save_writer)?
+            };
+            run(&args, emulator, save_writer, script)?
+        }
+        Hardware::Snes => {
+            let config = snes_config(args.forced_timing_mode);
+            let emulator =
+                SnesEmulator::create(rom, config, CoprocessorRoms::none(), &mut save_writer)?;
+            run(&args, emulator, save_writer, script)?
+        }
+        Hardware::Spc => {
+            let emulator = SpcPlayer::create(rom)?;
+            run(&args, emulator, save_writer, script)?
+        }
+        Hardware::GameBoy => {
+            let emulator = GameBoyEmulator::create(rom, gb_config(), &mut save_writer)?;
+            run(&args, emulator, save_writer, script)?
+        }
+    };
+
+    println!("frames: {}", summary.frames);
+    println!("frame_size: {}x{}", summary.frame_size.width, summary.frame_size.height);
+    println!("frame_crc32: {:08x}", summary.frame_crc32);
+    println!("audio_samples: {}", summary.audio_samples);
+    println!("audio_crc32: {:08x}", summary.audio_crc32);
+
+    let mut mismatch = false;
+    if let Some(expected) = args.expected_frame_crc {
+        if expected != summary.frame_crc32 {
+            eprintln!(
+                "Frame CRC-32 mismatch: expected {expected:08x}, got {:08x}",
+                summary.frame_crc32
+            );
+            mismatch = true;
+        }
+    }
+    if let Some(expected) = args.expected_audio_crc {
+        if expected != summary.audio_crc32 {
+            eprintln!(
+                "Audio CRC-32 mismatch: expected {expected:08x}, got {:08x}",
+                summary.audio_crc32
+            );
+            mismatch = true;
+        }
+    }
+
+    if mismatch {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn run<Emulator>(
+    args: &Args,
+    emulator: Emulator,
+    save_writer: MemorySaveWriter,
+    script: &str,
+) -> anyhow::Result<RunSummary>
+where
+    Emulator: EmulatorTrait,
+    Emulator::Inputs: ScriptableInputs,
+{
+    let script = InputScript::parse(script).context("Error parsing input script")?;
+
+    let mut runner = HeadlessRunner::new(emulator, save_writer);
+    runner.run_frames(args.frames, &script)?;
+
+    if let Some(path) = &args.screenshot {
+        write_png(&runner, path)
+            .with_context(|| format!("Error writing screenshot to '{}'", path.display()))?;
+    }
+
+    Ok(runner.summary())
+}
+
+fn write_png<Emulator>(runner: &HeadlessRunner<Emulator>, path: &Path) -> anyhow::Result<()>
+where
+    Emulator: EmulatorTrait,
+    Emulator::Inputs: ScriptableInputs,
+{
+    let renderer = runner.renderer();
+    let frame_size = renderer.frame_size();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, frame_size.width, frame_size.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let rgba: Vec<u8> = renderer
+        .frame_buffer()
+        .iter()
+        .flat_map(|color| [color.r, color.g, color.b, color.a])
+        .collect();
+    encoder.write_header()?.write_image_data(&rgba)?;
+
+    Ok(())
+}
+
+fn smsgg_config(hardware: Hardware, forced_timing_mode: Option<TimingMode>) -> SmsGgEmulatorConfig {
+    let (vdp_version, psg_version, aspect_ratio) = match (hardware, forced_timing_mode) {
+        (Hardware::GameGear, _) => {
+            (VdpVersion::GameGear, PsgVersion::Standard, smsgg_core::GAME_GEAR_LCD_ASPECT_RATIO)
+        }
+        (_, Some(TimingMode::Pal)) => (
+            VdpVersion::PalMasterSystem2,
+            PsgVersion::MasterSystem2,
+            smsgg_core::SMS_PAL_ASPECT_RATIO,
+        ),
+        (_, None | Some(TimingMode::Ntsc)) => (
+            VdpVersion::NtscMasterSystem2,
+            PsgVersion::MasterSystem2,
+            smsgg_core::SMS_NTSC_ASPECT_RATIO,
+        ),
+    };
+
+    SmsGgEmulatorConfig {
+        vdp_version,
+        psg_version,
+        pixel_aspect_ratio: PixelAspectRatio::try_from(aspect_ratio).ok(),
+        remove_sprite_limit: false,
+        sms_region: SmsRegion::default(),
+        sms_crop_vertical_border: false,
+        sms_crop_left_border: false,
+        fm_sound_unit_enabled: true,
+        overclock_z80: false,
+        p1_controller_type: SmsControllerType::default(),
+        p2_controller_type: SmsControllerType::default(),
+    }
+}
+
+fn genesis_config(forced_timing_mode: Option<TimingMode>) -> GenesisEmulatorConfig {
+    GenesisEmulatorConfig {
+        p1_controller_type: GenesisControllerType::default(),
+        p2_controller_type: GenesisControllerType::default(),
+        multitap_pad_types: [GenesisPadType::default(); 8],
+        forced_timing_mode,
+        forced_region: None,
+        aspect_ratio: GenesisAspectRatio::default(),
+        adjust_aspect_ratio_in_2x_resolution: true,
+        remove_sprite_limits: false,
+        emulate_non_linear_vdp_dac: false,
+        render_vertical_border: false,
+        render_horizontal_border: false,
+        quantize_ym2612_output: true,
+    }
+}
+
+fn nes_config(forced_timing_mode: Option<TimingMode>) -> NesEmulatorConfig {
+    NesEmulatorConfig {
+        forced_timing_mode,
+        aspect_ratio: NesAspectRatio::default(),
+        overscan: Overscan::default(),
+        remove_sprite_limit: false,
+        pal_black_border: false,
+        silence_ultrasonic_triangle_output: false,
+        audio_refresh_rate_adjustment: true,
+        allow_opposing_joypad_inputs: false,
+    }
+}
+
+fn snes_config(forced_timing_mode: Option<TimingMode>) -> SnesEmulatorConfig {
+    SnesEmulatorConfig {
+        forced_timing_mode,
+        aspect_ratio: SnesAspectRatio::default(),
+        audio_60hz_hack: true,
+        gsu_overclock_factor: NonZeroU64::new(1).unwrap(),
+    }
+}
+
+fn gb_config() -> GameBoyEmulatorConfig {
+    GameBoyEmulatorConfig {
+        force_dmg_mode: false,
+        pretend_to_be_gba: false,
+        aspect_ratio: GbAspectRatio::default(),
+        gb_palette: GbPalette::default(),
+        gbc_color_correction: GbcColorCorrection::default(),
+        audio_60hz_hack: true,
+    }
+}
@@ -0,0 +1,207 @@
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+use crc::{Crc, Digest};
+use jgenesis_common::bincode_config;
+use jgenesis_common::frontend::{
+    AudioOutput, Color, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
+};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use thiserror::Error;
+
+static CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+/// Renderer that keeps a copy of the most recently rendered frame in memory.
+#[derive(Debug, Clone)]
+pub struct HeadlessRenderer {
+    frame_buffer: Vec<Color>,
+    frame_size: FrameSize,
+    pixel_aspect_ratio: Option<PixelAspectRatio>,
+    frames_rendered: u64,
+}
+
+impl HeadlessRenderer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            frame_buffer: vec![],
+            frame_size: FrameSize { width: 0, height: 0 },
+            pixel_aspect_ratio: None,
+            frames_rendered: 0,
+        }
+    }
+
+    /// The most recently rendered frame, exactly `frame_size().width * frame_size().height` pixels
+    /// long in row-major order.
+    #[must_use]
+    pub fn frame_buffer(&self) -> &[Color] {
+        &self.frame_buffer
+    }
+
+    #[must_use]
+    pub fn frame_size(&self) -> FrameSize {
+        self.frame_size
+    }
+
+    #[must_use]
+    pub fn pixel_aspect_ratio(&self) -> Option<PixelAspectRatio> {
+        self.pixel_aspect_ratio
+    }
+
+    #[must_use]
+    pub fn frames_rendered(&self) -> u64 {
+        self.frames_rendered
+    }
+
+    /// CRC-32 of the most recently rendered frame's pixels in RGBA order.
+    #[must_use]
+    pub fn frame_crc32(&self) -> u32 {
+        let mut digest = CRC.digest();
+        for color in &self.frame_buffer {
+            digest.update(&[color.r, color.g, color.b, color.a]);
+        }
+        digest.finalize()
+    }
+}
+
+impl Default for HeadlessRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer for HeadlessRenderer {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        let len = (frame_size.width * frame_size.height) as usize;
+
+        self.frame_buffer.clear();
+        self.frame_buffer.extend_from_slice(&frame_buffer[..len]);
+        self.frame_size = frame_size;
+        self.pixel_aspect_ratio = pixel_aspect_ratio;
+        self.frames_rendered += 1;
+
+        Ok(())
+    }
+}
+
+/// Audio output that discards samples after folding them into a running checksum.
+pub struct HeadlessAudioOutput {
+    digest: Digest<'static, u32>,
+    samples: u64,
+}
+
+impl HeadlessAudioOutput {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { digest: CRC.digest(), samples: 0 }
+    }
+
+    /// Number of stereo samples pushed so far.
+    #[must_use]
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// CRC-32 of every sample pushed so far, with each sample encoded as a pair of little-endian
+    /// `f64`s.
+    #[must_use]
+    pub fn audio_crc32(&self) -> u32 {
+        self.digest.clone().finalize()
+    }
+}
+
+impl Default for HeadlessAudioOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioOutput for HeadlessAudioOutput {
+    type Err = Infallible;
+
+    fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), Self::Err> {
+        self.digest.update(&sample_l.to_le_bytes());
+        self.digest.update(&sample_r.to_le_bytes());
+        self.samples += 1;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MemorySaveError {
+    #[error("No save data with extension '{0}'")]
+    NotFound(String),
+    #[error("Error serializing save data with extension '{extension}': {source}")]
+    Encode {
+        extension: String,
+        #[source]
+        source: EncodeError,
+    },
+    #[error("Error deserializing save data with extension '{extension}': {source}")]
+    Decode {
+        extension: String,
+        #[source]
+        source: DecodeError,
+    },
+}
+
+/// Save writer that never touches the filesystem. Every run starts with no save data, which keeps
+/// runs reproducible regardless of what save files exist next to the ROM.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySaveWriter {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemorySaveWriter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the bytes most recently persisted with the given extension, if any.
+    #[must_use]
+    pub fn get(&self, extension: &str) -> Option<&[u8]> {
+        self.files.get(extension).map(Vec::as_slice)
+    }
+}
+
+impl SaveWriter for MemorySaveWriter {
+    type Err = MemorySaveError;
+
+    fn load_bytes(&mut self, extension: &str) -> Result<Vec<u8>, Self::Err> {
+        self.files
+            .get(extension)
+            .cloned()
+            .ok_or_else(|| MemorySaveError::NotFound(extension.into()))
+    }
+
+    fn persist_bytes(&mut self, extension: &str, bytes: &[u8]) -> Result<(), Self::Err> {
+        self.files.insert(extension.into(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn load_serialized<D: Decode>(&mut self, extension: &str) -> Result<D, Self::Err> {
+        let bytes =
+            self.files.get(extension).ok_or_else(|| MemorySaveError::NotFound(extension.into()))?;
+        let (value, _) = bincode::decode_from_slice(bytes, bincode_config!())
+            .map_err(|source| MemorySaveError::Decode { extension: extension.into(), source })?;
+
+        Ok(value)
+    }
+
+    fn persist_serialized<E: Encode>(&mut self, extension: &str, data: E) -> Result<(), Self::Err> {
+        let bytes = bincode::encode_to_vec(data, bincode_config!())
+            .map_err(|source| MemorySaveError::Encode { extension: extension.into(), source })?;
+        self.files.insert(extension.into(), bytes);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+//! Input scripts for headless runs.
+//!
+//! A script is a plain text file where each non-empty line has the form:
+//!
+//! ```text
+//! <frame> [button ...]
+//! ```
+//!
+//! Each line replaces the full set of held buttons starting at the given frame (counting from 0),
+//! and those buttons stay held until the next line. Buttons are named the same way as in the
+//! config file (e.g. `Start`, `A`, `Up`) and are case-insensitive; prefix a button with `p2:` to
+//! press it on player 2's controller, or `p3:` through `p8:` for multitap players. Everything after
+//! a `#` is a comment.
+//!
+//! ```text
+//! # Press Start on frame 120 for 5 frames, then hold Right + A starting on frame 300
+//! 120 Start
+//! 125
+//! 300 Right A p2:Start
+//! ```
+
+use gb_core::inputs::{GameBoyButton, GameBoyInputs};
+use genesis_core::input::{GenesisButton, GenesisControllerButton};
+use genesis_core::GenesisInputs;
+use jgenesis_common::input::Player;
+use nes_core::input::{NesButton, NesInputs};
+use smsgg_core::{SmsGgButton, SmsGgControllerButton, SmsGgInputs};
+use snes_core::input::{SnesButton, SnesControllerButton, SnesInputs};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("Line {line}: invalid frame number '{value}'")]
+    InvalidFrame { line: usize, value: String },
+    #[error("Line {line}: frame {frame} is not after the previous line's frame {prev_frame}")]
+    FrameOutOfOrder { line: usize, frame: u64, prev_frame: u64 },
+    #[error("Line {line}: invalid player '{value}'; expected p1 through p8")]
+    InvalidPlayer { line: usize, value: String },
+    #[error("Line {line}: {message}")]
+    InvalidButton { line: usize, message: String },
+}
+
+/// Inputs types that can be driven by an input script.
+pub trait ScriptableInputs: Default + Clone {
+    /// Press the named button for the given player.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the name does not match any button.
+    fn press_button(&mut self, name: &str, player: Player) -> Result<(), String>;
+}
+
+impl ScriptableInputs for SmsGgInputs {
+    fn press_button(&mut self, name: &str, player: Player) -> Result<(), String> {
+        let button = SmsGgButton::Controller(name.parse::<SmsGgControllerButton>()?);
+        self.set_button(button, player, true);
+        Ok(())
+    }
+}
+
+impl ScriptableInputs for GenesisInputs {
+    fn press_button(&mut self, name: &str, player: Player) -> Result<(), String> {
+        let button = GenesisButton::Controller(name.parse::<GenesisControllerButton>()?);
+        self.set_button(button, player, true);
+        Ok(())
+    }
+}
+
+impl ScriptableInputs for NesInputs {
+    fn press_button(&mut self, name: &str, player: Player) -> Result<(), String> {
+        self.set_button(name.parse::<NesButton>()?, player, true);
+        Ok(())
+    }
+}
+
+impl ScriptableInputs for SnesInputs {
+    fn press_button(&mut self, name: &str, player: Player) -> Result<(), String> {
+        let button = SnesButton::Controller(name.parse::<SnesControllerButton>()?);
+        self.set_button(button, player, true);
+        Ok(())
+    }
+}
+
+impl ScriptableInputs for GameBoyInputs {
+    fn press_button(&mut self, name: &str, player: Player) -> Result<(), String> {
+        if player != Player::One {
+            return Err("Game Boy only has one player".into());
+        }
+
+        self.set_button(name.parse::<GameBoyButton>()?, true);
+        Ok(())
+    }
+}
+
+/// A parsed input script, mapping frame numbers to the inputs held starting on that frame.
+#[derive(Debug, Clone)]
+pub struct InputScript<Inputs> {
+    // Sorted by frame number, strictly increasing
+    entries: Vec<(u64, Inputs)>,
+}
+
+impl<Inputs> Default for InputScript<Inputs> {
+    fn default() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+impl<Inputs: ScriptableInputs> InputScript<Inputs> {
+    /// Parse an input script from its text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any line contains an invalid frame number, player, or button name, or if
+    /// frame numbers are not strictly increasing.
+    pub fn parse(script: &str) -> Result<Self, ScriptError> {
+        let mut entries: Vec<(u64, Inputs)> = Vec::new();
+
+        for (i, line) in script.lines().enumerate() {
+            let line_number = i + 1;
+
+            let line = line.split('#').next().unwrap_or("");
+            let mut tokens = line.split_whitespace();
+            let Some(frame_token) = tokens.next() else { continue };
+
+            let frame: u64 = frame_token.parse().map_err(|_| ScriptError::InvalidFrame {
+                line: line_number,
+                value: frame_token.into(),
+            })?;
+            if let Some(&(prev_frame, _)) = entries.last() {
+                if frame <= prev_frame {
+                    return Err(ScriptError::FrameOutOfOrder {
+                        line: line_number,
+                        frame,
+                        prev_frame,
+                    });
+                }
+            }
+
+            let mut inputs = Inputs::default();
+            for token in tokens {
+                let (player, button) = match token.split_once(':') {
+                    Some((player, button)) => (parse_player(player, line_number)?, button),
+                    None => (Player::One, token),
+                };
+                inputs
+                    .press_button(button, player)
+                    .map_err(|message| ScriptError::InvalidButton { line: line_number, message })?;
+            }
+
+            entries.push((frame, inputs));
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Return the inputs that should be held during the given frame.
+    #[must_use]
+    pub fn inputs_for_frame(&self, frame: u64) -> Inputs {
+        let idx = self.entries.partition_point(|&(entry_frame, _)| entry_frame <= frame);
+        if idx == 0 { Inputs::default() } else { self.entries[idx - 1].1.clone() }
+    }
+}
+
+fn parse_player(s: &str, line: usize) -> Result<Player, ScriptError> {
+    s.to_ascii_lowercase()
+        .strip_prefix('p')
+        .and_then(|n| n.parse::<usize>().ok())
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|idx| Player::ALL.get(idx).copied())
+        .ok_or_else(|| ScriptError::InvalidPlayer { line, value: s.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_hold_until_next_line() {
+        let script = InputScript::<GenesisInputs>::parse(
+            "# comment\n\n10 start\n15  # release\n20 Right a p2:C\n",
+        )
+        .unwrap();
+
+        assert_eq!(script.inputs_for_frame(0), GenesisInputs::default());
+        assert_eq!(script.inputs_for_frame(9), GenesisInputs::default());
+
+        let start = GenesisInputs::default().with_button(
+            GenesisButton::Controller(GenesisControllerButton::Start),
+            Player::One,
+            true,
+        );
+        assert_eq!(script.inputs_for_frame(10), start);
+        assert_eq!(script.inputs_for_frame(14), start);
+        assert_eq!(script.inputs_for_frame(15), GenesisInputs::default());
+
+        let last = GenesisInputs::default()
+            .with_button(
+                GenesisButton::Controller(GenesisControllerButton::Right),
+                Player::One,
+                true,
+            )
+            .with_button(GenesisButton::Controller(GenesisControllerButton::A), Player::One, true)
+            .with_button(GenesisButton::Controller(GenesisControllerButton::C), Player::Two, true);
+        assert_eq!(script.inputs_for_frame(20), last);
+        assert_eq!(script.inputs_for_frame(1000), last);
+    }
+
+    #[test]
+    fn invalid_scripts() {
+        assert!(matches!(
+            InputScript::<GenesisInputs>::parse("abc Start"),
+            Err(ScriptError::InvalidFrame { line: 1, .. })
+        ));
+        assert!(matches!(
+            InputScript::<GenesisInputs>::parse("10 Start\n10 A"),
+            Err(ScriptError::FrameOutOfOrder { line: 2, frame: 10, prev_frame: 10 })
+        ));
+        assert!(matches!(
+            InputScript::<GenesisInputs>::parse("10 p9:Start"),
+            Err(ScriptError::InvalidPlayer { line: 1, .. })
+        ));
+        assert!(matches!(
+            InputScript::<GenesisInputs>::parse("10 Select"),
+            Err(ScriptError::InvalidButton { line: 1, .. })
+        ));
+    }
+}
@@ -45,6 +45,10 @@ pub struct CommonAppConfig {
     pub rewind_buffer_length_seconds: u64,
     #[serde(default)]
     pub hide_cursor_over_window: bool,
+    #[serde(default)]
+    pub screenshot_directory: Option<String>,
+    #[serde(default)]
+    pub screenshot_aspect_ratio_correction: bool,
 }
 
 impl CommonAppConfig {
@@ -98,6 +102,8 @@ impl AppConfig {
         keyboard_inputs: KC,
         joystick_inputs: JC,
     ) -> CommonConfig<KC, JC> {
+        let cheat_directory = crate::cheat_directory().to_string_lossy().into_owned();
+
         CommonConfig {
             rom_file_path: path,
             audio_sync: self.common.audio_sync,
@@ -128,6 +134,15 @@ impl AppConfig {
             joystick_inputs,
             hotkeys: self.inputs.hotkeys.clone(),
             hide_cursor_over_window: self.common.hide_cursor_over_window,
+            screenshot_directory: self.common.screenshot_directory.clone(),
+            screenshot_aspect_ratio_correction: self.common.screenshot_aspect_ratio_correction,
+            cheat_directory: Some(cheat_directory),
+            patch_path: None,
+            archive_entry: None,
+            gdb_port: None,
+            gdb_target: None,
+            trace_cpu: None,
+            trace_ranges: vec![],
         }
     }
 }
@@ -62,6 +62,9 @@ impl AppConfig {
             ),
             p1_controller_type: self.inputs.genesis_p1_type,
             p2_controller_type: self.inputs.genesis_p2_type,
+            multitap_pad_types: self.inputs.genesis_multitap_pad_types,
+            mouse_config: self.inputs.genesis_mouse.clone(),
+            light_gun_config: self.inputs.genesis_light_gun.clone(),
             forced_timing_mode: self.genesis.forced_timing_mode,
             forced_region: self.genesis.forced_region,
             aspect_ratio: self.genesis.aspect_ratio,
@@ -1,10 +1,12 @@
-use genesis_core::GenesisControllerType;
+use genesis_core::{GenesisControllerType, GenesisPadType};
 use jgenesis_native_driver::config::input::{
-    GameBoyInputConfig, GenesisInputConfig, HotkeyConfig, JoystickInput, KeyboardInput,
-    NesControllerType, NesInputConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig,
-    SuperScopeConfig, ZapperConfig,
+    GameBoyInputConfig, GenesisInputConfig, GenesisMouseConfig, HotkeyConfig, JoystickInput,
+    KeyboardInput, LightGunConfig, LightPhaserConfig, NesControllerType, NesInputConfig,
+    PaddleConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig, SnesMouseConfig,
+    SportsPadConfig, SuperScopeConfig, ZapperConfig,
 };
 use serde::{Deserialize, Serialize};
+use smsgg_core::SmsControllerType;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InputAppConfig {
@@ -13,14 +15,30 @@ pub struct InputAppConfig {
     #[serde(default)]
     pub smsgg_joystick: SmsGgInputConfig<JoystickInput>,
     #[serde(default)]
+    pub smsgg_p1_type: SmsControllerType,
+    #[serde(default)]
+    pub smsgg_p2_type: SmsControllerType,
+    #[serde(default)]
+    pub smsgg_light_phaser: LightPhaserConfig,
+    #[serde(default)]
+    pub smsgg_paddle: PaddleConfig,
+    #[serde(default)]
+    pub smsgg_sports_pad: SportsPadConfig,
+    #[serde(default)]
     pub genesis_p1_type: GenesisControllerType,
     #[serde(default)]
     pub genesis_p2_type: GenesisControllerType,
     #[serde(default)]
+    pub genesis_multitap_pad_types: [GenesisPadType; 8],
+    #[serde(default)]
     pub genesis_keyboard: GenesisInputConfig<KeyboardInput>,
     #[serde(default)]
     pub genesis_joystick: GenesisInputConfig<JoystickInput>,
     #[serde(default)]
+    pub genesis_mouse: GenesisMouseConfig,
+    #[serde(default)]
+    pub genesis_light_gun: LightGunConfig,
+    #[serde(default)]
     pub nes_keyboard: NesInputConfig<KeyboardInput>,
     #[serde(default)]
     pub nes_joystick: NesInputConfig<JoystickInput>,
@@ -33,9 +51,13 @@ pub struct InputAppConfig {
     #[serde(default)]
     pub snes_joystick: SnesInputConfig<JoystickInput>,
     #[serde(default)]
+    pub snes_p1_type: SnesControllerType,
+    #[serde(default)]
     pub snes_p2_type: SnesControllerType,
     #[serde(default)]
     pub snes_super_scope: SuperScopeConfig,
+    #[serde(default)]
+    pub snes_mouse: SnesMouseConfig,
     #[serde(default = "default_gb_keyboard_config")]
     pub gb_keyboard: GameBoyInputConfig<KeyboardInput>,
     #[serde(default)]
@@ -109,6 +109,14 @@ pub fn default_config_path() -> PathBuf {
     }
 }
 
+/// Cheat files are stored in a `cheats` directory next to the config file.
+#[must_use]
+pub fn cheat_directory() -> PathBuf {
+    let config_path = default_config_path();
+    let config_dir = config_path.parent().unwrap_or(Path::new(""));
+    config_dir.join("cheats")
+}
+
 #[cfg(target_os = "linux")]
 fn default_linux_config_path() -> PathBuf {
     if option_env!("JGENESIS_APPIMAGE_BUILD").is_none() {
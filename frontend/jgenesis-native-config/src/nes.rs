@@ -21,6 +21,8 @@ pub struct NesAppConfig {
     pub audio_60hz_hack: bool,
     #[serde(default)]
     pub allow_opposing_joypad_inputs: bool,
+    #[serde(default)]
+    pub fds_bios_path: Option<String>,
 }
 
 const fn true_fn() -> bool {
@@ -59,6 +61,7 @@ impl AppConfig {
             silence_ultrasonic_triangle_output: self.nes.silence_ultrasonic_triangle_output,
             audio_refresh_rate_adjustment: self.nes.audio_60hz_hack,
             allow_opposing_joypad_inputs: self.nes.allow_opposing_joypad_inputs,
+            fds_bios_path: self.nes.fds_bios_path.clone(),
         })
     }
 }
@@ -72,6 +72,11 @@ impl AppConfig {
                 self.inputs.smsgg_keyboard.clone(),
                 self.inputs.smsgg_joystick.clone(),
             ),
+            p1_controller_type: self.inputs.smsgg_p1_type,
+            p2_controller_type: self.inputs.smsgg_p2_type,
+            light_phaser_config: self.inputs.smsgg_light_phaser.clone(),
+            paddle_config: self.inputs.smsgg_paddle.clone(),
+            sports_pad_config: self.inputs.smsgg_sports_pad.clone(),
             vdp_version,
             psg_version: self.smsgg.psg_version,
             remove_sprite_limit: self.smsgg.remove_sprite_limit,
@@ -45,8 +45,10 @@ impl AppConfig {
                 self.inputs.snes_keyboard.clone(),
                 self.inputs.snes_joystick.clone(),
             ),
+            p1_controller_type: self.inputs.snes_p1_type,
             p2_controller_type: self.inputs.snes_p2_type,
             super_scope_config: self.inputs.snes_super_scope.clone(),
+            mouse_config: self.inputs.snes_mouse.clone(),
             forced_timing_mode: self.snes.forced_timing_mode,
             aspect_ratio: self.snes.aspect_ratio,
             audio_60hz_hack: self.snes.audio_60hz_hack,
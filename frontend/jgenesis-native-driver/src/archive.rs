@@ -0,0 +1,412 @@
+//! Reading ROM images out of compressed archives.
+//!
+//! Zip archives are supported for entries that are either stored uncompressed or compressed using
+//! deflate, which covers nearly every zipped ROM set in practice. Zip64, encrypted entries, and
+//! multi-disk archives are not supported. Gzip files are treated as an archive containing a single
+//! entry named after the gzip file minus its `.gz` extension.
+
+use crc::Crc;
+use flate2::read::{DeflateDecoder, GzDecoder};
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use thiserror::Error;
+
+pub const ARCHIVE_EXTENSIONS: [&str; 2] = ["zip", "gz"];
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+const EOCD_SIGNATURE: u32 = 0x06054B50;
+const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014B50;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034B50;
+
+const EOCD_LEN: u64 = 22;
+const MAX_COMMENT_LEN: u64 = u16::MAX as u64;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("I/O error reading archive: {0}")]
+    Io(#[from] io::Error),
+    #[error("File is not a valid zip archive")]
+    InvalidZip,
+    #[error("Zip64 archives are not supported")]
+    Zip64,
+    #[error("Entry '{name}' uses unsupported compression method {method}")]
+    UnsupportedCompression { name: String, method: u16 },
+    #[error("Entry '{0}' is encrypted")]
+    Encrypted(String),
+    #[error("Archive does not contain an entry named '{0}'")]
+    EntryNotFound(String),
+    #[error("CRC32 mismatch in entry '{name}'; expected {expected:08X}, was {actual:08X}")]
+    Checksum { name: String, expected: u32, actual: u32 },
+    #[error("Archive does not contain any files with a supported extension ({0})")]
+    NoSupportedEntry(String),
+}
+
+pub type ArchiveResult<T> = Result<T, ArchiveError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+}
+
+impl ArchiveEntry {
+    #[must_use]
+    pub fn extension(&self) -> Option<&str> {
+        Path::new(&self.name).extension().and_then(OsStr::to_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ZipEntry {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    local_header_offset: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    Gzip,
+}
+
+impl ArchiveFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some(ext) if ext.eq_ignore_ascii_case("zip") => Some(Self::Zip),
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+}
+
+/// Returns whether the given path has an archive file extension.
+#[must_use]
+pub fn is_archive<P: AsRef<Path>>(path: P) -> bool {
+    ArchiveFormat::from_path(path.as_ref()).is_some()
+}
+
+/// List all file entries in the given archive. Directory entries are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not a valid archive.
+pub fn list_entries<P: AsRef<Path>>(path: P) -> ArchiveResult<Vec<ArchiveEntry>> {
+    let path = path.as_ref();
+    match ArchiveFormat::from_path(path) {
+        Some(ArchiveFormat::Zip) => {
+            let entries = read_zip_directory(&mut BufReader::new(File::open(path)?))?;
+            Ok(entries
+                .into_iter()
+                .map(|entry| ArchiveEntry { name: entry.name, size: entry.uncompressed_size })
+                .collect())
+        }
+        Some(ArchiveFormat::Gzip) => {
+            let mut file = File::open(path)?;
+
+            // Uncompressed size (mod 2^32) is stored in the last 4 bytes of the file
+            file.seek(SeekFrom::End(-4))?;
+            let mut size = [0; 4];
+            file.read_exact(&mut size)?;
+
+            Ok(vec![ArchiveEntry {
+                name: gzip_entry_name(path),
+                size: u32::from_le_bytes(size).into(),
+            }])
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// Read and decompress the entry with the given name.
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read, the entry does not exist, or the entry fails to
+/// decompress.
+pub fn read_entry<P: AsRef<Path>>(path: P, name: &str) -> ArchiveResult<Vec<u8>> {
+    let path = path.as_ref();
+    match ArchiveFormat::from_path(path) {
+        Some(ArchiveFormat::Zip) => read_zip_entry(&mut BufReader::new(File::open(path)?), name),
+        Some(ArchiveFormat::Gzip) => {
+            if name != gzip_entry_name(path) {
+                return Err(ArchiveError::EntryNotFound(name.into()));
+            }
+
+            let mut contents = Vec::new();
+            GzDecoder::new(BufReader::new(File::open(path)?)).read_to_end(&mut contents)?;
+            Ok(contents)
+        }
+        None => Err(ArchiveError::EntryNotFound(name.into())),
+    }
+}
+
+/// Pick the entry to load out of an archive. If `entry_name` is set, that entry is used; otherwise
+/// this uses the first entry with one of the given file extensions.
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or if it does not contain a matching entry.
+pub fn select_entry<P: AsRef<Path>>(
+    path: P,
+    entry_name: Option<&str>,
+    extensions: &[&str],
+) -> ArchiveResult<ArchiveEntry> {
+    let entries = list_entries(path)?;
+
+    if let Some(entry_name) = entry_name {
+        return entries
+            .into_iter()
+            .find(|entry| entry.name == entry_name)
+            .ok_or_else(|| ArchiveError::EntryNotFound(entry_name.into()));
+    }
+
+    let mut supported = entries.into_iter().filter(|entry| {
+        entry.extension().is_some_and(|ext| {
+            extensions.iter().any(|supported| supported.eq_ignore_ascii_case(ext))
+        })
+    });
+    let first =
+        supported.next().ok_or_else(|| ArchiveError::NoSupportedEntry(extensions.join(", ")))?;
+
+    let remaining = supported.count();
+    if remaining != 0 {
+        log::warn!(
+            "Archive contains {} supported files; loading the first one, '{}'",
+            remaining + 1,
+            first.name
+        );
+    }
+
+    Ok(first)
+}
+
+fn gzip_entry_name(path: &Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn read_zip_directory<R: Read + Seek>(reader: &mut R) -> ArchiveResult<Vec<ZipEntry>> {
+    // The end of central directory record is at the end of the file, followed by a variable-length
+    // comment, so search backwards for its signature
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    if file_len < EOCD_LEN {
+        return Err(ArchiveError::InvalidZip);
+    }
+
+    let tail_len = file_len.min(EOCD_LEN + MAX_COMMENT_LEN);
+    reader.seek(SeekFrom::Start(file_len - tail_len))?;
+    let mut tail = vec![0; tail_len as usize];
+    reader.read_exact(&mut tail)?;
+
+    let eocd_idx = (0..=tail.len() - EOCD_LEN as usize)
+        .rev()
+        .find(|&i| read_u32(&tail, i) == EOCD_SIGNATURE)
+        .ok_or(ArchiveError::InvalidZip)?;
+    let eocd = &tail[eocd_idx..];
+
+    let num_entries = read_u16(eocd, 10);
+    let directory_size = read_u32(eocd, 12);
+    let directory_offset = read_u32(eocd, 16);
+    if num_entries == u16::MAX || directory_size == u32::MAX || directory_offset == u32::MAX {
+        return Err(ArchiveError::Zip64);
+    }
+
+    reader.seek(SeekFrom::Start(directory_offset.into()))?;
+    let mut directory = vec![0; directory_size as usize];
+    reader.read_exact(&mut directory)?;
+
+    let mut entries = Vec::with_capacity(num_entries.into());
+    let mut i = 0;
+    for _ in 0..num_entries {
+        if i + 46 > directory.len() || read_u32(&directory, i) != CENTRAL_DIRECTORY_SIGNATURE {
+            return Err(ArchiveError::InvalidZip);
+        }
+
+        let name_len: usize = read_u16(&directory, i + 28).into();
+        let extra_len: usize = read_u16(&directory, i + 30).into();
+        let comment_len: usize = read_u16(&directory, i + 32).into();
+
+        let name_bytes =
+            directory.get(i + 46..i + 46 + name_len).ok_or(ArchiveError::InvalidZip)?;
+        let name = String::from_utf8_lossy(name_bytes).into_owned();
+
+        if !name.ends_with('/') {
+            entries.push(ZipEntry {
+                name,
+                flags: read_u16(&directory, i + 8),
+                method: read_u16(&directory, i + 10),
+                crc32: read_u32(&directory, i + 16),
+                compressed_size: read_u32(&directory, i + 20).into(),
+                uncompressed_size: read_u32(&directory, i + 24).into(),
+                local_header_offset: read_u32(&directory, i + 42).into(),
+            });
+        }
+
+        i += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(entries)
+}
+
+fn read_zip_entry<R: Read + Seek>(reader: &mut R, name: &str) -> ArchiveResult<Vec<u8>> {
+    let entry = read_zip_directory(reader)?
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| ArchiveError::EntryNotFound(name.into()))?;
+
+    if entry.flags & 1 != 0 {
+        return Err(ArchiveError::Encrypted(entry.name));
+    }
+
+    // The local header duplicates most of the central directory fields, but its name and extra
+    // field lengths can differ
+    reader.seek(SeekFrom::Start(entry.local_header_offset))?;
+    let mut local_header = [0; 30];
+    reader.read_exact(&mut local_header)?;
+    if read_u32(&local_header, 0) != LOCAL_HEADER_SIGNATURE {
+        return Err(ArchiveError::InvalidZip);
+    }
+
+    let name_len = read_u16(&local_header, 26);
+    let extra_len = read_u16(&local_header, 28);
+    reader.seek(SeekFrom::Current(i64::from(name_len) + i64::from(extra_len)))?;
+
+    let mut compressed = vec![0; entry.compressed_size as usize];
+    reader.read_exact(&mut compressed)?;
+
+    let contents = match entry.method {
+        METHOD_STORED => compressed,
+        METHOD_DEFLATE => {
+            let mut contents = Vec::with_capacity(entry.uncompressed_size as usize);
+            DeflateDecoder::new(compressed.as_slice()).read_to_end(&mut contents)?;
+            contents
+        }
+        method => return Err(ArchiveError::UnsupportedCompression { name: entry.name, method }),
+    };
+
+    let actual = CRC.checksum(&contents);
+    if actual != entry.crc32 {
+        return Err(ArchiveError::Checksum { name: entry.name, expected: entry.crc32, actual });
+    }
+
+    Ok(contents)
+}
+
+fn read_u16(bytes: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([bytes[i], bytes[i + 1]])
+}
+
+fn read_u32(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::DeflateEncoder;
+    use std::io::{Cursor, Write};
+
+    fn build_zip(files: &[(&str, &[u8], u16)]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut directory = Vec::new();
+
+        for &(name, contents, method) in files {
+            let data = match method {
+                METHOD_DEFLATE => {
+                    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(contents).unwrap();
+                    encoder.finish().unwrap()
+                }
+                _ => contents.to_vec(),
+            };
+            let crc32 = CRC.checksum(contents);
+            let offset = zip.len() as u32;
+
+            zip.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            zip.extend([20, 0, 0, 0]);
+            zip.extend(method.to_le_bytes());
+            zip.extend([0; 4]);
+            zip.extend(crc32.to_le_bytes());
+            zip.extend((data.len() as u32).to_le_bytes());
+            zip.extend((contents.len() as u32).to_le_bytes());
+            zip.extend((name.len() as u16).to_le_bytes());
+            zip.extend([0; 2]);
+            zip.extend(name.as_bytes());
+            zip.extend(&data);
+
+            directory.extend(CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+            directory.extend([20, 0, 20, 0, 0, 0]);
+            directory.extend(method.to_le_bytes());
+            directory.extend([0; 4]);
+            directory.extend(crc32.to_le_bytes());
+            directory.extend((data.len() as u32).to_le_bytes());
+            directory.extend((contents.len() as u32).to_le_bytes());
+            directory.extend((name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(name.as_bytes());
+        }
+
+        let directory_offset = zip.len() as u32;
+        zip.extend(&directory);
+
+        zip.extend(EOCD_SIGNATURE.to_le_bytes());
+        zip.extend([0; 4]);
+        zip.extend((files.len() as u16).to_le_bytes());
+        zip.extend((files.len() as u16).to_le_bytes());
+        zip.extend((directory.len() as u32).to_le_bytes());
+        zip.extend(directory_offset.to_le_bytes());
+        zip.extend([0; 2]);
+
+        zip
+    }
+
+    #[test]
+    fn zip_stored_and_deflate() {
+        let rom = b"SEGA GENESIS SEGA GENESIS SEGA GENESIS";
+        let zip =
+            build_zip(&[("readme.txt", b"hello", METHOD_STORED), ("game.md", rom, METHOD_DEFLATE)]);
+
+        let mut reader = Cursor::new(zip);
+        let names: Vec<_> =
+            read_zip_directory(&mut reader).unwrap().into_iter().map(|entry| entry.name).collect();
+        assert_eq!(names, vec!["readme.txt", "game.md"]);
+
+        assert_eq!(read_zip_entry(&mut reader, "readme.txt").unwrap(), b"hello");
+        assert_eq!(read_zip_entry(&mut reader, "game.md").unwrap(), rom);
+        assert!(matches!(
+            read_zip_entry(&mut reader, "game.bin"),
+            Err(ArchiveError::EntryNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn zip_checksum_mismatch() {
+        let mut zip = build_zip(&[("game.sfc", b"abcdef", METHOD_STORED)]);
+
+        // Corrupt the stored file contents, which start after the 30-byte header and 8-byte name
+        zip[38] ^= 0xFF;
+
+        assert!(matches!(
+            read_zip_entry(&mut Cursor::new(zip), "game.sfc"),
+            Err(ArchiveError::Checksum { .. })
+        ));
+    }
+
+    #[test]
+    fn not_a_zip() {
+        let mut reader = Cursor::new(vec![0; 100]);
+        assert!(matches!(read_zip_directory(&mut reader), Err(ArchiveError::InvalidZip)));
+    }
+}
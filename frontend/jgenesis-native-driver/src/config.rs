@@ -1,14 +1,17 @@
 pub mod input;
 
 use crate::config::input::{
-    GameBoyInputConfig, GenesisInputConfig, HotkeyConfig, JoystickInput, KeyboardInput,
-    NesControllerType, NesInputConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig,
-    SuperScopeConfig, ZapperConfig,
+    GameBoyInputConfig, GenesisInputConfig, GenesisMouseConfig, HotkeyConfig, JoystickInput,
+    KeyboardInput, LightGunConfig, LightPhaserConfig, NesControllerType, NesInputConfig,
+    PaddleConfig, SmsGgInputConfig, SnesControllerType, SnesInputConfig, SnesMouseConfig,
+    SportsPadConfig, SuperScopeConfig, ZapperConfig,
 };
 use gb_core::api::{GameBoyEmulatorConfig, GbAspectRatio, GbPalette, GbcColorCorrection};
 use genesis_core::{
-    GenesisAspectRatio, GenesisControllerType, GenesisEmulatorConfig, GenesisRegion,
+    GenesisAspectRatio, GenesisControllerType, GenesisEmulatorConfig, GenesisPadType,
+    GenesisRegion,
 };
+use jgenesis_common::debug::TraceRange;
 use jgenesis_common::frontend::{PixelAspectRatio, TimingMode};
 use jgenesis_proc_macros::{ConfigDisplay, EnumDisplay, EnumFromStr};
 use jgenesis_renderer::config::RendererConfig;
@@ -16,7 +19,7 @@ use nes_core::api::{NesAspectRatio, NesEmulatorConfig, Overscan};
 use segacd_core::api::SegaCdEmulatorConfig;
 use serde::{Deserialize, Serialize};
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::{SmsGgEmulatorConfig, SmsRegion, VdpVersion};
+use smsgg_core::{SmsControllerType, SmsGgEmulatorConfig, SmsRegion, VdpVersion};
 use snes_core::api::{CoprocessorRomFn, CoprocessorRoms, SnesAspectRatio, SnesEmulatorConfig};
 use std::fs;
 use std::num::NonZeroU64;
@@ -102,12 +105,31 @@ pub struct CommonConfig<KeyboardConfig, JoystickConfig> {
     #[indent_nested]
     pub hotkeys: HotkeyConfig,
     pub hide_cursor_over_window: bool,
+    pub screenshot_directory: Option<String>,
+    pub screenshot_aspect_ratio_correction: bool,
+    /// Directory for per-ROM cheat files; see [`cheat_file_path`](crate::cheat_file_path)
+    pub cheat_directory: Option<String>,
+    pub patch_path: Option<String>,
+    pub archive_entry: Option<String>,
+    pub gdb_port: Option<u16>,
+    pub gdb_target: Option<String>,
+    pub trace_cpu: Option<String>,
+    #[debug_fmt]
+    pub trace_ranges: Vec<TraceRange>,
 }
 
 #[derive(Debug, Clone, ConfigDisplay)]
 pub struct SmsGgConfig {
     #[indent_nested]
     pub common: CommonConfig<SmsGgInputConfig<KeyboardInput>, SmsGgInputConfig<JoystickInput>>,
+    pub p1_controller_type: SmsControllerType,
+    pub p2_controller_type: SmsControllerType,
+    #[indent_nested]
+    pub light_phaser_config: LightPhaserConfig,
+    #[indent_nested]
+    pub paddle_config: PaddleConfig,
+    #[indent_nested]
+    pub sports_pad_config: SportsPadConfig,
     pub vdp_version: Option<VdpVersion>,
     pub psg_version: Option<PsgVersion>,
     pub remove_sprite_limit: bool,
@@ -141,6 +163,8 @@ impl SmsGgConfig {
             sms_crop_left_border: self.sms_crop_left_border,
             fm_sound_unit_enabled: self.fm_sound_unit_enabled,
             overclock_z80: self.overclock_z80,
+            p1_controller_type: self.p1_controller_type,
+            p2_controller_type: self.p2_controller_type,
         }
     }
 }
@@ -181,6 +205,12 @@ pub struct GenesisConfig {
     pub common: CommonConfig<GenesisInputConfig<KeyboardInput>, GenesisInputConfig<JoystickInput>>,
     pub p1_controller_type: GenesisControllerType,
     pub p2_controller_type: GenesisControllerType,
+    #[debug_fmt]
+    pub multitap_pad_types: [GenesisPadType; 8],
+    #[indent_nested]
+    pub mouse_config: GenesisMouseConfig,
+    #[indent_nested]
+    pub light_gun_config: LightGunConfig,
     pub forced_timing_mode: Option<TimingMode>,
     pub forced_region: Option<GenesisRegion>,
     pub aspect_ratio: GenesisAspectRatio,
@@ -208,6 +238,7 @@ impl GenesisConfig {
             quantize_ym2612_output: self.quantize_ym2612_output,
             p1_controller_type: self.p1_controller_type,
             p2_controller_type: self.p2_controller_type,
+            multitap_pad_types: self.multitap_pad_types,
         }
     }
 }
@@ -240,6 +271,7 @@ pub struct NesConfig {
     #[indent_nested]
     pub zapper_config: ZapperConfig,
     pub forced_timing_mode: Option<TimingMode>,
+    pub fds_bios_path: Option<String>,
     pub aspect_ratio: NesAspectRatio,
     pub overscan: Overscan,
     pub remove_sprite_limit: bool,
@@ -268,9 +300,12 @@ impl NesConfig {
 pub struct SnesConfig {
     #[indent_nested]
     pub common: CommonConfig<SnesInputConfig<KeyboardInput>, SnesInputConfig<JoystickInput>>,
+    pub p1_controller_type: SnesControllerType,
     pub p2_controller_type: SnesControllerType,
     #[indent_nested]
     pub super_scope_config: SuperScopeConfig,
+    #[indent_nested]
+    pub mouse_config: SnesMouseConfig,
     pub forced_timing_mode: Option<TimingMode>,
     pub aspect_ratio: SnesAspectRatio,
     pub audio_60hz_hack: bool,
@@ -5,12 +5,12 @@ use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use gb_core::inputs::GameBoyButton;
-use genesis_core::input::GenesisButton;
+use genesis_core::input::{GenesisControllerButton, GenesisMouseButton, LightGunButton};
 use jgenesis_common::input::Player;
 use jgenesis_proc_macros::{ConfigDisplay, EnumDisplay, EnumFromStr};
 use nes_core::input::NesButton;
-use smsgg_core::SmsGgButton;
-use snes_core::input::{SnesControllerButton, SuperScopeButton};
+use smsgg_core::{LightPhaserButton, PaddleButton, SmsGgControllerButton, SportsPadButton};
+use snes_core::input::{SnesControllerButton, SnesMouseButton, SuperScopeButton};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AxisDirection {
@@ -243,6 +243,7 @@ macro_rules! define_input_config {
         input_cfg: $input_cfg:ident,
         controller_cfg: $controller_cfg:ident,
         button: $button_t:ident
+        $(, extra_players: [$($extra_field:ident: $extra_player:ident),+ $(,)?])?
         $(, console_button: $console_btn_field:ident: button $console_btn:ident default $console_btn_default:ident),*
         $(,)?
     ) => {
@@ -252,6 +253,11 @@ macro_rules! define_input_config {
             pub p1: $controller_cfg<Input>,
             #[indent_nested]
             pub p2: $controller_cfg<Input>,
+            $($(
+                #[serde(default = "Default::default")]
+                #[indent_nested]
+                pub $extra_field: $controller_cfg<Input>,
+            )+)?
             $(pub $console_btn_field: Option<Input>,)*
         }
 
@@ -260,6 +266,7 @@ macro_rules! define_input_config {
                 Self {
                     p1: $controller_cfg::default_p1(),
                     p2: $controller_cfg::default(),
+                    $($($extra_field: $controller_cfg::default(),)+)?
                     $($console_btn_field: Some(KeyboardInput { keycode: Keycode::$console_btn_default.name() }),)*
                 }
             }
@@ -270,6 +277,7 @@ macro_rules! define_input_config {
                 Self {
                     p1: $controller_cfg::default(),
                     p2: $controller_cfg::default(),
+                    $($($extra_field: $controller_cfg::default(),)+)?
                     $($console_btn_field: None,)*
                 }
             }
@@ -281,6 +289,7 @@ macro_rules! define_input_config {
 
             #[inline]
             #[must_use]
+            #[allow(unreachable_patterns)]
             fn get_input(&self, button: $button_t, player: Player) -> Option<&Input> {
                 match (button, player) {
                     $(
@@ -288,10 +297,15 @@ macro_rules! define_input_config {
                     )*
                     (_, Player::One) => self.p1.get_button(button),
                     (_, Player::Two) => self.p2.get_button(button),
+                    $($(
+                        (_, Player::$extra_player) => self.$extra_field.get_button(button),
+                    )+)?
+                    _ => None,
                 }
             }
 
             #[inline]
+            #[allow(unreachable_patterns)]
             fn set_input(&mut self, button: $button_t, player: Player, input: Input) {
                 match (button, player) {
                     $(
@@ -299,10 +313,15 @@ macro_rules! define_input_config {
                     )*
                     (_, Player::One) => self.p1.set_button(button, input),
                     (_, Player::Two) => self.p2.set_button(button, input),
+                    $($(
+                        (_, Player::$extra_player) => self.$extra_field.set_button(button, input),
+                    )+)?
+                    _ => {}
                 }
             }
 
             #[inline]
+            #[allow(unreachable_patterns)]
             fn clear_input(&mut self, button: $button_t, player: Player) {
                 match (button, player) {
                     $(
@@ -310,13 +329,17 @@ macro_rules! define_input_config {
                     )*
                     (_, Player::One) => self.p1.clear_button(button),
                     (_, Player::Two) => self.p2.clear_button(button),
+                    $($(
+                        (_, Player::$extra_player) => self.$extra_field.clear_button(button),
+                    )+)?
+                    _ => {}
                 }
             }
         }
     }
 }
 
-define_controller_config!(controller_cfg: SmsGgControllerConfig, button: SmsGgButton, fields: [
+define_controller_config!(controller_cfg: SmsGgControllerConfig, button: SmsGgControllerButton, fields: [
     up: button Up default Up,
     left: button Left default Left,
     right: button Right default Right,
@@ -328,11 +351,11 @@ define_controller_config!(controller_cfg: SmsGgControllerConfig, button: SmsGgBu
 define_input_config!(
     input_cfg: SmsGgInputConfig,
     controller_cfg: SmsGgControllerConfig,
-    button: SmsGgButton,
+    button: SmsGgControllerButton,
     console_button: pause: button Pause default Return,
 );
 
-define_controller_config!(controller_cfg: GenesisControllerConfig, button: GenesisButton, fields: [
+define_controller_config!(controller_cfg: GenesisControllerConfig, button: GenesisControllerButton, fields: [
     up: button Up default Up,
     left: button Left default Left,
     right: button Right default Right,
@@ -347,7 +370,12 @@ define_controller_config!(controller_cfg: GenesisControllerConfig, button: Genes
     mode: button Mode default RShift,
 ]);
 
-define_input_config!(input_cfg: GenesisInputConfig, controller_cfg: GenesisControllerConfig, button: GenesisButton);
+define_input_config!(
+    input_cfg: GenesisInputConfig,
+    controller_cfg: GenesisControllerConfig,
+    button: GenesisControllerButton,
+    extra_players: [p3: Three, p4: Four, p5: Five, p6: Six, p7: Seven, p8: Eight],
+);
 
 define_controller_config!(controller_cfg: NesControllerConfig, button: NesButton, fields: [
     up: button Up default Up,
@@ -385,6 +413,7 @@ define_input_config!(
     input_cfg: SnesInputConfig,
     controller_cfg: SnesControllerConfig,
     button: SnesControllerButton,
+    extra_players: [p3: Three, p4: Four, p5: Five],
 );
 
 define_controller_config!(controller_cfg: GameBoyInputConfig, button: GameBoyButton, fields: [
@@ -431,6 +460,180 @@ impl<Input> InputConfig for GameBoyInputConfig<Input> {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct GenesisMouseConfig {
+    pub left: Option<KeyboardOrMouseInput>,
+    pub right: Option<KeyboardOrMouseInput>,
+    pub middle: Option<KeyboardOrMouseInput>,
+    pub start: Option<KeyboardOrMouseInput>,
+}
+
+impl Default for GenesisMouseConfig {
+    fn default() -> Self {
+        Self {
+            left: Some(KeyboardOrMouseInput::MouseLeft),
+            right: Some(KeyboardOrMouseInput::MouseRight),
+            middle: Some(KeyboardOrMouseInput::MouseMiddle),
+            start: Some(KeyboardOrMouseInput::Keyboard("Return".into())),
+        }
+    }
+}
+
+impl GenesisMouseConfig {
+    #[inline]
+    #[must_use]
+    pub fn get_button(&self, button: GenesisMouseButton) -> Option<&KeyboardOrMouseInput> {
+        match button {
+            GenesisMouseButton::Left => self.left.as_ref(),
+            GenesisMouseButton::Right => self.right.as_ref(),
+            GenesisMouseButton::Middle => self.middle.as_ref(),
+            GenesisMouseButton::Start => self.start.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: GenesisMouseButton, input: Option<KeyboardOrMouseInput>) {
+        match button {
+            GenesisMouseButton::Left => self.left = input,
+            GenesisMouseButton::Right => self.right = input,
+            GenesisMouseButton::Middle => self.middle = input,
+            GenesisMouseButton::Start => self.start = input,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct LightGunConfig {
+    pub trigger: Option<KeyboardOrMouseInput>,
+    pub a: Option<KeyboardOrMouseInput>,
+    pub b: Option<KeyboardOrMouseInput>,
+    pub start: Option<KeyboardOrMouseInput>,
+}
+
+impl Default for LightGunConfig {
+    fn default() -> Self {
+        Self {
+            trigger: Some(KeyboardOrMouseInput::MouseLeft),
+            a: Some(KeyboardOrMouseInput::MouseRight),
+            b: Some(KeyboardOrMouseInput::MouseMiddle),
+            start: Some(KeyboardOrMouseInput::Keyboard("Return".into())),
+        }
+    }
+}
+
+impl LightGunConfig {
+    #[inline]
+    #[must_use]
+    pub fn get_button(&self, button: LightGunButton) -> Option<&KeyboardOrMouseInput> {
+        match button {
+            LightGunButton::Trigger => self.trigger.as_ref(),
+            LightGunButton::A => self.a.as_ref(),
+            LightGunButton::B => self.b.as_ref(),
+            LightGunButton::Start => self.start.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: LightGunButton, input: Option<KeyboardOrMouseInput>) {
+        match button {
+            LightGunButton::Trigger => self.trigger = input,
+            LightGunButton::A => self.a = input,
+            LightGunButton::B => self.b = input,
+            LightGunButton::Start => self.start = input,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct LightPhaserConfig {
+    pub trigger: Option<KeyboardOrMouseInput>,
+}
+
+impl Default for LightPhaserConfig {
+    fn default() -> Self {
+        Self { trigger: Some(KeyboardOrMouseInput::MouseLeft) }
+    }
+}
+
+impl LightPhaserConfig {
+    #[inline]
+    #[must_use]
+    pub fn get_button(&self, button: LightPhaserButton) -> Option<&KeyboardOrMouseInput> {
+        match button {
+            LightPhaserButton::Trigger => self.trigger.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: LightPhaserButton, input: Option<KeyboardOrMouseInput>) {
+        match button {
+            LightPhaserButton::Trigger => self.trigger = input,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct PaddleConfig {
+    pub button: Option<KeyboardOrMouseInput>,
+}
+
+impl Default for PaddleConfig {
+    fn default() -> Self {
+        Self { button: Some(KeyboardOrMouseInput::MouseLeft) }
+    }
+}
+
+impl PaddleConfig {
+    #[inline]
+    #[must_use]
+    pub fn get_button(&self, button: PaddleButton) -> Option<&KeyboardOrMouseInput> {
+        match button {
+            PaddleButton::Button => self.button.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: PaddleButton, input: Option<KeyboardOrMouseInput>) {
+        match button {
+            PaddleButton::Button => self.button = input,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct SportsPadConfig {
+    pub button1: Option<KeyboardOrMouseInput>,
+    pub button2: Option<KeyboardOrMouseInput>,
+}
+
+impl Default for SportsPadConfig {
+    fn default() -> Self {
+        Self {
+            button1: Some(KeyboardOrMouseInput::MouseLeft),
+            button2: Some(KeyboardOrMouseInput::MouseRight),
+        }
+    }
+}
+
+impl SportsPadConfig {
+    #[inline]
+    #[must_use]
+    pub fn get_button(&self, button: SportsPadButton) -> Option<&KeyboardOrMouseInput> {
+        match button {
+            SportsPadButton::Button1 => self.button1.as_ref(),
+            SportsPadButton::Button2 => self.button2.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: SportsPadButton, input: Option<KeyboardOrMouseInput>) {
+        match button {
+            SportsPadButton::Button1 => self.button1 = input,
+            SportsPadButton::Button2 => self.button2 = input,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
 pub struct ZapperConfig {
     pub fire: Option<KeyboardOrMouseInput>,
@@ -523,7 +726,45 @@ impl SuperScopeConfig {
 pub enum SnesControllerType {
     #[default]
     Gamepad,
+    Mouse,
+    /// Port 2 only
     SuperScope,
+    /// Super Multitap for players 2-5; port 2 only
+    SuperMultitap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ConfigDisplay)]
+pub struct SnesMouseConfig {
+    pub left: Option<KeyboardOrMouseInput>,
+    pub right: Option<KeyboardOrMouseInput>,
+}
+
+impl Default for SnesMouseConfig {
+    fn default() -> Self {
+        Self {
+            left: Some(KeyboardOrMouseInput::MouseLeft),
+            right: Some(KeyboardOrMouseInput::MouseRight),
+        }
+    }
+}
+
+impl SnesMouseConfig {
+    #[inline]
+    #[must_use]
+    pub fn get_button(&self, button: SnesMouseButton) -> Option<&KeyboardOrMouseInput> {
+        match button {
+            SnesMouseButton::Left => self.left.as_ref(),
+            SnesMouseButton::Right => self.right.as_ref(),
+        }
+    }
+
+    #[inline]
+    pub fn set_button(&mut self, button: SnesMouseButton, input: Option<KeyboardOrMouseInput>) {
+        match button {
+            SnesMouseButton::Left => self.left = input,
+            SnesMouseButton::Right => self.right = input,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, ConfigDisplay, Serialize, Deserialize)]
@@ -539,6 +780,16 @@ pub struct HotkeyConfig {
     pub save_state: Option<KeyboardInput>,
     #[serde(default = "default_load_state", deserialize_with = "deserialize_load_state")]
     pub load_state: Option<KeyboardInput>,
+    #[serde(
+        default = "default_next_save_state_slot",
+        deserialize_with = "deserialize_next_save_state_slot"
+    )]
+    pub next_save_state_slot: Option<KeyboardInput>,
+    #[serde(
+        default = "default_prev_save_state_slot",
+        deserialize_with = "deserialize_prev_save_state_slot"
+    )]
+    pub prev_save_state_slot: Option<KeyboardInput>,
     #[serde(default = "default_soft_reset", deserialize_with = "deserialize_soft_reset")]
     pub soft_reset: Option<KeyboardInput>,
     #[serde(default = "default_hard_reset", deserialize_with = "deserialize_hard_reset")]
@@ -553,6 +804,52 @@ pub struct HotkeyConfig {
     pub rewind: Option<KeyboardInput>,
     #[serde(default = "default_open_debugger", deserialize_with = "deserialize_open_debugger")]
     pub open_debugger: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_movie_recording",
+        deserialize_with = "deserialize_toggle_movie_recording"
+    )]
+    pub toggle_movie_recording: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_movie_playback",
+        deserialize_with = "deserialize_toggle_movie_playback"
+    )]
+    pub toggle_movie_playback: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_movie_read_only",
+        deserialize_with = "deserialize_toggle_movie_read_only"
+    )]
+    pub toggle_movie_read_only: Option<KeyboardInput>,
+    #[serde(default = "default_screenshot", deserialize_with = "deserialize_screenshot")]
+    pub screenshot: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_audio_recording",
+        deserialize_with = "deserialize_toggle_audio_recording"
+    )]
+    pub toggle_audio_recording: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_video_recording",
+        deserialize_with = "deserialize_toggle_video_recording"
+    )]
+    pub toggle_video_recording: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_vgm_logging",
+        deserialize_with = "deserialize_toggle_vgm_logging"
+    )]
+    pub toggle_vgm_logging: Option<KeyboardInput>,
+    #[serde(default = "default_export_spc", deserialize_with = "deserialize_export_spc")]
+    pub export_spc: Option<KeyboardInput>,
+    #[serde(
+        default = "default_switch_fds_disk_side",
+        deserialize_with = "deserialize_switch_fds_disk_side"
+    )]
+    pub switch_fds_disk_side: Option<KeyboardInput>,
+    #[serde(default = "default_toggle_cheats", deserialize_with = "deserialize_toggle_cheats")]
+    pub toggle_cheats: Option<KeyboardInput>,
+    #[serde(
+        default = "default_toggle_trace_logging",
+        deserialize_with = "deserialize_toggle_trace_logging"
+    )]
+    pub toggle_trace_logging: Option<KeyboardInput>,
 }
 
 impl Default for HotkeyConfig {
@@ -562,6 +859,8 @@ impl Default for HotkeyConfig {
             toggle_fullscreen: default_toggle_fullscreen(),
             save_state: default_save_state(),
             load_state: default_load_state(),
+            next_save_state_slot: default_next_save_state_slot(),
+            prev_save_state_slot: default_prev_save_state_slot(),
             soft_reset: default_soft_reset(),
             hard_reset: default_hard_reset(),
             pause: default_pause(),
@@ -569,6 +868,17 @@ impl Default for HotkeyConfig {
             fast_forward: default_fast_forward(),
             rewind: default_rewind(),
             open_debugger: default_open_debugger(),
+            toggle_movie_recording: default_toggle_movie_recording(),
+            toggle_movie_playback: default_toggle_movie_playback(),
+            toggle_movie_read_only: default_toggle_movie_read_only(),
+            screenshot: default_screenshot(),
+            toggle_audio_recording: default_toggle_audio_recording(),
+            toggle_video_recording: default_toggle_video_recording(),
+            toggle_vgm_logging: default_toggle_vgm_logging(),
+            export_spc: default_export_spc(),
+            switch_fds_disk_side: default_switch_fds_disk_side(),
+            toggle_cheats: default_toggle_cheats(),
+            toggle_trace_logging: default_toggle_trace_logging(),
         }
     }
 }
@@ -595,6 +905,14 @@ fn default_load_state() -> Option<KeyboardInput> {
     key_input!(F6)
 }
 
+fn default_next_save_state_slot() -> Option<KeyboardInput> {
+    key_input!(F8)
+}
+
+fn default_prev_save_state_slot() -> Option<KeyboardInput> {
+    key_input!(F7)
+}
+
 fn default_soft_reset() -> Option<KeyboardInput> {
     key_input!(F1)
 }
@@ -623,6 +941,50 @@ fn default_open_debugger() -> Option<KeyboardInput> {
     key_input!(Quote)
 }
 
+fn default_toggle_movie_recording() -> Option<KeyboardInput> {
+    key_input!(F10)
+}
+
+fn default_toggle_movie_playback() -> Option<KeyboardInput> {
+    key_input!(F11)
+}
+
+fn default_toggle_movie_read_only() -> Option<KeyboardInput> {
+    None
+}
+
+fn default_screenshot() -> Option<KeyboardInput> {
+    key_input!(F12)
+}
+
+fn default_toggle_audio_recording() -> Option<KeyboardInput> {
+    key_input!(F3)
+}
+
+fn default_toggle_video_recording() -> Option<KeyboardInput> {
+    key_input!(F4)
+}
+
+fn default_toggle_vgm_logging() -> Option<KeyboardInput> {
+    None
+}
+
+fn default_export_spc() -> Option<KeyboardInput> {
+    None
+}
+
+fn default_switch_fds_disk_side() -> Option<KeyboardInput> {
+    key_input!(Backslash)
+}
+
+fn default_toggle_cheats() -> Option<KeyboardInput> {
+    None
+}
+
+fn default_toggle_trace_logging() -> Option<KeyboardInput> {
+    None
+}
+
 macro_rules! impl_deserialize_or_default {
     ($name:ident, $default_fn:ident) => {
         fn $name<'de, D>(deserializer: D) -> Result<Option<KeyboardInput>, D::Error>
@@ -638,6 +1000,8 @@ impl_deserialize_or_default!(deserialize_quit, default_quit);
 impl_deserialize_or_default!(deserialize_toggle_fullscreen, default_toggle_fullscreen);
 impl_deserialize_or_default!(deserialize_save_state, default_save_state);
 impl_deserialize_or_default!(deserialize_load_state, default_load_state);
+impl_deserialize_or_default!(deserialize_next_save_state_slot, default_next_save_state_slot);
+impl_deserialize_or_default!(deserialize_prev_save_state_slot, default_prev_save_state_slot);
 impl_deserialize_or_default!(deserialize_soft_reset, default_soft_reset);
 impl_deserialize_or_default!(deserialize_hard_reset, default_hard_reset);
 impl_deserialize_or_default!(deserialize_pause, default_pause);
@@ -645,3 +1009,14 @@ impl_deserialize_or_default!(deserialize_step_frame, default_step_frame);
 impl_deserialize_or_default!(deserialize_fast_forward, default_fast_forward);
 impl_deserialize_or_default!(deserialize_rewind, default_rewind);
 impl_deserialize_or_default!(deserialize_open_debugger, default_open_debugger);
+impl_deserialize_or_default!(deserialize_toggle_movie_recording, default_toggle_movie_recording);
+impl_deserialize_or_default!(deserialize_toggle_movie_playback, default_toggle_movie_playback);
+impl_deserialize_or_default!(deserialize_toggle_movie_read_only, default_toggle_movie_read_only);
+impl_deserialize_or_default!(deserialize_screenshot, default_screenshot);
+impl_deserialize_or_default!(deserialize_toggle_audio_recording, default_toggle_audio_recording);
+impl_deserialize_or_default!(deserialize_toggle_video_recording, default_toggle_video_recording);
+impl_deserialize_or_default!(deserialize_toggle_vgm_logging, default_toggle_vgm_logging);
+impl_deserialize_or_default!(deserialize_export_spc, default_export_spc);
+impl_deserialize_or_default!(deserialize_switch_fds_disk_side, default_switch_fds_disk_side);
+impl_deserialize_or_default!(deserialize_toggle_cheats, default_toggle_cheats);
+impl_deserialize_or_default!(deserialize_toggle_trace_logging, default_toggle_trace_logging);
@@ -1,11 +1,15 @@
 use crate::config::input::{
-    AxisDirection, HatDirection, HotkeyConfig, InputConfig, JoystickAction, JoystickDeviceId,
-    JoystickInput, KeyboardInput, KeyboardOrMouseInput, NesControllerType, NesInputConfig,
-    SnesControllerType, SnesInputConfig, SuperScopeConfig, ZapperConfig,
+    AxisDirection, GenesisInputConfig, GenesisMouseConfig, HatDirection, HotkeyConfig, InputConfig,
+    JoystickAction, JoystickDeviceId, JoystickInput, KeyboardInput, KeyboardOrMouseInput,
+    LightGunConfig, LightPhaserConfig, NesControllerType, NesInputConfig, PaddleConfig,
+    SmsGgInputConfig, SnesControllerType, SnesInputConfig, SnesMouseConfig, SportsPadConfig,
+    SuperScopeConfig, ZapperConfig,
 };
 use crate::mainloop::{NativeEmulatorError, NativeEmulatorResult};
 use gb_core::inputs::{GameBoyButton, GameBoyInputs};
-use genesis_core::input::GenesisButton;
+use genesis_core::input::{
+    GenesisButton, GenesisControllerButton, GenesisMouseButton, LightGunButton,
+};
 use genesis_core::GenesisInputs;
 use jgenesis_common::frontend::FrameSize;
 use jgenesis_common::input::Player;
@@ -16,10 +20,13 @@ use sdl2::joystick::{HatState, Joystick};
 use sdl2::keyboard::Keycode;
 use sdl2::mouse::MouseButton;
 use sdl2::JoystickSubsystem;
-use smsgg_core::{SmsGgButton, SmsGgInputs};
+use smsgg_core::{
+    LightPhaserButton, PaddleButton, SmsGgButton, SmsGgControllerButton, SmsGgInputs,
+    SportsPadButton,
+};
 use snes_core::input::{
     SnesButton, SnesControllerButton, SnesInputDevice, SnesInputs, SnesJoypadState,
-    SuperScopeButton, SuperScopeState,
+    SnesMouseButton, SnesMouseState, SuperScopeButton, SuperScopeState,
 };
 use std::collections::HashMap;
 use std::hash::Hash;
@@ -78,12 +85,55 @@ impl MappableInputs<SmsGgButton> for SmsGgInputs {
     fn set_field(&mut self, button: SmsGgButton, player: Player, pressed: bool) {
         self.set_button(button, player, pressed);
     }
+
+    fn handle_mouse_motion(
+        &mut self,
+        x: i32,
+        y: i32,
+        frame_size: FrameSize,
+        display_area: DisplayArea,
+    ) {
+        // The emulator config decides which of these devices are connected
+        let position = viewport_position_to_frame_position(x, y, frame_size, display_area);
+        self.light_phaser.position = position;
+        self.sports_pad.position = position;
+
+        // The paddle knob follows the mouse's horizontal position and stays in place when the mouse
+        // leaves the frame
+        if let Some((frame_x, _)) = position {
+            let max_x = frame_size.width.saturating_sub(1).max(1);
+            self.paddle.position = (u32::from(frame_x) * 255 / max_x).min(255) as u8;
+        }
+    }
+
+    fn handle_mouse_leave(&mut self) {
+        self.light_phaser.position = None;
+        self.sports_pad.position = None;
+    }
 }
 
 impl MappableInputs<GenesisButton> for GenesisInputs {
     fn set_field(&mut self, button: GenesisButton, player: Player, pressed: bool) {
         self.set_button(button, player, pressed);
     }
+
+    fn handle_mouse_motion(
+        &mut self,
+        x: i32,
+        y: i32,
+        frame_size: FrameSize,
+        display_area: DisplayArea,
+    ) {
+        // The emulator config decides whether the mouse or the light gun is connected
+        let position = viewport_position_to_frame_position(x, y, frame_size, display_area);
+        self.mouse.position = position;
+        self.light_gun.position = position;
+    }
+
+    fn handle_mouse_leave(&mut self) {
+        self.mouse.position = None;
+        self.light_gun.position = None;
+    }
 }
 
 impl MappableInputs<NesButton> for NesInputs {
@@ -123,15 +173,25 @@ impl MappableInputs<SnesButton> for SnesInputs {
         frame_size: FrameSize,
         display_area: DisplayArea,
     ) {
-        if let SnesInputDevice::SuperScope(super_scope_state) = &mut self.p2 {
-            super_scope_state.position =
-                viewport_position_to_frame_position(x, y, frame_size, display_area);
+        let position = viewport_position_to_frame_position(x, y, frame_size, display_area);
+        for device in [&mut self.p1, &mut self.p2] {
+            match device {
+                SnesInputDevice::SuperScope(super_scope_state) => {
+                    super_scope_state.position = position;
+                }
+                SnesInputDevice::Mouse(mouse_state) => mouse_state.position = position,
+                SnesInputDevice::Controller(_) | SnesInputDevice::Multitap(_) => {}
+            }
         }
     }
 
     fn handle_mouse_leave(&mut self) {
-        if let SnesInputDevice::SuperScope(super_scope_state) = &mut self.p2 {
-            super_scope_state.position = None;
+        for device in [&mut self.p1, &mut self.p2] {
+            match device {
+                SnesInputDevice::SuperScope(super_scope_state) => super_scope_state.position = None,
+                SnesInputDevice::Mouse(mouse_state) => mouse_state.position = None,
+                SnesInputDevice::Controller(_) | SnesInputDevice::Multitap(_) => {}
+            }
         }
     }
 }
@@ -287,6 +347,185 @@ impl<Inputs, Button> InputMapper<Inputs, Button> {
     }
 }
 
+fn generate_genesis_key_or_mouse_mapping(
+    mouse_config: &GenesisMouseConfig,
+    light_gun_config: &LightGunConfig,
+) -> NativeEmulatorResult<HashMap<KeycodeOrMouseButton, Vec<GenesisButton>>> {
+    let mouse_buttons = [
+        GenesisMouseButton::Left,
+        GenesisMouseButton::Right,
+        GenesisMouseButton::Middle,
+        GenesisMouseButton::Start,
+    ]
+    .map(|button| (mouse_config.get_button(button), GenesisButton::Mouse(button)));
+    let light_gun_buttons =
+        [LightGunButton::Trigger, LightGunButton::A, LightGunButton::B, LightGunButton::Start]
+            .map(|button| (light_gun_config.get_button(button), GenesisButton::LightGun(button)));
+
+    let mut map: HashMap<KeycodeOrMouseButton, Vec<GenesisButton>> = HashMap::new();
+    for (input, button) in mouse_buttons.into_iter().chain(light_gun_buttons) {
+        let Some(input) = input else { continue };
+        let key_or_mouse_button = input.clone().try_into()?;
+        map.entry(key_or_mouse_button).or_default().push(button);
+    }
+
+    Ok(map)
+}
+
+fn convert_genesis_mapping<Input: Eq + Hash>(
+    map: HashMap<Input, Vec<(GenesisControllerButton, Player)>>,
+) -> HashMap<Input, Vec<(GenesisButton, Player)>> {
+    map.into_iter()
+        .map(|(input, buttons)| {
+            (
+                input,
+                buttons
+                    .into_iter()
+                    .map(|(button, player)| (GenesisButton::Controller(button), player))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+impl InputMapper<GenesisInputs, GenesisButton> {
+    pub(crate) fn new_genesis(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: &GenesisInputConfig<KeyboardInput>,
+        joystick_inputs: &GenesisInputConfig<JoystickInput>,
+        mouse_config: &GenesisMouseConfig,
+        light_gun_config: &LightGunConfig,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        let (keyboard_mapping, joystick_mapping) =
+            generate_mappings(keyboard_inputs, joystick_inputs, &GenesisControllerButton::ALL)?;
+
+        Ok(Self::new_internal(
+            GenesisInputs::default(),
+            joystick_subsystem,
+            convert_genesis_mapping(keyboard_mapping),
+            convert_genesis_mapping(joystick_mapping),
+            generate_genesis_key_or_mouse_mapping(mouse_config, light_gun_config)?,
+            axis_deadzone,
+        ))
+    }
+
+    pub(crate) fn reload_config_genesis(
+        &mut self,
+        keyboard_inputs: &GenesisInputConfig<KeyboardInput>,
+        joystick_inputs: &GenesisInputConfig<JoystickInput>,
+        mouse_config: &GenesisMouseConfig,
+        light_gun_config: &LightGunConfig,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        let (keyboard_mapping, joystick_mapping) =
+            generate_mappings(keyboard_inputs, joystick_inputs, &GenesisControllerButton::ALL)?;
+
+        self.reload_config_internal(
+            convert_genesis_mapping(keyboard_mapping),
+            convert_genesis_mapping(joystick_mapping),
+            generate_genesis_key_or_mouse_mapping(mouse_config, light_gun_config)?,
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
+fn generate_smsgg_key_or_mouse_mapping(
+    light_phaser_config: &LightPhaserConfig,
+    paddle_config: &PaddleConfig,
+    sports_pad_config: &SportsPadConfig,
+) -> NativeEmulatorResult<HashMap<KeycodeOrMouseButton, Vec<SmsGgButton>>> {
+    let light_phaser_buttons = [LightPhaserButton::Trigger]
+        .map(|button| (light_phaser_config.get_button(button), SmsGgButton::LightPhaser(button)));
+    let paddle_buttons = [PaddleButton::Button]
+        .map(|button| (paddle_config.get_button(button), SmsGgButton::Paddle(button)));
+    let sports_pad_buttons = [SportsPadButton::Button1, SportsPadButton::Button2]
+        .map(|button| (sports_pad_config.get_button(button), SmsGgButton::SportsPad(button)));
+
+    let mut map: HashMap<KeycodeOrMouseButton, Vec<SmsGgButton>> = HashMap::new();
+    for (input, button) in
+        light_phaser_buttons.into_iter().chain(paddle_buttons).chain(sports_pad_buttons)
+    {
+        let Some(input) = input else { continue };
+        let key_or_mouse_button = input.clone().try_into()?;
+        map.entry(key_or_mouse_button).or_default().push(button);
+    }
+
+    Ok(map)
+}
+
+fn convert_smsgg_mapping<Input: Eq + Hash>(
+    map: HashMap<Input, Vec<(SmsGgControllerButton, Player)>>,
+) -> HashMap<Input, Vec<(SmsGgButton, Player)>> {
+    map.into_iter()
+        .map(|(input, buttons)| {
+            (
+                input,
+                buttons
+                    .into_iter()
+                    .map(|(button, player)| (SmsGgButton::Controller(button), player))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+impl InputMapper<SmsGgInputs, SmsGgButton> {
+    pub(crate) fn new_smsgg(
+        joystick_subsystem: JoystickSubsystem,
+        keyboard_inputs: &SmsGgInputConfig<KeyboardInput>,
+        joystick_inputs: &SmsGgInputConfig<JoystickInput>,
+        light_phaser_config: &LightPhaserConfig,
+        paddle_config: &PaddleConfig,
+        sports_pad_config: &SportsPadConfig,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<Self> {
+        let (keyboard_mapping, joystick_mapping) =
+            generate_mappings(keyboard_inputs, joystick_inputs, &SmsGgControllerButton::ALL)?;
+
+        Ok(Self::new_internal(
+            SmsGgInputs::default(),
+            joystick_subsystem,
+            convert_smsgg_mapping(keyboard_mapping),
+            convert_smsgg_mapping(joystick_mapping),
+            generate_smsgg_key_or_mouse_mapping(
+                light_phaser_config,
+                paddle_config,
+                sports_pad_config,
+            )?,
+            axis_deadzone,
+        ))
+    }
+
+    pub(crate) fn reload_config_smsgg(
+        &mut self,
+        keyboard_inputs: &SmsGgInputConfig<KeyboardInput>,
+        joystick_inputs: &SmsGgInputConfig<JoystickInput>,
+        light_phaser_config: &LightPhaserConfig,
+        paddle_config: &PaddleConfig,
+        sports_pad_config: &SportsPadConfig,
+        axis_deadzone: i16,
+    ) -> NativeEmulatorResult<()> {
+        let (keyboard_mapping, joystick_mapping) =
+            generate_mappings(keyboard_inputs, joystick_inputs, &SmsGgControllerButton::ALL)?;
+
+        self.reload_config_internal(
+            convert_smsgg_mapping(keyboard_mapping),
+            convert_smsgg_mapping(joystick_mapping),
+            generate_smsgg_key_or_mouse_mapping(
+                light_phaser_config,
+                paddle_config,
+                sports_pad_config,
+            )?,
+            axis_deadzone,
+        );
+
+        Ok(())
+    }
+}
+
 fn generate_nes_key_or_mouse_mapping(
     config: &ZapperConfig,
 ) -> NativeEmulatorResult<HashMap<KeycodeOrMouseButton, Vec<NesButton>>> {
@@ -365,17 +604,23 @@ impl InputMapper<NesInputs, NesButton> {
 
 fn generate_snes_key_or_mouse_mapping(
     super_scope_config: &SuperScopeConfig,
+    mouse_config: &SnesMouseConfig,
 ) -> NativeEmulatorResult<HashMap<KeycodeOrMouseButton, Vec<SnesButton>>> {
+    let super_scope_buttons = [
+        SuperScopeButton::Fire,
+        SuperScopeButton::Cursor,
+        SuperScopeButton::Pause,
+        SuperScopeButton::TurboToggle,
+    ]
+    .map(|button| (super_scope_config.get_button(button), SnesButton::SuperScope(button)));
+    let mouse_buttons = [SnesMouseButton::Left, SnesMouseButton::Right]
+        .map(|button| (mouse_config.get_button(button), SnesButton::Mouse(button)));
+
     let mut map: HashMap<KeycodeOrMouseButton, Vec<SnesButton>> = HashMap::new();
-    for (input, button) in [
-        (&super_scope_config.fire, SuperScopeButton::Fire),
-        (&super_scope_config.cursor, SuperScopeButton::Cursor),
-        (&super_scope_config.pause, SuperScopeButton::Pause),
-        (&super_scope_config.turbo_toggle, SuperScopeButton::TurboToggle),
-    ] {
+    for (input, button) in super_scope_buttons.into_iter().chain(mouse_buttons) {
         let Some(input) = input else { continue };
         let key_or_mouse_button = input.clone().try_into()?;
-        map.entry(key_or_mouse_button).or_default().push(SnesButton::SuperScope(button));
+        map.entry(key_or_mouse_button).or_default().push(button);
     }
 
     Ok(map)
@@ -397,31 +642,43 @@ fn convert_snes_mapping<Input: Eq + Hash>(
         .collect()
 }
 
+fn default_snes_input_device(
+    controller_type: SnesControllerType,
+    super_scope_turbo: bool,
+) -> SnesInputDevice {
+    match controller_type {
+        SnesControllerType::Gamepad => SnesInputDevice::Controller(SnesJoypadState::default()),
+        SnesControllerType::Mouse => SnesInputDevice::Mouse(SnesMouseState::default()),
+        SnesControllerType::SuperScope => SnesInputDevice::SuperScope(SuperScopeState {
+            turbo: super_scope_turbo,
+            ..SuperScopeState::default()
+        }),
+        SnesControllerType::SuperMultitap => {
+            SnesInputDevice::Multitap([SnesJoypadState::default(); 4])
+        }
+    }
+}
+
 fn set_default_snes_inputs(
     inputs: &mut SnesInputs,
+    p1_controller_type: SnesControllerType,
     p2_controller_type: SnesControllerType,
     super_scope_turbo: bool,
 ) {
-    match p2_controller_type {
-        SnesControllerType::Gamepad => {
-            inputs.p2 = SnesInputDevice::Controller(SnesJoypadState::default());
-        }
-        SnesControllerType::SuperScope => {
-            inputs.p2 = SnesInputDevice::SuperScope(SuperScopeState {
-                turbo: super_scope_turbo,
-                ..SuperScopeState::default()
-            });
-        }
-    }
+    inputs.p1 = default_snes_input_device(p1_controller_type, super_scope_turbo);
+    inputs.p2 = default_snes_input_device(p2_controller_type, super_scope_turbo);
 }
 
 impl InputMapper<SnesInputs, SnesButton> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new_snes(
         joystick_subsystem: JoystickSubsystem,
+        p1_controller_type: SnesControllerType,
         p2_controller_type: SnesControllerType,
         keyboard_inputs: &SnesInputConfig<KeyboardInput>,
         joystick_inputs: &SnesInputConfig<JoystickInput>,
         super_scope_config: &SuperScopeConfig,
+        mouse_config: &SnesMouseConfig,
         axis_deadzone: i16,
     ) -> NativeEmulatorResult<Self> {
         let (keyboard_mapping, joystick_mapping) =
@@ -430,29 +687,37 @@ impl InputMapper<SnesInputs, SnesButton> {
         let joystick_mapping = convert_snes_mapping(joystick_mapping);
 
         let mut inputs = SnesInputs::default();
-        set_default_snes_inputs(&mut inputs, p2_controller_type, SuperScopeState::default().turbo);
+        set_default_snes_inputs(
+            &mut inputs,
+            p1_controller_type,
+            p2_controller_type,
+            SuperScopeState::default().turbo,
+        );
 
         Ok(Self::new_internal(
             inputs,
             joystick_subsystem,
             keyboard_mapping,
             joystick_mapping,
-            generate_snes_key_or_mouse_mapping(super_scope_config)?,
+            generate_snes_key_or_mouse_mapping(super_scope_config, mouse_config)?,
             axis_deadzone,
         ))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn reload_config_snes(
         &mut self,
+        p1_controller_type: SnesControllerType,
         p2_controller_type: SnesControllerType,
         keyboard_inputs: &SnesInputConfig<KeyboardInput>,
         joystick_inputs: &SnesInputConfig<JoystickInput>,
         super_scope_config: &SuperScopeConfig,
+        mouse_config: &SnesMouseConfig,
         axis_deadzone: i16,
     ) -> NativeEmulatorResult<()> {
         let existing_super_scope_turbo = match self.inputs.p2 {
             SnesInputDevice::SuperScope(super_scope_state) => super_scope_state.turbo,
-            SnesInputDevice::Controller(_) => SuperScopeState::default().turbo,
+            _ => SuperScopeState::default().turbo,
         };
 
         let (keyboard_mapping, joystick_mapping) =
@@ -463,10 +728,15 @@ impl InputMapper<SnesInputs, SnesButton> {
         self.reload_config_internal(
             keyboard_mapping,
             joystick_mapping,
-            generate_snes_key_or_mouse_mapping(super_scope_config)?,
+            generate_snes_key_or_mouse_mapping(super_scope_config, mouse_config)?,
             axis_deadzone,
         );
-        set_default_snes_inputs(&mut self.inputs, p2_controller_type, existing_super_scope_turbo);
+        set_default_snes_inputs(
+            &mut self.inputs,
+            p1_controller_type,
+            p2_controller_type,
+            existing_super_scope_turbo,
+        );
 
         Ok(())
     }
@@ -484,7 +754,7 @@ where
 {
     let mut keyboard_mapping: HashMap<Keycode, Vec<(Button, Player)>> = HashMap::new();
     let mut joystick_mapping: HashMap<JoystickInput, Vec<(Button, Player)>> = HashMap::new();
-    for player in [Player::One, Player::Two] {
+    for player in Player::ALL {
         for &button in all_buttons {
             if let Some(key) = keyboard_config.get_input(button, player) {
                 let Some(keycode) = Keycode::from_name(&key.keycode) else {
@@ -764,6 +1034,8 @@ pub enum Hotkey {
     ToggleFullscreen,
     SaveState,
     LoadState,
+    NextSaveStateSlot,
+    PrevSaveStateSlot,
     SoftReset,
     HardReset,
     Pause,
@@ -771,6 +1043,17 @@ pub enum Hotkey {
     FastForward,
     Rewind,
     OpenDebugger,
+    ToggleMovieRecording,
+    ToggleMoviePlayback,
+    ToggleMovieReadOnly,
+    Screenshot,
+    ToggleAudioRecording,
+    ToggleVideoRecording,
+    ToggleVgmLogging,
+    ExportSpc,
+    SwitchFdsDiskSide,
+    ToggleCheats,
+    ToggleTraceLogging,
 }
 
 pub(crate) enum HotkeyMapResult<'a> {
@@ -798,6 +1081,8 @@ impl HotkeyMapper {
             (&config.toggle_fullscreen, Hotkey::ToggleFullscreen),
             (&config.save_state, Hotkey::SaveState),
             (&config.load_state, Hotkey::LoadState),
+            (&config.next_save_state_slot, Hotkey::NextSaveStateSlot),
+            (&config.prev_save_state_slot, Hotkey::PrevSaveStateSlot),
             (&config.soft_reset, Hotkey::SoftReset),
             (&config.hard_reset, Hotkey::HardReset),
             (&config.pause, Hotkey::Pause),
@@ -805,6 +1090,17 @@ impl HotkeyMapper {
             (&config.fast_forward, Hotkey::FastForward),
             (&config.rewind, Hotkey::Rewind),
             (&config.open_debugger, Hotkey::OpenDebugger),
+            (&config.toggle_movie_recording, Hotkey::ToggleMovieRecording),
+            (&config.toggle_movie_playback, Hotkey::ToggleMoviePlayback),
+            (&config.toggle_movie_read_only, Hotkey::ToggleMovieReadOnly),
+            (&config.screenshot, Hotkey::Screenshot),
+            (&config.toggle_audio_recording, Hotkey::ToggleAudioRecording),
+            (&config.toggle_video_recording, Hotkey::ToggleVideoRecording),
+            (&config.toggle_vgm_logging, Hotkey::ToggleVgmLogging),
+            (&config.export_spc, Hotkey::ExportSpc),
+            (&config.switch_fds_disk_side, Hotkey::SwitchFdsDiskSide),
+            (&config.toggle_cheats, Hotkey::ToggleCheats),
+            (&config.toggle_trace_logging, Hotkey::ToggleTraceLogging),
         ] {
             if let Some(input) = input {
                 let keycode = Keycode::from_name(&input.keycode)
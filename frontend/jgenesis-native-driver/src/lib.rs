@@ -1,10 +1,15 @@
+pub mod archive;
 pub mod config;
 pub mod input;
 mod mainloop;
 
 pub use mainloop::{
-    create_gb, create_genesis, create_nes, create_sega_cd, create_smsgg, create_snes, AudioError,
-    NativeEmulator, NativeEmulatorResult, NativeGameBoyEmulator, NativeGenesisEmulator,
-    NativeNesEmulator, NativeSegaCdEmulator, NativeSmsGgEmulator, NativeSnesEmulator,
-    NativeTickEffect, SaveWriteError,
+    cheat_file_path, create_gb, create_genesis, create_nes, create_sega_cd, create_smsgg,
+    create_snes, create_spc_player, list_save_states, load_cheats, read_metadata,
+    read_save_state_slot, rom_base_path, save_cheats, save_state_path, AudioError, CheatEntry,
+    CheatFileError, MovieError, MovieStart, NativeEmulator, NativeEmulatorResult,
+    NativeGameBoyEmulator, NativeGenesisEmulator, NativeNesEmulator, NativeSegaCdEmulator,
+    NativeSmsGgEmulator, NativeSnesEmulator, NativeSpcPlayer, NativeTickEffect, RecordingError,
+    SaveStateConsole, SaveStateMetadata, SaveStateSlot, SaveStateThumbnail, SaveWriteError,
+    TraceError, VgmError, WavError, SAVE_STATE_SLOTS,
 };
@@ -1,47 +1,80 @@
 mod audio;
+mod cheats;
 mod debug;
+mod features;
 mod gb;
+mod gdb;
 mod genesis;
+mod movie;
 mod nes;
+mod recording;
 mod rewind;
 mod save;
+mod savestate;
+mod screenshot;
 mod smsgg;
 mod snes;
+#[cfg(test)]
+mod testutil;
+mod trace;
+mod vgm;
 
 pub use gb::{create_gb, NativeGameBoyEmulator};
 pub use genesis::{create_genesis, create_sega_cd, NativeGenesisEmulator, NativeSegaCdEmulator};
 pub use nes::{create_nes, NativeNesEmulator};
 pub use smsgg::{create_smsgg, NativeSmsGgEmulator};
-pub use snes::{create_snes, NativeSnesEmulator};
+pub use snes::{create_snes, create_spc_player, NativeSnesEmulator, NativeSpcPlayer};
 
+use crate::archive;
+use crate::archive::ArchiveError;
 use crate::config::{CommonConfig, WindowSize};
 use crate::input::{Hotkey, HotkeyMapResult, HotkeyMapper, InputMapper, Joysticks, MappableInputs};
 use crate::mainloop::audio::SdlAudioOutput;
 use crate::mainloop::debug::{DebugRenderFn, DebuggerWindow};
+use crate::mainloop::features::ConsoleFeatures;
+use crate::mainloop::gdb::GdbServer;
+use crate::mainloop::movie::{MovieProgress, MovieSession};
+use crate::mainloop::recording::AvRecorder;
 use crate::mainloop::rewind::Rewinder;
 use crate::mainloop::save::FsSaveWriter;
-pub use audio::AudioError;
-use bincode::error::{DecodeError, EncodeError};
+use crate::mainloop::trace::{TraceFile, TraceOptions};
+use crate::mainloop::vgm::VgmFile;
+pub use audio::{AudioError, WavError};
 use bincode::{Decode, Encode};
+use bincode::error::{DecodeError, EncodeError};
 use gb_core::api::GameBoyLoadError;
-use jgenesis_common::frontend::{EmulatorTrait, PartialClone, TickEffect};
+use jgenesis_common::bincode_config;
+use jgenesis_common::cheats::CheatSet;
+use jgenesis_common::debug::Debuggable;
+use jgenesis_common::frontend::{EmulatorTrait, TickEffect};
+use jgenesis_common::patch;
+use jgenesis_common::patch::PatchError;
 use jgenesis_renderer::renderer::{RendererError, WgpuRenderer};
 use nes_core::api::NesInitializationError;
+pub use cheats::{cheat_file_path, load_cheats, save_cheats, CheatEntry, CheatFileError};
+pub use movie::{MovieError, MovieStart};
+pub use recording::RecordingError;
 pub use save::SaveWriteError;
+pub use trace::TraceError;
+pub use vgm::VgmError;
+pub use savestate::{
+    list_save_states, read_metadata, read_save_state_slot, save_state_path, SaveStateConsole,
+    SaveStateMetadata, SaveStateSlot, SaveStateThumbnail, SAVE_STATE_SLOTS,
+};
 use sdl2::event::{Event, WindowEvent};
 use sdl2::render::TextureValueError;
 use sdl2::video::{FullscreenType, Window, WindowBuildError};
 use sdl2::{AudioSubsystem, EventPump, IntegerOrSdlError, JoystickSubsystem, Sdl, VideoSubsystem};
 use segacd_core::api::SegaCdLoadError;
 use snes_core::api::SnesLoadError;
+use snes_core::spc::SpcError;
 use std::error::Error;
 use std::ffi::{NulError, OsStr};
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
-use std::{io, thread};
+use std::{fs, io, thread};
 use thiserror::Error;
+use time::OffsetDateTime;
 
 trait RendererExt {
     fn focus(&mut self);
@@ -92,33 +125,117 @@ fn sleep(duration: Duration) {
 }
 
 struct HotkeyState<Emulator> {
-    save_state_path: PathBuf,
+    rom_base_path: PathBuf,
+    rom_name: String,
+    console: SaveStateConsole,
+    rom_checksum: u32,
+    save_state_slot: usize,
     paused: bool,
     should_step_frame: bool,
     fast_forward_multiplier: u64,
+    screenshot_directory: Option<PathBuf>,
+    screenshot_aspect_ratio_correction: bool,
     rewinder: Rewinder<Emulator>,
     debugger_window: Option<DebuggerWindow<Emulator>>,
     debug_render_fn: fn() -> Box<DebugRenderFn<Emulator>>,
+    cheats: Vec<CheatEntry>,
+    cheats_enabled: bool,
+    trace_options: TraceOptions,
 }
 
-impl<Emulator: PartialClone> HotkeyState<Emulator> {
+impl<Emulator: EmulatorTrait> HotkeyState<Emulator> {
     fn new<KC, JC>(
         common_config: &CommonConfig<KC, JC>,
-        save_state_path: PathBuf,
+        rom_base_path: PathBuf,
+        console: SaveStateConsole,
+        rom_checksum: u32,
         debug_render_fn: fn() -> Box<DebugRenderFn<Emulator>>,
     ) -> Self {
+        let rom_name = file_name_no_ext(&rom_base_path).unwrap_or_default();
+        let cheats = load_cheat_list(common_config.cheat_directory.as_deref(), &rom_base_path);
+
         Self {
-            save_state_path,
+            rom_base_path,
+            rom_name,
+            console,
+            rom_checksum,
+            save_state_slot: 0,
             paused: false,
             should_step_frame: false,
             fast_forward_multiplier: common_config.fast_forward_multiplier,
+            screenshot_directory: common_config.screenshot_directory.as_ref().map(PathBuf::from),
+            screenshot_aspect_ratio_correction: common_config.screenshot_aspect_ratio_correction,
             rewinder: Rewinder::new(Duration::from_secs(
                 common_config.rewind_buffer_length_seconds,
             )),
             debugger_window: None,
             debug_render_fn,
+            cheats,
+            cheats_enabled: true,
+            trace_options: TraceOptions {
+                cpu: common_config.trace_cpu.clone(),
+                ranges: common_config.trace_ranges.clone(),
+            },
         }
     }
+
+    fn save_state_path(&self, slot: usize) -> PathBuf {
+        savestate::save_state_path(&self.rom_base_path, slot)
+    }
+
+    fn movie_path(&self) -> PathBuf {
+        self.rom_base_path.with_extension("jgm")
+    }
+
+    fn wav_path(&self) -> PathBuf {
+        timestamped_path(self.rom_directory(), &self.rom_name, "wav")
+    }
+
+    fn recording_path(&self) -> PathBuf {
+        timestamped_path(self.rom_directory(), &self.rom_name, "")
+    }
+
+    fn spc_path(&self) -> PathBuf {
+        timestamped_path(self.rom_directory(), &self.rom_name, "spc")
+    }
+
+    fn vgm_path(&self) -> PathBuf {
+        timestamped_path(self.rom_directory(), &self.rom_name, "vgm")
+    }
+
+    fn trace_path(&self, cpu_suffix: &str) -> PathBuf {
+        timestamped_path(self.rom_directory(), &self.rom_name, &format!("{cpu_suffix}.log"))
+    }
+
+    fn rom_directory(&self) -> &Path {
+        self.rom_base_path.parent().unwrap_or(Path::new("."))
+    }
+
+    fn change_save_state_slot(&mut self, slot: usize) {
+        self.save_state_slot = slot % SAVE_STATE_SLOTS;
+        log::info!("Selected save state slot {}", self.save_state_slot);
+    }
+
+    fn apply_cheats(&self, emulator: &mut Emulator)
+    where
+        Emulator: ConsoleFeatures,
+    {
+        let cheat_set = if self.cheats_enabled {
+            cheats::build_cheat_set::<Emulator>(&self.cheats)
+        } else {
+            CheatSet::new()
+        };
+        emulator.set_cheats(cheat_set);
+    }
+}
+
+fn load_cheat_list(cheat_directory: Option<&str>, rom_base_path: &Path) -> Vec<CheatEntry> {
+    let Some(cheat_directory) = cheat_directory else { return vec![] };
+
+    load_cheats(cheats::cheat_file_path(cheat_directory, rom_base_path)).unwrap_or_else(|err| {
+        log::error!("{err}");
+        vec![]
+    })
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -139,9 +256,14 @@ pub struct NativeEmulator<Inputs, Button, Config, Emulator> {
     event_pump: EventPump,
     video: VideoSubsystem,
     hotkey_state: HotkeyState<Emulator>,
+    movie: Option<MovieSession<Inputs>>,
+    av_recorder: Option<AvRecorder>,
+    vgm_file: Option<VgmFile>,
+    trace_file: Option<TraceFile>,
+    gdb_server: Option<GdbServer>,
 }
 
-impl<Inputs, Button, Config, Emulator: PartialClone>
+impl<Inputs, Button, Config, Emulator: EmulatorTrait>
     NativeEmulator<Inputs, Button, Config, Emulator>
 {
     fn reload_common_config<KC, JC>(
@@ -152,6 +274,10 @@ impl<Inputs, Button, Config, Emulator: PartialClone>
         self.audio_output.reload_config(config)?;
 
         self.hotkey_state.fast_forward_multiplier = config.fast_forward_multiplier;
+        self.hotkey_state.screenshot_directory =
+            config.screenshot_directory.as_ref().map(PathBuf::from);
+        self.hotkey_state.screenshot_aspect_ratio_correction =
+            config.screenshot_aspect_ratio_correction;
         // Reset speed multiplier in case the fast forward hotkey changed
         self.renderer.set_speed_multiplier(1);
         self.audio_output.set_speed_multiplier(1);
@@ -194,6 +320,16 @@ pub enum NativeEmulatorError {
     Audio(#[from] AudioError),
     #[error("{0}")]
     SaveWrite(#[from] SaveWriteError),
+    #[error("{0}")]
+    Movie(#[from] MovieError),
+    #[error("{0}")]
+    Wav(#[from] WavError),
+    #[error("{0}")]
+    Recording(#[from] RecordingError),
+    #[error("{0}")]
+    Vgm(#[from] VgmError),
+    #[error("{0}")]
+    Trace(#[from] TraceError),
     #[error("Error initializing SDL2: {0}")]
     SdlInit(String),
     #[error("Error initializing SDL2 video subsystem: {0}")]
@@ -232,14 +368,30 @@ pub enum NativeEmulatorError {
     InvalidKeycode(String),
     #[error("Unable to determine file name for path: '{0}'")]
     ParseFileName(String),
-    #[error("Unable to determine file extension for path: '{0}'")]
-    ParseFileExtension(String),
     #[error("Failed to read ROM file at '{path}': {source}")]
     RomRead {
         path: String,
         #[source]
         source: io::Error,
     },
+    #[error("Error reading archive '{path}': {source}")]
+    Archive {
+        path: String,
+        #[source]
+        source: ArchiveError,
+    },
+    #[error("Error reading patch file '{path}': {source}")]
+    PatchRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error applying patch file '{path}': {source}")]
+    Patch {
+        path: String,
+        #[source]
+        source: PatchError,
+    },
     #[error("BIOS is required for Sega CD emulation")]
     SegaCdNoBios,
     #[error("Error opening BIOS file at '{path}': {source}")]
@@ -248,6 +400,14 @@ pub enum NativeEmulatorError {
         #[source]
         source: io::Error,
     },
+    #[error("BIOS is required for Famicom Disk System emulation")]
+    FdsNoBios,
+    #[error("Error opening FDS BIOS file at '{path}': {source}")]
+    FdsBiosRead {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
 
     #[error("{0}")]
     SegaCdDisc(#[from] SegaCdLoadError),
@@ -256,6 +416,8 @@ pub enum NativeEmulatorError {
     #[error("{0}")]
     SnesLoad(#[from] SnesLoadError),
     #[error("{0}")]
+    SpcLoad(#[from] SpcError),
+    #[error("{0}")]
     GameBoyLoad(#[from] GameBoyLoadError),
     #[error("I/O error opening save state file '{path}': {source}")]
     StateFileOpen {
@@ -263,10 +425,43 @@ pub enum NativeEmulatorError {
         #[source]
         source: io::Error,
     },
+    #[error("I/O error writing save state file '{path}': {source}")]
+    StateFileWrite {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("'{path}' is not a jgenesis save state file")]
+    SaveStateInvalidMagic { path: String },
+    #[error(
+        "Save state '{path}' uses container format version {actual}; this version of jgenesis only supports version {expected}"
+    )]
+    SaveStateFormatVersion { path: String, expected: u16, actual: u16 },
+    #[error("Save state is for {actual}, but the running console is {expected}")]
+    SaveStateConsoleMismatch { expected: SaveStateConsole, actual: SaveStateConsole },
+    #[error(
+        "Save state was created from a different ROM (ROM checksum {actual:08X}, expected {expected:08X})"
+    )]
+    SaveStateRomMismatch { expected: u32, actual: u32 },
+    #[error(
+        "Save state was created by jgenesis {saved_version} and is not compatible with jgenesis {current_version}: {source}"
+    )]
+    SaveStateIncompatibleVersion {
+        saved_version: String,
+        current_version: String,
+        #[source]
+        source: DecodeError,
+    },
     #[error("Error saving state: {0}")]
     SaveState(#[from] EncodeError),
     #[error("Error loading state: {0}")]
     LoadState(#[from] DecodeError),
+    #[error("Error starting GDB server on port {port}: {source}")]
+    GdbServer {
+        port: u16,
+        #[source]
+        source: io::Error,
+    },
     #[error("Error in emulation core: {0}")]
     Emulator(#[source] Box<dyn Error + Send + Sync + 'static>),
 }
@@ -276,20 +471,22 @@ pub type NativeEmulatorResult<T> = Result<T, NativeEmulatorError>;
 // TODO simplify or generalize these trait bounds
 impl<Inputs, Button, Config, Emulator> NativeEmulator<Inputs, Button, Config, Emulator>
 where
-    Inputs: Default + MappableInputs<Button>,
+    Inputs: Clone + Default + Encode + Decode + MappableInputs<Button>,
     Button: Copy,
-    Emulator: EmulatorTrait<Inputs = Inputs, Config = Config>,
+    Emulator: EmulatorTrait<Inputs = Inputs, Config = Config> + ConsoleFeatures + Debuggable,
     Emulator::Err<RendererError, AudioError, SaveWriteError>: Error + Send + Sync + 'static,
 {
     #[allow(clippy::too_many_arguments)]
     fn new<KC, JC, InputMapperFn>(
-        emulator: Emulator,
+        mut emulator: Emulator,
         emulator_config: Emulator::Config,
         common_config: CommonConfig<KC, JC>,
         default_window_size: WindowSize,
         window_title: &str,
         save_writer: FsSaveWriter,
-        save_state_path: PathBuf,
+        rom_base_path: PathBuf,
+        console: SaveStateConsole,
+        rom_checksum: u32,
         input_mapper_fn: InputMapperFn,
         debug_render_fn: fn() -> Box<DebugRenderFn<Emulator>>,
     ) -> NativeEmulatorResult<Self>
@@ -322,6 +519,18 @@ where
         let input_mapper = input_mapper_fn(joystick, &common_config)?;
         let hotkey_mapper = HotkeyMapper::from_config(&common_config.hotkeys)?;
 
+        let hotkey_state =
+            HotkeyState::new(&common_config, rom_base_path, console, rom_checksum, debug_render_fn);
+        hotkey_state.apply_cheats(&mut emulator);
+
+        let gdb_server = common_config
+            .gdb_port
+            .map(|port| {
+                GdbServer::bind(port, common_config.gdb_target.as_deref(), &emulator)
+                    .map_err(|source| NativeEmulatorError::GdbServer { port, source })
+            })
+            .transpose()?;
+
         Ok(Self {
             emulator,
             config: emulator_config,
@@ -333,7 +542,12 @@ where
             sdl,
             event_pump,
             video,
-            hotkey_state: HotkeyState::new(&common_config, save_state_path, debug_render_fn),
+            hotkey_state,
+            movie: None,
+            av_recorder: None,
+            vgm_file: None,
+            trace_file: None,
+            gdb_server,
         })
     }
 
@@ -346,23 +560,62 @@ where
     pub fn render_frame(&mut self) -> NativeEmulatorResult<NativeTickEffect> {
         loop {
             let rewinding = self.hotkey_state.rewinder.is_rewinding();
-            let should_tick_emulator =
-                !rewinding && (!self.hotkey_state.paused || self.hotkey_state.should_step_frame);
+            let debugger_stopped = self.emulator.debug_hooks().stop_event().is_some();
+            let should_tick_emulator = !rewinding
+                && !debugger_stopped
+                && (!self.hotkey_state.paused || self.hotkey_state.should_step_frame);
+            let inputs = match &self.movie {
+                Some(movie) => movie.inputs(self.input_mapper.inputs()),
+                None => self.input_mapper.inputs(),
+            };
             let frame_rendered = should_tick_emulator
-                && self
-                    .emulator
-                    .tick(
+                && match &mut self.av_recorder {
+                    Some(av_recorder) => av_recorder.tick(
+                        &mut self.emulator,
                         &mut self.renderer,
                         &mut self.audio_output,
-                        self.input_mapper.inputs(),
+                        inputs,
                         &mut self.save_writer,
-                    )
-                    .map_err(|err| NativeEmulatorError::Emulator(err.into()))?
+                    ),
+                    None => self.emulator.tick(
+                        &mut self.renderer,
+                        &mut self.audio_output,
+                        inputs,
+                        &mut self.save_writer,
+                    ),
+                }
+                .map_err(|err| NativeEmulatorError::Emulator(err.into()))?
                     == TickEffect::FrameRendered;
 
+            if let Some(err) = self.av_recorder.as_mut().and_then(AvRecorder::take_error) {
+                log::error!("Stopping video recording: {err}");
+                self.av_recorder = None;
+            }
+
             if !should_tick_emulator || frame_rendered {
                 self.hotkey_state.should_step_frame = false;
 
+                // Advance the movie before processing events so that recordings capture the inputs
+                // that were actually used for this frame
+                if frame_rendered {
+                    vgm::write_pending_commands(&mut self.emulator, &mut self.vgm_file);
+
+                    if let Some(movie) = &mut self.movie {
+                        if movie.advance_frame(self.input_mapper.inputs())
+                            == MovieProgress::Finished
+                        {
+                            self.movie = None;
+                        }
+                    }
+                }
+
+                // Also write trace lines while stopped so that stepping in the debugger shows up
+                trace::write_pending_lines(&mut self.emulator, &mut self.trace_file);
+
+                if let Some(gdb_server) = &mut self.gdb_server {
+                    gdb_server.poll(&mut self.emulator);
+                }
+
                 if let Some(debugger_window) = &mut self.hotkey_state.debugger_window {
                     if let Err(err) = debugger_window.update(&mut self.emulator) {
                         log::error!("Debugger window error: {err}");
@@ -390,6 +643,10 @@ where
                         save_writer: &mut self.save_writer,
                         video: &self.video,
                         hotkey_state: &mut self.hotkey_state,
+                        movie: &mut self.movie,
+                        av_recorder: &mut self.av_recorder,
+                        vgm_file: &mut self.vgm_file,
+                        trace_file: &mut self.trace_file,
                     })? == HotkeyResult::Quit
                     {
                         return Ok(NativeTickEffect::Exit);
@@ -412,6 +669,12 @@ where
                                     .is_some_and(|debugger| window_id == debugger.window_id())
                                 {
                                     self.hotkey_state.debugger_window = None;
+
+                                    // Don't leave the emulator stuck at a breakpoint with no way
+                                    // to resume it
+                                    if self.gdb_server.is_none() {
+                                        self.emulator.debug_hooks_mut().clear();
+                                    }
                                 }
                             }
 
@@ -424,7 +687,7 @@ where
                 }
 
                 if frame_rendered {
-                    self.hotkey_state.rewinder.record_frame(&self.emulator);
+                    self.hotkey_state.rewinder.record_frame(&mut self.emulator);
                 }
 
                 if rewinding {
@@ -435,7 +698,7 @@ where
                     )?;
                 }
 
-                if rewinding || self.hotkey_state.paused {
+                if rewinding || debugger_stopped || self.hotkey_state.paused {
                     // Don't spin loop when the emulator is not actively running
                     sleep(Duration::from_millis(1));
                 }
@@ -446,10 +709,12 @@ where
     }
 
     pub fn soft_reset(&mut self) {
+        stop_movie_on_desync(&mut self.movie, "the console was reset");
         self.emulator.soft_reset();
     }
 
     pub fn hard_reset(&mut self) {
+        stop_movie_on_desync(&mut self.movie, "the console was reset");
         self.emulator.hard_reset(&mut self.save_writer);
     }
 
@@ -459,6 +724,234 @@ where
                 open_debugger_window(&self.video, self.hotkey_state.debug_render_fn);
         }
     }
+
+    /// Save the current emulator state to the given slot.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if unable to write the save state file.
+    pub fn save_state(&mut self, slot: usize) -> NativeEmulatorResult<()> {
+        save_state_to_slot(&mut self.emulator, &self.hotkey_state, slot)
+    }
+
+    /// Load the emulator state from the given slot. Errors are logged rather than propagated
+    /// because a missing or invalid save state should not terminate the emulator.
+    pub fn load_state(&mut self, slot: usize) {
+        stop_movie_on_desync(&mut self.movie, "a state was loaded");
+        load_state_from_slot(&mut self.emulator, &self.config, &self.hotkey_state, slot);
+    }
+
+    /// Change which slot the save state and load state hotkeys use.
+    pub fn select_save_state_slot(&mut self, slot: usize) {
+        self.hotkey_state.change_save_state_slot(slot);
+    }
+
+    /// Start recording an input movie to the given path, replacing any movie that is currently
+    /// recording or playing back.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if unable to encode the starting emulator state.
+    pub fn start_movie_recording(
+        &mut self,
+        path: PathBuf,
+        start: MovieStart,
+    ) -> NativeEmulatorResult<()> {
+        self.movie = None;
+        self.movie = Some(MovieSession::start_recording(
+            path,
+            start,
+            &mut self.emulator,
+            &mut self.save_writer,
+            &self.hotkey_state,
+        )?);
+
+        Ok(())
+    }
+
+    /// Start playing back the input movie at the given path, replacing any movie that is currently
+    /// recording or playing back.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if unable to read the movie file or if the movie was
+    /// recorded on a different console or with a different ROM.
+    pub fn start_movie_playback(
+        &mut self,
+        path: PathBuf,
+        read_only: bool,
+    ) -> NativeEmulatorResult<()> {
+        self.movie = None;
+        self.movie = Some(MovieSession::start_playback(
+            path,
+            read_only,
+            &mut self.emulator,
+            &self.config,
+            &mut self.save_writer,
+            &self.hotkey_state,
+        )?);
+
+        Ok(())
+    }
+
+    /// Stop the current movie recording or playback, if any. Recordings are saved to disk.
+    pub fn stop_movie(&mut self) {
+        self.movie = None;
+    }
+
+    #[must_use]
+    pub fn is_movie_active(&self) -> bool {
+        self.movie.is_some()
+    }
+
+    /// Start recording the emulator's audio output to a WAV file at the given path, replacing any
+    /// audio recording that is currently in progress.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if unable to create the WAV file.
+    pub fn start_wav_recording(&mut self, path: PathBuf) -> NativeEmulatorResult<()> {
+        self.audio_output.start_wav_recording(path)?;
+
+        Ok(())
+    }
+
+    /// Stop the current audio recording, if any, and finish writing the WAV file.
+    pub fn stop_wav_recording(&mut self) {
+        self.audio_output.stop_wav_recording();
+    }
+
+    /// Start recording every emitted frame and audio sample to the given directory, replacing any
+    /// video recording that is currently in progress.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if unable to create the recording directory or files.
+    pub fn start_video_recording(&mut self, directory: PathBuf) -> NativeEmulatorResult<()> {
+        self.av_recorder = None;
+        self.av_recorder = Some(AvRecorder::create(directory, self.emulator.timing_mode())?);
+
+        Ok(())
+    }
+
+    /// Stop the current video recording, if any.
+    pub fn stop_video_recording(&mut self) {
+        self.av_recorder = None;
+    }
+
+    /// Start logging sound chip register writes to a VGM file at the given path, replacing any VGM
+    /// log that is currently in progress.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the console does not support VGM logging or if unable to
+    /// create the VGM file.
+    pub fn start_vgm_logging(&mut self, path: PathBuf) -> NativeEmulatorResult<()> {
+        vgm::stop(&mut self.emulator, &mut self.vgm_file);
+        self.vgm_file = Some(VgmFile::start(&mut self.emulator, path)?);
+
+        Ok(())
+    }
+
+    /// Stop the current VGM log, if any, and finish writing the VGM file.
+    pub fn stop_vgm_logging(&mut self) {
+        vgm::stop(&mut self.emulator, &mut self.vgm_file);
+    }
+
+    /// Start writing an instruction trace to the given path, replacing any trace that is currently
+    /// in progress. The traced CPU and address ranges come from the common config.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the configured CPU does not exist on this console or if
+    /// unable to create the trace log.
+    pub fn start_trace_logging(&mut self, path: PathBuf) -> NativeEmulatorResult<()> {
+        trace::stop(&mut self.emulator, &mut self.trace_file);
+        self.trace_file =
+            Some(TraceFile::start(&mut self.emulator, &self.hotkey_state.trace_options, path)?);
+
+        Ok(())
+    }
+
+    /// Stop the current instruction trace, if any, and close the trace log.
+    pub fn stop_trace_logging(&mut self) {
+        trace::stop(&mut self.emulator, &mut self.trace_file);
+    }
+
+    /// Replace the current cheat list. Enabled cheats take effect immediately unless cheats have
+    /// been toggled off.
+    pub fn update_cheats(&mut self, cheats: Vec<CheatEntry>) {
+        self.hotkey_state.cheats = cheats;
+        self.hotkey_state.apply_cheats(&mut self.emulator);
+    }
+}
+
+fn save_state_to_slot<Emulator: EmulatorTrait>(
+    emulator: &mut Emulator,
+    hotkey_state: &HotkeyState<Emulator>,
+    slot: usize,
+) -> NativeEmulatorResult<()> {
+    let thumbnail = savestate::capture_thumbnail(emulator);
+    let metadata = SaveStateMetadata::new(
+        hotkey_state.console,
+        hotkey_state.rom_checksum,
+        hotkey_state.rom_name.clone(),
+        thumbnail,
+    );
+    savestate::save_state(emulator, &metadata, hotkey_state.save_state_path(slot))
+}
+
+fn load_state_from_slot<Emulator: EmulatorTrait>(
+    emulator: &mut Emulator,
+    config: &Emulator::Config,
+    hotkey_state: &HotkeyState<Emulator>,
+    slot: usize,
+) {
+    let save_state_path = hotkey_state.save_state_path(slot);
+    let mut loaded_emulator: Emulator = match savestate::load_state(
+        &save_state_path,
+        hotkey_state.console,
+        hotkey_state.rom_checksum,
+    ) {
+        Ok((emulator, _metadata)) => emulator,
+        Err(err) => {
+            log::error!("Error loading save state from {}: {err}", save_state_path.display());
+            return;
+        }
+    };
+    loaded_emulator.take_rom_from(emulator);
+
+    // Force a config reload because the emulator will contain some config fields
+    loaded_emulator.reload_config(config);
+
+    *emulator = loaded_emulator;
+}
+
+/// Build a path in `directory` named after the ROM and the current UTC time, e.g.
+/// `Sonic_2024-01-31_18-30-05.png`, adding a numeric suffix if that file already exists. An empty
+/// extension produces a path with no extension.
+fn timestamped_path(directory: &Path, rom_name: &str, extension: &str) -> PathBuf {
+    let now = OffsetDateTime::now_utc();
+    let base_name = format!(
+        "{rom_name}_{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        now.year(),
+        u8::from(now.month()),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+
+    // Avoid overwriting an earlier file created within the same second
+    let extension = if extension.is_empty() { String::new() } else { format!(".{extension}") };
+    let mut path = directory.join(format!("{base_name}{extension}"));
+    let mut suffix = 2;
+    while path.exists() {
+        path = directory.join(format!("{base_name}_{suffix}{extension}"));
+        suffix += 1;
+    }
+
+    path
 }
 
 fn file_name_no_ext<P: AsRef<Path>>(path: P) -> NativeEmulatorResult<String> {
@@ -469,10 +962,85 @@ fn file_name_no_ext<P: AsRef<Path>>(path: P) -> NativeEmulatorResult<String> {
         .ok_or_else(|| NativeEmulatorError::ParseFileName(path.as_ref().display().to_string()))
 }
 
-fn parse_file_ext(path: &Path) -> NativeEmulatorResult<&str> {
-    path.extension()
-        .and_then(OsStr::to_str)
-        .ok_or_else(|| NativeEmulatorError::ParseFileExtension(path.display().to_string()))
+/// Path that per-game files such as save files, save states, patches, and movies are named after,
+/// by replacing its extension.
+///
+/// This is the ROM path itself, except for ROMs inside an archive, where it is the archive entry's
+/// file name in the archive's directory. Each ROM in an archive thus gets its own files.
+#[must_use]
+pub fn rom_base_path<P: AsRef<Path>>(rom_path: P, archive_entry: Option<&str>) -> PathBuf {
+    let rom_path = rom_path.as_ref();
+    match archive_entry.and_then(|entry| Path::new(entry).file_name()) {
+        Some(entry_file_name) => rom_path.with_file_name(entry_file_name),
+        None => rom_path.into(),
+    }
+}
+
+struct RomFile {
+    contents: Vec<u8>,
+    /// Extension of the ROM image itself; for archives this is the extension of the archive entry
+    extension: String,
+    /// See [`rom_base_path`]
+    base_path: PathBuf,
+}
+
+/// Read a ROM file and soft-patch it. If the ROM path is a zip or gzip archive, the configured
+/// archive entry is read, or else the first entry with one of the given extensions. If no patch path
+/// is specified, this will look for an IPS, BPS, or UPS patch with the same file name as the ROM
+/// (or archive entry) next to the ROM file.
+fn read_rom_file<KC, JC>(
+    common_config: &CommonConfig<KC, JC>,
+    supported_extensions: &[&str],
+) -> NativeEmulatorResult<RomFile> {
+    let rom_path = Path::new(&common_config.rom_file_path);
+
+    let (rom, extension, base_path) = if archive::is_archive(rom_path) {
+        let archive_err =
+            |source| NativeEmulatorError::Archive { path: rom_path.display().to_string(), source };
+
+        let entry = archive::select_entry(
+            rom_path,
+            common_config.archive_entry.as_deref(),
+            supported_extensions,
+        )
+        .map_err(archive_err)?;
+        let rom = archive::read_entry(rom_path, &entry.name).map_err(archive_err)?;
+
+        log::info!("Loaded '{}' from archive '{}'", entry.name, rom_path.display());
+
+        let extension = entry.extension().unwrap_or("").to_ascii_lowercase();
+        (rom, extension, rom_base_path(rom_path, Some(&entry.name)))
+    } else {
+        let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
+            path: rom_path.display().to_string(),
+            source,
+        })?;
+        let extension = rom_path.extension().and_then(OsStr::to_str).unwrap_or("");
+
+        (rom, extension.into(), rom_path.into())
+    };
+
+    let patch_path = common_config.patch_path.as_ref().map(PathBuf::from).or_else(|| {
+        patch::PATCH_EXTENSIONS
+            .into_iter()
+            .map(|extension| base_path.with_extension(extension))
+            .find(|path| path.is_file())
+    });
+    let Some(patch_path) = patch_path else {
+        return Ok(RomFile { contents: rom, extension, base_path });
+    };
+
+    let patch = fs::read(&patch_path).map_err(|source| NativeEmulatorError::PatchRead {
+        path: patch_path.display().to_string(),
+        source,
+    })?;
+    let patched_rom = patch::apply_patch(&rom, &patch).map_err(|source| {
+        NativeEmulatorError::Patch { path: patch_path.display().to_string(), source }
+    })?;
+
+    log::info!("Applied patch file '{}'", patch_path.display());
+
+    Ok(RomFile { contents: patched_rom, extension, base_path })
 }
 
 fn basic_input_mapper_fn<KC, JC, Inputs, Button>(
@@ -548,13 +1116,18 @@ struct HandleHotkeysArgs<'a, Emulator: EmulatorTrait> {
     save_writer: &'a mut FsSaveWriter,
     video: &'a VideoSubsystem,
     hotkey_state: &'a mut HotkeyState<Emulator>,
+    movie: &'a mut Option<MovieSession<Emulator::Inputs>>,
+    av_recorder: &'a mut Option<AvRecorder>,
+    vgm_file: &'a mut Option<VgmFile>,
+    trace_file: &'a mut Option<TraceFile>,
 }
 
 fn handle_hotkeys<Emulator>(
     mut args: HandleHotkeysArgs<'_, Emulator>,
 ) -> NativeEmulatorResult<HotkeyResult>
 where
-    Emulator: EmulatorTrait,
+    Emulator: EmulatorTrait + ConsoleFeatures + Debuggable,
+    Emulator::Inputs: Clone + Encode + Decode,
 {
     match args.hotkey_mapper.check_for_hotkeys(args.event) {
         HotkeyMapResult::Pressed(hotkeys) => {
@@ -589,10 +1162,9 @@ fn handle_hotkey_pressed<Emulator>(
     args: &mut HandleHotkeysArgs<'_, Emulator>,
 ) -> NativeEmulatorResult<HotkeyResult>
 where
-    Emulator: EmulatorTrait,
+    Emulator: EmulatorTrait + ConsoleFeatures + Debuggable,
+    Emulator::Inputs: Clone + Encode + Decode,
 {
-    let save_state_path = &args.hotkey_state.save_state_path;
-
     match hotkey {
         Hotkey::Quit => {
             return Ok(HotkeyResult::Quit);
@@ -601,30 +1173,28 @@ where
             args.renderer.toggle_fullscreen().map_err(NativeEmulatorError::SdlSetFullscreen)?;
         }
         Hotkey::SaveState => {
-            save_state(args.emulator, save_state_path)?;
+            let slot = args.hotkey_state.save_state_slot;
+            save_state_to_slot(args.emulator, args.hotkey_state, slot)?;
         }
         Hotkey::LoadState => {
-            let mut loaded_emulator: Emulator = match load_state(save_state_path) {
-                Ok(emulator) => emulator,
-                Err(err) => {
-                    log::error!(
-                        "Error loading save state from {}: {err}",
-                        save_state_path.display()
-                    );
-                    return Ok(HotkeyResult::None);
-                }
-            };
-            loaded_emulator.take_rom_from(args.emulator);
-
-            // Force a config reload because the emulator will contain some config fields
-            loaded_emulator.reload_config(args.config);
-
-            *args.emulator = loaded_emulator;
+            stop_movie_on_desync(args.movie, "a state was loaded");
+            let slot = args.hotkey_state.save_state_slot;
+            load_state_from_slot(args.emulator, args.config, args.hotkey_state, slot);
+        }
+        Hotkey::NextSaveStateSlot => {
+            let slot = args.hotkey_state.save_state_slot + 1;
+            args.hotkey_state.change_save_state_slot(slot);
+        }
+        Hotkey::PrevSaveStateSlot => {
+            let slot = args.hotkey_state.save_state_slot + SAVE_STATE_SLOTS - 1;
+            args.hotkey_state.change_save_state_slot(slot);
         }
         Hotkey::SoftReset => {
+            stop_movie_on_desync(args.movie, "the console was reset");
             args.emulator.soft_reset();
         }
         Hotkey::HardReset => {
+            stop_movie_on_desync(args.movie, "the console was reset");
             args.emulator.hard_reset(args.save_writer);
         }
         Hotkey::Pause => {
@@ -638,8 +1208,133 @@ where
             args.audio_output.set_speed_multiplier(args.hotkey_state.fast_forward_multiplier);
         }
         Hotkey::Rewind => {
+            stop_movie_on_desync(args.movie, "rewind was used");
             args.hotkey_state.rewinder.start_rewinding();
         }
+        Hotkey::ToggleMovieRecording => match args.movie {
+            Some(movie) if movie.is_recording() => {
+                *args.movie = None;
+            }
+            Some(movie) => {
+                if movie.take_over() == MovieProgress::Finished {
+                    *args.movie = None;
+                }
+            }
+            None => {
+                match MovieSession::start_recording(
+                    args.hotkey_state.movie_path(),
+                    MovieStart::CurrentState,
+                    args.emulator,
+                    args.save_writer,
+                    args.hotkey_state,
+                ) {
+                    Ok(movie) => *args.movie = Some(movie),
+                    Err(err) => log::error!("Error starting movie recording: {err}"),
+                }
+            }
+        },
+        Hotkey::ToggleMoviePlayback => {
+            if args.movie.is_some() {
+                *args.movie = None;
+            } else {
+                match MovieSession::start_playback(
+                    args.hotkey_state.movie_path(),
+                    true,
+                    args.emulator,
+                    args.config,
+                    args.save_writer,
+                    args.hotkey_state,
+                ) {
+                    Ok(movie) => *args.movie = Some(movie),
+                    Err(err) => log::error!("Error starting movie playback: {err}"),
+                }
+            }
+        }
+        Hotkey::ToggleMovieReadOnly => {
+            if let Some(movie) = args.movie {
+                movie.toggle_read_only();
+            }
+        }
+        Hotkey::Screenshot => {
+            let hotkey_state = &*args.hotkey_state;
+            match screenshot::save_screenshot(
+                args.emulator,
+                &hotkey_state.rom_base_path,
+                &hotkey_state.rom_name,
+                hotkey_state.screenshot_directory.as_deref(),
+                hotkey_state.screenshot_aspect_ratio_correction,
+            ) {
+                Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+                Err(err) => log::error!("Error saving screenshot: {err}"),
+            }
+        }
+        Hotkey::ToggleAudioRecording => {
+            if args.audio_output.is_wav_recording() {
+                args.audio_output.stop_wav_recording();
+            } else if let Err(err) =
+                args.audio_output.start_wav_recording(args.hotkey_state.wav_path())
+            {
+                log::error!("Error starting audio recording: {err}");
+            }
+        }
+        Hotkey::ToggleVideoRecording => {
+            if args.av_recorder.is_some() {
+                *args.av_recorder = None;
+            } else {
+                match AvRecorder::create(
+                    args.hotkey_state.recording_path(),
+                    args.emulator.timing_mode(),
+                ) {
+                    Ok(av_recorder) => *args.av_recorder = Some(av_recorder),
+                    Err(err) => log::error!("Error starting video recording: {err}"),
+                }
+            }
+        }
+        Hotkey::ToggleVgmLogging => {
+            if args.vgm_file.is_some() {
+                vgm::stop(args.emulator, args.vgm_file);
+            } else {
+                match VgmFile::start(args.emulator, args.hotkey_state.vgm_path()) {
+                    Ok(vgm_file) => *args.vgm_file = Some(vgm_file),
+                    Err(err) => log::error!("Error starting VGM logging: {err}"),
+                }
+            }
+        }
+        Hotkey::ToggleTraceLogging => {
+            if args.trace_file.is_some() {
+                trace::stop(args.emulator, args.trace_file);
+            } else {
+                let options = &args.hotkey_state.trace_options;
+                let path = args.hotkey_state.trace_path(&options.file_suffix(args.emulator));
+                match TraceFile::start(args.emulator, options, path) {
+                    Ok(trace_file) => *args.trace_file = Some(trace_file),
+                    Err(err) => log::error!("Error starting instruction trace: {err}"),
+                }
+            }
+        }
+        Hotkey::ExportSpc => match args.emulator.export_spc() {
+            Some(spc_file) => {
+                let path = args.hotkey_state.spc_path();
+                match fs::write(&path, spc_file) {
+                    Ok(()) => log::info!("Exported SPC file to {}", path.display()),
+                    Err(err) => log::error!("Error writing SPC file '{}': {err}", path.display()),
+                }
+            }
+            None => log::warn!("SPC export is only supported for the SNES"),
+        },
+        Hotkey::SwitchFdsDiskSide => {
+            if !args.emulator.switch_fds_disk_side() {
+                log::warn!("Disk side switching is only supported for Famicom Disk System games");
+            }
+        }
+        Hotkey::ToggleCheats => {
+            args.hotkey_state.cheats_enabled = !args.hotkey_state.cheats_enabled;
+            args.hotkey_state.apply_cheats(args.emulator);
+            log::info!(
+                "Cheats {}",
+                if args.hotkey_state.cheats_enabled { "enabled" } else { "disabled" }
+            );
+        }
         Hotkey::OpenDebugger => {
             if args.hotkey_state.debugger_window.is_none() {
                 let debug_render_fn = (args.hotkey_state.debug_render_fn)();
@@ -658,6 +1353,13 @@ where
     Ok(HotkeyResult::None)
 }
 
+fn stop_movie_on_desync<Inputs>(movie: &mut Option<MovieSession<Inputs>>, reason: &str) {
+    if movie.is_some() {
+        log::warn!("Stopping movie because {reason}; the movie would no longer stay in sync");
+        *movie = None;
+    }
+}
+
 fn open_debugger_window<Emulator>(
     video: &VideoSubsystem,
     debug_render_fn: fn() -> Box<DebugRenderFn<Emulator>>,
@@ -681,52 +1383,4 @@ fn handle_window_event(win_event: WindowEvent, renderer: &mut WgpuRenderer<Windo
     }
 }
 
-macro_rules! bincode_config {
-    () => {
-        bincode::config::standard()
-            .with_little_endian()
-            .with_fixed_int_encoding()
-            .with_limit::<{ 100 * 1024 * 1024 }>()
-    };
-}
-
 use crate::config::input::{InputConfig, JoystickInput, KeyboardInput};
-use bincode_config;
-
-fn save_state<E, P>(emulator: &E, path: P) -> NativeEmulatorResult<()>
-where
-    E: Encode,
-    P: AsRef<Path>,
-{
-    let path = path.as_ref();
-
-    let mut file = BufWriter::new(File::create(path).map_err(|source| {
-        NativeEmulatorError::StateFileOpen { path: path.display().to_string(), source }
-    })?);
-
-    let conf = bincode_config!();
-    bincode::encode_into_std_write(emulator, &mut file, conf)?;
-
-    log::info!("Saved state to {}", path.display());
-
-    Ok(())
-}
-
-fn load_state<D, P>(path: P) -> NativeEmulatorResult<D>
-where
-    D: Decode,
-    P: AsRef<Path>,
-{
-    let path = path.as_ref();
-
-    let mut file = BufReader::new(File::open(path).map_err(|source| {
-        NativeEmulatorError::StateFileOpen { path: path.display().to_string(), source }
-    })?);
-
-    let conf = bincode_config!();
-    let emulator = bincode::decode_from_std_read(&mut file, conf)?;
-
-    log::info!("Loaded state from {}", path.display());
-
-    Ok(emulator)
-}
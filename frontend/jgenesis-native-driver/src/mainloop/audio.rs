@@ -1,11 +1,17 @@
+mod wav;
+
 use crate::config::CommonConfig;
 use crate::mainloop;
 use jgenesis_common::frontend::AudioOutput;
 use sdl2::audio::{AudioQueue, AudioSpecDesired};
 use sdl2::AudioSubsystem;
+use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 
+pub use wav::WavError;
+pub(crate) use wav::WavRecorder;
+
 #[derive(Debug, Error)]
 pub enum AudioError {
     #[error("Error opening SDL2 audio queue: {0}")]
@@ -23,6 +29,7 @@ pub struct SdlAudioOutput {
     audio_gain_multiplier: f64,
     sample_count: u64,
     speed_multiplier: u64,
+    wav_recorder: Option<WavRecorder>,
 }
 
 impl SdlAudioOutput {
@@ -51,6 +58,7 @@ impl SdlAudioOutput {
             audio_gain_multiplier: decibels_to_multiplier(config.audio_gain_db),
             sample_count: 0,
             speed_multiplier: 1,
+            wav_recorder: None,
         })
     }
 
@@ -89,6 +97,30 @@ impl SdlAudioOutput {
     pub fn set_speed_multiplier(&mut self, speed_multiplier: u64) {
         self.speed_multiplier = speed_multiplier;
     }
+
+    /// Start recording all audio samples to a WAV file at the given path, replacing any recording
+    /// that is currently in progress.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if unable to create the WAV file.
+    pub fn start_wav_recording(&mut self, path: PathBuf) -> Result<(), WavError> {
+        self.wav_recorder = None;
+
+        let wav_recorder = WavRecorder::create(path)?;
+        log::info!("Started recording audio to {}", wav_recorder.path().display());
+        self.wav_recorder = Some(wav_recorder);
+
+        Ok(())
+    }
+
+    pub fn stop_wav_recording(&mut self) {
+        self.wav_recorder = None;
+    }
+
+    pub fn is_wav_recording(&self) -> bool {
+        self.wav_recorder.is_some()
+    }
 }
 
 fn decibels_to_multiplier(decibels: f64) -> f64 {
@@ -100,6 +132,14 @@ impl AudioOutput for SdlAudioOutput {
 
     #[inline]
     fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), Self::Err> {
+        // Record every sample the core produces, before fast forward sample skipping and gain
+        if let Some(wav_recorder) = &mut self.wav_recorder {
+            if let Err(err) = wav_recorder.push_sample(sample_l, sample_r) {
+                log::error!("Stopping audio recording: {err}");
+                self.wav_recorder = None;
+            }
+        }
+
         self.sample_count += 1;
         if self.sample_count % self.speed_multiplier != 0 {
             return Ok(());
@@ -0,0 +1,220 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+// All emulation cores resample their audio output to 48 KHz stereo
+const SAMPLE_RATE: u32 = 48000;
+const CHANNELS: u16 = 2;
+const BYTES_PER_SAMPLE: u16 = 4;
+const BLOCK_ALIGN: u16 = CHANNELS * BYTES_PER_SAMPLE;
+
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+// RIFF header (12) + fmt chunk (8 + 18) + fact chunk (8 + 4) + data chunk header (8)
+const HEADER_LEN: u32 = 58;
+const RIFF_SIZE_OFFSET: u64 = 4;
+const FACT_SAMPLE_FRAMES_OFFSET: u64 = 46;
+const DATA_SIZE_OFFSET: u64 = 54;
+
+// WAV sizes are 32-bit; stop recording before the RIFF chunk size would overflow (~3.1 hours)
+const MAX_SAMPLE_FRAMES: u32 = (u32::MAX - HEADER_LEN) / BLOCK_ALIGN as u32;
+
+#[derive(Debug, Error)]
+pub enum WavError {
+    #[error("I/O error creating WAV file '{path}': {source}")]
+    Create {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("I/O error writing WAV file '{path}': {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("WAV file '{path}' reached the maximum WAV file size")]
+    SizeLimit { path: String },
+}
+
+/// Writes raw emulator audio output to a 48 KHz stereo WAV file with 32-bit float samples.
+///
+/// Samples are written exactly as the emulation core produced them, without any volume adjustment,
+/// and the WAV header sizes are filled in when the recorder is dropped.
+pub struct WavRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    sample_frames: u32,
+}
+
+impl WavRecorder {
+    /// Create a new WAV file at the given path, overwriting it if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if unable to create the file or write the WAV header.
+    pub fn create(path: PathBuf) -> Result<Self, WavError> {
+        let file = File::create(&path)
+            .map_err(|source| WavError::Create { path: path.display().to_string(), source })?;
+        let mut recorder = Self { path, writer: BufWriter::new(file), sample_frames: 0 };
+
+        recorder.write_header().map_err(|source| recorder.write_error(source))?;
+
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a single stereo sample.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if unable to write to the file or if the file has reached the maximum size
+    /// that the WAV format can represent.
+    pub fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), WavError> {
+        if self.sample_frames == MAX_SAMPLE_FRAMES {
+            return Err(WavError::SizeLimit { path: self.path.display().to_string() });
+        }
+
+        let mut bytes = [0; BLOCK_ALIGN as usize];
+        bytes[..4].copy_from_slice(&(sample_l as f32).to_le_bytes());
+        bytes[4..].copy_from_slice(&(sample_r as f32).to_le_bytes());
+        self.writer.write_all(&bytes).map_err(|source| self.write_error(source))?;
+
+        self.sample_frames += 1;
+
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let w = &mut self.writer;
+
+        w.write_all(b"RIFF")?;
+        w.write_all(&(HEADER_LEN - 8).to_le_bytes())?;
+        w.write_all(b"WAVE")?;
+
+        w.write_all(b"fmt ")?;
+        w.write_all(&18_u32.to_le_bytes())?;
+        w.write_all(&WAVE_FORMAT_IEEE_FLOAT.to_le_bytes())?;
+        w.write_all(&CHANNELS.to_le_bytes())?;
+        w.write_all(&SAMPLE_RATE.to_le_bytes())?;
+        w.write_all(&(SAMPLE_RATE * u32::from(BLOCK_ALIGN)).to_le_bytes())?;
+        w.write_all(&BLOCK_ALIGN.to_le_bytes())?;
+        w.write_all(&(8 * BYTES_PER_SAMPLE).to_le_bytes())?;
+        // Extension size; float formats have no extra format bytes
+        w.write_all(&0_u16.to_le_bytes())?;
+
+        // Non-PCM formats are required to have a fact chunk containing the number of sample frames
+        w.write_all(b"fact")?;
+        w.write_all(&4_u32.to_le_bytes())?;
+        w.write_all(&0_u32.to_le_bytes())?;
+
+        w.write_all(b"data")?;
+        w.write_all(&0_u32.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        let data_size = self.sample_frames * u32::from(BLOCK_ALIGN);
+
+        self.writer.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+        self.writer.write_all(&(HEADER_LEN - 8 + data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(FACT_SAMPLE_FRAMES_OFFSET))?;
+        self.writer.write_all(&self.sample_frames.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(DATA_SIZE_OFFSET))?;
+        self.writer.write_all(&data_size.to_le_bytes())?;
+        self.writer.flush()
+    }
+
+    fn write_error(&self, source: io::Error) -> WavError {
+        WavError::Write { path: self.path.display().to_string(), source }
+    }
+}
+
+impl Drop for WavRecorder {
+    fn drop(&mut self) {
+        match self.finalize() {
+            Ok(()) => {
+                let seconds = f64::from(self.sample_frames) / f64::from(SAMPLE_RATE);
+                log::info!("Saved {seconds:.2} seconds of audio to {}", self.path.display());
+            }
+            Err(err) => {
+                log::error!("Error finalizing WAV file: {}", self.write_error(err));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mainloop::testutil::TempFile;
+
+    fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn record(file: &TempFile, samples: &[(f64, f64)]) -> Vec<u8> {
+        let mut recorder = WavRecorder::create(file.0.clone()).unwrap();
+        for &(sample_l, sample_r) in samples {
+            recorder.push_sample(sample_l, sample_r).unwrap();
+        }
+        // Header sizes are filled in on drop
+        drop(recorder);
+
+        std::fs::read(&file.0).unwrap()
+    }
+
+    #[test]
+    fn header_and_data_length() {
+        let file = TempFile::new("header.wav");
+        let samples: Vec<_> =
+            (0..1000).map(|i| (f64::from(i) / 1000.0, -f64::from(i) / 2000.0)).collect();
+        let bytes = record(&file, &samples);
+
+        let data_len = samples.len() * usize::from(BLOCK_ALIGN);
+        assert_eq!(bytes.len(), HEADER_LEN as usize + data_len);
+
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(read_u32(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(read_u32(&bytes, 16), 18);
+        assert_eq!(read_u16(&bytes, 20), WAVE_FORMAT_IEEE_FLOAT);
+        assert_eq!(read_u16(&bytes, 22), 2);
+        assert_eq!(read_u32(&bytes, 24), 48000);
+        assert_eq!(read_u32(&bytes, 28), 48000 * 8);
+        assert_eq!(read_u16(&bytes, 32), 8);
+        assert_eq!(read_u16(&bytes, 34), 32);
+        assert_eq!(&bytes[38..42], b"fact");
+        assert_eq!(read_u32(&bytes, 46), 1000);
+        assert_eq!(&bytes[50..54], b"data");
+        assert_eq!(read_u32(&bytes, 54) as usize, data_len);
+
+        // Samples are interleaved little-endian f32
+        let data = &bytes[HEADER_LEN as usize..];
+        for (frame, &(sample_l, sample_r)) in data.chunks_exact(8).zip(&samples) {
+            assert_eq!(frame[..4], (sample_l as f32).to_le_bytes());
+            assert_eq!(frame[4..], (sample_r as f32).to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn empty_recording() {
+        let file = TempFile::new("empty.wav");
+        let bytes = record(&file, &[]);
+
+        assert_eq!(bytes.len(), HEADER_LEN as usize);
+        assert_eq!(read_u32(&bytes, 4), HEADER_LEN - 8);
+        assert_eq!(read_u32(&bytes, 46), 0);
+        assert_eq!(read_u32(&bytes, 54), 0);
+    }
+}
@@ -0,0 +1,120 @@
+//! Per-ROM cheat lists.
+//!
+//! Cheats are stored as TOML files containing a list of codes in whatever formats the console's
+//! decoder accepts. Enabled codes are decoded and handed to the emulation core, which applies them
+//! at the bus level.
+
+use crate::mainloop::features::ConsoleFeatures;
+use jgenesis_common::cheats::CheatSet;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CheatFileError {
+    #[error("I/O error reading cheat file '{path}': {source}")]
+    Read {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error parsing cheat file '{path}': {source}")]
+    Parse {
+        path: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("Error serializing cheats: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("I/O error writing cheat file '{path}': {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheatEntry {
+    pub code: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "true_fn")]
+    pub enabled: bool,
+}
+
+fn true_fn() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CheatFile {
+    #[serde(default)]
+    cheats: Vec<CheatEntry>,
+}
+
+/// Path of the cheat file for a ROM in the given cheat directory. See
+/// [`rom_base_path`](crate::rom_base_path) for the base path.
+///
+/// The file is named after the ROM's full file name, e.g. `Sonic.md.toml`. Keeping the extension
+/// gives ROMs with the same name for different consoles separate files, since each console uses
+/// different code formats.
+#[must_use]
+pub fn cheat_file_path<P: AsRef<Path>, Q: AsRef<Path>>(
+    cheat_directory: P,
+    rom_base_path: Q,
+) -> PathBuf {
+    let mut file_name = rom_base_path.as_ref().file_name().unwrap_or_default().to_os_string();
+    file_name.push(".toml");
+    cheat_directory.as_ref().join(file_name)
+}
+
+/// Read the cheat list at the given path. A missing file is treated as an empty list.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load_cheats<P: AsRef<Path>>(path: P) -> Result<Vec<CheatEntry>, CheatFileError> {
+    let path = path.as_ref();
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(source) => {
+            return Err(CheatFileError::Read { path: path.display().to_string(), source });
+        }
+    };
+
+    let file: CheatFile = toml::from_str(&contents)
+        .map_err(|source| CheatFileError::Parse { path: path.display().to_string(), source })?;
+    Ok(file.cheats)
+}
+
+/// Write the cheat list to the given path, creating the parent directory if needed.
+///
+/// # Errors
+///
+/// Returns an error if unable to serialize the list or write the file.
+pub fn save_cheats<P: AsRef<Path>>(path: P, cheats: &[CheatEntry]) -> Result<(), CheatFileError> {
+    let path = path.as_ref();
+    let contents = toml::to_string_pretty(&CheatFile { cheats: cheats.to_vec() })?;
+
+    let write_err = |source| CheatFileError::Write { path: path.display().to_string(), source };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_err)?;
+    }
+    fs::write(path, contents).map_err(write_err)?;
+
+    Ok(())
+}
+
+/// Decode every enabled cheat in the list. Codes that fail to decode are logged and skipped.
+pub fn build_cheat_set<Emulator: ConsoleFeatures>(cheats: &[CheatEntry]) -> CheatSet {
+    let patches = cheats.iter().filter(|entry| entry.enabled).flat_map(|entry| {
+        Emulator::decode_cheat(&entry.code).unwrap_or_else(|err| {
+            log::warn!("Skipping cheat '{}': {err}", entry.code);
+            vec![]
+        })
+    });
+    CheatSet::from_patches(patches)
+}
@@ -1,3 +1,4 @@
+mod cpu;
 mod eguisdl;
 pub mod gb;
 pub mod genesis;
@@ -57,7 +58,7 @@ impl<Emulator> DebuggerWindow<Emulator> {
         video: &VideoSubsystem,
         render_fn: Box<DebugRenderFn<Emulator>>,
     ) -> Result<Self, DebuggerError> {
-        let window = video.window("Memory Viewer", 800, 700).resizable().metal_view().build()?;
+        let window = video.window("Debugger", 800, 700).resizable().metal_view().build()?;
         let (width, height) = window.size();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
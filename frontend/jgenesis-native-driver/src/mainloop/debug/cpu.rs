@@ -0,0 +1,356 @@
+//! CPU debugger pane shared between all consoles
+
+use egui::{Color32, ComboBox, Grid, RichText, ScrollArea, TextEdit, Ui};
+use jgenesis_common::debug::{
+    CpuArch, Debuggable, StopEvent, StopReason, Watchpoint, WatchpointKind,
+};
+
+const DISASSEMBLY_LINES: usize = 32;
+
+#[derive(Debug)]
+pub(super) struct CpuPane {
+    cpu: usize,
+    // Address of the first disassembled instruction; follows the PC while the CPU is stopped
+    disassembly_start: Option<u32>,
+    cursor: Option<u32>,
+    goto_address: String,
+    breakpoint_address: String,
+    watchpoint_address: String,
+    watchpoint_len: String,
+    watchpoint_kind: WatchpointKind,
+    last_stop: Option<StopEvent>,
+}
+
+impl CpuPane {
+    pub(super) fn new() -> Self {
+        Self {
+            cpu: 0,
+            disassembly_start: None,
+            cursor: None,
+            goto_address: String::new(),
+            breakpoint_address: String::new(),
+            watchpoint_address: String::new(),
+            watchpoint_len: "1".into(),
+            watchpoint_kind: WatchpointKind::Write,
+            last_stop: None,
+        }
+    }
+
+    pub(super) fn render<Emulator: Debuggable>(&mut self, ui: &mut Ui, emulator: &mut Emulator) {
+        let cpus = emulator.debug_cpus();
+        self.cpu = self.cpu.min(cpus.len() - 1);
+
+        let stop = emulator.debug_hooks().stop_event();
+        if let Some(stop) = stop {
+            if self.last_stop != Some(stop) {
+                // Follow the CPU that stopped and scroll the disassembly to its PC
+                self.cpu = stop.cpu;
+                self.disassembly_start = None;
+            }
+        }
+        self.last_stop = stop;
+
+        let cpu = self.cpu;
+        let arch = cpus[cpu].arch;
+        let address_mask = arch.address_mask();
+
+        ui.horizontal(|ui| {
+            ComboBox::from_label("CPU").selected_text(cpus[cpu].name).show_ui(ui, |ui| {
+                for (i, debug_cpu) in cpus.iter().enumerate() {
+                    if ui.selectable_value(&mut self.cpu, i, debug_cpu.name).changed() {
+                        self.disassembly_start = None;
+                        self.cursor = None;
+                    }
+                }
+            });
+
+            ui.add_space(15.0);
+            ui.label(stop_text(stop, cpus[stop.map_or(0, |stop| stop.cpu)].name));
+        });
+
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            let hooks = emulator.debug_hooks_mut();
+            if stop.is_some() {
+                if ui.button("Continue").clicked() {
+                    hooks.resume();
+                }
+            } else if ui.button("Break").clicked() {
+                hooks.interrupt(cpu);
+            }
+
+            ui.add_enabled_ui(stop.is_some(), |ui| {
+                if ui.button("Step into").clicked() {
+                    emulator.debug_hooks_mut().step(cpu);
+                }
+
+                if ui.button("Step over").clicked() {
+                    step_over(emulator, cpu);
+                }
+
+                ui.add_enabled_ui(self.cursor.is_some(), |ui| {
+                    if ui.button("Run to cursor").clicked() {
+                        if let Some(cursor) = self.cursor {
+                            emulator.debug_hooks_mut().run_to(cpu, cursor);
+                        }
+                    }
+                });
+            });
+        });
+
+        ui.add_space(10.0);
+
+        let registers = emulator.read_registers(cpu);
+        render_registers(ui, arch, &registers);
+
+        ui.add_space(10.0);
+
+        let pc = arch.pc(&registers);
+        ui.horizontal(|ui| {
+            ui.label("Go to");
+            let response = ui.add(TextEdit::singleline(&mut self.goto_address).desired_width(80.0));
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                if let Some(address) = parse_hex(&self.goto_address) {
+                    self.disassembly_start = Some(address & address_mask);
+                }
+            }
+
+            if ui.button("Follow PC").clicked() {
+                self.disassembly_start = None;
+            }
+        });
+
+        ui.add_space(5.0);
+
+        let start = *self.disassembly_start.get_or_insert(pc);
+        self.render_disassembly(ui, emulator, start, pc);
+
+        ui.add_space(10.0);
+
+        ui.columns(2, |columns| {
+            self.render_breakpoints(&mut columns[0], emulator, address_mask);
+            self.render_watchpoints(&mut columns[1], emulator, address_mask);
+        });
+    }
+
+    fn render_disassembly<Emulator: Debuggable>(
+        &mut self,
+        ui: &mut Ui,
+        emulator: &mut Emulator,
+        start: u32,
+        pc: u32,
+    ) {
+        let cpu = self.cpu;
+        let address_mask = emulator.debug_cpus()[cpu].arch.address_mask();
+        let address_width = hex_width(address_mask);
+
+        let mut toggle_breakpoint = None;
+        ScrollArea::vertical().id_source("disassembly").max_height(300.0).show(ui, |ui| {
+            let mut address = start;
+            for _ in 0..DISASSEMBLY_LINES {
+                let (text, len) = emulator.disassemble(cpu, address);
+                let is_breakpoint = emulator.debug_hooks().breakpoints(cpu).contains(&address);
+
+                ui.horizontal(|ui| {
+                    let line = format!(
+                        "{}{} {address:0address_width$X}  {text}",
+                        if is_breakpoint { "*" } else { " " },
+                        if address == pc { ">" } else { " " },
+                    );
+                    let mut line = RichText::new(line).monospace();
+                    if address == pc {
+                        line = line.color(Color32::YELLOW);
+                    } else if is_breakpoint {
+                        line = line.color(Color32::LIGHT_RED);
+                    }
+
+                    if ui.selectable_label(self.cursor == Some(address), line).clicked() {
+                        self.cursor = Some(address);
+                    }
+                    if ui.small_button("Toggle breakpoint").clicked() {
+                        toggle_breakpoint = Some(address);
+                    }
+                });
+
+                address = address.wrapping_add(len.max(1)) & address_mask;
+            }
+        });
+
+        if let Some(address) = toggle_breakpoint {
+            let hooks = emulator.debug_hooks_mut();
+            if hooks.breakpoints(cpu).contains(&address) {
+                hooks.remove_breakpoint(cpu, address);
+            } else {
+                hooks.add_breakpoint(cpu, address);
+            }
+        }
+    }
+
+    fn render_breakpoints<Emulator: Debuggable>(
+        &mut self,
+        ui: &mut Ui,
+        emulator: &mut Emulator,
+        address_mask: u32,
+    ) {
+        let cpu = self.cpu;
+        let address_width = hex_width(address_mask);
+
+        ui.heading("Breakpoints");
+
+        let mut remove = None;
+        for &address in emulator.debug_hooks().breakpoints(cpu) {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{address:0address_width$X}"));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(address);
+                }
+            });
+        }
+
+        if let Some(address) = remove {
+            emulator.debug_hooks_mut().remove_breakpoint(cpu, address);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.breakpoint_address)
+                    .hint_text("Address")
+                    .desired_width(80.0),
+            );
+            if ui.button("Add").clicked() {
+                if let Some(address) = parse_hex(&self.breakpoint_address) {
+                    emulator.debug_hooks_mut().add_breakpoint(cpu, address & address_mask);
+                    self.breakpoint_address.clear();
+                }
+            }
+        });
+    }
+
+    fn render_watchpoints<Emulator: Debuggable>(
+        &mut self,
+        ui: &mut Ui,
+        emulator: &mut Emulator,
+        address_mask: u32,
+    ) {
+        let cpu = self.cpu;
+        let address_width = hex_width(address_mask);
+
+        ui.heading("Watchpoints");
+
+        let mut remove = None;
+        for &watchpoint in emulator.debug_hooks().watchpoints(cpu) {
+            ui.horizontal(|ui| {
+                ui.monospace(format!(
+                    "{:0address_width$X} ({} bytes, {})",
+                    watchpoint.address,
+                    watchpoint.len,
+                    watchpoint_kind_str(watchpoint.kind)
+                ));
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(watchpoint);
+                }
+            });
+        }
+
+        if let Some(watchpoint) = remove {
+            emulator.debug_hooks_mut().remove_watchpoint(cpu, watchpoint);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.watchpoint_address)
+                    .hint_text("Address")
+                    .desired_width(80.0),
+            );
+            ui.add(
+                TextEdit::singleline(&mut self.watchpoint_len)
+                    .hint_text("Length")
+                    .desired_width(30.0),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            for kind in [WatchpointKind::Read, WatchpointKind::Write, WatchpointKind::Access] {
+                ui.radio_value(&mut self.watchpoint_kind, kind, watchpoint_kind_str(kind));
+            }
+
+            if ui.button("Add").clicked() {
+                let address = parse_hex(&self.watchpoint_address);
+                let len = self.watchpoint_len.trim().parse::<u32>().ok().filter(|&len| len != 0);
+                if let (Some(address), Some(len)) = (address, len) {
+                    emulator.debug_hooks_mut().add_watchpoint(
+                        cpu,
+                        Watchpoint {
+                            address: address & address_mask,
+                            len,
+                            kind: self.watchpoint_kind,
+                        },
+                    );
+                    self.watchpoint_address.clear();
+                }
+            }
+        });
+    }
+}
+
+fn render_registers(ui: &mut Ui, arch: CpuArch, values: &[u32]) {
+    Grid::new("cpu_registers").num_columns(8).spacing([15.0, 3.0]).show(ui, |ui| {
+        for (i, (register, &value)) in arch.registers().iter().zip(values).enumerate() {
+            ui.monospace(register.name);
+
+            let width = register.bits.div_ceil(4) as usize;
+            ui.monospace(format!("{value:0width$X}"));
+
+            if i % 4 == 3 {
+                ui.end_row();
+            }
+        }
+    });
+}
+
+/// Step over subroutine calls by running to the instruction after the call; any other instruction
+/// is single stepped.
+fn step_over<Emulator: Debuggable>(emulator: &mut Emulator, cpu: usize) {
+    let arch = emulator.debug_cpus()[cpu].arch;
+    let pc = arch.pc(&emulator.read_registers(cpu));
+
+    let bytes = [emulator.read_memory(cpu, pc), emulator.read_memory(cpu, pc.wrapping_add(1))];
+    match arch.call_length(bytes) {
+        Some(len) => {
+            emulator.debug_hooks_mut().run_to(cpu, pc.wrapping_add(len) & arch.address_mask());
+        }
+        None => emulator.debug_hooks_mut().step(cpu),
+    }
+}
+
+fn stop_text(stop: Option<StopEvent>, cpu_name: &str) -> String {
+    let Some(stop) = stop else { return "Running".into() };
+
+    match stop.reason {
+        StopReason::Breakpoint => format!("{cpu_name} stopped at breakpoint"),
+        StopReason::Watchpoint { kind, address } => {
+            format!("{cpu_name} stopped at {} watchpoint ({address:X})", watchpoint_kind_str(kind))
+        }
+        StopReason::Step => format!("{cpu_name} stopped after step"),
+        StopReason::Interrupt => "Stopped".into(),
+    }
+}
+
+fn watchpoint_kind_str(kind: WatchpointKind) -> &'static str {
+    match kind {
+        WatchpointKind::Read => "read",
+        WatchpointKind::Write => "write",
+        WatchpointKind::Access => "access",
+    }
+}
+
+fn hex_width(mask: u32) -> usize {
+    (32 - mask.leading_zeros()).div_ceil(4) as usize
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let s = s.strip_prefix('$').or_else(|| s.strip_prefix("0x")).unwrap_or(s);
+    u32::from_str_radix(s, 16).ok()
+}
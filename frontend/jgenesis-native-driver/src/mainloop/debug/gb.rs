@@ -1,4 +1,5 @@
 use crate::mainloop::debug;
+use crate::mainloop::debug::cpu::CpuPane;
 use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, SelectableButton};
 use egui::{CentralPanel, Grid, ScrollArea, Vec2};
 use gb_core::api::{BackgroundTileMap, GameBoyEmulator};
@@ -10,11 +11,13 @@ enum Tab {
     Background,
     Sprites,
     Palettes,
+    Cpu,
 }
 
 #[derive(Debug)]
 struct State {
     tab: Tab,
+    cpu_pane: CpuPane,
     background_tile_map: BackgroundTileMap,
     background_buffer: Vec<Color>,
     background_texture: Option<(wgpu::Texture, egui::TextureId)>,
@@ -29,6 +32,7 @@ impl State {
     fn new() -> Self {
         Self {
             tab: Tab::default(),
+            cpu_pane: CpuPane::new(),
             background_tile_map: BackgroundTileMap::default(),
             background_buffer: vec![Color::default(); 256 * 256],
             background_texture: None,
@@ -58,6 +62,7 @@ fn render(mut ctx: DebugRenderContext<'_, GameBoyEmulator>, state: &mut State) {
             ui.add(SelectableButton::new("Background", &mut state.tab, Tab::Background));
             ui.add(SelectableButton::new("Sprites", &mut state.tab, Tab::Sprites));
             ui.add(SelectableButton::new("Palettes", &mut state.tab, Tab::Palettes));
+            ui.add(SelectableButton::new("CPU", &mut state.tab, Tab::Cpu));
         });
 
         ui.add_space(15.0);
@@ -136,6 +141,7 @@ fn render(mut ctx: DebugRenderContext<'_, GameBoyEmulator>, state: &mut State) {
                     ui.end_row();
                 });
             }
+            Tab::Cpu => state.cpu_pane.render(ui, ctx.emulator),
         }
     });
 }
@@ -1,7 +1,9 @@
 use crate::mainloop::debug;
+use crate::mainloop::debug::cpu::CpuPane;
 use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, SelectableButton};
 use egui::{CentralPanel, ScrollArea, Vec2};
 use genesis_core::GenesisEmulator;
+use jgenesis_common::debug::Debuggable;
 use jgenesis_common::frontend::Color;
 use segacd_core::api::SegaCdEmulator;
 
@@ -10,10 +12,12 @@ enum Tab {
     Cram,
     #[default]
     Vram,
+    Cpu,
 }
 
 struct State {
     tab: Tab,
+    cpu_pane: CpuPane,
     vram_palette: u8,
     cram_texture: Option<(wgpu::Texture, egui::TextureId)>,
     vram_texture: Option<(wgpu::Texture, egui::TextureId)>,
@@ -25,6 +29,7 @@ impl State {
     fn new() -> Self {
         Self {
             tab: Tab::default(),
+            cpu_pane: CpuPane::new(),
             vram_palette: 0,
             cram_texture: None,
             vram_texture: None,
@@ -60,12 +65,15 @@ impl GenesisBase for SegaCdEmulator {
     }
 }
 
-pub(crate) fn render_fn<Emulator: GenesisBase>() -> Box<DebugRenderFn<Emulator>> {
+pub(crate) fn render_fn<Emulator: GenesisBase + Debuggable>() -> Box<DebugRenderFn<Emulator>> {
     let mut state = State::new();
     Box::new(move |ctx| render(ctx, &mut state))
 }
 
-fn render<Emulator: GenesisBase>(mut ctx: DebugRenderContext<'_, Emulator>, state: &mut State) {
+fn render<Emulator: GenesisBase + Debuggable>(
+    mut ctx: DebugRenderContext<'_, Emulator>,
+    state: &mut State,
+) {
     update_cram_texture(&mut ctx, state);
     update_vram_texture(&mut ctx, state);
 
@@ -75,6 +83,7 @@ fn render<Emulator: GenesisBase>(mut ctx: DebugRenderContext<'_, Emulator>, stat
         ui.horizontal(|ui| {
             ui.add(SelectableButton::new("VRAM", &mut state.tab, Tab::Vram));
             ui.add(SelectableButton::new("CRAM", &mut state.tab, Tab::Cram));
+            ui.add(SelectableButton::new("CPU", &mut state.tab, Tab::Cpu));
         });
 
         ui.add_space(15.0);
@@ -100,6 +109,7 @@ fn render<Emulator: GenesisBase>(mut ctx: DebugRenderContext<'_, Emulator>, stat
                     ui.image((egui_texture, Vec2::new(screen_width, screen_width * 0.5)));
                 });
             }
+            Tab::Cpu => state.cpu_pane.render(ui, ctx.emulator),
         }
     });
 }
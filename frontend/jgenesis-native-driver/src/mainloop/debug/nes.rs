@@ -1,6 +1,7 @@
 use crate::mainloop::debug;
+use crate::mainloop::debug::cpu::CpuPane;
 use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, SelectableButton};
-use egui::{CentralPanel, ScrollArea, Vec2};
+use egui::{CentralPanel, ScrollArea, Ui, Vec2};
 use jgenesis_common::frontend::Color;
 use nes_core::api::{NesEmulator, PatternTable};
 
@@ -10,11 +11,14 @@ enum Tab {
     Nametables,
     Oam,
     PaletteRam,
+    Nsf,
+    Cpu,
 }
 
 #[derive(Debug)]
 struct State {
     tab: Tab,
+    cpu_pane: CpuPane,
     nametables_pattern_table: PatternTable,
     nametables_buffer: Vec<Color>,
     nametables_texture: Option<(wgpu::Texture, egui::TextureId)>,
@@ -29,6 +33,7 @@ impl State {
     fn new() -> Self {
         Self {
             tab: Tab::default(),
+            cpu_pane: CpuPane::new(),
             nametables_pattern_table: PatternTable::Zero,
             nametables_buffer: vec![Color::default(); 4 * 256 * 240],
             nametables_texture: None,
@@ -58,6 +63,11 @@ fn render(mut ctx: DebugRenderContext<'_, NesEmulator>, state: &mut State) {
             ui.add(SelectableButton::new("Nametables", &mut state.tab, Tab::Nametables));
             ui.add(SelectableButton::new("OAM", &mut state.tab, Tab::Oam));
             ui.add(SelectableButton::new("Palette RAM", &mut state.tab, Tab::PaletteRam));
+            ui.add(SelectableButton::new("CPU", &mut state.tab, Tab::Cpu));
+
+            if ctx.emulator.nsf_metadata().is_some() {
+                ui.add(SelectableButton::new("NSF", &mut state.tab, Tab::Nsf));
+            }
         });
 
         ui.add_space(15.0);
@@ -123,8 +133,56 @@ fn render(mut ctx: DebugRenderContext<'_, NesEmulator>, state: &mut State) {
                     ui.image((egui_texture, Vec2::new(screen_width * 0.325, screen_width * 0.65)));
                 });
             }
+            Tab::Nsf => render_nsf_tab(ui, ctx.emulator),
+            Tab::Cpu => state.cpu_pane.render(ui, ctx.emulator),
+        }
+    });
+}
+
+fn render_nsf_tab(ui: &mut Ui, emulator: &mut NesEmulator) {
+    let (Some(metadata), Some(track)) = (emulator.nsf_metadata(), emulator.nsf_track()) else {
+        ui.label("No NSF file loaded");
+        return;
+    };
+
+    for (label, value) in [
+        ("Title", &metadata.title),
+        ("Artist", &metadata.artist),
+        ("Copyright", &metadata.copyright),
+    ] {
+        ui.label(format!("{label}: {value}"));
+    }
+
+    let expansion_chips = metadata.expansion_audio.chip_names();
+    if !expansion_chips.is_empty() {
+        ui.label(format!("Expansion audio: {}", expansion_chips.join(", ")));
+    }
+
+    ui.add_space(10.0);
+
+    let total_tracks = metadata.total_tracks;
+    let track_label = match metadata.track_label(track) {
+        Some(label) => format!("Track {} / {total_tracks}: {label}", track + 1),
+        None => format!("Track {} / {total_tracks}", track + 1),
+    };
+    ui.label(track_label);
+
+    ui.horizontal(|ui| {
+        if ui.button("Previous").clicked() {
+            emulator.select_nsf_track(track.checked_sub(1).unwrap_or(total_tracks - 1));
+        }
+
+        if ui.button("Restart").clicked() {
+            emulator.select_nsf_track(track);
+        }
+
+        if ui.button("Next").clicked() {
+            emulator.select_nsf_track((track + 1) % total_tracks);
         }
     });
+
+    ui.add_space(5.0);
+    ui.label("Left/Right on the P1 controller also select the previous/next track");
 }
 
 fn update_nametables_texture(ctx: &mut DebugRenderContext<'_, NesEmulator>, state: &mut State) {
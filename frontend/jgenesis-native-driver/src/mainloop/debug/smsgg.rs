@@ -1,4 +1,5 @@
 use crate::mainloop::debug;
+use crate::mainloop::debug::cpu::CpuPane;
 use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, SelectableButton};
 use egui::{CentralPanel, ScrollArea, Vec2};
 use jgenesis_common::frontend::Color;
@@ -9,10 +10,12 @@ enum Tab {
     Cram,
     #[default]
     Vram,
+    Cpu,
 }
 
 struct State {
     tab: Tab,
+    cpu_pane: CpuPane,
     vram_palette: u8,
     cram_texture: Option<(wgpu::Texture, egui::TextureId)>,
     vram_texture: Option<(wgpu::Texture, egui::TextureId)>,
@@ -24,6 +27,7 @@ impl State {
     fn new() -> Self {
         Self {
             tab: Tab::default(),
+            cpu_pane: CpuPane::new(),
             vram_palette: 0,
             cram_texture: None,
             vram_texture: None,
@@ -48,6 +52,7 @@ fn render(mut ctx: DebugRenderContext<'_, SmsGgEmulator>, state: &mut State) {
         ui.horizontal(|ui| {
             ui.add(SelectableButton::new("VRAM", &mut state.tab, Tab::Vram));
             ui.add(SelectableButton::new("CRAM", &mut state.tab, Tab::Cram));
+            ui.add(SelectableButton::new("CPU", &mut state.tab, Tab::Cpu));
         });
 
         ui.add_space(15.0);
@@ -72,6 +77,7 @@ fn render(mut ctx: DebugRenderContext<'_, SmsGgEmulator>, state: &mut State) {
                     ui.image((vram_texture, Vec2::new(screen_width, screen_width * 0.5)));
                 });
             }
+            Tab::Cpu => state.cpu_pane.render(ui, ctx.emulator),
         }
     });
 }
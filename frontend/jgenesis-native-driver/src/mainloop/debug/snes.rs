@@ -1,14 +1,17 @@
 use crate::mainloop::debug;
+use crate::mainloop::debug::cpu::CpuPane;
 use crate::mainloop::debug::{DebugRenderContext, DebugRenderFn, SelectableButton};
 use egui::{CentralPanel, ScrollArea, Vec2};
 use jgenesis_common::frontend::Color;
 use snes_core::api::SnesEmulator;
+use snes_core::spc::SpcPlayer;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Tab {
     Cgram,
     #[default]
     Vram,
+    Cpu,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -25,6 +28,7 @@ const VRAM_BUFFER_LEN: usize = 256 * 1024;
 
 struct State {
     tab: Tab,
+    cpu_pane: CpuPane,
     vram_mode: VramMode,
     vram_palette: u8,
     cgram_texture: Option<(wgpu::Texture, egui::TextureId)>,
@@ -40,6 +44,7 @@ impl State {
     fn new() -> Self {
         Self {
             tab: Tab::default(),
+            cpu_pane: CpuPane::new(),
             vram_mode: VramMode::default(),
             vram_palette: 0,
             cgram_texture: None,
@@ -64,6 +69,44 @@ pub fn render_fn() -> Box<DebugRenderFn<SnesEmulator>> {
     Box::new(move |ctx| render(ctx, &mut state))
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SpcPlayerTab {
+    #[default]
+    Metadata,
+    Cpu,
+}
+
+pub fn spc_player_render_fn() -> Box<DebugRenderFn<SpcPlayer>> {
+    let mut tab = SpcPlayerTab::default();
+    let mut cpu_pane = CpuPane::new();
+    Box::new(move |ctx| {
+        CentralPanel::default().show(ctx.egui_ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(SelectableButton::new("Metadata", &mut tab, SpcPlayerTab::Metadata));
+                ui.add(SelectableButton::new("CPU", &mut tab, SpcPlayerTab::Cpu));
+            });
+
+            ui.add_space(15.0);
+
+            match tab {
+                SpcPlayerTab::Metadata => {
+                    let metadata = ctx.emulator.metadata();
+                    for (label, value) in [
+                        ("Song", &metadata.song_title),
+                        ("Game", &metadata.game_title),
+                        ("Artist", &metadata.artist),
+                        ("Dumper", &metadata.dumper),
+                        ("Comments", &metadata.comments),
+                    ] {
+                        ui.label(format!("{label}: {value}"));
+                    }
+                }
+                SpcPlayerTab::Cpu => cpu_pane.render(ui, ctx.emulator),
+            }
+        });
+    })
+}
+
 fn render(mut ctx: DebugRenderContext<'_, SnesEmulator>, state: &mut State) {
     update_cgram_texture(&mut ctx, state);
     update_vram_texture(&mut ctx, state);
@@ -74,6 +117,7 @@ fn render(mut ctx: DebugRenderContext<'_, SnesEmulator>, state: &mut State) {
         ui.horizontal(|ui| {
             ui.add(SelectableButton::new("VRAM", &mut state.tab, Tab::Vram));
             ui.add(SelectableButton::new("CGRAM", &mut state.tab, Tab::Cgram));
+            ui.add(SelectableButton::new("CPU", &mut state.tab, Tab::Cpu));
         });
 
         ui.add_space(15.0);
@@ -147,6 +191,7 @@ fn render(mut ctx: DebugRenderContext<'_, SnesEmulator>, state: &mut State) {
                     }
                 });
             }
+            Tab::Cpu => state.cpu_pane.render(ui, ctx.emulator),
         }
     });
 }
@@ -0,0 +1,123 @@
+//! Frontend features that only some consoles support.
+
+use gb_core::api::GameBoyEmulator;
+use genesis_core::GenesisEmulator;
+use jgenesis_common::cheats::{CheatError, CheatPatch, CheatResult, CheatSet};
+use nes_core::api::NesEmulator;
+use segacd_core::api::SegaCdEmulator;
+use smsgg_core::vgm::VgmLogger;
+use smsgg_core::SmsGgEmulator;
+use snes_core::api::SnesEmulator;
+use snes_core::spc::SpcPlayer;
+
+/// Optional console features exposed by the frontend. Every method defaults to reporting the
+/// feature as unsupported, so each emulator only overrides the features it actually has.
+pub trait ConsoleFeatures {
+    /// Start logging sound chip writes. Returns false if the console's sound hardware cannot be
+    /// represented in a VGM file.
+    fn start_vgm_logging(&mut self) -> bool {
+        false
+    }
+
+    fn vgm_logger_mut(&mut self) -> Option<&mut VgmLogger> {
+        None
+    }
+
+    /// Export the current state of the audio processor as an SPC file. Returns None if the console
+    /// does not have an SNES APU.
+    fn export_spc(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Switch to the next Famicom Disk System disk side. Returns false if the emulator is not
+    /// running an FDS game.
+    fn switch_fds_disk_side(&mut self) -> bool {
+        false
+    }
+
+    /// Decode a cheat code in any of the formats supported for this console.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code is not valid for this console.
+    fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+        Err(CheatError::UnrecognizedFormat(code.into()))
+    }
+
+    fn set_cheats(&mut self, _cheats: CheatSet) {}
+}
+
+macro_rules! impl_vgm_and_cheats {
+    ($($emulator:ty => $decode_fn:path),* $(,)?) => {
+        $(
+            impl ConsoleFeatures for $emulator {
+                fn start_vgm_logging(&mut self) -> bool {
+                    <$emulator>::start_vgm_logging(self);
+                    true
+                }
+
+                fn vgm_logger_mut(&mut self) -> Option<&mut VgmLogger> {
+                    Some(<$emulator>::vgm_logger_mut(self))
+                }
+
+                fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+                    $decode_fn(code)
+                }
+
+                fn set_cheats(&mut self, cheats: CheatSet) {
+                    <$emulator>::set_cheats(self, cheats);
+                }
+            }
+        )*
+    };
+}
+
+impl_vgm_and_cheats!(
+    SmsGgEmulator => smsgg_core::cheats::decode_cheat,
+    GenesisEmulator => genesis_core::cheats::decode_cheat,
+    SegaCdEmulator => genesis_core::cheats::decode_cheat,
+);
+
+impl ConsoleFeatures for NesEmulator {
+    fn switch_fds_disk_side(&mut self) -> bool {
+        NesEmulator::switch_fds_disk_side(self)
+    }
+
+    fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+        nes_core::cheats::decode_cheat(code)
+    }
+
+    fn set_cheats(&mut self, cheats: CheatSet) {
+        NesEmulator::set_cheats(self, cheats);
+    }
+}
+
+impl ConsoleFeatures for SnesEmulator {
+    fn export_spc(&mut self) -> Option<Vec<u8>> {
+        Some(SnesEmulator::export_spc(self))
+    }
+
+    fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+        snes_core::cheats::decode_cheat(code)
+    }
+
+    fn set_cheats(&mut self, cheats: CheatSet) {
+        SnesEmulator::set_cheats(self, cheats);
+    }
+}
+
+impl ConsoleFeatures for SpcPlayer {
+    fn export_spc(&mut self) -> Option<Vec<u8>> {
+        Some(SpcPlayer::export_spc(self))
+    }
+}
+
+impl ConsoleFeatures for GameBoyEmulator {
+    fn decode_cheat(code: &str) -> CheatResult<Vec<CheatPatch>> {
+        gb_core::cheats::decode_cheat(code)
+    }
+
+    fn set_cheats(&mut self, cheats: CheatSet) {
+        GameBoyEmulator::set_cheats(self, cheats);
+    }
+}
@@ -1,12 +1,13 @@
 use crate::config::GameBoyConfig;
 use crate::mainloop::save::FsSaveWriter;
-use crate::mainloop::{basic_input_mapper_fn, debug, file_name_no_ext, NativeEmulatorError};
+use crate::mainloop::{
+    basic_input_mapper_fn, debug, file_name_no_ext, read_rom_file, savestate, RomFile,
+    SaveStateConsole,
+};
 use crate::{config, AudioError, NativeEmulator, NativeEmulatorResult};
 use gb_core::api::{GameBoyEmulator, GameBoyEmulatorConfig};
 use gb_core::inputs::{GameBoyButton, GameBoyInputs};
 use jgenesis_common::frontend::EmulatorTrait;
-use std::fs;
-use std::path::Path;
 
 pub type NativeGameBoyEmulator =
     NativeEmulator<GameBoyInputs, GameBoyButton, GameBoyEmulatorConfig, GameBoyEmulator>;
@@ -37,6 +38,8 @@ impl NativeGameBoyEmulator {
     }
 }
 
+const SUPPORTED_EXTENSIONS: &[&str] = &["gb", "gbc"];
+
 /// Create an emulator with the Game Boy core with the given config.
 ///
 /// # Errors
@@ -45,20 +48,17 @@ impl NativeGameBoyEmulator {
 pub fn create_gb(config: Box<GameBoyConfig>) -> NativeEmulatorResult<NativeGameBoyEmulator> {
     log::info!("Running with config: {config}");
 
-    let rom_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: config.common.rom_file_path.clone(),
-        source,
-    })?;
+    let RomFile { contents: rom, base_path, .. } =
+        read_rom_file(&config.common, SUPPORTED_EXTENSIONS)?;
 
-    let save_path = rom_path.with_extension("sav");
-    let save_state_path = rom_path.with_extension("ss0");
+    let save_path = base_path.with_extension("sav");
     let mut save_writer = FsSaveWriter::new(save_path);
 
     let emulator_config = config.to_emulator_config();
+    let rom_checksum = savestate::rom_checksum(&rom);
     let emulator = GameBoyEmulator::create(rom, emulator_config, &mut save_writer)?;
 
-    let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
+    let rom_title = file_name_no_ext(&base_path)?;
     let window_title = format!("gb - {rom_title}");
 
     NativeGameBoyEmulator::new(
@@ -68,7 +68,9 @@ pub fn create_gb(config: Box<GameBoyConfig>) -> NativeEmulatorResult<NativeGameB
         config::DEFAULT_GB_WINDOW_SIZE,
         &window_title,
         save_writer,
-        save_state_path,
+        base_path,
+        SaveStateConsole::GameBoy,
+        rom_checksum,
         basic_input_mapper_fn(&GameBoyButton::ALL),
         debug::gb::render_fn,
     )
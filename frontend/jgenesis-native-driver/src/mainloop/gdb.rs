@@ -0,0 +1,796 @@
+//! Minimal GDB remote serial protocol server.
+//!
+//! The server listens on localhost and accepts a single client at a time. It debugs one CPU per
+//! session; for consoles with more than one debuggable CPU, the target is selected by name when
+//! the server is created. Only the packets that GDB needs for register/memory access,
+//! breakpoints, watchpoints, and single stepping are supported; everything else receives an empty
+//! response, which tells GDB that the packet is not supported.
+
+use jgenesis_common::debug::{
+    CpuArch, Debuggable, Register, StopReason, Watchpoint, WatchpointKind,
+};
+use std::fmt::Write as _;
+use std::io;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+// GDB's Ctrl+C interrupt is sent as a raw byte outside of a packet
+const INTERRUPT: u8 = 0x03;
+
+// Maximum packet size advertised to GDB in the qSupported response
+const PACKET_SIZE: u32 = 0x4000;
+
+const SIGINT: u8 = 2;
+const SIGTRAP: u8 = 5;
+
+struct GdbClient {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    no_ack: bool,
+}
+
+impl GdbClient {
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let checksum = data.bytes().fold(0_u8, u8::wrapping_add);
+        let packet = format!("${data}#{checksum:02x}");
+
+        // Packets are small and the client is local, so it's simplest to briefly block on writes
+        self.stream.set_nonblocking(false)?;
+        self.stream.write_all(packet.as_bytes())?;
+        self.stream.set_nonblocking(true)
+    }
+
+    fn ack(&mut self) -> io::Result<()> {
+        if self.no_ack {
+            return Ok(());
+        }
+
+        self.stream.set_nonblocking(false)?;
+        self.stream.write_all(b"+")?;
+        self.stream.set_nonblocking(true)
+    }
+
+    /// Read any pending bytes from the socket. Returns false if the client disconnected.
+    fn receive(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Ok(false),
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(true),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Remove the next complete packet or interrupt from the receive buffer.
+    fn next_message(&mut self) -> Option<Message> {
+        loop {
+            match *self.buffer.first()? {
+                INTERRUPT => {
+                    self.buffer.remove(0);
+                    return Some(Message::Interrupt);
+                }
+                b'$' => {
+                    let end = self.buffer.iter().position(|&b| b == b'#')?;
+                    if self.buffer.len() < end + 3 {
+                        return None;
+                    }
+
+                    let data = String::from_utf8_lossy(&self.buffer[1..end]).into_owned();
+                    let checksum = std::str::from_utf8(&self.buffer[end + 1..end + 3])
+                        .ok()
+                        .and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+                    self.buffer.drain(..end + 3);
+
+                    let expected = data.bytes().fold(0_u8, u8::wrapping_add);
+                    if !self.no_ack && checksum != Some(expected) {
+                        log::warn!("Discarding GDB packet with bad checksum: {data}");
+                        return Some(Message::BadChecksum);
+                    }
+
+                    return Some(Message::Packet(data));
+                }
+                _ => {
+                    // Acks and anything else outside of a packet
+                    self.buffer.remove(0);
+                }
+            }
+        }
+    }
+}
+
+enum Message {
+    Packet(String),
+    BadChecksum,
+    Interrupt,
+}
+
+enum Response {
+    Reply(String),
+    // Execution resumed; the reply is sent when the target stops
+    Resumed,
+    Disconnect,
+}
+
+impl From<&str> for Response {
+    fn from(value: &str) -> Self {
+        Self::Reply(value.into())
+    }
+}
+
+impl From<String> for Response {
+    fn from(value: String) -> Self {
+        Self::Reply(value)
+    }
+}
+
+pub struct GdbServer {
+    listener: TcpListener,
+    client: Option<GdbClient>,
+    cpu: usize,
+    arch: CpuArch,
+    target_xml: String,
+    running: bool,
+}
+
+impl GdbServer {
+    /// Start listening on the given localhost port. If `target` is set, it selects which CPU to
+    /// debug by name (case-insensitive); otherwise the first CPU is debugged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if unable to bind the port, if `target` does not name one of the
+    /// emulator's CPUs, or if GDB does not support the selected CPU's architecture.
+    pub fn bind(port: u16, target: Option<&str>, emulator: &dyn Debuggable) -> io::Result<Self> {
+        let cpus = emulator.debug_cpus();
+        let cpu = match target {
+            Some(target) => cpus
+                .iter()
+                .position(|cpu| cpu.name.eq_ignore_ascii_case(target))
+                .ok_or_else(|| {
+                    let names: Vec<_> = cpus.iter().map(|cpu| cpu.name).collect();
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Unknown debug target '{target}'; expected one of {}",
+                            names.join(", ")
+                        ),
+                    )
+                })?,
+            None => 0,
+        };
+        let arch = cpus[cpu].arch;
+        let Some(target_xml) = target_xml(arch) else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("GDB debugging is not supported for the {}", cpus[cpu].name),
+            ));
+        };
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+
+        log::info!("GDB server debugging {} listening on localhost:{port}", cpus[cpu].name);
+
+        Ok(Self { listener, client: None, cpu, arch, target_xml, running: true })
+    }
+
+    /// Accept a pending connection, handle any packets received from the client, and report a
+    /// stop to the client if the target stopped since the last poll.
+    pub fn poll(&mut self, emulator: &mut dyn Debuggable) {
+        if let Err(err) = self.try_poll(emulator) {
+            log::error!("GDB connection error: {err}");
+            self.disconnect(emulator);
+        }
+    }
+
+    fn try_poll(&mut self, emulator: &mut dyn Debuggable) -> io::Result<()> {
+        if self.client.is_none() {
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    log::info!("GDB client connected from {address}");
+                    stream.set_nonblocking(true)?;
+                    stream.set_nodelay(true)?;
+                    self.client = Some(GdbClient { stream, buffer: vec![], no_ack: false });
+
+                    // GDB expects the target to be halted when it attaches
+                    emulator.debug_hooks_mut().interrupt(self.cpu);
+                    self.running = false;
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+
+        let Some(client) = &mut self.client else { return Ok(()) };
+        if !client.receive()? {
+            log::info!("GDB client disconnected");
+            self.disconnect(emulator);
+            return Ok(());
+        }
+
+        while let Some(message) = self.client.as_mut().and_then(GdbClient::next_message) {
+            let Some(client) = &mut self.client else { break };
+            match message {
+                Message::Interrupt => {
+                    emulator.debug_hooks_mut().interrupt(self.cpu);
+                }
+                Message::BadChecksum => {
+                    client.stream.set_nonblocking(false)?;
+                    client.stream.write_all(b"-")?;
+                    client.stream.set_nonblocking(true)?;
+                }
+                Message::Packet(packet) => {
+                    client.ack()?;
+                    match self.handle_packet(&packet, emulator) {
+                        Response::Reply(reply) => {
+                            let Some(client) = &mut self.client else { break };
+                            client.send(&reply)?;
+                            if packet == "QStartNoAckMode" {
+                                client.no_ack = true;
+                            }
+                        }
+                        Response::Resumed => {
+                            self.running = true;
+                        }
+                        Response::Disconnect => {
+                            if let Some(client) = &mut self.client {
+                                // The client may have already closed the connection after a kill
+                                let _ = client.send("OK");
+                            }
+                            self.disconnect(emulator);
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.running {
+            if let Some(stop) = emulator.debug_hooks().stop_event() {
+                self.running = false;
+                let reply = stop_reply(stop.reason);
+                if let Some(client) = &mut self.client {
+                    client.send(&reply)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn disconnect(&mut self, emulator: &mut dyn Debuggable) {
+        self.client = None;
+        self.running = true;
+
+        let hooks = emulator.debug_hooks_mut();
+        hooks.clear();
+        hooks.resume();
+    }
+
+    fn handle_packet(&mut self, packet: &str, emulator: &mut dyn Debuggable) -> Response {
+        let (command, args) = packet.split_at(packet.len().min(1));
+        match command {
+            "?" => {
+                let stop = emulator.debug_hooks().stop_event();
+                Response::Reply(stop_reply(stop.map_or(StopReason::Interrupt, |stop| stop.reason)))
+            }
+            "g" => self.read_registers(emulator).into(),
+            "G" => {
+                let Some(registers) = self.decode_registers(args) else { return "E01".into() };
+                emulator.write_registers(self.cpu, &registers);
+                "OK".into()
+            }
+            "p" => {
+                let registers = emulator.read_registers(self.cpu);
+                match parse_hex(args).and_then(|n| registers.get(n as usize)) {
+                    Some(&value) => self.encode_register(value).into(),
+                    None => "E01".into(),
+                }
+            }
+            "P" => self.write_register(args, emulator).unwrap_or_else(|| "E01".into()),
+            "m" => self.read_memory(args, emulator).unwrap_or_else(|| "E01".into()),
+            "M" => self.write_memory(args, emulator).unwrap_or_else(|| "E01".into()),
+            "c" => {
+                emulator.debug_hooks_mut().resume();
+                Response::Resumed
+            }
+            "s" => {
+                emulator.debug_hooks_mut().step(self.cpu);
+                Response::Resumed
+            }
+            "Z" | "z" => self
+                .update_breakpoint(command == "Z", args, emulator)
+                .unwrap_or_else(|| "E01".into()),
+            "H" | "T" => "OK".into(),
+            "D" | "k" => Response::Disconnect,
+            _ => match packet {
+                "qAttached" => "1".into(),
+                "qC" => "QC1".into(),
+                "qfThreadInfo" => "m1".into(),
+                "qsThreadInfo" => "l".into(),
+                "QStartNoAckMode" => "OK".into(),
+                _ if packet.starts_with("qSupported") => {
+                    format!("PacketSize={PACKET_SIZE:x};qXfer:features:read+;QStartNoAckMode+")
+                        .into()
+                }
+                _ if packet.starts_with("qXfer:features:read:target.xml:") => {
+                    self.read_target_xml(&packet["qXfer:features:read:target.xml:".len()..])
+                }
+                _ => "".into(),
+            },
+        }
+    }
+
+    fn encode_register(&self, value: u32) -> String {
+        let bytes = value.to_be_bytes();
+        let bytes = match self.arch {
+            CpuArch::M68000 => bytes.to_vec(),
+            _ => vec![bytes[3], bytes[2]],
+        };
+        encode_hex(&bytes)
+    }
+
+    fn decode_register(&self, hex: &str) -> Option<u32> {
+        let bytes = decode_hex(hex)?;
+        match (self.arch, bytes.as_slice()) {
+            (CpuArch::M68000, &[a, b, c, d]) => Some(u32::from_be_bytes([a, b, c, d])),
+            (CpuArch::M68000, _) => None,
+            (_, &[lsb, msb]) => Some(u16::from_le_bytes([lsb, msb]).into()),
+            _ => None,
+        }
+    }
+
+    fn read_registers(&self, emulator: &dyn Debuggable) -> String {
+        emulator
+            .read_registers(self.cpu)
+            .into_iter()
+            .map(|value| self.encode_register(value))
+            .collect()
+    }
+
+    fn decode_registers(&self, hex: &str) -> Option<Vec<u32>> {
+        let digits = 2 * register_bytes(self.arch);
+        if hex.len() != digits * self.arch.registers().len() {
+            return None;
+        }
+
+        (0..hex.len())
+            .step_by(digits)
+            .map(|i| self.decode_register(hex.get(i..i + digits)?))
+            .collect()
+    }
+
+    fn write_register(&self, args: &str, emulator: &mut dyn Debuggable) -> Option<Response> {
+        let (n, value) = args.split_once('=')?;
+        let n = parse_hex(n)? as usize;
+        let value = self.decode_register(value)?;
+
+        let mut registers = emulator.read_registers(self.cpu);
+        *registers.get_mut(n)? = value;
+        emulator.write_registers(self.cpu, &registers);
+
+        Some("OK".into())
+    }
+
+    fn read_memory(&self, args: &str, emulator: &mut dyn Debuggable) -> Option<Response> {
+        let (address, len) = args.split_once(',')?;
+        let address = parse_hex(address)?;
+        // Each byte is sent as 2 hex digits, so longer reads would not fit in a single packet
+        let len = parse_hex(len)?.min(PACKET_SIZE / 2);
+
+        let bytes: Vec<_> = (0..len)
+            .map(|i| {
+                emulator.read_memory(self.cpu, address.wrapping_add(i) & self.arch.address_mask())
+            })
+            .collect();
+        Some(Response::Reply(encode_hex(&bytes)))
+    }
+
+    fn write_memory(&self, args: &str, emulator: &mut dyn Debuggable) -> Option<Response> {
+        let (location, data) = args.split_once(':')?;
+        let (address, _) = location.split_once(',')?;
+        let address = parse_hex(address)?;
+        let bytes = decode_hex(data)?;
+
+        for (i, byte) in (0..).zip(bytes) {
+            emulator.write_memory(
+                self.cpu,
+                address.wrapping_add(i) & self.arch.address_mask(),
+                byte,
+            );
+        }
+        Some("OK".into())
+    }
+
+    fn update_breakpoint(
+        &self,
+        insert: bool,
+        args: &str,
+        emulator: &mut dyn Debuggable,
+    ) -> Option<Response> {
+        let mut fields = args.split(',');
+        let kind = fields.next()?;
+        let address = parse_hex(fields.next()?)? & self.arch.address_mask();
+        let len = parse_hex(fields.next()?)?;
+
+        let watchpoint_kind = match kind {
+            // Software and hardware breakpoints are implemented identically
+            "0" | "1" => None,
+            "2" => Some(WatchpointKind::Write),
+            "3" => Some(WatchpointKind::Read),
+            "4" => Some(WatchpointKind::Access),
+            _ => return Some("".into()),
+        };
+
+        let hooks = emulator.debug_hooks_mut();
+        match (watchpoint_kind, insert) {
+            (None, true) => hooks.add_breakpoint(self.cpu, address),
+            (None, false) => hooks.remove_breakpoint(self.cpu, address),
+            (Some(kind), true) => {
+                hooks.add_watchpoint(self.cpu, Watchpoint { address, len, kind });
+            }
+            (Some(kind), false) => {
+                hooks.remove_watchpoint(self.cpu, Watchpoint { address, len, kind });
+            }
+        }
+
+        Some("OK".into())
+    }
+
+    fn read_target_xml(&self, args: &str) -> Response {
+        let Some((offset, len)) = args.split_once(',') else { return "E01".into() };
+        let (Some(offset), Some(len)) = (parse_hex(offset), parse_hex(len)) else {
+            return "E01".into();
+        };
+
+        let xml = self.target_xml.as_bytes();
+        let start = (offset as usize).min(xml.len());
+        let end = (start + len as usize).min(xml.len());
+        let prefix = if end == xml.len() { 'l' } else { 'm' };
+
+        Response::Reply(format!("{prefix}{}", String::from_utf8_lossy(&xml[start..end])))
+    }
+}
+
+fn stop_reply(reason: StopReason) -> String {
+    match reason {
+        StopReason::Breakpoint | StopReason::Step => format!("S{SIGTRAP:02x}"),
+        StopReason::Watchpoint { kind, address } => {
+            let name = match kind {
+                WatchpointKind::Read => "rwatch",
+                WatchpointKind::Write => "watch",
+                WatchpointKind::Access => "awatch",
+            };
+            format!("T{SIGTRAP:02x}{name}:{address:x};")
+        }
+        StopReason::Interrupt => format!("S{SIGINT:02x}"),
+    }
+}
+
+// GDB expects 32-bit big-endian registers for the 68000 and 16-bit little-endian registers for
+// the Z80
+fn register_bytes(arch: CpuArch) -> usize {
+    if arch == CpuArch::M68000 { 4 } else { 2 }
+}
+
+fn target_xml(arch: CpuArch) -> Option<String> {
+    let (architecture, feature) = match arch {
+        CpuArch::M68000 => ("m68k", "org.gnu.gdb.m68k.core"),
+        CpuArch::Z80 => ("z80", "org.gnu.gdb.z80.cpu"),
+        CpuArch::Mos6502 | CpuArch::Wdc65816 | CpuArch::Spc700 | CpuArch::Sm83 => return None,
+    };
+
+    let mut xml = format!(
+        "<?xml version=\"1.0\"?><!DOCTYPE target SYSTEM \"gdb-target.dtd\"><target version=\"1.0\">\
+         <architecture>{architecture}</architecture><feature name=\"{feature}\">"
+    );
+    for &Register { name, bits } in arch.registers() {
+        let register_type = match name {
+            "pc" => " type=\"code_ptr\"",
+            "sp" | "fp" => " type=\"data_ptr\"",
+            _ => "",
+        };
+        write!(xml, "<reg name=\"{name}\" bitsize=\"{bits}\"{register_type}/>").unwrap();
+    }
+    xml.push_str("</feature></target>");
+
+    Some(xml)
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    u32::from_str_radix(s, 16).ok()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(2 * bytes.len()), |mut s, byte| {
+        write!(s, "{byte:02x}").unwrap();
+        s
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jgenesis_common::debug::{DebugCpu, DebugHooks, StopEvent};
+    use std::time::Duration;
+
+    const FAKE_CPUS: &[DebugCpu] = &[
+        DebugCpu { name: "68000", arch: CpuArch::M68000 },
+        DebugCpu { name: "Z80", arch: CpuArch::Z80 },
+    ];
+
+    struct FakeEmulator {
+        hooks: DebugHooks,
+        registers: [Vec<u32>; 2],
+        memory: Vec<u8>,
+    }
+
+    impl FakeEmulator {
+        fn new() -> Self {
+            Self {
+                hooks: DebugHooks::new(FAKE_CPUS.len()),
+                registers: [vec![0; 18], vec![0; 13]],
+                memory: vec![0; 0x10000],
+            }
+        }
+    }
+
+    impl Debuggable for FakeEmulator {
+        fn debug_cpus(&self) -> &'static [DebugCpu] {
+            FAKE_CPUS
+        }
+
+        fn debug_hooks(&self) -> &DebugHooks {
+            &self.hooks
+        }
+
+        fn debug_hooks_mut(&mut self) -> &mut DebugHooks {
+            &mut self.hooks
+        }
+
+        fn read_registers(&self, cpu: usize) -> Vec<u32> {
+            self.registers[cpu].clone()
+        }
+
+        fn write_registers(&mut self, cpu: usize, registers: &[u32]) {
+            self.registers[cpu] = registers.to_vec();
+        }
+
+        fn read_memory(&mut self, _cpu: usize, address: u32) -> u8 {
+            self.memory[(address & 0xFFFF) as usize]
+        }
+
+        fn write_memory(&mut self, _cpu: usize, address: u32, value: u8) {
+            self.memory[(address & 0xFFFF) as usize] = value;
+        }
+    }
+
+    fn reply(response: Response) -> String {
+        match response {
+            Response::Reply(reply) => reply,
+            Response::Resumed => panic!("expected a reply, got Resumed"),
+            Response::Disconnect => panic!("expected a reply, got Disconnect"),
+        }
+    }
+
+    fn packet(data: &str) -> String {
+        let checksum = data.bytes().fold(0_u8, u8::wrapping_add);
+        format!("${data}#{checksum:02x}")
+    }
+
+    #[test]
+    fn m68k_registers() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, None, &emulator).unwrap();
+
+        emulator.registers[0][0] = 0x12345678;
+        emulator.registers[0][17] = 0x00FF0010;
+
+        let registers = reply(server.handle_packet("g", &mut emulator));
+        assert_eq!(registers.len(), 18 * 8);
+        assert!(registers.starts_with("12345678"));
+        assert!(registers.ends_with("00ff0010"));
+
+        assert_eq!(reply(server.handle_packet("p11", &mut emulator)), "00ff0010");
+        assert_eq!(reply(server.handle_packet("p12", &mut emulator)), "E01");
+
+        assert_eq!(reply(server.handle_packet("P8=00c00000", &mut emulator)), "OK");
+        assert_eq!(emulator.registers[0][8], 0x00C00000);
+        assert_eq!(reply(server.handle_packet("P8=0000", &mut emulator)), "E01");
+
+        let written: Vec<_> = (1..=18_u32).flat_map(u32::to_be_bytes).collect();
+        let written = encode_hex(&written);
+        assert_eq!(reply(server.handle_packet(&format!("G{written}"), &mut emulator)), "OK");
+        assert_eq!(emulator.registers[0], (1..=18).collect::<Vec<_>>());
+        assert_eq!(reply(server.handle_packet("G0000", &mut emulator)), "E01");
+    }
+
+    #[test]
+    fn z80_registers() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, Some("z80"), &emulator).unwrap();
+
+        // Z80 registers are 16-bit little-endian
+        emulator.registers[1][5] = 0x1234;
+        assert_eq!(reply(server.handle_packet("p5", &mut emulator)), "3412");
+
+        assert_eq!(reply(server.handle_packet("P4=f0df", &mut emulator)), "OK");
+        assert_eq!(emulator.registers[1][4], 0xDFF0);
+        assert_eq!(emulator.registers[0], vec![0; 18]);
+
+        let registers = reply(server.handle_packet("g", &mut emulator));
+        assert_eq!(registers.len(), 13 * 4);
+    }
+
+    #[test]
+    fn memory() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, Some("z80"), &emulator).unwrap();
+
+        assert_eq!(reply(server.handle_packet("M1ffe,3:aabbcc", &mut emulator)), "OK");
+        assert_eq!(&emulator.memory[0x1FFE..0x2001], &[0xAA, 0xBB, 0xCC]);
+        assert_eq!(reply(server.handle_packet("m1ffd,5", &mut emulator)), "00aabbcc00");
+
+        // Addresses wrap around the CPU's address space
+        assert_eq!(reply(server.handle_packet("Mffff,2:1122", &mut emulator)), "OK");
+        assert_eq!((emulator.memory[0xFFFF], emulator.memory[0]), (0x11, 0x22));
+
+        assert_eq!(reply(server.handle_packet("m0", &mut emulator)), "E01");
+        assert_eq!(reply(server.handle_packet("M0,1:a", &mut emulator)), "E01");
+
+        // Reads are limited so that the reply fits within the advertised packet size
+        let data = reply(server.handle_packet("m0,10000", &mut emulator));
+        assert_eq!(data.len(), PACKET_SIZE as usize);
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, None, &emulator).unwrap();
+
+        assert_eq!(reply(server.handle_packet("Z0,1000200,2", &mut emulator)), "OK");
+        assert_eq!(emulator.hooks.breakpoints(0), &[0x200]);
+        assert_eq!(reply(server.handle_packet("z0,200,2", &mut emulator)), "OK");
+        assert!(emulator.hooks.breakpoints(0).is_empty());
+
+        for (kind, watchpoint_kind) in
+            [(2, WatchpointKind::Write), (3, WatchpointKind::Read), (4, WatchpointKind::Access)]
+        {
+            let watchpoint = Watchpoint { address: 0xFF0000, len: 2, kind: watchpoint_kind };
+
+            let insert = format!("Z{kind},ff0000,2");
+            assert_eq!(reply(server.handle_packet(&insert, &mut emulator)), "OK");
+            assert_eq!(emulator.hooks.watchpoints(0), &[watchpoint]);
+
+            let remove = format!("z{kind},ff0000,2");
+            assert_eq!(reply(server.handle_packet(&remove, &mut emulator)), "OK");
+            assert!(emulator.hooks.watchpoints(0).is_empty());
+        }
+
+        // Unsupported breakpoint types and malformed packets
+        assert_eq!(reply(server.handle_packet("Z5,0,2", &mut emulator)), "");
+        assert_eq!(reply(server.handle_packet("Z0,xyz,2", &mut emulator)), "E01");
+    }
+
+    #[test]
+    fn continue_and_step() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, None, &emulator).unwrap();
+
+        emulator.hooks.interrupt(0);
+        assert_eq!(reply(server.handle_packet("?", &mut emulator)), "S02");
+
+        assert!(matches!(server.handle_packet("c", &mut emulator), Response::Resumed));
+        assert_eq!(emulator.hooks.stop_event(), None);
+
+        emulator.hooks.interrupt(0);
+        assert!(matches!(server.handle_packet("s", &mut emulator), Response::Resumed));
+        assert!(!emulator.hooks.check_execute(0, 0x100));
+        assert!(emulator.hooks.check_execute(0, 0x102));
+        assert_eq!(
+            emulator.hooks.stop_event(),
+            Some(StopEvent { cpu: 0, reason: StopReason::Step })
+        );
+        assert_eq!(reply(server.handle_packet("?", &mut emulator)), "S05");
+    }
+
+    #[test]
+    fn target_xml_paging() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, None, &emulator).unwrap();
+        let xml = target_xml(CpuArch::M68000).unwrap();
+
+        let supported = reply(server.handle_packet("qSupported:xmlRegisters=i386", &mut emulator));
+        assert!(supported.contains(&format!("PacketSize={PACKET_SIZE:x}")));
+        assert!(supported.contains("qXfer:features:read+"));
+
+        let mut read_xml = |offset: usize, len: usize| {
+            let packet = format!("qXfer:features:read:target.xml:{offset:x},{len:x}");
+            reply(server.handle_packet(&packet, &mut emulator))
+        };
+
+        assert_eq!(read_xml(0, 0x10), format!("m{}", &xml[..0x10]));
+        assert_eq!(read_xml(0x10, 0x10), format!("m{}", &xml[0x10..0x20]));
+        assert_eq!(read_xml(xml.len() - 5, 0x10), format!("l{}", &xml[xml.len() - 5..]));
+        assert_eq!(read_xml(xml.len() + 1, 0x10), "l");
+        assert_eq!(read_xml(0, 0xFFF), format!("l{xml}"));
+    }
+
+    #[test]
+    fn checksums_and_no_ack_mode() {
+        let mut emulator = FakeEmulator::new();
+        let mut server = GdbServer::bind(0, None, &emulator).unwrap();
+        let address = server.listener.local_addr().unwrap();
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+
+        // Send raw bytes to the server and return everything it sends back, up to and including
+        // the first complete packet
+        let mut exchange = |request: &[u8]| {
+            stream.write_all(request).unwrap();
+
+            let mut response = vec![];
+            for _ in 0..200 {
+                server.poll(&mut emulator);
+
+                let mut chunk = [0; 4096];
+                match stream.read(&mut chunk) {
+                    Ok(len) => response.extend_from_slice(&chunk[..len]),
+                    Err(err)
+                        if matches!(
+                            err.kind(),
+                            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                        ) => {}
+                    Err(err) => panic!("{err}"),
+                }
+
+                let complete = response
+                    .iter()
+                    .position(|&b| b == b'#')
+                    .is_some_and(|end| response.len() >= end + 3);
+                if complete || response == b"-" {
+                    break;
+                }
+            }
+
+            String::from_utf8(response).unwrap()
+        };
+
+        // Bad checksums are rejected until no-ack mode is enabled
+        assert_eq!(exchange(b"$qC#00"), "-");
+        assert_eq!(exchange(packet("qC").as_bytes()), format!("+{}", packet("QC1")));
+
+        assert_eq!(exchange(packet("QStartNoAckMode").as_bytes()), format!("+{}", packet("OK")));
+        assert_eq!(exchange(packet("qC").as_bytes()), packet("QC1"));
+        assert_eq!(exchange(b"$qC#00"), packet("QC1"));
+    }
+
+    #[test]
+    fn stop_replies() {
+        assert_eq!(stop_reply(StopReason::Breakpoint), "S05");
+        assert_eq!(stop_reply(StopReason::Interrupt), "S02");
+        assert_eq!(
+            stop_reply(StopReason::Watchpoint { kind: WatchpointKind::Write, address: 0xFF0010 }),
+            "T05watch:ff0010;"
+        );
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(encode_hex(&[0x00, 0xAB, 0x12]), "00ab12");
+        assert_eq!(decode_hex("00ab12"), Some(vec![0x00, 0xAB, 0x12]));
+        assert_eq!(decode_hex("0ab"), None);
+    }
+}
@@ -1,6 +1,9 @@
-use crate::config::{GenesisConfig, SegaCdConfig};
+use crate::config::{CommonConfig, GenesisConfig, SegaCdConfig};
+use crate::input::InputMapper;
 use crate::mainloop::save::FsSaveWriter;
-use crate::mainloop::{basic_input_mapper_fn, debug, NativeEmulatorError};
+use crate::mainloop::{
+    debug, read_rom_file, savestate, NativeEmulatorError, RomFile, SaveStateConsole,
+};
 use crate::{config, AudioError, NativeEmulator, NativeEmulatorResult};
 use genesis_core::input::GenesisButton;
 use genesis_core::{GenesisEmulator, GenesisEmulatorConfig, GenesisInputs};
@@ -26,11 +29,12 @@ impl NativeGenesisEmulator {
         self.emulator.reload_config(&emulator_config);
         self.config = emulator_config;
 
-        if let Err(err) = self.input_mapper.reload_config(
-            config.common.keyboard_inputs,
-            config.common.joystick_inputs,
+        if let Err(err) = self.input_mapper.reload_config_genesis(
+            &config.common.keyboard_inputs,
+            &config.common.joystick_inputs,
+            &config.mouse_config,
+            &config.light_gun_config,
             config.common.axis_deadzone,
-            &GenesisButton::ALL,
         ) {
             log::error!("Error reloading input config: {err}");
         }
@@ -52,11 +56,12 @@ impl NativeSegaCdEmulator {
         self.reload_common_config(&config.genesis.common)?;
         self.emulator.reload_config(&config.to_emulator_config());
 
-        if let Err(err) = self.input_mapper.reload_config(
-            config.genesis.common.keyboard_inputs,
-            config.genesis.common.joystick_inputs,
+        if let Err(err) = self.input_mapper.reload_config_genesis(
+            &config.genesis.common.keyboard_inputs,
+            &config.genesis.common.joystick_inputs,
+            &config.genesis.mouse_config,
+            &config.genesis.light_gun_config,
             config.genesis.common.axis_deadzone,
-            &GenesisButton::ALL,
         ) {
             log::error!("Error reloading input config: {err}");
         }
@@ -106,6 +111,8 @@ impl NativeSegaCdEmulator {
     }
 }
 
+const SUPPORTED_EXTENSIONS: &[&str] = &["md", "bin"];
+
 /// Create an emulator with the Genesis core with the given config.
 ///
 /// # Errors
@@ -114,17 +121,14 @@ impl NativeSegaCdEmulator {
 pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<NativeGenesisEmulator> {
     log::info!("Running with config: {config}");
 
-    let rom_file_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_file_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: rom_file_path.display().to_string(),
-        source,
-    })?;
+    let RomFile { contents: rom, base_path, .. } =
+        read_rom_file(&config.common, SUPPORTED_EXTENSIONS)?;
 
-    let save_path = rom_file_path.with_extension("sav");
-    let save_state_path = rom_file_path.with_extension("ss0");
+    let save_path = base_path.with_extension("sav");
     let mut save_writer = FsSaveWriter::new(save_path);
 
     let emulator_config = config.to_emulator_config();
+    let rom_checksum = savestate::rom_checksum(&rom);
     let emulator = GenesisEmulator::create(rom, emulator_config, &mut save_writer);
 
     let mut cartridge_title = emulator.cartridge_title();
@@ -134,6 +138,17 @@ pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<Native
     });
     let window_title = format!("genesis - {cartridge_title}");
 
+    let input_mapper_fn = |joystick_subsystem, common_config: &CommonConfig<_, _>| {
+        InputMapper::new_genesis(
+            joystick_subsystem,
+            &common_config.keyboard_inputs,
+            &common_config.joystick_inputs,
+            &config.mouse_config,
+            &config.light_gun_config,
+            common_config.axis_deadzone,
+        )
+    };
+
     NativeGenesisEmulator::new(
         emulator,
         emulator_config,
@@ -141,8 +156,10 @@ pub fn create_genesis(config: Box<GenesisConfig>) -> NativeEmulatorResult<Native
         config::DEFAULT_GENESIS_WINDOW_SIZE,
         &window_title,
         save_writer,
-        save_state_path,
-        basic_input_mapper_fn(&GenesisButton::ALL),
+        base_path,
+        SaveStateConsole::Genesis,
+        rom_checksum,
+        input_mapper_fn,
         debug::genesis::render_fn,
     )
 }
@@ -165,8 +182,8 @@ pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeS
         CdRomFileFormat::CueBin
     });
 
+    let rom_base_path = rom_path.to_path_buf();
     let save_path = rom_path.with_extension("sav");
-    let save_state_path = rom_path.with_extension("ss0");
     let mut save_writer = FsSaveWriter::new(save_path);
 
     let bios_file_path = config.bios_file_path.as_ref().ok_or(NativeEmulatorError::SegaCdNoBios)?;
@@ -176,7 +193,7 @@ pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeS
     })?;
 
     let emulator_config = config.to_emulator_config();
-    let emulator = SegaCdEmulator::create(
+    let mut emulator = SegaCdEmulator::create(
         bios,
         rom_path,
         rom_format,
@@ -187,6 +204,22 @@ pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeS
 
     let window_title = format!("sega cd - {}", emulator.disc_title());
 
+    // Hashing an entire disc image would be far too slow, so identify discs by their first data
+    // sector (which contains the disc header) and their TOC
+    let disc_identifier = emulator.disc_identifier()?.unwrap_or_default();
+    let rom_checksum = savestate::rom_checksum(&disc_identifier);
+
+    let input_mapper_fn = |joystick_subsystem, common_config: &CommonConfig<_, _>| {
+        InputMapper::new_genesis(
+            joystick_subsystem,
+            &common_config.keyboard_inputs,
+            &common_config.joystick_inputs,
+            &config.genesis.mouse_config,
+            &config.genesis.light_gun_config,
+            common_config.axis_deadzone,
+        )
+    };
+
     NativeSegaCdEmulator::new(
         emulator,
         emulator_config,
@@ -194,8 +227,10 @@ pub fn create_sega_cd(config: Box<SegaCdConfig>) -> NativeEmulatorResult<NativeS
         config::DEFAULT_GENESIS_WINDOW_SIZE,
         &window_title,
         save_writer,
-        save_state_path,
-        basic_input_mapper_fn(&GenesisButton::ALL),
+        rom_base_path,
+        SaveStateConsole::SegaCd,
+        rom_checksum,
+        input_mapper_fn,
         debug::genesis::render_fn,
     )
 }
@@ -0,0 +1,517 @@
+use crate::mainloop::{bincode_config, HotkeyState, SaveStateConsole};
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+use flate2::bufread::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use jgenesis_common::frontend::{EmulatorTrait, SaveWriter};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const MOVIE_MAGIC: [u8; 8] = *b"JGENMOVI";
+const MOVIE_FORMAT_VERSION: u16 = 1;
+
+#[derive(Debug, Error)]
+pub enum MovieError {
+    #[error("I/O error opening movie file '{path}': {source}")]
+    Open {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("I/O error writing movie file '{path}': {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("'{path}' is not a jgenesis movie file")]
+    InvalidMagic { path: String },
+    #[error(
+        "Movie '{path}' uses format version {actual}; this version of jgenesis only supports version {expected}"
+    )]
+    FormatVersion { path: String, expected: u16, actual: u16 },
+    #[error("Movie was recorded on {actual}, but the running console is {expected}")]
+    ConsoleMismatch { expected: SaveStateConsole, actual: SaveStateConsole },
+    #[error(
+        "Movie was recorded with a different ROM (ROM checksum {actual:08X}, expected {expected:08X})"
+    )]
+    RomMismatch { expected: u32, actual: u32 },
+    #[error("Error encoding movie: {0}")]
+    Encode(#[from] EncodeError),
+    #[error("Error decoding movie: {0}")]
+    Decode(#[from] DecodeError),
+}
+
+/// Where a movie begins recording from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovieStart {
+    /// Hard reset the console when recording or playback starts. Cartridge save RAM is still loaded
+    /// from disk, so power-on movies only replay exactly if the save file has not changed.
+    PowerOn,
+    /// Embed the current emulator state in the movie and load it when playback starts.
+    CurrentState,
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+enum EncodedMovieStart {
+    PowerOn,
+    SaveState(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Encode, Decode)]
+struct MovieHeader {
+    console: SaveStateConsole,
+    rom_checksum: u32,
+    rom_name: String,
+    emulator_version: String,
+    start: EncodedMovieStart,
+}
+
+impl MovieHeader {
+    fn check_compatible(
+        &self,
+        console: SaveStateConsole,
+        rom_checksum: u32,
+    ) -> Result<(), MovieError> {
+        if self.console != console {
+            return Err(MovieError::ConsoleMismatch { expected: console, actual: self.console });
+        }
+
+        if self.rom_checksum != rom_checksum {
+            return Err(MovieError::RomMismatch {
+                expected: rom_checksum,
+                actual: self.rom_checksum,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MovieProgress {
+    Continue,
+    Finished,
+}
+
+enum MovieMode<Inputs> {
+    // Frames are encoded as they are recorded so that the movie can be written out on drop without
+    // needing any trait bounds on the inputs type
+    Recording { encoded_frames: Vec<u8>, frame_count: u64 },
+    Playback { frames: Vec<Inputs>, position: usize, read_only: bool },
+}
+
+/// An in-progress movie recording or playback.
+///
+/// Movies record the `Inputs` value that was used for each rendered frame. Recordings are written
+/// to disk when the session is dropped, which happens when recording is stopped or when the
+/// emulator is closed.
+pub(crate) struct MovieSession<Inputs> {
+    path: PathBuf,
+    header: MovieHeader,
+    mode: MovieMode<Inputs>,
+}
+
+impl<Inputs: Clone + Encode + Decode> MovieSession<Inputs> {
+    pub(crate) fn start_recording<Emulator, S>(
+        path: PathBuf,
+        start: MovieStart,
+        emulator: &mut Emulator,
+        save_writer: &mut S,
+        hotkey_state: &HotkeyState<Emulator>,
+    ) -> Result<Self, MovieError>
+    where
+        Emulator: EmulatorTrait<Inputs = Inputs>,
+        S: SaveWriter,
+    {
+        let start = match start {
+            MovieStart::PowerOn => {
+                emulator.hard_reset(save_writer);
+                EncodedMovieStart::PowerOn
+            }
+            MovieStart::CurrentState => {
+                EncodedMovieStart::SaveState(bincode::encode_to_vec(&*emulator, bincode_config!())?)
+            }
+        };
+
+        let header = MovieHeader {
+            console: hotkey_state.console,
+            rom_checksum: hotkey_state.rom_checksum,
+            rom_name: hotkey_state.rom_name.clone(),
+            emulator_version: env!("CARGO_PKG_VERSION").into(),
+            start,
+        };
+
+        log::info!("Started recording movie to {}", path.display());
+
+        Ok(Self {
+            path,
+            header,
+            mode: MovieMode::Recording { encoded_frames: Vec::new(), frame_count: 0 },
+        })
+    }
+
+    pub(crate) fn start_playback<Emulator, S>(
+        path: PathBuf,
+        read_only: bool,
+        emulator: &mut Emulator,
+        config: &Emulator::Config,
+        save_writer: &mut S,
+        hotkey_state: &HotkeyState<Emulator>,
+    ) -> Result<Self, MovieError>
+    where
+        Emulator: EmulatorTrait<Inputs = Inputs>,
+        S: SaveWriter,
+    {
+        let (header, frames) = read_movie::<Inputs>(&path)?;
+        header.check_compatible(hotkey_state.console, hotkey_state.rom_checksum)?;
+
+        match &header.start {
+            EncodedMovieStart::PowerOn => {
+                emulator.hard_reset(save_writer);
+            }
+            EncodedMovieStart::SaveState(state) => {
+                let (mut loaded_emulator, _): (Emulator, _) =
+                    bincode::decode_from_slice(state, bincode_config!())?;
+                loaded_emulator.take_rom_from(emulator);
+                loaded_emulator.reload_config(config);
+                *emulator = loaded_emulator;
+            }
+        }
+
+        log::info!(
+            "Started playing back movie from {} ({} frames, {})",
+            path.display(),
+            frames.len(),
+            if read_only { "read-only" } else { "read-write" }
+        );
+
+        Ok(Self { path, header, mode: MovieMode::Playback { frames, position: 0, read_only } })
+    }
+
+    /// Return the inputs to use for the next frame: the movie's inputs during playback, otherwise
+    /// the live inputs.
+    pub(crate) fn inputs<'a>(&'a self, live_inputs: &'a Inputs) -> &'a Inputs {
+        match &self.mode {
+            MovieMode::Playback { frames, position, .. } => {
+                frames.get(*position).unwrap_or(live_inputs)
+            }
+            MovieMode::Recording { .. } => live_inputs,
+        }
+    }
+
+    /// Advance to the next frame. Must be called after every rendered frame, with the same live
+    /// inputs that were passed to [`Self::inputs`] for that frame.
+    pub(crate) fn advance_frame(&mut self, live_inputs: &Inputs) -> MovieProgress {
+        match &mut self.mode {
+            MovieMode::Recording { encoded_frames, frame_count } => {
+                if let Err(err) =
+                    bincode::encode_into_std_write(live_inputs, encoded_frames, bincode_config!())
+                {
+                    log::error!("Error recording movie frame, stopping recording: {err}");
+                    return MovieProgress::Finished;
+                }
+                *frame_count += 1;
+
+                MovieProgress::Continue
+            }
+            MovieMode::Playback { frames, position, read_only } => {
+                *position += 1;
+                if *position < frames.len() {
+                    return MovieProgress::Continue;
+                }
+
+                if *read_only {
+                    log::info!("Movie playback finished");
+                    return MovieProgress::Finished;
+                }
+
+                log::info!("Movie playback finished; continuing to record in read-write mode");
+                self.switch_to_recording()
+            }
+        }
+    }
+
+    /// Switch from read-write playback to recording, discarding any movie frames after the current
+    /// position. Has no effect when already recording.
+    pub(crate) fn take_over(&mut self) -> MovieProgress {
+        match &self.mode {
+            MovieMode::Recording { .. } => MovieProgress::Continue,
+            MovieMode::Playback { read_only: true, .. } => {
+                log::warn!(
+                    "Movie is in read-only mode; toggle read-write mode to resume recording"
+                );
+                MovieProgress::Continue
+            }
+            MovieMode::Playback { read_only: false, position, .. } => {
+                log::info!("Resuming movie recording from frame {position}");
+                self.switch_to_recording()
+            }
+        }
+    }
+
+    fn switch_to_recording(&mut self) -> MovieProgress {
+        let MovieMode::Playback { frames, position, .. } = &self.mode else {
+            return MovieProgress::Continue;
+        };
+
+        let frame_count = *position as u64;
+        let mut encoded_frames = Vec::new();
+        for frame_inputs in &frames[..*position] {
+            if let Err(err) =
+                bincode::encode_into_std_write(frame_inputs, &mut encoded_frames, bincode_config!())
+            {
+                log::error!("Error re-encoding movie frames: {err}");
+                return MovieProgress::Finished;
+            }
+        }
+
+        self.mode = MovieMode::Recording { encoded_frames, frame_count };
+        MovieProgress::Continue
+    }
+
+    pub(crate) fn toggle_read_only(&mut self) {
+        match &mut self.mode {
+            MovieMode::Playback { read_only, .. } => {
+                *read_only = !*read_only;
+                log::info!("Movie is now {}", if *read_only { "read-only" } else { "read-write" });
+            }
+            MovieMode::Recording { .. } => {
+                log::info!("Read-only mode only applies to movie playback");
+            }
+        }
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        matches!(self.mode, MovieMode::Recording { .. })
+    }
+}
+
+impl<Inputs> Drop for MovieSession<Inputs> {
+    fn drop(&mut self) {
+        let MovieMode::Recording { encoded_frames, frame_count } = &self.mode else { return };
+
+        match write_movie(&self.path, &self.header, *frame_count, encoded_frames) {
+            Ok(()) => {
+                log::info!("Saved {frame_count}-frame movie to {}", self.path.display());
+            }
+            Err(err) => {
+                log::error!("Error saving movie: {err}");
+            }
+        }
+    }
+}
+
+fn write_movie(
+    path: &Path,
+    header: &MovieHeader,
+    frame_count: u64,
+    encoded_frames: &[u8],
+) -> Result<(), MovieError> {
+    let write_err = |source| MovieError::Write { path: path.display().to_string(), source };
+
+    let file = File::create(path)
+        .map_err(|source| MovieError::Open { path: path.display().to_string(), source })?;
+    let mut file = BufWriter::new(file);
+
+    let conf = bincode_config!();
+    bincode::encode_into_std_write(MOVIE_MAGIC, &mut file, conf)?;
+    bincode::encode_into_std_write(MOVIE_FORMAT_VERSION, &mut file, conf)?;
+
+    let mut encoder = DeflateEncoder::new(file, Compression::default());
+    bincode::encode_into_std_write(header, &mut encoder, conf)?;
+    bincode::encode_into_std_write(frame_count, &mut encoder, conf)?;
+    encoder.write_all(encoded_frames).map_err(write_err)?;
+    encoder.finish().and_then(|mut file| file.flush()).map_err(write_err)?;
+
+    Ok(())
+}
+
+fn read_movie<Inputs: Decode>(path: &Path) -> Result<(MovieHeader, Vec<Inputs>), MovieError> {
+    let file = File::open(path)
+        .map_err(|source| MovieError::Open { path: path.display().to_string(), source })?;
+    let mut file = BufReader::new(file);
+
+    let conf = bincode_config!();
+
+    let magic: [u8; 8] = bincode::decode_from_std_read(&mut file, conf)
+        .map_err(|_| MovieError::InvalidMagic { path: path.display().to_string() })?;
+    if magic != MOVIE_MAGIC {
+        return Err(MovieError::InvalidMagic { path: path.display().to_string() });
+    }
+
+    let format_version: u16 = bincode::decode_from_std_read(&mut file, conf)?;
+    if format_version != MOVIE_FORMAT_VERSION {
+        return Err(MovieError::FormatVersion {
+            path: path.display().to_string(),
+            expected: MOVIE_FORMAT_VERSION,
+            actual: format_version,
+        });
+    }
+
+    let mut payload = DeflateDecoder::new(file);
+    let header: MovieHeader = bincode::decode_from_std_read(&mut payload, conf)?;
+    let frame_count: u64 = bincode::decode_from_std_read(&mut payload, conf)?;
+
+    let frames = (0..frame_count)
+        .map(|_| bincode::decode_from_std_read(&mut payload, conf))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((header, frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mainloop::testutil::TempFile;
+
+    fn test_header(start: EncodedMovieStart) -> MovieHeader {
+        MovieHeader {
+            console: SaveStateConsole::Snes,
+            rom_checksum: 0x12345678,
+            rom_name: "Test ROM".into(),
+            emulator_version: env!("CARGO_PKG_VERSION").into(),
+            start,
+        }
+    }
+
+    fn encode_frames<Inputs: Encode>(frames: &[Inputs]) -> Vec<u8> {
+        let mut encoded_frames = Vec::new();
+        for frame in frames {
+            bincode::encode_into_std_write(frame, &mut encoded_frames, bincode_config!()).unwrap();
+        }
+        encoded_frames
+    }
+
+    fn recording_session(path: PathBuf) -> MovieSession<u16> {
+        MovieSession {
+            path,
+            header: test_header(EncodedMovieStart::PowerOn),
+            mode: MovieMode::Recording { encoded_frames: Vec::new(), frame_count: 0 },
+        }
+    }
+
+    fn playback_session(path: PathBuf, read_only: bool) -> MovieSession<u16> {
+        let (header, frames) = read_movie(&path).unwrap();
+        MovieSession { path, header, mode: MovieMode::Playback { frames, position: 0, read_only } }
+    }
+
+    #[test]
+    fn movie_file_round_trip() {
+        let file = TempFile::new("round_trip.jgm");
+        let header = test_header(EncodedMovieStart::SaveState(vec![1, 2, 3]));
+        let frames: Vec<_> = (0..500).map(|i| (i * 3, i % 7 == 0)).collect();
+
+        write_movie(&file.0, &header, frames.len() as u64, &encode_frames(&frames)).unwrap();
+
+        let (read_header, read_frames) = read_movie::<(u16, bool)>(&file.0).unwrap();
+        assert_eq!(read_frames, frames);
+        assert_eq!(read_header.console, header.console);
+        assert_eq!(read_header.rom_checksum, header.rom_checksum);
+        assert_eq!(read_header.rom_name, header.rom_name);
+        assert_eq!(read_header.emulator_version, header.emulator_version);
+        assert!(
+            matches!(read_header.start, EncodedMovieStart::SaveState(state) if state == [1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn compatibility_check() {
+        let header = test_header(EncodedMovieStart::PowerOn);
+
+        assert!(header.check_compatible(SaveStateConsole::Snes, 0x12345678).is_ok());
+        assert!(matches!(
+            header.check_compatible(SaveStateConsole::Nes, 0x12345678),
+            Err(MovieError::ConsoleMismatch {
+                expected: SaveStateConsole::Nes,
+                actual: SaveStateConsole::Snes
+            })
+        ));
+        assert!(matches!(
+            header.check_compatible(SaveStateConsole::Snes, 0x87654321),
+            Err(MovieError::RomMismatch { expected: 0x87654321, actual: 0x12345678 })
+        ));
+    }
+
+    #[test]
+    fn invalid_movie_file() {
+        let file = TempFile::new("invalid.jgm");
+
+        std::fs::write(&file.0, b"JGENSTAT\x01\x00").unwrap();
+        assert!(matches!(read_movie::<u8>(&file.0), Err(MovieError::InvalidMagic { .. })));
+
+        std::fs::write(&file.0, b"JGENMOVI\xFF\xFF").unwrap();
+        assert!(matches!(
+            read_movie::<u8>(&file.0),
+            Err(MovieError::FormatVersion { expected: MOVIE_FORMAT_VERSION, actual: 0xFFFF, .. })
+        ));
+
+        // Frame count claims more frames than the file contains
+        let header = test_header(EncodedMovieStart::PowerOn);
+        write_movie(&file.0, &header, 10, &encode_frames(&[(1, true)])).unwrap();
+        assert!(matches!(read_movie::<(u16, bool)>(&file.0), Err(MovieError::Decode(_))));
+    }
+
+    #[test]
+    fn record_then_play_back() {
+        let file = TempFile::new("record_playback.jgm");
+        let recorded: Vec<u16> = (0..300).map(|i| i * 5).collect();
+
+        let mut session = recording_session(file.0.clone());
+        for inputs in &recorded {
+            assert_eq!(session.inputs(inputs), inputs);
+            assert_eq!(session.advance_frame(inputs), MovieProgress::Continue);
+        }
+        // Recordings are written to disk when the session is dropped
+        drop(session);
+
+        // Playback should ignore the live inputs and replay the recorded inputs in order
+        let live_inputs = 0xFFFF;
+        let mut session = playback_session(file.0.clone(), true);
+        for (i, inputs) in recorded.iter().enumerate() {
+            assert_eq!(session.inputs(&live_inputs), inputs);
+
+            let expected = if i == recorded.len() - 1 {
+                MovieProgress::Finished
+            } else {
+                MovieProgress::Continue
+            };
+            assert_eq!(session.advance_frame(&live_inputs), expected);
+        }
+    }
+
+    #[test]
+    fn playback_stops_at_end() {
+        let file = TempFile::new("playback_end.jgm");
+        let header = test_header(EncodedMovieStart::PowerOn);
+        write_movie(&file.0, &header, 3, &encode_frames(&[1_u16, 2, 3])).unwrap();
+
+        // Read-only playback finishes on the last frame and then falls back to the live inputs
+        let mut session = playback_session(file.0.clone(), true);
+        assert_eq!(session.advance_frame(&0), MovieProgress::Continue);
+        assert_eq!(session.advance_frame(&0), MovieProgress::Continue);
+        assert_eq!(session.advance_frame(&0), MovieProgress::Finished);
+        assert_eq!(session.inputs(&10), &10);
+        assert!(!session.is_recording());
+        drop(session);
+
+        // Read-write playback switches to recording at the end, keeping the played back frames
+        let mut session = playback_session(file.0.clone(), false);
+        for _ in 0..3 {
+            assert_eq!(session.advance_frame(&0), MovieProgress::Continue);
+        }
+        assert!(session.is_recording());
+        for inputs in [10, 11] {
+            assert_eq!(session.inputs(&inputs), &inputs);
+            assert_eq!(session.advance_frame(&inputs), MovieProgress::Continue);
+        }
+        drop(session);
+
+        let (_, frames) = read_movie::<u16>(&file.0).unwrap();
+        assert_eq!(frames, vec![1, 2, 3, 10, 11]);
+    }
+}
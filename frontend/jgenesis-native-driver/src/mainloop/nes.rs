@@ -1,7 +1,10 @@
 use crate::config::{CommonConfig, NesConfig};
 
 use crate::mainloop::save::FsSaveWriter;
-use crate::mainloop::{debug, file_name_no_ext, NativeEmulatorError};
+use crate::mainloop::{
+    debug, file_name_no_ext, read_rom_file, savestate, NativeEmulatorError, RomFile,
+    SaveStateConsole,
+};
 use crate::{config, AudioError, NativeEmulator, NativeEmulatorResult};
 use jgenesis_common::frontend::EmulatorTrait;
 
@@ -10,7 +13,6 @@ use nes_core::input::{NesButton, NesInputs};
 
 use crate::input::InputMapper;
 use std::fs;
-use std::path::Path;
 
 pub type NativeNesEmulator = NativeEmulator<NesInputs, NesButton, NesEmulatorConfig, NesEmulator>;
 
@@ -41,6 +43,8 @@ impl NativeNesEmulator {
     }
 }
 
+const SUPPORTED_EXTENSIONS: &[&str] = &["nes", "nsf", "nsfe", "fds"];
+
 /// Create an emulator with the NES core with the given config.
 ///
 /// # Errors
@@ -49,20 +53,27 @@ impl NativeNesEmulator {
 pub fn create_nes(config: Box<NesConfig>) -> NativeEmulatorResult<NativeNesEmulator> {
     log::info!("Running with config: {config}");
 
-    let rom_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: config.common.rom_file_path.clone(),
-        source,
-    })?;
+    let RomFile { contents: rom, extension, base_path } =
+        read_rom_file(&config.common, SUPPORTED_EXTENSIONS)?;
 
-    let save_path = rom_path.with_extension("sav");
-    let save_state_path = rom_path.with_extension("ss0");
+    let save_path = base_path.with_extension("sav");
     let mut save_writer = FsSaveWriter::new(save_path);
 
     let emulator_config = config.to_emulator_config();
-    let emulator = NesEmulator::create(rom, emulator_config, &mut save_writer)?;
+    let rom_checksum = savestate::rom_checksum(&rom);
+    let emulator = if extension == "fds" {
+        let bios_file_path = config.fds_bios_path.as_ref().ok_or(NativeEmulatorError::FdsNoBios)?;
+        let bios = fs::read(bios_file_path).map_err(|source| NativeEmulatorError::FdsBiosRead {
+            path: bios_file_path.clone(),
+            source,
+        })?;
+
+        NesEmulator::create_fds(bios, rom, emulator_config, &mut save_writer)?
+    } else {
+        NesEmulator::create(rom, emulator_config, &mut save_writer)?
+    };
 
-    let rom_title = file_name_no_ext(&config.common.rom_file_path)?;
+    let rom_title = file_name_no_ext(&base_path)?;
     let window_title = format!("nes - {rom_title}");
 
     let input_mapper_fn = |joystick_subsystem, common_config: &CommonConfig<_, _>| {
@@ -83,7 +94,9 @@ pub fn create_nes(config: Box<NesConfig>) -> NativeEmulatorResult<NativeNesEmula
         config::DEFAULT_GENESIS_WINDOW_SIZE,
         &window_title,
         save_writer,
-        save_state_path,
+        base_path,
+        SaveStateConsole::Nes,
+        rom_checksum,
         input_mapper_fn,
         debug::nes::render_fn,
     )
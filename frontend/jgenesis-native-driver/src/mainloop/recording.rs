@@ -0,0 +1,424 @@
+//! Lossless gameplay recording.
+//!
+//! A recording is a directory containing:
+//! * `frame_00000000.png`, `frame_00000001.png`, ...: Every frame emitted by the emulation core at
+//!   its native resolution. Frames can change size mid-recording (e.g. Genesis H32/H40 or interlaced
+//!   modes), so each image has its own dimensions.
+//! * `audio.wav`: Every audio sample emitted by the core, as 48 KHz stereo 32-bit float
+//! * `video.ffconcat`: An ffmpeg concat script listing the frames in order along with how long each
+//!   frame was displayed. Frame durations are measured in audio samples, so the video stays in sync
+//!   with the audio regardless of console or timing mode.
+//!
+//! The recording can be encoded with e.g.:
+//! ```text
+//! ffmpeg -f concat -i video.ffconcat -i audio.wav -vf scale=1280:960:flags=neighbor output.mkv
+//! ```
+
+use crate::mainloop::audio::{WavError, WavRecorder};
+use jgenesis_common::frontend::{
+    AudioOutput, Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer, SaveWriter,
+    TickResult, TimingMode,
+};
+use std::cell::Cell;
+use std::fmt::{Debug, Display};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use thiserror::Error;
+
+// Must match the sample rate in the WAV recorder
+const SAMPLE_RATE: f64 = 48000.0;
+
+const CONCAT_FILE_NAME: &str = "video.ffconcat";
+const WAV_FILE_NAME: &str = "audio.wav";
+
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("Error creating recording directory '{path}': {source}")]
+    CreateDirectory {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("I/O error writing recording file '{path}': {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error encoding recorded frame '{path}': {source}")]
+    Encode {
+        path: String,
+        #[source]
+        source: png::EncodingError,
+    },
+    #[error("Error recording audio: {0}")]
+    Audio(#[from] WavError),
+}
+
+struct PendingFrame {
+    file_name: String,
+    sample_count: u64,
+}
+
+struct FrameWriter {
+    directory: PathBuf,
+    concat_path: PathBuf,
+    concat: BufWriter<File>,
+    frame_count: u64,
+    pending_frame: Option<PendingFrame>,
+    rgb_buffer: Vec<u8>,
+}
+
+impl FrameWriter {
+    fn create(directory: &Path, timing_mode: TimingMode) -> Result<Self, RecordingError> {
+        let concat_path = directory.join(CONCAT_FILE_NAME);
+        let write_err =
+            |source| RecordingError::Write { path: concat_path.display().to_string(), source };
+
+        let mut concat = BufWriter::new(File::create(&concat_path).map_err(write_err)?);
+        writeln!(concat, "ffconcat version 1.0").map_err(write_err)?;
+        writeln!(
+            concat,
+            "# Recorded by jgenesis {}; timing mode: {timing_mode}",
+            env!("CARGO_PKG_VERSION")
+        )
+        .map_err(write_err)?;
+
+        Ok(Self {
+            directory: directory.into(),
+            concat_path,
+            concat,
+            frame_count: 0,
+            pending_frame: None,
+            rgb_buffer: vec![],
+        })
+    }
+
+    fn write_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        sample_count: u64,
+    ) -> Result<(), RecordingError> {
+        // A frame's duration is not known until the next frame is emitted
+        self.flush_pending_frame(sample_count)?;
+
+        let file_name = format!("frame_{:08}.png", self.frame_count);
+        let path = self.directory.join(&file_name);
+
+        let len = (frame_size.width * frame_size.height) as usize;
+        self.rgb_buffer.clear();
+        self.rgb_buffer
+            .extend(frame_buffer[..len].iter().flat_map(|color| [color.r, color.g, color.b]));
+        write_png(&path, &self.rgb_buffer, frame_size)?;
+
+        self.frame_count += 1;
+        self.pending_frame = Some(PendingFrame { file_name, sample_count });
+
+        Ok(())
+    }
+
+    fn flush_pending_frame(&mut self, sample_count: u64) -> Result<(), RecordingError> {
+        let Some(pending_frame) = self.pending_frame.take() else { return Ok(()) };
+
+        let duration = (sample_count - pending_frame.sample_count) as f64 / SAMPLE_RATE;
+        writeln!(self.concat, "file '{}'", pending_frame.file_name)
+            .and_then(|()| writeln!(self.concat, "duration {duration:.6}"))
+            .map_err(|source| self.write_error(source))?;
+
+        Ok(())
+    }
+
+    fn finish(&mut self, sample_count: u64) -> Result<(), RecordingError> {
+        let last_file_name = self.pending_frame.as_ref().map(|frame| frame.file_name.clone());
+        self.flush_pending_frame(sample_count)?;
+
+        // ffmpeg ignores the duration of the last file in a concat script, so list it once more
+        if let Some(last_file_name) = last_file_name {
+            writeln!(self.concat, "file '{last_file_name}'")
+                .map_err(|source| self.write_error(source))?;
+        }
+
+        self.concat.flush().map_err(|source| self.write_error(source))
+    }
+
+    fn write_error(&self, source: io::Error) -> RecordingError {
+        RecordingError::Write { path: self.concat_path.display().to_string(), source }
+    }
+}
+
+fn write_png(path: &Path, rgb: &[u8], frame_size: FrameSize) -> Result<(), RecordingError> {
+    let file = File::create(path)
+        .map_err(|source| RecordingError::Write { path: path.display().to_string(), source })?;
+    let encode_err = |source| RecordingError::Encode { path: path.display().to_string(), source };
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), frame_size.width, frame_size.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    // Recording encodes a PNG every frame, so favor speed over file size
+    encoder.set_compression(png::Compression::Fast);
+
+    let mut writer = encoder.write_header().map_err(encode_err)?;
+    writer.write_image_data(rgb).map_err(encode_err)?;
+    writer.finish().map_err(encode_err)?;
+
+    Ok(())
+}
+
+/// Records every frame and audio sample that the emulator emits while it is ticked through
+/// [`AvRecorder::tick`].
+pub(crate) struct AvRecorder {
+    directory: PathBuf,
+    frames: FrameWriter,
+    wav: WavRecorder,
+    sample_count: Cell<u64>,
+    error: Option<RecordingError>,
+}
+
+impl AvRecorder {
+    pub(crate) fn create(
+        directory: PathBuf,
+        timing_mode: TimingMode,
+    ) -> Result<Self, RecordingError> {
+        fs::create_dir_all(&directory).map_err(|source| RecordingError::CreateDirectory {
+            path: directory.display().to_string(),
+            source,
+        })?;
+
+        let frames = FrameWriter::create(&directory, timing_mode)?;
+        let wav = WavRecorder::create(directory.join(WAV_FILE_NAME))?;
+
+        log::info!("Started recording video and audio to {}", directory.display());
+
+        Ok(Self { directory, frames, wav, sample_count: Cell::new(0), error: None })
+    }
+
+    /// Tick the emulator, recording any frames and audio samples it emits before passing them
+    /// through to the given renderer and audio output.
+    ///
+    /// Recording errors do not fail the tick; they can be retrieved afterwards using
+    /// [`AvRecorder::take_error`].
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn tick<Emulator, R, A, S>(
+        &mut self,
+        emulator: &mut Emulator,
+        renderer: &mut R,
+        audio_output: &mut A,
+        inputs: &Emulator::Inputs,
+        save_writer: &mut S,
+    ) -> TickResult<Emulator::Err<R::Err, A::Err, S::Err>>
+    where
+        Emulator: EmulatorTrait,
+        R: Renderer,
+        R::Err: Debug + Display + Send + Sync + 'static,
+        A: AudioOutput,
+        A::Err: Debug + Display + Send + Sync + 'static,
+        S: SaveWriter,
+        S::Err: Debug + Display + Send + Sync + 'static,
+    {
+        let mut renderer = RecordingRenderer {
+            inner: renderer,
+            frames: &mut self.frames,
+            sample_count: &self.sample_count,
+            error: None,
+        };
+        let mut audio_output = RecordingAudioOutput {
+            inner: audio_output,
+            wav: &mut self.wav,
+            sample_count: &self.sample_count,
+            error: None,
+        };
+
+        let result = emulator.tick(&mut renderer, &mut audio_output, inputs, save_writer);
+
+        if self.error.is_none() {
+            self.error = renderer.error.or(audio_output.error);
+        }
+
+        result
+    }
+
+    /// Return the first error encountered while recording, if any. The recording should be stopped
+    /// after an error.
+    pub(crate) fn take_error(&mut self) -> Option<RecordingError> {
+        self.error.take()
+    }
+}
+
+impl Drop for AvRecorder {
+    fn drop(&mut self) {
+        match self.frames.finish(self.sample_count.get()) {
+            Ok(()) => {
+                log::info!(
+                    "Saved {}-frame recording to {}",
+                    self.frames.frame_count,
+                    self.directory.display()
+                );
+            }
+            Err(err) => {
+                log::error!("Error finalizing recording: {err}");
+            }
+        }
+    }
+}
+
+struct RecordingRenderer<'a, R> {
+    inner: &'a mut R,
+    frames: &'a mut FrameWriter,
+    sample_count: &'a Cell<u64>,
+    error: Option<RecordingError>,
+}
+
+impl<R: Renderer> Renderer for RecordingRenderer<'_, R> {
+    type Err = R::Err;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        if self.error.is_none() {
+            if let Err(err) =
+                self.frames.write_frame(frame_buffer, frame_size, self.sample_count.get())
+            {
+                self.error = Some(err);
+            }
+        }
+
+        self.inner.render_frame(frame_buffer, frame_size, pixel_aspect_ratio)
+    }
+}
+
+struct RecordingAudioOutput<'a, A> {
+    inner: &'a mut A,
+    wav: &'a mut WavRecorder,
+    sample_count: &'a Cell<u64>,
+    error: Option<RecordingError>,
+}
+
+impl<A: AudioOutput> AudioOutput for RecordingAudioOutput<'_, A> {
+    type Err = A::Err;
+
+    #[inline]
+    fn push_sample(&mut self, sample_l: f64, sample_r: f64) -> Result<(), Self::Err> {
+        if self.error.is_none() {
+            match self.wav.push_sample(sample_l, sample_r) {
+                Ok(()) => self.sample_count.set(self.sample_count.get() + 1),
+                Err(err) => self.error = Some(err.into()),
+            }
+        }
+
+        self.inner.push_sample(sample_l, sample_r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mainloop::testutil::TempDir;
+    use std::convert::Infallible;
+    use std::io::BufReader;
+
+    struct NullOutput;
+
+    impl Renderer for NullOutput {
+        type Err = Infallible;
+
+        fn render_frame(
+            &mut self,
+            _frame_buffer: &[Color],
+            _frame_size: FrameSize,
+            _pixel_aspect_ratio: Option<PixelAspectRatio>,
+        ) -> Result<(), Self::Err> {
+            Ok(())
+        }
+    }
+
+    impl AudioOutput for NullOutput {
+        type Err = Infallible;
+
+        fn push_sample(&mut self, _sample_l: f64, _sample_r: f64) -> Result<(), Self::Err> {
+            Ok(())
+        }
+    }
+
+    fn read_png(path: &Path) -> (u32, u32, Vec<u8>) {
+        let decoder = png::Decoder::new(BufReader::new(File::open(path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        buffer.truncate(info.buffer_size());
+        (info.width, info.height, buffer)
+    }
+
+    #[test]
+    fn resolution_change() {
+        let directory = TempDir::new("recording");
+        let mut recorder = AvRecorder::create(directory.0.clone(), TimingMode::Ntsc).unwrap();
+
+        // H40, then H32, then interlaced H40, with 1/60th of a second of audio after each frame
+        let frames = [
+            (FrameSize { width: 320, height: 224 }, Color::rgb(255, 0, 0)),
+            (FrameSize { width: 256, height: 224 }, Color::rgb(0, 255, 0)),
+            (FrameSize { width: 320, height: 448 }, Color::rgb(0, 0, 255)),
+        ];
+
+        let mut null_renderer = NullOutput;
+        let mut null_audio_output = NullOutput;
+        let mut renderer = RecordingRenderer {
+            inner: &mut null_renderer,
+            frames: &mut recorder.frames,
+            sample_count: &recorder.sample_count,
+            error: None,
+        };
+        let mut audio_output = RecordingAudioOutput {
+            inner: &mut null_audio_output,
+            wav: &mut recorder.wav,
+            sample_count: &recorder.sample_count,
+            error: None,
+        };
+
+        for (frame_size, color) in frames {
+            // Frame buffers are sized for the largest resolution; only the frame size is recorded
+            let frame_buffer = vec![color; 320 * 448];
+            renderer.render_frame(&frame_buffer, frame_size, None).unwrap();
+            for _ in 0..800 {
+                audio_output.push_sample(0.0, 0.0).unwrap();
+            }
+        }
+
+        assert!(renderer.error.is_none());
+        assert!(audio_output.error.is_none());
+        drop(recorder);
+
+        // Each frame keeps its own dimensions
+        for (i, (frame_size, color)) in frames.into_iter().enumerate() {
+            let (width, height, rgb) = read_png(&directory.0.join(format!("frame_{i:08}.png")));
+            assert_eq!((width, height), (frame_size.width, frame_size.height));
+            assert_eq!(rgb.len(), (width * height * 3) as usize);
+            assert!(rgb.chunks_exact(3).all(|pixel| pixel == [color.r, color.g, color.b]));
+        }
+
+        let concat = fs::read_to_string(directory.0.join(CONCAT_FILE_NAME)).unwrap();
+        let entries: Vec<_> = concat.lines().skip(2).collect();
+        assert_eq!(
+            entries,
+            [
+                "file 'frame_00000000.png'",
+                "duration 0.016667",
+                "file 'frame_00000001.png'",
+                "duration 0.016667",
+                "file 'frame_00000002.png'",
+                "duration 0.016667",
+                "file 'frame_00000002.png'",
+            ]
+        );
+
+        // WAV header (58 bytes) followed by 3 frames' worth of stereo f32 samples
+        let wav_len = fs::metadata(directory.0.join(WAV_FILE_NAME)).unwrap().len();
+        assert_eq!(wav_len, 58 + 3 * 800 * 8);
+    }
+}
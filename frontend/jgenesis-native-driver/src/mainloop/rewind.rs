@@ -1,28 +1,92 @@
-use jgenesis_common::frontend::{EmulatorTrait, PartialClone, Renderer};
+use crate::mainloop::bincode_config;
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use jgenesis_common::frontend::{Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer};
 use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::io;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
 use std::time::{Duration, Instant};
+use thiserror::Error;
 
 const FRAME_DIVIDER: u64 = 10;
 
+#[derive(Debug, Error)]
+enum RewindError {
+    #[error("Error encoding rewind snapshot: {0}")]
+    Encode(#[from] EncodeError),
+    #[error("Error decoding rewind snapshot: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("Error compressing or decompressing rewind snapshot: {0}")]
+    Compression(#[from] io::Error),
+}
+
+// Frame buffers are not included in encoded emulator state, so each snapshot also stores the frame
+// that was on screen at the time so that it can be displayed while rewinding
+#[derive(Debug, Clone, Default, Encode, Decode)]
+struct FrameSnapshot {
+    width: u32,
+    height: u32,
+    pixel_aspect_ratio: Option<f64>,
+    pixels: Vec<Color>,
+}
+
+impl Renderer for FrameSnapshot {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        let len = (frame_size.width * frame_size.height) as usize;
+
+        self.width = frame_size.width;
+        self.height = frame_size.height;
+        self.pixel_aspect_ratio = pixel_aspect_ratio.map(f64::from);
+        self.pixels.clear();
+        self.pixels.extend_from_slice(&frame_buffer[..len]);
+
+        Ok(())
+    }
+}
+
+/// Snapshots are stored as reverse deltas: the newest snapshot is kept as raw encoded bytes, and
+/// every older snapshot is stored as the deflate-compressed XOR of its encoded bytes against the
+/// encoded bytes of the next newer snapshot. Consecutive snapshots are mostly identical, so the XOR
+/// is mostly zeroes and compresses extremely well. Rewinding pops from the newest end, which is
+/// always fully materialized, and dropping the oldest snapshot never invalidates another delta.
 pub struct Rewinder<Emulator> {
-    previous_states: VecDeque<Emulator>,
+    newest_snapshot: Option<Vec<u8>>,
+    // Ordered from oldest to newest
+    compressed_deltas: VecDeque<Vec<u8>>,
     buffer_len: usize,
     frame_count: u64,
     last_rewind_time: Option<Instant>,
+    frame_capture: FrameSnapshot,
+    _emulator: PhantomData<Emulator>,
 }
 
-impl<Emulator: PartialClone> Rewinder<Emulator> {
+impl<Emulator: EmulatorTrait> Rewinder<Emulator> {
     pub fn new(buffer_duration: Duration) -> Self {
         let buffer_len = duration_to_buffer_len(buffer_duration);
         Self {
-            previous_states: VecDeque::with_capacity(buffer_len + 1),
+            newest_snapshot: None,
+            compressed_deltas: VecDeque::with_capacity(buffer_len + 1),
             buffer_len,
             frame_count: 0,
             last_rewind_time: None,
+            frame_capture: FrameSnapshot::default(),
+            _emulator: PhantomData,
         }
     }
 
-    pub fn record_frame(&mut self, emulator: &Emulator) {
+    pub fn record_frame(&mut self, emulator: &mut Emulator) {
         if self.buffer_len == 0 {
             return;
         }
@@ -30,14 +94,56 @@ impl<Emulator: PartialClone> Rewinder<Emulator> {
         self.frame_count += 1;
 
         if self.frame_count % FRAME_DIVIDER == 0 {
-            self.previous_states.push_back(emulator.partial_clone());
+            if let Err(err) = self.push_snapshot(emulator) {
+                log::error!("{err}");
+                return;
+            }
 
-            while self.previous_states.len() > self.buffer_len {
-                self.previous_states.pop_front();
+            while self.len() > self.buffer_len {
+                self.compressed_deltas.pop_front();
             }
         }
     }
 
+    fn push_snapshot(&mut self, emulator: &mut Emulator) -> Result<(), RewindError> {
+        let Ok(()) = emulator.force_render(&mut self.frame_capture);
+
+        let conf = bincode_config!();
+        let mut snapshot = Vec::with_capacity(self.newest_snapshot.as_ref().map_or(0, Vec::len));
+        bincode::encode_into_std_write(&self.frame_capture, &mut snapshot, conf)?;
+        bincode::encode_into_std_write(&*emulator, &mut snapshot, conf)?;
+
+        if let Some(prev_snapshot) = &self.newest_snapshot {
+            self.compressed_deltas.push_back(encode_delta(prev_snapshot, &snapshot)?);
+        }
+        self.newest_snapshot = Some(snapshot);
+
+        Ok(())
+    }
+
+    fn pop_snapshot(&mut self) -> Result<Option<(FrameSnapshot, Emulator)>, RewindError> {
+        let Some(snapshot) = self.newest_snapshot.take() else { return Ok(None) };
+
+        if let Some(delta) = self.compressed_deltas.pop_back() {
+            self.newest_snapshot = Some(decode_delta(&delta, &snapshot)?);
+        }
+
+        let conf = bincode_config!();
+        let (frame, frame_len): (FrameSnapshot, _) = bincode::decode_from_slice(&snapshot, conf)?;
+        let (emulator, _) = bincode::decode_from_slice(&snapshot[frame_len..], conf)?;
+
+        Ok(Some((frame, emulator)))
+    }
+
+    fn len(&self) -> usize {
+        self.compressed_deltas.len() + usize::from(self.newest_snapshot.is_some())
+    }
+
+    fn clear(&mut self) {
+        self.newest_snapshot = None;
+        self.compressed_deltas.clear();
+    }
+
     pub fn start_rewinding(&mut self) {
         if self.last_rewind_time.is_none() {
             self.last_rewind_time = Some(Instant::now());
@@ -59,7 +165,6 @@ impl<Emulator: PartialClone> Rewinder<Emulator> {
         config: &Emulator::Config,
     ) -> Result<(), R::Err>
     where
-        Emulator: EmulatorTrait,
         R: Renderer,
     {
         let Some(last_rewind_time) = self.last_rewind_time else { return Ok(()) };
@@ -69,12 +174,26 @@ impl<Emulator: PartialClone> Rewinder<Emulator> {
 
         let now = Instant::now();
         if now.duration_since(last_rewind_time) >= Duration::from_secs_f64(rewind_interval_secs) {
-            let Some(mut clone) = self.previous_states.pop_back() else { return Ok(()) };
-            clone.take_rom_from(emulator);
-            *emulator = clone;
+            let (frame, mut snapshot) = match self.pop_snapshot() {
+                Ok(Some(popped)) => popped,
+                Ok(None) => return Ok(()),
+                Err(err) => {
+                    log::error!("{err}");
+                    self.clear();
+                    return Ok(());
+                }
+            };
+
+            snapshot.take_rom_from(emulator);
+            *emulator = snapshot;
 
             emulator.reload_config(config);
-            emulator.force_render(renderer)?;
+
+            renderer.render_frame(
+                &frame.pixels,
+                FrameSize { width: frame.width, height: frame.height },
+                frame.pixel_aspect_ratio.and_then(|par| PixelAspectRatio::try_from(par).ok()),
+            )?;
 
             self.last_rewind_time = Some(now);
         }
@@ -89,15 +208,80 @@ impl<Emulator: PartialClone> Rewinder<Emulator> {
     fn set_buffer_len(&mut self, buffer_len: usize) {
         self.buffer_len = buffer_len;
 
-        if buffer_len + 1 > self.previous_states.capacity() {
+        if buffer_len + 1 > self.compressed_deltas.capacity() {
             // Immediately resize deque to avoid incremental allocations later
             let mut resized = VecDeque::with_capacity(buffer_len + 1);
-            resized.extend(self.previous_states.drain(..));
-            self.previous_states = resized;
+            resized.extend(self.compressed_deltas.drain(..));
+            self.compressed_deltas = resized;
         }
     }
 }
 
+// Encode an older snapshot as a compressed delta against the next newer snapshot
+fn encode_delta(older: &[u8], newer: &[u8]) -> io::Result<Vec<u8>> {
+    compress(&xor_bytes(older, newer))
+}
+
+// Restore an older snapshot from its compressed delta and the next newer snapshot
+fn decode_delta(delta: &[u8], newer: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(xor_bytes(&decompress(delta)?, newer))
+}
+
+// XOR is its own inverse, so this both creates deltas and restores snapshots from deltas. The
+// output is always the length of `a`; bytes past the end of `b` are treated as zero.
+fn xor_bytes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().enumerate().map(|(i, &byte)| byte ^ b.get(i).copied().unwrap_or(0)).collect()
+}
+
+fn compress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+fn decompress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    DeflateDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
 fn duration_to_buffer_len(duration: Duration) -> usize {
     (duration.as_secs() * 60 / 5) as usize
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_round_trip() {
+        // Snapshot lengths can change between frames, e.g. when a Vec in the emulator state grows
+        let snapshots: Vec<Vec<u8>> = vec![
+            (0..100).collect(),
+            (0..100).map(|i| if i == 50 { 0xFF } else { i }).collect(),
+            (0..120).collect(),
+            (0..80).rev().collect(),
+            vec![],
+            vec![7; 64],
+        ];
+
+        let deltas: Vec<_> =
+            snapshots.windows(2).map(|pair| encode_delta(&pair[0], &pair[1]).unwrap()).collect();
+
+        // Walk backwards from the newest snapshot, the same way rewinding does
+        let mut snapshot = snapshots.last().unwrap().clone();
+        for (delta, expected) in deltas.iter().zip(&snapshots).rev() {
+            snapshot = decode_delta(delta, &snapshot).unwrap();
+            assert_eq!(&snapshot, expected);
+        }
+    }
+
+    #[test]
+    fn identical_snapshots_compress() {
+        let snapshot = vec![0xA5; 100_000];
+        let delta = encode_delta(&snapshot, &snapshot).unwrap();
+
+        assert!(delta.len() < 1000, "delta length {}", delta.len());
+        assert_eq!(decode_delta(&delta, &snapshot).unwrap(), snapshot);
+    }
+}
@@ -0,0 +1,520 @@
+use crate::mainloop::{bincode_config, NativeEmulatorError, NativeEmulatorResult};
+use bincode::error::DecodeError;
+use bincode::{Decode, Encode};
+use crc::Crc;
+use flate2::bufread::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use jgenesis_common::frontend::{Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer};
+use jgenesis_proc_macros::EnumDisplay;
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use time::OffsetDateTime;
+
+pub const SAVE_STATE_SLOTS: usize = 10;
+
+/// Magic number written at the very start of every save state file
+const SAVE_STATE_MAGIC: [u8; 8] = *b"JGENSTAT";
+
+/// Version of the save state container layout (magic, version, then deflate-compressed metadata and
+/// payload). This only covers the container; payload compatibility is tracked through the emulator
+/// version stored in the metadata.
+pub const SAVE_STATE_FORMAT_VERSION: u16 = 3;
+
+/// Thumbnails are downscaled to fit within this many pixels in each dimension
+const THUMBNAIL_MAX_WIDTH: u32 = 160;
+const THUMBNAIL_MAX_HEIGHT: u32 = 120;
+
+const CRC: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+/// Compute the checksum used to tie save states to a specific ROM image.
+#[must_use]
+pub(crate) fn rom_checksum(rom: &[u8]) -> u32 {
+    CRC.checksum(rom)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, EnumDisplay)]
+pub enum SaveStateConsole {
+    MasterSystem,
+    GameGear,
+    Genesis,
+    SegaCd,
+    Nes,
+    Snes,
+    GameBoy,
+    SpcPlayer,
+}
+
+/// Header that is written in front of every save state, after the magic number and format version.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SaveStateMetadata {
+    /// Seconds since the Unix epoch at the time the state was saved
+    pub timestamp_secs: u64,
+    pub console: SaveStateConsole,
+    /// CRC32 of the ROM image that was loaded when the state was saved
+    pub rom_checksum: u32,
+    pub rom_name: String,
+    pub emulator_version: String,
+    /// Downscaled copy of the last rendered frame, if the emulator had rendered one
+    pub thumbnail: Option<SaveStateThumbnail>,
+}
+
+/// Downscaled RGBA8 image of the frame that was on screen when a state was saved.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SaveStateThumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major RGBA8 pixels with straight (not premultiplied) alpha, as expected by PNG encoders
+    pub rgba: Vec<u8>,
+}
+
+impl SaveStateMetadata {
+    pub(crate) fn new(
+        console: SaveStateConsole,
+        rom_checksum: u32,
+        rom_name: String,
+        thumbnail: Option<SaveStateThumbnail>,
+    ) -> Self {
+        let timestamp_secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            timestamp_secs,
+            console,
+            rom_checksum,
+            rom_name,
+            emulator_version: env!("CARGO_PKG_VERSION").into(),
+            thumbnail,
+        }
+    }
+
+    /// Format the save timestamp as `YYYY-MM-DD HH:MM:SS` in UTC.
+    #[must_use]
+    pub fn formatted_timestamp(&self) -> String {
+        let Ok(date_time) = OffsetDateTime::from_unix_timestamp(self.timestamp_secs as i64) else {
+            return "<invalid timestamp>".into();
+        };
+
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            date_time.year(),
+            u8::from(date_time.month()),
+            date_time.day(),
+            date_time.hour(),
+            date_time.minute(),
+            date_time.second()
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct ThumbnailRenderer {
+    thumbnail: Option<SaveStateThumbnail>,
+}
+
+impl Renderer for ThumbnailRenderer {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        self.thumbnail = Some(downscale_frame(frame_buffer, frame_size, pixel_aspect_ratio));
+        Ok(())
+    }
+}
+
+/// Capture a thumbnail of the current frame by having the emulator re-render it.
+pub(crate) fn capture_thumbnail<Emulator: EmulatorTrait>(
+    emulator: &mut Emulator,
+) -> Option<SaveStateThumbnail> {
+    let mut renderer = ThumbnailRenderer::default();
+    let Ok(()) = emulator.force_render(&mut renderer);
+    renderer.thumbnail
+}
+
+/// Box filter the frame down to thumbnail size, correcting for the pixel aspect ratio so that the
+/// thumbnail can be displayed with square pixels.
+fn downscale_frame(
+    frame_buffer: &[Color],
+    frame_size: FrameSize,
+    pixel_aspect_ratio: Option<PixelAspectRatio>,
+) -> SaveStateThumbnail {
+    let FrameSize { width: src_width, height: src_height } = frame_size;
+    if src_width == 0 || src_height == 0 {
+        return SaveStateThumbnail { width: 0, height: 0, rgba: vec![] };
+    }
+
+    let par = pixel_aspect_ratio.map_or(1.0, f64::from);
+    let display_width = f64::from(src_width) * par;
+    // Frames that already fit are kept at their display size rather than scaled up
+    let scale = (f64::from(THUMBNAIL_MAX_WIDTH) / display_width)
+        .min(f64::from(THUMBNAIL_MAX_HEIGHT) / f64::from(src_height))
+        .min(1.0);
+    let width = ((display_width * scale).round() as u32).clamp(1, THUMBNAIL_MAX_WIDTH);
+    let height = ((f64::from(src_height) * scale).round() as u32).clamp(1, THUMBNAIL_MAX_HEIGHT);
+
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        let src_y_start = y * src_height / height;
+        let src_y_end = ((y + 1) * src_height / height).max(src_y_start + 1);
+
+        for x in 0..width {
+            let src_x_start = x * src_width / width;
+            let src_x_end = ((x + 1) * src_width / width).max(src_x_start + 1);
+
+            let mut sums = [0_u32; 3];
+            for src_y in src_y_start..src_y_end {
+                for src_x in src_x_start..src_x_end {
+                    let color = frame_buffer[(src_y * src_width + src_x) as usize];
+                    sums[0] += u32::from(color.r);
+                    sums[1] += u32::from(color.g);
+                    sums[2] += u32::from(color.b);
+                }
+            }
+
+            let count = (src_y_end - src_y_start) * (src_x_end - src_x_start);
+            rgba.extend(sums.map(|sum| (sum / count) as u8));
+            rgba.push(255);
+        }
+    }
+
+    SaveStateThumbnail { width, height, rgba }
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveStateSlot {
+    pub slot: usize,
+    pub path: PathBuf,
+    /// None if the slot is empty or the state file could not be read
+    pub metadata: Option<SaveStateMetadata>,
+}
+
+#[must_use]
+pub fn save_state_path<P: AsRef<Path>>(rom_path: P, slot: usize) -> PathBuf {
+    rom_path.as_ref().with_extension(format!("ss{slot}"))
+}
+
+/// List every save state slot for the given ROM file, including empty slots.
+#[must_use]
+pub fn list_save_states<P: AsRef<Path>>(rom_path: P) -> Vec<SaveStateSlot> {
+    (0..SAVE_STATE_SLOTS).map(|slot| read_save_state_slot(rom_path.as_ref(), slot)).collect()
+}
+
+/// Read a single save state slot for the given ROM file. The slot's metadata is None if the slot
+/// is empty.
+#[must_use]
+pub fn read_save_state_slot<P: AsRef<Path>>(rom_path: P, slot: usize) -> SaveStateSlot {
+    let path = save_state_path(rom_path, slot);
+    let metadata = if path.is_file() {
+        read_metadata(&path)
+            .map_err(|err| {
+                log::warn!("Unable to read save state metadata from {}: {err}", path.display());
+            })
+            .ok()
+    } else {
+        None
+    };
+
+    SaveStateSlot { slot, path, metadata }
+}
+
+/// Read only the metadata header from a save state file.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be opened, if it is not a save state file
+/// in a supported container format version, or if the header cannot be decoded.
+pub fn read_metadata<P: AsRef<Path>>(path: P) -> NativeEmulatorResult<SaveStateMetadata> {
+    let mut payload = open_payload(path.as_ref())?;
+    let metadata = bincode::decode_from_std_read(&mut payload, bincode_config!())?;
+
+    Ok(metadata)
+}
+
+/// Open a save state file and verify the uncompressed magic number and format version, returning
+/// a reader over the decompressed remainder of the file.
+fn open_payload(path: &Path) -> NativeEmulatorResult<DeflateDecoder<BufReader<File>>> {
+    let mut file = open_state_file(path)?;
+
+    let conf = bincode_config!();
+
+    let magic: [u8; 8] = bincode::decode_from_std_read(&mut file, conf).map_err(|_| {
+        NativeEmulatorError::SaveStateInvalidMagic { path: path.display().to_string() }
+    })?;
+    if magic != SAVE_STATE_MAGIC {
+        return Err(NativeEmulatorError::SaveStateInvalidMagic {
+            path: path.display().to_string(),
+        });
+    }
+
+    let format_version: u16 = bincode::decode_from_std_read(&mut file, conf)?;
+    if format_version != SAVE_STATE_FORMAT_VERSION {
+        return Err(NativeEmulatorError::SaveStateFormatVersion {
+            path: path.display().to_string(),
+            expected: SAVE_STATE_FORMAT_VERSION,
+            actual: format_version,
+        });
+    }
+
+    Ok(DeflateDecoder::new(file))
+}
+
+pub(crate) fn save_state<E, P>(
+    emulator: &E,
+    metadata: &SaveStateMetadata,
+    path: P,
+) -> NativeEmulatorResult<()>
+where
+    E: Encode,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let write_err =
+        |source| NativeEmulatorError::StateFileWrite { path: path.display().to_string(), source };
+
+    let mut file = BufWriter::new(File::create(path).map_err(|source| {
+        NativeEmulatorError::StateFileOpen { path: path.display().to_string(), source }
+    })?);
+
+    let conf = bincode_config!();
+    bincode::encode_into_std_write(SAVE_STATE_MAGIC, &mut file, conf)?;
+    bincode::encode_into_std_write(SAVE_STATE_FORMAT_VERSION, &mut file, conf)?;
+
+    let mut encoder = DeflateEncoder::new(file, Compression::default());
+    bincode::encode_into_std_write(metadata, &mut encoder, conf)?;
+    bincode::encode_into_std_write(emulator, &mut encoder, conf)?;
+    encoder.finish().and_then(|mut file| file.flush()).map_err(write_err)?;
+
+    log::info!("Saved state to {}", path.display());
+
+    Ok(())
+}
+
+/// Load a save state, verifying that it was created for the given console and ROM before
+/// decoding the emulator state.
+pub(crate) fn load_state<D, P>(
+    path: P,
+    console: SaveStateConsole,
+    rom_checksum: u32,
+) -> NativeEmulatorResult<(D, SaveStateMetadata)>
+where
+    D: Decode,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+
+    let mut payload = open_payload(path)?;
+    let metadata: SaveStateMetadata =
+        bincode::decode_from_std_read(&mut payload, bincode_config!())?;
+
+    if metadata.console != console {
+        return Err(NativeEmulatorError::SaveStateConsoleMismatch {
+            expected: console,
+            actual: metadata.console,
+        });
+    }
+
+    if metadata.rom_checksum != rom_checksum {
+        return Err(NativeEmulatorError::SaveStateRomMismatch {
+            expected: rom_checksum,
+            actual: metadata.rom_checksum,
+        });
+    }
+
+    let emulator = bincode::decode_from_std_read(&mut payload, bincode_config!())
+        .map_err(|err| payload_decode_error(err, &metadata))?;
+
+    log::info!(
+        "Loaded state from {} (saved {} by version {})",
+        path.display(),
+        metadata.formatted_timestamp(),
+        metadata.emulator_version
+    );
+
+    Ok((emulator, metadata))
+}
+
+fn payload_decode_error(err: DecodeError, metadata: &SaveStateMetadata) -> NativeEmulatorError {
+    let current_version = env!("CARGO_PKG_VERSION");
+    if metadata.emulator_version == current_version {
+        return err.into();
+    }
+
+    NativeEmulatorError::SaveStateIncompatibleVersion {
+        saved_version: metadata.emulator_version.clone(),
+        current_version: current_version.into(),
+        source: err,
+    }
+}
+
+fn open_state_file(path: &Path) -> NativeEmulatorResult<BufReader<File>> {
+    let file = File::open(path).map_err(|source| NativeEmulatorError::StateFileOpen {
+        path: path.display().to_string(),
+        source,
+    })?;
+
+    Ok(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mainloop::testutil::TempFile;
+    use std::num::NonZeroU32;
+
+    fn test_metadata() -> SaveStateMetadata {
+        SaveStateMetadata::new(
+            SaveStateConsole::Genesis,
+            rom_checksum(b"test ROM"),
+            "Test ROM".into(),
+            Some(SaveStateThumbnail { width: 1, height: 1, rgba: vec![1, 2, 3, 255] }),
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let file = TempFile::new("round_trip.ss0");
+        let metadata = test_metadata();
+        let state = (0x1234_u16, vec![5_u8; 1000]);
+
+        save_state(&state, &metadata, &file.0).unwrap();
+
+        assert_eq!(read_metadata(&file.0).unwrap(), metadata);
+
+        let (loaded_state, loaded_metadata): ((u16, Vec<u8>), _) =
+            load_state(&file.0, SaveStateConsole::Genesis, rom_checksum(b"test ROM")).unwrap();
+        assert_eq!(loaded_state, state);
+        assert_eq!(loaded_metadata, metadata);
+    }
+
+    #[test]
+    fn console_and_rom_mismatch() {
+        let file = TempFile::new("mismatch.ss0");
+        save_state(&0_u32, &test_metadata(), &file.0).unwrap();
+
+        let result = load_state::<u32, _>(&file.0, SaveStateConsole::SegaCd, 0);
+        assert!(matches!(
+            result,
+            Err(NativeEmulatorError::SaveStateConsoleMismatch {
+                expected: SaveStateConsole::SegaCd,
+                actual: SaveStateConsole::Genesis
+            })
+        ));
+
+        let checksum = rom_checksum(b"test ROM");
+        let result = load_state::<u32, _>(&file.0, SaveStateConsole::Genesis, checksum ^ 1);
+        assert!(matches!(
+            result,
+            Err(NativeEmulatorError::SaveStateRomMismatch { expected, actual })
+                if expected == checksum ^ 1 && actual == checksum
+        ));
+    }
+
+    #[test]
+    fn invalid_container() {
+        let file = TempFile::new("invalid.ss0");
+
+        std::fs::write(&file.0, b"NOTSTATE\x03\x00").unwrap();
+        assert!(matches!(
+            read_metadata(&file.0),
+            Err(NativeEmulatorError::SaveStateInvalidMagic { .. })
+        ));
+
+        // Too short to contain the magic number
+        std::fs::write(&file.0, b"JGEN").unwrap();
+        assert!(matches!(
+            read_metadata(&file.0),
+            Err(NativeEmulatorError::SaveStateInvalidMagic { .. })
+        ));
+
+        std::fs::write(&file.0, b"JGENSTAT\xFF\xFF").unwrap();
+        assert!(matches!(
+            read_metadata(&file.0),
+            Err(NativeEmulatorError::SaveStateFormatVersion { expected, actual: 0xFFFF, .. })
+                if expected == SAVE_STATE_FORMAT_VERSION
+        ));
+    }
+
+    fn test_frame(width: u32, height: u32, color_fn: impl Fn(u32, u32) -> Color) -> Vec<Color> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| color_fn(x, y))
+            .collect()
+    }
+
+    fn par(width: u32, height: u32) -> PixelAspectRatio {
+        PixelAspectRatio::from_width_and_height(
+            NonZeroU32::new(width).unwrap(),
+            NonZeroU32::new(height).unwrap(),
+        )
+    }
+
+    #[test]
+    fn thumbnail_max_size() {
+        // Wide frame is limited by the max width
+        let frame = test_frame(320, 224, |_, _| Color::BLACK);
+        let thumbnail =
+            downscale_frame(&frame, FrameSize { width: 320, height: 224 }, Some(par(8, 7)));
+        assert_eq!((thumbnail.width, thumbnail.height), (160, 98));
+        assert_eq!(thumbnail.rgba.len(), 160 * 98 * 4);
+
+        // Tall frame is limited by the max height
+        let frame = test_frame(256, 480, |_, _| Color::BLACK);
+        let thumbnail = downscale_frame(&frame, FrameSize { width: 256, height: 480 }, None);
+        assert_eq!((thumbnail.width, thumbnail.height), (64, 120));
+        assert_eq!(thumbnail.rgba.len(), 64 * 120 * 4);
+    }
+
+    #[test]
+    fn thumbnail_box_filter() {
+        // Alternating black and white columns downscaled by 2x should average to gray
+        let white = Color::rgb(255, 255, 255);
+        let frame = test_frame(320, 240, |x, _| if x % 2 == 0 { Color::BLACK } else { white });
+        let thumbnail = downscale_frame(&frame, FrameSize { width: 320, height: 240 }, None);
+        assert_eq!((thumbnail.width, thumbnail.height), (160, 120));
+        assert!(thumbnail.rgba.chunks_exact(4).all(|pixel| pixel == [127, 127, 127, 255]));
+    }
+
+    #[test]
+    fn thumbnail_aspect_ratio() {
+        let frame_size = FrameSize { width: 256, height: 224 };
+        let frame = test_frame(256, 224, |_, _| Color::BLACK);
+
+        // Square pixels
+        let thumbnail = downscale_frame(&frame, frame_size, None);
+        assert_eq!((thumbnail.width, thumbnail.height), (137, 120));
+
+        // Pixel aspect ratio should widen the thumbnail so it can be displayed with square pixels
+        let thumbnail = downscale_frame(&frame, frame_size, Some(par(8, 7)));
+        assert_eq!((thumbnail.width, thumbnail.height), (157, 120));
+        let display_aspect = 256.0 * 8.0 / 7.0 / 224.0;
+        let thumbnail_aspect = f64::from(thumbnail.width) / f64::from(thumbnail.height);
+        assert!((thumbnail_aspect - display_aspect).abs() < 0.01);
+    }
+
+    #[test]
+    fn thumbnail_small_frame() {
+        // Frames smaller than the max size are copied as-is rather than scaled up
+        let color_fn = |x, y| Color::rgb(x as u8, y as u8, (x + y) as u8);
+        let frame = test_frame(64, 48, color_fn);
+        let thumbnail = downscale_frame(&frame, FrameSize { width: 64, height: 48 }, None);
+        assert_eq!((thumbnail.width, thumbnail.height), (64, 48));
+
+        let expected: Vec<_> =
+            frame.iter().flat_map(|color| [color.r, color.g, color.b, 255]).collect();
+        assert_eq!(thumbnail.rgba, expected);
+
+        // Empty frames produce an empty thumbnail
+        let thumbnail = downscale_frame(&[], FrameSize { width: 0, height: 0 }, None);
+        assert_eq!((thumbnail.width, thumbnail.height), (0, 0));
+        assert!(thumbnail.rgba.is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::mainloop;
+use jgenesis_common::frontend::{Color, EmulatorTrait, FrameSize, PixelAspectRatio, Renderer};
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub(crate) enum ScreenshotError {
+    #[error("Error creating screenshot directory '{path}': {source}")]
+    CreateDirectory {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error opening screenshot file '{path}': {source}")]
+    Open {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Error encoding screenshot PNG '{path}': {source}")]
+    Encode {
+        path: String,
+        #[source]
+        source: png::EncodingError,
+    },
+}
+
+struct ScreenshotRenderer {
+    frame_buffer: Vec<Color>,
+    frame_size: FrameSize,
+    pixel_aspect_ratio: Option<PixelAspectRatio>,
+}
+
+impl Renderer for ScreenshotRenderer {
+    type Err = Infallible;
+
+    fn render_frame(
+        &mut self,
+        frame_buffer: &[Color],
+        frame_size: FrameSize,
+        pixel_aspect_ratio: Option<PixelAspectRatio>,
+    ) -> Result<(), Self::Err> {
+        let len = (frame_size.width * frame_size.height) as usize;
+
+        self.frame_buffer = frame_buffer[..len].to_vec();
+        self.frame_size = frame_size;
+        self.pixel_aspect_ratio = pixel_aspect_ratio;
+
+        Ok(())
+    }
+}
+
+/// Save the current frame as a PNG at the emulator's native resolution, returning the path that
+/// was written.
+///
+/// The file is written to `directory`, or next to the ROM file if `directory` is None, and is named
+/// after the ROM and the current UTC time. If `aspect_ratio_correction` is set, the frame is
+/// stretched horizontally or vertically using nearest-neighbor sampling so that pixels are square;
+/// otherwise every emulated pixel maps to exactly one image pixel.
+pub(crate) fn save_screenshot<Emulator: EmulatorTrait>(
+    emulator: &mut Emulator,
+    rom_path: &Path,
+    rom_name: &str,
+    directory: Option<&Path>,
+    aspect_ratio_correction: bool,
+) -> Result<PathBuf, ScreenshotError> {
+    let mut renderer = ScreenshotRenderer {
+        frame_buffer: vec![],
+        frame_size: FrameSize { width: 0, height: 0 },
+        pixel_aspect_ratio: None,
+    };
+    let Ok(()) = emulator.force_render(&mut renderer);
+
+    let (frame_buffer, frame_size) = match renderer.pixel_aspect_ratio {
+        Some(par) if aspect_ratio_correction => {
+            correct_aspect_ratio(&renderer.frame_buffer, renderer.frame_size, par.into())
+        }
+        _ => (renderer.frame_buffer, renderer.frame_size),
+    };
+
+    let directory = directory.or_else(|| rom_path.parent()).unwrap_or(Path::new("."));
+    fs::create_dir_all(directory).map_err(|source| ScreenshotError::CreateDirectory {
+        path: directory.display().to_string(),
+        source,
+    })?;
+
+    let path = mainloop::timestamped_path(directory, rom_name, "png");
+    write_png(&path, &frame_buffer, frame_size)?;
+
+    Ok(path)
+}
+
+fn correct_aspect_ratio(
+    frame_buffer: &[Color],
+    frame_size: FrameSize,
+    pixel_aspect_ratio: f64,
+) -> (Vec<Color>, FrameSize) {
+    // Only ever scale up so that no emulated pixels are dropped
+    let (width, height) = if pixel_aspect_ratio >= 1.0 {
+        ((f64::from(frame_size.width) * pixel_aspect_ratio).round() as u32, frame_size.height)
+    } else {
+        (frame_size.width, (f64::from(frame_size.height) / pixel_aspect_ratio).round() as u32)
+    };
+
+    let mut scaled = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        let src_y = y * frame_size.height / height;
+        for x in 0..width {
+            let src_x = x * frame_size.width / width;
+            scaled.push(frame_buffer[(src_y * frame_size.width + src_x) as usize]);
+        }
+    }
+
+    (scaled, FrameSize { width, height })
+}
+
+fn write_png(
+    path: &Path,
+    frame_buffer: &[Color],
+    frame_size: FrameSize,
+) -> Result<(), ScreenshotError> {
+    let file = File::create(path)
+        .map_err(|source| ScreenshotError::Open { path: path.display().to_string(), source })?;
+    let encode_err = |source| ScreenshotError::Encode { path: path.display().to_string(), source };
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), frame_size.width, frame_size.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let rgb: Vec<u8> = frame_buffer.iter().flat_map(|color| [color.r, color.g, color.b]).collect();
+    let mut writer = encoder.write_header().map_err(encode_err)?;
+    writer.write_image_data(&rgb).map_err(encode_err)?;
+    writer.finish().map_err(encode_err)?;
+
+    Ok(())
+}
@@ -1,16 +1,15 @@
-use crate::config::SmsGgConfig;
+use crate::config::{CommonConfig, SmsGgConfig};
 
+use crate::input::InputMapper;
 use crate::mainloop::save::FsSaveWriter;
 use crate::mainloop::{
-    basic_input_mapper_fn, debug, file_name_no_ext, parse_file_ext, NativeEmulatorError,
+    debug, file_name_no_ext, read_rom_file, savestate, RomFile, SaveStateConsole,
 };
 use crate::{config, AudioError, NativeEmulator, NativeEmulatorResult};
 use jgenesis_common::frontend::EmulatorTrait;
 
 use smsgg_core::psg::PsgVersion;
 use smsgg_core::{SmsGgButton, SmsGgEmulator, SmsGgEmulatorConfig, SmsGgInputs};
-use std::fs;
-use std::path::Path;
 
 pub type NativeSmsGgEmulator =
     NativeEmulator<SmsGgInputs, SmsGgButton, SmsGgEmulatorConfig, SmsGgEmulator>;
@@ -37,11 +36,13 @@ impl NativeSmsGgEmulator {
         self.emulator.reload_config(&emulator_config);
         self.config = emulator_config;
 
-        if let Err(err) = self.input_mapper.reload_config(
-            config.common.keyboard_inputs,
-            config.common.joystick_inputs,
+        if let Err(err) = self.input_mapper.reload_config_smsgg(
+            &config.common.keyboard_inputs,
+            &config.common.joystick_inputs,
+            &config.light_phaser_config,
+            &config.paddle_config,
+            &config.sports_pad_config,
             config.common.axis_deadzone,
-            &SmsGgButton::ALL,
         ) {
             log::error!("Error reloading input config: {err}");
         }
@@ -50,6 +51,8 @@ impl NativeSmsGgEmulator {
     }
 }
 
+const SUPPORTED_EXTENSIONS: &[&str] = &["sms", "gg"];
+
 /// Create an emulator with the SMS/GG core with the given config.
 ///
 /// # Errors
@@ -58,33 +61,45 @@ impl NativeSmsGgEmulator {
 pub fn create_smsgg(config: Box<SmsGgConfig>) -> NativeEmulatorResult<NativeSmsGgEmulator> {
     log::info!("Running with config: {config}");
 
-    let rom_file_path = Path::new(&config.common.rom_file_path);
-    let file_ext = parse_file_ext(rom_file_path)?;
-
-    let save_state_path = rom_file_path.with_extension("ss0");
+    let RomFile { contents: rom, extension, base_path } =
+        read_rom_file(&config.common, SUPPORTED_EXTENSIONS)?;
 
-    let rom = fs::read(rom_file_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: rom_file_path.display().to_string(),
-        source,
-    })?;
-
-    let save_path = rom_file_path.with_extension("sav");
+    let save_path = base_path.with_extension("sav");
     let mut save_writer = FsSaveWriter::new(save_path);
 
     let vdp_version =
-        config.vdp_version.unwrap_or_else(|| config::default_vdp_version_for_ext(file_ext));
+        config.vdp_version.unwrap_or_else(|| config::default_vdp_version_for_ext(&extension));
     let psg_version =
-        config.psg_version.unwrap_or_else(|| config::default_psg_version_for_ext(file_ext));
+        config.psg_version.unwrap_or_else(|| config::default_psg_version_for_ext(&extension));
 
     log::info!("VDP version: {vdp_version:?}");
     log::info!("PSG version: {psg_version:?}");
 
-    let rom_title = file_name_no_ext(rom_file_path)?;
+    let rom_title = file_name_no_ext(&base_path)?;
     let window_title = format!("smsgg - {rom_title}");
 
     let emulator_config = config.to_emulator_config(vdp_version, psg_version);
+    let rom_checksum = savestate::rom_checksum(&rom);
     let emulator = SmsGgEmulator::create(rom, emulator_config, &mut save_writer);
 
+    let console = if vdp_version.is_master_system() {
+        SaveStateConsole::MasterSystem
+    } else {
+        SaveStateConsole::GameGear
+    };
+
+    let input_mapper_fn = |joystick_subsystem, common_config: &CommonConfig<_, _>| {
+        InputMapper::new_smsgg(
+            joystick_subsystem,
+            &common_config.keyboard_inputs,
+            &common_config.joystick_inputs,
+            &config.light_phaser_config,
+            &config.paddle_config,
+            &config.sports_pad_config,
+            common_config.axis_deadzone,
+        )
+    };
+
     NativeSmsGgEmulator::new(
         emulator,
         emulator_config,
@@ -92,8 +107,10 @@ pub fn create_smsgg(config: Box<SmsGgConfig>) -> NativeEmulatorResult<NativeSmsG
         config::default_smsgg_window_size(vdp_version),
         &window_title,
         save_writer,
-        save_state_path,
-        basic_input_mapper_fn(&SmsGgButton::ALL),
+        base_path,
+        console,
+        rom_checksum,
+        input_mapper_fn,
         debug::smsgg::render_fn,
     )
 }
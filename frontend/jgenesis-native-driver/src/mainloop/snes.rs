@@ -2,12 +2,15 @@ use crate::config::{CommonConfig, SnesConfig};
 use crate::input::InputMapper;
 
 use crate::mainloop::save::FsSaveWriter;
-use crate::mainloop::{debug, NativeEmulatorError};
+use crate::mainloop::{
+    debug, read_rom_file, savestate, NativeEmulatorError, RomFile, SaveStateConsole,
+};
 use crate::{config, AudioError, NativeEmulator, NativeEmulatorResult};
 use jgenesis_common::frontend::EmulatorTrait;
 
 use snes_core::api::{SnesEmulator, SnesEmulatorConfig};
 use snes_core::input::{SnesButton, SnesInputs};
+use snes_core::spc::SpcPlayer;
 use std::fs;
 use std::path::Path;
 
@@ -28,10 +31,12 @@ impl NativeSnesEmulator {
         self.config = emulator_config;
 
         if let Err(err) = self.input_mapper.reload_config_snes(
+            config.p1_controller_type,
             config.p2_controller_type,
             &config.common.keyboard_inputs,
             &config.common.joystick_inputs,
             &config.super_scope_config,
+            &config.mouse_config,
             config.common.axis_deadzone,
         ) {
             log::error!("Error reloading input config: {err}");
@@ -41,6 +46,8 @@ impl NativeSnesEmulator {
     }
 }
 
+const SUPPORTED_EXTENSIONS: &[&str] = &["sfc", "smc"];
+
 /// Create an emulator with the SNES core with the given config.
 ///
 /// # Errors
@@ -49,18 +56,15 @@ impl NativeSnesEmulator {
 pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEmulator> {
     log::info!("Running with config: {config}");
 
-    let rom_path = Path::new(&config.common.rom_file_path);
-    let rom = fs::read(rom_path).map_err(|source| NativeEmulatorError::RomRead {
-        path: config.common.rom_file_path.clone(),
-        source,
-    })?;
+    let RomFile { contents: rom, base_path, .. } =
+        read_rom_file(&config.common, SUPPORTED_EXTENSIONS)?;
 
-    let save_path = rom_path.with_extension("sav");
-    let save_state_path = rom_path.with_extension("ss0");
+    let save_path = base_path.with_extension("sav");
     let mut save_writer = FsSaveWriter::new(save_path);
 
     let emulator_config = config.to_emulator_config();
     let coprocessor_roms = config.to_coprocessor_roms();
+    let rom_checksum = savestate::rom_checksum(&rom);
     let mut emulator =
         SnesEmulator::create(rom, emulator_config, coprocessor_roms, &mut save_writer)?;
 
@@ -70,10 +74,12 @@ pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEm
     let input_mapper_fn = |joystick, common_config: &CommonConfig<_, _>| {
         InputMapper::new_snes(
             joystick,
+            config.p1_controller_type,
             config.p2_controller_type,
             &common_config.keyboard_inputs,
             &common_config.joystick_inputs,
             &config.super_scope_config,
+            &config.mouse_config,
             common_config.axis_deadzone,
         )
     };
@@ -85,8 +91,74 @@ pub fn create_snes(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSnesEm
         config::DEFAULT_GENESIS_WINDOW_SIZE,
         &window_title,
         save_writer,
-        save_state_path,
+        base_path,
+        SaveStateConsole::Snes,
+        rom_checksum,
         input_mapper_fn,
         debug::snes::render_fn,
     )
 }
+
+pub type NativeSpcPlayer = NativeEmulator<SnesInputs, SnesButton, SnesEmulatorConfig, SpcPlayer>;
+
+/// Create a player that runs only the SNES APU from an `.spc` file. The ROM file path in the
+/// config should point to the SPC file.
+///
+/// # Errors
+///
+/// This function will return an error if unable to read or parse the SPC file, or if unable to
+/// initialize the frontend.
+pub fn create_spc_player(config: Box<SnesConfig>) -> NativeEmulatorResult<NativeSpcPlayer> {
+    log::info!("Running SPC player with config: {config}");
+
+    let spc_path = Path::new(&config.common.rom_file_path);
+    let spc_file = fs::read(spc_path).map_err(|source| NativeEmulatorError::RomRead {
+        path: config.common.rom_file_path.clone(),
+        source,
+    })?;
+
+    let rom_checksum = savestate::rom_checksum(&spc_file);
+    let emulator = SpcPlayer::create(spc_file)?;
+
+    let metadata = emulator.metadata();
+    log::info!("Song: {}", metadata.song_title);
+    log::info!("Game: {}", metadata.game_title);
+    log::info!("Artist: {}", metadata.artist);
+
+    let window_title = match (metadata.song_title.as_str(), metadata.game_title.as_str()) {
+        ("", "") => "spc".into(),
+        (song_title, "") => format!("spc - {song_title}"),
+        (song_title, game_title) => format!("spc - {game_title} - {song_title}"),
+    };
+
+    // The player never writes save files
+    let save_writer = FsSaveWriter::new(spc_path.with_extension("sav"));
+    let rom_base_path = spc_path.to_path_buf();
+
+    let input_mapper_fn = |joystick, common_config: &CommonConfig<_, _>| {
+        InputMapper::new_snes(
+            joystick,
+            config.p1_controller_type,
+            config.p2_controller_type,
+            &common_config.keyboard_inputs,
+            &common_config.joystick_inputs,
+            &config.super_scope_config,
+            &config.mouse_config,
+            common_config.axis_deadzone,
+        )
+    };
+
+    NativeSpcPlayer::new(
+        emulator,
+        config.to_emulator_config(),
+        config.common,
+        config::DEFAULT_GENESIS_WINDOW_SIZE,
+        &window_title,
+        save_writer,
+        rom_base_path,
+        SaveStateConsole::SpcPlayer,
+        rom_checksum,
+        input_mapper_fn,
+        debug::snes::spc_player_render_fn,
+    )
+}
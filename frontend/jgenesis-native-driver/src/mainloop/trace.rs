@@ -0,0 +1,135 @@
+//! Instruction trace logging.
+//!
+//! The emulation cores buffer trace lines in their debug hooks while tracing is active; this module
+//! selects the CPU to trace and periodically drains the buffered lines into a file.
+
+use jgenesis_common::debug::{Debuggable, TraceRange};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TraceError {
+    #[error("Unknown trace CPU '{name}'; expected one of {expected}")]
+    UnknownCpu { name: String, expected: String },
+    #[error("I/O error creating trace log '{path}': {source}")]
+    Create {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("I/O error writing trace log '{path}': {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Which CPU to trace and which addresses to include, from the frontend config.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TraceOptions {
+    /// CPU name as listed by [`Debuggable::debug_cpus`]; defaults to the first CPU
+    pub(crate) cpu: Option<String>,
+    pub(crate) ranges: Vec<TraceRange>,
+}
+
+impl TraceOptions {
+    fn resolve_cpu(&self, emulator: &dyn Debuggable) -> Result<usize, TraceError> {
+        let Some(name) = &self.cpu else { return Ok(0) };
+
+        let cpus = emulator.debug_cpus();
+        cpus.iter().position(|cpu| cpu.name.eq_ignore_ascii_case(name)).ok_or_else(|| {
+            let expected =
+                cpus.iter().map(|cpu| format!("'{}'", cpu.name)).collect::<Vec<_>>().join(", ");
+            TraceError::UnknownCpu { name: name.clone(), expected }
+        })
+    }
+
+    /// File name suffix for trace logs of the selected CPU, e.g. `sub_68000`.
+    pub(crate) fn file_suffix(&self, emulator: &dyn Debuggable) -> String {
+        let cpu = self.resolve_cpu(emulator).unwrap_or(0);
+        emulator.debug_cpus()[cpu].name.to_ascii_lowercase().replace(' ', "_")
+    }
+}
+
+/// A trace log that is being written while tracing is active.
+pub(crate) struct TraceFile {
+    cpu: usize,
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl TraceFile {
+    /// Start tracing the CPU selected by `options` and create a trace log at the given path.
+    pub(crate) fn start<Emulator: Debuggable>(
+        emulator: &mut Emulator,
+        options: &TraceOptions,
+        path: PathBuf,
+    ) -> Result<Self, TraceError> {
+        let cpu = options.resolve_cpu(emulator)?;
+
+        let file = File::create(&path)
+            .map_err(|source| TraceError::Create { path: path.display().to_string(), source })?;
+        emulator.debug_hooks_mut().start_trace(cpu, options.ranges.clone());
+
+        log::info!(
+            "Started {} instruction trace to {}",
+            emulator.debug_cpus()[cpu].name,
+            path.display()
+        );
+
+        Ok(Self { cpu, path, writer: BufWriter::new(file) })
+    }
+
+    fn write_error(&self, source: io::Error) -> TraceError {
+        TraceError::Write { path: self.path.display().to_string(), source }
+    }
+}
+
+impl Drop for TraceFile {
+    fn drop(&mut self) {
+        match self.writer.flush() {
+            Ok(()) => log::info!("Saved instruction trace to {}", self.path.display()),
+            Err(err) => log::error!("Error finalizing trace log: {}", self.write_error(err)),
+        }
+    }
+}
+
+/// Write all trace lines that the emulator has recorded since the last call to the trace log, if
+/// tracing is active.
+///
+/// Tracing stops if the file cannot be written or if the traced CPU is no longer being traced.
+pub(crate) fn write_pending_lines<Emulator: Debuggable>(
+    emulator: &mut Emulator,
+    trace_file: &mut Option<TraceFile>,
+) {
+    let Some(file) = trace_file else { return };
+
+    match emulator.debug_hooks_mut().drain_trace(file.cpu) {
+        Some(lines) => {
+            if let Err(err) = file.writer.write_all(lines.as_bytes()) {
+                log::error!("Stopping instruction trace: {}", file.write_error(err));
+                *trace_file = None;
+            }
+        }
+        None => {
+            log::warn!("Stopping instruction trace because tracing was disabled in the emulator");
+            *trace_file = None;
+        }
+    }
+}
+
+/// Write any remaining trace lines, then stop tracing in the emulator and close the trace log.
+pub(crate) fn stop<Emulator: Debuggable>(
+    emulator: &mut Emulator,
+    trace_file: &mut Option<TraceFile>,
+) {
+    write_pending_lines(emulator, trace_file);
+
+    if let Some(file) = trace_file.take() {
+        emulator.debug_hooks_mut().stop_trace(file.cpu);
+    }
+}
@@ -0,0 +1,145 @@
+//! VGM logging of sound chip register writes.
+//!
+//! The emulation cores buffer VGM commands in memory; this module periodically drains them into a
+//! file and fills in the VGM header once logging stops.
+
+use crate::mainloop::features::ConsoleFeatures;
+use smsgg_core::vgm;
+use smsgg_core::vgm::{VgmChips, VgmCommands, VgmLogger};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum VgmError {
+    #[error("VGM logging is not supported for this console")]
+    Unsupported,
+    #[error("I/O error creating VGM file '{path}': {source}")]
+    Create {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("I/O error writing VGM file '{path}': {source}")]
+    Write {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// A VGM file that is being written while logging is active. The header is written with the final
+/// lengths when the file is dropped.
+pub(crate) struct VgmFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    chips: VgmChips,
+    data_len: u64,
+    total_samples: u64,
+}
+
+impl VgmFile {
+    /// Start VGM logging in the emulator and create a VGM file at the given path.
+    pub(crate) fn start<Emulator: ConsoleFeatures>(
+        emulator: &mut Emulator,
+        path: PathBuf,
+    ) -> Result<Self, VgmError> {
+        if !emulator.start_vgm_logging() {
+            return Err(VgmError::Unsupported);
+        }
+        let chips = emulator.vgm_logger_mut().and_then(|logger| logger.chips());
+        let Some(chips) = chips else { return Err(VgmError::Unsupported) };
+
+        let file = File::create(&path)
+            .map_err(|source| VgmError::Create { path: path.display().to_string(), source })?;
+        let mut vgm_file =
+            Self { path, writer: BufWriter::new(file), chips, data_len: 0, total_samples: 0 };
+
+        // Placeholder header; lengths are filled in when logging stops
+        vgm_file
+            .writer
+            .write_all(&vgm::vgm_header(chips, 0, 0))
+            .map_err(|source| vgm_file.write_error(source))?;
+
+        log::info!("Started VGM logging to {}", vgm_file.path.display());
+
+        Ok(vgm_file)
+    }
+
+    fn append(&mut self, commands: &VgmCommands) -> Result<(), VgmError> {
+        self.writer.write_all(&commands.data).map_err(|source| self.write_error(source))?;
+        self.data_len += commands.data.len() as u64;
+        self.total_samples += commands.samples;
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        self.writer.write_all(&[vgm::VGM_END_OF_DATA])?;
+
+        // VGM offsets and lengths are 32-bit; a log would need to run for over a day to overflow
+        let data_len = u32::try_from(self.data_len + 1).unwrap_or(u32::MAX);
+        let total_samples = u32::try_from(self.total_samples).unwrap_or(u32::MAX);
+
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&vgm::vgm_header(self.chips, total_samples, data_len))?;
+        self.writer.flush()
+    }
+
+    fn write_error(&self, source: io::Error) -> VgmError {
+        VgmError::Write { path: self.path.display().to_string(), source }
+    }
+}
+
+impl Drop for VgmFile {
+    fn drop(&mut self) {
+        match self.finalize() {
+            Ok(()) => {
+                let seconds = self.total_samples as f64 / vgm::VGM_SAMPLE_RATE as f64;
+                log::info!("Saved {seconds:.2} seconds of VGM log to {}", self.path.display());
+            }
+            Err(err) => {
+                log::error!("Error finalizing VGM file: {}", self.write_error(err));
+            }
+        }
+    }
+}
+
+/// Write all commands that the emulator has logged since the last call to the VGM file, if logging
+/// is active.
+///
+/// Logging stops if the file cannot be written or if the emulator's logger is no longer active.
+pub(crate) fn write_pending_commands<Emulator: ConsoleFeatures>(
+    emulator: &mut Emulator,
+    vgm_file: &mut Option<VgmFile>,
+) {
+    let Some(file) = vgm_file else { return };
+
+    match emulator.vgm_logger_mut().and_then(VgmLogger::drain) {
+        Some(commands) => {
+            if let Err(err) = file.append(&commands) {
+                log::error!("Stopping VGM logging: {err}");
+                *vgm_file = None;
+            }
+        }
+        None => {
+            log::warn!("Stopping VGM logging because logging was disabled in the emulator");
+            *vgm_file = None;
+        }
+    }
+}
+
+/// Write any remaining logged commands, then stop logging in the emulator and finish the VGM file.
+pub(crate) fn stop<Emulator: ConsoleFeatures>(
+    emulator: &mut Emulator,
+    vgm_file: &mut Option<VgmFile>,
+) {
+    write_pending_commands(emulator, vgm_file);
+    *vgm_file = None;
+
+    if let Some(logger) = emulator.vgm_logger_mut() {
+        logger.stop();
+    }
+}
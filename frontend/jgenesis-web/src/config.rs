@@ -1,5 +1,5 @@
 use crate::SmsGgConsole;
-use genesis_core::input::GenesisControllerType;
+use genesis_core::input::{GenesisControllerType, GenesisPadType};
 use genesis_core::{GenesisAspectRatio, GenesisEmulatorConfig};
 use jgenesis_common::frontend::{PixelAspectRatio, TimingMode};
 use jgenesis_proc_macros::{EnumDisplay, EnumFromStr};
@@ -8,7 +8,7 @@ use jgenesis_renderer::config::{
     VSyncMode, WgpuBackend,
 };
 use smsgg_core::psg::PsgVersion;
-use smsgg_core::{SmsGgEmulatorConfig, SmsRegion, VdpVersion};
+use smsgg_core::{SmsControllerType, SmsGgEmulatorConfig, SmsRegion, VdpVersion};
 use snes_core::api::{SnesAspectRatio, SnesEmulatorConfig};
 use std::cell::RefCell;
 use std::collections::VecDeque;
@@ -139,6 +139,8 @@ impl SmsGgWebConfig {
             sms_crop_vertical_border: self.sms_crop_vertical_border,
             fm_sound_unit_enabled: self.fm_unit_enabled,
             overclock_z80: false,
+            p1_controller_type: SmsControllerType::default(),
+            p2_controller_type: SmsControllerType::default(),
         }
     }
 }
@@ -157,6 +159,7 @@ impl GenesisWebConfig {
         GenesisEmulatorConfig {
             p1_controller_type: GenesisControllerType::default(),
             p2_controller_type: GenesisControllerType::default(),
+            multitap_pad_types: [GenesisPadType::default(); 8],
             forced_timing_mode: None,
             forced_region: None,
             aspect_ratio: self.aspect_ratio,
@@ -303,6 +306,7 @@ impl Default for WebConfigRef {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EmulatorCommand {
     OpenFile,
+    OpenFileWithPatch,
     OpenSegaCd,
     Reset,
     UploadSaveFile,
@@ -326,6 +330,10 @@ impl EmulatorChannel {
         self.commands.borrow_mut().push_back(EmulatorCommand::OpenFile);
     }
 
+    pub fn request_open_file_with_patch(&self) {
+        self.commands.borrow_mut().push_back(EmulatorCommand::OpenFileWithPatch);
+    }
+
     pub fn request_open_sega_cd(&self) {
         self.commands.borrow_mut().push_back(EmulatorCommand::OpenSegaCd);
     }
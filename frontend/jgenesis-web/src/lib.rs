@@ -13,12 +13,13 @@ use genesis_core::{GenesisEmulator, GenesisInputs};
 use jgenesis_common::frontend::{
     AudioOutput, Color, EmulatorTrait, FrameSize, Renderer, SaveWriter, TickEffect, TimingMode,
 };
+use jgenesis_common::patch;
 use jgenesis_renderer::renderer::WgpuRenderer;
 use rfd::AsyncFileDialog;
 use segacd_core::api::{SegaCdEmulator, SegaCdEmulatorConfig};
 use smsgg_core::{SmsGgEmulator, SmsGgInputs};
 use snes_core::api::{CoprocessorRoms, SnesEmulator};
-use snes_core::input::SnesInputs;
+use snes_core::input::{SnesInputDevice, SnesInputs};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Debug, Display};
@@ -426,26 +427,29 @@ fn handle_snes_input(inputs: &mut SnesInputs, event: &WindowEvent<'_>) {
     };
     let pressed = *state == ElementState::Pressed;
 
+    // The web frontend only supports a gamepad in port 1
+    let SnesInputDevice::Controller(p1) = &mut inputs.p1 else { return };
+
     match keycode {
-        VirtualKeyCode::Up => inputs.p1.up = pressed,
-        VirtualKeyCode::Left => inputs.p1.left = pressed,
-        VirtualKeyCode::Right => inputs.p1.right = pressed,
-        VirtualKeyCode::Down => inputs.p1.down = pressed,
-        VirtualKeyCode::S => inputs.p1.a = pressed,
-        VirtualKeyCode::X => inputs.p1.b = pressed,
-        VirtualKeyCode::A => inputs.p1.x = pressed,
-        VirtualKeyCode::Z => inputs.p1.y = pressed,
-        VirtualKeyCode::D => inputs.p1.l = pressed,
-        VirtualKeyCode::C => inputs.p1.r = pressed,
-        VirtualKeyCode::Return => inputs.p1.start = pressed,
-        VirtualKeyCode::RShift => inputs.p1.select = pressed,
+        VirtualKeyCode::Up => p1.up = pressed,
+        VirtualKeyCode::Left => p1.left = pressed,
+        VirtualKeyCode::Right => p1.right = pressed,
+        VirtualKeyCode::Down => p1.down = pressed,
+        VirtualKeyCode::S => p1.a = pressed,
+        VirtualKeyCode::X => p1.b = pressed,
+        VirtualKeyCode::A => p1.x = pressed,
+        VirtualKeyCode::Z => p1.y = pressed,
+        VirtualKeyCode::D => p1.l = pressed,
+        VirtualKeyCode::C => p1.r = pressed,
+        VirtualKeyCode::Return => p1.start = pressed,
+        VirtualKeyCode::RShift => p1.select = pressed,
         _ => {}
     }
 }
 
 #[derive(Debug, Clone)]
 enum JgenesisUserEvent {
-    FileOpen { rom: Vec<u8>, bios: Option<Vec<u8>>, rom_file_name: String },
+    FileOpen { rom: Vec<u8>, bios: Option<Vec<u8>>, patch: Option<Vec<u8>>, rom_file_name: String },
     UploadSaveFile { contents_base64: String },
 }
 
@@ -512,7 +516,18 @@ fn run_event_loop(
     let event_loop_proxy = event_loop.create_proxy();
     event_loop.run(move |event, _, control_flow| match event {
         Event::UserEvent(user_event) => match user_event {
-            JgenesisUserEvent::FileOpen { rom, bios, rom_file_name } => {
+            JgenesisUserEvent::FileOpen { rom, bios, patch, rom_file_name } => {
+                let rom = match patch {
+                    Some(patch) => match patch::apply_patch(&rom, &patch) {
+                        Ok(patched_rom) => patched_rom,
+                        Err(err) => {
+                            js::alert(&format!("Error applying patch file: {err}"));
+                            return;
+                        }
+                    },
+                    None => rom,
+                };
+
                 audio_output.suspend();
 
                 let prev_file_name = Rc::clone(&save_writer.file_name);
@@ -574,7 +589,16 @@ fn run_event_loop(
             while let Some(command) = emulator_channel.pop_command() {
                 match command {
                     EmulatorCommand::OpenFile => {
-                        wasm_bindgen_futures::spawn_local(open_file(event_loop_proxy.clone()));
+                        wasm_bindgen_futures::spawn_local(open_file(
+                            event_loop_proxy.clone(),
+                            false,
+                        ));
+                    }
+                    EmulatorCommand::OpenFileWithPatch => {
+                        wasm_bindgen_futures::spawn_local(open_file(
+                            event_loop_proxy.clone(),
+                            true,
+                        ));
                     }
                     EmulatorCommand::OpenSegaCd => {
                         wasm_bindgen_futures::spawn_local(open_sega_cd(event_loop_proxy.clone()));
@@ -635,7 +659,7 @@ fn run_event_loop(
     });
 }
 
-async fn open_file(event_loop_proxy: EventLoopProxy<JgenesisUserEvent>) {
+async fn open_file(event_loop_proxy: EventLoopProxy<JgenesisUserEvent>, with_patch: bool) {
     let file = AsyncFileDialog::new()
         .add_filter("sms/gg/md", &["sms", "gg", "md", "bin", "sfc", "smc"])
         .pick_file()
@@ -645,10 +669,23 @@ async fn open_file(event_loop_proxy: EventLoopProxy<JgenesisUserEvent>) {
     let contents = file.read().await;
     let file_name = file.file_name();
 
+    let patch = if with_patch {
+        let patch_file = AsyncFileDialog::new()
+            .set_title("Patch file")
+            .add_filter("ips/bps/ups", &patch::PATCH_EXTENSIONS)
+            .pick_file()
+            .await;
+        let Some(patch_file) = patch_file else { return };
+        Some(patch_file.read().await)
+    } else {
+        None
+    };
+
     event_loop_proxy
         .send_event(JgenesisUserEvent::FileOpen {
             rom: contents,
             bios: None,
+            patch,
             rom_file_name: file_name,
         })
         .expect("Unable to send file opened event");
@@ -678,6 +715,7 @@ async fn open_sega_cd(event_loop_proxy: EventLoopProxy<JgenesisUserEvent>) {
         .send_event(JgenesisUserEvent::FileOpen {
             rom: chd_contents,
             bios: Some(bios_contents),
+            patch: None,
             rom_file_name: chd_file_name,
         })
         .expect("Unable to send Sega CD BIOS/CHD opened event");
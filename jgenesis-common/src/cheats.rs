@@ -0,0 +1,143 @@
+//! Shared cheat code types.
+//!
+//! Each emulation core decodes its own code formats (Game Genie, Pro Action Replay, etc.) into a
+//! list of byte-sized patches. ROM patches substitute the value returned by CPU reads from a given
+//! address, optionally only when the original value matches a compare value. RAM patches are
+//! written to memory once per frame.
+
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomPatch {
+    pub address: u32,
+    pub value: u8,
+    pub compare: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RamPatch {
+    pub address: u32,
+    pub value: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheatPatch {
+    Rom(RomPatch),
+    Ram(RamPatch),
+}
+
+impl CheatPatch {
+    #[must_use]
+    pub fn rom(address: u32, value: u8, compare: Option<u8>) -> Self {
+        Self::Rom(RomPatch { address, value, compare })
+    }
+
+    #[must_use]
+    pub fn ram(address: u32, value: u8) -> Self {
+        Self::Ram(RamPatch { address, value })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CheatError {
+    #[error("Unrecognized cheat code format: '{0}'")]
+    UnrecognizedFormat(String),
+    #[error("Invalid character '{ch}' in cheat code '{code}'")]
+    InvalidCharacter { code: String, ch: char },
+}
+
+pub type CheatResult<T> = Result<T, CheatError>;
+
+/// The set of active patches for an emulator instance. Cheats are frontend configuration rather
+/// than emulated state, so they are not persisted in save states; cores carry them across state
+/// loads in `take_rom_from`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, FakeEncode, FakeDecode)]
+pub struct CheatSet {
+    rom_patches: Vec<RomPatch>,
+    ram_patches: Vec<RamPatch>,
+}
+
+impl CheatSet {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn from_patches<I: IntoIterator<Item = CheatPatch>>(patches: I) -> Self {
+        let mut cheat_set = Self::new();
+        for patch in patches {
+            match patch {
+                CheatPatch::Rom(patch) => cheat_set.rom_patches.push(patch),
+                CheatPatch::Ram(patch) => cheat_set.ram_patches.push(patch),
+            }
+        }
+        cheat_set
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rom_patches.is_empty() && self.ram_patches.is_empty()
+    }
+
+    /// Apply any ROM patches for the given address to a value read by the CPU.
+    #[inline]
+    #[must_use]
+    pub fn apply_rom_read(&self, address: u32, value: u8) -> u8 {
+        self.rom_patches
+            .iter()
+            .find(|patch| {
+                patch.address == address && patch.compare.is_none_or(|compare| compare == value)
+            })
+            .map_or(value, |patch| patch.value)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn ram_patches(&self) -> &[RamPatch] {
+        &self.ram_patches
+    }
+}
+
+/// Parse a string of hex digits, e.g. the address or value portion of a code.
+///
+/// # Errors
+///
+/// Returns an error if `digits` contains any characters that are not hex digits. `code` is only
+/// used for the error message.
+pub fn parse_hex(code: &str, digits: &str) -> CheatResult<u32> {
+    if let Some(ch) = digits.chars().find(|ch| !ch.is_ascii_hexdigit()) {
+        return Err(CheatError::InvalidCharacter { code: code.into(), ch });
+    }
+
+    u32::from_str_radix(digits, 16).map_err(|_| CheatError::UnrecognizedFormat(code.into()))
+}
+
+/// Split a raw `address:value` code into its address and value parts. `=` is also accepted as
+/// the separator.
+#[must_use]
+pub fn split_raw_code(code: &str) -> Option<(&str, &str)> {
+    code.split_once(':').or_else(|| code.split_once('='))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cheats_not_encoded() {
+        let cheats = CheatSet::from_patches([
+            CheatPatch::rom(0x1234, 0x56, Some(0x78)),
+            CheatPatch::ram(0xFF00, 0x99),
+        ]);
+
+        let config = bincode::config::standard();
+        let bytes = bincode::encode_to_vec(&cheats, config).unwrap();
+        assert!(bytes.is_empty());
+
+        let (decoded, _): (CheatSet, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+        assert!(decoded.is_empty());
+    }
+}
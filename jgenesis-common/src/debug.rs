@@ -0,0 +1,656 @@
+//! CPU debugging support shared between emulation cores and frontends.
+//!
+//! Cores that support debugging own a [`DebugHooks`] instance and consult it before each
+//! instruction (breakpoints and single stepping) and on each memory access while watchpoints are
+//! set. When a hook fires, the core records a [`StopEvent`] and the frontend stops ticking the
+//! emulator until the debugger resumes execution.
+//!
+//! CPUs are identified by their index into [`Debuggable::debug_cpus`].
+//!
+//! Hooks can also record an instruction trace per CPU. While tracing is active, cores report each
+//! instruction along with the CPU's registers, and the frontend periodically drains the buffered
+//! trace lines into a file.
+
+use jgenesis_proc_macros::{FakeDecode, FakeEncode};
+use std::fmt::Write;
+use std::mem;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuArch {
+    M68000,
+    Z80,
+    Mos6502,
+    Wdc65816,
+    Spc700,
+    Sm83,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Register {
+    pub name: &'static str,
+    pub bits: u32,
+}
+
+macro_rules! registers {
+    ($($name:literal: $bits:literal),* $(,)?) => {
+        &[$(Register { name: $name, bits: $bits }),*]
+    };
+}
+
+impl CpuArch {
+    /// Registers in the order used by [`Debuggable::read_registers`]. For the M68000 and Z80,
+    /// this matches the order that GDB uses.
+    #[must_use]
+    pub fn registers(self) -> &'static [Register] {
+        match self {
+            Self::M68000 => registers!(
+                "d0": 32, "d1": 32, "d2": 32, "d3": 32, "d4": 32, "d5": 32, "d6": 32, "d7": 32,
+                "a0": 32, "a1": 32, "a2": 32, "a3": 32, "a4": 32, "a5": 32, "fp": 32, "sp": 32,
+                "ps": 32, "pc": 32,
+            ),
+            Self::Z80 => registers!(
+                "af": 16, "bc": 16, "de": 16, "hl": 16, "sp": 16, "pc": 16, "ix": 16, "iy": 16,
+                "af'": 16, "bc'": 16, "de'": 16, "hl'": 16, "ir": 16,
+            ),
+            Self::Mos6502 => registers!("a": 8, "x": 8, "y": 8, "s": 8, "p": 8, "pc": 16),
+            Self::Wdc65816 => registers!(
+                "a": 16, "x": 16, "y": 16, "s": 16, "d": 16, "pc": 16, "pbr": 8, "dbr": 8, "p": 8,
+                "e": 1,
+            ),
+            Self::Spc700 => registers!("a": 8, "x": 8, "y": 8, "sp": 8, "psw": 8, "pc": 16),
+            Self::Sm83 => registers!("af": 16, "bc": 16, "de": 16, "hl": 16, "sp": 16, "pc": 16),
+        }
+    }
+
+    #[must_use]
+    pub fn address_mask(self) -> u32 {
+        match self {
+            Self::M68000 | Self::Wdc65816 => 0x00FF_FFFF,
+            Self::Z80 | Self::Mos6502 | Self::Spc700 | Self::Sm83 => 0xFFFF,
+        }
+    }
+
+    /// The full address of the next instruction, given registers in the order of
+    /// [`Self::registers`].
+    #[must_use]
+    pub fn pc(self, registers: &[u32]) -> u32 {
+        match self {
+            Self::M68000 => registers[17],
+            // PC is only the low 16 bits of the address; the bank is in PBR
+            Self::Wdc65816 => (registers[6] << 16) | registers[5],
+            Self::Z80 | Self::Mos6502 | Self::Spc700 | Self::Sm83 => registers[5],
+        }
+    }
+
+    /// If the instruction starting with the given bytes is a subroutine call, returns the length
+    /// of the instruction in bytes. This is used to step over calls.
+    #[must_use]
+    pub fn call_length(self, bytes: [u8; 2]) -> Option<u32> {
+        let opcode = bytes[0];
+        match self {
+            Self::M68000 => {
+                let opcode = u16::from_be_bytes(bytes);
+                match opcode {
+                    // BSR; an 8-bit displacement of 0 indicates a 16-bit displacement word
+                    0x6100..=0x61FF => Some(if opcode & 0xFF == 0 { 4 } else { 2 }),
+                    // TRAP
+                    0x4E40..=0x4E4F => Some(2),
+                    // JSR; length depends on the addressing mode
+                    0x4E80..=0x4EBF => match ((opcode >> 3) & 7, opcode & 7) {
+                        (2, _) => Some(2),
+                        (5 | 6, _) | (7, 0 | 2 | 3) => Some(4),
+                        (7, 1) => Some(6),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            // CALL nn, CALL cc,nn, RST n
+            Self::Z80 | Self::Sm83 if opcode == 0xCD => Some(3),
+            Self::Z80 if opcode & 0xC7 == 0xC4 => Some(3),
+            Self::Sm83 if matches!(opcode, 0xC4 | 0xCC | 0xD4 | 0xDC) => Some(3),
+            Self::Z80 | Self::Sm83 if opcode & 0xC7 == 0xC7 => Some(1),
+            // JSR abs
+            Self::Mos6502 | Self::Wdc65816 if opcode == 0x20 => Some(3),
+            // JSR (abs,X), JSL long
+            Self::Wdc65816 if opcode == 0xFC => Some(3),
+            Self::Wdc65816 if opcode == 0x22 => Some(4),
+            // CALL !abs, PCALL up, TCALL n
+            Self::Spc700 if opcode == 0x3F => Some(3),
+            Self::Spc700 if opcode == 0x4F => Some(2),
+            Self::Spc700 if opcode & 0x0F == 0x01 => Some(1),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugCpu {
+    pub name: &'static str,
+    pub arch: CpuArch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchpointKind {
+    Read,
+    Write,
+    Access,
+}
+
+impl WatchpointKind {
+    fn matches(self, write: bool) -> bool {
+        match self {
+            Self::Read => !write,
+            Self::Write => write,
+            Self::Access => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub address: u32,
+    pub len: u32,
+    pub kind: WatchpointKind,
+}
+
+impl Watchpoint {
+    fn overlaps(&self, address: u32, len: u32) -> bool {
+        address < self.address.wrapping_add(self.len) && self.address < address.wrapping_add(len)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint,
+    Watchpoint { kind: WatchpointKind, address: u32 },
+    Step,
+    Interrupt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopEvent {
+    pub cpu: usize,
+    pub reason: StopReason,
+}
+
+/// An inclusive range of instruction addresses to include in a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl TraceRange {
+    #[must_use]
+    pub fn contains(&self, address: u32) -> bool {
+        (self.start..=self.end).contains(&address)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid trace range '{0}'; expected hex addresses as START-END, e.g. 8000-FFFF")]
+pub struct ParseTraceRangeError(String);
+
+impl FromStr for TraceRange {
+    type Err = ParseTraceRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_address = |address: &str| {
+            let address = address.trim();
+            let digits = address.strip_prefix('$').or_else(|| address.strip_prefix("0x"));
+            u32::from_str_radix(digits.unwrap_or(address), 16).ok()
+        };
+
+        let err = || ParseTraceRangeError(s.into());
+        let (start, end) = s.split_once('-').ok_or_else(err)?;
+        let (Some(start), Some(end)) = (parse_address(start), parse_address(end)) else {
+            return Err(err());
+        };
+        if start > end {
+            return Err(err());
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct TraceState {
+    // Instructions are only traced if they start within one of these ranges; empty means all
+    ranges: Vec<TraceRange>,
+    cycles: u64,
+    buffer: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CpuHooks {
+    breakpoints: Vec<u32>,
+    watchpoints: Vec<Watchpoint>,
+    trace: Option<Box<TraceState>>,
+    // Set on resume so that a CPU stopped at a breakpoint can execute the instruction at that
+    // address instead of immediately stopping again
+    resuming: bool,
+}
+
+/// Debugger state for every CPU in a core. Hooks are not persisted in save states; cores carry
+/// them across state loads in `take_rom_from`.
+#[derive(Debug, Clone, Default, FakeEncode, FakeDecode)]
+pub struct DebugHooks {
+    cpus: Vec<CpuHooks>,
+    step: Option<usize>,
+    run_to: Option<(usize, u32)>,
+    stop: Option<StopEvent>,
+}
+
+impl DebugHooks {
+    #[must_use]
+    pub fn new(num_cpus: usize) -> Self {
+        Self { cpus: vec![CpuHooks::default(); num_cpus], step: None, run_to: None, stop: None }
+    }
+
+    /// Whether the core needs to check for breakpoints, single stepping, or tracing on the given
+    /// CPU.
+    #[inline]
+    #[must_use]
+    pub fn checks_execution(&self, cpu: usize) -> bool {
+        self.stop.is_some()
+            || self.step.is_some()
+            || self.run_to.is_some()
+            || self
+                .cpus
+                .get(cpu)
+                .is_some_and(|hooks| !hooks.breakpoints.is_empty() || hooks.trace.is_some())
+    }
+
+    /// Whether the core needs to report memory accesses on the given CPU.
+    #[inline]
+    #[must_use]
+    pub fn checks_accesses(&self, cpu: usize) -> bool {
+        self.cpus.get(cpu).is_some_and(|hooks| !hooks.watchpoints.is_empty())
+    }
+
+    #[must_use]
+    pub fn breakpoints(&self, cpu: usize) -> &[u32] {
+        self.cpus.get(cpu).map_or(&[], |hooks| &hooks.breakpoints)
+    }
+
+    pub fn add_breakpoint(&mut self, cpu: usize, address: u32) {
+        let Some(hooks) = self.cpus.get_mut(cpu) else { return };
+        if !hooks.breakpoints.contains(&address) {
+            hooks.breakpoints.push(address);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, cpu: usize, address: u32) {
+        if let Some(hooks) = self.cpus.get_mut(cpu) {
+            hooks.breakpoints.retain(|&breakpoint| breakpoint != address);
+        }
+    }
+
+    #[must_use]
+    pub fn watchpoints(&self, cpu: usize) -> &[Watchpoint] {
+        self.cpus.get(cpu).map_or(&[], |hooks| &hooks.watchpoints)
+    }
+
+    pub fn add_watchpoint(&mut self, cpu: usize, watchpoint: Watchpoint) {
+        let Some(hooks) = self.cpus.get_mut(cpu) else { return };
+        if !hooks.watchpoints.contains(&watchpoint) {
+            hooks.watchpoints.push(watchpoint);
+        }
+    }
+
+    pub fn remove_watchpoint(&mut self, cpu: usize, watchpoint: Watchpoint) {
+        if let Some(hooks) = self.cpus.get_mut(cpu) {
+            hooks.watchpoints.retain(|&existing| existing != watchpoint);
+        }
+    }
+
+    /// Remove all breakpoints and watchpoints and cancel any pending step or stop. Traces are not
+    /// affected.
+    pub fn clear(&mut self) {
+        let traces: Vec<_> =
+            self.cpus.iter_mut().map(|hooks| mem::take(&mut hooks.trace)).collect();
+        *self = Self::new(self.cpus.len());
+        for (hooks, trace) in self.cpus.iter_mut().zip(traces) {
+            hooks.trace = trace;
+        }
+    }
+
+    /// Start tracing instructions on the given CPU, discarding any trace in progress. If `ranges`
+    /// is not empty, only instructions that start within one of the ranges are traced.
+    pub fn start_trace(&mut self, cpu: usize, ranges: Vec<TraceRange>) {
+        if let Some(hooks) = self.cpus.get_mut(cpu) {
+            hooks.trace = Some(Box::new(TraceState { ranges, cycles: 0, buffer: String::new() }));
+        }
+    }
+
+    /// Stop tracing on the given CPU. Any trace lines that have not been drained are discarded.
+    pub fn stop_trace(&mut self, cpu: usize) {
+        if let Some(hooks) = self.cpus.get_mut(cpu) {
+            hooks.trace = None;
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_tracing(&self, cpu: usize) -> bool {
+        self.cpus.get(cpu).is_some_and(|hooks| hooks.trace.is_some())
+    }
+
+    /// Advance the cycle counter reported in the given CPU's trace. Does nothing if the CPU is not
+    /// being traced.
+    #[inline]
+    pub fn add_trace_cycles(&mut self, cpu: usize, cycles: u64) {
+        if let Some(trace) = self.cpus.get_mut(cpu).and_then(|hooks| hooks.trace.as_mut()) {
+            trace.cycles += cycles;
+        }
+    }
+
+    /// Whether the instruction at `pc` should be traced on the given CPU.
+    #[must_use]
+    pub fn traces(&self, cpu: usize, pc: u32) -> bool {
+        self.cpus.get(cpu).and_then(|hooks| hooks.trace.as_ref()).is_some_and(|trace| {
+            trace.ranges.is_empty() || trace.ranges.iter().any(|range| range.contains(pc))
+        })
+    }
+
+    /// Append a line to the given CPU's trace containing the cycle counter, the instruction
+    /// address and disassembly, and the register values before the instruction executes.
+    /// `registers` must be in the order of [`CpuArch::registers`].
+    pub fn record_trace(
+        &mut self,
+        cpu: usize,
+        arch: CpuArch,
+        disassembly: &str,
+        registers: &[u32],
+    ) {
+        let Some(trace) = self.cpus.get_mut(cpu).and_then(|hooks| hooks.trace.as_mut()) else {
+            return;
+        };
+
+        let pc_width = (32 - arch.address_mask().leading_zeros()).div_ceil(4) as usize;
+        let pc = arch.pc(registers);
+        let buffer = &mut trace.buffer;
+        write!(buffer, "{:>12}  {pc:0pc_width$X}  {disassembly:<28}", trace.cycles).unwrap();
+        for (register, &value) in arch.registers().iter().zip(registers) {
+            let width = register.bits.div_ceil(4) as usize;
+            write!(buffer, " {}={value:0width$X}", register.name).unwrap();
+        }
+        buffer.push('\n');
+    }
+
+    /// Take all trace lines that have been recorded for the given CPU since the last call. Returns
+    /// `None` if the CPU is not being traced.
+    pub fn drain_trace(&mut self, cpu: usize) -> Option<String> {
+        let trace = self.cpus.get_mut(cpu)?.trace.as_mut()?;
+        Some(mem::take(&mut trace.buffer))
+    }
+
+    #[must_use]
+    pub fn stop_event(&self) -> Option<StopEvent> {
+        self.stop
+    }
+
+    /// Clear the current stop event (if any) and allow every CPU to execute at least one
+    /// instruction before breakpoints are checked again.
+    pub fn resume(&mut self) {
+        self.stop = None;
+        self.step = None;
+        self.run_to = None;
+        for hooks in &mut self.cpus {
+            hooks.resuming = true;
+        }
+    }
+
+    /// Resume execution and stop again after the given CPU executes one instruction.
+    pub fn step(&mut self, cpu: usize) {
+        self.resume();
+        self.step = Some(cpu);
+    }
+
+    /// Resume execution and stop when the given CPU reaches `address`.
+    pub fn run_to(&mut self, cpu: usize, address: u32) {
+        self.resume();
+        self.run_to = Some((cpu, address));
+    }
+
+    /// Stop execution at the next instruction boundary, e.g. because the debugger requested a
+    /// break. The stop is reported as occurring on the given CPU.
+    pub fn interrupt(&mut self, cpu: usize) {
+        self.step = None;
+        self.run_to = None;
+        self.stop.get_or_insert(StopEvent { cpu, reason: StopReason::Interrupt });
+    }
+
+    /// Check whether the given CPU should stop before executing the instruction at `pc`. Cores
+    /// should call this before every instruction when [`Self::checks_execution`] returns true,
+    /// and should not execute the instruction if this returns true.
+    #[must_use]
+    pub fn check_execute(&mut self, cpu: usize, pc: u32) -> bool {
+        if self.stop.is_some() {
+            return true;
+        }
+
+        let Some(hooks) = self.cpus.get_mut(cpu) else { return false };
+        if std::mem::take(&mut hooks.resuming) {
+            return false;
+        }
+
+        let reason = if self.step == Some(cpu) {
+            StopReason::Step
+        } else if hooks.breakpoints.contains(&pc) || self.run_to == Some((cpu, pc)) {
+            StopReason::Breakpoint
+        } else {
+            return false;
+        };
+
+        self.step = None;
+        self.run_to = None;
+        self.stop = Some(StopEvent { cpu, reason });
+        true
+    }
+
+    /// Record a memory access of `len` bytes starting at `address`. If the access hits a
+    /// watchpoint, the emulator will stop after the current instruction completes.
+    pub fn check_access(&mut self, cpu: usize, address: u32, len: u32, write: bool) {
+        if self.stop.is_some() {
+            return;
+        }
+
+        let Some(hooks) = self.cpus.get(cpu) else { return };
+        if let Some(watchpoint) = hooks
+            .watchpoints
+            .iter()
+            .find(|watchpoint| watchpoint.kind.matches(write) && watchpoint.overlaps(address, len))
+        {
+            self.step = None;
+            self.run_to = None;
+            self.stop = Some(StopEvent {
+                cpu,
+                reason: StopReason::Watchpoint { kind: watchpoint.kind, address },
+            });
+        }
+    }
+}
+
+/// Emulators that expose CPU state to debuggers.
+pub trait Debuggable {
+    fn debug_cpus(&self) -> &'static [DebugCpu];
+
+    fn debug_hooks(&self) -> &DebugHooks;
+
+    fn debug_hooks_mut(&mut self) -> &mut DebugHooks;
+
+    /// Read all registers for the given CPU, in the order of [`CpuArch::registers`].
+    fn read_registers(&self, cpu: usize) -> Vec<u32>;
+
+    /// Overwrite all registers for the given CPU. `registers` is in the same order as
+    /// [`Self::read_registers`].
+    fn write_registers(&mut self, cpu: usize, registers: &[u32]);
+
+    /// Read a byte from the given CPU's address space. Reads should avoid side effects where
+    /// possible, e.g. by reporting registers that change state when read as open bus.
+    fn read_memory(&mut self, cpu: usize, address: u32) -> u8;
+
+    fn write_memory(&mut self, cpu: usize, address: u32, value: u8);
+
+    /// Disassemble the instruction at `address`, returning its text and its length in bytes.
+    /// Cores without a disassembler for the given CPU show raw data instead.
+    fn disassemble(&mut self, cpu: usize, address: u32) -> (String, u32) {
+        if self.debug_cpus()[cpu].arch == CpuArch::M68000 {
+            let word = u16::from_be_bytes([
+                self.read_memory(cpu, address),
+                self.read_memory(cpu, address.wrapping_add(1)),
+            ]);
+            return (format!("dc.w ${word:04X}"), 2);
+        }
+
+        (format!("db ${:02X}", self.read_memory(cpu, address)), 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoint_resume() {
+        let mut hooks = DebugHooks::new(2);
+        hooks.add_breakpoint(0, 0x200);
+
+        assert!(!hooks.check_execute(0, 0x100));
+        assert!(!hooks.check_execute(1, 0x200));
+        assert!(hooks.check_execute(0, 0x200));
+        assert_eq!(hooks.stop_event(), Some(StopEvent { cpu: 0, reason: StopReason::Breakpoint }));
+
+        // Resuming should execute the instruction at the breakpoint instead of stopping again
+        hooks.resume();
+        assert!(!hooks.check_execute(0, 0x200));
+        assert!(!hooks.check_execute(0, 0x202));
+        assert!(hooks.check_execute(0, 0x200));
+    }
+
+    #[test]
+    fn hooks_not_encoded() {
+        let mut hooks = DebugHooks::new(2);
+        hooks.add_breakpoint(0, 0x200);
+        hooks.start_trace(1, vec![]);
+
+        let config = bincode::config::standard();
+        let bytes = bincode::encode_to_vec(&hooks, config).unwrap();
+        assert!(bytes.is_empty());
+
+        let (decoded, _): (DebugHooks, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+        assert!(decoded.breakpoints(0).is_empty());
+        assert!(!decoded.is_tracing(1));
+    }
+
+    #[test]
+    fn step() {
+        let mut hooks = DebugHooks::new(2);
+        hooks.step(1);
+
+        assert!(!hooks.check_execute(0, 0x100));
+        assert!(!hooks.check_execute(1, 0x10));
+        assert!(!hooks.check_execute(0, 0x102));
+        assert!(hooks.check_execute(1, 0x11));
+        assert_eq!(hooks.stop_event(), Some(StopEvent { cpu: 1, reason: StopReason::Step }));
+    }
+
+    #[test]
+    fn run_to() {
+        let mut hooks = DebugHooks::new(2);
+        hooks.add_breakpoint(0, 0x100);
+        assert!(hooks.check_execute(0, 0x100));
+
+        hooks.run_to(1, 0x40);
+        assert!(!hooks.check_execute(0, 0x100));
+        assert!(!hooks.check_execute(1, 0x40));
+        assert!(!hooks.check_execute(0, 0x40));
+        assert!(hooks.check_execute(1, 0x40));
+
+        // Run-to targets only fire once
+        hooks.resume();
+        assert!(!hooks.check_execute(1, 0x40));
+        assert!(!hooks.check_execute(1, 0x40));
+    }
+
+    #[test]
+    fn call_length() {
+        assert_eq!(CpuArch::M68000.call_length([0x61, 0x00]), Some(4));
+        assert_eq!(CpuArch::M68000.call_length([0x61, 0x10]), Some(2));
+        assert_eq!(CpuArch::M68000.call_length([0x4E, 0xB9]), Some(6));
+        assert_eq!(CpuArch::M68000.call_length([0x4E, 0x90]), Some(2));
+        assert_eq!(CpuArch::M68000.call_length([0x4E, 0x75]), None);
+        assert_eq!(CpuArch::Z80.call_length([0xDC, 0x00]), Some(3));
+        assert_eq!(CpuArch::Z80.call_length([0xFF, 0x00]), Some(1));
+        assert_eq!(CpuArch::Sm83.call_length([0xE4, 0x00]), None);
+        assert_eq!(CpuArch::Wdc65816.call_length([0x22, 0x00]), Some(4));
+        assert_eq!(CpuArch::Spc700.call_length([0x81, 0x00]), Some(1));
+    }
+
+    #[test]
+    fn trace_range_from_str() {
+        assert_eq!("8000-FFFF".parse(), Ok(TraceRange { start: 0x8000, end: 0xFFFF }));
+        assert_eq!("$FF0000 - $FFFFFF".parse(), Ok(TraceRange { start: 0xFF0000, end: 0xFFFFFF }));
+        assert!("8000".parse::<TraceRange>().is_err());
+        assert!("FFFF-8000".parse::<TraceRange>().is_err());
+    }
+
+    #[test]
+    fn trace() {
+        let mut hooks = DebugHooks::new(2);
+        assert!(!hooks.checks_execution(1));
+
+        hooks.start_trace(1, vec![TraceRange { start: 0x8000, end: 0x80FF }]);
+        assert!(hooks.checks_execution(1));
+        assert!(!hooks.checks_execution(0));
+        assert!(hooks.traces(1, 0x8010));
+        assert!(!hooks.traces(1, 0x8100));
+        assert!(!hooks.traces(0, 0x8010));
+
+        hooks.add_trace_cycles(1, 12);
+        hooks.record_trace(1, CpuArch::Spc700, "MOV A, #$12", &[0x12, 0, 0, 0xEF, 0x02, 0x8010]);
+        let lines = hooks.drain_trace(1).unwrap();
+        assert_eq!(
+            lines,
+            format!(
+                "{:>12}  8010  {:<28} a=12 x=00 y=00 sp=EF psw=02 pc=8010\n",
+                12, "MOV A, #$12"
+            )
+        );
+        assert_eq!(hooks.drain_trace(1).as_deref(), Some(""));
+
+        // Clearing breakpoints should not stop the trace
+        hooks.clear();
+        assert!(hooks.is_tracing(1));
+
+        hooks.stop_trace(1);
+        assert_eq!(hooks.drain_trace(1), None);
+    }
+
+    #[test]
+    fn watchpoint() {
+        let mut hooks = DebugHooks::new(1);
+        hooks.add_watchpoint(
+            0,
+            Watchpoint { address: 0xFF0010, len: 2, kind: WatchpointKind::Write },
+        );
+
+        hooks.check_access(0, 0xFF0010, 2, false);
+        hooks.check_access(0, 0xFF000E, 2, true);
+        assert_eq!(hooks.stop_event(), None);
+
+        hooks.check_access(0, 0xFF000E, 4, true);
+        assert_eq!(
+            hooks.stop_event(),
+            Some(StopEvent {
+                cpu: 0,
+                reason: StopReason::Watchpoint { kind: WatchpointKind::Write, address: 0xFF000E }
+            })
+        );
+    }
+}
@@ -1,5 +1,23 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use jgenesis_proc_macros::EnumAll;
+
+/// Player number. Consoles without multitap support only use players one and two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumAll)]
 pub enum Player {
     One,
     Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+}
+
+impl Player {
+    /// Zero-based player index, e.g. 2 for player three.
+    #[inline]
+    #[must_use]
+    pub fn index(self) -> usize {
+        self as usize
+    }
 }
@@ -1,5 +1,20 @@
 pub mod audio;
+pub mod cheats;
+pub mod debug;
 pub mod frontend;
 pub mod input;
 pub mod num;
+pub mod patch;
 pub mod timeutils;
+
+/// Bincode configuration shared by native frontends for save files, save states, and other
+/// serialized emulator data.
+#[macro_export]
+macro_rules! bincode_config {
+    () => {
+        bincode::config::standard()
+            .with_little_endian()
+            .with_fixed_int_encoding()
+            .with_limit::<{ 100 * 1024 * 1024 }>()
+    };
+}
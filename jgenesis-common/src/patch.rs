@@ -0,0 +1,519 @@
+//! ROM soft-patching in IPS, BPS, and UPS formats
+//!
+//! The patch format is detected from the patch file's header rather than its file extension.
+//! BPS and UPS patches contain CRC32 checksums of the source ROM, the patched ROM, and the patch
+//! itself, and all three are validated.
+
+use crc::Crc;
+use thiserror::Error;
+
+const CRC32: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+const IPS_HEADER: &[u8] = b"PATCH";
+const IPS_FOOTER: &[u8] = b"EOF";
+const BPS_HEADER: &[u8] = b"BPS1";
+const UPS_HEADER: &[u8] = b"UPS1";
+
+// Source CRC32 + target CRC32 + patch CRC32
+const CHECKSUM_FOOTER_LEN: usize = 12;
+
+/// File extensions that are recognized as patch files, in order of preference.
+pub const PATCH_EXTENSIONS: [&str; 3] = ["ips", "bps", "ups"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchFormat {
+    Ips,
+    Bps,
+    Ups,
+}
+
+impl PatchFormat {
+    #[must_use]
+    pub fn detect(patch: &[u8]) -> Option<Self> {
+        if patch.starts_with(IPS_HEADER) {
+            Some(Self::Ips)
+        } else if patch.starts_with(BPS_HEADER) {
+            Some(Self::Bps)
+        } else if patch.starts_with(UPS_HEADER) {
+            Some(Self::Ups)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PatchError {
+    #[error("Unrecognized patch format; expected IPS, BPS, or UPS")]
+    UnknownFormat,
+    #[error("{format:?} patch is truncated or malformed")]
+    Malformed { format: PatchFormat },
+    #[error("{format:?} patch is corrupt; expected patch CRC32 {expected:08X}, was {actual:08X}")]
+    PatchChecksum { format: PatchFormat, expected: u32, actual: u32 },
+    #[error("{format:?} patch is for a different ROM; expected ROM size {expected}, was {actual}")]
+    SourceSize { format: PatchFormat, expected: usize, actual: usize },
+    #[error(
+        "{format:?} patch is for a different ROM; expected ROM CRC32 {expected:08X}, was {actual:08X}"
+    )]
+    SourceChecksum { format: PatchFormat, expected: u32, actual: u32 },
+    #[error("Patched ROM failed validation; expected CRC32 {expected:08X}, was {actual:08X}")]
+    TargetChecksum { expected: u32, actual: u32 },
+}
+
+pub type PatchResult<T> = Result<T, PatchError>;
+
+/// Apply an IPS, BPS, or UPS patch to the given ROM, returning the patched ROM.
+///
+/// # Errors
+///
+/// Returns an error if the patch format is not recognized, if the patch is malformed, or if any
+/// of the checksums in a BPS or UPS patch do not match.
+pub fn apply_patch(rom: &[u8], patch: &[u8]) -> PatchResult<Vec<u8>> {
+    match PatchFormat::detect(patch) {
+        Some(PatchFormat::Ips) => apply_ips(rom, patch),
+        Some(PatchFormat::Bps) => apply_bps(rom, patch),
+        Some(PatchFormat::Ups) => apply_ups(rom, patch),
+        None => Err(PatchError::UnknownFormat),
+    }
+}
+
+struct PatchReader<'a> {
+    format: PatchFormat,
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(format: PatchFormat, data: &'a [u8], position: usize) -> Self {
+        Self { format, data, position }
+    }
+
+    fn malformed(&self) -> PatchError {
+        PatchError::Malformed { format: self.format }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> PatchResult<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + len)
+            .ok_or(PatchError::Malformed { format: self.format })?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> PatchResult<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_be(&mut self, len: usize) -> PatchResult<usize> {
+        Ok(self.read_bytes(len)?.iter().fold(0, |acc, &byte| (acc << 8) | usize::from(byte)))
+    }
+
+    // BPS and UPS use the same variable-length integer encoding: 7 bits per byte, least
+    // significant first, with the high bit marking the final byte and an implicit +1 added to
+    // each continuation so that every value has exactly one encoding
+    fn read_varint(&mut self) -> PatchResult<usize> {
+        let mut value = 0_usize;
+        let mut shift = 1_usize;
+        loop {
+            let byte = self.read_u8()?;
+            value = usize::from(byte & 0x7F)
+                .checked_mul(shift)
+                .and_then(|n| value.checked_add(n))
+                .ok_or_else(|| self.malformed())?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+
+            shift = shift.checked_shl(7).ok_or_else(|| self.malformed())?;
+            value = value.checked_add(shift).ok_or_else(|| self.malformed())?;
+        }
+    }
+}
+
+fn apply_ips(rom: &[u8], patch: &[u8]) -> PatchResult<Vec<u8>> {
+    let mut reader = PatchReader::new(PatchFormat::Ips, patch, IPS_HEADER.len());
+    let mut output = rom.to_vec();
+
+    loop {
+        if reader.data[reader.position..].starts_with(IPS_FOOTER) {
+            reader.position += IPS_FOOTER.len();
+            break;
+        }
+
+        let offset = reader.read_be(3)?;
+        let len = reader.read_be(2)?;
+
+        // A length of 0 indicates a run-length encoded record
+        let (len, fill) = if len == 0 {
+            let run_len = reader.read_be(2)?;
+            (run_len, Some(reader.read_u8()?))
+        } else {
+            (len, None)
+        };
+
+        if output.len() < offset + len {
+            output.resize(offset + len, 0);
+        }
+
+        match fill {
+            Some(fill) => output[offset..offset + len].fill(fill),
+            None => output[offset..offset + len].copy_from_slice(reader.read_bytes(len)?),
+        }
+    }
+
+    // Some IPS patches append a 3-byte size to truncate the output to
+    if let Ok(truncate_len) = reader.read_be(3) {
+        output.truncate(truncate_len);
+    }
+
+    Ok(output)
+}
+
+struct ChecksumFooter {
+    source_crc: u32,
+    target_crc: u32,
+    start: usize,
+}
+
+// Read the checksums from the end of a BPS or UPS patch and verify the patch checksum
+fn read_checksum_footer(format: PatchFormat, patch: &[u8]) -> PatchResult<ChecksumFooter> {
+    if patch.len() < CHECKSUM_FOOTER_LEN {
+        return Err(PatchError::Malformed { format });
+    }
+
+    let start = patch.len() - CHECKSUM_FOOTER_LEN;
+    let read_crc = |offset: usize| {
+        u32::from_le_bytes(patch[offset..offset + 4].try_into().expect("slice is 4 bytes"))
+    };
+
+    let patch_crc = read_crc(start + 8);
+    let actual_patch_crc = CRC32.checksum(&patch[..patch.len() - 4]);
+    if actual_patch_crc != patch_crc {
+        return Err(PatchError::PatchChecksum {
+            format,
+            expected: patch_crc,
+            actual: actual_patch_crc,
+        });
+    }
+
+    Ok(ChecksumFooter { source_crc: read_crc(start), target_crc: read_crc(start + 4), start })
+}
+
+fn validate_source(
+    format: PatchFormat,
+    rom: &[u8],
+    expected_size: usize,
+    expected_crc: u32,
+) -> PatchResult<()> {
+    if rom.len() != expected_size {
+        return Err(PatchError::SourceSize { format, expected: expected_size, actual: rom.len() });
+    }
+
+    let actual_crc = CRC32.checksum(rom);
+    if actual_crc != expected_crc {
+        return Err(PatchError::SourceChecksum {
+            format,
+            expected: expected_crc,
+            actual: actual_crc,
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_target(output: &[u8], expected: u32) -> PatchResult<()> {
+    let actual = CRC32.checksum(output);
+    if actual != expected {
+        return Err(PatchError::TargetChecksum { expected, actual });
+    }
+
+    Ok(())
+}
+
+fn apply_bps(rom: &[u8], patch: &[u8]) -> PatchResult<Vec<u8>> {
+    const SOURCE_READ: usize = 0;
+    const TARGET_READ: usize = 1;
+    const SOURCE_COPY: usize = 2;
+    const TARGET_COPY: usize = 3;
+
+    let format = PatchFormat::Bps;
+    let footer = read_checksum_footer(format, patch)?;
+    let actions_end = footer.start;
+
+    let mut reader = PatchReader::new(format, &patch[..actions_end], BPS_HEADER.len());
+    let source_size = reader.read_varint()?;
+    let target_size = reader.read_varint()?;
+    let metadata_size = reader.read_varint()?;
+    reader.read_bytes(metadata_size)?;
+
+    validate_source(format, rom, source_size, footer.source_crc)?;
+
+    // Target size comes from the patch, so don't trust it to be a reasonable allocation size
+    let mut output = Vec::new();
+    output.try_reserve_exact(target_size).map_err(|_| reader.malformed())?;
+    let mut source_offset = 0_usize;
+    let mut target_offset = 0_usize;
+
+    while reader.position < actions_end {
+        let data = reader.read_varint()?;
+        let command = data & 3;
+        let len = (data >> 2) + 1;
+
+        let output_end = output.len().checked_add(len).ok_or_else(|| reader.malformed())?;
+        if output_end > target_size {
+            return Err(reader.malformed());
+        }
+
+        match command {
+            SOURCE_READ => {
+                let bytes = rom.get(output.len()..output_end).ok_or_else(|| reader.malformed())?;
+                output.extend_from_slice(bytes);
+            }
+            TARGET_READ => {
+                output.extend_from_slice(reader.read_bytes(len)?);
+            }
+            SOURCE_COPY | TARGET_COPY => {
+                let data = reader.read_varint()?;
+                let relative = data >> 1;
+                let base =
+                    if command == SOURCE_COPY { &mut source_offset } else { &mut target_offset };
+                *base = if data & 1 != 0 {
+                    base.checked_sub(relative)
+                } else {
+                    base.checked_add(relative)
+                }
+                .ok_or_else(|| reader.malformed())?;
+
+                if command == SOURCE_COPY {
+                    let source_end =
+                        source_offset.checked_add(len).ok_or_else(|| reader.malformed())?;
+                    let bytes =
+                        rom.get(source_offset..source_end).ok_or_else(|| reader.malformed())?;
+                    output.extend_from_slice(bytes);
+                    source_offset = source_end;
+                } else {
+                    // Target copies can overlap the bytes being written, so copy one byte at a time
+                    for _ in 0..len {
+                        let byte = *output.get(target_offset).ok_or_else(|| reader.malformed())?;
+                        output.push(byte);
+                        target_offset += 1;
+                    }
+                }
+            }
+            _ => unreachable!("value & 3 is always <= 3"),
+        }
+    }
+
+    if output.len() != target_size {
+        return Err(reader.malformed());
+    }
+
+    validate_target(&output, footer.target_crc)?;
+
+    Ok(output)
+}
+
+fn apply_ups(rom: &[u8], patch: &[u8]) -> PatchResult<Vec<u8>> {
+    let format = PatchFormat::Ups;
+    let footer = read_checksum_footer(format, patch)?;
+    let hunks_end = footer.start;
+
+    let mut reader = PatchReader::new(format, &patch[..hunks_end], UPS_HEADER.len());
+    let source_size = reader.read_varint()?;
+    let target_size = reader.read_varint()?;
+
+    validate_source(format, rom, source_size, footer.source_crc)?;
+
+    // Target size comes from the patch, so don't trust it to be a reasonable allocation size
+    let mut output = rom.to_vec();
+    output
+        .try_reserve_exact(target_size.saturating_sub(output.len()))
+        .map_err(|_| reader.malformed())?;
+    output.resize(target_size, 0);
+
+    // Each hunk skips some number of bytes and then XORs bytes until a 0 terminator
+    let mut position = 0_usize;
+    while reader.position < hunks_end {
+        position = position.checked_add(reader.read_varint()?).ok_or_else(|| reader.malformed())?;
+
+        loop {
+            let byte = reader.read_u8()?;
+            if byte == 0 {
+                break;
+            }
+
+            if let Some(output_byte) = output.get_mut(position) {
+                *output_byte ^= byte;
+            }
+            position += 1;
+        }
+        position += 1;
+    }
+
+    validate_target(&output, footer.target_crc)?;
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_varint(mut value: usize, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte | 0x80);
+                return;
+            }
+            out.push(byte);
+            value -= 1;
+        }
+    }
+
+    fn bps_action(command: usize, len: usize) -> usize {
+        ((len - 1) << 2) | command
+    }
+
+    fn append_checksums(patch: &mut Vec<u8>, source: &[u8], target: &[u8]) {
+        patch.extend(CRC32.checksum(source).to_le_bytes());
+        patch.extend(CRC32.checksum(target).to_le_bytes());
+        patch.extend(CRC32.checksum(patch).to_le_bytes());
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        for value in [0, 1, 0x7F, 0x80, 0x3FFF, 0x4000, 0x123456] {
+            let mut encoded = vec![];
+            encode_varint(value, &mut encoded);
+            let mut reader = PatchReader::new(PatchFormat::Bps, &encoded, 0);
+            assert_eq!(reader.read_varint().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn ips() {
+        let rom = vec![0; 8];
+
+        let mut patch = IPS_HEADER.to_vec();
+        // Write 2 bytes at offset 1
+        patch.extend([0x00, 0x00, 0x01, 0x00, 0x02, 0xAA, 0xBB]);
+        // RLE: write 3 copies of 0xCC at offset 8, extending the ROM
+        patch.extend([0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x03, 0xCC]);
+        patch.extend(IPS_FOOTER);
+
+        assert_eq!(
+            apply_patch(&rom, &patch).unwrap(),
+            vec![0x00, 0xAA, 0xBB, 0x00, 0x00, 0x00, 0x00, 0x00, 0xCC, 0xCC, 0xCC]
+        );
+
+        // Truncate extension
+        patch.extend([0x00, 0x00, 0x04]);
+        assert_eq!(apply_patch(&rom, &patch).unwrap(), vec![0x00, 0xAA, 0xBB, 0x00]);
+    }
+
+    #[test]
+    fn bps() {
+        let source = b"abcdefgh".to_vec();
+        let target = b"abcXYabXYabX".to_vec();
+
+        let mut patch = BPS_HEADER.to_vec();
+        encode_varint(source.len(), &mut patch);
+        encode_varint(target.len(), &mut patch);
+        encode_varint(0, &mut patch);
+        // SourceRead 3 ("abc")
+        encode_varint(bps_action(0, 3), &mut patch);
+        // TargetRead 2 ("XY")
+        encode_varint(bps_action(1, 2), &mut patch);
+        patch.extend(b"XY");
+        // SourceCopy 2 from offset 0 ("ab")
+        encode_varint(bps_action(2, 2), &mut patch);
+        encode_varint(0, &mut patch);
+        // TargetCopy 5 from offset 3, overlapping the bytes being written ("XYabX")
+        encode_varint(bps_action(3, 5), &mut patch);
+        encode_varint(3 << 1, &mut patch);
+        append_checksums(&mut patch, &source, &target);
+
+        assert_eq!(apply_patch(&source, &patch).unwrap(), target);
+
+        // Wrong source ROM
+        assert!(matches!(apply_patch(b"abcdefgX", &patch), Err(PatchError::SourceChecksum { .. })));
+
+        // Corrupted patch
+        let mut corrupted = patch.clone();
+        corrupted[BPS_HEADER.len() + 4] ^= 1;
+        assert!(matches!(apply_patch(&source, &corrupted), Err(PatchError::PatchChecksum { .. })));
+    }
+
+    #[test]
+    fn bps_out_of_range() {
+        let source = b"abcdefgh".to_vec();
+
+        let malformed_patch = |target_size: usize, actions: &[usize]| {
+            let mut patch = BPS_HEADER.to_vec();
+            encode_varint(source.len(), &mut patch);
+            encode_varint(target_size, &mut patch);
+            encode_varint(0, &mut patch);
+            for &action in actions {
+                encode_varint(action, &mut patch);
+            }
+            append_checksums(&mut patch, &source, b"");
+            patch
+        };
+
+        // Target size too large to allocate
+        let patch = malformed_patch(usize::MAX >> 1, &[]);
+        assert!(matches!(apply_patch(&source, &patch), Err(PatchError::Malformed { .. })));
+
+        // Action length past the end of the target
+        let patch = malformed_patch(4, &[bps_action(0, 5)]);
+        assert!(matches!(apply_patch(&source, &patch), Err(PatchError::Malformed { .. })));
+
+        // SourceCopy from far past the end of the source
+        let patch = malformed_patch(4, &[bps_action(2, 4), (usize::MAX >> 2) << 1]);
+        assert!(matches!(apply_patch(&source, &patch), Err(PatchError::Malformed { .. })));
+
+        // TargetCopy before the start of the target
+        let patch = malformed_patch(4, &[bps_action(3, 4), (1 << 1) | 1]);
+        assert!(matches!(apply_patch(&source, &patch), Err(PatchError::Malformed { .. })));
+    }
+
+    #[test]
+    fn ups() {
+        let source = b"abcdefgh".to_vec();
+        let target = b"abXdefghYZ".to_vec();
+
+        let mut patch = UPS_HEADER.to_vec();
+        encode_varint(source.len(), &mut patch);
+        encode_varint(target.len(), &mut patch);
+        // Skip 2, XOR 1 byte
+        encode_varint(2, &mut patch);
+        patch.extend([b'c' ^ b'X', 0x00]);
+        // Skip 4 (position is 4 after the terminator), XOR 2 bytes past the end of the source
+        encode_varint(4, &mut patch);
+        patch.extend([b'Y', b'Z', 0x00]);
+        append_checksums(&mut patch, &source, &target);
+
+        assert_eq!(apply_patch(&source, &patch).unwrap(), target);
+        assert!(apply_patch(b"abcdefg", &patch).is_err());
+    }
+
+    #[test]
+    fn ups_target_too_large() {
+        let source = b"abcdefgh".to_vec();
+
+        let mut patch = UPS_HEADER.to_vec();
+        encode_varint(source.len(), &mut patch);
+        encode_varint(usize::MAX >> 1, &mut patch);
+        append_checksums(&mut patch, &source, b"");
+
+        assert!(matches!(apply_patch(&source, &patch), Err(PatchError::Malformed { .. })));
+    }
+
+    #[test]
+    fn unknown_format() {
+        assert!(matches!(apply_patch(&[0; 4], b"NOTAPATCH"), Err(PatchError::UnknownFormat)));
+    }
+}
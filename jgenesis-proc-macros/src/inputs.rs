@@ -274,6 +274,7 @@ fn generate_inputs_struct(
                 match (player, button) {
                     #(#button_match_arms,)*
                     #(#player_match_arms,)*
+                    _ => {}
                 }
             }
 